@@ -0,0 +1,140 @@
+//! Benchmarks for the paths that run on every frame or on every dataset load.
+//! Run with `cargo bench`; the targets printed first are the numbers a change
+//! to these paths should stay under on a typical laptop.
+#[path = "../tests/common/mod.rs"]
+mod fixtures;
+
+use criterion::{criterion_group, BenchmarkId, Criterion};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{data::{DataCache, GeoLevel}, gdp_reader::{GDPData, GDP_CSV}, map_draw::{MapStyle, MapView}, state::AppState, ui};
+use std::hint::black_box;
+
+const DATA_DIR: &str = "data";
+
+/// (benchmark, target) pairs printed before the run
+const TARGETS: &[(&str, &str)] = &[
+    ("map_view_new/world", "< 15 ms"),
+    ("map_view_new/synthetic_400x500", "< 60 ms"),
+    ("map_render/*", "< 2 ms per frame at 200x60"),
+    ("gdp_data_new/266_countries", "< 5 ms"),
+    ("gdp_chart/lookup_and_points", "< 20 µs"),
+    ("feature_at/world_grid", "< 50 µs for 100 probes"),
+    ("feature_at/world_grid_exhaustive", "reference: no index, ~50x slower"),
+    ("app_state_new/fixture_3x3", "< 10 ms"),
+];
+
+/// Real world map from the data directory
+fn world_map(cache: &mut DataCache) -> MapView {
+    let raw = cache.load_geojson(&GeoLevel::World, "world").expect("world map in data/");
+    MapView::new(raw, cache).expect("valid world map")
+}
+
+fn map_view_new(c: &mut Criterion) {
+    let mut cache = DataCache::new(DATA_DIR).unwrap();
+    let world = cache.load_geojson(&GeoLevel::World, "world").expect("world map in data/");
+    let synthetic = fixtures::polygon_world(400, 500, 1);
+
+    let mut group = c.benchmark_group("map_view_new");
+    group.bench_function("world", |b| {
+        b.iter(|| MapView::new(black_box(world.clone()), &mut cache).unwrap())
+    });
+    group.bench_function("synthetic_400x500", |b| {
+        b.iter(|| MapView::new(black_box(synthetic.clone()), &mut cache).unwrap())
+    });
+    group.finish();
+}
+
+fn map_render(c: &mut Criterion) {
+    let mut cache = DataCache::new(DATA_DIR).unwrap();
+    let map = world_map(&mut cache);
+
+    let mut group = c.benchmark_group("map_render");
+    for (w, h) in [(80, 24), (200, 60), (400, 120)] {
+        let mut terminal = Terminal::new(TestBackend::new(w, h)).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(format!("{}x{}", w, h)), &(), |b, _| {
+            b.iter(|| {
+                terminal
+                    .draw(|f| map.render(f, f.area(), "bench", Some("Europe"), Some("red: Europe"), MapStyle::default()))
+                    .unwrap();
+            })
+        });
+    }
+    group.finish();
+}
+
+fn gdp_data_new(c: &mut Criterion) {
+    let path = std::env::temp_dir().join("rustatlas-bench-pkb.csv");
+    std::fs::write(&path, fixtures::gdp_csv(266, 2)).unwrap();
+    c.bench_function("gdp_data_new/266_countries", |b| b.iter(|| GDPData::new(black_box(&path)).unwrap()));
+    let _ = std::fs::remove_file(&path);
+}
+
+fn gdp_chart(c: &mut Criterion) {
+    let path = std::env::temp_dir().join("rustatlas-bench-chart.csv");
+    std::fs::write(&path, fixtures::gdp_csv(266, 3)).unwrap();
+    let data = GDPData::new(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    // Same steps as opening the chart: history lookup, copy, points
+    c.bench_function("gdp_chart/lookup_and_points", |b| {
+        b.iter(|| {
+            let all = data.get_all_gdp_data(black_box("Synth 0200")).unwrap().clone();
+            ui::chart_points(&all)
+        })
+    });
+}
+
+fn feature_at(c: &mut Criterion) {
+    let mut cache = DataCache::new(DATA_DIR).unwrap();
+    let map = world_map(&mut cache);
+
+    // 10x10 probe grid over the whole globe; most probes land in the sea
+    let probes: Vec<(f64, f64)> = (0..100)
+        .map(|i| (-170.0 + 34.0 * (i % 10) as f64, -80.0 + 17.0 * (i / 10) as f64))
+        .collect();
+    let mut group = c.benchmark_group("feature_at");
+    group.bench_function("world_grid", |b| {
+        b.iter(|| probes.iter().filter(|&&(x, y)| map.feature_at(x, y).is_some()).count())
+    });
+    group.bench_function("world_grid_exhaustive", |b| {
+        b.iter(|| probes.iter().filter(|&&(x, y)| map.feature_at_exhaustive(x, y).is_some()).count())
+    });
+    group.finish();
+}
+
+fn app_state_new(c: &mut Criterion) {
+    // Three continents of three countries, one of them matched to the GDP data by alias
+    let fixture = fixtures::FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Northland", ["Delta", "Epsilon", "Zeta"])
+        .continent("Southland", ["Eta", "Theta", "Iota"])
+        .country("Alpha", fixtures::square_at(10.0, 50.0))
+        .facts("Alpha", &["Alpha is square.", "Alpha is small."])
+        .alias("Beta", "Beta Republic")
+        .gdp("Alpha", 2000..=2020, |y| 1e9 * f64::from(y - 1999))
+        .gdp("Beta Republic", 1990..=2010, |y| 5e8 + f64::from(y))
+        .write("bench")
+        .unwrap();
+    // The generator's own check: the data reads back as it was built
+    let gdp = GDPData::new(fixture.dir.join(GDP_CSV)).unwrap();
+    for (name, series) in &fixture.gdp {
+        assert_eq!(gdp.get_all_gdp_data(name), Some(series), "GDP row {} in the fixture", name);
+    }
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    cache.load_continent_mappings().unwrap();
+    assert!(cache.list_issues().is_empty(), "fixture lists open: {:?}", cache.list_issues());
+
+    c.bench_function("app_state_new/fixture_3x3", |b| b.iter(|| AppState::new(black_box(&fixture.dir)).unwrap()));
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+criterion_group!(benches, map_view_new, map_render, gdp_data_new, gdp_chart, feature_at, app_state_new);
+
+fn main() {
+    println!("Targets:");
+    for (name, target) in TARGETS {
+        println!("  {:<34} {}", name, target);
+    }
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,195 @@
+use geojson::GeoJson;
+use std::{collections::{BTreeMap, BTreeSet}, error::Error, fmt::Write, path::Path};
+use crate::{data::{country_key, DataCache, GeoLevel}, gdp_reader::{GDPData, GDP_CSV}, humanize::{self, Noun}};
+
+/// Run `check-data`: GDP coverage per dataset country, the map countries the dataset
+/// can't resolve (candidates for `aliases.json`), then list entries that can't be opened
+/// or have no boundary data. The report is printed once complete, so a failure prints none.
+pub fn run(base: &Path) -> Result<(), Box<dyn Error>> {
+    print!("{}", report(base)?.text);
+    Ok(())
+}
+
+/// Outcome of `check-data`
+pub struct Report {
+    pub text: String,    // the printed report
+    pub problems: usize, // names listed as missing, unopenable or matching nothing
+}
+
+/// Build the `check-data` report for the data directory `base`
+pub fn report(base: &Path) -> Result<Report, Box<dyn Error>> {
+    let mut out = String::new();
+    let mut problems = 0;
+    let mut cache = DataCache::new(base)?;
+    let gdp_path = base.join(GDP_CSV);
+    let gdp = GDPData::new(&gdp_path)
+        .map_err(|e| format!("cannot read {}: {}", gdp_path.display(), e))?;
+
+    let coverage: Vec<_> = gdp.countries().collect();
+    let empty = coverage.iter().filter(|(_, n, _)| *n == 0).count();
+    writeln!(
+        out,
+        "GDP dataset {}: {}, {} without values",
+        gdp_path.display(),
+        humanize::format_count(gdp.len(), &humanize::COUNTRIES),
+        empty
+    )?;
+    if let Some(magnitude) = gdp.magnitude() {
+        writeln!(
+            out,
+            "  WARNING: median latest value {:.1e} USD is outside the plausible range {:.0e}-{:.0e};",
+            magnitude.median, magnitude.plausible[0], magnitude.plausible[1]
+        )?;
+        writeln!(
+            out,
+            "  the values may be in other units, set \"scale\": {:e} in {}",
+            magnitude.suggested_scale,
+            crate::gdp_reader::Metadata::path(&gdp_path).display()
+        )?;
+    }
+    for (name, n, span) in &coverage {
+        if *n == 0 {
+            writeln!(out, "  {:<52} no values", name)?;
+        } else {
+            writeln!(out, "  {:<52} {:>2} years  {}-{}", name, n, span.start(), span.end())?;
+        }
+    }
+
+    let countries: BTreeSet<String> = cache.load_continent_mappings()?.into_values().flatten().collect();
+    let missing: Vec<&String> = countries
+        .iter()
+        .filter(|c| gdp.get_all_gdp_data(cache.gdp_name(c)).is_none_or(|years| years.is_empty()))
+        .collect();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} of {} have no GDP data",
+        missing.len(),
+        humanize::format_count(countries.len(), &Noun::en("map country", "map countries"))
+    )?;
+    problems += missing.len();
+    for name in missing {
+        writeln!(out, "  {}", name)?;
+    }
+
+    // Lists were all loaded above; entries that can't be opened were recorded on the way
+    let issues = cache.list_issues();
+    writeln!(out)?;
+    writeln!(out, "{} without a map file or alias", humanize::format_count(issues.len(), &humanize::LIST_ENTRIES))?;
+    problems += issues.len();
+    for issue in issues {
+        writeln!(out, "  {}", issue)?;
+    }
+
+    // Features present only as `"geometry": null` open without a map
+    let continents: Vec<String> = cache.load_continent_mappings()?.into_keys().collect();
+    let features = feature_geometry(&cache, &continents);
+    let shapeless: Vec<&String> = countries
+        .iter()
+        .filter(|name| {
+            let own = cache.geojson_path(&GeoLevel::Country, name);
+            match cache.load_geojson(&GeoLevel::Country, name) {
+                Ok(raw) if own.exists() => !collection(raw).iter().any(|f| f.geometry.is_some()),
+                _ => features.get(&country_key(name)) == Some(&false),
+            }
+        })
+        .collect();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} have no boundary data (null geometry)",
+        humanize::format_count(shapeless.len(), &humanize::LIST_ENTRIES)
+    )?;
+    problems += shapeless.len();
+    for name in shapeless {
+        writeln!(out, "  {}", name)?;
+    }
+
+    // Every name in constituents.json must be a feature of some continent map
+    let unknown: Vec<&String> = cache
+        .constituents()
+        .iter()
+        .flat_map(|(parent, parts)| std::iter::once(parent).chain(parts))
+        .filter(|name| !features.contains_key(&country_key(name)))
+        .collect();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} in constituents.json match no map feature",
+        humanize::format_count(unknown.len(), &Noun::en("name", "names"))
+    )?;
+    problems += unknown.len();
+    for name in unknown {
+        writeln!(out, "  {}", name)?;
+    }
+
+    // Likewise for neighbours.json, where a name matching nothing is never drawn
+    let unknown: Vec<&String> = cache
+        .neighbours()
+        .iter()
+        .flat_map(|(country, around)| std::iter::once(country).chain(around))
+        .filter(|name| !features.contains_key(&country_key(name)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} in neighbours.json match no map feature",
+        humanize::format_count(unknown.len(), &Noun::en("name", "names"))
+    )?;
+    problems += unknown.len();
+    for name in unknown {
+        writeln!(out, "  {}", name)?;
+    }
+
+    // render_hints.json: names must be map features, and boxes must not be inside out
+    let hints = cache.render_hints();
+    let unknown: Vec<&String> = hints.names().filter(|name| !features.contains_key(&country_key(name))).collect();
+    let inverted: Vec<&String> = hints
+        .countries
+        .iter()
+        .filter(|(_, h)| {
+            h.bounds.is_some_and(|[x, y]| x[0] >= x[1] || y[0] >= y[1])
+                || h.padding.is_some_and(|p| p.iter().any(|&share| share < 0.0))
+        })
+        .map(|(name, _)| name)
+        .collect();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{} in render_hints.json match no map feature, {} have inverted bounds or negative padding",
+        humanize::format_count(unknown.len(), &Noun::en("name", "names")),
+        inverted.len()
+    )?;
+    problems += unknown.len() + inverted.len();
+    for name in unknown {
+        writeln!(out, "  {}", name)?;
+    }
+    for name in inverted {
+        writeln!(out, "  {} (bounds or padding)", name)?;
+    }
+    Ok(Report { text: out, problems })
+}
+
+/// Canonical keys of every feature (by its ADMIN name) in the maps of `continents`, and
+/// whether any feature under that name has a geometry
+fn feature_geometry(cache: &DataCache, continents: &[String]) -> BTreeMap<String, bool> {
+    let mut features = BTreeMap::new();
+    for raw in continents.iter().filter_map(|continent| cache.load_geojson(&GeoLevel::Continent, continent).ok()) {
+        for feature in collection(raw) {
+            if let Some(name) = feature.property("ADMIN").and_then(|v| v.as_str()) {
+                *features.entry(country_key(name)).or_insert(false) |= feature.geometry.is_some();
+            }
+        }
+    }
+    features
+}
+
+/// Features of a FeatureCollection; none for other GeoJSON
+fn collection(raw: GeoJson) -> Vec<geojson::Feature> {
+    match raw {
+        GeoJson::FeatureCollection(fc) => fc.features,
+        _ => Vec::new(),
+    }
+}
@@ -0,0 +1,213 @@
+use crate::theme::ColorSupport;
+
+/// Command-line entry points: the interactive TUI or a headless subcommand
+pub enum Command {
+    Tui { tour: bool, colors: Option<ColorSupport>, animations: bool, watch: bool, low_mem: bool, accessible: bool, force_tui: bool }, // `--tour` replays the walkthrough, `--colors` overrides detection, `--no-animations` keeps the screen still, `--watch` reloads config.json on change, `--low-mem` streams maps, `--accessible` describes the map in text, `--force-tui` starts it even when stdout isn't a terminal
+    ExportGdp(ExportArgs),
+    CheckData,
+    SelfTest,
+    Map(MapArgs),
+    DataDiff(DiffArgs),
+}
+
+/// Options of `export-gdp`
+pub struct ExportArgs {
+    pub countries: Vec<String>,  // explicit country names (empty with `--all`)
+    pub all: bool,               // export every country from the continent lists
+    pub long: bool,              // tidy country,year,value rows instead of one row per country
+    pub output: Option<String>,  // output file, stdout when absent
+    pub export_dir: Option<String>, // directory for relative output files
+}
+
+/// Options of `map`
+pub struct MapArgs {
+    pub name: Option<String>,      // world, a continent or a country; the world when absent
+    pub width: u16,                // output columns
+    pub height: u16,               // output rows
+    pub highlight: Option<String>, // continent or country drawn in red
+    pub no_color: bool,            // plain text even on a terminal
+    pub low_mem: bool,             // stream the map feature by feature
+}
+
+/// Options of `data-diff`
+pub struct DiffArgs {
+    pub old: String,    // data directory before the update
+    pub new: String,    // data directory after the update
+    pub threshold: f64, // vertex count change worth reporting, in percent
+    pub json: bool,     // machine-readable output instead of grouped text
+}
+
+/// Option of every subcommand: report a failure on stderr as one JSON object
+pub const JSON_ERRORS: &str = "--json-errors";
+
+/// Option of every command: the data directory, `data` when absent
+pub const DATA_DIR: &str = "--data-dir";
+
+/// Remove `--data-dir <dir>` from `args` wherever it is, returning the directory
+pub fn take_data_dir(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let Some(at) = args.iter().position(|a| a == DATA_DIR) else { return Ok(None) };
+    if at + 1 >= args.len() {
+        return Err(format!("missing directory after {}", DATA_DIR));
+    }
+    let dir = args.remove(at + 1);
+    args.remove(at);
+    Ok(Some(dir))
+}
+
+/// Why `command` shouldn't run when stdout is or isn't a terminal (`stdout_tty`): the
+/// interactive atlas would fill a file or pipe with escape sequences, so it refuses unless
+/// `--force-tui` was given; headless subcommands run either way
+pub fn tty_refusal(command: &Command, stdout_tty: bool) -> Option<String> {
+    match command {
+        Command::Tui { force_tui: false, .. } if !stdout_tty => Some(
+            "stdout is not a terminal, so the interactive atlas can't start; use a subcommand \
+             (export-gdp, check-data, map, data-diff, self-test) or pass --force-tui"
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+/// Whether a `--data-dir` value names a web server rather than a directory
+pub fn is_url(dir: &str) -> bool {
+    dir.starts_with("http://") || dir.starts_with("https://")
+}
+
+pub const USAGE: &str = "\
+Usage:
+  RustAtlas [--tour] [--colors 16|256|true] [--no-animations] [--watch] [--low-mem] [--accessible]
+            [--force-tui]                     start the interactive atlas (--tour replays the introduction,
+                                              --no-animations keeps the highlight and facts still,
+                                              --watch reloads keys and colors when config.json changes,
+                                              --low-mem reads maps one feature at a time to save memory,
+                                              --accessible describes the map in sentences for screen readers,
+                                              --force-tui starts it even when stdout isn't a terminal)
+  RustAtlas export-gdp (--all | <country>...) [--long] [-o <file>] [--export-dir <dir>]
+  RustAtlas check-data                        report GDP coverage and unmatched countries
+  RustAtlas self-test                         drive the atlas without a terminal (world, a continent,
+                                              a country, its GDP chart) and check every view it draws
+  RustAtlas map [<name>] [--width <n>] [--height <n>] [--highlight <name>] [--no-color] [--low-mem]
+                                              print the world, a continent or a country as text (120x40)
+  RustAtlas data-diff <old-dir> <new-dir> [--threshold <percent>] [--json]
+                                              report what changed between two data directories
+                                              (geometry changes above 5% by default)
+
+Every command accepts --data-dir <dir> to read the data from <dir> instead of ./data;
+the interactive atlas also takes an http(s):// URL when built with the `online` feature,
+keeping copies in the user cache directory and using them when the server is down.
+
+Subcommands accept --json-errors to report a failure on stderr as
+{\"code\", \"kind\", \"message\", \"suggestions\"} and exit with:
+  0 success, 2 usage error, 3 unknown name, 4 missing or invalid data, 5 I/O failure";
+
+/// Parse process arguments (without the program name) into a command
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        None => parse_tui(std::iter::empty()),
+        Some("export-gdp") => {
+            let mut ea = ExportArgs { countries: Vec::new(), all: false, long: false, output: None, export_dir: None };
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--all" => ea.all = true,
+                    "--long" => ea.long = true,
+                    "-o" | "--output" => {
+                        ea.output = Some(args.next().ok_or("missing file name after -o")?);
+                    }
+                    "--export-dir" => {
+                        ea.export_dir = Some(args.next().ok_or("missing directory after --export-dir")?);
+                    }
+                    flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+                    _ => ea.countries.push(arg),
+                }
+            }
+            // Exactly one of `--all` or explicit names must be given
+            match (ea.all, ea.countries.is_empty()) {
+                (true, true) | (false, false) => Ok(Command::ExportGdp(ea)),
+                _ => Err("export-gdp needs either --all or a list of countries".to_string()),
+            }
+        }
+        Some("check-data") => match args.next() {
+            None => Ok(Command::CheckData),
+            Some(extra) => Err(format!("unexpected argument '{}'", extra)),
+        },
+        Some("self-test") => match args.next() {
+            None => Ok(Command::SelfTest),
+            Some(extra) => Err(format!("unexpected argument '{}'", extra)),
+        },
+        Some("map") => {
+            let mut ma = MapArgs { name: None, width: 120, height: 40, highlight: None, no_color: false, low_mem: false };
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--width" => ma.width = parse_size("--width", args.next())?,
+                    "--height" => ma.height = parse_size("--height", args.next())?,
+                    "--highlight" => {
+                        ma.highlight = Some(args.next().ok_or("missing name after --highlight")?);
+                    }
+                    "--no-color" => ma.no_color = true,
+                    "--low-mem" => ma.low_mem = true,
+                    flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+                    _ if ma.name.is_none() => ma.name = Some(arg),
+                    _ => return Err(format!("unexpected argument '{}'", arg)),
+                }
+            }
+            Ok(Command::Map(ma))
+        }
+        Some("data-diff") => {
+            let (mut dirs, mut threshold, mut json) = (Vec::new(), 5.0, false);
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--threshold" => {
+                        let value = args.next().ok_or("missing value after --threshold")?;
+                        threshold = value
+                            .parse::<f64>()
+                            .ok()
+                            .filter(|t| t.is_finite() && *t >= 0.0)
+                            .ok_or_else(|| format!("--threshold must be a percentage, not '{}'", value))?;
+                    }
+                    "--json" => json = true,
+                    flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+                    _ => dirs.push(arg),
+                }
+            }
+            let [old, new] = <[String; 2]>::try_from(dirs)
+                .map_err(|_| "data-diff needs an old and a new data directory".to_string())?;
+            Ok(Command::DataDiff(DiffArgs { old, new, threshold, json }))
+        }
+        Some(flag) if flag.starts_with("--") => parse_tui(std::iter::once(flag.to_string()).chain(args)),
+        Some(other) => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+/// Options of the interactive mode
+fn parse_tui(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let (mut tour, mut colors, mut animations, mut watch, mut low_mem) = (false, None, true, false, false);
+    let (mut accessible, mut force_tui) = (false, false);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--tour" => tour = true,
+            "--no-animations" => animations = false,
+            "--watch" => watch = true,
+            "--low-mem" => low_mem = true,
+            "--accessible" => accessible = true,
+            "--force-tui" => force_tui = true,
+            "--colors" => {
+                let value = args.next().ok_or("missing value after --colors")?;
+                colors = Some(ColorSupport::parse(&value)
+                    .ok_or_else(|| format!("--colors must be 16, 256 or true, not '{}'", value))?);
+            }
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+    Ok(Command::Tui { tour, colors, animations, watch, low_mem, accessible, force_tui })
+}
+
+/// Value of a `--width`/`--height` option: a size of at least 3 cells (borders plus one)
+fn parse_size(flag: &str, value: Option<String>) -> Result<u16, String> {
+    let value = value.ok_or_else(|| format!("missing value after {}", flag))?;
+    value
+        .parse::<u16>()
+        .ok()
+        .filter(|&n| n >= 3)
+        .ok_or_else(|| format!("{} must be a number from 3 to 65535, not '{}'", flag, value))
+}
@@ -0,0 +1,248 @@
+use ratatui::symbols::Marker;
+use serde::Deserialize;
+use serde_json::from_slice;
+use std::{collections::BTreeMap, fs, path::Path};
+use crate::{keys::Action, map_draw::{OutlierPolicy, SIMPLIFY_EPSILON}, theme::ColorSupport};
+
+/// User preferences loaded from `config.json`; missing keys fall back to defaults
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Slowly scroll fun facts that don't fit in their block
+    pub fact_autoscroll: bool,
+    /// Seconds before the fun fact changes on its own; 0 keeps it until changed by hand
+    pub fact_rotation_secs: u64,
+    /// Show a legend line under the map explaining the highlight
+    pub show_map_legend: bool,
+    /// Key overrides per action, e.g. `"quit": ["x"]`
+    pub keys: BTreeMap<Action, Vec<String>>,
+    /// Antarctica and features in no continent list: "dim", "hide" or "include"
+    pub antarctica: OutlierPolicy,
+    /// Drop map detail finer than this share of the view's larger side when drawing; 0 draws every point
+    pub simplify_epsilon: f64,
+    /// Shade countries already opened on continent maps and dot them in the list
+    pub show_visited_on_map: bool,
+    /// Draw the constituents listed in `constituents.json` (Greenland) as part of their parent (Denmark)
+    pub merge_constituents: bool,
+    /// Longest run of missing years the choropleth fills by interpolating; 0 turns it off
+    pub interpolate_gdp_gaps: u16,
+    /// Print country names on the world and continent maps where they fit (toggled with `n`)
+    pub world_labels: bool,
+    /// Draw with Unicode symbols (true) or plain ASCII (false); unset follows the locale
+    pub unicode: Option<bool>,
+    /// Colors to render with: "16", "256" or "true"; unset detects them from the terminal
+    pub colors: Option<ColorSupport>,
+    /// Describe the map in sentences instead of drawing it, without decorative symbols (also `--accessible`)
+    pub accessible: bool,
+    /// Order of the continents in the world list: "alphabetical" or "geographic" (west to east, toggled with `g`)
+    pub world_order: WorldOrder,
+    /// Tint each continent's countries on the world map, with a matching bullet in the list
+    pub continent_colors: bool,
+    /// How maps are projected: "corrected" (shapes kept in proportion), "equirectangular", "robinson" or "mollweide" (cycled with `p`)
+    pub projection: Projection,
+    /// Dots the map outlines are drawn with: "braille", "dot", "half_block" or "block" (cycled with `M`)
+    pub map_marker: MapMarker,
+    /// Fill highlighted countries instead of only outlining them
+    pub fill_highlight: bool,
+    /// Seconds without input before random countries are shown one after another; 0 turns it off
+    pub attract_after_secs: u64,
+    /// Notifications that ring the bell: "errors", "all" or "never"
+    pub bell: Bell,
+    /// Ring by briefly inverting the status bar instead of sounding the terminal bell
+    pub visual_bell: bool,
+    /// Keep list search and ranking filter history across runs, in `session.json`
+    pub save_history: bool,
+}
+
+/// Order of the continents in the world list
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorldOrder {
+    /// By name
+    #[default]
+    Alphabetical,
+    /// West to east by the centroid of their countries, as Left/Right go through them on the map
+    Geographic,
+}
+
+impl WorldOrder {
+    /// The other order
+    pub fn toggled(self) -> Self {
+        match self {
+            WorldOrder::Alphabetical => WorldOrder::Geographic,
+            WorldOrder::Geographic => WorldOrder::Alphabetical,
+        }
+    }
+}
+
+/// How longitude and latitude become cells on the map
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Projection {
+    /// A degree of longitude shrunk by the cosine of the view's middle latitude and the
+    /// cells' height against their width taken into account, so shapes keep their proportions
+    #[default]
+    Corrected,
+    /// Degrees straight to the canvas, stretched to fill it
+    Equirectangular,
+    /// Robinson's compromise: meridians curving in towards flattened poles
+    Robinson,
+    /// Mollweide's equal-area ellipse, the poles drawn as points
+    Mollweide,
+}
+
+impl Projection {
+    /// The projection `p` switches to next
+    pub fn next(self) -> Self {
+        match self {
+            Projection::Corrected => Projection::Equirectangular,
+            Projection::Equirectangular => Projection::Robinson,
+            Projection::Robinson => Projection::Mollweide,
+            Projection::Mollweide => Projection::Corrected,
+        }
+    }
+
+    /// Whether it bends the meridians, so points are projected before they are drawn;
+    /// the others only fit the bounds to the area
+    pub fn bends(self) -> bool {
+        matches!(self, Projection::Robinson | Projection::Mollweide)
+    }
+
+    /// The projection a single country is drawn in: a world projection would only skew
+    /// its outline, so it keeps to the corrected one
+    pub fn for_country(self) -> Self {
+        if self.bends() { Projection::Corrected } else { self }
+    }
+
+    /// Name of the projection for the map title
+    pub fn label(self) -> &'static str {
+        match self {
+            Projection::Corrected => "proporcje",
+            Projection::Equirectangular => "stopnie",
+            Projection::Robinson => "Robinson",
+            Projection::Mollweide => "Mollweide",
+        }
+    }
+}
+
+/// Dots the map outlines are drawn with, finest first
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapMarker {
+    /// Braille patterns, 2x4 dots a cell; some fonts draw them badly
+    #[default]
+    Braille,
+    /// One dot a cell
+    Dot,
+    /// Half blocks, two rows a cell
+    HalfBlock,
+    /// Whole cells
+    Block,
+}
+
+impl MapMarker {
+    /// The next marker of the cycle, back to Braille after Block
+    pub fn next(self) -> Self {
+        match self {
+            MapMarker::Braille => MapMarker::Dot,
+            MapMarker::Dot => MapMarker::HalfBlock,
+            MapMarker::HalfBlock => MapMarker::Block,
+            MapMarker::Block => MapMarker::Braille,
+        }
+    }
+
+    /// The canvas marker it draws with
+    pub fn marker(self) -> Marker {
+        match self {
+            MapMarker::Braille => Marker::Braille,
+            MapMarker::Dot => Marker::Dot,
+            MapMarker::HalfBlock => Marker::HalfBlock,
+            MapMarker::Block => Marker::Block,
+        }
+    }
+
+    /// Its name in the status bar
+    pub fn label(self) -> &'static str {
+        match self {
+            MapMarker::Braille => "Braille",
+            MapMarker::Dot => "kropki",
+            MapMarker::HalfBlock => "półbloki",
+            MapMarker::Block => "bloki",
+        }
+    }
+}
+
+/// How important a status bar notification is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Info,  // something happened as asked
+    Error, // an action failed or had nothing to act on
+}
+
+/// Which notifications ring the bell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bell {
+    /// Failed actions and data problems
+    Errors,
+    /// Every notification
+    All,
+    /// None; notifications are only shown
+    #[default]
+    Never,
+}
+
+impl Bell {
+    /// Whether a notification of `severity` rings under this policy
+    pub fn rings(self, severity: Severity) -> bool {
+        match self {
+            Bell::Errors => severity == Severity::Error,
+            Bell::All => true,
+            Bell::Never => false,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fact_autoscroll: true,
+            fact_rotation_secs: 0,
+            show_map_legend: true,
+            keys: BTreeMap::new(),
+            antarctica: OutlierPolicy::default(),
+            simplify_epsilon: SIMPLIFY_EPSILON,
+            show_visited_on_map: false,
+            merge_constituents: false,
+            interpolate_gdp_gaps: 0,
+            world_labels: false,
+            unicode: None,
+            colors: None,
+            accessible: false,
+            world_order: WorldOrder::default(),
+            continent_colors: false,
+            projection: Projection::default(),
+            map_marker: MapMarker::default(),
+            fill_highlight: false,
+            attract_after_secs: 0,
+            bell: Bell::default(),
+            visual_bell: false,
+            save_history: false,
+        }
+    }
+}
+
+impl Config {
+    /// Load `config.json` from the data directory, or use defaults if absent or invalid
+    pub fn load<P: AsRef<Path>>(base: P) -> Self {
+        fs::read(base.as_ref().join("config.json"))
+            .ok()
+            .and_then(|b| Self::parse(&b).ok())
+            .unwrap_or_default()
+    }
+
+    /// Parse the contents of `config.json`
+    pub fn parse(bytes: &[u8]) -> serde_json::Result<Self> {
+        from_slice(bytes)
+    }
+}
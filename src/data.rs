@@ -1,13 +1,15 @@
-use serde::Deserialize;
+use serde::{de::{self, DeserializeOwned}, Deserialize, Serialize};
 use serde_json::from_slice;
 use std::{
-    collections::{BTreeMap, HashMap, HashSet},
-    fs,
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
     path::{Path, PathBuf},
     str::FromStr,
+    time::{Duration, Instant},
 };
 use geojson::GeoJson;
-use rand::{rng, Rng};
+use unicode_width::UnicodeWidthStr;
+use crate::{editor::DataFile, map_draw::{MapShard, MapView, OutlierPolicy}, summary::KEPT_PROPERTIES, text::truncate_to_width};
 
 /// Geographic hierarchy levels: world -> continent -> country
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -17,6 +19,36 @@ pub enum GeoLevel {
     Country,
 }
 
+/// Canonical identity of a country or continent: the normalized name used for
+/// file names and every lookup (info, fun facts, aliases, map highlights)
+pub fn country_key(name: &str) -> String {
+    name.trim().to_lowercase().replace(' ', "_").replace(['(', ')'], "")
+}
+
+/// Prefix of the list and map files for places at `level`: a level's places are listed
+/// in `continent_world.json`, a continent's in `country_<continent>.json`
+fn file_prefix(level: &GeoLevel) -> &'static str {
+    match level {
+        GeoLevel::World => "continent",
+        GeoLevel::Continent | GeoLevel::Country => "country",
+    }
+}
+
+/// Name of the list file for `level` and `key`, e.g. "country_europe.json"
+pub fn list_file(level: &GeoLevel, key: &str) -> String {
+    format!("{}_{}.json", file_prefix(level), country_key(key))
+}
+
+/// Name of the GeoJSON file for `level` and `key`, e.g. "country_france.geojson"
+pub fn map_file(level: &GeoLevel, key: &str) -> String {
+    format!("{}_{}.geojson", file_prefix(level), country_key(key))
+}
+
+/// Contents of the JSON file `name` from `source`; None when it is missing or doesn't parse
+fn load_json<T: DeserializeOwned>(source: &dyn DataSource, name: &str) -> Option<T> {
+    fs::read(source.path(name)).ok().and_then(|b| from_slice::<T>(&b).ok())
+}
+
 /// Metadata for a country loaded from `country_info.json`
 #[derive(Clone, Debug, Deserialize)]
 pub struct CountryInfo {
@@ -27,12 +59,144 @@ pub struct CountryInfo {
     pub currency: String,
 }
 
-/// Caches loaded data: directory base, index of lists, optional country info, and fun facts
+/// A list entry whose normalized name matches neither a geojson file nor an alias
+#[derive(Clone, Debug, PartialEq)]
+pub struct ListIssue {
+    pub list: String,   // list file, e.g. "country_europe.json"
+    pub entry: String,  // entry as written, after trimming
+    pub probed: String, // geojson file looked for, e.g. "country_frnace.geojson"
+}
+
+impl fmt::Display for ListIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: \"{}\" has no {} and no alias", self.list, self.entry, self.probed)
+    }
+}
+
+/// Per-feature drawing overrides from `render_hints.json`
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RenderHints {
+    /// Feature name -> true to keep all its islands, false to drop the small ones even
+    /// when the feature looks like an archipelago
+    pub keep_islands: BTreeMap<String, bool>,
+    /// Feature name -> framing, label and marker overrides for that country
+    pub countries: BTreeMap<String, CountryHints>,
+}
+
+/// Overrides for one country where the heuristics get it wrong; every field is optional
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CountryHints {
+    pub padding: Option<[f64; 2]>,     // margin around the country on its own map, as shares of its width and height
+    pub bounds: Option<[[f64; 2]; 2]>, // its own map shows exactly [[west, east], [south, north]]
+    pub label: Option<[f64; 2]>,       // longitude and latitude its name is centered on
+    pub force_marker: bool,            // box the highlight in however large it looks
+    pub min_zoom: Option<f64>,         // its name is printed only from this zoom level on
+}
+
+impl RenderHints {
+    /// Overrides for the country `name`, if any
+    pub fn country(&self, name: &str) -> Option<&CountryHints> {
+        let key = country_key(name);
+        self.countries.iter().find(|(n, _)| country_key(n) == key).map(|(_, hints)| hints)
+    }
+
+    /// Every feature name the hints mention, for checking against the maps
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.keep_islands.keys().chain(self.countries.keys())
+    }
+
+    /// Override for the islands of `name`; None leaves it to the fragmentation check
+    pub fn keep_islands(&self, name: &str) -> Option<bool> {
+        let key = country_key(name);
+        self.keep_islands.iter().find(|(n, _)| country_key(n) == key).map(|(_, &keep)| keep)
+    }
+}
+
+/// Where the data files are read from: a directory on disk, or (`online` feature) a web
+/// server mirrored into a local cache. Names are file names within the data directory.
+pub trait DataSource: Send + Sync {
+    /// Local path of `name` with its current contents, fetched first if need be; a path
+    /// that doesn't exist when there is no such file
+    fn path(&self, name: &str) -> PathBuf;
+
+    /// Whether `name` exists, as far as the source can tell without fetching it
+    fn exists(&self, name: &str) -> bool {
+        self.path(name).exists()
+    }
+
+    /// Local directory the files are read from, and written to (aliases)
+    fn dir(&self) -> &Path;
+
+    /// Where the files come from, for display
+    fn origin(&self) -> String {
+        self.dir().display().to_string()
+    }
+
+    /// Problems worth telling the user about since the previous call
+    fn take_notices(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Whether editing the local files changes the data; a remote source's copies
+    /// would only be replaced by the server's on the next fetch
+    fn editable(&self) -> bool {
+        true
+    }
+}
+
+/// A data directory on disk
+pub struct LocalDir {
+    dir: PathBuf,
+}
+
+impl LocalDir {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+}
+
+impl DataSource for LocalDir {
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+
+    fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Trim hand-edited list entries and drop the ones left empty, and repeats of an entry
+/// under the same canonical key (the first one written is kept)
+pub fn clean_list(list: Vec<String>) -> Vec<String> {
+    let mut seen = BTreeSet::new();
+    list.into_iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty() && seen.insert(country_key(entry)))
+        .collect()
+}
+
+/// Caches loaded data: directory base, index of lists, optional country info, fun facts,
+/// GDP aliases, curated short display names, constituent parts, dependent territories and
+/// neighbours of countries, drawing hints, the maps built so far and problems found in
+/// the lists
 pub struct DataCache {
-    base: PathBuf,
+    source: Box<dyn DataSource>, // where the files are read from
     index: BTreeMap<(GeoLevel, String), Vec<String>>,
     country_info: Option<BTreeMap<String, CountryInfo>>,
     funfacts: BTreeMap<String, Vec<String>>,
+    aliases: BTreeMap<String, String>,
+    display_names: BTreeMap<String, String>,
+    constituents: BTreeMap<String, Vec<String>>, // parent feature name -> constituent feature names
+    territories: BTreeMap<String, Vec<String>>,  // sovereign country name -> its dependent territories
+    neighbours: BTreeMap<String, Vec<String>>,   // country name -> countries sharing a border with it
+    render_hints: RenderHints,
+    maps: BTreeMap<(GeoLevel, String, OutlierPolicy), MapView>, // views as first built from their files, by canonical key
+    shards: BTreeMap<String, MapShard>, // continents' features cut out of the world map, by canonical key
+    list_issues: Vec<ListIssue>,
+    reported_issues: usize, // how many of `list_issues` take_new_list_issues already returned
+    low_mem: bool, // maps are streamed feature by feature instead of parsed whole
 }
 
 impl DataCache {
@@ -40,70 +204,278 @@ impl DataCache {
     pub fn new<P: AsRef<Path>>(base: P) -> Result<Self, Box<dyn std::error::Error>> {
         let base = base.as_ref().to_path_buf();
         fs::create_dir_all(&base)?;
+        Ok(Self::with_source(Box::new(LocalDir::new(base))))
+    }
 
-        // Attempt to load country metadata
-        let country_info = fs::read(base.join("country_info.json"))
-            .ok()
-            .and_then(|b| from_slice::<BTreeMap<String, CountryInfo>>(&b).ok());
+    /// Create a DataCache reading every file through `source`, loading JSON files if present
+    pub fn with_source(source: Box<dyn DataSource>) -> Self {
+        // Missing or unparsable files leave their part empty
+        Self {
+            country_info: load_json(source.as_ref(), "country_info.json"),
+            funfacts: load_json(source.as_ref(), "funfacts.json").unwrap_or_default(),
+            aliases: load_json(source.as_ref(), "aliases.json").unwrap_or_default(),
+            display_names: load_json(source.as_ref(), "display_names.json").unwrap_or_default(),
+            constituents: load_json(source.as_ref(), "constituents.json").unwrap_or_default(),
+            territories: load_json(source.as_ref(), "territories.json").unwrap_or_default(),
+            neighbours: load_json(source.as_ref(), "neighbours.json").unwrap_or_default(),
+            render_hints: load_json(source.as_ref(), "render_hints.json").unwrap_or_default(),
+            source,
+            index: BTreeMap::new(),
+            maps: BTreeMap::new(),
+            shards: BTreeMap::new(),
+            list_issues: Vec::new(),
+            reported_issues: 0,
+            low_mem: false,
+        }
+    }
+
+    /// Whether maps are streamed feature by feature (`--low-mem`)
+    pub fn low_mem(&self) -> bool {
+        self.low_mem
+    }
 
-        // Load fun facts or default to empty map
-        let funfacts = fs::read(base.join("funfacts.json"))
-            .ok()
-            .and_then(|b| from_slice::<BTreeMap<String, Vec<String>>>(&b).ok())
-            .unwrap_or_default();
+    /// Stream maps feature by feature from now on, keeping peak memory near one feature
+    pub fn set_low_mem(&mut self, low_mem: bool) {
+        self.low_mem = low_mem;
+    }
+
+    /// Directory the data files are read from; a remote source's local copies
+    pub fn base(&self) -> &Path {
+        self.source.dir()
+    }
+
+    /// Where the data files come from, for display: the directory or the server's URL
+    pub fn origin(&self) -> String {
+        self.source.origin()
+    }
+
+    /// Local path of the data file `name`, fetched first from a remote source
+    pub fn data_path(&self, name: &str) -> PathBuf {
+        self.source.path(name)
+    }
+
+    /// Whether the data files can be edited in place (see `DataSource::editable`)
+    pub fn editable(&self) -> bool {
+        self.source.editable()
+    }
 
-        Ok(Self { base, index: BTreeMap::new(), country_info, funfacts })
+    /// Problems the source ran into since the previous call, e.g. copies used because the
+    /// server couldn't be reached
+    pub fn take_notices(&self) -> Vec<String> {
+        self.source.take_notices()
     }
 
-    /// Load a JSON list for the given level and key, caching the result
+    /// Load a JSON list for the given level and key, caching the result. Entries are
+    /// trimmed and empty ones dropped; entries without a geojson file or alias are
+    /// recorded as list issues.
     pub fn load_list(&mut self, level: GeoLevel, key: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let skey = key.to_lowercase().replace(' ', "_").replace(['(', ')'], "");
-        let prefix = match level {
-            GeoLevel::World => "continent",
-            GeoLevel::Continent | GeoLevel::Country => "country",
-        };
-        let filename = format!("{}_{}.json", prefix, skey);
-        let data = fs::read(self.base.join(&filename))?;
-        let list: Vec<String> = from_slice(&data)?;
+        let filename = list_file(&level, key);
+        let data = fs::read(self.source.path(&filename))?;
+        let list = clean_list(from_slice(&data)?);
+
+        // Every entry opens as a continent or country map: country_<key>.geojson
+        for entry in &list {
+            let entry_key = country_key(entry);
+            let probed = format!("country_{}.geojson", entry_key);
+            if self.source.exists(&probed) || self.aliases.contains_key(&entry_key) {
+                continue;
+            }
+            let issue = ListIssue { list: filename.clone(), entry: entry.clone(), probed };
+            if !self.list_issues.contains(&issue) {
+                self.list_issues.push(issue);
+            }
+        }
         self.index.insert((level, key.to_string()), list.clone());
         Ok(list)
     }
 
+    /// Map `name` to `gdp_name` in `aliases.json`, saved atomically; on failure the
+    /// aliases are left unchanged
+    pub fn set_alias(&mut self, name: &str, gdp_name: &str) -> io::Result<()> {
+        let mut aliases = self.aliases.clone();
+        aliases.insert(country_key(name), gdp_name.to_string());
+        write_atomic(&self.source.dir().join("aliases.json"), &serde_json::to_vec_pretty(&aliases)?)?;
+        self.aliases = aliases;
+        Ok(())
+    }
+
+    /// Every list issue found so far, in the order found
+    pub fn list_issues(&self) -> &[ListIssue] {
+        &self.list_issues
+    }
+
+    /// List issues found since the previous call
+    pub fn take_new_list_issues(&mut self) -> Vec<ListIssue> {
+        let new = self.list_issues[self.reported_issues..].to_vec();
+        self.reported_issues = self.list_issues.len();
+        new
+    }
+
     /// Load GeoJSON data for the specified level and key
     pub fn load_geojson(&self, level: &GeoLevel, key: &str) -> Result<GeoJson, Box<dyn std::error::Error>> {
-        let skey = key.to_lowercase().replace(' ', "_").replace(['(', ')'], "");
-        let prefix = match level {
-            GeoLevel::World => "continent",
-            GeoLevel::Continent | GeoLevel::Country => "country",
-        };
-        let filename = format!("{}_{}.geojson", prefix, skey);
-        let txt = fs::read_to_string(self.base.join(&filename))?;
-        Ok(GeoJson::from_str(&txt)?)
+        read_geojson(&self.geojson_path(level, key))
+    }
+
+    /// View built earlier from the GeoJSON file for `level` and `key` with `policy`, sharing
+    /// its geometry (see `MapView::share`); None until `keep_map` was given one
+    pub fn cached_map(&self, level: &GeoLevel, key: &str, policy: OutlierPolicy) -> Option<MapView> {
+        self.maps.get(&(level.clone(), country_key(key), policy)).map(MapView::share)
+    }
+
+    /// Keep `view`, just built from the GeoJSON file for `level` and `key` with `policy`,
+    /// so the file isn't read and parsed again. The world map is also cut into continents
+    /// (see `MapView::shards`), so their files needn't be read at all. Nothing is kept in
+    /// low-memory mode, where holding every map visited would defeat the point.
+    pub fn keep_map(&mut self, level: &GeoLevel, key: &str, policy: OutlierPolicy, view: &MapView) {
+        if self.low_mem {
+            return;
+        }
+        if *level == GeoLevel::World {
+            for (continent, shard) in view.shards() {
+                self.shards.entry(country_key(&continent)).or_insert(shard);
+            }
+        }
+        self.maps.insert((level.clone(), country_key(key), policy), view.share());
+    }
+
+    /// Features of `continent` cut out of the world map; None until the world map was
+    /// kept, or when it lacks some of the continent's countries
+    pub fn shard(&self, continent: &str) -> Option<MapShard> {
+        self.shards.get(&country_key(continent)).cloned()
+    }
+
+    /// Whether `MapView::load` can give the map for `level` and `key` without reading a file
+    pub fn map_ready(&self, level: &GeoLevel, key: &str, policy: OutlierPolicy) -> bool {
+        self.maps.contains_key(&(level.clone(), country_key(key), policy))
+            || (*level == GeoLevel::Continent && self.shards.contains_key(&country_key(key)))
+    }
+
+    /// Path of the GeoJSON file for the specified level and key
+    pub fn geojson_path(&self, level: &GeoLevel, key: &str) -> PathBuf {
+        self.source.path(&map_file(level, key))
+    }
+
+    /// Drop what was read from `file`, just edited, so the next look at it reads the file
+    /// again: a list, with the continents cut out of the world map (their members may have
+    /// changed); the views built from a map, with the continents cut from it; the country
+    /// info or fun facts, read again at once
+    pub fn forget(&mut self, file: &DataFile) {
+        match file {
+            DataFile::List(level, key) => {
+                self.index.retain(|(l, k), _| !(l == level && country_key(k) == country_key(key)));
+                self.shards.clear();
+            }
+            DataFile::Map(level, key) => {
+                let key = country_key(key);
+                self.maps.retain(|(l, k, _), _| !(l == level && *k == key));
+                match level {
+                    GeoLevel::World => self.shards.clear(),
+                    GeoLevel::Continent => {
+                        self.shards.remove(&key);
+                    }
+                    GeoLevel::Country => {}
+                }
+            }
+            DataFile::CountryInfo => self.country_info = load_json(self.source.as_ref(), &file.name()),
+            DataFile::FunFacts => self.funfacts = load_json(self.source.as_ref(), &file.name()).unwrap_or_default(),
+        }
     }
 
     /// Retrieve country metadata by key, if loaded
     pub fn load_country_info(&self, key: &str) -> Option<&CountryInfo> {
-        let skey = key.to_lowercase().replace(' ', "_").replace(['(', ')'], "");
+        let skey = country_key(key);
         self.country_info.as_ref()?.get(&skey)
     }
 
-    /// Return a random fun fact for the given key, if available (1 of 3)
-    pub fn random_funfact(&self, key: &str) -> Option<String> {
-        let skey = key.to_lowercase().replace(' ', "_");
-        self.funfacts.get(&skey).and_then(|facts| {
-            if facts.is_empty() {
-                None
-            } else {
-                let mut rng = rng();
-                let idx = rng.random_range(0..facts.len());
-                Some(facts[idx].clone())
+    /// Resolve a map country name to the name used by the GDP dataset, via `aliases.json`
+    pub fn gdp_name<'a>(&'a self, key: &'a str) -> &'a str {
+        let skey = country_key(key);
+        self.aliases.get(&skey).map(String::as_str).unwrap_or(key)
+    }
+
+    /// Name to show in `width` display cells: the name itself when it fits, otherwise
+    /// its curated short name from `display_names.json`, truncated with "…" if needed
+    pub fn display_name(&self, name: &str, width: usize) -> String {
+        if name.width() <= width {
+            return name.to_string();
+        }
+        let short = self.display_names.get(&country_key(name)).map(String::as_str).unwrap_or(name);
+        truncate_to_width(short, width)
+    }
+
+    /// Every parent feature name with the names of its constituent features, as listed
+    /// in `constituents.json` (e.g. "Denmark" -> ["Greenland"])
+    pub fn constituents(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.constituents
+    }
+
+    /// Constituent feature names of `parent`; empty when it has none
+    pub fn constituents_of(&self, parent: &str) -> &[String] {
+        let key = country_key(parent);
+        self.constituents
+            .iter()
+            .find(|(name, _)| country_key(name) == key)
+            .map_or(&[], |(_, parts)| parts.as_slice())
+    }
+
+    /// Whether `a` and `b` name the same place: the same key, or the same GDP dataset name
+    /// once `aliases.json` is applied
+    pub fn same_place(&self, a: &str, b: &str) -> bool {
+        country_key(a) == country_key(b) || country_key(self.gdp_name(a)) == country_key(self.gdp_name(b))
+    }
+
+    /// Territories of `parent`: its constituents, then what `territories.json` lists for
+    /// it (e.g. "France" -> ["New Caledonia", "French Southern and Antarctic Lands"]), each
+    /// once; empty when it has none
+    pub fn territories_of(&self, parent: &str) -> Vec<&str> {
+        let mut found: Vec<&str> = Vec::new();
+        let listed = self.constituents.iter().chain(&self.territories).filter(|(name, _)| self.same_place(name, parent));
+        for part in listed.flat_map(|(_, parts)| parts) {
+            if !found.iter().any(|f| self.same_place(f, part)) {
+                found.push(part);
             }
-        })
+        }
+        found
+    }
+
+    /// Country `name` is a territory of, by `constituents.json` or `territories.json`;
+    /// None for a country of its own
+    pub fn sovereign_of(&self, name: &str) -> Option<&str> {
+        self.constituents
+            .iter()
+            .chain(&self.territories)
+            .find(|(_, parts)| parts.iter().any(|part| self.same_place(part, name)))
+            .map(|(parent, _)| parent.as_str())
+    }
+
+    /// Countries `neighbours.json` lists as bordering `name`; empty for an island and for a
+    /// country it leaves out
+    pub fn neighbours_of(&self, name: &str) -> &[String] {
+        let key = country_key(name);
+        self.neighbours
+            .iter()
+            .find(|(country, _)| country_key(country) == key)
+            .map_or(&[], |(_, around)| around.as_slice())
+    }
+
+    /// Every country with its neighbours, as listed in `neighbours.json`
+    pub fn neighbours(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.neighbours
+    }
+
+    /// Drawing overrides from `render_hints.json`
+    pub fn render_hints(&self) -> &RenderHints {
+        &self.render_hints
     }
 
-    /// Build a mapping of continents to their countries
-    pub fn load_continent_mappings(&mut self) -> Result<HashMap<String, HashSet<String>>, Box<dyn std::error::Error>> {
-        let mut result = HashMap::new();
+    /// All fun facts for the given key, in file order
+    pub fn facts(&self, key: &str) -> &[String] {
+        self.funfacts.get(&country_key(key)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Build a mapping of continents to their countries, both in name order
+    pub fn load_continent_mappings(&mut self) -> Result<BTreeMap<String, BTreeSet<String>>, Box<dyn std::error::Error>> {
+        let mut result = BTreeMap::new();
         let continents = self.load_list(GeoLevel::World, "world")?;
         for continent in continents {
             if let Ok(countries) = self.load_list(GeoLevel::Continent, &continent) {
@@ -113,3 +485,274 @@ impl DataCache {
         Ok(result)
     }
 }
+
+/// Read and parse a GeoJSON file
+pub fn read_geojson(path: &Path) -> Result<GeoJson, Box<dyn std::error::Error>> {
+    let txt = fs::read_to_string(path)?;
+    Ok(GeoJson::from_str(&txt)?)
+}
+
+/// Properties of a map feature, cut down to `KEPT_PROPERTIES`
+pub type Properties = serde_json::Map<String, serde_json::Value>;
+
+/// `all` with only the `KEPT_PROPERTIES` left
+pub fn keep_properties(mut all: serde_json::Map<String, serde_json::Value>) -> Properties {
+    all.retain(|key, _| KEPT_PROPERTIES.contains(&key.as_str()));
+    all
+}
+
+/// Read a GeoJSON FeatureCollection one feature at a time, handing `each` the feature's
+/// `ADMIN` name ("" when absent), its `KEPT_PROPERTIES` and geometry (None for
+/// `"geometry": null`) before the next feature is parsed. Other properties and top-level
+/// members are skipped unread, so neither the file nor a JSON tree of it is ever held in memory.
+pub fn stream_features<F>(path: &Path, each: F) -> Result<(), Box<dyn std::error::Error>>
+where
+    F: FnMut(String, Properties, Option<geojson::Geometry>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    let reader = io::BufReader::new(fs::File::open(path)?);
+    let mut de = serde_json::Deserializer::from_reader(reader);
+    de::DeserializeSeed::deserialize(Collection(each), &mut de)?;
+    de.end()?;
+    Ok(())
+}
+
+/// A feature with only what the app reads: the kept properties and the geometry
+#[derive(Deserialize)]
+struct LeanFeature {
+    #[serde(default)]
+    properties: Option<LeanProperties>,
+    #[serde(default)]
+    geometry: Option<geojson::Geometry>,
+}
+
+/// The `KEPT_PROPERTIES` of a feature; the values of the others are skipped unparsed
+struct LeanProperties(Properties);
+
+impl<'de> Deserialize<'de> for LeanProperties {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(LeanProperties(Properties::new()))
+    }
+}
+
+impl<'de> de::Visitor<'de> for LeanProperties {
+    type Value = LeanProperties;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("feature properties")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(mut self, mut map: A) -> Result<Self, A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if KEPT_PROPERTIES.contains(&key.as_str()) {
+                let value = map.next_value()?;
+                self.0.insert(key, value);
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Visits the top-level object, streaming its `features` array through the callback
+struct Collection<F>(F);
+
+impl<'de, F> de::DeserializeSeed<'de> for Collection<F>
+where
+    F: FnMut(String, Properties, Option<geojson::Geometry>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, F> de::Visitor<'de> for Collection<F>
+where
+    F: FnMut(String, Properties, Option<geojson::Geometry>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a GeoJSON FeatureCollection")
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "features" {
+                map.next_value_seed(Features(&mut self.0))?;
+            } else {
+                map.next_value::<de::IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Visits the `features` array, one feature in memory at a time
+struct Features<'a, F>(&'a mut F);
+
+impl<'de, F> de::DeserializeSeed<'de> for Features<'_, F>
+where
+    F: FnMut(String, Properties, Option<geojson::Geometry>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F> de::Visitor<'de> for Features<'_, F>
+where
+    F: FnMut(String, Properties, Option<geojson::Geometry>) -> Result<(), Box<dyn std::error::Error>>,
+{
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of GeoJSON features")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(feature) = seq.next_element::<LeanFeature>()? {
+            let properties = feature.properties.map(|p| p.0).unwrap_or_default();
+            let name = properties.get("ADMIN").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            (self.0)(name, properties, feature.geometry).map_err(de::Error::custom)?;
+        }
+        Ok(())
+    }
+}
+
+/// The file operations state files are read and saved with, so that a failing filesystem
+/// can stand in for the real one
+pub trait StateFs: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+}
+
+/// The disk, through `std::fs`
+pub struct RealFs;
+
+impl StateFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        fs::write(path, bytes)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+}
+
+/// Write `bytes` to `path` via a temporary file and a rename, so readers never see a partial
+/// file; on failure `path` is left as it was and no temporary file is left behind
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    write_atomic_in(&RealFs, path, bytes)
+}
+
+/// `write_atomic` through `fs`
+pub fn write_atomic_in(fs: &dyn StateFs, path: &Path, bytes: &[u8]) -> io::Result<()> {
+    // State directories outside the data dir may not exist yet
+    if let Some(parent) = path.parent() {
+        fs.create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let written = fs.write(&tmp, bytes).and_then(|()| fs.rename(&tmp, path));
+    if written.is_err() {
+        let _ = fs.remove_file(&tmp);
+    }
+    written
+}
+
+/// A JSON file holding mutable application state.
+/// Loads with a fallback to defaults, saves atomically and at most once per `interval`.
+pub struct PersistentStore<T> {
+    path: PathBuf,
+    value: T,
+    dirty: bool,
+    last_save: Option<Instant>,
+    interval: Duration,
+    fs: Box<dyn StateFs>, // where the file is read and saved
+}
+
+impl<T: Serialize + DeserializeOwned + Default> PersistentStore<T> {
+    /// Load `path`, starting from defaults if it is missing. A file that can't be parsed is
+    /// moved aside to `<path>.corrupt`; the returned message says so.
+    pub fn load(path: PathBuf, interval: Duration) -> (Self, Option<String>) {
+        Self::load_in(Box::new(RealFs), path, interval)
+    }
+
+    /// `load`, reading and later saving the file through `fs`
+    pub fn load_in(fs: Box<dyn StateFs>, path: PathBuf, interval: Duration) -> (Self, Option<String>) {
+        let mut warning = None;
+        let value = match fs.read(&path) {
+            Ok(bytes) => match from_slice::<T>(&bytes) {
+                Ok(value) => value,
+                Err(_) => {
+                    let mut bad = path.as_os_str().to_owned();
+                    bad.push(".corrupt");
+                    let bad = PathBuf::from(bad);
+                    warning = Some(match fs.rename(&path, &bad) {
+                        Ok(()) => format!("Uszkodzony plik {} przeniesiono do {}", path.display(), bad.display()),
+                        Err(e) => format!(
+                            "Uszkodzony plik {}, nie przeniesiono do {}: {}; zostanie nadpisany",
+                            path.display(),
+                            bad.display(),
+                            e
+                        ),
+                    });
+                    T::default()
+                }
+            },
+            Err(_) => T::default(),
+        };
+        (Self { path, value, dirty: false, last_save: None, interval, fs }, warning)
+    }
+
+    /// Current value
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Replace the value; it will be written by the next due save
+    pub fn set(&mut self, value: T) {
+        self.value = value;
+        self.dirty = true;
+    }
+
+    /// Save pending changes if the debounce interval has passed since the last save
+    pub fn save_due(&mut self, now: Instant) -> io::Result<()> {
+        if self.dirty && self.last_save.is_none_or(|t| now.duration_since(t) >= self.interval) {
+            self.last_save = Some(now);
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Save pending changes immediately (e.g. on quit)
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.dirty {
+            let bytes = serde_json::to_vec_pretty(&self.value).map_err(io::Error::other)?;
+            write_atomic_in(self.fs.as_ref(), &self.path, &bytes)?;
+            self.dirty = false;
+        }
+        Ok(())
+    }
+}
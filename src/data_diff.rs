@@ -0,0 +1,396 @@
+//! The `data-diff` subcommand: what changed between two data directories, e.g. the bundle
+//! in use and an update. Both sides are loaded the way the atlas loads them, so only
+//! changes to the data show up, not reformatted files.
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    error::Error,
+    ops::RangeInclusive,
+    path::Path,
+};
+use crate::{
+    cli::DiffArgs,
+    data::{country_key, stream_features, CountryInfo, DataCache, GeoLevel},
+    gdp_reader::{GDPData, GDP_CSV},
+    humanize::{self, Noun},
+};
+
+/// Everything compared of one data directory
+struct Snapshot {
+    continents: BTreeSet<String>,
+    countries: BTreeMap<String, Listed>,                // canonical key -> list entry
+    vertices: BTreeMap<String, usize>,                  // canonical key -> points of its geometry
+    info: BTreeMap<String, CountryInfo>,                // canonical key -> country_info.json record
+    gdp: BTreeMap<String, Option<RangeInclusive<u16>>>, // dataset name -> years with values
+}
+
+/// A country as listed: its name and the continent list it is on
+#[derive(Clone, Debug)]
+struct Listed {
+    name: String,
+    continent: String,
+}
+
+/// A country on a continent list
+#[derive(Debug, Serialize)]
+pub struct Placed {
+    pub continent: String,
+    pub country: String,
+}
+
+/// A country listed under another name, recognized by its unchanged data
+#[derive(Debug, Serialize)]
+pub struct Renamed {
+    pub continent: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A country that moved to another continent list
+#[derive(Debug, Serialize)]
+pub struct Moved {
+    pub country: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// Geometry of a country with a different number of points
+#[derive(Debug, Serialize)]
+pub struct VertexChange {
+    pub country: String,
+    pub old: usize,
+    pub new: usize,
+}
+
+/// Years with GDP values of a dataset country, as `[first, last]`; None without values
+#[derive(Debug, Serialize)]
+pub struct YearsChange {
+    pub country: String,
+    pub old: Option<[u16; 2]>,
+    pub new: Option<[u16; 2]>,
+}
+
+/// One `country_info.json` field of a country; None where the country has no record
+#[derive(Debug, Serialize)]
+pub struct InfoChange {
+    pub country: String,
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Differences between two data directories, each group in name order
+#[derive(Debug, Default, Serialize)]
+pub struct DataDiff {
+    pub continents_added: Vec<String>,
+    pub continents_removed: Vec<String>,
+    pub countries_added: Vec<Placed>,
+    pub countries_removed: Vec<Placed>,
+    pub countries_renamed: Vec<Renamed>,
+    pub countries_moved: Vec<Moved>,
+    pub geometry: Vec<VertexChange>,  // only changes above the threshold
+    pub gdp_years: Vec<YearsChange>,
+    pub info: Vec<InfoChange>,
+}
+
+impl DataDiff {
+    /// Whether the two directories hold the same data
+    pub fn is_empty(&self) -> bool {
+        self.continents_added.is_empty()
+            && self.continents_removed.is_empty()
+            && self.countries_added.is_empty()
+            && self.countries_removed.is_empty()
+            && self.countries_renamed.is_empty()
+            && self.countries_moved.is_empty()
+            && self.geometry.is_empty()
+            && self.gdp_years.is_empty()
+            && self.info.is_empty()
+    }
+}
+
+/// Run `data-diff`: load both directories, compare them and print the differences as
+/// grouped text or, with `--json`, as one JSON object
+pub fn run(args: DiffArgs) -> Result<(), Box<dyn Error>> {
+    let old = Snapshot::load(Path::new(&args.old))?;
+    let new = Snapshot::load(Path::new(&args.new))?;
+    let diff = compare(&old, &new, args.threshold);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print_text(&diff, &args);
+    }
+    Ok(())
+}
+
+impl Snapshot {
+    /// Load the lists, geometry, country info and GDP dataset of the directory at `base`
+    fn load(base: &Path) -> Result<Self, Box<dyn Error>> {
+        // DataCache would create a missing directory, hiding a mistyped path
+        if !base.is_dir() {
+            return Err(format!("{} is not a data directory", base.display()).into());
+        }
+        let mut cache = DataCache::new(base)?;
+        let mappings = cache.load_continent_mappings()?;
+
+        let mut countries = BTreeMap::new();
+        for (continent, names) in &mappings {
+            for name in names {
+                countries.insert(country_key(name), Listed { name: name.clone(), continent: continent.clone() });
+            }
+        }
+
+        // A country's own file wins over its feature in the continent map, as when drawing it
+        let mut vertices = BTreeMap::new();
+        for continent in mappings.keys() {
+            let path = cache.geojson_path(&GeoLevel::Continent, continent);
+            if path.exists() {
+                stream_features(&path, |name, _, geometry| {
+                    *vertices.entry(country_key(&name)).or_insert(0) += geometry.map_or(0, |g| count_points(&g.value));
+                    Ok(())
+                })?;
+            }
+        }
+        for key in countries.keys() {
+            let path = cache.geojson_path(&GeoLevel::Country, key);
+            if path.exists() {
+                let mut points = 0;
+                stream_features(&path, |_, _, geometry| {
+                    points += geometry.map_or(0, |g| count_points(&g.value));
+                    Ok(())
+                })?;
+                vertices.insert(key.clone(), points);
+            }
+        }
+
+        let info = countries
+            .keys()
+            .filter_map(|key| cache.load_country_info(key).map(|info| (key.clone(), info.clone())))
+            .collect();
+
+        // A directory without the dataset compares as one without GDP values
+        let gdp_path = base.join(GDP_CSV);
+        let gdp = if gdp_path.exists() {
+            let gdp = GDPData::new(&gdp_path).map_err(|e| format!("cannot read {}: {}", gdp_path.display(), e))?;
+            gdp.countries().map(|(name, n, span)| (name.to_string(), (n > 0).then_some(span))).collect()
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Snapshot { continents: mappings.into_keys().collect(), countries, vertices, info, gdp })
+    }
+}
+
+/// Number of positions in a geometry, rings and parts included
+fn count_points(value: &geojson::Value) -> usize {
+    use geojson::Value::*;
+    match value {
+        Point(_) => 1,
+        MultiPoint(points) | LineString(points) => points.len(),
+        MultiLineString(lines) | Polygon(lines) => lines.iter().map(Vec::len).sum(),
+        MultiPolygon(polygons) => polygons.iter().flatten().map(Vec::len).sum(),
+        GeometryCollection(parts) => parts.iter().map(|g| count_points(&g.value)).sum(),
+    }
+}
+
+/// Whether two info records describe the same country, whatever it is called
+fn same_country(a: &CountryInfo, b: &CountryInfo) -> bool {
+    a.capital == b.capital && a.area == b.area && a.population == b.population && a.currency == b.currency
+}
+
+/// Fields of `country_info.json` as compared and printed
+fn info_fields(info: &CountryInfo) -> [(&'static str, String); 5] {
+    [
+        ("name", info.name.clone()),
+        ("capital", info.capital.clone()),
+        ("area", info.area.to_string()),
+        ("population", info.population.to_string()),
+        ("currency", info.currency.clone()),
+    ]
+}
+
+/// Differences from `old` to `new`; geometry counts only when the number of points
+/// changed by more than `threshold` percent
+fn compare(old: &Snapshot, new: &Snapshot, threshold: f64) -> DataDiff {
+    let mut diff = DataDiff {
+        continents_added: new.continents.difference(&old.continents).cloned().collect(),
+        continents_removed: old.continents.difference(&new.continents).cloned().collect(),
+        ..DataDiff::default()
+    };
+
+    let mut removed: Vec<&String> = old.countries.keys().filter(|k| !new.countries.contains_key(*k)).collect();
+    let mut added: Vec<&String> = new.countries.keys().filter(|k| !old.countries.contains_key(*k)).collect();
+
+    // A removed and an added country on the same continent with the same info record, or
+    // else the same geometry, are one country under a new name
+    let mut renamed = Vec::new();
+    removed.retain(|from| {
+        let before = &old.countries[*from];
+        let matches = |to: &&String| {
+            new.countries[*to].continent == before.continent
+                && match (old.info.get(*from), new.info.get(*to)) {
+                    (Some(a), Some(b)) => same_country(a, b),
+                    _ => old.vertices.get(*from).is_some_and(|&n| n > 0 && new.vertices.get(*to) == Some(&n)),
+                }
+        };
+        match added.iter().position(matches) {
+            Some(i) => {
+                renamed.push((*from, added.remove(i)));
+                false
+            }
+            None => true,
+        }
+    });
+    diff.countries_removed = removed
+        .iter()
+        .map(|k| Placed { continent: old.countries[*k].continent.clone(), country: old.countries[*k].name.clone() })
+        .collect();
+    diff.countries_added = added
+        .iter()
+        .map(|k| Placed { continent: new.countries[*k].continent.clone(), country: new.countries[*k].name.clone() })
+        .collect();
+    diff.countries_renamed = renamed
+        .iter()
+        .map(|(from, to)| Renamed {
+            continent: new.countries[*to].continent.clone(),
+            from: old.countries[*from].name.clone(),
+            to: new.countries[*to].name.clone(),
+        })
+        .collect();
+
+    // Countries in both, renamed ones under their new name
+    let kept: Vec<(&String, &String)> = old
+        .countries
+        .keys()
+        .filter(|k| new.countries.contains_key(*k))
+        .map(|k| (k, k))
+        .chain(renamed.iter().copied())
+        .collect();
+    for &(was, is) in &kept {
+        let (before, after) = (&old.countries[was], &new.countries[is]);
+        if before.continent != after.continent {
+            diff.countries_moved.push(Moved {
+                country: after.name.clone(),
+                from: before.continent.clone(),
+                to: after.continent.clone(),
+            });
+        }
+
+        let (a, b) = (old.vertices.get(was).copied().unwrap_or(0), new.vertices.get(is).copied().unwrap_or(0));
+        let percent = match a {
+            0 if b == 0 => 0.0,
+            0 => f64::INFINITY,
+            _ => (b as f64 - a as f64).abs() / a as f64 * 100.0,
+        };
+        if percent > threshold {
+            diff.geometry.push(VertexChange { country: after.name.clone(), old: a, new: b });
+        }
+
+        // A record added or dropped shows as every field changing from or to nothing
+        let (a, b) = (old.info.get(was).map(info_fields), new.info.get(is).map(info_fields));
+        for i in 0..5 {
+            let (x, y) = (a.as_ref().map(|f| &f[i]), b.as_ref().map(|f| &f[i]));
+            let Some(&(field, _)) = x.or(y) else { break };
+            let (x, y) = (x.map(|(_, v)| v.clone()), y.map(|(_, v)| v.clone()));
+            if x != y {
+                diff.info.push(InfoChange { country: after.name.clone(), field, old: x, new: y });
+            }
+        }
+    }
+    diff.countries_moved.sort_by(|a, b| a.country.cmp(&b.country));
+    diff.geometry.sort_by(|a, b| a.country.cmp(&b.country));
+    diff.info.sort_by(|a, b| a.country.cmp(&b.country));
+
+    // GDP series are compared by their dataset names
+    let names: BTreeSet<&String> = old.gdp.keys().chain(new.gdp.keys()).collect();
+    let years = |span: Option<&Option<RangeInclusive<u16>>>| span.cloned().flatten().map(|s| [*s.start(), *s.end()]);
+    for name in names {
+        let (a, b) = (years(old.gdp.get(name)), years(new.gdp.get(name)));
+        if a != b {
+            diff.gdp_years.push(YearsChange { country: name.clone(), old: a, new: b });
+        }
+    }
+    diff
+}
+
+/// `[first, last]` years as "1990-2023", or "none"
+fn span_label(years: Option<[u16; 2]>) -> String {
+    years.map_or("none".to_string(), |[first, last]| format!("{}-{}", first, last))
+}
+
+/// Print the differences grouped by kind, leaving out the groups without any
+fn print_text(diff: &DataDiff, args: &DiffArgs) {
+    println!("Data in {} compared with {}", args.new, args.old);
+    if diff.is_empty() {
+        println!("No differences");
+        return;
+    }
+    if !diff.continents_added.is_empty() || !diff.continents_removed.is_empty() {
+        println!();
+        println!(
+            "Continents: {} added, {} removed",
+            diff.continents_added.len(),
+            diff.continents_removed.len()
+        );
+        for name in &diff.continents_added {
+            println!("  + {}", name);
+        }
+        for name in &diff.continents_removed {
+            println!("  - {}", name);
+        }
+    }
+    let changed = diff.countries_added.len()
+        + diff.countries_removed.len()
+        + diff.countries_renamed.len()
+        + diff.countries_moved.len();
+    if changed > 0 {
+        println!();
+        println!(
+            "Countries: {} added, {} removed, {} renamed, {} moved",
+            diff.countries_added.len(),
+            diff.countries_removed.len(),
+            diff.countries_renamed.len(),
+            diff.countries_moved.len()
+        );
+        for p in &diff.countries_added {
+            println!("  + {} ({})", p.country, p.continent);
+        }
+        for p in &diff.countries_removed {
+            println!("  - {} ({})", p.country, p.continent);
+        }
+        for r in &diff.countries_renamed {
+            println!("  ~ {} -> {} ({})", r.from, r.to, r.continent);
+        }
+        for m in &diff.countries_moved {
+            println!("  > {}: {} -> {}", m.country, m.from, m.to);
+        }
+    }
+    if !diff.geometry.is_empty() {
+        println!();
+        println!(
+            "Geometry: {} with the vertex count changed by more than {}%",
+            humanize::format_count(diff.geometry.len(), &humanize::COUNTRIES),
+            args.threshold
+        );
+        for g in &diff.geometry {
+            println!("  {:<40} {:>8} -> {:>8}", g.country, g.old, g.new);
+        }
+    }
+    if !diff.gdp_years.is_empty() {
+        println!();
+        println!(
+            "GDP years: {} with a different span",
+            humanize::format_count(diff.gdp_years.len(), &Noun::en("dataset country", "dataset countries"))
+        );
+        for y in &diff.gdp_years {
+            println!("  {:<52} {} -> {}", y.country, span_label(y.old), span_label(y.new));
+        }
+    }
+    if !diff.info.is_empty() {
+        println!();
+        println!("Country info: {}", humanize::format_count(diff.info.len(), &Noun::en("field changed", "fields changed")));
+        for i in &diff.info {
+            let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "(no record)".to_string());
+            println!("  {:<40} {}: {} -> {}", i.country, i.field, value(&i.old), value(&i.new));
+        }
+    }
+}
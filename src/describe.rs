@@ -0,0 +1,213 @@
+//! The map said in sentences, for screen readers and very low vision (`--accessible`):
+//! what the center panel shows at each level, built only from what it is given so the
+//! same scene always reads the same, in Polish or English.
+use crate::{
+    gdp_reader::GDPData,
+    humanize::{self, Lang, Noun},
+    summary::CountrySummary,
+};
+
+const KONTYNENTY: Noun = Noun::pl("kontynent", "kontynenty", "kontynentów");
+
+/// One of eight compass directions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bearing {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Bearing {
+    /// Direction from `from` to `to` (longitude, latitude), a degree of longitude counting
+    /// as the cosine of their middle latitude of a degree of latitude
+    pub fn between(from: (f64, f64), to: (f64, f64)) -> Self {
+        let shrink = ((from.1 + to.1) / 2.0).to_radians().cos();
+        let degrees = ((to.1 - from.1).atan2((to.0 - from.0) * shrink)).to_degrees();
+        // Counterclockwise from east, in eighths
+        match ((degrees + 360.0 + 22.5) % 360.0 / 45.0) as u8 {
+            0 => Bearing::East,
+            1 => Bearing::NorthEast,
+            2 => Bearing::North,
+            3 => Bearing::NorthWest,
+            4 => Bearing::West,
+            5 => Bearing::SouthWest,
+            6 => Bearing::South,
+            _ => Bearing::SouthEast,
+        }
+    }
+
+    /// Part of the box `bounds` ([minx, maxx], [miny, maxy]) that `point` lies in, split
+    /// in thirds each way; None for the middle
+    pub fn within(point: (f64, f64), (x_bounds, y_bounds): ([f64; 2], [f64; 2])) -> Option<Self> {
+        let third = |v: f64, [lo, hi]: [f64; 2]| {
+            let t = (v - lo) / (hi - lo);
+            if t < 1.0 / 3.0 { -1 } else if t > 2.0 / 3.0 { 1 } else { 0 }
+        };
+        match (third(point.0, x_bounds), third(point.1, y_bounds)) {
+            (0, 1) => Some(Bearing::North),
+            (1, 1) => Some(Bearing::NorthEast),
+            (1, 0) => Some(Bearing::East),
+            (1, -1) => Some(Bearing::SouthEast),
+            (0, -1) => Some(Bearing::South),
+            (-1, -1) => Some(Bearing::SouthWest),
+            (-1, 0) => Some(Bearing::West),
+            (-1, 1) => Some(Bearing::NorthWest),
+            _ => None,
+        }
+    }
+
+    /// "to the west" / "na zachodzie"
+    fn direction(self, lang: Lang) -> &'static str {
+        match (lang, self) {
+            (Lang::Pl, Bearing::North) => "na północy",
+            (Lang::Pl, Bearing::NorthEast) => "na północnym wschodzie",
+            (Lang::Pl, Bearing::East) => "na wschodzie",
+            (Lang::Pl, Bearing::SouthEast) => "na południowym wschodzie",
+            (Lang::Pl, Bearing::South) => "na południu",
+            (Lang::Pl, Bearing::SouthWest) => "na południowym zachodzie",
+            (Lang::Pl, Bearing::West) => "na zachodzie",
+            (Lang::Pl, Bearing::NorthWest) => "na północnym zachodzie",
+            (Lang::En, Bearing::North) => "to the north",
+            (Lang::En, Bearing::NorthEast) => "to the north-east",
+            (Lang::En, Bearing::East) => "to the east",
+            (Lang::En, Bearing::SouthEast) => "to the south-east",
+            (Lang::En, Bearing::South) => "to the south",
+            (Lang::En, Bearing::SouthWest) => "to the south-west",
+            (Lang::En, Bearing::West) => "to the west",
+            (Lang::En, Bearing::NorthWest) => "to the north-west",
+        }
+    }
+
+    /// "western" / "zachodnia" (część); None is the middle
+    fn part(bearing: Option<Self>, lang: Lang) -> &'static str {
+        match (lang, bearing) {
+            (Lang::Pl, None) => "środkowa",
+            (Lang::Pl, Some(Bearing::North)) => "północna",
+            (Lang::Pl, Some(Bearing::NorthEast)) => "północno-wschodnia",
+            (Lang::Pl, Some(Bearing::East)) => "wschodnia",
+            (Lang::Pl, Some(Bearing::SouthEast)) => "południowo-wschodnia",
+            (Lang::Pl, Some(Bearing::South)) => "południowa",
+            (Lang::Pl, Some(Bearing::SouthWest)) => "południowo-zachodnia",
+            (Lang::Pl, Some(Bearing::West)) => "zachodnia",
+            (Lang::Pl, Some(Bearing::NorthWest)) => "północno-zachodnia",
+            (Lang::En, None) => "central",
+            (Lang::En, Some(Bearing::North)) => "northern",
+            (Lang::En, Some(Bearing::NorthEast)) => "north-eastern",
+            (Lang::En, Some(Bearing::East)) => "eastern",
+            (Lang::En, Some(Bearing::SouthEast)) => "south-eastern",
+            (Lang::En, Some(Bearing::South)) => "southern",
+            (Lang::En, Some(Bearing::SouthWest)) => "south-western",
+            (Lang::En, Some(Bearing::West)) => "western",
+            (Lang::En, Some(Bearing::NorthWest)) => "north-western",
+        }
+    }
+}
+
+/// What the center panel shows, with what its description needs
+pub enum Scene<'a> {
+    /// The world map: each continent with the number of its countries
+    World { continents: &'a [(String, usize)] },
+    /// A continent map with `country` selected: where it lies on the continent (None in
+    /// the middle) and its neighbours on the map, each in the direction it lies in
+    Continent {
+        continent: &'a str,
+        country: &'a CountrySummary,
+        position: Option<Bearing>,
+        neighbours: &'a [(String, Bearing)],
+    },
+    /// An opened country
+    Country { country: &'a CountrySummary },
+}
+
+/// `scene` as paragraphs of full sentences in `lang`
+pub fn describe(scene: &Scene, lang: Lang) -> Vec<String> {
+    match scene {
+        Scene::World { continents } => world(continents, lang),
+        Scene::Continent { continent, country, position, neighbours } => {
+            vec![placement(continent, &country.name, *position, neighbours, lang)]
+        }
+        Scene::Country { country } => facts(country, lang),
+    }
+}
+
+/// "Świat: 6 kontynentów, 195 krajów." and a line per continent
+fn world(continents: &[(String, usize)], lang: Lang) -> Vec<String> {
+    let (continent_noun, country_noun) = match lang {
+        Lang::Pl => (&KONTYNENTY, &humanize::KRAJE),
+        Lang::En => (&humanize::CONTINENTS, &humanize::COUNTRIES),
+    };
+    let total = continents.iter().map(|(_, n)| n).sum();
+    let heading = match lang {
+        Lang::Pl => "Świat",
+        Lang::En => "The world",
+    };
+    let mut lines = vec![format!(
+        "{}: {}, {}.",
+        heading,
+        humanize::format_count(continents.len(), continent_noun),
+        humanize::format_count(total, country_noun)
+    )];
+    lines.extend(continents.iter().map(|(name, n)| format!("{}: {}.", name, humanize::format_count(*n, country_noun))));
+    lines
+}
+
+/// "Poland: central Europe, bordered by Germany to the west, …"
+fn placement(continent: &str, name: &str, position: Option<Bearing>, neighbours: &[(String, Bearing)], lang: Lang) -> String {
+    let part = Bearing::part(position, lang);
+    let borders: Vec<String> =
+        neighbours.iter().map(|(n, bearing)| format!("{} {}", n, bearing.direction(lang))).collect();
+    match (lang, borders.is_empty()) {
+        (Lang::Pl, true) => format!("{}: {} część kontynentu {}; nie graniczy z żadnym krajem na mapie.", name, part, continent),
+        (Lang::Pl, false) => format!("{}: {} część kontynentu {}; graniczy z: {}.", name, part, continent, borders.join(", ")),
+        (Lang::En, true) => format!("{}: {} {}, bordering no country on the map.", name, part, continent),
+        (Lang::En, false) => format!("{}: {} {}, bordered by {}.", name, part, continent, borders.join(", ")),
+    }
+}
+
+/// The country's key facts, its latest GDP and its fun facts, a sentence each
+fn facts(country: &CountrySummary, lang: Lang) -> Vec<String> {
+    let mut lines = vec![country.name.clone()];
+    if let Some(info) = &country.info {
+        let (area, population) = (humanize::format_area(info.area, lang), humanize::format_population(info.population, lang));
+        lines.extend(match lang {
+            Lang::Pl => [
+                format!("Stolicą jest {}.", info.capital),
+                format!("Powierzchnia wynosi {}.", area),
+                format!("Liczba ludności wynosi {}.", population),
+                format!("Walutą jest {}.", info.currency),
+            ],
+            Lang::En => [
+                format!("The capital is {}.", info.capital),
+                format!("The area is {}.", area),
+                format!("The population is {}.", population),
+                format!("The currency is {}.", info.currency),
+            ],
+        });
+    }
+    lines.push(match (lang, country.latest_gdp()) {
+        (Lang::Pl, Some((year, value))) => format!("PKB w {} roku wyniósł {}.", year, GDPData::format_gdp_value(value)),
+        (Lang::En, Some((year, value))) => format!("GDP in {} was {}.", year, gdp_en(value)),
+        (Lang::Pl, None) => "Brak danych o PKB.".to_string(),
+        (Lang::En, None) => "There is no GDP data.".to_string(),
+    });
+    let fact = match lang {
+        Lang::Pl => "Ciekawostka",
+        Lang::En => "Fun fact",
+    };
+    lines.extend(country.facts.iter().map(|f| format!("{}: {}", fact, f)));
+    lines
+}
+
+/// GDP with English units: "1.23 trillion USD"
+fn gdp_en(value: f64) -> String {
+    let units = [(1e12, "trillion"), (1e9, "billion"), (1e6, "million")];
+    match units.iter().find(|(base, _)| value >= *base) {
+        Some((base, unit)) => format!("{:.2} {} USD", value / base, unit),
+        None => format!("{} USD", humanize::group_thousands(value.max(0.0).round() as u64, Lang::En)),
+    }
+}
@@ -0,0 +1,121 @@
+//! Ctrl+E: the data file behind the current view opened in the user's editor. Which file
+//! that is follows from the place and the focused panel (`data_file`); the event loop
+//! hands the terminal to the editor and takes it back (see `platform::run`), and the
+//! caches the file fed are dropped so the view is rebuilt from what was saved.
+use serde_json::Value;
+use std::{path::{Path, PathBuf}, process::Command, time::SystemTime};
+use crate::{
+    data::{list_file, map_file, GeoLevel},
+    humanize::{self, format_count},
+    state::{Location, Panel},
+};
+
+/// Editor run when neither $VISUAL nor $EDITOR is set
+pub const FALLBACK_EDITOR: &str = "vi";
+
+/// A data file views are built from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DataFile {
+    List(GeoLevel, String), // places of the world or a continent: continent_world.json, country_<continent>.json
+    Map(GeoLevel, String),  // GeoJSON of the world, a continent or a country
+    CountryInfo,            // country_info.json
+    FunFacts,               // funfacts.json
+}
+
+impl DataFile {
+    /// Name of the file in the data directory
+    pub fn name(&self) -> String {
+        match self {
+            DataFile::List(level, key) => list_file(level, key),
+            DataFile::Map(level, key) => map_file(level, key),
+            DataFile::CountryInfo => "country_info.json".to_string(),
+            DataFile::FunFacts => "funfacts.json".to_string(),
+        }
+    }
+}
+
+/// File behind what `panel` shows at `location`: the list for the list, the map's GeoJSON
+/// for the map, and for the right panel the country info of a continent's countries or
+/// the fun facts of a country. A country's list is itself alone, so its list panel
+/// stands for its country info. A country without a GeoJSON of its own (`own_map` false)
+/// is cut out of its continent's map, which is then the file to edit.
+pub fn data_file(location: &Location, panel: Panel, own_map: bool) -> DataFile {
+    match (location, panel) {
+        (Location::World, Panel::Center) => DataFile::Map(GeoLevel::World, "world".to_string()),
+        (Location::World, _) => DataFile::List(GeoLevel::World, "world".to_string()),
+        (Location::Continent(continent), Panel::Left) => DataFile::List(GeoLevel::Continent, continent.clone()),
+        (Location::Continent(continent), Panel::Center) => DataFile::Map(GeoLevel::Continent, continent.clone()),
+        (Location::Continent(_), Panel::Right) => DataFile::CountryInfo,
+        (Location::Country { country, .. }, Panel::Center) if own_map => DataFile::Map(GeoLevel::Country, country.clone()),
+        (Location::Country { continent, .. }, Panel::Center) => DataFile::Map(GeoLevel::Continent, continent.clone()),
+        (Location::Country { .. }, Panel::Left) => DataFile::CountryInfo,
+        (Location::Country { .. }, Panel::Right) => DataFile::FunFacts,
+    }
+}
+
+/// Editor command from $VISUAL, else $EDITOR (looked up with `var`), split on whitespace
+/// so that "code --wait" works; `FALLBACK_EDITOR` when neither is set
+pub fn command(var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(var)
+        .map(|value| value.split_whitespace().map(str::to_string).collect::<Vec<_>>())
+        .find(|words| !words.is_empty())
+        .unwrap_or_else(|| vec![FALLBACK_EDITOR.to_string()])
+}
+
+/// Run `command` on `path` and wait for it to exit; what went wrong when it couldn't be
+/// started or exited with an error
+pub fn run(command: &[String], path: &Path) -> Result<(), String> {
+    let Some((program, args)) = command.split_first() else { return Err("brak polecenia edytora".to_string()) };
+    let status = Command::new(program)
+        .args(args)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("nie można uruchomić {}: {}", program, e))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(format!("{} zakończył pracę z błędem ({})", program, status)),
+    }
+}
+
+/// Entries in the contents of a data file: the items of a list, the features of a GeoJSON
+/// collection, the countries of an object like `country_info.json`; an error when the
+/// contents aren't JSON
+pub fn count_entries(bytes: &[u8]) -> Result<usize, serde_json::Error> {
+    Ok(match serde_json::from_slice(bytes)? {
+        Value::Array(items) => items.len(),
+        Value::Object(object) => match object.get("features") {
+            Some(Value::Array(features)) => features.len(),
+            _ if object.get("type").and_then(Value::as_str) == Some("Feature") => 1,
+            _ => object.len(),
+        },
+        _ => 1,
+    })
+}
+
+/// An edit asked for with Ctrl+E, waiting for the event loop to run the editor
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataEdit {
+    pub file: DataFile,
+    pub path: PathBuf,                // local path of the file
+    pub command: Vec<String>,         // editor program and its arguments, the path added last
+    pub before: Option<usize>,        // entries before editing; None when missing or not JSON
+    pub modified: Option<SystemTime>, // modification time before editing
+}
+
+/// Status line for `name` edited from `before` to `after` entries, e.g.
+/// "country_europe.json: 45 → 46 wpisów (+1)"
+pub fn change_text(name: &str, before: Option<usize>, after: usize) -> String {
+    match before {
+        None => format!("{}: {}", name, format_count(after, &humanize::WPISY)),
+        Some(before) if before == after => format!("{}: zapisano, nadal {}", name, format_count(after, &humanize::WPISY)),
+        Some(before) => format!(
+            "{}: {} → {} ({:+})",
+            name,
+            before,
+            format_count(after, &humanize::WPISY),
+            after as i64 - before as i64
+        ),
+    }
+}
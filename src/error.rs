@@ -0,0 +1,97 @@
+//! How the headless subcommands fail. Every failure is one `AtlasError` kind with a fixed
+//! exit code, reported on stderr as a line of text or, with `--json-errors`, as one JSON
+//! object (`{code, kind, message, suggestions}`) for scripts.
+use serde::Serialize;
+use std::{error::Error, fmt, io};
+
+/// Why a subcommand failed
+#[derive(Debug)]
+pub enum AtlasError {
+    Usage(String),                                          // bad arguments; exit code 2
+    UnknownName { name: String, suggestions: Vec<String> }, // no such place or country; 3
+    Data(String),                                           // data files missing or invalid; 4
+    Io(String),                                             // reading or writing failed otherwise; 5
+}
+
+/// What `--json-errors` prints
+#[derive(Serialize)]
+struct Report<'a> {
+    code: i32,
+    kind: &'static str,
+    message: String,
+    suggestions: &'a [String],
+}
+
+impl AtlasError {
+    /// Process exit code; 0 is success and 1 is left to the interactive mode
+    pub fn code(&self) -> i32 {
+        match self {
+            AtlasError::Usage(_) => 2,
+            AtlasError::UnknownName { .. } => 3,
+            AtlasError::Data(_) => 4,
+            AtlasError::Io(_) => 5,
+        }
+    }
+
+    /// Name of the kind in JSON reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AtlasError::Usage(_) => "usage",
+            AtlasError::UnknownName { .. } => "unknown_name",
+            AtlasError::Data(_) => "data",
+            AtlasError::Io(_) => "io",
+        }
+    }
+
+    /// Close matches worth trying instead; empty except for unknown names
+    pub fn suggestions(&self) -> &[String] {
+        match self {
+            AtlasError::UnknownName { suggestions, .. } => suggestions,
+            _ => &[],
+        }
+    }
+
+    /// Sort an error from the loaders into a kind: an `AtlasError` stays as it is, an
+    /// I/O error is an I/O failure unless a file is missing or invalid, and everything
+    /// else (parse errors, problems already described) is a data problem
+    pub fn classify(e: Box<dyn Error>) -> Self {
+        let e = match e.downcast::<AtlasError>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        match e.downcast::<io::Error>() {
+            Ok(e) if matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::InvalidData) => {
+                AtlasError::Data(e.to_string())
+            }
+            Ok(e) => AtlasError::Io(e.to_string()),
+            Err(e) => AtlasError::Data(e.to_string()),
+        }
+    }
+
+    /// Write the error to stderr, as text or as one line of JSON
+    pub fn report(&self, json: bool) {
+        if json {
+            let report = Report { code: self.code(), kind: self.kind(), message: self.to_string(), suggestions: self.suggestions() };
+            match serde_json::to_string(&report) {
+                Ok(line) => eprintln!("{}", line),
+                Err(_) => eprintln!("Error: {}", self),
+            }
+        } else {
+            eprintln!("Error: {}", self);
+            if !self.suggestions().is_empty() {
+                eprintln!("Did you mean: {}?", self.suggestions().join(", "));
+            }
+        }
+    }
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::Usage(msg) | AtlasError::Data(msg) | AtlasError::Io(msg) => write!(f, "{}", msg),
+            AtlasError::UnknownName { name, .. } => write!(f, "unknown name '{}'", name),
+        }
+    }
+}
+
+impl Error for AtlasError {}
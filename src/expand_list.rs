@@ -0,0 +1,115 @@
+//! A scrollable list whose focused item is shown in full, wrapped, while the
+//! others show only their first line; used for fun facts, fit for any list of
+//! long texts such as message histories.
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+use crate::text::{truncate_to_width, wrap};
+
+pub struct ExpandList<'a> {
+    items: &'a [String],
+    focused: usize,
+    offset: u16, // lines scrolled within a focused item taller than the list
+    block: Option<Block<'a>>,
+    style: Style,
+}
+
+impl<'a> ExpandList<'a> {
+    pub fn new(items: &'a [String], focused: usize) -> Self {
+        Self { items, focused, offset: 0, block: None, style: Style::default() }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Scroll a focused item that doesn't fit by `offset` lines
+    pub fn offset(mut self, offset: u16) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Lines of the focused item that don't fit in `area`; the most `offset` can use
+    pub fn focus_overflow(&self, area: Rect) -> u16 {
+        let inner = self.inner(area);
+        let (_, focus) = self.lines(text_width(inner));
+        (focus.len() as u16).saturating_sub(inner.height)
+    }
+
+    fn inner(&self, area: Rect) -> Rect {
+        self.block.as_ref().map_or(area, |b| b.inner(area))
+    }
+
+    /// Every line of the list, numbered, and the range of lines of the focused item
+    fn lines(&self, width: usize) -> (Vec<Line<'a>>, std::ops::Range<usize>) {
+        let digits = self.items.len().to_string().len();
+        let indent = digits + 2; // "N. "
+        let body = width.saturating_sub(indent);
+        let mut lines = Vec::new();
+        let mut focus = 0..0;
+        for (i, item) in self.items.iter().enumerate() {
+            let number = format!("{:>digits$}. ", i + 1);
+            if i == self.focused {
+                let start = lines.len();
+                for (j, text) in wrap(item, body).into_iter().enumerate() {
+                    let prefix = if j == 0 { number.clone() } else { " ".repeat(number.width()) };
+                    lines.push(Line::styled(prefix + &text, self.style.add_modifier(Modifier::BOLD)));
+                }
+                focus = start..lines.len();
+            } else {
+                let first = wrap(item, usize::MAX).swap_remove(0);
+                lines.push(Line::styled(number + &truncate_to_width(&first, body), self.style));
+            }
+        }
+        (lines, focus)
+    }
+}
+
+/// Text columns inside `inner`, leaving the last one for the scrollbar
+fn text_width(inner: Rect) -> usize {
+    inner.width.saturating_sub(1) as usize
+}
+
+/// First visible line: the focused item fully shown with as much as possible above
+/// it, or, when it's taller than `height`, its lines from `offset` on
+fn first_line(focus: &std::ops::Range<usize>, height: usize, offset: u16) -> usize {
+    if focus.len() > height {
+        focus.start + (offset as usize).min(focus.len() - height)
+    } else {
+        focus.end.saturating_sub(height)
+    }
+}
+
+impl Widget for ExpandList<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let inner = self.inner(area);
+        let (lines, focus) = self.lines(text_width(inner));
+        let height = inner.height as usize;
+        let top = first_line(&focus, height, self.offset);
+        let total = lines.len();
+        let visible: Vec<Line> = lines.into_iter().skip(top).take(height).collect();
+        if let Some(block) = self.block {
+            block.render(area, buf);
+        }
+        Paragraph::new(visible).render(Rect { width: text_width(inner) as u16, ..inner }, buf);
+
+        if total > height {
+            let mut state = ScrollbarState::new(total - height + 1).position(top).viewport_content_length(height);
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None)
+                .render(inner, buf, &mut state);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fs::{self, File},
+    io::{self, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use crate::{
+    cli::ExportArgs,
+    data::{country_key, DataCache},
+    error::AtlasError,
+    gdp_reader::{GDPData, GDP_CSV},
+    humanize,
+    jobs::{Job, JobState, Outcome, Progress},
+    suggest,
+};
+
+/// File written by `--export-dir` when no `-o` name is given
+const DEFAULT_EXPORT_FILE: &str = "gdp.csv";
+
+/// Shape of the exported GDP table
+pub enum CsvLayout {
+    Wide, // one row per country, one column per year
+    Long, // one `country,year,value` row per data point
+}
+
+/// Summary of an export run
+pub struct ExportReport {
+    pub exported: usize,
+    pub skipped: usize, // countries without any GDP data
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Stream GDP series for the given map country names to `out`, sorted by name and year.
+/// Names are resolved to the GDP dataset through the alias layer of `cache`; each row is
+/// written as it is looked up, from the dataset's own storage. Progress is reported per
+/// country; a cancelled export stops with an `Interrupted` error.
+pub fn write_gdp_csv<W: Write>(
+    mut out: W,
+    countries: &BTreeSet<String>,
+    cache: &DataCache,
+    gdp: &GDPData,
+    layout: CsvLayout,
+    progress: &Progress,
+) -> io::Result<ExportReport> {
+    let series = |name: &str| gdp.series(cache.gdp_name(name)).filter(|points| !points.is_empty());
+    let exported = countries.iter().filter(|name| series(name).is_some()).count();
+    let report = ExportReport { exported, skipped: countries.len() - exported };
+    let step = |i: usize, name: &str| {
+        if progress.cancelled() {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "export cancelled"));
+        }
+        progress.report((i * 100 / exported) as u8, name);
+        Ok(())
+    };
+    let rows = countries.iter().filter_map(|name| Some((name, series(name)?))).enumerate();
+
+    match layout {
+        CsvLayout::Wide => {
+            let years: BTreeSet<u16> =
+                countries.iter().filter_map(|name| series(name)).flatten().map(|&(year, _)| year).collect();
+            write!(out, "country")?;
+            for year in &years {
+                write!(out, ",{}", year)?;
+            }
+            writeln!(out)?;
+            for (i, (name, points)) in rows {
+                step(i, name)?;
+                write!(out, "{}", csv_field(name))?;
+                let mut points = points.iter().peekable();
+                for &year in &years {
+                    match points.next_if(|&&(y, _)| y == year) {
+                        Some((_, v)) => write!(out, ",{}", v)?,
+                        None => write!(out, ",")?,
+                    }
+                }
+                writeln!(out)?;
+            }
+        }
+        CsvLayout::Long => {
+            writeln!(out, "country,year,value")?;
+            for (i, (name, points)) in rows {
+                step(i, name)?;
+                let name = csv_field(name);
+                for (year, v) in points {
+                    writeln!(out, "{},{},{}", name, year, v)?;
+                }
+            }
+        }
+    }
+    out.flush()?;
+    Ok(report)
+}
+
+/// Run the `export-gdp` subcommand against the data directory `base`
+pub fn run(args: ExportArgs, base: &Path) -> Result<(), Box<dyn Error>> {
+    let mut cache = DataCache::new(base)?;
+    let gdp = GDPData::new(base.join(GDP_CSV))
+        .map_err(|e| format!("cannot read {}: {}", base.join(GDP_CSV).display(), e))?;
+
+    let listed: BTreeSet<String> = cache.load_continent_mappings()?.into_values().flatten().collect();
+    let countries: BTreeSet<String> = if args.all {
+        listed.clone()
+    } else {
+        args.countries.into_iter().collect()
+    };
+
+    // Names neither listed nor in the dataset fail before anything is written; listed
+    // countries without GDP data are only skipped
+    let known = |name: &str| {
+        listed.iter().any(|c| country_key(c) == country_key(name)) || gdp.get_all_gdp_data(cache.gdp_name(name)).is_some()
+    };
+    if let Some(name) = countries.iter().find(|name| !known(name)) {
+        let places: BTreeSet<&str> = listed.iter().map(String::as_str).chain(gdp.countries().map(|(name, _, _)| name)).collect();
+        let suggestions = suggest::closest(name, places, 3).into_iter().map(str::to_string).collect();
+        return Err(AtlasError::UnknownName { name: name.clone(), suggestions }.into());
+    }
+    let layout = if args.long { CsvLayout::Long } else { CsvLayout::Wide };
+
+    // Exports never go to the data directory: relative names resolve against
+    // `--export-dir` or the working directory
+    let output = match (&args.output, &args.export_dir) {
+        (Some(file), Some(dir)) => Some(Path::new(dir).join(file)),
+        (Some(file), None) => Some(PathBuf::from(file)),
+        (None, Some(dir)) => Some(Path::new(dir).join(DEFAULT_EXPORT_FILE)),
+        (None, None) => None,
+    };
+
+    // Write on a worker thread, showing progress when stderr is a terminal. A file is
+    // written under a temporary name and renamed when complete, so a failed export
+    // leaves neither a partial file nor a truncated earlier one.
+    let mut job = Job::spawn("export", move |progress| {
+        let written = match &output {
+            Some(path) => {
+                let part = path.with_extension("csv.part");
+                let written = File::create(&part)
+                    .and_then(|file| write_gdp_csv(BufWriter::new(file), &countries, &cache, &gdp, layout, progress))
+                    .and_then(|report| fs::rename(&part, path).map(|()| report));
+                if written.is_err() {
+                    let _ = fs::remove_file(&part);
+                }
+                written.map_err(|e| io::Error::new(e.kind(), format!("cannot write {}: {}", path.display(), e)))
+            }
+            None => write_gdp_csv(io::stdout().lock(), &countries, &cache, &gdp, layout, progress),
+        };
+        match written {
+            Ok(report) => Outcome::Done(report),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => Outcome::Cancelled,
+            Err(e) => Outcome::Failed(e.to_string()),
+        }
+    });
+    let show_progress = io::stderr().is_terminal();
+    let state = job.wait(Duration::from_millis(100), |state| {
+        if show_progress && let JobState::Running { percent, message } = state {
+            eprint!("\r\x1b[2KExporting… {:>3}% {}", percent, message);
+        }
+    });
+    if show_progress {
+        eprint!("\r\x1b[2K");
+    }
+    match state.clone() {
+        JobState::Failed(e) => return Err(AtlasError::Io(e).into()),
+        JobState::Cancelled => return Err("export cancelled".into()),
+        _ => {}
+    }
+    let report = job.take_result().ok_or("export finished without a report")?;
+    eprintln!(
+        "Exported {}, skipped {} without GDP data",
+        humanize::format_count(report.exported, &humanize::COUNTRIES),
+        report.skipped
+    );
+    Ok(())
+}
@@ -0,0 +1,103 @@
+//! Filter expressions for the continent ranking: space-separated terms that must all
+//! hold, each a name substring ("slo") or a comparison on population, area or GDP
+//! ("pop>10m", "gdp<=500bn", "area>=1k").
+
+/// Numeric column a comparison looks at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Population,
+    Area, // km²
+    Gdp,  // USD
+}
+
+/// Comparison operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+/// One condition of a filter
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    Name(String), // lowercase substring of the name
+    Compare(Field, Op, f64),
+}
+
+/// Parsed filter; the empty filter keeps every row
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    pub terms: Vec<Term>,
+}
+
+/// Values of one row that a filter tests; None where the data is missing
+pub struct Values<'a> {
+    pub name: &'a str,
+    pub population: Option<f64>,
+    pub area: Option<f64>,
+    pub gdp: Option<f64>,
+}
+
+impl Filter {
+    /// Whether every term holds for `row`; a comparison on a missing value fails
+    pub fn matches(&self, row: &Values) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Name(part) => row.name.to_lowercase().contains(part),
+            Term::Compare(field, op, bound) => {
+                let value = match field {
+                    Field::Population => row.population,
+                    Field::Area => row.area,
+                    Field::Gdp => row.gdp,
+                };
+                value.is_some_and(|v| match op {
+                    Op::Lt => v < *bound,
+                    Op::Le => v <= *bound,
+                    Op::Gt => v > *bound,
+                    Op::Ge => v >= *bound,
+                    Op::Eq => v == *bound,
+                })
+            }
+        })
+    }
+}
+
+/// Parse `expr` into a filter, or say what is wrong with it (in the TUI's Polish)
+pub fn parse(expr: &str) -> Result<Filter, String> {
+    let terms = expr.split_whitespace().map(parse_term).collect::<Result<_, _>>()?;
+    Ok(Filter { terms })
+}
+
+/// A comparison if the word holds an operator, else a name substring
+fn parse_term(word: &str) -> Result<Term, String> {
+    let Some(at) = word.find(['<', '>', '=']) else {
+        return Ok(Term::Name(word.to_lowercase()));
+    };
+    let (field, rest) = word.split_at(at);
+    let field = match field.to_lowercase().as_str() {
+        "pop" | "population" => Field::Population,
+        "area" => Field::Area,
+        "gdp" => Field::Gdp,
+        "" => return Err(format!("brak pola przed '{}' (pop, area, gdp)", rest)),
+        other => return Err(format!("nieznane pole '{}' (pop, area, gdp)", other)),
+    };
+    let (op, value) = [(">=", Op::Ge), ("<=", Op::Le), (">", Op::Gt), ("<", Op::Lt), ("=", Op::Eq)]
+        .into_iter()
+        .find_map(|(sym, op)| rest.strip_prefix(sym).map(|value| (op, value)))
+        .ok_or_else(|| format!("zły operator w '{}'", word))?;
+    let value = parse_number(value).ok_or_else(|| format!("zła liczba '{}' (np. 10m, 2.5bn)", value))?;
+    Ok(Term::Compare(field, op, value))
+}
+
+/// Number with an optional k (thousand), m (million) or bn (billion) suffix
+fn parse_number(text: &str) -> Option<f64> {
+    let lower = text.to_lowercase();
+    let (digits, scale) = [("bn", 1e9), ("k", 1e3), ("m", 1e6)]
+        .into_iter()
+        .find_map(|(suffix, scale)| lower.strip_suffix(suffix).map(|digits| (digits.to_string(), scale)))
+        .unwrap_or((lower, 1.0));
+    let n: f64 = digits.parse().ok()?;
+    n.is_finite().then_some(n * scale)
+}
@@ -1,97 +1,354 @@
+use serde::Deserialize;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashMap};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader};
-use std::path::Path;
+use std::ops::{Range, RangeInclusive};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::humanize::{self, Lang};
 
-/// Holds GDP values by country code and provides lookup by country name.
+/// Location of the World Bank GDP CSV inside the data directory
+pub const GDP_CSV: &str = "dataPKB/pkb.csv";
+
+/// Sidecar metadata of a GDP CSV, read from `<name>.meta.json` beside it (`pkb.meta.json`
+/// for `pkb.csv`); every key is optional
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Range, in USD, a typical country's latest value falls in; a dataset whose median
+    /// latest value is outside it is probably in other units
+    pub plausible: [f64; 2],
+    /// Factor every value in the file is multiplied by, e.g. 1e6 for a file in millions
+    pub scale: f64,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self { plausible: [1e7, 1e14], scale: 1.0 }
+    }
+}
+
+impl Metadata {
+    /// Path of the sidecar file for the CSV at `csv_path`
+    pub fn path(csv_path: &Path) -> PathBuf {
+        csv_path.with_extension("meta.json")
+    }
+
+    /// Metadata of the CSV at `csv_path`: defaults without a sidecar file, an error when
+    /// the file exists but doesn't parse, so a mistyped scale is never ignored
+    pub fn load(csv_path: &Path) -> io::Result<Self> {
+        match fs::read(Self::path(csv_path)) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", Self::path(csv_path).display(), e))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// A dataset whose values look like they are in the wrong unit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Magnitude {
+    pub median: f64,          // median of the countries' latest values, after any scale
+    pub plausible: [f64; 2],  // range it was expected in
+    pub suggested_scale: f64, // power of 1000 bringing the median closest to the range's middle
+}
+
+/// Check the countries' latest values against `plausible`: None when their median falls
+/// inside it (or there are no values), otherwise the median and the scale factor (a power
+/// of 1000, as unit mix-ups are thousands, millions or billions) that would fix it
+pub fn check_magnitude(latest: &[f64], plausible: [f64; 2]) -> Option<Magnitude> {
+    let mut values: Vec<f64> = latest.iter().copied().filter(|v| v.is_finite() && *v > 0.0).collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let median = values[values.len() / 2];
+    if (plausible[0]..=plausible[1]).contains(&median) {
+        return None;
+    }
+    let middle = (plausible[0].log10() + plausible[1].log10()) / 2.0;
+    let thousands = ((middle - median.log10()) / 3.0).round() as i32;
+    Some(Magnitude { median, plausible, suggested_scale: 1e3f64.powi(thousands) })
+}
+
+/// Fields of one CSV line. Quotes around a field are removed and doubled quotes inside
+/// it unescaped; a comma between quotes is part of the field, as in "Bahamas, The".
+pub fn csv_fields(line: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    let mut rest = Some(line);
+    std::iter::from_fn(move || {
+        let line = rest?;
+        let Some(quoted) = line.strip_prefix('"') else {
+            let (field, after) = line.split_once(',').map_or((line, None), |(f, a)| (f, Some(a)));
+            rest = after;
+            return Some(Cow::Borrowed(field));
+        };
+        // Scan to the closing quote, skipping doubled ones
+        let mut end = 0;
+        let mut escaped = false;
+        loop {
+            match quoted[end..].find('"') {
+                Some(i) if quoted[end + i + 1..].starts_with('"') => {
+                    end += i + 2;
+                    escaped = true;
+                }
+                Some(i) => {
+                    end += i;
+                    break;
+                }
+                None => {
+                    end = quoted.len();
+                    break;
+                }
+            }
+        }
+        let field = &quoted[..end];
+        let after = quoted.get(end + 1..).unwrap_or("");
+        rest = after.find(',').map(|i| &after[i + 1..]);
+        Some(if escaped { Cow::Owned(field.replace("\"\"", "\"")) } else { Cow::Borrowed(field) })
+    })
+}
+
+/// One dataset row: the country's name and where its values sit in `GDPData::points`.
+struct CountrySeries {
+    name: String,
+    points: Range<u32>,
+    /// The same values as a year -> GDP map, built the first time a caller asks for one.
+    years: OnceLock<BTreeMap<u16, f64>>,
+}
+
+/// A series' value in one year: reported by the dataset, or interpolated across a gap
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum YearValue {
+    Reported(f64),
+    Interpolated(f64),
+}
+
+impl YearValue {
+    /// The value, however it was obtained
+    pub fn value(self) -> f64 {
+        match self {
+            YearValue::Reported(v) | YearValue::Interpolated(v) => v,
+        }
+    }
+}
+
+/// Change of a series from the previous year with a value to its latest year
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Growth {
+    pub previous_year: u16, // not necessarily the year before the latest, when years are missing
+    pub percent: f64,       // e.g. 4.1 for 4.1% growth, negative for a contraction
+}
+
+/// Value of `series` in `year`. A year missing inside a run of at most `max_gap` missing
+/// years is interpolated linearly between the known years around it (0 turns this off);
+/// years before the first or after the last known value are never extrapolated.
+pub fn value_in_year(series: &BTreeMap<u16, f64>, year: u16, max_gap: u16) -> Option<YearValue> {
+    if let Some(&v) = series.get(&year) {
+        return Some(YearValue::Reported(v));
+    }
+    let (&y0, &v0) = series.range(..year).next_back()?;
+    let (&y1, &v1) = series.range(year..).next()?;
+    if y1 - y0 - 1 > max_gap {
+        return None;
+    }
+    let t = f64::from(year - y0) / f64::from(y1 - y0);
+    Some(YearValue::Interpolated(v0 + (v1 - v0) * t))
+}
+
+/// GDP per person of a group of countries (a continent, the world, the marked ones),
+/// weighted by population
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PerCapita {
+    pub gdp: f64,         // total GDP of the countries with both figures
+    pub population: u64,  // their total population
+    pub covered: usize,   // countries with both figures
+    pub countries: usize, // countries in the group
+}
+
+impl PerCapita {
+    /// Total GDP over total population
+    pub fn value(&self) -> f64 {
+        self.gdp / self.population as f64
+    }
+
+    /// Share of the group's countries the figure covers, 0.0 to 1.0
+    pub fn coverage(&self) -> f64 {
+        self.covered as f64 / self.countries as f64
+    }
+}
+
+/// GDP per capita of a group from each country's GDP and population, where known: the
+/// summed GDP of the countries with both over their summed population. Unlike the mean
+/// of their own per-capita figures, a microstate counts for its few people rather than
+/// as much as a country of a billion. None when no country has both figures.
+pub fn aggregate_per_capita(countries: impl IntoIterator<Item = (Option<f64>, Option<u64>)>) -> Option<PerCapita> {
+    let mut total = PerCapita { gdp: 0.0, population: 0, covered: 0, countries: 0 };
+    for (gdp, population) in countries {
+        total.countries += 1;
+        if let (Some(gdp), Some(population)) = (gdp, population.filter(|&p| p > 0)) {
+            total.gdp += gdp;
+            total.population += population;
+            total.covered += 1;
+        }
+    }
+    (total.covered > 0).then_some(total)
+}
+
+/// Holds GDP values per country and provides lookup by country name.
 pub struct GDPData {
-    /// Map from ISO country code to a sorted map of year -> GDP value.
-    data: HashMap<String, BTreeMap<u16, f64>>,
-    /// Map from country name (original and lowercase) to ISO country code.
-    country_codes: HashMap<String, String>,
-    /// List of original country names for simple fuzzy matching.
-    country_names: Vec<String>,
+    /// Countries in file order.
+    countries: Vec<CountrySeries>,
+    /// Every country's (year, value) pairs, one run per country sorted by year, in a
+    /// single allocation rather than a map per country.
+    points: Vec<(u16, f64)>,
+    /// Lowercase country name and country code -> index into `countries`; lookup only,
+    /// never iterated.
+    index: HashMap<String, usize>,
+    /// Sidecar metadata the values were read with.
+    metadata: Metadata,
+    /// Set when the values look like they are in the wrong unit.
+    magnitude: Option<Magnitude>,
 }
 
 impl GDPData {
     /// Load GDP CSV, skipping 5 header lines, and build in-memory data structures.
+    /// Values are multiplied by the sidecar metadata's `scale`, then checked for plausibility.
     pub fn new<P: AsRef<Path>>(csv_path: P) -> io::Result<Self> {
+        let metadata = Metadata::load(csv_path.as_ref())?;
         let file = File::open(csv_path)?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-
-        // Skip metadata headers
-        for _ in 0..5 { let _ = lines.next(); }
+        let mut reader = BufReader::new(file);
 
-        let mut data = HashMap::new();
-        let mut country_codes = HashMap::new();
-        let mut country_names = Vec::new();
+        let mut countries = Vec::new();
+        let mut points = Vec::new();
+        let mut index = HashMap::new();
 
-        // Parse each line as country, code, and yearly GDP values
-        for line in lines.flatten() {
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() < 5 { continue; }
+        // Parse each line as country, code, and yearly GDP values, all read into one buffer
+        let mut line = String::new();
+        let mut row = 0;
+        loop {
+            line.clear();
+            // An unreadable line (an I/O error, invalid UTF-8) fails the load
+            if reader.read_line(&mut line)? == 0 { break; }
+            row += 1;
+            // Skip metadata headers
+            if row <= 5 { continue; }
 
-            let name = parts[0].trim_matches('"');
-            let code = parts[1].trim_matches('"');
+            let mut parts = csv_fields(line.trim_end_matches(['\r', '\n']));
+            let (Some(name), Some(code)) = (parts.next(), parts.next()) else { continue };
+            let mut values = parts.skip(2).peekable();
+            if values.peek().is_none() { continue; }
 
-            // Register exact and lowercase name lookups
-            country_codes.insert(name.to_string(), code.to_string());
-            country_codes.insert(name.to_lowercase(), code.to_string());
-            country_names.push(name.to_string());
-
-            let mut by_year = BTreeMap::new();
-            // Years start at 1960 from the fifth column
-            for (i, raw) in parts.iter().enumerate().skip(4) {
-                let year = 1960 + (i - 4);
+            // Years start at 1960 from the fifth column, so they come in order
+            let start = points.len() as u32;
+            for (i, raw) in values.enumerate() {
+                let year = 1960 + i;
                 if year > 2024 { break; }
-                let s = raw.trim_matches('"');
-                if !s.is_empty() {
-                    if let Ok(val) = s.parse::<f64>() {
-                        by_year.insert(year as u16, val);
-                    }
+                // An empty cell doesn't parse either
+                if let Ok(val) = raw.parse::<f64>() {
+                    points.push((year as u16, val * metadata.scale));
                 }
             }
 
-            data.insert(code.to_string(), by_year);
+            // The first row under a name or code is the one found, as by the substring fallback
+            index.entry(name.to_lowercase()).or_insert(countries.len());
+            if !code.is_empty() {
+                index.entry(code.to_lowercase()).or_insert(countries.len());
+            }
+            countries.push(CountrySeries { name: name.into_owned(), points: start..points.len() as u32, years: OnceLock::new() });
         }
+        points.shrink_to_fit();
+        countries.shrink_to_fit();
 
-        Ok(Self { data, country_codes, country_names })
+        let mut data = Self { countries, points, index, metadata, magnitude: None };
+        let latest: Vec<f64> = data.countries.iter().filter_map(|c| data.points_of(c).last().map(|&(_, v)| v)).collect();
+        data.magnitude = check_magnitude(&latest, data.metadata.plausible);
+        Ok(data)
     }
 
-    /// Resolve a country name to its ISO code via exact, lowercase, or substring match.
-    fn find_country_code(&self, query: &str) -> Option<&String> {
-        // Try exact match
-        if let Some(code) = self.country_codes.get(query) {
-            return Some(code);
-        }
-        // Try lowercase match
-        let lc = query.to_lowercase();
-        if let Some(code) = self.country_codes.get(&lc) {
-            return Some(code);
+    /// Values of `country`, sorted by year
+    fn points_of(&self, country: &CountrySeries) -> &[(u16, f64)] {
+        &self.points[country.points.start as usize..country.points.end as usize]
+    }
+
+    /// Sidecar metadata the values were read with
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// The values' median and a fixing scale factor, when they look like the wrong unit
+    pub fn magnitude(&self) -> Option<&Magnitude> {
+        self.magnitude.as_ref()
+    }
+
+    /// Number of countries (dataset rows) loaded.
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    /// Whether the dataset has no countries at all.
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    /// Every loaded country in file order: its dataset name, number of data points and
+    /// the span of years covered (an empty range when the country has no values).
+    pub fn countries(&self) -> impl Iterator<Item = (&str, usize, RangeInclusive<u16>)> {
+        self.countries.iter().map(|c| {
+            let points = self.points_of(c);
+            let span = match (points.first(), points.last()) {
+                (Some(&(first, _)), Some(&(last, _))) => first..=last,
+                _ => RangeInclusive::new(1, 0),
+            };
+            (c.name.as_str(), points.len(), span)
+        })
+    }
+
+    /// First to last year with a value in any country; None when there are no values
+    pub fn year_span(&self) -> Option<RangeInclusive<u16>> {
+        let first = self.countries.iter().filter_map(|c| self.points_of(c).first()).map(|&(y, _)| y).min()?;
+        let last = self.countries.iter().filter_map(|c| self.points_of(c).last()).map(|&(y, _)| y).max()?;
+        Some(first..=last)
+    }
+
+    /// Resolve a country name or code via case-insensitive exact, then substring match.
+    fn find_country(&self, query: &str) -> Option<&CountrySeries> {
+        // Try exact (case-insensitive) match
+        if let Some(&i) = self.index.get(&query.to_lowercase()) {
+            return Some(&self.countries[i]);
         }
         // Fallback to simple substring fuzzy match
-        for name in &self.country_names {
-            if name.contains(query) || query.contains(name) {
-                if let Some(code) = self.country_codes.get(name) {
-                    return Some(code);
-                }
-            }
-        }
-        None
+        self.countries
+            .iter()
+            .find(|c| c.name.contains(query) || query.contains(c.name.as_str()))
     }
 
     /// Get the most recent year and GDP value for a given country name.
     pub fn get_latest_gdp(&self, country_name: &str) -> Option<(u16, f64)> {
-        let code = self.find_country_code(country_name)?;
-        let years = self.data.get(code)?;
-        years.iter().next_back().map(|(&y, &v)| (y, v))
+        self.series(country_name)?.last().copied()
     }
 
-    /// Access the full year -> GDP map for charting purposes.
+    /// The (year, value) pairs of a country, sorted by year, without copying them.
+    pub fn series(&self, country_name: &str) -> Option<&[(u16, f64)]> {
+        self.find_country(country_name).map(|c| self.points_of(c))
+    }
+
+    /// Access the full year -> GDP map for charting purposes. It is built from the flat
+    /// values the first time a country is asked for, so loading allocates none.
     pub fn get_all_gdp_data(&self, country_name: &str) -> Option<&BTreeMap<u16, f64>> {
-        let code = self.find_country_code(country_name)?;
-        self.data.get(code)
+        let country = self.find_country(country_name)?;
+        Some(country.years.get_or_init(|| self.points_of(country).iter().copied().collect()))
+    }
+
+    /// Growth from the second-latest to the latest year of `series`; None with fewer
+    /// than two years or a previous value of zero
+    pub fn year_over_year(series: &BTreeMap<u16, f64>) -> Option<Growth> {
+        let mut years = series.iter().rev();
+        let (_, &latest) = years.next()?;
+        let (&previous_year, &previous) = years.next()?;
+        (previous != 0.0).then(|| Growth { previous_year, percent: (latest / previous - 1.0) * 100.0 })
     }
 
     /// Format a GDP value into a human-friendly string with units.
@@ -103,7 +360,7 @@ impl GDPData {
         } else if val >= 1e6 {
             format!("{:.2} mln USD", val / 1e6)
         } else {
-            format!("{:.2} USD", val)
+            format!("{} USD", humanize::group_thousands(val.max(0.0).round() as u64, Lang::Pl))
         }
     }
 }
@@ -0,0 +1,116 @@
+//! Human-readable counts, populations and areas. The TUI speaks Polish and the
+//! command-line reports English, so each formatter takes the language to use.
+
+/// Language of the formatted text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lang {
+    Pl,
+    En,
+}
+
+/// Forms of a counted noun. English uses `one` and `many`; Polish also `few`,
+/// for counts ending in 2–4 other than 12–14 ("2 kraje", "5 krajów", "22 kraje").
+pub struct Noun {
+    pub one: &'static str,
+    pub few: &'static str,
+    pub many: &'static str,
+}
+
+impl Noun {
+    /// A noun with an English-style singular and plural
+    pub const fn en(one: &'static str, many: &'static str) -> Self {
+        Noun { one, few: many, many }
+    }
+
+    /// A noun with the three Polish forms
+    pub const fn pl(one: &'static str, few: &'static str, many: &'static str) -> Self {
+        Noun { one, few, many }
+    }
+
+    /// Form to use after `n`
+    pub fn form(&self, n: usize) -> &'static str {
+        if n == 1 {
+            self.one
+        } else if matches!(n % 10, 2..=4) && !matches!(n % 100, 12..=14) {
+            self.few
+        } else {
+            self.many
+        }
+    }
+}
+
+pub const COUNTRIES: Noun = Noun::en("country", "countries");
+pub const KRAJE: Noun = Noun::pl("kraj", "kraje", "krajów");
+pub const OBIEKTY: Noun = Noun::pl("obiekt", "obiekty", "obiektów");
+pub const WPISY: Noun = Noun::pl("wpis", "wpisy", "wpisów");
+pub const LIST_ENTRIES: Noun = Noun::en("list entry", "list entries");
+pub const CONTINENTS: Noun = Noun::en("continent", "continents");
+
+/// "N noun" with the noun in the form `n` takes, e.g. "1 kraj", "3 kraje", "1 country"
+pub fn format_count(n: usize, noun: &Noun) -> String {
+    format!("{} {}", n, noun.form(n))
+}
+
+/// Whole number with digits grouped in thousands: "38 000 000" (Polish), "38,000,000" (English).
+/// Four-digit numbers stay ungrouped in Polish, as Polish typesetting does.
+pub fn group_thousands(n: u64, lang: Lang) -> String {
+    let digits = n.to_string();
+    let sep = match lang {
+        Lang::Pl if digits.len() <= 4 => return digits,
+        Lang::Pl => '\u{a0}',
+        Lang::En => ',',
+    };
+    let mut out = String::with_capacity(digits.len() * 4 / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Population to three significant digits in millions or billions, e.g. "38.0 mln" /
+/// "38.0 M" and "1.44 mld" / "1.44 bn"; smaller populations in full with grouped digits.
+pub fn format_population(n: u64, lang: Lang) -> String {
+    let units: [(f64, &str); 2] = match lang {
+        Lang::Pl => [(1e9, "mld"), (1e6, "mln")],
+        Lang::En => [(1e9, "bn"), (1e6, "M")],
+    };
+    // Round first, so 999 950 000 becomes "1.00 bn" rather than "1000 M"
+    let rounded = round_significant(n as f64, 3);
+    match units.iter().find(|(base, _)| rounded >= *base) {
+        Some(&(base, unit)) => {
+            let scaled = rounded / base;
+            let decimals = if scaled < 10.0 { 2 } else if scaled < 100.0 { 1 } else { 0 };
+            format!("{:.*}{}{}", decimals, scaled, unit_sep(lang), unit)
+        }
+        None => group_thousands(n, lang),
+    }
+}
+
+/// Area in square kilometres: whole km² with grouped digits, one decimal below 10 km²
+pub fn format_area(km2: f64, lang: Lang) -> String {
+    if km2 < 9.95 {
+        format!("{:.1}{}km²", km2.max(0.0), unit_sep(lang))
+    } else {
+        format!("{}{}km²", group_thousands(km2.round() as u64, lang), unit_sep(lang))
+    }
+}
+
+/// Space between a number and its unit; non-breaking in the TUI so wrapping keeps them together
+fn unit_sep(lang: Lang) -> char {
+    match lang {
+        Lang::Pl => '\u{a0}',
+        Lang::En => ' ',
+    }
+}
+
+/// `x` rounded to `digits` significant digits
+fn round_significant(x: f64, digits: i32) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let scale = 10f64.powi(digits - 1 - x.log10().floor() as i32);
+    (x * scale).round() / scale
+}
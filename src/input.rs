@@ -0,0 +1,304 @@
+//! Line editing shared by the text inputs (the list search and the ranking filter):
+//! typing, and a session history with readline-style recall. Up/Down walk through
+//! earlier entries, typing leaves the history, and Ctrl+R searches it backwards.
+//! The cursor moves and deletes by grapheme cluster, and typed characters are
+//! normalized to NFC, so "São Tomé" reads the same whether the terminal sent `ã` or
+//! `a` followed by a combining tilde.
+use crossterm::event::KeyCode;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::keys;
+
+/// Key starting a reverse search through the history
+pub const CTRL_R: KeyCode = keys::ctrl('r');
+
+/// What a key did to the text being edited
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Edit {
+    Changed,    // the text is different now
+    Unchanged,  // the key was used, but the text stayed (e.g. Up at the oldest entry)
+    Submit,     // Enter: the text was accepted and recorded in the history
+    Cancel,     // Esc: the input was abandoned, nothing recorded
+    NotHandled, // not an editing key
+}
+
+/// Text being typed, with a cursor between two grapheme clusters
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineBuffer {
+    text: String,  // NFC
+    cursor: usize, // byte offset into `text`, always at a grapheme boundary
+}
+
+impl LineBuffer {
+    /// `text` with the cursor at its end
+    pub fn new(text: &str) -> Self {
+        let mut line = LineBuffer::default();
+        line.set(text);
+        line
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replace the text with `text`, the cursor at its end
+    pub fn set(&mut self, text: &str) {
+        self.text = text.nfc().collect();
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set("");
+    }
+
+    /// Text before and after the cursor, for drawing the cursor between them; its column
+    /// is the display width of the first part, whatever the bytes or code points
+    pub fn split(&self) -> (&str, &str) {
+        self.text.split_at(self.cursor)
+    }
+
+    /// Type `c` at the cursor. A combining character joins the cluster before it,
+    /// composed with it where Unicode has a precomposed form; the cursor ends up after
+    /// the whole cluster.
+    pub fn insert(&mut self, c: char) {
+        let (before, after) = self.split();
+        let mut before: String = before.chars().chain([c]).nfc().collect();
+        let after = after.to_string();
+        self.cursor = before.len();
+        before.push_str(&after);
+        self.text = before;
+        if !self.at_boundary(self.cursor) {
+            self.cursor = self.next_boundary();
+        }
+    }
+
+    /// Delete the grapheme cluster before the cursor; false at the start
+    pub fn backspace(&mut self) -> bool {
+        let start = self.previous_boundary();
+        if start == self.cursor {
+            return false;
+        }
+        self.text.replace_range(start..self.cursor, "");
+        self.cursor = start;
+        true
+    }
+
+    /// Delete the grapheme cluster after the cursor; false at the end
+    pub fn delete(&mut self) -> bool {
+        let end = self.next_boundary();
+        if end == self.cursor {
+            return false;
+        }
+        self.text.replace_range(self.cursor..end, "");
+        true
+    }
+
+    /// Move the cursor one grapheme cluster left; false at the start
+    pub fn left(&mut self) -> bool {
+        let to = self.previous_boundary();
+        std::mem::replace(&mut self.cursor, to) != to
+    }
+
+    /// Move the cursor one grapheme cluster right; false at the end
+    pub fn right(&mut self) -> bool {
+        let to = self.next_boundary();
+        std::mem::replace(&mut self.cursor, to) != to
+    }
+
+    /// Move the cursor to the start; false when already there
+    pub fn home(&mut self) -> bool {
+        std::mem::replace(&mut self.cursor, 0) != 0
+    }
+
+    /// Move the cursor to the end; false when already there
+    pub fn end(&mut self) -> bool {
+        let to = self.text.len();
+        std::mem::replace(&mut self.cursor, to) != to
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor].grapheme_indices(true).next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor..].graphemes(true).next().map_or(self.cursor, |g| self.cursor + g.len())
+    }
+
+    fn at_boundary(&self, at: usize) -> bool {
+        at == self.text.len() || self.text.grapheme_indices(true).any(|(i, _)| i == at)
+    }
+}
+
+/// An in-progress reverse search through the history
+#[derive(Clone, Debug, Default)]
+pub struct ReverseSearch {
+    pub needle: LineBuffer,
+    pub hit: Option<usize>, // entry matching the needle, index into the entries
+    original: LineBuffer,   // text before the search, restored on Esc
+}
+
+/// Earlier entries of one input, oldest first, with the recall position
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    recall: Option<usize>, // entry shown by Up/Down; None while typing
+    draft: LineBuffer,     // text typed before recalling, shown again past the newest entry
+    pub search: Option<ReverseSearch>,
+}
+
+impl History {
+    /// Oldest entries are dropped beyond this many
+    pub const LIMIT: usize = 100;
+
+    /// History holding `entries`, oldest first, as `entries()` returned them
+    pub fn from_entries(entries: Vec<String>) -> Self {
+        let mut history = History::default();
+        for entry in entries {
+            history.record(&entry);
+        }
+        history
+    }
+
+    /// Entries, oldest first
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Whether Up/Down are showing an earlier entry
+    pub fn recalling(&self) -> bool {
+        self.recall.is_some()
+    }
+
+    /// Add an accepted input as the newest entry; empty inputs are left out and an
+    /// entry already present moves to the end instead of repeating
+    pub fn record(&mut self, text: &str) {
+        self.recall = None;
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != text);
+        self.entries.push(text.to_string());
+        if self.entries.len() > Self::LIMIT {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Show the entry before the recalled one (the newest when typing); false at the oldest
+    pub fn older(&mut self, text: &mut LineBuffer) -> bool {
+        let pos = match self.recall {
+            None if self.entries.is_empty() => return false,
+            None => {
+                self.draft = text.clone();
+                self.entries.len() - 1
+            }
+            Some(0) => return false,
+            Some(pos) => pos - 1,
+        };
+        self.recall = Some(pos);
+        text.set(&self.entries[pos]);
+        true
+    }
+
+    /// Show the entry after the recalled one, and past the newest the text typed before
+    /// recalling; false while typing
+    pub fn newer(&mut self, text: &mut LineBuffer) -> bool {
+        let Some(pos) = self.recall else { return false };
+        if pos + 1 < self.entries.len() {
+            self.recall = Some(pos + 1);
+            text.set(&self.entries[pos + 1]);
+        } else {
+            self.recall = None;
+            *text = std::mem::take(&mut self.draft);
+        }
+        true
+    }
+
+    /// Newest entry before index `before` containing `needle`, ignoring case
+    fn find(&self, needle: &str, before: usize) -> Option<usize> {
+        let needle = needle.to_lowercase();
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|e| e.to_lowercase().contains(&needle))
+    }
+
+    /// Apply `key` to `text`: typing, Backspace and Delete edit it (leaving the history),
+    /// Left/Right/Home/End move the cursor, Up/Down recall entries, Ctrl+R searches
+    /// backwards, Enter records it and Esc abandons it. During a reverse search, typing
+    /// extends the needle, Ctrl+R finds the next older match, Enter keeps the match as the
+    /// text and Esc restores the text from before.
+    pub fn edit(&mut self, text: &mut LineBuffer, key: KeyCode) -> Edit {
+        if let Some(mut search) = self.search.take() {
+            match key {
+                KeyCode::Enter => {}
+                KeyCode::Esc => *text = search.original,
+                CTRL_R => {
+                    let before = search.hit.unwrap_or(self.entries.len());
+                    search.hit = self.find(search.needle.as_str(), before).or(search.hit);
+                    self.search = Some(search);
+                }
+                KeyCode::Backspace | KeyCode::Char(_) if !matches!(key, KeyCode::Char(c) if c.is_control()) => {
+                    match key {
+                        KeyCode::Char(c) => search.needle.insert(c),
+                        _ => _ = search.needle.backspace(),
+                    }
+                    search.hit = self.find(search.needle.as_str(), self.entries.len());
+                    self.search = Some(search);
+                }
+                _ => {
+                    self.search = Some(search);
+                    return Edit::Unchanged;
+                }
+            }
+            if let Some(hit) = self.search.as_ref().and_then(|s| s.hit) {
+                text.set(&self.entries[hit]);
+            }
+            self.recall = None;
+            return Edit::Changed;
+        }
+        match key {
+            KeyCode::Enter => {
+                self.record(text.as_str());
+                Edit::Submit
+            }
+            KeyCode::Esc => {
+                self.recall = None;
+                Edit::Cancel
+            }
+            CTRL_R => {
+                self.search = Some(ReverseSearch { original: text.clone(), ..ReverseSearch::default() });
+                Edit::Unchanged
+            }
+            KeyCode::Up if self.older(text) => Edit::Changed,
+            KeyCode::Down if self.newer(text) => Edit::Changed,
+            KeyCode::Up | KeyCode::Down => Edit::Unchanged,
+            KeyCode::Backspace | KeyCode::Delete => {
+                self.recall = None;
+                let changed = match key {
+                    KeyCode::Backspace => text.backspace(),
+                    _ => text.delete(),
+                };
+                if changed { Edit::Changed } else { Edit::Unchanged }
+            }
+            KeyCode::Char(c) if !c.is_control() => {
+                self.recall = None;
+                text.insert(c);
+                Edit::Changed
+            }
+            KeyCode::Left | KeyCode::Right | KeyCode::Home | KeyCode::End => {
+                match key {
+                    KeyCode::Left => text.left(),
+                    KeyCode::Right => text.right(),
+                    KeyCode::Home => text.home(),
+                    _ => text.end(),
+                };
+                Edit::Unchanged
+            }
+            _ => Edit::NotHandled,
+        }
+    }
+}
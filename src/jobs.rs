@@ -0,0 +1,157 @@
+//! Long operations on a worker thread, reporting progress and observing cancellation,
+//! so the UI keeps drawing while they run.
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Where a job is in its life
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobState {
+    Queued,                                     // spawned, the worker hasn't started it yet
+    Running { percent: u8, message: String },   // last progress report
+    Cancelled,                                  // stopped early on request
+    Done,
+    Failed(String),
+}
+
+impl JobState {
+    /// Whether the job has stopped, one way or another
+    pub fn finished(&self) -> bool {
+        matches!(self, JobState::Cancelled | JobState::Done | JobState::Failed(_))
+    }
+}
+
+/// What a job's work function returns
+pub enum Outcome<T> {
+    Done(T),
+    Cancelled, // the work saw `Progress::cancelled` and stopped
+    Failed(String),
+}
+
+/// A job's side of the channels: progress reports out, the cancellation request in
+pub struct Progress {
+    tx: Option<Sender<(u8, String)>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Progress {
+    /// Handle for running job code directly: reports go nowhere and it is never cancelled
+    pub fn detached() -> Self {
+        Progress { tx: None, cancel: Arc::new(AtomicBool::new(false)) }
+    }
+
+    /// Report `percent` done (clamped to 100) and what is happening now
+    pub fn report(&self, percent: u8, message: impl Into<String>) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send((percent.min(100), message.into()));
+        }
+    }
+
+    /// Whether cancellation was requested; jobs check this at their loop boundaries
+    pub fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// A job running on its own worker thread, polled by its owner
+pub struct Job<T> {
+    pub title: String,
+    pub started: Instant,
+    state: JobState,
+    result: Option<T>,
+    progress: Receiver<(u8, String)>,
+    done: Receiver<Outcome<T>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Start `work` on a new thread
+    pub fn spawn<F>(title: impl Into<String>, work: F) -> Self
+    where
+        F: FnOnce(&Progress) -> Outcome<T> + Send + 'static,
+    {
+        let (progress_tx, progress) = channel();
+        let (done_tx, done) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let handle = Progress { tx: Some(progress_tx), cancel: cancel.clone() };
+        thread::spawn(move || {
+            handle.report(0, "");
+            let _ = done_tx.send(work(&handle));
+        });
+        Job {
+            title: title.into(),
+            started: Instant::now(),
+            state: JobState::Queued,
+            result: None,
+            progress,
+            done,
+            cancel,
+        }
+    }
+}
+
+impl<T> Job<T> {
+    /// Ask the job to stop; it does at its next loop boundary
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether `cancel` was called
+    pub fn cancel_requested(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Current state, as of the last `poll`
+    pub fn state(&self) -> &JobState {
+        &self.state
+    }
+
+    /// Apply the worker's pending updates. A worker that died without finishing
+    /// counts as failed.
+    pub fn poll(&mut self) -> &JobState {
+        if self.state.finished() {
+            return &self.state;
+        }
+        // Progress is always sent before the outcome, so draining it first keeps the order
+        while let Ok((percent, message)) = self.progress.try_recv() {
+            self.state = JobState::Running { percent, message };
+        }
+        match self.done.try_recv() {
+            Ok(Outcome::Done(value)) => {
+                self.state = JobState::Done;
+                self.result = Some(value);
+            }
+            Ok(Outcome::Cancelled) => self.state = JobState::Cancelled,
+            Ok(Outcome::Failed(e)) => self.state = JobState::Failed(e),
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => self.state = JobState::Failed("worker thread stopped".to_string()),
+        }
+        &self.state
+    }
+
+    /// The job's result once it is done; taken at most once
+    pub fn take_result(&mut self) -> Option<T> {
+        self.result.take()
+    }
+
+    /// Block until the job finishes, polling every `interval` and calling `on_update`
+    /// whenever the state changed
+    pub fn wait(&mut self, interval: Duration, mut on_update: impl FnMut(&JobState)) -> &JobState {
+        let mut last = None;
+        while !self.poll().finished() {
+            if last.as_ref() != Some(&self.state) {
+                on_update(&self.state);
+                last = Some(self.state.clone());
+            }
+            thread::sleep(interval);
+        }
+        on_update(&self.state);
+        &self.state
+    }
+}
@@ -0,0 +1,478 @@
+use crossterm::event::KeyCode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use crate::{data::GeoLevel, state::Panel};
+
+/// User commands, independent of the keys bound to them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back,
+    Undo,
+    Redo,
+    SwitchView,
+    LargestEconomy,
+    LogScale,
+    MiniMap,
+    Labels,
+    WorldOrder,
+    Projection,
+    MapMarker,
+    MapColors,
+    Graticule,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    AutoZoom,
+    Scatter,
+    Ranking,
+    SortColumn,
+    Filter,
+    Choropleth,
+    Legend,
+    Mark,
+    CompareGrid,
+    SharedAxis,
+    PickAlias,
+    NextFact,
+    OpenSource,
+    Parent,
+    Territory,
+    Quiz,
+    QuizReview,
+    Reload,
+    EditData,
+    SaveView,
+    Diagnostics,
+    Help,
+    Quit,
+}
+
+/// Where the user currently is; decides which actions apply
+pub struct Context {
+    pub level: GeoLevel,
+    pub chart: bool,   // GDP chart is open
+    pub compare: bool, // comparison grid is open
+    pub gdp_missing: bool, // GDP lookup found no entry for the country
+    pub choropleth: bool,  // map is colored by GDP buckets
+    pub panel: Panel,  // focused panel
+    pub ranking: bool, // continent ranking table is open
+    pub zoomed: bool,  // map is zoomed in
+    pub territory: bool,   // the country is a territory of another
+    pub territories: bool, // the country has territories, or shares its sovereign with others
+}
+
+impl Action {
+    /// Every action, in the order help lists them
+    pub const ALL: [Action; 44] = [
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::Select,
+        Action::Back,
+        Action::Undo,
+        Action::Redo,
+        Action::SwitchView,
+        Action::LargestEconomy,
+        Action::LogScale,
+        Action::MiniMap,
+        Action::Labels,
+        Action::WorldOrder,
+        Action::Projection,
+        Action::MapMarker,
+        Action::MapColors,
+        Action::Graticule,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::ZoomReset,
+        Action::AutoZoom,
+        Action::Scatter,
+        Action::Ranking,
+        Action::SortColumn,
+        Action::Filter,
+        Action::Choropleth,
+        Action::Legend,
+        Action::Mark,
+        Action::CompareGrid,
+        Action::SharedAxis,
+        Action::PickAlias,
+        Action::NextFact,
+        Action::OpenSource,
+        Action::Parent,
+        Action::Territory,
+        Action::Quiz,
+        Action::QuizReview,
+        Action::Reload,
+        Action::EditData,
+        Action::SaveView,
+        Action::Diagnostics,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    /// Actions that must stay bound: without them the app can't be left
+    pub const CORE: [Action; 2] = [Action::Back, Action::Quit];
+
+    /// Help text shown next to the bound keys
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Up => "w górę (lista, ciekawostki kraju / w prawym panelu / przybliżona mapa)",
+            Action::Down => "w dół (lista, ciekawostki kraju / w prawym panelu / przybliżona mapa)",
+            Action::Left => "poprzedni rok (wykres, kolory wg GDP) / kontynent na zachód (mapa) / w lewo (przybliżona mapa)",
+            Action::Right => "następny rok (wykres, kolory wg GDP) / kontynent na wschód (mapa) / w prawo (przybliżona mapa)",
+            Action::Select => "zagłębienie (świat → kontynent → kraj)",
+            Action::Back => "wstecz (poziom wyżej)",
+            Action::Undo => "poprzednio odwiedzone miejsce",
+            Action::Redo => "następne odwiedzone miejsce",
+            Action::SwitchView => "wykres GDP (kraj) / zmiana panelu",
+            Action::LargestEconomy => "największa gospodarka kontynentu",
+            Action::LogScale => "skala logarytmiczna (wykres)",
+            Action::MiniMap => "minimapa świata (kontynent, kraj)",
+            Action::Labels => "nazwy krajów na mapie (świat, kontynent)",
+            Action::WorldOrder => "kontynenty alfabetycznie / z zachodu na wschód (świat)",
+            Action::Projection => "odwzorowanie mapy: proporcje / stopnie / Robinson / Mollweide",
+            Action::MapMarker => "rysowanie mapy: Braille / kropki / półbloki / bloki",
+            Action::MapColors => "kolory mapy i zaznaczenia: klasyczne / bez czerwieni / jasne tło",
+            Action::Graticule => "siatka południków i równoleżników na mapie",
+            Action::ZoomIn => "przybliż mapę",
+            Action::ZoomOut => "oddal mapę",
+            Action::ZoomReset => "cała mapa",
+            Action::AutoZoom => "mapa przybliżona do zaznaczonego kraju (kontynent)",
+            Action::Scatter => "GDP a populacja krajów (kontynent)",
+            Action::Ranking => "ranking krajów (kontynent)",
+            Action::SortColumn => "kolejność krajów na liście kontynentu / kolumna sortowania (ranking)",
+            Action::Filter => "szukaj na liście / filtr rankingu (np. pop>10m, gdp<1bn)",
+            Action::Choropleth => "kolory krajów wg GDP (świat, kontynent)",
+            Action::Legend => "legenda kolorów: ↑/↓ wybiera przedział i pokazuje jego kraje",
+            Action::Mark => "zaznacz kraj do porównania (maks. 4)",
+            Action::CompareGrid => "siatka wykresów GDP zaznaczonych krajów",
+            Action::SharedAxis => "wspólna oś GDP (siatka porównania)",
+            Action::PickAlias => "wybierz nazwę kraju w danych GDP (brak GDP)",
+            Action::NextFact => "następna ciekawostka (kraj)",
+            Action::OpenSource => "otwórz źródło ciekawostki (kraj)",
+            Action::Parent => "kraj, do którego należy terytorium (kraj)",
+            Action::Territory => "kolejne terytorium kraju (kraj)",
+            Action::Quiz => "quiz: który to kraj? (świat, kontynent; 1–4 odpowiada)",
+            Action::QuizReview => "quiz z krajów, w których były błędy (świat, kontynent)",
+            Action::Reload => "wczytaj ponownie config.json (klawisze, kolory)",
+            Action::EditData => "otwórz plik danych tego widoku w $EDITOR (lista, mapa, informacje, ciekawostki)",
+            Action::SaveView => "zapisz kolejność, filtr, kolory i nazwy jako domyślne dla tego miejsca",
+            Action::Diagnostics => "diagnostyka danych",
+            Action::Help => "pomoc",
+            Action::Quit => "wyjście",
+        }
+    }
+
+    /// A word or two for the status bar, where every available action has to fit on one line
+    pub fn hint(self) -> &'static str {
+        match self {
+            Action::Up => "w górę",
+            Action::Down => "w dół",
+            Action::Left => "w lewo",
+            Action::Right => "w prawo",
+            Action::Select => "otwórz",
+            Action::Back => "wstecz",
+            Action::Undo => "cofnij",
+            Action::Redo => "ponów",
+            Action::SwitchView => "panel",
+            Action::LargestEconomy => "największa gospodarka",
+            Action::LogScale => "skala log",
+            Action::MiniMap => "minimapa",
+            Action::Labels => "nazwy",
+            Action::WorldOrder => "kolejność",
+            Action::Projection => "odwzorowanie",
+            Action::MapMarker => "znaki mapy",
+            Action::MapColors => "kolory mapy",
+            Action::Graticule => "siatka",
+            Action::ZoomIn => "przybliż",
+            Action::ZoomOut => "oddal",
+            Action::ZoomReset => "cała mapa",
+            Action::AutoZoom => "śledź kraj",
+            Action::Scatter => "GDP/populacja",
+            Action::Ranking => "ranking",
+            Action::SortColumn => "sortuj",
+            Action::Filter => "szukaj",
+            Action::Choropleth => "kolory GDP",
+            Action::Legend => "legenda",
+            Action::Mark => "zaznacz",
+            Action::CompareGrid => "porównaj",
+            Action::SharedAxis => "wspólna oś",
+            Action::PickAlias => "nazwa GDP",
+            Action::NextFact => "ciekawostka",
+            Action::OpenSource => "źródło",
+            Action::Parent => "państwo",
+            Action::Territory => "terytorium",
+            Action::Quiz => "quiz",
+            Action::QuizReview => "powtórka",
+            Action::Reload => "wczytaj config",
+            Action::EditData => "edytuj dane",
+            Action::SaveView => "zapisz widok",
+            Action::Diagnostics => "diagnostyka",
+            Action::Help => "pomoc",
+            Action::Quit => "wyjście",
+        }
+    }
+
+    /// Keys bound when `config.json` doesn't override the action
+    fn default_keys(self) -> &'static [KeyCode] {
+        match self {
+            Action::Up => &[KeyCode::Up],
+            Action::Down => &[KeyCode::Down],
+            Action::Left => &[KeyCode::Left],
+            Action::Right => &[KeyCode::Right],
+            Action::Select => &[KeyCode::Enter],
+            Action::Back => &[KeyCode::Esc, KeyCode::Backspace],
+            Action::Undo => &[KeyCode::Char('[')],
+            Action::Redo => &[KeyCode::Char(']')],
+            Action::SwitchView => &[KeyCode::Tab],
+            Action::LargestEconomy => &[KeyCode::Char('$')],
+            Action::LogScale => &[KeyCode::Char('L')],
+            Action::MiniMap => &[KeyCode::Char('~')],
+            Action::Labels => &[KeyCode::Char('n')],
+            Action::WorldOrder => &[KeyCode::Char('g')],
+            Action::Projection => &[KeyCode::Char('p')],
+            Action::MapMarker => &[KeyCode::Char('M')],
+            Action::MapColors => &[KeyCode::Char('C')],
+            Action::Graticule => &[KeyCode::Char('G')],
+            Action::ZoomIn => &[KeyCode::Char('+'), KeyCode::Char('=')],
+            Action::ZoomOut => &[KeyCode::Char('-')],
+            Action::ZoomReset => &[KeyCode::Char('0')],
+            Action::AutoZoom => &[KeyCode::Char('z')],
+            Action::Scatter => &[KeyCode::Char('P')],
+            Action::Ranking => &[KeyCode::Char('r')],
+            Action::SortColumn => &[KeyCode::Char('s')],
+            Action::Filter => &[KeyCode::Char('/')],
+            Action::Choropleth => &[KeyCode::Char('c')],
+            Action::Legend => &[KeyCode::Char('k')],
+            Action::Mark => &[KeyCode::Char('m')],
+            Action::CompareGrid => &[KeyCode::Char('V')],
+            Action::SharedAxis => &[KeyCode::Char('l')],
+            Action::PickAlias => &[KeyCode::Char('a')],
+            Action::NextFact => &[KeyCode::Char('f')],
+            Action::OpenSource => &[KeyCode::Char('o')],
+            Action::Parent => &[KeyCode::Char('u')],
+            Action::Territory => &[KeyCode::Char('t')],
+            Action::Quiz => &[KeyCode::Char('Q')],
+            Action::QuizReview => &[KeyCode::Char('R')],
+            Action::Reload => &[CTRL_T],
+            Action::EditData => &[CTRL_E],
+            Action::SaveView => &[CTRL_S],
+            Action::Diagnostics => &[KeyCode::Char('d')],
+            Action::Help => &[KeyCode::Char('?')],
+            Action::Quit => &[KeyCode::Char('q')],
+        }
+    }
+
+    /// Whether the action does anything in the given context
+    pub fn available(self, ctx: &Context) -> bool {
+        if ctx.compare {
+            return matches!(
+                self,
+                Action::Back
+                    | Action::CompareGrid
+                    | Action::SharedAxis
+                    | Action::Reload
+                    | Action::EditData
+                    | Action::MapColors
+                    | Action::Diagnostics
+                    | Action::Help
+                    | Action::Quit
+            );
+        }
+        if ctx.ranking {
+            return matches!(
+                self,
+                Action::Up
+                    | Action::Down
+                    | Action::Select
+                    | Action::Back
+                    | Action::Ranking
+                    | Action::SortColumn
+                    | Action::Filter
+                    | Action::SaveView
+                    | Action::Reload
+                    | Action::EditData
+                    | Action::MapColors
+                    | Action::Diagnostics
+                    | Action::Help
+                    | Action::Quit
+            );
+        }
+        match self {
+            Action::Up | Action::Down => !ctx.chart,
+            Action::Left | Action::Right => {
+                ctx.chart || (ctx.panel == Panel::Center && (ctx.level == GeoLevel::World || ctx.zoomed || ctx.choropleth))
+            }
+            Action::Select => !ctx.chart && ctx.level != GeoLevel::Country && ctx.panel != Panel::Right,
+            Action::LargestEconomy => !ctx.chart && ctx.level != GeoLevel::Country,
+            Action::Back => ctx.chart || ctx.level != GeoLevel::World,
+            Action::LogScale => ctx.chart,
+            Action::Undo | Action::Redo => !ctx.chart,
+            Action::MiniMap => !ctx.chart && ctx.level != GeoLevel::World,
+            Action::Labels => !ctx.chart && ctx.level != GeoLevel::Country,
+            Action::WorldOrder => !ctx.chart && ctx.level == GeoLevel::World,
+            Action::Projection | Action::MapMarker | Action::Graticule => !ctx.chart,
+            Action::ZoomIn | Action::ZoomOut => !ctx.chart && ctx.panel == Panel::Center,
+            Action::ZoomReset => !ctx.chart && ctx.panel == Panel::Center && ctx.zoomed,
+            Action::AutoZoom | Action::Scatter | Action::Ranking => !ctx.chart && ctx.level == GeoLevel::Continent,
+            Action::Choropleth => !ctx.chart && ctx.level != GeoLevel::Country,
+            Action::SortColumn => !ctx.chart && ctx.level == GeoLevel::Continent,
+            Action::Filter => !ctx.chart && ctx.level != GeoLevel::Country,
+            Action::Legend => !ctx.chart && ctx.choropleth,
+            Action::Mark => !ctx.chart && ctx.level != GeoLevel::World,
+            Action::CompareGrid => !ctx.chart,
+            Action::SharedAxis => false, // grid only
+            Action::PickAlias => !ctx.chart && ctx.gdp_missing,
+            Action::NextFact | Action::OpenSource => !ctx.chart && ctx.level == GeoLevel::Country,
+            Action::Parent => !ctx.chart && ctx.level == GeoLevel::Country && ctx.territory,
+            Action::Territory => !ctx.chart && ctx.level == GeoLevel::Country && ctx.territories,
+            Action::Quiz | Action::QuizReview | Action::SaveView => !ctx.chart && ctx.level != GeoLevel::Country,
+            Action::SwitchView | Action::Reload | Action::EditData | Action::MapColors | Action::Diagnostics | Action::Help | Action::Quit => true,
+        }
+    }
+}
+
+/// Key code of Ctrl+`letter`: the control character terminals send for it
+pub const fn ctrl(letter: char) -> KeyCode {
+    KeyCode::Char(((letter as u8) & 0x1f) as char)
+}
+
+const CTRL_T: KeyCode = ctrl('t');
+const CTRL_S: KeyCode = ctrl('s');
+const CTRL_E: KeyCode = ctrl('e');
+
+/// Parse a key name from `config.json`: a single character, Ctrl+letter, or
+/// Up/Down/Left/Right/Enter/Esc/Backspace/Tab
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let lower = name.to_lowercase();
+    if let Some(letter) = lower.strip_prefix("ctrl+").or_else(|| lower.strip_prefix("ctrl-")) {
+        let mut chars = letter.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_lowercase() => Some(ctrl(c)),
+            _ => None,
+        };
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    match name.to_lowercase().as_str() {
+        "up" => Some(KeyCode::Up),
+        "down" => Some(KeyCode::Down),
+        "left" => Some(KeyCode::Left),
+        "right" => Some(KeyCode::Right),
+        "enter" => Some(KeyCode::Enter),
+        "esc" => Some(KeyCode::Esc),
+        "backspace" => Some(KeyCode::Backspace),
+        "tab" => Some(KeyCode::Tab),
+        _ => None,
+    }
+}
+
+/// Display name of a key in help texts
+pub fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Char(c) if ('\x01'..='\x1a').contains(&c) => format!("Ctrl+{}", (c as u8 + b'@') as char),
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// Key → action table: defaults, with per-action overrides from `config.json`
+pub struct KeyBindings {
+    bindings: Vec<(KeyCode, Action)>,
+}
+
+impl KeyBindings {
+    /// Build the table; an override replaces all default keys of its action and
+    /// takes the key away from any other action. Returns unparseable key names.
+    pub fn new(overrides: &BTreeMap<Action, Vec<String>>) -> (Self, Vec<String>) {
+        let mut invalid = Vec::new();
+        let mut bindings: Vec<(KeyCode, Action)> = Vec::new();
+        for action in Action::ALL {
+            match overrides.get(&action) {
+                Some(names) => {
+                    for name in names {
+                        match parse_key(name) {
+                            Some(key) => {
+                                bindings.retain(|(k, _)| *k != key);
+                                bindings.push((key, action));
+                            }
+                            None => invalid.push(name.clone()),
+                        }
+                    }
+                }
+                None => {
+                    for &key in action.default_keys() {
+                        if !bindings.iter().any(|(k, _)| *k == key) {
+                            bindings.push((key, action));
+                        }
+                    }
+                }
+            }
+        }
+        (Self { bindings }, invalid)
+    }
+
+    /// Core actions (see `Action::CORE`) no key is bound to
+    pub fn unbound_core(&self) -> Vec<Action> {
+        Action::CORE
+            .into_iter()
+            .filter(|&a| !self.bindings.iter().any(|&(_, b)| b == a))
+            .collect()
+    }
+
+    /// Action bound to `key`, if any
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        self.bindings.iter().find(|(k, _)| *k == key).map(|&(_, a)| a)
+    }
+
+    /// First key bound to `action`, for replaying it; None when it has none
+    pub fn key(&self, action: Action) -> Option<KeyCode> {
+        self.bindings.iter().find(|(_, a)| *a == action).map(|&(k, _)| k)
+    }
+
+    /// Names of the keys bound to `action`, joined with "/"; empty if unbound
+    pub fn label(&self, action: Action) -> String {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|&(k, _)| key_name(k))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// "keys: hint" entries of the status bar for every bound action available in `ctx`;
+    /// ↑/↓ and ←/→ share one entry when both are available
+    pub fn hints(&self, ctx: &Context) -> Vec<String> {
+        const PAIRS: [(Action, Action, &str); 2] = [(Action::Up, Action::Down, "ruch"), (Action::Left, Action::Right, "zmiana")];
+        let bound = |a: Action| Some(self.label(a)).filter(|label| !label.is_empty() && a.available(ctx));
+        let mut hints = Vec::new();
+        for a in Action::ALL {
+            let pair = PAIRS.iter().find(|&&(first, second, _)| a == first || a == second);
+            match pair {
+                Some(&(first, second, hint)) if let (Some(one), Some(other)) = (bound(first), bound(second)) => {
+                    if a == first {
+                        hints.push(format!("{}/{}: {}", one, other, hint));
+                    }
+                }
+                _ => hints.extend(bound(a).map(|label| format!("{}: {}", label, a.hint()))),
+            }
+        }
+        hints
+    }
+}
@@ -0,0 +1,36 @@
+//! RustAtlas as a library: data loading, map geometry, GDP lookups and the TUI
+//! building blocks. The `RustAtlas` binary and the benchmarks are built on top of it.
+
+pub mod ui;
+pub mod view_model;
+pub mod state;
+pub mod config;
+pub mod prefs;
+pub mod cli;
+pub mod export;
+pub mod check;
+pub mod self_test;
+pub mod data_diff;
+pub mod error;
+pub mod map_dump;
+pub mod keys;
+pub mod data;
+#[cfg(feature = "online")]
+pub mod remote;
+pub mod map_draw;
+pub mod gdp_reader;
+pub mod paths;
+pub mod text;
+pub mod humanize;
+pub mod theme;
+pub mod suggest;
+pub mod summary;
+pub mod describe;
+pub mod filter;
+pub mod input;
+pub mod expand_list;
+pub mod year_slider;
+pub mod quiz;
+pub mod jobs;
+pub mod platform;
+pub mod editor;
@@ -1,51 +1,110 @@
-mod ui;
-mod state;
-mod data;
-mod map_draw;
-mod gdp_reader;
-
-use crossterm::{
-    event::{self, Event, KeyEvent, KeyEventKind, DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
-use state::AppState;
+use std::{io::IsTerminal, path::Path};
+use rustatlas::{check, cli::{self, Command}, data::DataSource, data_diff, error::AtlasError, export, map_dump, platform, self_test, state::AppState, theme::{ColorSupport, Theme}};
+#[cfg(feature = "online")]
+use rustatlas::{paths::{self, Artifact}, remote};
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Load application state with GDP data
-    let mut state = AppState::new("data")?;
-
-    // Enter raw mode and alternate screen
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+#[cfg(not(any(feature = "backend-crossterm", all(unix, feature = "backend-termion"))))]
+compile_error!("enable a terminal backend: `backend-crossterm` (default) or `backend-termion` (Unix)");
 
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+/// Directory holding all map, list and dataset files
+const DATA_DIR: &str = "data";
 
-    // Main loop: draw UI and handle key input
-    loop {
-        terminal.draw(|f| ui::draw(f, &mut state))?;
-
-        if event::poll(std::time::Duration::from_millis(100))? {
-            if let Event::Key(KeyEvent { code, kind: KeyEventKind::Press, .. }) = event::read()? {
-                if state.handle_input(code) {
-                    break; // Exit on quit command
-                }
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Headless subcommands run without touching the terminal and exit with the code of
+    // their error kind; `--json-errors` may go anywhere on the command line
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let json_errors = args.iter().any(|a| a == cli::JSON_ERRORS);
+    args.retain(|a| a != cli::JSON_ERRORS);
+    let command = cli::take_data_dir(&mut args).and_then(|dir| Ok((dir, cli::parse(args)?)));
+    let (data_dir, command) = match command {
+        Ok(command) => command,
+        Err(msg) => {
+            if json_errors {
+                AtlasError::Usage(msg).report(true);
+            } else {
+                eprintln!("{}\n\n{}", msg, cli::USAGE);
             }
+            std::process::exit(2);
+        }
+    };
+    let data_dir = data_dir.unwrap_or_else(|| DATA_DIR.to_string());
+    let base = Path::new(&data_dir);
+    let result = match command {
+        // Only the interactive atlas reads from a server
+        _ if cli::is_url(&data_dir) && !matches!(command, Command::Tui { .. }) => {
+            Err(AtlasError::Usage(format!("{} takes a URL only for the interactive atlas", cli::DATA_DIR)).into())
         }
+        // Raw mode and the alternate screen make no sense in a file or a pipe
+        _ if let Some(why) = cli::tty_refusal(&command, std::io::stdout().is_terminal()) => Err(AtlasError::Usage(why).into()),
+        Command::ExportGdp(args) => export::run(args, base),
+        Command::CheckData => check::run(base),
+        Command::SelfTest => self_test::run(base),
+        Command::Map(args) => map_dump::run(args, base),
+        Command::DataDiff(args) => data_diff::run(args),
+        Command::Tui { tour, colors, animations, watch, low_mem, accessible, .. } => {
+            return run_tui(&data_dir, tour, colors, animations, watch, low_mem, accessible);
+        }
+    };
+    if let Err(e) = result {
+        let e = AtlasError::classify(e);
+        e.report(json_errors);
+        std::process::exit(e.code());
     }
+    Ok(())
+}
 
-    // Restore terminal state
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+/// Run the interactive atlas until it is quit, then save the session
+fn run_tui(
+    data_dir: &str,
+    tour: bool,
+    colors: Option<ColorSupport>,
+    animations: bool,
+    watch: bool,
+    low_mem: bool,
+    accessible: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+
+    // Load application state with GDP data
+    let mut state = if cli::is_url(data_dir) {
+        AppState::open_source(remote_source(data_dir)?, low_mem)?
+    } else {
+        AppState::open(data_dir, low_mem)?
+    };
+    if tour {
+        state.start_tour();
+    }
+    if let Some(colors) = colors {
+        state.theme = Theme::new(colors).with_symbols(state.theme.symbols());
+    }
+    state.colors_override = colors;
+    state.animations = animations;
+    state.watch = watch;
+    if accessible || state.config.accessible {
+        state.make_accessible();
+    }
+
+    // Draw and handle keys on the terminal backend the build was made for
+    #[cfg(feature = "backend-crossterm")]
+    let mut driver = platform::CrosstermDriver;
+    #[cfg(all(not(feature = "backend-crossterm"), unix, feature = "backend-termion"))]
+    let mut driver = platform::TermionDriver::new();
+    platform::run(&mut driver, &mut state)?;
+
+    if let Err(e) = state.save_session() {
+        eprintln!("Cannot save session: {}", e);
+    }
 
     Ok(())
 }
+
+/// Source reading the data directory at `url`, with copies in the user cache directory
+#[cfg(feature = "online")]
+fn remote_source(url: &str) -> Result<Box<dyn DataSource>, Box<dyn std::error::Error>> {
+    let root = paths::select_dir(Artifact::Cache, Path::new(DATA_DIR), true, |name| std::env::var(name).ok());
+    Ok(Box::new(remote::HttpSource::new(url, &root.join("remote"))?))
+}
+
+#[cfg(not(feature = "online"))]
+fn remote_source(url: &str) -> Result<Box<dyn DataSource>, Box<dyn std::error::Error>> {
+    Err(format!("{} is a URL, but this build can't read from a server (build with --features online)", url).into())
+}
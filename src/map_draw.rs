@@ -1,10 +1,12 @@
 /// Provides map rendering view with geographic features and optional highlighting.
-use geo::{Geometry, MultiPolygon, Polygon};
-use geojson::GeoJson;
-use std::{collections::{HashMap, HashSet}, error::Error};
-use crate::data::DataCache;
-use ratatui::widgets::canvas::{Canvas, Line};
-use ratatui::{layout::Rect as TuiRect, Frame, style::Color};
+use geo::{Centroid, Contains, Coord, CoordsIter, LineString, MapCoords, MapCoordsInPlace, MultiPolygon, Point, Polygon, Simplify};
+use geojson::{GeoJson, PolygonType, Position};
+use std::{borrow::Cow, cell::OnceCell, f64::consts::{FRAC_PI_2, PI}, collections::{BTreeMap, BTreeSet, HashSet}, error::Error, path::Path, sync::{atomic::{AtomicU64, Ordering}, Arc}};
+use crate::{config::Projection as ProjectionKind, data::{country_key, keep_properties, stream_features, CountryHints, DataCache, GeoLevel, Properties, RenderHints}, text::truncate_to_width, theme::{continent_tint, Rgb, Symbols, UNICODE}};
+use unicode_width::UnicodeWidthStr;
+use ratatui::widgets::canvas::{Canvas, Line, Rectangle};
+use serde::Deserialize;
+use ratatui::{buffer::Buffer, layout::Rect as TuiRect, Frame, style::{Color, Style}, symbols::Marker, widgets::Widget};
 
 /// Calculates the absolute area of a polygon via the shoelace formula.
 fn poly_area(poly: &Polygon<f64>) -> f64 {
@@ -18,74 +20,1244 @@ fn poly_area(poly: &Polygon<f64>) -> f64 {
     (sum * 0.5).abs()
 }
 
-pub struct MapView {
+/// Share of a feature's total area in its largest polygon; 1 for a single polygon
+pub fn largest_share(mp: &MultiPolygon<f64>) -> f64 {
+    let areas: Vec<f64> = mp.0.iter().map(poly_area).collect();
+    let total: f64 = areas.iter().sum();
+    if total > 0.0 { areas.iter().cloned().fold(0.0, f64::max) / total } else { 1.0 }
+}
+
+/// A feature is an archipelago when no polygon holds more than this share of its area
+const ARCHIPELAGO_SHARE: f64 = 0.40;
+
+/// Polygons smaller than this share of a feature's largest one are dropped as specks
+const SPECK_SHARE: f64 = 0.20;
+
+/// `items` largest first by total area, ties in their given order: features are drawn in
+/// this order, so a microstate (Vatican, Monaco) comes after the neighbour around it and
+/// isn't lost under its outline
+fn by_area(items: Vec<(String, MultiPolygon<f64>)>) -> Vec<(String, MultiPolygon<f64>)> {
+    let mut sized: Vec<(f64, (String, MultiPolygon<f64>))> =
+        items.into_iter().map(|item| (item.1.0.iter().map(poly_area).sum(), item)).collect();
+    sized.sort_by(|a, b| b.0.total_cmp(&a.0));
+    sized.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Checks that a ring, as written in the file, is a GeoJSON linear ring: at least 4
+/// positions (a closed triangle), the last one repeating the first, and at least 3
+/// distinct points, so it encloses something. Positions are counted as written, before
+/// conversion closes an open ring.
+fn is_valid_ring(ring: &[Position]) -> bool {
+    if ring.len() < 4 || ring.first() != ring.last() {
+        return false;
+    }
+    let mut pts: Vec<&Position> = ring.iter().collect();
+    pts.sort_by(|a, b| {
+        let by_coord = a.iter().zip(b.iter()).map(|(x, y)| x.total_cmp(y)).find(|o| o.is_ne());
+        by_coord.unwrap_or_else(|| a.len().cmp(&b.len()))
+    });
+    pts.dedup();
+    pts.len() >= 3
+}
+
+/// Drops polygons whose exterior ring is degenerate, and degenerate holes.
+fn drop_degenerate(polygons: Vec<PolygonType>) -> Vec<PolygonType> {
+    polygons
+        .into_iter()
+        .filter(|rings| rings.first().is_some_and(|exterior| is_valid_ring(exterior)))
+        .map(|rings| {
+            let mut rings = rings.into_iter();
+            let exterior = rings.next();
+            exterior.into_iter().chain(rings.filter(|hole| is_valid_ring(hole))).collect()
+        })
+        .collect()
+}
+
+/// Orders continents west→east by the centroid longitude of their member countries' geometry.
+/// Continents without any geometry in `items` are left out; ties are broken by name.
+fn continent_order(
+    items: &[(String, MultiPolygon<f64>)],
+    continents: &BTreeMap<String, HashSet<String>>,
+) -> Vec<String> {
+    let mut centroids: Vec<(String, f64)> = continents
+        .iter()
+        .filter_map(|(continent, members)| {
+            let polys: Vec<Polygon<f64>> = items
+                .iter()
+                .filter(|(name, _)| members.contains(&country_key(name)))
+                .flat_map(|(_, mp)| mp.0.iter().cloned())
+                .collect();
+            MultiPolygon(polys).centroid().map(|c| (continent.clone(), c.x()))
+        })
+        .collect();
+    centroids.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    centroids.into_iter().map(|(name, _)| name).collect()
+}
+
+/// Whether a feature lies wholly outside LAT_BAND or belongs to no continent list.
+/// Without any continent lists, only the latitude test applies.
+fn is_outlying(name: &str, mp: &MultiPolygon<f64>, continents: &BTreeMap<String, HashSet<String>>) -> bool {
+    let unlisted = !continents.is_empty() && !continents.values().any(|members| members.contains(&country_key(name)));
+    let outside_band = bounds([mp]).is_some_and(|(_, [miny, maxy])| maxy < LAT_BAND[0] || miny > LAT_BAND[1]);
+    unlisted || outside_band
+}
+
+/// Bounds ([minx, maxx], [miny, maxy]) of the given geometries, padded when flat along
+/// an axis so a canvas still has some extent; None when there are no coordinates.
+fn bounds<'a>(geoms: impl IntoIterator<Item = &'a MultiPolygon<f64>>) -> Option<([f64; 2], [f64; 2])> {
+    let (mut minx, mut miny, mut maxx, mut maxy) =
+        (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
+    for mp in geoms {
+        for poly in &mp.0 {
+            for coord in poly.exterior().0.iter()
+                .chain(poly.interiors().iter().flat_map(|r| r.0.iter()))
+            {
+                minx = minx.min(coord.x);
+                miny = miny.min(coord.y);
+                maxx = maxx.max(coord.x);
+                maxy = maxy.max(coord.y);
+            }
+        }
+    }
+
+    if ![minx, miny, maxx, maxy].iter().all(|v| v.is_finite()) {
+        return None;
+    }
+    // A single sliver can be flat along one axis
+    if maxx - minx <= 0.0 { minx -= 0.5; maxx += 0.5; }
+    if maxy - miny <= 0.0 { miny -= 0.5; maxy += 0.5; }
+    Some(([minx, maxx], [miny, maxy]))
+}
+
+/// Longitude `x` in the frame running east from the prime meridian (0..360) when `east`,
+/// otherwise in the usual -180..180
+fn in_frame(x: f64, east: bool) -> f64 {
+    match east {
+        true if x < 0.0 => x + 360.0,
+        false if x > 180.0 => x - 360.0,
+        _ => x,
+    }
+}
+
+/// Width in 0..360, against that in -180..180, below which a map is taken to cross the
+/// antimeridian
+const ANTIMERIDIAN_SHARE: f64 = 0.75;
+
+/// Whether `geoms` straddle the antimeridian rather than span the globe: their longitudes
+/// reach across most of -180..180 but leave a wide empty gap around the prime meridian,
+/// so in 0..360 they take up less than ANTIMERIDIAN_SHARE of that width. Russia, Fiji and
+/// the Asia and Oceania maps do; the world and Antarctica, which go all the way round, don't.
+fn crosses_antimeridian<'a>(geoms: impl IntoIterator<Item = &'a MultiPolygon<f64>>) -> bool {
+    let (mut west, mut east) = ([f64::INFINITY, f64::NEG_INFINITY], [f64::INFINITY, f64::NEG_INFINITY]);
+    for coord in geoms.into_iter().flat_map(|mp| mp.coords_iter()) {
+        let shifted = in_frame(coord.x, true);
+        west = [west[0].min(coord.x), west[1].max(coord.x)];
+        east = [east[0].min(shifted), east[1].max(shifted)];
+    }
+    let (span, shifted_span) = (west[1] - west[0], east[1] - east[0]);
+    span > 180.0 && shifted_span < span * ANTIMERIDIAN_SHARE
+}
+
+/// Move every longitude of `mp` into the 0..360 frame when `east`, else into -180..180
+fn shift_longitudes(mp: &mut MultiPolygon<f64>, east: bool) {
+    mp.map_coords_in_place(|c| geo::Coord { x: in_frame(c.x, east), y: c.y });
+}
+
+/// Move the longitudes `hints` give (label anchor, framing) into the frame of `east`
+fn shift_hints<'a>(hints: impl IntoIterator<Item = &'a mut CountryHints>, east: bool) {
+    for hint in hints {
+        if let Some([lon, _]) = &mut hint.label {
+            *lon = in_frame(*lon, east);
+        }
+        if let Some([[west, east_edge], _]) = &mut hint.bounds {
+            (*west, *east_edge) = (in_frame(*west, east), in_frame(*east_edge, east));
+        }
+    }
+}
+
+/// Uniform grid over the features' bounding boxes: each cell lists, in item order, the
+/// features whose box overlaps it, so a point lookup runs the exact polygon test only
+/// on those instead of on every feature
+struct HitGrid {
+    origin: [f64; 2],   // lower-left corner of the grid
+    cell: [f64; 2],     // cell width and height in degrees
+    cols: usize,
+    rows: usize,
+    cells: Vec<Vec<usize>>, // row-major; indexes into the view's items
+    boxes: Vec<Option<([f64; 2], [f64; 2])>>, // bounding box of each item
+}
+
+impl HitGrid {
+    /// Most cells along either axis
+    const MAX_CELLS: usize = 64;
+
+    fn new(items: &[(String, MultiPolygon<f64>)]) -> Self {
+        let boxes: Vec<_> = items.iter().map(|(_, mp)| bounds([mp])).collect();
+        let ([minx, maxx], [miny, maxy]) =
+            bounds(items.iter().map(|(_, mp)| mp)).unwrap_or(([0.0, 1.0], [0.0, 1.0]));
+        // About two cells per feature along each axis keeps the lists short
+        let side = ((items.len() as f64).sqrt().ceil() as usize * 2).clamp(1, Self::MAX_CELLS);
+        let (cols, rows) = (side, side);
+        let cell = [(maxx - minx) / cols as f64, (maxy - miny) / rows as f64];
+        let mut grid = HitGrid { origin: [minx, miny], cell, cols, rows, cells: vec![Vec::new(); cols * rows], boxes };
+        for (i, b) in grid.boxes.iter().enumerate() {
+            let Some(([x0, x1], [y0, y1])) = *b else { continue };
+            let (c0, r0) = grid.cell_of(x0, y0);
+            let (c1, r1) = grid.cell_of(x1, y1);
+            for r in r0..=r1 {
+                for c in c0..=c1 {
+                    grid.cells[r * cols + c].push(i);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Cell holding the coordinate, clamped to the grid
+    fn cell_of(&self, x: f64, y: f64) -> (usize, usize) {
+        let col = ((x - self.origin[0]) / self.cell[0]).floor().max(0.0) as usize;
+        let row = ((y - self.origin[1]) / self.cell[1]).floor().max(0.0) as usize;
+        (col.min(self.cols - 1), row.min(self.rows - 1))
+    }
+
+    /// Items whose bounding box holds the coordinate, in item order
+    fn candidates(&self, x: f64, y: f64) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        let (col, row) = self.cell_of(x, y);
+        self.cells[row * self.cols + col].iter().copied().filter(move |&i| {
+            self.boxes[i].is_some_and(|([x0, x1], [y0, y1])| (x0..=x1).contains(&x) && (y0..=y1).contains(&y))
+        })
+    }
+}
+
+/// Corner of the map block an inset is placed in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Pick the corner for an inset spanning `frac` (width, height) of a map showing
+/// `x_bounds` x `y_bounds`: the one whose data region overlaps the `highlight` box least.
+/// Ties, including no highlight at all, prefer bottom-right, bottom-left, top-right, top-left.
+pub fn inset_corner(
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+    highlight: Option<([f64; 2], [f64; 2])>,
+    frac: (f64, f64),
+) -> Corner {
+    let w = (x_bounds[1] - x_bounds[0]) * frac.0;
+    let h = (y_bounds[1] - y_bounds[0]) * frac.1;
+    let overlap = |corner: Corner| {
+        let Some((hx, hy)) = highlight else { return 0.0 };
+        let rx = match corner {
+            Corner::TopLeft | Corner::BottomLeft => [x_bounds[0], x_bounds[0] + w],
+            Corner::TopRight | Corner::BottomRight => [x_bounds[1] - w, x_bounds[1]],
+        };
+        let ry = match corner {
+            Corner::BottomLeft | Corner::BottomRight => [y_bounds[0], y_bounds[0] + h],
+            Corner::TopLeft | Corner::TopRight => [y_bounds[1] - h, y_bounds[1]],
+        };
+        let ox = (rx[1].min(hx[1]) - rx[0].max(hx[0])).max(0.0);
+        let oy = (ry[1].min(hy[1]) - ry[0].max(hy[0])).max(0.0);
+        ox * oy
+    };
+    [Corner::BottomRight, Corner::BottomLeft, Corner::TopRight, Corner::TopLeft]
+        .into_iter()
+        .min_by(|&a, &b| overlap(a).total_cmp(&overlap(b)))
+        .unwrap_or(Corner::BottomRight)
+}
+
+/// Parts of `viewport` inside `world`, as ([minx, maxx], [miny, maxy]); none when they
+/// don't meet. The viewport of a map shifted into 0..360 (one crossing the antimeridian)
+/// is brought back into -180..180, in two parts where it crosses the antimeridian.
+pub fn viewport_rects(
+    viewport: ([f64; 2], [f64; 2]),
+    world: ([f64; 2], [f64; 2]),
+) -> Vec<([f64; 2], [f64; 2])> {
+    let y = [viewport.1[0].max(world.1[0]), viewport.1[1].min(world.1[1])];
+    [0.0, -360.0, 360.0]
+        .into_iter()
+        .filter_map(|shift| {
+            let x = [(viewport.0[0] + shift).max(world.0[0]), (viewport.0[1] + shift).min(world.0[1])];
+            // A shifted copy only touching the edge of the world is no part of it
+            let meets = if shift == 0.0 { x[0] <= x[1] } else { x[0] < x[1] };
+            (meets && y[0] <= y[1]).then_some((x, y))
+        })
+        .collect()
+}
+
+/// Highlights covering fewer terminal cells than this get a box drawn around them
+pub const MIN_VISIBLE_CELLS: f64 = 4.0;
+
+/// Height of a terminal cell against its width, in most fonts
+pub const CELL_ASPECT: f64 = 2.0;
+
+/// Bounds to give the canvas so `viewport` drawn into `inner` (the map inside its borders)
+/// comes out under `projection`. Corrected, a degree of longitude counts as the cosine of
+/// the middle latitude of a degree of latitude, and the shorter side of the viewport is
+/// widened around its middle until the box has the shape of the cells it fills; the
+/// viewport stays whole in view. Equirectangular leaves it as it is. For Robinson and
+/// Mollweide `viewport` is already projected (see `MapView::canvas_bounds`) and only
+/// widened to the shape of the cells.
+pub fn projected_bounds(
+    projection: ProjectionKind,
+    (x_bounds, y_bounds): ([f64; 2], [f64; 2]),
+    inner: TuiRect,
+) -> ([f64; 2], [f64; 2]) {
+    let span_x = x_bounds[1] - x_bounds[0];
+    let span_y = y_bounds[1] - y_bounds[0];
+    if projection == ProjectionKind::Equirectangular || inner.width == 0 || inner.height == 0 || span_x <= 0.0 || span_y <= 0.0 {
+        return (x_bounds, y_bounds);
+    }
+    // Near the poles the cosine goes to zero; stop where the maps' own bounds stop
+    let mid_lat = ((y_bounds[0] + y_bounds[1]) / 2.0).clamp(-85.0, 85.0);
+    let shrink = match projection.bends() {
+        true => 1.0,
+        false => mid_lat.to_radians().cos(),
+    };
+    let cells = inner.width as f64 / (inner.height as f64 * CELL_ASPECT);
+    let widen = |[lo, hi]: [f64; 2], span: f64| {
+        let mid = (lo + hi) / 2.0;
+        [mid - span / 2.0, mid + span / 2.0]
+    };
+    if span_x * shrink / span_y > cells {
+        (x_bounds, widen(y_bounds, span_x * shrink / cells))
+    } else {
+        (widen(x_bounds, span_y * cells / shrink), y_bounds)
+    }
+}
+
+/// Robinson's table every 5° of latitude from the equator: the length of the parallel
+/// against the equator's, and its distance from the equator against the pole's
+const ROBINSON: [(f64, f64); 19] = [
+    (1.0000, 0.0000), (0.9986, 0.0620), (0.9954, 0.1240), (0.9900, 0.1860), (0.9822, 0.2480),
+    (0.9730, 0.3100), (0.9600, 0.3720), (0.9427, 0.4340), (0.9216, 0.4958), (0.8962, 0.5571),
+    (0.8679, 0.6176), (0.8350, 0.6769), (0.7986, 0.7346), (0.7597, 0.7903), (0.7186, 0.8435),
+    (0.6732, 0.8936), (0.6213, 0.9394), (0.5722, 0.9761), (0.5322, 1.0000),
+];
+
+/// Distance of Robinson's poles from the equator, in degrees of the equator
+const ROBINSON_POLE: f64 = 1.3523 / 0.8487 * 180.0 / PI;
+
+/// Robinson's table at `lat`, interpolated between its rows
+fn robinson_row(lat: f64) -> (f64, f64) {
+    let at = lat.abs().min(90.0) / 5.0;
+    let i = (at.floor() as usize).min(ROBINSON.len() - 2);
+    let t = at - i as f64;
+    let ((x0, y0), (x1, y1)) = (ROBINSON[i], ROBINSON[i + 1]);
+    (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+}
+
+/// Mollweide's auxiliary angle for `lat`, in radians: the θ with 2θ + sin 2θ = π sin φ,
+/// found by Newton's method
+fn mollweide_theta(lat: f64) -> f64 {
+    let phi = lat.clamp(-90.0, 90.0).to_radians();
+    let target = PI * phi.sin();
+    let mut theta = phi;
+    for _ in 0..20 {
+        let slope = 2.0 + 2.0 * (2.0 * theta).cos();
+        // At the poles the slope vanishes, and θ is the pole itself
+        if slope < 1e-9 {
+            break;
+        }
+        let step = (2.0 * theta + (2.0 * theta).sin() - target) / slope;
+        theta = (theta - step).clamp(-FRAC_PI_2, FRAC_PI_2);
+        if step.abs() < 1e-10 {
+            break;
+        }
+    }
+    theta
+}
+
+/// Where the points of the globe land on the canvas. x keeps the equator's degrees, so it
+/// runs over ±180 around the central meridian like longitude, and y about ±90.
+pub trait Projection {
+    /// Canvas point of `(lon, lat)`
+    fn project(&self, lon: f64, lat: f64) -> (f64, f64);
+    /// The `(lon, lat)` that `project` takes to `(x, y)`; None off the projected globe
+    fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)>;
+}
+
+/// Degrees straight to the canvas; the corrected projection only fits the bounds to the
+/// area on top of it (see `projected_bounds`)
+pub struct Equirectangular;
+
+impl Projection for Equirectangular {
+    fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        (lon, lat)
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        Some((x, y))
+    }
+}
+
+/// Robinson's table of parallel lengths and heights, around the meridian `central`
+pub struct Robinson {
+    pub central: f64,
+}
+
+impl Projection for Robinson {
+    fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let (length, height) = robinson_row(lat);
+        (self.central + (lon - self.central) * length, (ROBINSON_POLE * height).copysign(lat))
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        let height = y.abs() / ROBINSON_POLE;
+        if height > 1.0 {
+            return None;
+        }
+        let i = ROBINSON.windows(2).position(|rows| height <= rows[1].1).unwrap_or(ROBINSON.len() - 2);
+        let ((x0, y0), (x1, y1)) = (ROBINSON[i], ROBINSON[i + 1]);
+        let t = (height - y0) / (y1 - y0);
+        unbend(self.central, x, x0 + (x1 - x0) * t, (5.0 * (i as f64 + t)).copysign(y))
+    }
+}
+
+/// Mollweide's equal-area ellipse around the meridian `central`
+pub struct Mollweide {
+    pub central: f64,
+}
+
+impl Projection for Mollweide {
+    fn project(&self, lon: f64, lat: f64) -> (f64, f64) {
+        let theta = mollweide_theta(lat);
+        (self.central + (lon - self.central) * theta.cos(), 90.0 * theta.sin())
+    }
+
+    fn unproject(&self, x: f64, y: f64) -> Option<(f64, f64)> {
+        if y.abs() > 90.0 {
+            return None;
+        }
+        let theta = (y / 90.0).asin();
+        let lat = ((2.0 * theta + (2.0 * theta).sin()) / PI).clamp(-1.0, 1.0).asin().to_degrees();
+        unbend(self.central, x, theta.cos(), lat)
+    }
+}
+
+/// Longitude and latitude back from `x` on a parallel `length` times the equator's;
+/// None past the edge meridians
+fn unbend(central: f64, x: f64, length: f64, lat: f64) -> Option<(f64, f64)> {
+    let lon = central + (x - central) / length;
+    // A hair past the edge meridian is still on it, whatever the rounding
+    (length > 0.0 && (lon - central).abs() <= 180.0 + 1e-9).then(|| (lon.clamp(central - 180.0, central + 180.0), lat))
+}
+
+/// The projection `kind` draws with, around the meridian `central`
+pub fn projection(kind: ProjectionKind, central: f64) -> Box<dyn Projection> {
+    match kind {
+        ProjectionKind::Robinson => Box::new(Robinson { central }),
+        ProjectionKind::Mollweide => Box::new(Mollweide { central }),
+        ProjectionKind::Corrected | ProjectionKind::Equirectangular => Box::new(Equirectangular),
+    }
+}
+
+/// Terminal cells (columns × rows) the data box `bounds` covers when `viewport` is drawn
+/// into `area`, inside the map block's borders
+pub fn cells_covered(
+    bounds: ([f64; 2], [f64; 2]),
+    viewport: ([f64; 2], [f64; 2]),
+    area: TuiRect,
+) -> f64 {
+    let (cols, rows) = (area.width.saturating_sub(2) as f64, area.height.saturating_sub(2) as f64);
+    let span_x = (viewport.0[1] - viewport.0[0]).max(f64::EPSILON);
+    let span_y = (viewport.1[1] - viewport.1[0]).max(f64::EPSILON);
+    let w = (bounds.0[1] - bounds.0[0]) / span_x * cols;
+    let h = (bounds.1[1] - bounds.1[0]) / span_y * rows;
+    w * h
+}
+
+/// Whether the data box `bounds` is too small to notice in `viewport` drawn into `area`
+pub fn too_small_to_see(
+    bounds: ([f64; 2], [f64; 2]),
+    viewport: ([f64; 2], [f64; 2]),
+    area: TuiRect,
+) -> bool {
+    cells_covered(bounds, viewport, area) < MIN_VISIBLE_CELLS
+}
+
+/// Horizontal spans inside `poly` on `rows` scanlines evenly spread over `y_bounds`,
+/// each (latitude, west, east). Crossings with the exterior and the holes are paired
+/// even-odd, so lakes and enclaves stay open.
+pub fn scanline_fill(poly: &Polygon<f64>, y_bounds: [f64; 2], rows: usize) -> Vec<(f64, f64, f64)> {
+    let step = (y_bounds[1] - y_bounds[0]) / rows.max(1) as f64;
+    let rings: Vec<&LineString<f64>> = std::iter::once(poly.exterior()).chain(poly.interiors()).collect();
+    let (lo, hi) = poly.exterior().0.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), c| (lo.min(c.y), hi.max(c.y)));
+    if step <= 0.0 || lo > hi {
+        return Vec::new();
+    }
+    let first = ((lo.max(y_bounds[0]) - y_bounds[0]) / step).floor() as usize;
+    let last = ((hi.min(y_bounds[1]) - y_bounds[0]) / step).ceil() as usize;
+    let mut spans = Vec::new();
+    for row in first..last.min(rows) {
+        let y = y_bounds[0] + (row as f64 + 0.5) * step;
+        let mut xs: Vec<f64> = rings
+            .iter()
+            .flat_map(|ring| ring.0.windows(2))
+            // Half-open in y, so a vertex on the scanline counts once
+            .filter(|w| (w[0].y <= y) != (w[1].y <= y))
+            .map(|w| w[0].x + (y - w[0].y) / (w[1].y - w[0].y) * (w[1].x - w[0].x))
+            .collect();
+        xs.sort_by(f64::total_cmp);
+        spans.extend(xs.chunks_exact(2).map(|pair| (y, pair[0], pair[1])));
+    }
+    spans
+}
+
+/// A name the map would like to print: the cells it covers when centered on its
+/// feature, and the feature's size, which decides who wins a contested spot
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LabelCandidate {
+    pub rect: TuiRect,
+    pub weight: f64,
+}
+
+/// Rows a label may move from its desired one, tried in this order
+const LABEL_OFFSETS: [i32; 5] = [0, -1, 1, -2, 2];
+
+/// Greedy label placement: largest features first, each label at its desired row or the
+/// nearest free one within LABEL_OFFSETS, inside `area` and at least a column apart from
+/// labels already placed; labels with no free spot are left out.
+/// Returns (candidate index, rectangle) of the placed labels.
+pub fn place_labels(candidates: &[LabelCandidate], area: TuiRect) -> Vec<(usize, TuiRect)> {
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by(|&a, &b| candidates[b].weight.total_cmp(&candidates[a].weight));
+    let mut placed: Vec<(usize, TuiRect)> = Vec::new();
+    for i in order {
+        let want = candidates[i].rect;
+        let spot = LABEL_OFFSETS.iter().find_map(|&dy| {
+            let y = u16::try_from(want.y as i32 + dy).ok()?;
+            let rect = TuiRect { y, ..want };
+            let inside = area.intersection(rect) == rect && !rect.is_empty();
+            // Widen by a column each side so neighbouring names don't run together
+            let padded = TuiRect { x: rect.x.saturating_sub(1), width: rect.width + 2, ..rect };
+            (inside && !placed.iter().any(|(_, p)| p.intersects(padded))).then_some(rect)
+        });
+        if let Some(rect) = spot {
+            placed.push((i, rect));
+        }
+    }
+    placed
+}
+
+/// Degrees between graticule lines, finest first
+const GRATICULE_STEPS: [f64; 7] = [1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 45.0];
+
+/// Most graticule lines across the view's width
+const GRATICULE_LINES: f64 = 13.0;
+
+/// Degrees between graticule lines on a view `span` degrees wide: the finest step that
+/// keeps to GRATICULE_LINES lines, so the world gets 30° and a small country 1–5°
+pub fn graticule_step(span: f64) -> f64 {
+    GRATICULE_STEPS.iter().copied().find(|&step| span / step <= GRATICULE_LINES).unwrap_or(90.0)
+}
+
+/// Multiples of `step` within `bounds` and the coordinates' `range` (-180..180 or 0..360
+/// for longitude, -90..90 for latitude), where graticule lines go
+pub fn graticule_lines(bounds: [f64; 2], range: [f64; 2], step: f64) -> Vec<f64> {
+    let first = (bounds[0].max(range[0]) / step).ceil() as i64;
+    let last = (bounds[1].min(range[1]) / step).floor() as i64;
+    (first..=last).map(|i| i as f64 * step).collect()
+}
+
+/// `value` degrees as a graticule label: `positive` or `negative` after it (N/S, E/W),
+/// nothing after 0
+pub fn degree_label(value: f64, positive: char, negative: char, degree: &str) -> String {
+    let side = match value {
+        v if v > 0.0 => positive.to_string(),
+        v if v < 0.0 => negative.to_string(),
+        _ => String::new(),
+    };
+    format!("{}{}{}", value.abs(), degree, side)
+}
+
+/// Low-detail world outline drawn in the mini-map inset
+pub struct MiniMap {
+    rings: Vec<LineString<f64>>,
+    x_bounds: [f64; 2],
+    y_bounds: [f64; 2],
+}
+
+impl MiniMap {
+    /// Draw the outline with a rectangle in the highlight color of `style` marking
+    /// `viewport` (data bounds of the main map) and, in yellow, one around `selection`
+    /// (the selected country's bounds), which shrinks to a dot for a country smaller than
+    /// the inset's dots. Either may be in the 0..360 longitudes of a map across the
+    /// antimeridian.
+    pub fn render<'a>(
+        &self,
+        f: &mut Frame<'a>,
+        area: TuiRect,
+        viewport: ([f64; 2], [f64; 2]),
+        selection: Option<([f64; 2], [f64; 2])>,
+        symbols: &Symbols,
+        style: MapStyle,
+    ) {
+        let world = (self.x_bounds, self.y_bounds);
+        let marker = viewport_rects(viewport, world);
+        let selected = selection.map(|bounds| viewport_rects(bounds, world)).unwrap_or_default();
+        let canvas = Canvas::default()
+            .block(
+                ratatui::widgets::Block::default()
+                    .borders(ratatui::widgets::Borders::ALL)
+                    .border_set(symbols.border),
+            )
+            .marker(symbols.marker)
+            .x_bounds(self.x_bounds)
+            .y_bounds(self.y_bounds)
+            .paint(|ctx| {
+                for ring in &self.rings {
+                    for window in ring.0.windows(2) {
+                        let (a, b) = (window[0], window[1]);
+                        ctx.draw(&Line { x1: a.x, y1: a.y, x2: b.x, y2: b.y, color: Color::DarkGray });
+                    }
+                }
+                for (parts, color) in [(&marker, style.highlight), (&selected, Color::Yellow)] {
+                    for (x, y) in parts {
+                        ctx.draw(&Rectangle {
+                            x: x[0],
+                            y: y[0],
+                            width: x[1] - x[0],
+                            height: y[1] - y[0],
+                            color,
+                        });
+                    }
+                }
+            });
+        f.render_widget(ratatui::widgets::Clear, area);
+        f.render_widget(canvas, area);
+    }
+}
+
+/// Reason in `MapView::skipped` of features with `"geometry": null`
+pub const NO_GEOMETRY: &str = "no geometry";
+
+/// Reason in `MapView::skipped` of features with no polygon left whole (see `is_valid_ring`)
+pub const DEGENERATE: &str = "degenerate geometry";
+
+/// Holes in a feature (lakes, enclaves such as Lesotho) under its border color
+const HOLE_COLOR: Color = Color::DarkGray;
+
+/// Graticule lines and their labels
+const GRATICULE_COLOR: Color = Color::DarkGray;
+
+/// Degrees of latitude between the points a meridian of the graticule is drawn through
+const MERIDIAN_STEP: f64 = 2.0;
+
+/// Default simplification tolerance, as a share of the larger side of the view's bounds
+pub const SIMPLIFY_EPSILON: f64 = 0.001;
+
+/// Fewest points a simplified ring keeps, the closing point included (a triangle)
+const MIN_RING_POINTS: usize = 4;
+
+/// Outline of `mp` to draw: each ring, holes included, simplified (Douglas-Peucker) with
+/// `tolerance` degrees, or left whole where that would keep fewer than `MIN_RING_POINTS`
+/// points
+fn simplify_outline(mp: &MultiPolygon<f64>, tolerance: f64) -> MultiPolygon<f64> {
+    let simplify_ring = |ring: &LineString<f64>| {
+        let simple = ring.simplify(&tolerance);
+        if simple.0.len() < MIN_RING_POINTS { ring.clone() } else { simple }
+    };
+    let polys = mp.0
+        .iter()
+        .map(|poly| Polygon::new(simplify_ring(poly.exterior()), poly.interiors().iter().map(simplify_ring).collect()))
+        .collect();
+    MultiPolygon(polys)
+}
+
+/// Line segments of the rings of `mp`, holes included
+fn segment_count(mp: &MultiPolygon<f64>) -> usize {
+    mp.0
+        .iter()
+        .flat_map(|poly| std::iter::once(poly.exterior()).chain(poly.interiors()))
+        .map(|ring| ring.0.len().saturating_sub(1))
+        .sum()
+}
+
+/// Closest zoom `MapView::zoom` goes to, relative to the computed bounds
+pub const MAX_ZOOM: f64 = 64.0;
+
+/// Room left around the selection on each side when auto-zoom frames it, as a share of
+/// its width and height
+const AUTO_ZOOM_PADDING: f64 = 0.2;
+
+/// Latitudes the automatic bounds keep to; a feature wholly outside is outlying
+const LAT_BAND: [f64; 2] = [-60.0, 85.0];
+
+/// How to treat outlying features: Antarctica and anything else wholly outside
+/// LAT_BAND, or features in no continent list (e.g. French Southern and Antarctic Lands)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutlierPolicy {
+    /// Drawn dimmed and left out of the automatic bounds
+    #[default]
+    Dim,
+    /// Left out of the view entirely
+    Hide,
+    /// Drawn and bounded like every other feature
+    Include,
+}
+
+/// What a highlight name refers to in a view
+enum Highlight<'a> {
+    Continent(&'a HashSet<String>), // canonical keys of all member countries
+    Country(String),                // canonical key of a single country
+}
+
+impl Highlight<'_> {
+    /// Whether the feature called `name` is drawn highlighted
+    fn contains(&self, name: &str) -> bool {
+        match self {
+            Highlight::Continent(members) => members.contains(&country_key(name)),
+            Highlight::Country(key) => &country_key(name) == key,
+        }
+    }
+}
+
+/// Convert one feature's geometry and drop its small islands, adding it to `items`,
+/// or to `skipped` when it has no geometry or nothing drawable is left; non-polygon
+/// geometry is ignored. Archipelagos keep every island, unless `hints` say otherwise.
+fn add_feature(
+    items: &mut Vec<(String, MultiPolygon<f64>)>,
+    skipped: &mut Vec<(String, &'static str)>,
+    hints: &RenderHints,
+    name: String,
+    gj: Option<geojson::Geometry>,
+) -> Result<(), Box<dyn Error>> {
+    // Placeholder entries for territories without mapped boundaries
+    let Some(gj) = gj else {
+        skipped.push((name, NO_GEOMETRY));
+        return Ok(());
+    };
+    let polygons = match gj.value {
+        geojson::Value::Polygon(rings) => drop_degenerate(vec![rings]),
+        geojson::Value::MultiPolygon(polygons) => drop_degenerate(polygons),
+        _ => return Ok(()),
+    };
+    if polygons.is_empty() {
+        skipped.push((name, DEGENERATE));
+        return Ok(());
+    }
+    let mut mp: MultiPolygon<f64> = geojson::Value::MultiPolygon(polygons).try_into()?;
+
+    // Filter out small holes by area threshold; an inherently fragmented country (the
+    // Maldives, Indonesia) would be left with a few of its islands
+    let archipelago = hints.keep_islands(&name).unwrap_or_else(|| largest_share(&mp) <= ARCHIPELAGO_SHARE);
+    if mp.0.len() > 1 && !archipelago {
+        let orig: Vec<Polygon<f64>> = mp.0.clone();
+        let areas: Vec<f64> = orig.iter().map(poly_area).collect();
+        let max_area = areas.iter().cloned().fold(f64::NAN, f64::max);
+        let threshold = max_area * SPECK_SHARE;
+        let filtered: Vec<Polygon<f64>> = orig.into_iter()
+            .zip(areas)
+            .filter(|(_, area)| *area >= threshold)
+            .map(|(poly, _)| poly)
+            .collect();
+        if !filtered.is_empty() {
+            mp = MultiPolygon(filtered);
+        }
+    }
+
+    items.push((name, mp));
+    Ok(())
+}
+
+/// Countries of the continent lists compared with the features of a map
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CountryCount {
+    pub countries: usize,      // distinct countries in the lists
+    pub continents: usize,     // lists with at least one country
+    pub features: usize,       // features on the map
+    pub unmapped: Vec<String>, // listed countries without a feature on the map, in name order
+    pub unlisted: Vec<String>, // features in none of the lists, in name order
+}
+
+/// Count the countries in `lists` (continent -> countries) and match them by
+/// `country_key` against the map's feature names, noting the ones on only one side.
+/// A country listed under two continents counts once.
+pub fn count_countries<'a>(
+    lists: &BTreeMap<String, BTreeSet<String>>,
+    features: impl IntoIterator<Item = &'a str>,
+) -> CountryCount {
+    let mut listed: BTreeMap<String, &str> = BTreeMap::new();
+    for name in lists.values().flatten() {
+        listed.entry(country_key(name)).or_insert(name);
+    }
+    let mut mapped = HashSet::new();
+    let mut features_seen = 0;
+    let mut unlisted = Vec::new();
+    for name in features {
+        features_seen += 1;
+        let key = country_key(name);
+        if !listed.contains_key(&key) {
+            unlisted.push(name.to_string());
+        }
+        mapped.insert(key);
+    }
+    unlisted.sort();
+    unlisted.dedup();
+    let mut unmapped: Vec<String> =
+        listed.iter().filter(|(key, _)| !mapped.contains(*key)).map(|(_, name)| name.to_string()).collect();
+    unmapped.sort();
+    CountryCount {
+        countries: listed.len(),
+        continents: lists.values().filter(|countries| !countries.is_empty()).count(),
+        features: features_seen,
+        unmapped,
+        unlisted,
+    }
+}
+
+/// Reason a feature left out by `OutlierPolicy::Hide` is recorded as skipped
+const HIDDEN: &str = "hidden by config";
+
+/// One continent's features cut out of the world map, to build the continent's view from
+/// without reading its own file
+#[derive(Clone, Debug)]
+pub struct MapShard {
     items: Vec<(String, MultiPolygon<f64>)>,
+    skipped: Vec<(String, &'static str)>,
+    properties: BTreeMap<String, Properties>,
+}
+
+/// A revision no map view has had yet
+fn next_revision() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+pub struct MapView {
+    items: Arc<Vec<(String, MultiPolygon<f64>)>>, // shared with the other views of the same file until changed
+    skipped: Vec<(String, &'static str)>,
+    hints: BTreeMap<String, CountryHints>, // render hints of the features shown, by canonical key
+    properties: BTreeMap<String, Properties>, // kept properties by canonical key, skipped features' too
     x_bounds: [f64; 2],
     y_bounds: [f64; 2],
-    continents: HashMap<String, HashSet<String>>,
+    continents: BTreeMap<String, HashSet<String>>, // continent -> canonical country keys
+    continent_order: OnceCell<Vec<String>>,
+    hit_grid: OnceCell<HitGrid>, // index for feature_at, built on the first lookup
+    outlines: OnceCell<Vec<MultiPolygon<f64>>>, // items simplified for the bounds, as drawn; on first use
+    simplify_epsilon: f64, // simplification tolerance as a share of the bounds' larger side; 0 draws every point
+    focus: Option<String>, // canonical key of the feature a focused view zooms onto
+    outlying: HashSet<String>, // canonical keys of features drawn dimmed, outside the bounds
+    home: Option<([f64; 2], [f64; 2])>, // bounds as computed, while zoomed or panned away from them
+    east: bool, // longitudes run 0..360 instead of -180..180: the map crosses the antimeridian
+    revision: u64, // new whenever what is drawn changes, so a cached rendering of another isn't reused
 }
 
 impl MapView {
-    /// Initialize view from GeoJSON and load continent mappings.
+    /// Initialize view from GeoJSON and load continent mappings; outlying features are dimmed.
     pub fn new(raw: GeoJson, data_cache: &mut DataCache) -> Result<Self, Box<dyn Error>> {
-        let mut items = Vec::new();
+        Self::with_outliers(raw, data_cache, OutlierPolicy::default())
+    }
 
+    /// Initialize view from GeoJSON, treating outlying features according to `policy`.
+    pub fn with_outliers(raw: GeoJson, data_cache: &mut DataCache, policy: OutlierPolicy) -> Result<Self, Box<dyn Error>> {
+        let mappings = data_cache.load_continent_mappings().unwrap_or_default();
+        Self::build(raw, mappings, data_cache.render_hints(), policy)
+    }
+
+    /// Initialize view from GeoJSON and continent lists and hints already loaded, so it
+    /// can be built away from the `DataCache`, e.g. on a worker thread
+    pub fn build(
+        raw: GeoJson,
+        mappings: BTreeMap<String, BTreeSet<String>>,
+        hints: &RenderHints,
+        policy: OutlierPolicy,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut items = Vec::new();
+        let mut skipped = Vec::new();
+        let mut properties = BTreeMap::new();
         if let GeoJson::FeatureCollection(fc) = raw {
             for feature in fc.features {
-                let name = feature
-                    .properties
-                    .as_ref()
-                    .and_then(|p| p.get("ADMIN").and_then(|v| v.as_str()))
-                    .unwrap_or("")
-                    .to_string();
-
-                if let Some(gj) = feature.geometry {
-                    let geom: Geometry<f64> = gj.value.try_into()?;
-                    let mut mp = match geom {
-                        Geometry::Polygon(p) => p.into(),
-                        Geometry::MultiPolygon(m) => m,
-                        _ => continue,
-                    };
-
-                    // Filter out small holes by area threshold
-                    if mp.0.len() > 1 {
-                        let orig: Vec<Polygon<f64>> = mp.0.clone();
-                        let areas: Vec<f64> = orig.iter().map(poly_area).collect();
-                        let max_area = areas.iter().cloned().fold(0./0., f64::max);
-                        let threshold = max_area * 0.20;
-                        let filtered: Vec<Polygon<f64>> = orig.into_iter()
-                            .zip(areas.into_iter())
-                            .filter(|(_, area)| *area >= threshold)
-                            .map(|(poly, _)| poly)
-                            .collect();
-                        if !filtered.is_empty() {
-                            mp = MultiPolygon(filtered);
-                        }
-                    }
+                let props = keep_properties(feature.properties.unwrap_or_default());
+                let name = props.get("ADMIN").and_then(|v| v.as_str()).unwrap_or("").to_string();
+                if !props.is_empty() {
+                    properties.insert(country_key(&name), props);
+                }
+                add_feature(&mut items, &mut skipped, hints, name, feature.geometry)?;
+            }
+        }
+        Self::from_items(items, skipped, properties, mappings, hints, policy)
+    }
+
+    /// Like `build`, but reading the file at `path` one feature at a time: each feature is
+    /// converted and simplified before the next is parsed, so peak memory stays near the
+    /// size of one feature rather than of the whole collection
+    pub fn build_streamed(
+        path: &Path,
+        mappings: BTreeMap<String, BTreeSet<String>>,
+        hints: &RenderHints,
+        policy: OutlierPolicy,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut items = Vec::new();
+        let mut skipped = Vec::new();
+        let mut properties = BTreeMap::new();
+        stream_features(path, |name, props, gj| {
+            if !props.is_empty() {
+                properties.insert(country_key(&name), props);
+            }
+            add_feature(&mut items, &mut skipped, hints, name, gj)
+        })?;
+        Self::from_items(items, skipped, properties, mappings, hints, policy)
+    }
+
+    /// View of the map for `level` and `key`: from `data_cache` when it was built before or,
+    /// for a continent, cut out of the world map; otherwise read from its file (streamed in
+    /// low-memory mode). Either way it is kept there for the next time.
+    pub fn load(
+        data_cache: &mut DataCache,
+        level: &GeoLevel,
+        key: &str,
+        policy: OutlierPolicy,
+    ) -> Result<Self, Box<dyn Error>> {
+        if let Some(view) = data_cache.cached_map(level, key, policy) {
+            return Ok(view);
+        }
+        let view = if *level == GeoLevel::Continent
+            && let Some(shard) = data_cache.shard(key)
+        {
+            let mappings = data_cache.load_continent_mappings().unwrap_or_default();
+            Self::from_shard(shard, mappings, data_cache.render_hints(), policy)?
+        } else if data_cache.low_mem() {
+            let path = data_cache.geojson_path(level, key);
+            if !path.exists() {
+                return Err(format!("{} not found", path.display()).into());
+            }
+            let mappings = data_cache.load_continent_mappings().unwrap_or_default();
+            Self::build_streamed(&path, mappings, data_cache.render_hints(), policy)?
+        } else {
+            let raw = data_cache.load_geojson(level, key)?;
+            Self::with_outliers(raw, data_cache, policy)?
+        };
+        data_cache.keep_map(level, key, policy, &view);
+        Ok(view)
+    }
+
+    /// Finish a view from converted features, sorted largest first (see `by_area`)
+    fn from_items(
+        mut items: Vec<(String, MultiPolygon<f64>)>,
+        mut skipped: Vec<(String, &'static str)>,
+        properties: BTreeMap<String, Properties>,
+        mappings: BTreeMap<String, BTreeSet<String>>,
+        all_hints: &RenderHints,
+        policy: OutlierPolicy,
+    ) -> Result<Self, Box<dyn Error>> {
+        items = by_area(items);
+        let mut hints: BTreeMap<String, CountryHints> = items
+            .iter()
+            .filter_map(|(name, _)| all_hints.country(name).map(|h| (country_key(name), h.clone())))
+            .collect();
+
+        // Continent membership by canonical country key, so spelling variants still match
+        let continents: BTreeMap<String, HashSet<String>> = mappings
+            .into_iter()
+            .map(|(continent, members)| (continent, members.iter().map(|m| country_key(m)).collect()))
+            .collect();
+
+        // Outlying features; a view made only of them (Antarctica's own map) has none
+        let mut outlying: HashSet<String> = match policy {
+            OutlierPolicy::Include => HashSet::new(),
+            OutlierPolicy::Dim | OutlierPolicy::Hide => items
+                .iter()
+                .filter(|(name, mp)| is_outlying(name, mp, &continents))
+                .map(|(name, _)| country_key(name))
+                .collect(),
+        };
+        if items.iter().all(|(name, _)| outlying.contains(&country_key(name))) {
+            outlying.clear();
+        }
+        if policy == OutlierPolicy::Hide {
+            let (hidden, kept) = items.into_iter().partition(|(name, _)| outlying.contains(&country_key(name)));
+            items = kept;
+            skipped.extend(hidden.into_iter().map(|(name, _): (String, _)| (name, HIDDEN)));
+            outlying.clear();
+        }
 
-                    items.push((name, mp));
+        // Across the antimeridian, -180..180 would stretch the map over the whole globe:
+        // draw it east of the prime meridian instead, hints included
+        let east = crosses_antimeridian(
+            items.iter().filter(|(name, _)| !outlying.contains(&country_key(name))).map(|(_, mp)| mp),
+        );
+        if east {
+            for (_, mp) in &mut items {
+                shift_longitudes(mp, true);
+            }
+            shift_hints(hints.values_mut(), true);
+        }
+
+        // Determine spatial bounds of all features that aren't outlying
+        let (x_bounds, y_bounds) = bounds(
+            items.iter().filter(|(name, _)| !outlying.contains(&country_key(name))).map(|(_, mp)| mp),
+        )
+        .ok_or("no valid polygon geometry in GeoJSON")?;
+
+        Ok(Self {
+            items: Arc::new(items),
+            skipped,
+            hints,
+            properties,
+            x_bounds,
+            y_bounds,
+            continents,
+            continent_order: OnceCell::new(),
+            hit_grid: OnceCell::new(),
+            outlines: OnceCell::new(),
+            simplify_epsilon: SIMPLIFY_EPSILON,
+            focus: None,
+            outlying,
+            home: None,
+            east,
+            revision: next_revision(),
+        })
+    }
+
+    /// View of a continent from its `shard` of the world map, as `build` would make it
+    /// from the continent's own file
+    pub fn from_shard(
+        shard: MapShard,
+        mappings: BTreeMap<String, BTreeSet<String>>,
+        hints: &RenderHints,
+        policy: OutlierPolicy,
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::from_items(shard.items, shard.skipped, shard.properties, mappings, hints, policy)
+    }
+
+    /// Each continent's features of this world map, as it was built, with the continent's
+    /// name. A continent with a listed country the map lacks (not in the file, or hidden
+    /// by the outlier policy) is left out, to be read from its own file; so is every one
+    /// of a map drawn across the antimeridian, whose longitudes were shifted.
+    pub fn shards(&self) -> Vec<(String, MapShard)> {
+        if self.east {
+            return Vec::new();
+        }
+        let drawn: HashSet<String> = self.items.iter().map(|(name, _)| country_key(name)).collect();
+        let skipped: HashSet<String> =
+            self.skipped.iter().filter(|(_, reason)| *reason != HIDDEN).map(|(name, _)| country_key(name)).collect();
+        self.continents
+            .iter()
+            .filter(|(_, members)| members.iter().all(|key| drawn.contains(key) || skipped.contains(key)))
+            .map(|(continent, members)| {
+                let shard = MapShard {
+                    items: self.items.iter().filter(|(name, _)| members.contains(&country_key(name))).cloned().collect(),
+                    skipped: self.skipped.iter().filter(|(name, _)| members.contains(&country_key(name))).cloned().collect(),
+                    properties: self
+                        .properties
+                        .iter()
+                        .filter(|(key, _)| members.contains(*key))
+                        .map(|(key, props)| (key.clone(), props.clone()))
+                        .collect(),
+                };
+                (continent.clone(), shard)
+            })
+            .collect()
+    }
+
+    /// A new view of this map as it was built, sharing its geometry rather than copying
+    /// it: nothing worked out on first use is carried over, and it has a revision of its
+    /// own. Meant for views fresh from a file, e.g. those `DataCache` keeps.
+    pub fn share(&self) -> Self {
+        Self {
+            items: Arc::clone(&self.items),
+            skipped: self.skipped.clone(),
+            hints: self.hints.clone(),
+            properties: self.properties.clone(),
+            x_bounds: self.x_bounds,
+            y_bounds: self.y_bounds,
+            continents: self.continents.clone(),
+            continent_order: OnceCell::new(),
+            hit_grid: OnceCell::new(),
+            outlines: OnceCell::new(),
+            simplify_epsilon: self.simplify_epsilon,
+            focus: self.focus.clone(),
+            outlying: self.outlying.clone(),
+            home: self.home,
+            east: self.east,
+            revision: next_revision(),
+        }
+    }
+
+    /// View of a whole collection zoomed onto the feature named `focus`, with the other
+    /// features drawn dimmed around it. Errors when no feature has that name.
+    pub fn focused(raw: GeoJson, focus: &str, data_cache: &mut DataCache) -> Result<Self, Box<dyn Error>> {
+        Self::new(raw, data_cache)?.into_focused(focus)
+    }
+
+    /// This view zoomed onto the feature named `focus`, as `focused` builds it
+    pub fn into_focused(mut self, focus: &str) -> Result<Self, Box<dyn Error>> {
+        let key = country_key(focus);
+        let focus_geoms = self.items.iter().filter(|(name, _)| country_key(name) == key).map(|(_, mp)| mp);
+        let ([minx, maxx], [miny, maxy]) = bounds(focus_geoms)
+            .ok_or_else(|| format!("'{}' not found in GeoJSON", focus))?;
+
+        // Leave a margin of context around the focused feature
+        let (pad_x, pad_y) = ((maxx - minx) * 0.25, (maxy - miny) * 0.25);
+        self.x_bounds = [minx - pad_x, maxx + pad_x];
+        self.y_bounds = [miny - pad_y, maxy + pad_y];
+        self.focus = Some(key);
+        self.redraw();
+        Ok(self)
+    }
+
+    /// Fold each constituent feature into its parent's MultiPolygon when both are in this
+    /// view, so the pair is drawn, highlighted and looked up as the parent.
+    /// `constituents` maps parent feature names to constituent feature names.
+    pub fn merge_constituents(&mut self, constituents: &BTreeMap<String, Vec<String>>) {
+        for (parent, parts) in constituents {
+            let parent_key = country_key(parent);
+            let part_keys: HashSet<String> = parts.iter().map(|p| country_key(p)).collect();
+            if !self.items.iter().any(|(name, _)| country_key(name) == parent_key) {
+                continue;
+            }
+            let items = Arc::make_mut(&mut self.items);
+            let (merged, kept): (Vec<_>, Vec<_>) =
+                items.drain(..).partition(|(name, _)| part_keys.contains(&country_key(name)));
+            *items = kept;
+            for (name, mp) in merged {
+                self.outlying.remove(&country_key(&name));
+                if let Some((_, parent_mp)) = items.iter_mut().find(|(n, _)| country_key(n) == parent_key) {
+                    parent_mp.0.extend(mp.0);
+                }
+            }
+        }
+        self.hit_grid = OnceCell::new();
+        self.redraw();
+        // A focused view keeps its zoom; otherwise refit to what is now in bounds
+        if self.focus.is_none()
+            && let Some((x, y)) = bounds(
+                self.items.iter().filter(|(name, _)| !self.outlying.contains(&country_key(name))).map(|(_, mp)| mp),
+            ) {
+            self.x_bounds = x;
+            self.y_bounds = y;
+        }
+    }
+
+    /// Add the constituents `data_cache` lists for `parent` (e.g. Greenland for Denmark),
+    /// each read from its own country file: merged into the parent's feature when `merge`,
+    /// otherwise drawn dimmed beside it for context. The bounds grow to take them in;
+    /// constituents without a file are recorded as skipped.
+    pub fn include_constituents(&mut self, parent: &str, data_cache: &mut DataCache, merge: bool) {
+        let parent_key = country_key(parent);
+        for part in data_cache.constituents_of(parent).to_vec() {
+            let Ok(mut view) = Self::load(data_cache, &GeoLevel::Country, &part, OutlierPolicy::Include) else {
+                self.skipped.push((part, "constituent without map file"));
+                continue;
+            };
+            // Into this map's longitudes, should only one of them cross the antimeridian
+            if view.east != self.east {
+                for (_, mp) in Arc::make_mut(&mut view.items) {
+                    shift_longitudes(mp, self.east);
+                }
+                shift_hints(view.hints.values_mut(), self.east);
+            }
+            let Some(([px0, px1], [py0, py1])) = bounds(view.items.iter().map(|(_, mp)| mp)) else { continue };
+            self.hints.extend(view.hints);
+            self.properties.extend(view.properties);
+            self.x_bounds = [self.x_bounds[0].min(px0), self.x_bounds[1].max(px1)];
+            self.y_bounds = [self.y_bounds[0].min(py0), self.y_bounds[1].max(py1)];
+            let items = Arc::make_mut(&mut self.items);
+            for (name, mp) in Arc::unwrap_or_clone(view.items) {
+                let parent_mp = items.iter_mut().find(|(n, _)| country_key(n) == parent_key);
+                match parent_mp {
+                    Some((_, parent_mp)) if merge => parent_mp.0.extend(mp.0),
+                    _ => {
+                        self.outlying.insert(country_key(&name));
+                        items.push((name, mp));
+                    }
                 }
             }
         }
+        self.hit_grid = OnceCell::new();
+        self.redraw();
+    }
 
-        // Determine spatial bounds of all features
-        let (mut minx, mut miny, mut maxx, mut maxy) =
-            (f64::INFINITY, f64::INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
-        for (_, mp) in &items {
-            for poly in &mp.0 {
-                for coord in poly.exterior().0.iter()
-                    .chain(poly.interiors().iter().flat_map(|r| r.0.iter()))
-                {
-                    minx = minx.min(coord.x);
-                    miny = miny.min(coord.y);
-                    maxx = maxx.max(coord.x);
-                    maxy = maxy.max(coord.y);
+    /// Add the features of `neighbours` (e.g. Germany and Italy for Austria) to draw around
+    /// the country: taken from `continent`'s map, or read from their own country file for
+    /// those on another continent. They stay out of the bounds, so the country keeps its
+    /// zoom; neighbours already in the view are left as they are, and those found nowhere
+    /// are recorded as skipped.
+    pub fn include_neighbours(&mut self, neighbours: &[String], continent: &str, data_cache: &mut DataCache) {
+        let present: HashSet<String> = self.items.iter().map(|(name, _)| country_key(name)).collect();
+        let mut wanted: Vec<&String> = neighbours.iter().filter(|n| !present.contains(&country_key(n))).collect();
+        if wanted.is_empty() {
+            return;
+        }
+        // Each map they were found on, with the keys of the features to take from it: the
+        // continent's holds every other country too, a country file only the neighbour
+        let mut found: Vec<(Self, Option<HashSet<String>>)> = Vec::new();
+        if let Ok(view) = Self::load(data_cache, &GeoLevel::Continent, continent, OutlierPolicy::Include) {
+            let keys: HashSet<String> = wanted.iter().map(|n| country_key(n)).collect();
+            wanted.retain(|n| !view.items.iter().any(|(name, _)| country_key(name) == country_key(n)));
+            found.push((view, Some(keys)));
+        }
+        for name in wanted {
+            match Self::load(data_cache, &GeoLevel::Country, name, OutlierPolicy::Include) {
+                Ok(view) => found.push((view, None)),
+                Err(_) => self.skipped.push((name.clone(), "neighbour without map data")),
+            }
+        }
+        let items = Arc::make_mut(&mut self.items);
+        for (mut view, keys) in found {
+            let taken = view.items.iter().filter(|(name, _)| keys.as_ref().is_none_or(|keys| keys.contains(&country_key(name))));
+            for (name, mp) in taken {
+                let mut mp = mp.clone();
+                // Into this map's longitudes, should only one of them cross the antimeridian
+                if view.east != self.east {
+                    shift_longitudes(&mut mp, self.east);
+                }
+                let key = country_key(name);
+                if let Some(props) = view.properties.remove(&key) {
+                    self.properties.entry(key.clone()).or_insert(props);
                 }
+                self.outlying.insert(key);
+                items.push((name.clone(), mp));
             }
         }
+        self.hit_grid = OnceCell::new();
+        self.redraw();
+    }
+
+    /// Longitudes the map's coordinates run over: 0..360 for a map crossing the
+    /// antimeridian, otherwise -180..180
+    pub fn lon_range(&self) -> [f64; 2] {
+        if self.east { [0.0, 360.0] } else { [-180.0, 180.0] }
+    }
 
-        let continents = data_cache.load_continent_mappings().unwrap_or_default();
-        Ok(Self { items, x_bounds: [minx, maxx], y_bounds: [miny, maxy], continents })
+    /// Meridian the world projections are centred on: the middle of `lon_range`
+    pub fn central_meridian(&self) -> f64 {
+        let [west, east] = self.lon_range();
+        (west + east) / 2.0
+    }
+
+    /// Canvas bounds to draw `viewport` into `inner` with under `projection`: for one that
+    /// bends the meridians, the box around the projected viewport, whose edges become
+    /// curves, fitted to the area; otherwise as `projected_bounds` gives them
+    pub fn canvas_bounds(&self, projection: ProjectionKind, viewport: ([f64; 2], [f64; 2]), inner: TuiRect) -> ([f64; 2], [f64; 2]) {
+        if !projection.bends() {
+            return projected_bounds(projection, viewport, inner);
+        }
+        const STEPS: usize = 16;
+        let central = self.central_meridian();
+        let ([x0, x1], [y0, y1]) = viewport;
+        let (y0, y1) = (y0.max(-90.0), y1.min(90.0));
+        // A parallel is longest at the equator, where the sides bulge out furthest
+        let equator = 0.0_f64.clamp(y0, y1.max(y0));
+        let mut points = vec![(x0, equator), (x1, equator)];
+        for i in 0..=STEPS {
+            let t = i as f64 / STEPS as f64;
+            let (lon, lat) = (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t);
+            points.extend([(lon, y0), (lon, y1), (x0, lat), (x1, lat)]);
+        }
+        let projector = self::projection(projection, central);
+        let projected: Vec<(f64, f64)> = points.into_iter().map(|(lon, lat)| projector.project(lon, lat)).collect();
+        let xs = projected.iter().map(|p| p.0);
+        let ys = projected.iter().map(|p| p.1);
+        let x = [xs.clone().fold(f64::INFINITY, f64::min), xs.fold(f64::NEG_INFINITY, f64::max)];
+        let y = [ys.clone().fold(f64::INFINITY, f64::min), ys.fold(f64::NEG_INFINITY, f64::max)];
+        projected_bounds(projection, (x, y), inner)
     }
 
     /// Returns number of geographic features loaded.
@@ -93,64 +1265,897 @@ impl MapView {
         self.items.len()
     }
 
-    /// Render all polygons, optionally highlighting a continent or country in red.
+    /// Names of the features in this view, in drawing order: largest first
+    pub fn feature_names(&self) -> impl Iterator<Item = &str> {
+        self.items.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Continents with geometry in this view, ordered west→east (computed once).
+    pub fn continents_west_to_east(&self) -> &[String] {
+        self.continent_order.get_or_init(|| continent_order(&self.items, &self.continents))
+    }
+
+    /// Countries the lists put on `continent`
+    pub fn continent_size(&self, continent: &str) -> usize {
+        self.continents.get(continent).map_or(0, HashSet::len)
+    }
+
+    /// Continent the lists put `name` on; None when no list has it
+    pub fn continent_of(&self, name: &str) -> Option<&str> {
+        let key = country_key(name);
+        self.continents.iter().find(|(_, members)| members.contains(&key)).map(|(continent, _)| continent.as_str())
+    }
+
+    /// Middle of the largest polygon of `name`'s features (longitude, latitude), where
+    /// its label goes; None when it has none
+    pub fn anchor(&self, name: &str) -> Option<(f64, f64)> {
+        let key = country_key(name);
+        let largest = self.items
+            .iter()
+            .filter(|(n, _)| country_key(n) == key)
+            .flat_map(|(_, mp)| &mp.0)
+            .max_by(|a, b| poly_area(a).total_cmp(&poly_area(b)))?;
+        largest.centroid().map(|c| (c.x(), c.y()))
+    }
+
+    /// Tint of each continent with geometry: its own by name (`theme::continent_tint`),
+    /// else by its west→east position, so every part of the UI colors a continent the same way.
+    pub fn continent_tints(&self) -> BTreeMap<String, Rgb> {
+        self.continents_west_to_east()
+            .iter()
+            .enumerate()
+            .map(|(i, continent)| (continent.clone(), continent_tint(continent, i)))
+            .collect()
+    }
+
+    /// Canonical keys of the member countries of the continents in `tints`, with the
+    /// continent's tint, for `MapWidget::colors`.
+    pub fn member_tints(&self, tints: &BTreeMap<String, Rgb>) -> BTreeMap<String, Rgb> {
+        self.continents
+            .iter()
+            .filter_map(|(continent, members)| tints.get(continent).map(|&tint| (members, tint)))
+            .flat_map(|(members, tint)| members.iter().map(move |key| (key.clone(), tint)))
+            .collect()
+    }
+
+    /// Data bounds ([minx, maxx], [miny, maxy]) shown by this view.
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        (self.x_bounds, self.y_bounds)
+    }
+
+    /// How far in the view is zoomed compared with its computed bounds; 1 when it isn't
+    pub fn zoom_level(&self) -> f64 {
+        self.home.map_or(1.0, |([x0, x1], _)| (x1 - x0) / (self.x_bounds[1] - self.x_bounds[0]))
+    }
+
+    /// Whether the view shows less than its computed bounds
+    pub fn is_zoomed(&self) -> bool {
+        self.home.is_some()
+    }
+
+    /// Zoom by `factor` (above 1 closer, below 1 farther) around the middle of the view,
+    /// the same on both axes so shapes keep their proportions. Stays between the computed
+    /// bounds and `MAX_ZOOM` times closer; zooming all the way out restores them.
+    pub fn zoom(&mut self, factor: f64) {
+        let (home_x, home_y) = self.home.unwrap_or((self.x_bounds, self.y_bounds));
+        let (home_w, home_h) = (home_x[1] - home_x[0], home_y[1] - home_y[0]);
+        let level = (self.zoom_level() * factor).clamp(1.0, MAX_ZOOM);
+        if level <= 1.0 {
+            self.reset_zoom();
+            return;
+        }
+        let (w, h) = (home_w / level, home_h / level);
+        let (cx, cy) = ((self.x_bounds[0] + self.x_bounds[1]) / 2.0, (self.y_bounds[0] + self.y_bounds[1]) / 2.0);
+        self.home = Some((home_x, home_y));
+        self.x_bounds = [cx - w / 2.0, cx + w / 2.0];
+        self.y_bounds = [cy - h / 2.0, cy + h / 2.0];
+        self.keep_within_home();
+        self.redraw();
+    }
+
+    /// Move a zoomed view by `dx`, `dy` times its width and height (right and up for
+    /// positive values), no further than the computed bounds; nothing to move otherwise
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        if self.home.is_none() {
+            return;
+        }
+        let (w, h) = (self.x_bounds[1] - self.x_bounds[0], self.y_bounds[1] - self.y_bounds[0]);
+        self.x_bounds = [self.x_bounds[0] + dx * w, self.x_bounds[1] + dx * w];
+        self.y_bounds = [self.y_bounds[0] + dy * h, self.y_bounds[1] + dy * h];
+        self.keep_within_home();
+        self.revision = next_revision();
+    }
+
+    /// Back to the computed bounds
+    pub fn reset_zoom(&mut self) {
+        if let Some((x, y)) = self.home.take() {
+            self.x_bounds = x;
+            self.y_bounds = y;
+            self.redraw();
+        }
+    }
+
+    /// Drop the outlines simplified for the old bounds or items and take a new revision
+    fn redraw(&mut self) {
+        self.outlines = OnceCell::new();
+        self.revision = next_revision();
+    }
+
+    /// Shift the view back inside the computed bounds where it has moved past them
+    fn keep_within_home(&mut self) {
+        let Some((home_x, home_y)) = self.home else { return };
+        for (view, home) in [(&mut self.x_bounds, home_x), (&mut self.y_bounds, home_y)] {
+            let shift = if view[0] < home[0] {
+                home[0] - view[0]
+            } else if view[1] > home[1] {
+                home[1] - view[1]
+            } else {
+                0.0
+            };
+            *view = [view[0] + shift, view[1] + shift];
+        }
+    }
+
+    /// Simplify the drawn outlines with a tolerance of `epsilon` times the larger side of
+    /// the bounds, so detail finer than the screen shows isn't drawn; 0 draws every point.
+    /// Lookups, highlights and counts always use the full geometry.
+    pub fn set_simplify(&mut self, epsilon: f64) {
+        if epsilon != self.simplify_epsilon {
+            self.simplify_epsilon = epsilon.max(0.0);
+            self.redraw();
+        }
+    }
+
+    /// Outlines of the items as drawn, simplified for the current bounds on first use
+    fn outlines(&self) -> &[MultiPolygon<f64>] {
+        self.outlines.get_or_init(|| {
+            let span = (self.x_bounds[1] - self.x_bounds[0]).max(self.y_bounds[1] - self.y_bounds[0]);
+            let tolerance = self.simplify_epsilon * span;
+            self.items
+                .iter()
+                .map(|(_, mp)| if tolerance > 0.0 { simplify_outline(mp, tolerance) } else { mp.clone() })
+                .collect()
+        })
+    }
+
+    /// Line segments in the full geometry and in the outlines drawn, for diagnostics
+    pub fn segments(&self) -> (usize, usize) {
+        let full = self.items.iter().map(|(_, mp)| segment_count(mp)).sum();
+        (full, self.outlines().iter().map(segment_count).sum())
+    }
+
+    /// Bounds of everything the highlight name `sel` covers; None when it matches nothing.
+    pub fn highlight_bounds(&self, sel: &str) -> Option<([f64; 2], [f64; 2])> {
+        let hl = self.highlight(sel);
+        bounds(self.items.iter().filter(|(name, _)| hl.contains(name)).map(|(_, mp)| mp))
+    }
+
+    /// Bounds widened to take in everything `sel` highlights, so selecting an
+    /// outlying feature (Antarctica) still shows it
+    pub fn bounds_including(&self, sel: &str) -> ([f64; 2], [f64; 2]) {
+        let ([minx, maxx], [miny, maxy]) = self.bounds();
+        match self.highlight_bounds(sel) {
+            Some(([hx0, hx1], [hy0, hy1])) => ([minx.min(hx0), maxx.max(hx1)], [miny.min(hy0), maxy.max(hy1)]),
+            None => self.bounds(),
+        }
+    }
+
+    /// Bounds to show with `sel` selected when not zoomed: `hinted_viewport`, otherwise
+    /// `bounds_including`
+    pub fn preferred_viewport(&self, sel: &str) -> ([f64; 2], [f64; 2]) {
+        self.hinted_viewport(sel).unwrap_or_else(|| self.bounds_including(sel))
+    }
+
+    /// On the country `sel`'s own map (or a view focused on it), the bounds its render
+    /// hints ask for: explicit ones, or its own padded by the given shares
+    pub fn hinted_viewport(&self, sel: &str) -> Option<([f64; 2], [f64; 2])> {
+        let key = country_key(sel);
+        let own_map = self.focus.as_ref() == Some(&key)
+            || self.items.iter().all(|(name, _)| country_key(name) == key || self.outlying.contains(&country_key(name)));
+        let hint = self.hints.get(&key).filter(|_| own_map)?;
+        match (hint.bounds, hint.padding) {
+            (Some([x, y]), _) => Some((x, y)),
+            (None, Some([px, py])) => {
+                let ([x0, x1], [y0, y1]) = self.highlight_bounds(sel)?;
+                let (dx, dy) = ((x1 - x0) * px, (y1 - y0) * py);
+                Some(([x0 - dx, x1 + dx], [y0 - dy, y1 + dy]))
+            }
+            (None, None) => None,
+        }
+    }
+
+    /// Bounds closing in on what `sel` highlights, with `AUTO_ZOOM_PADDING` around it and
+    /// no closer than `MAX_ZOOM`, so a microstate still shows its neighbours; everything else
+    /// inside is drawn as usual. None when `sel` matches nothing.
+    pub fn auto_zoom_viewport(&self, sel: &str) -> Option<([f64; 2], [f64; 2])> {
+        let ([x0, x1], [y0, y1]) = self.highlight_bounds(sel)?;
+        let ([bx0, bx1], [by0, by1]) = self.bounds();
+        let padded = |lo: f64, hi: f64, whole: f64| {
+            let half = ((hi - lo) * (0.5 + AUTO_ZOOM_PADDING)).max(whole / MAX_ZOOM / 2.0);
+            let middle = (lo + hi) / 2.0;
+            [middle - half, middle + half]
+        };
+        Some((padded(x0, x1, bx1 - bx0), padded(y0, y1, by1 - by0)))
+    }
+
+    /// Outline of this view heavily simplified for the mini-map: only the largest
+    /// polygon of each feature, with detail below `epsilon` degrees dropped.
+    pub fn mini_map(&self, epsilon: f64) -> MiniMap {
+        let rings = self.items
+            .iter()
+            .filter_map(|(_, mp)| mp.0.iter().max_by(|a, b| poly_area(a).total_cmp(&poly_area(b))))
+            .map(|poly| poly.exterior().simplify(&epsilon))
+            .filter(|ring| ring.0.len() >= 3)
+            .collect();
+        MiniMap { rings, x_bounds: self.x_bounds, y_bounds: self.y_bounds }
+    }
+
+    /// Resolve a highlight name: a continent highlights its members, anything else one country.
+    fn highlight(&self, sel: &str) -> Highlight<'_> {
+        match self.continents.get(sel) {
+            Some(members) => Highlight::Continent(members),
+            None => Highlight::Country(country_key(sel)),
+        }
+    }
+
+    /// Legend line describing what the highlight shows in `style`, e.g. "czerwony: Europe
+    /// (Enter otwiera)". `open_key` names the key that opens the highlighted item, when it
+    /// can be opened. Returns None when nothing in this view is highlighted.
+    pub fn legend(&self, highlight: Option<&str>, open_key: Option<&str>, style: MapStyle) -> Option<String> {
+        let sel = highlight?;
+        let hl = self.highlight(sel);
+        if !self.items.iter().any(|(name, _)| hl.contains(name)) {
+            return None;
+        }
+        Some(match open_key {
+            Some(key) => format!("{}: {} ({} otwiera)", style.highlight_name(), sel, key),
+            None => format!("{}: {}", style.highlight_name(), sel),
+        })
+    }
+
+    /// Name of the feature containing the map coordinate (`x` = longitude, `y` = latitude),
+    /// the one drawn on top where features overlap. Only features whose bounding box holds
+    /// the point get the exact polygon test.
+    pub fn feature_at(&self, x: f64, y: f64) -> Option<&str> {
+        let pt = Point::new(x, y);
+        let grid = self.hit_grid.get_or_init(|| HitGrid::new(&self.items));
+        grid.candidates(x, y)
+            .rev()
+            .map(|i| &self.items[i])
+            .find(|(_, mp)| mp.contains(&pt))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// `feature_at` without the index, testing every feature in turn; the reference
+    /// answer for checking and benchmarking the index
+    pub fn feature_at_exhaustive(&self, x: f64, y: f64) -> Option<&str> {
+        let pt = Point::new(x, y);
+        self.items
+            .iter()
+            .rev()
+            .find(|(_, mp)| mp.contains(&pt))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Whether `name` appears only as features without geometry (`"geometry": null`), so
+    /// there is nothing to draw for it
+    pub fn lacks_geometry(&self, name: &str) -> bool {
+        let key = country_key(name);
+        self.skipped.iter().any(|(n, reason)| *reason == NO_GEOMETRY && country_key(n) == key)
+            && !self.items.iter().any(|(n, _)| country_key(n) == key)
+    }
+
+    /// Three-letter code of the feature called `name` for a label with no room for the
+    /// name: its ISO code where the data has one, else the first letters of the name
+    pub fn short_code(&self, name: &str) -> String {
+        let iso = self.properties(name).and_then(|props| {
+            ["ISO_A3", "ADM0_A3"]
+                .iter()
+                .filter_map(|key| props.get(*key)?.as_str())
+                .find(|code| code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()))
+        });
+        match iso {
+            Some(code) => code.to_string(),
+            None => name.chars().filter(|c| c.is_alphabetic()).take(3).flat_map(char::to_uppercase).collect(),
+        }
+    }
+
+    /// Kept properties (`KEPT_PROPERTIES`) of the feature called `name`, also when it
+    /// was skipped for having no geometry
+    pub fn properties(&self, name: &str) -> Option<&Properties> {
+        self.properties.get(&country_key(name))
+    }
+
+    /// Features left out of the view, with the reason they were skipped.
+    pub fn skipped(&self) -> &[(String, &'static str)] {
+        &self.skipped
+    }
+
+    /// Widget drawing this view; set title, highlight, legend and viewport on it
+    pub fn widget(&self) -> MapWidget<'_> {
+        MapWidget {
+            view: self,
+            title: "",
+            highlight: None,
+            highlight_keys: None,
+            legend: None,
+            viewport: None,
+            projection: ProjectionKind::default(),
+            projector: projection(ProjectionKind::default(), self.central_meridian()),
+            colors: None,
+            visited: None,
+            neighbours: None,
+            pulse: false,
+            labels: false,
+            fill_highlight: false,
+            graticule: false,
+            symbols: &UNICODE,
+            marker: None,
+            border_style: Style::default(),
+            style: MapStyle::default(),
+        }
+    }
+
+    /// Render all polygons in the colors of `style`, optionally highlighting a continent
+    /// or country, with an optional legend line in the bottom border.
     pub fn render<'a>(
         &self,
         f: &mut Frame<'a>,
         area: TuiRect,
         title: &str,
         highlight: Option<&str>,
+        legend: Option<&str>,
+        style: MapStyle,
+    ) {
+        let mut widget = self.widget().title(title).style(style);
+        if let Some(sel) = highlight {
+            widget = widget.highlight(sel);
+        }
+        if let Some(legend) = legend {
+            widget = widget.legend(legend);
+        }
+        f.render_widget(widget, area);
+    }
+}
+
+/// Colors a map is drawn in, whose highlight the lists around it follow. The presets
+/// switch at runtime; the first is the one every map was drawn in before there was a choice.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapStyle {
+    pub base: Color,               // features under no other layer
+    pub highlight: Color,          // the selection, on the map and in the lists
+    pub background: Option<Color>, // behind the map and the GDP chart; None keeps the terminal's
+}
+
+impl Default for MapStyle {
+    fn default() -> Self {
+        MapStyle::CLASSIC
+    }
+}
+
+impl MapStyle {
+    /// White outlines, the selection in red, on the terminal's background
+    pub const CLASSIC: MapStyle = MapStyle { base: Color::White, highlight: Color::Red, background: None };
+    /// The selection in light blue, which red-green colorblindness leaves distinct
+    pub const NO_RED: MapStyle = MapStyle { base: Color::White, highlight: Color::LightBlue, background: None };
+    /// Black outlines and a blue selection on white, for light terminal themes
+    pub const LIGHT: MapStyle = MapStyle { base: Color::Black, highlight: Color::Blue, background: Some(Color::White) };
+    /// Every preset with its name, in the order the key cycles through them
+    pub const PRESETS: [(MapStyle, &'static str); 3] =
+        [(MapStyle::CLASSIC, "klasyczne"), (MapStyle::NO_RED, "bez czerwieni"), (MapStyle::LIGHT, "jasne tło")];
+
+    /// The preset after this one; the first for a style that isn't a preset
+    pub fn next(self) -> Self {
+        let at = Self::PRESETS.iter().position(|&(style, _)| style == self);
+        Self::PRESETS[at.map_or(0, |i| (i + 1) % Self::PRESETS.len())].0
+    }
+
+    /// Name of the preset, for the notification
+    pub fn label(self) -> &'static str {
+        Self::PRESETS.iter().find(|&&(style, _)| style == self).map_or("własne", |&(_, name)| name)
+    }
+
+    /// Polish name of the highlight color, for the legend
+    pub fn highlight_name(self) -> &'static str {
+        match self.highlight {
+            Color::Red | Color::LightRed => "czerwony",
+            Color::Blue => "niebieski",
+            Color::LightBlue => "jasnoniebieski",
+            Color::Green | Color::LightGreen => "zielony",
+            Color::Yellow | Color::LightYellow => "żółty",
+            Color::Magenta | Color::LightMagenta => "fioletowy",
+            Color::Cyan | Color::LightCyan => "turkusowy",
+            _ => "zaznaczenie",
+        }
+    }
+
+    /// The highlight in its brighter phase, as it pulses
+    pub fn bright_highlight(self) -> Color {
+        match self.highlight {
+            Color::Red => Color::LightRed,
+            Color::Green => Color::LightGreen,
+            Color::Yellow => Color::LightYellow,
+            Color::Blue => Color::LightBlue,
+            Color::Magenta => Color::LightMagenta,
+            Color::Cyan | Color::LightBlue => Color::LightCyan,
+            Color::DarkGray => Color::Gray,
+            Color::Gray => Color::White,
+            other => other,
+        }
+    }
+}
+
+/// A `MapView` as a ratatui widget, so it can be drawn into any area or buffer.
+///
+/// Each feature gets one color from these layers, the first that applies winning:
+/// the highlight (the style's, red by default), the country's neighbours (yellow),
+/// dimming (dark gray: outside a focused view's feature, or outlying), its choropleth
+/// color, visited (gray), and the style's base color (white by default).
+/// A highlight too small to notice at the current zoom also gets a box around it; a
+/// filled highlight keeps its outline, so the smallest still show.
+pub struct MapWidget<'a> {
+    view: &'a MapView,
+    title: &'a str,
+    highlight: Option<&'a str>,
+    highlight_keys: Option<&'a BTreeSet<String>>, // replaces `highlight` when set
+    legend: Option<&'a str>,
+    viewport: Option<([f64; 2], [f64; 2])>,
+    projection: ProjectionKind, // how the viewport is fitted to the area
+    projector: Box<dyn Projection>, // `projection` around the view's central meridian
+    colors: Option<&'a BTreeMap<String, Color>>, // choropleth color by canonical key
+    visited: Option<&'a BTreeSet<String>>, // canonical keys of countries already opened
+    neighbours: Option<&'a BTreeSet<String>>, // canonical keys of the country's neighbours
+    pulse: bool, // draw the highlight in its brighter variant
+    labels: bool, // print feature names where they fit
+    fill_highlight: bool, // fill the highlighted polygons, not just outline them
+    graticule: bool, // latitude and longitude lines behind the outlines
+    symbols: &'a Symbols, // borders and line marker
+    marker: Option<Marker>, // replaces the symbols' line marker when set
+    border_style: Style,  // e.g. the focus highlight
+    style: MapStyle,      // base, highlight and background colors
+}
+
+/// Everything a `MapWidget` draws from, as of one frame: equal frames draw the same
+/// cells, so the buffer rendered for one can be copied in for the other
+#[derive(Clone, Debug, PartialEq)]
+pub struct MapFrame {
+    area: TuiRect,
+    revision: u64, // the view's geometry and bounds
+    title: String,
+    highlight: Option<String>,
+    highlight_keys: Option<BTreeSet<String>>,
+    legend: Option<String>,
+    viewport: Option<([f64; 2], [f64; 2])>,
+    projection: ProjectionKind,
+    colors: Option<BTreeMap<String, Color>>,
+    visited: Option<BTreeSet<String>>,
+    neighbours: Option<BTreeSet<String>>,
+    pulse: bool,
+    labels: bool,
+    fill_highlight: bool,
+    graticule: bool,
+    symbols: *const Symbols, // symbol sets are statics, so the same address is the same set
+    marker: Option<Marker>,
+    border_style: Style,
+    style: MapStyle,
+}
+
+impl<'a> MapWidget<'a> {
+    /// What this widget would draw into `area`
+    pub fn frame(&self, area: TuiRect) -> MapFrame {
+        MapFrame {
+            area,
+            revision: self.view.revision,
+            title: self.title.to_string(),
+            highlight: self.highlight.map(str::to_string),
+            highlight_keys: self.highlight_keys.cloned(),
+            legend: self.legend.map(str::to_string),
+            viewport: self.viewport,
+            projection: self.projection,
+            colors: self.colors.cloned(),
+            visited: self.visited.cloned(),
+            neighbours: self.neighbours.cloned(),
+            pulse: self.pulse,
+            labels: self.labels,
+            fill_highlight: self.fill_highlight,
+            graticule: self.graticule,
+            symbols: self.symbols,
+            marker: self.marker,
+            border_style: self.border_style,
+            style: self.style,
+        }
+    }
+
+    /// Title in the top border
+    pub fn title(mut self, title: &'a str) -> Self {
+        self.title = title;
+        self
+    }
+
+    /// Draw a continent (all its members) or a country in the highlight color
+    pub fn highlight(mut self, name: &'a str) -> Self {
+        self.highlight = Some(name);
+        self
+    }
+
+    /// Draw exactly the features with these canonical keys highlighted, in place of `highlight`
+    pub fn highlight_keys(mut self, keys: &'a BTreeSet<String>) -> Self {
+        self.highlight_keys = Some(keys);
+        self
+    }
+
+    /// Legend line in the bottom border
+    pub fn legend(mut self, legend: &'a str) -> Self {
+        self.legend = Some(legend);
+        self
+    }
+
+    /// Show this ([minx, maxx], [miny, maxy]) region instead of the view's own bounds
+    pub fn viewport(mut self, viewport: ([f64; 2], [f64; 2])) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    /// Fit the viewport to the area with `projection` (corrected by default)
+    pub fn projection(mut self, projection: ProjectionKind) -> Self {
+        self.projection = projection;
+        self.projector = self::projection(projection, self.view.central_meridian());
+        self
+    }
+
+    /// Draw features in these colors, by canonical key, e.g. their choropleth buckets
+    pub fn colors(mut self, colors: &'a BTreeMap<String, Color>) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Shade the features whose canonical key is in `visited`
+    pub fn visited(mut self, visited: &'a BTreeSet<String>) -> Self {
+        self.visited = Some(visited);
+        self
+    }
+
+    /// Draw the features whose canonical key is in `neighbours` in yellow, around the
+    /// highlighted country
+    pub fn neighbours(mut self, neighbours: &'a BTreeSet<String>) -> Self {
+        self.neighbours = Some(neighbours);
+        self
+    }
+
+    /// Draw the highlight in its brighter variant (light red for red); toggled over time it pulses
+    pub fn pulse(mut self, bright: bool) -> Self {
+        self.pulse = bright;
+        self
+    }
+
+    /// Draw in the colors of `style` (`MapStyle::CLASSIC` by default)
+    pub fn style(mut self, style: MapStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Draw borders and lines with `symbols` (Unicode by default)
+    pub fn symbols(mut self, symbols: &'a Symbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Draw the outlines with `marker` instead of the symbols' line marker
+    pub fn marker(mut self, marker: Marker) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    /// Style of the borders
+    pub fn border_style(mut self, style: Style) -> Self {
+        self.border_style = style;
+        self
+    }
+
+    /// Print the names of the features that aren't dimmed, as many as fit without overlapping
+    pub fn labels(mut self, on: bool) -> Self {
+        self.labels = on;
+        self
+    }
+
+    /// Fill the highlighted countries, holes (lakes) left open, instead of only outlining them
+    pub fn fill_highlight(mut self, on: bool) -> Self {
+        self.fill_highlight = on;
+        self
+    }
+
+    /// Draw a latitude/longitude grid behind the outlines, labeled along the edges
+    pub fn graticule(mut self, on: bool) -> Self {
+        self.graticule = on;
+        self
+    }
+
+    /// Features to label, the text to print and the cells it'd cover in `inner` (the map
+    /// inside its borders),
+    /// centered on the middle of each feature's largest polygon or on its hinted anchor. A
+    /// name wider than its feature is cut to a three-letter code (and anything wider than
+    /// the map to its width), and features covering
+    /// less than a cell or hinted to need more zoom get no label
+    fn label_candidates(&self, inner: TuiRect, x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Vec<(&'a str, Cow<'a, str>, LabelCandidate)> {
+        let (span_x, span_y) = (x_bounds[1] - x_bounds[0], y_bounds[1] - y_bounds[0]);
+        let zoom = self.view.zoom_level();
+        self.view.items
+            .iter()
+            .filter(|(name, _)| self.base_color(name) != Color::DarkGray)
+            .filter_map(|(name, mp)| {
+                let hint = self.view.hints.get(&country_key(name));
+                if hint.and_then(|h| h.min_zoom).is_some_and(|min| zoom < min) {
+                    return None;
+                }
+                let center = match hint.and_then(|h| h.label) {
+                    Some([lon, lat]) => Point::new(lon, lat),
+                    None => mp.0.iter().max_by(|a, b| poly_area(a).total_cmp(&poly_area(b)))?.centroid()?,
+                };
+                let (x, y) = self.to_canvas(center.x(), center.y());
+                let col = ((x - x_bounds[0]) / span_x * inner.width as f64).floor();
+                let row = ((y_bounds[1] - y) / span_y * inner.height as f64).floor();
+                if col < 0.0 || row < 0.0 || col >= inner.width as f64 || row >= inner.height as f64 {
+                    return None;
+                }
+                let ([x0, x1], [y0, y1]) = self.canvas_box(bounds([mp])?);
+                let cells_x = (x1 - x0) / span_x * inner.width as f64;
+                let cells_y = (y1 - y0) / span_y * inner.height as f64;
+                if cells_x * cells_y < 1.0 {
+                    return None;
+                }
+                let text = if name.width() as f64 <= cells_x + 2.0 {
+                    Cow::Borrowed(name.as_str())
+                } else {
+                    Cow::Owned(self.view.short_code(name))
+                };
+                // Never wider than the map itself
+                let text = match text.width() > inner.width as usize {
+                    true => Cow::Owned(truncate_to_width(&text, inner.width as usize)),
+                    false => text,
+                };
+                let width = text.width() as u16;
+                let x = (inner.x + col as u16).saturating_sub(width / 2).max(inner.x);
+                let rect = TuiRect::new(x, inner.y + row as u16, width, 1);
+                Some((name.as_str(), text, LabelCandidate { rect, weight: mp.0.iter().map(poly_area).sum() }))
+            })
+            .collect()
+    }
+
+    /// Label the equator, the prime meridian and the outermost graticule lines: latitudes
+    /// down the left edge of `inner`, longitudes along its bottom (where each meridian
+    /// meets the latitude `bottom`), leaving out any that would run into one already printed
+    fn graticule_labels(
+        &self,
+        inner: TuiRect,
+        (x_bounds, y_bounds): ([f64; 2], [f64; 2]),
+        (meridians, parallels): (&[f64], &[f64]),
+        bottom: f64,
+        buf: &mut Buffer,
     ) {
-        // Helper closure to draw a polygon path in a given color
-        let draw_poly = |ctx: &mut ratatui::widgets::canvas::Context, poly: &Polygon<f64>, color: Color| {
-            for window in poly.exterior().0.windows(2) {
+        if inner.width == 0 || inner.height == 0 {
+            return;
+        }
+        let labeled = |lines: &[f64]| -> Vec<f64> {
+            let mut picked: Vec<f64> = lines.iter().copied().filter(|&v| v == 0.0).collect();
+            picked.extend(lines.first().into_iter().chain(lines.last()).copied().filter(|&v| v != 0.0));
+            picked.dedup();
+            picked
+        };
+        let (span_x, span_y) = (x_bounds[1] - x_bounds[0], y_bounds[1] - y_bounds[0]);
+        let degree = self.symbols.degree;
+        let mut texts = Vec::new();
+        for lat in labeled(parallels) {
+            let y = self.to_canvas(0.0, lat).1;
+            let row = ((y_bounds[1] - y) / span_y * inner.height as f64).floor().clamp(0.0, inner.height as f64 - 1.0);
+            texts.push((inner.x, inner.y + row as u16, degree_label(lat, 'N', 'S', degree)));
+        }
+        for lon in labeled(meridians) {
+            let x = self.to_canvas(lon, bottom).0;
+            let col = ((x - x_bounds[0]) / span_x * inner.width as f64).floor().clamp(0.0, inner.width as f64 - 1.0);
+            let text = degree_label(in_frame(lon, false), 'E', 'W', degree);
+            let width = text.chars().count() as u16;
+            let x = (inner.x + col as u16).saturating_sub(width / 2).min(inner.right().saturating_sub(width)).max(inner.x);
+            texts.push((x, inner.bottom() - 1, text));
+        }
+        let mut placed: Vec<TuiRect> = Vec::new();
+        for (x, y, text) in texts {
+            let rect = TuiRect::new(x, y, text.chars().count() as u16, 1);
+            let padded = TuiRect { x: rect.x.saturating_sub(1), width: rect.width + 2, ..rect };
+            if inner.intersection(rect) == rect && !placed.iter().any(|p| p.intersects(padded)) {
+                buf.set_string(x, y, &text, Style::default().fg(GRATICULE_COLOR));
+                placed.push(rect);
+            }
+        }
+    }
+
+    /// Canvas point of `(lon, lat)` under the widget's projection
+    fn to_canvas(&self, lon: f64, lat: f64) -> (f64, f64) {
+        self.projector.project(lon, lat)
+    }
+
+    /// `mp` moved onto the canvas under the widget's projection
+    fn project_shape(&self, mp: &MultiPolygon<f64>) -> MultiPolygon<f64> {
+        mp.map_coords(|c| {
+            let (x, y) = self.to_canvas(c.x, c.y);
+            Coord { x, y }
+        })
+    }
+
+    /// Canvas box around the corners of the box `([x0, x1], [y0, y1])` of degrees
+    fn canvas_box(&self, ([x0, x1], [y0, y1]): ([f64; 2], [f64; 2])) -> ([f64; 2], [f64; 2]) {
+        let corners = [self.to_canvas(x0, y0), self.to_canvas(x0, y1), self.to_canvas(x1, y0), self.to_canvas(x1, y1)];
+        let xs = corners.map(|c| c.0);
+        let ys = corners.map(|c| c.1);
+        (
+            [xs.iter().copied().fold(f64::INFINITY, f64::min), xs.iter().copied().fold(f64::NEG_INFINITY, f64::max)],
+            [ys.iter().copied().fold(f64::INFINITY, f64::min), ys.iter().copied().fold(f64::NEG_INFINITY, f64::max)],
+        )
+    }
+
+    /// Color of the feature called `name` under the highlight, following the layer
+    /// order in the type's docs
+    pub fn base_color(&self, name: &str) -> Color {
+        let key = country_key(name);
+        let outside_focus = self.view.focus.as_ref().is_some_and(|focus| *focus != key);
+        if self.neighbours.is_some_and(|neighbours| neighbours.contains(&key)) {
+            Color::Yellow
+        } else if outside_focus || self.view.outlying.contains(&key) {
+            Color::DarkGray
+        } else if let Some(&color) = self.colors.and_then(|colors| colors.get(&key)) {
+            color
+        } else if self.visited.is_some_and(|visited| visited.contains(&key)) {
+            Color::Gray
+        } else {
+            self.style.base
+        }
+    }
+}
+
+impl Widget for MapWidget<'_> {
+    fn render(self, area: TuiRect, buf: &mut Buffer) {
+        let view = self.view;
+
+        // Helper closures to draw a ring, and a polygon's border with its holes (lakes,
+        // enclaves) in a color of their own
+        let draw_ring = |ctx: &mut ratatui::widgets::canvas::Context, ring: &LineString<f64>, color: Color| {
+            for window in ring.0.windows(2) {
                 let a = window[0];
                 let b = window[1];
                 ctx.draw(&Line { x1: a.x, y1: a.y, x2: b.x, y2: b.y, color });
             }
-            if let (Some(first), Some(last)) = (poly.exterior().0.first(), poly.exterior().0.last()) {
+            if let (Some(first), Some(last)) = (ring.0.first(), ring.0.last()) {
                 ctx.draw(&Line { x1: last.x, y1: last.y, x2: first.x, y2: first.y, color });
             }
         };
+        let draw_poly = |ctx: &mut ratatui::widgets::canvas::Context, poly: &Polygon<f64>, color: Color, holes: Color| {
+            draw_ring(ctx, poly.exterior(), color);
+            for ring in poly.interiors() {
+                draw_ring(ctx, ring, holes);
+            }
+        };
+
+        let mut block = ratatui::widgets::Block::default()
+            .title(self.title)
+            .borders(ratatui::widgets::Borders::ALL)
+            .border_set(self.symbols.border)
+            .border_style(self.border_style);
+        if let Some(legend) = self.legend {
+            block = block.title_bottom(legend);
+        }
 
+        let inner = block.inner(area);
+        let viewport = self.viewport.unwrap_or((view.x_bounds, view.y_bounds));
+        let (x_bounds, y_bounds) = view.canvas_bounds(self.projection, viewport, inner);
+        let labels = if self.labels {
+            let candidates = self.label_candidates(inner, x_bounds, y_bounds);
+            let rects: Vec<_> = candidates.iter().map(|&(_, _, c)| c).collect();
+            place_labels(&rects, inner).into_iter().map(|(i, rect)| (candidates[i].0, candidates[i].1.clone(), rect)).collect()
+        } else {
+            Vec::new()
+        };
+        // Selected features (a continent's members, one country, or the given set)
+        let hl = self.highlight.map(|sel| view.highlight(sel));
+        let highlighted = |name: &str| match self.highlight_keys {
+            Some(keys) => keys.contains(&country_key(name)),
+            None => hl.as_ref().is_some_and(|hl| hl.contains(name)),
+        };
+        let hl_color = if self.pulse { self.style.bright_highlight() } else { self.style.highlight };
+        // Graticule: lines every `step` degrees inside the shown bounds, those of the
+        // viewport itself where the projection bends the meridians
+        let (geo_x, geo_y) = if self.projection.bends() { viewport } else { (x_bounds, y_bounds) };
+        let step = graticule_step(geo_x[1] - geo_x[0]);
+        let lon_range = view.lon_range();
+        let (meridians, parallels) = match self.graticule {
+            true => (graticule_lines(geo_x, lon_range, step), graticule_lines(geo_y, [-90.0, 90.0], step)),
+            false => (Vec::new(), Vec::new()),
+        };
+        let (y0, y1) = (geo_y[0].max(-90.0), geo_y[1].min(90.0));
+        let (x0, x1) = (geo_x[0].max(lon_range[0]), geo_x[1].min(lon_range[1]));
         let canvas = Canvas::default()
-            .block(ratatui::widgets::Block::default()
-                .title(title)
-                .borders(ratatui::widgets::Borders::ALL))
-            .x_bounds(self.x_bounds)
-            .y_bounds(self.y_bounds)
+            .block(block)
+            .background_color(self.style.background.unwrap_or(Color::Reset))
+            .marker(self.marker.unwrap_or(self.symbols.marker))
+            .x_bounds(x_bounds)
+            .y_bounds(y_bounds)
             .paint(|ctx| {
-                // Draw all features in white
-                for (_, mp) in &self.items {
+                // The graticule on a layer of its own, so outlines crossing it replace it
+                if !meridians.is_empty() || !parallels.is_empty() {
+                    // Meridians in short steps, as they curve under some projections
+                    let steps = ((y1 - y0) / MERIDIAN_STEP).ceil().max(1.0) as usize;
+                    for &lon in &meridians {
+                        let points: Vec<(f64, f64)> =
+                            (0..=steps).map(|i| self.to_canvas(lon, y0 + (y1 - y0) * i as f64 / steps as f64)).collect();
+                        for pair in points.windows(2) {
+                            let ((ax, ay), (bx, by)) = (pair[0], pair[1]);
+                            ctx.draw(&Line { x1: ax, y1: ay, x2: bx, y2: by, color: GRATICULE_COLOR });
+                        }
+                    }
+                    for &lat in &parallels {
+                        let ((ax, ay), (bx, by)) = (self.to_canvas(x0, lat), self.to_canvas(x1, lat));
+                        ctx.draw(&Line { x1: ax, y1: ay, x2: bx, y2: by, color: GRATICULE_COLOR });
+                    }
+                    ctx.layer();
+                }
+
+                // Draw every feature in its base color, largest first, then the highlight over
+                // them; a projection bending the meridians moves every point first
+                let outlines: Cow<[MultiPolygon<f64>]> = match self.projection.bends() {
+                    true => Cow::Owned(view.outlines().iter().map(|mp| self.project_shape(mp)).collect()),
+                    false => Cow::Borrowed(view.outlines()),
+                };
+                for ((name, _), mp) in view.items.iter().zip(outlines.iter()) {
+                    let color = self.base_color(name);
                     for poly in &mp.0 {
-                        draw_poly(ctx, poly, Color::White);
+                        draw_poly(ctx, poly, color, HOLE_COLOR);
                     }
                 }
 
-                // If highlighting, draw selected features in red
-                if let Some(sel) = highlight {
-                    let highlight_color = Color::Red;
-                    // Check if it's a continent (multiple countries)
-                    if let Some(countries) = self.continents.get(sel) {
-                        for (name, mp) in &self.items {
-                            if countries.contains(name) {
-                                for poly in &mp.0 {
-                                    draw_poly(ctx, poly, highlight_color);
-                                }
-                            }
-                        }
-                    } else {
-                        // Single country highlight
-                        for (name, mp) in &self.items {
-                            if name == sel {
-                                for poly in &mp.0 {
-                                    draw_poly(ctx, poly, highlight_color);
+                // If highlighting, draw the selected features in red
+                // One scanline per row of canvas dots (Braille has four to a cell)
+                let rows = inner.height as usize * 4;
+                for ((name, _), mp) in view.items.iter().zip(outlines.iter()) {
+                    if highlighted(name) {
+                        for poly in &mp.0 {
+                            if self.fill_highlight {
+                                for (y, x0, x1) in scanline_fill(poly, y_bounds, rows) {
+                                    ctx.draw(&Line { x1: x0, y1: y, x2: x1, y2: y, color: hl_color });
                                 }
                             }
+                            draw_poly(ctx, poly, hl_color, hl_color);
                         }
                     }
                 }
+
+                // A highlight of a cell or two (Malta, Singapore) is lost among its
+                // neighbours: box it in, a few cells wider than itself. A hint can ask
+                // for the box where the outline misleads the size check.
+                let shapes = view.items.iter().zip(outlines.iter()).filter(|((name, _), _)| highlighted(name));
+                let hl_bounds = match self.projection.bends() {
+                    true => bounds(shapes.map(|(_, drawn)| drawn)),
+                    false => bounds(shapes.map(|((_, mp), _)| mp)),
+                };
+                let forced = view.items.iter().any(|(name, _)| {
+                    highlighted(name) && view.hints.get(&country_key(name)).is_some_and(|h| h.force_marker)
+                });
+                if let Some(b) = hl_bounds
+                    && (forced || too_small_to_see(b, (x_bounds, y_bounds), area)) {
+                    let cell_w = (x_bounds[1] - x_bounds[0]) / area.width.saturating_sub(2).max(1) as f64;
+                    let cell_h = (y_bounds[1] - y_bounds[0]) / area.height.saturating_sub(2).max(1) as f64;
+                    ctx.draw(&Rectangle {
+                        x: b.0[0] - 1.5 * cell_w,
+                        y: b.1[0] - cell_h,
+                        width: b.0[1] - b.0[0] + 3.0 * cell_w,
+                        height: b.1[1] - b.1[0] + 2.0 * cell_h,
+                        color: hl_color,
+                    });
+                }
             });
-        f.render_widget(canvas, area);
+        canvas.render(area, buf);
+        self.graticule_labels(inner, (x_bounds, y_bounds), (&meridians, &parallels), y0, buf);
+        // The selection's names take its color, the rest are cyan
+        for (name, text, rect) in labels {
+            let color = if highlighted(name) { hl_color } else { Color::Cyan };
+            buf.set_string(rect.x, rect.y, text, Style::default().fg(color));
+        }
     }
 }
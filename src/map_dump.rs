@@ -0,0 +1,143 @@
+//! The `map` subcommand: draw a map with the TUI's map widget into an off-screen buffer
+//! and print it as text, without raw mode or the alternate screen.
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use std::{
+    error::Error,
+    io::{self, IsTerminal, Write},
+    path::Path,
+};
+use crate::{
+    cli::MapArgs,
+    config::Config,
+    data::{country_key, DataCache, GeoLevel},
+    error::AtlasError,
+    map_draw::MapView,
+    suggest,
+    theme::{self, Symbols},
+};
+
+/// Run `map`: resolve the name to the world, a continent or a country, render it at
+/// the requested size and write it to stdout, colored when stdout is a terminal
+pub fn run(args: MapArgs, base: &Path) -> Result<(), Box<dyn Error>> {
+    let mut cache = DataCache::new(base)?;
+    cache.set_low_mem(args.low_mem);
+    let config = Config::load(base);
+    let name = args.name.as_deref().unwrap_or("world");
+
+    let continents = cache.load_list(GeoLevel::World, "world")?;
+    let (level, title) = if country_key(name) == "world" {
+        (GeoLevel::World, "World".to_string())
+    } else if let Some(continent) = continents.iter().find(|c| country_key(c) == country_key(name)) {
+        (GeoLevel::Continent, continent.clone())
+    } else {
+        // Title the map with the country's name as the lists spell it
+        let countries: Vec<String> = cache.load_continent_mappings()?.into_values().flatten().collect();
+        match countries.iter().find(|c| country_key(c) == country_key(name)) {
+            Some(country) => (GeoLevel::Country, country.clone()),
+            // A country file the lists leave out can still be drawn
+            None if cache.geojson_path(&GeoLevel::Country, name).exists() => (GeoLevel::Country, name.to_string()),
+            None => {
+                let places = continents.iter().chain(&countries).map(String::as_str);
+                let suggestions = suggest::closest(name, places, 3).into_iter().map(str::to_string).collect();
+                return Err(AtlasError::UnknownName { name: name.to_string(), suggestions }.into());
+            }
+        }
+    };
+    let mut view = MapView::load(&mut cache, &level, &title, config.antarctica)
+        .map_err(|e| AtlasError::Data(format!("no map for '{}': {}", name, e)))?;
+    if config.merge_constituents {
+        view.merge_constituents(cache.constituents());
+    }
+    if level == GeoLevel::Country {
+        view.include_constituents(&title, &mut cache, config.merge_constituents);
+    }
+    view.set_simplify(config.simplify_epsilon);
+
+    let color = !args.no_color && io::stdout().is_terminal();
+    let unicode = config.unicode.unwrap_or_else(|| theme::utf8_locale(|name| std::env::var(name).ok()));
+    let symbols = if unicode { &theme::UNICODE } else { &theme::ASCII };
+    let text = render_text(&view, &title, args.highlight.as_deref(), (args.width, args.height), color, symbols, &config);
+    io::stdout().lock().write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// `view` drawn by its widget with `symbols`, and the projection, fill and (with Unicode
+/// symbols) marker of `config`, into a `width` x `height` grid, one line per
+/// row with trailing blanks trimmed; with `color`, cells carry ANSI foreground colors
+pub fn render_text(
+    view: &MapView,
+    title: &str,
+    highlight: Option<&str>,
+    (width, height): (u16, u16),
+    color: bool,
+    symbols: &Symbols,
+    config: &Config,
+) -> String {
+    let area = Rect::new(0, 0, width, height);
+    let mut buf = Buffer::empty(area);
+    let mut widget = view
+        .widget()
+        .title(title)
+        .symbols(symbols)
+        .projection(config.projection)
+        .fill_highlight(config.fill_highlight);
+    if !symbols.ascii {
+        widget = widget.marker(config.map_marker.marker());
+    }
+    if let Some(sel) = highlight {
+        // Widen the view like the TUI does, so an outlying highlight stays in frame
+        widget = widget.highlight(sel).viewport(view.preferred_viewport(sel));
+    } else if let Some(viewport) = view.hinted_viewport(title) {
+        widget = widget.viewport(viewport);
+    }
+    widget.render(area, &mut buf);
+    if symbols.ascii {
+        theme::fold_to_ascii(&mut buf);
+    }
+
+    let mut out = String::new();
+    for y in 0..height {
+        let mut line = String::new();
+        let mut current = Color::Reset;
+        for x in 0..width {
+            let cell = &buf[(x, y)];
+            if color && cell.fg != current {
+                line.push_str(&sgr(cell.fg));
+                current = cell.fg;
+            }
+            line.push_str(cell.symbol());
+        }
+        if color && current != Color::Reset {
+            line.push_str(&sgr(Color::Reset));
+        }
+        out.push_str(line.trim_end_matches(' '));
+        out.push('\n');
+    }
+    out
+}
+
+/// ANSI escape setting the foreground to `color`
+fn sgr(color: Color) -> String {
+    let code = match color {
+        Color::Reset => "39".to_string(),
+        Color::Black => "30".to_string(),
+        Color::Red => "31".to_string(),
+        Color::Green => "32".to_string(),
+        Color::Yellow => "33".to_string(),
+        Color::Blue => "34".to_string(),
+        Color::Magenta => "35".to_string(),
+        Color::Cyan => "36".to_string(),
+        Color::Gray => "37".to_string(),
+        Color::DarkGray => "90".to_string(),
+        Color::LightRed => "91".to_string(),
+        Color::LightGreen => "92".to_string(),
+        Color::LightYellow => "93".to_string(),
+        Color::LightBlue => "94".to_string(),
+        Color::LightMagenta => "95".to_string(),
+        Color::LightCyan => "96".to_string(),
+        Color::White => "97".to_string(),
+        Color::Indexed(i) => format!("38;5;{}", i),
+        Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+    };
+    format!("\x1b[{}m", code)
+}
@@ -0,0 +1,89 @@
+//! Where each kind of file lives: bundled data next to the app, user state and
+//! caches in the XDG directories when the data directory can't be written.
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Application directory name under the XDG state and cache roots
+const APP_DIR: &str = "rustatlas";
+
+/// Kinds of files the app reads or writes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Artifact {
+    Data,  // bundled maps, lists and datasets (read only)
+    State, // session and other user state
+    Cache, // derived files that can be rebuilt
+}
+
+/// Directory for `kind` files. Everything stays in `data_dir` while it is writable;
+/// otherwise state and caches go to `$XDG_STATE_HOME` / `$XDG_CACHE_HOME`
+/// (default `~/.local/state`, `~/.cache`). `var` looks up environment variables.
+pub fn select_dir(kind: Artifact, data_dir: &Path, read_only: bool, var: impl Fn(&str) -> Option<String>) -> PathBuf {
+    let (xdg, fallback) = match kind {
+        Artifact::Data => return data_dir.to_path_buf(),
+        _ if !read_only => return data_dir.to_path_buf(),
+        Artifact::State => ("XDG_STATE_HOME", ".local/state"),
+        Artifact::Cache => ("XDG_CACHE_HOME", ".cache"),
+    };
+    let root = var(xdg)
+        .filter(|v| Path::new(v).is_absolute())
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").filter(|h| !h.is_empty()).map(|h| Path::new(&h).join(fallback)))
+        .unwrap_or_else(env::temp_dir);
+    root.join(APP_DIR)
+}
+
+/// Whether files can be created in `dir`, checked by writing and removing a probe file
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".rustatlas-write-probe");
+    let ok = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    ok
+}
+
+/// Resolved directories for one data directory
+pub struct Paths {
+    data: PathBuf,
+    state: PathBuf,
+    cache: PathBuf,
+    pub read_only: bool,
+}
+
+impl Paths {
+    /// Check whether `data_dir` is writable and pick the directory of every artifact kind
+    pub fn detect(data_dir: &Path) -> Self {
+        let read_only = !is_writable(data_dir);
+        let var = |name: &str| env::var(name).ok();
+        Self {
+            data: data_dir.to_path_buf(),
+            state: select_dir(Artifact::State, data_dir, read_only, var),
+            cache: select_dir(Artifact::Cache, data_dir, read_only, var),
+            read_only,
+        }
+    }
+
+    /// Path of the file `name` of the given kind
+    pub fn file(&self, kind: Artifact, name: &str) -> PathBuf {
+        match kind {
+            Artifact::Data => self.data.join(name),
+            Artifact::State => self.state.join(name),
+            Artifact::Cache => self.cache.join(name),
+        }
+    }
+
+    /// Startup notice when user state was moved out of a read-only data directory
+    pub fn notice(&self) -> Option<String> {
+        if !self.read_only {
+            return None;
+        }
+        let state = match env::var("HOME") {
+            Ok(home) if !home.is_empty() => match self.state.strip_prefix(&home) {
+                Ok(rest) => Path::new("~").join(rest),
+                Err(_) => self.state.clone(),
+            },
+            _ => self.state.clone(),
+        };
+        Some(format!("Katalog danych tylko do odczytu; stan użytkownika w {}", state.display()))
+    }
+}
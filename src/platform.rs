@@ -0,0 +1,319 @@
+//! Terminal setup, teardown and key input behind `TerminalDriver`, so the TUI runs on
+//! crossterm (default feature `backend-crossterm`), termion (`backend-termion`, Unix only)
+//! or headless in memory. Keys from every driver arrive as crossterm `KeyCode`s, the key
+//! model `keys.rs` binds actions to; mouse clicks, drags and moves as `MouseInput`s. The
+//! terminal is handed over to the editor for Ctrl+E and taken back (see `editor.rs`).
+use crossterm::event::KeyCode;
+use ratatui::{backend::{Backend, TestBackend}, buffer::Buffer, Terminal};
+use std::{collections::VecDeque, io, time::{Duration, Instant}};
+use crate::{editor, keys, state::{AppState, MouseInput}, ui, view_model::Drawn};
+
+/// Time the event loop waits for a key before redrawing
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the driver's event source produced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseInput),
+    Idle, // no key within the timeout
+    End,  // the source is exhausted (a headless script ran out, stdin closed)
+}
+
+/// A terminal the TUI can draw on and read keys from
+pub trait TerminalDriver {
+    type Backend: Backend;
+
+    /// Prepare the terminal (raw mode, alternate screen) and return it
+    fn enter(&mut self) -> io::Result<Terminal<Self::Backend>>;
+
+    /// Next key pressed or mouse event within `timeout`
+    fn next_input(&mut self, timeout: Duration) -> io::Result<Input>;
+
+    /// Sound the terminal bell
+    fn bell(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()>;
+
+    /// Restore the terminal to how `enter` found it
+    fn leave(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()>;
+
+    /// Hand the terminal to another program, as `leave` does, until `resume`
+    fn suspend(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()>;
+
+    /// Take the terminal back after `suspend` and redraw it whole on the next frame
+    fn resume(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()>;
+}
+
+/// Draw and handle keys until the user quits or the input ends, then restore the terminal
+pub fn run<D: TerminalDriver>(driver: &mut D, state: &mut AppState) -> io::Result<()> {
+    let mut terminal = driver.enter()?;
+    let result = event_loop(driver, &mut terminal, state);
+    driver.leave(&mut terminal)?;
+    result
+}
+
+fn event_loop<D: TerminalDriver>(driver: &mut D, terminal: &mut Terminal<D::Backend>, state: &mut AppState) -> io::Result<()> {
+    loop {
+        let mut drawn = Drawn::default();
+        terminal.draw(|f| drawn = ui::draw(f, &state.view_model()))?;
+        state.apply_drawn(drawn);
+        state.tick(Instant::now());
+        match driver.next_input(POLL_INTERVAL)? {
+            Input::Key(key) if state.handle_input(key) => return Ok(()),
+            Input::Mouse(mouse) => state.handle_mouse(mouse),
+            Input::End => return Ok(()),
+            _ => {}
+        }
+        if state.take_bell() {
+            driver.bell(terminal)?;
+        }
+        if let Some(edit) = state.take_edit() {
+            let outcome = match driver.suspend(terminal) {
+                Ok(()) => {
+                    let ran = editor::run(&edit.command, &edit.path);
+                    driver.resume(terminal)?;
+                    ran
+                }
+                Err(e) => Err(format!("nie można oddać terminala edytorowi: {}", e)),
+            };
+            state.finish_edit(edit, outcome);
+        }
+    }
+}
+
+/// Write the BEL control character to `out`, which the terminal turns into its bell
+pub fn ring(out: &mut impl io::Write) -> io::Result<()> {
+    out.write_all(b"\x07")?;
+    out.flush()
+}
+
+/// The default driver: crossterm on stdout
+#[cfg(feature = "backend-crossterm")]
+pub struct CrosstermDriver;
+
+#[cfg(feature = "backend-crossterm")]
+impl TerminalDriver for CrosstermDriver {
+    type Backend = ratatui::backend::CrosstermBackend<io::Stdout>;
+
+    fn enter(&mut self) -> io::Result<Terminal<Self::Backend>> {
+        use crossterm::{event::EnableMouseCapture, execute, terminal::{enable_raw_mode, EnterAlternateScreen}};
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        Terminal::new(ratatui::backend::CrosstermBackend::new(stdout))
+    }
+
+    fn next_input(&mut self, timeout: Duration) -> io::Result<Input> {
+        use crossterm::event::{self, Event, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        if !event::poll(timeout)? {
+            return Ok(Input::Idle);
+        }
+        Ok(match event::read()? {
+            // Ctrl+letter arrives as its control character (see `keys::ctrl`)
+            Event::Key(KeyEvent { code, modifiers, kind: KeyEventKind::Press, .. }) => Input::Key(match code {
+                KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) && c.is_ascii_alphabetic() => {
+                    keys::ctrl(c.to_ascii_lowercase())
+                }
+                other => other,
+            }),
+            Event::Mouse(MouseEvent { kind, column, row, .. }) => match kind {
+                MouseEventKind::Down(MouseButton::Left) => Input::Mouse(MouseInput::Click { column, row }),
+                MouseEventKind::Drag(MouseButton::Left) => Input::Mouse(MouseInput::Drag { column, row }),
+                MouseEventKind::Moved => Input::Mouse(MouseInput::Move { column, row }),
+                _ => Input::Idle,
+            },
+            _ => Input::Idle,
+        })
+    }
+
+    fn bell(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        ring(terminal.backend_mut())
+    }
+
+    fn leave(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        use crossterm::{event::DisableMouseCapture, execute, terminal::{disable_raw_mode, LeaveAlternateScreen}};
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+        terminal.show_cursor()
+    }
+
+    fn suspend(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        self.leave(terminal)
+    }
+
+    fn resume(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        use crossterm::{event::EnableMouseCapture, execute, terminal::{enable_raw_mode, EnterAlternateScreen}};
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+        terminal.clear()
+    }
+}
+
+/// Driver for terminals where crossterm misbehaves: termion on stdout, with events read
+/// by a thread since termion's reader blocks. Termion reports clicks and drags but not plain moves.
+#[cfg(all(unix, feature = "backend-termion"))]
+pub struct TermionDriver {
+    events: Option<std::sync::mpsc::Receiver<io::Result<termion::event::Event>>>,
+}
+
+#[cfg(all(unix, feature = "backend-termion"))]
+impl TermionDriver {
+    pub fn new() -> Self {
+        TermionDriver { events: None }
+    }
+}
+
+#[cfg(all(unix, feature = "backend-termion"))]
+impl Default for TermionDriver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(unix, feature = "backend-termion"))]
+type TermionOut = termion::input::MouseTerminal<termion::screen::AlternateScreen<termion::raw::RawTerminal<io::Stdout>>>;
+
+#[cfg(all(unix, feature = "backend-termion"))]
+impl TerminalDriver for TermionDriver {
+    type Backend = ratatui::backend::TermionBackend<TermionOut>;
+
+    fn enter(&mut self) -> io::Result<Terminal<Self::Backend>> {
+        use termion::{input::{MouseTerminal, TermRead}, raw::IntoRawMode, screen::IntoAlternateScreen};
+        let out = MouseTerminal::from(io::stdout().into_raw_mode()?.into_alternate_screen()?);
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for event in io::stdin().events() {
+                if tx.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        self.events = Some(rx);
+        Terminal::new(ratatui::backend::TermionBackend::new(out))
+    }
+
+    fn next_input(&mut self, timeout: Duration) -> io::Result<Input> {
+        use std::sync::mpsc::RecvTimeoutError;
+        use termion::event::{Event, MouseButton, MouseEvent};
+        let Some(events) = &self.events else { return Ok(Input::End) };
+        match events.recv_timeout(timeout) {
+            Ok(event) => Ok(match event? {
+                Event::Key(key) => key_from_termion(key).map_or(Input::Idle, Input::Key),
+                // Termion counts cells from 1
+                Event::Mouse(MouseEvent::Press(MouseButton::Left, x, y)) => {
+                    Input::Mouse(MouseInput::Click { column: x.saturating_sub(1), row: y.saturating_sub(1) })
+                }
+                Event::Mouse(MouseEvent::Hold(x, y)) => {
+                    Input::Mouse(MouseInput::Drag { column: x.saturating_sub(1), row: y.saturating_sub(1) })
+                }
+                _ => Input::Idle,
+            }),
+            Err(RecvTimeoutError::Timeout) => Ok(Input::Idle),
+            Err(RecvTimeoutError::Disconnected) => Ok(Input::End),
+        }
+    }
+
+    fn bell(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        ring(terminal.backend_mut())
+    }
+
+    fn leave(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        // Raw mode, mouse reporting and the alternate screen end when the terminal's writer is dropped
+        self.events = None;
+        terminal.show_cursor()
+    }
+
+    fn suspend(&mut self, _terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        // The thread reading stdin can't be stopped and would take the editor's keys
+        Err(io::Error::new(io::ErrorKind::Unsupported, "not supported with the termion backend"))
+    }
+
+    fn resume(&mut self, _terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The crate's key for a termion key; None for keys nothing can be bound to
+#[cfg(all(unix, feature = "backend-termion"))]
+pub fn key_from_termion(key: termion::event::Key) -> Option<KeyCode> {
+    use termion::event::Key;
+    match key {
+        Key::Char('\n' | '\r') => Some(KeyCode::Enter),
+        Key::Char('\t') => Some(KeyCode::Tab),
+        Key::Char(c) => Some(KeyCode::Char(c)),
+        Key::Up => Some(KeyCode::Up),
+        Key::Down => Some(KeyCode::Down),
+        Key::Left => Some(KeyCode::Left),
+        Key::Right => Some(KeyCode::Right),
+        Key::Backspace => Some(KeyCode::Backspace),
+        Key::Esc => Some(KeyCode::Esc),
+        Key::Ctrl(c) if c.is_ascii_alphabetic() => Some(keys::ctrl(c.to_ascii_lowercase())),
+        _ => None,
+    }
+}
+
+/// Driver without a terminal: draws into memory and replays a fixed list of keys and
+/// mouse events, for scripted runs and tests
+pub struct HeadlessDriver {
+    width: u16,
+    height: u16,
+    inputs: VecDeque<Input>,
+    last_frame: Option<Buffer>,
+    bell: Vec<u8>,    // what `ring` wrote, a BEL per bell
+    suspended: usize, // times the terminal was handed to another program
+}
+
+impl HeadlessDriver {
+    pub fn new(width: u16, height: u16, keys: impl IntoIterator<Item = KeyCode>) -> Self {
+        Self::with_inputs(width, height, keys.into_iter().map(Input::Key))
+    }
+
+    /// Driver replaying `inputs`, keys and mouse events mixed, in order
+    pub fn with_inputs(width: u16, height: u16, inputs: impl IntoIterator<Item = Input>) -> Self {
+        HeadlessDriver { width, height, inputs: inputs.into_iter().collect(), last_frame: None, bell: Vec::new(), suspended: 0 }
+    }
+
+    /// Screen contents when the run ended
+    pub fn last_frame(&self) -> Option<&Buffer> {
+        self.last_frame.as_ref()
+    }
+
+    /// How many times the bell rang
+    pub fn bells(&self) -> usize {
+        self.bell.iter().filter(|&&b| b == 0x07).count()
+    }
+
+    /// How many times the terminal was handed to the editor
+    pub fn suspensions(&self) -> usize {
+        self.suspended
+    }
+}
+
+impl TerminalDriver for HeadlessDriver {
+    type Backend = TestBackend;
+
+    fn enter(&mut self) -> io::Result<Terminal<Self::Backend>> {
+        Terminal::new(TestBackend::new(self.width, self.height))
+    }
+
+    fn next_input(&mut self, _timeout: Duration) -> io::Result<Input> {
+        Ok(self.inputs.pop_front().unwrap_or(Input::End))
+    }
+
+    fn bell(&mut self, _terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        ring(&mut self.bell)
+    }
+
+    fn leave(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        self.last_frame = Some(terminal.backend().buffer().clone());
+        Ok(())
+    }
+
+    fn suspend(&mut self, _terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        self.suspended += 1;
+        Ok(())
+    }
+
+    fn resume(&mut self, terminal: &mut Terminal<Self::Backend>) -> io::Result<()> {
+        terminal.clear()
+    }
+}
@@ -0,0 +1,174 @@
+//! View preferences remembered per place in `views.json`: how the list is sorted, the
+//! ranking filter, the GDP colors and the country names on the map. An entry is keyed by
+//! place (`"world"`, `"continent:africa"`) or by level (`"continent"`, for every continent
+//! without its own); a setting missing from both falls back to the session's own.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::{config::WorldOrder, data::country_key};
+
+/// Order of a list: by name, by a column of the ranking (continents) or west to east (world)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListSort {
+    Name,
+    Population,
+    Area,
+    Gdp,
+    Geographic,
+}
+
+impl From<WorldOrder> for ListSort {
+    fn from(order: WorldOrder) -> Self {
+        match order {
+            WorldOrder::Alphabetical => ListSort::Name,
+            WorldOrder::Geographic => ListSort::Geographic,
+        }
+    }
+}
+
+impl ListSort {
+    /// Whether a list at `level` can be sorted this way
+    pub fn fits(self, level: &str) -> bool {
+        match self {
+            ListSort::Name => true,
+            ListSort::Geographic => level == WORLD,
+            ListSort::Population | ListSort::Area | ListSort::Gdp => level == CONTINENT,
+        }
+    }
+
+    /// The continent order `s` switches to next
+    pub fn next(self) -> Self {
+        match self {
+            ListSort::Name => ListSort::Population,
+            ListSort::Population => ListSort::Area,
+            ListSort::Area => ListSort::Gdp,
+            ListSort::Gdp | ListSort::Geographic => ListSort::Name,
+        }
+    }
+
+    /// The world list order this stands for
+    pub fn world_order(self) -> WorldOrder {
+        match self {
+            ListSort::Geographic => WorldOrder::Geographic,
+            _ => WorldOrder::Alphabetical,
+        }
+    }
+
+    /// Name of the order for the list title
+    pub fn label(self) -> &'static str {
+        match self {
+            ListSort::Name => "nazwa",
+            ListSort::Population => "populacja",
+            ListSort::Area => "powierzchnia",
+            ListSort::Gdp => "GDP",
+            ListSort::Geographic => "zachód→wschód",
+        }
+    }
+}
+
+/// Settings saved for one place or level; those left out aren't overridden
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewPrefs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ListSort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>, // ranking filter expression, as typed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub choropleth: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub labels: Option<bool>,
+}
+
+/// Every saved entry, persisted in `views.json`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlacePrefs {
+    pub entries: BTreeMap<String, ViewPrefs>, // place or level key -> its settings
+}
+
+/// Level key of the world, which is also its place key
+pub const WORLD: &str = "world";
+/// Level key shared by every continent
+pub const CONTINENT: &str = "continent";
+
+/// Place key of `continent`
+pub fn continent_key(continent: &str) -> String {
+    format!("{}:{}", CONTINENT, country_key(continent))
+}
+
+/// Where a setting in force came from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Source {
+    Place,  // saved for this place
+    Level,  // saved for every place of its level
+    #[default]
+    Global, // the session's own, as toggled elsewhere
+}
+
+impl Source {
+    /// Short note for the list title; nothing for the session's own
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            Source::Place => Some("tu"),
+            Source::Level => Some("poziom"),
+            Source::Global => None,
+        }
+    }
+}
+
+/// Settings of a view, e.g. those in force or the session's own
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewSettings {
+    pub sort: ListSort,
+    pub filter: String,
+    pub choropleth: bool,
+    pub labels: bool,
+}
+
+/// Where each setting of a resolved `ViewSettings` came from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sources {
+    pub sort: Source,
+    pub filter: Source,
+    pub choropleth: Source,
+    pub labels: Source,
+}
+
+/// One setting: the place's entry if it has it, else the level's, else `global`
+pub fn resolve<T>(place: Option<&ViewPrefs>, level: Option<&ViewPrefs>, field: impl Fn(&ViewPrefs) -> Option<T>, global: T) -> (T, Source) {
+    if let Some(value) = place.and_then(&field) {
+        (value, Source::Place)
+    } else if let Some(value) = level.and_then(&field) {
+        (value, Source::Level)
+    } else {
+        (global, Source::Global)
+    }
+}
+
+impl PlacePrefs {
+    /// Settings in force at the place `place` of level `level`, each resolved on its own
+    /// (see `resolve`), with `global` for those saved nowhere. A sort that doesn't fit the
+    /// level, e.g. by population on the world list, is passed over.
+    pub fn effective(&self, place: &str, level: &str, global: &ViewSettings) -> (ViewSettings, Sources) {
+        // The world is its own level
+        let level_entry = self.entries.get(level).filter(|_| place != level);
+        let place = self.entries.get(place);
+        let (sort, sort_from) = resolve(place, level_entry, |p| p.sort.filter(|s| s.fits(level)), global.sort);
+        let (filter, filter_from) = resolve(place, level_entry, |p| p.filter.clone(), global.filter.clone());
+        let (choropleth, choropleth_from) = resolve(place, level_entry, |p| p.choropleth, global.choropleth);
+        let (labels, labels_from) = resolve(place, level_entry, |p| p.labels, global.labels);
+        let sources = Sources { sort: sort_from, filter: filter_from, choropleth: choropleth_from, labels: labels_from };
+        (ViewSettings { sort, filter, choropleth, labels }, sources)
+    }
+
+    /// Save `settings` as the entry of `place`, replacing what it had
+    pub fn save(&mut self, place: &str, settings: &ViewSettings) {
+        let entry = ViewPrefs {
+            sort: Some(settings.sort),
+            filter: Some(settings.filter.clone()).filter(|f| !f.is_empty()),
+            choropleth: Some(settings.choropleth),
+            labels: Some(settings.labels),
+        };
+        self.entries.insert(place.to_string(), entry);
+    }
+}
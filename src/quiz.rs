@@ -0,0 +1,194 @@
+//! The map quiz: a country is highlighted on the map and picked from a few names. Right
+//! and wrong answers are kept per country in `quiz.json`, and questions lean towards the
+//! countries missed most; review mode asks only those still owed a review.
+use rand::{rng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::data::country_key;
+
+/// Questions in one quiz, fewer when there aren't that many countries to ask about
+pub const QUESTIONS: usize = 10;
+/// Names offered for each question, the right one among them
+pub const OPTIONS: usize = 4;
+
+/// Right and wrong answers for one country, across quizzes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    pub hits: u32,
+    pub misses: u32,
+}
+
+/// Answers per country, persisted in `quiz.json`
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QuizStats {
+    pub countries: BTreeMap<String, Tally>, // canonical country key -> answers
+}
+
+impl QuizStats {
+    /// Count an answer about `country`
+    pub fn record(&mut self, country: &str, correct: bool) {
+        let tally = self.countries.entry(country_key(country)).or_default();
+        if correct {
+            tally.hits += 1;
+        } else {
+            tally.misses += 1;
+        }
+    }
+
+    /// How strongly `country` is drawn as a question (see `weight`)
+    pub fn weight(&self, country: &str) -> u32 {
+        weight(self.countries.get(&country_key(country)).copied().unwrap_or_default())
+    }
+}
+
+/// Draw weight of a country with `tally`: 1 + 3 × misses − hits, at least 1, so a country
+/// never played or always answered right is as likely as any other, and each miss adds
+/// three times its pull until hits wear it down
+pub fn weight(tally: Tally) -> u32 {
+    (1 + 3 * tally.misses as i64 - tally.hits as i64).max(1) as u32
+}
+
+/// Index of the entry of `weights` that `roll` (in 0..sum of the weights) lands on; None
+/// when the roll is past the end, e.g. for no weights at all
+pub fn pick(weights: &[u32], roll: u64) -> Option<usize> {
+    let mut left = roll;
+    for (i, &w) in weights.iter().enumerate() {
+        if left < w as u64 {
+            return Some(i);
+        }
+        left -= w as u64;
+    }
+    None
+}
+
+/// Which countries a quiz asks about
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuizMode {
+    All,    // every country of the map, the missed ones more often
+    Review, // only countries whose weight is above 1: missed more than hits made up for
+}
+
+/// A country to ask about and the continent it is on
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub continent: String,
+    pub country: String,
+}
+
+/// The current question: the country highlighted and the names offered
+#[derive(Clone, Debug, PartialEq)]
+pub struct Question {
+    pub answer: Candidate,
+    pub options: Vec<String>, // shuffled, the answer among them
+    pub selected: usize,      // option under the cursor
+}
+
+/// One answered question
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answer {
+    pub continent: String,
+    pub country: String,
+    pub correct: bool,
+}
+
+/// A quiz in progress, or finished and showing its summary
+#[derive(Clone, Debug)]
+pub struct Quiz {
+    pub mode: QuizMode,
+    everyone: Vec<Candidate>,       // countries of the map, for the wrong options
+    pool: Vec<Candidate>,           // countries that can still be asked
+    length: usize,                  // questions in this quiz
+    pub question: Option<Question>, // None once every question is answered
+    pub answers: Vec<Answer>,
+}
+
+impl Quiz {
+    /// A quiz over `candidates` in `mode`, weighted by `stats`; None when there is nothing
+    /// to ask (review mode with nothing missed, or fewer than two countries)
+    pub fn start(mode: QuizMode, candidates: Vec<Candidate>, stats: &QuizStats) -> Option<Self> {
+        let everyone = candidates.clone();
+        let pool: Vec<Candidate> = match mode {
+            QuizMode::All => candidates,
+            QuizMode::Review => candidates.into_iter().filter(|c| stats.weight(&c.country) > 1).collect(),
+        };
+        if pool.is_empty() || everyone.len() < 2 {
+            return None;
+        }
+        let length = pool.len().min(QUESTIONS);
+        let mut quiz = Self { mode, everyone, pool, length, question: None, answers: Vec::new() };
+        quiz.next_question(stats);
+        Some(quiz)
+    }
+
+    /// Number of the current question (from 1) and how many there are
+    pub fn progress(&self) -> (usize, usize) {
+        ((self.answers.len() + 1).min(self.length), self.length)
+    }
+
+    /// Answer the current question with the option under the cursor, count it in
+    /// `stats` and ask the next one; the answer given, or None when no question is open
+    pub fn answer(&mut self, stats: &mut QuizStats) -> Option<Answer> {
+        let question = self.question.take()?;
+        let correct = question.options.get(question.selected) == Some(&question.answer.country);
+        stats.record(&question.answer.country, correct);
+        let answer = Answer { continent: question.answer.continent, country: question.answer.country, correct };
+        self.answers.push(answer.clone());
+        if self.answers.len() < self.length {
+            self.next_question(stats);
+        }
+        Some(answer)
+    }
+
+    /// Draw a question from the pool by weight, with wrong options from the same
+    /// continent where there are enough of them
+    fn next_question(&mut self, stats: &QuizStats) {
+        let weights: Vec<u32> = self.pool.iter().map(|c| stats.weight(&c.country)).collect();
+        let total: u64 = weights.iter().map(|&w| w as u64).sum();
+        let Some(i) = (total > 0).then(|| rng().random_range(0..total)).and_then(|roll| pick(&weights, roll)) else {
+            return;
+        };
+        let answer = self.pool.swap_remove(i);
+
+        let mut others: Vec<&Candidate> = self.everyone.iter().filter(|c| c.country != answer.country).collect();
+        others.shuffle(&mut rng());
+        // Neighbours on the same continent first: telling them apart is the point
+        others.sort_by_key(|c| c.continent != answer.continent);
+        let mut options: Vec<String> = others.iter().take(OPTIONS - 1).map(|c| c.country.clone()).collect();
+        options.push(answer.country.clone());
+        options.shuffle(&mut rng());
+        self.question = Some(Question { answer, options, selected: 0 });
+    }
+}
+
+/// How a quiz went
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    pub score: usize,
+    pub total: usize,
+    pub accuracy: f64,                           // share right, 0.0 with no answers
+    pub weakest: Option<(String, usize, usize)>, // continent with the lowest share right: (name, right, asked)
+}
+
+/// Score, accuracy and weakest continent of `answers`. The weakest continent is the one
+/// with the lowest share of right answers, ties going to the one with more questions and
+/// then by name; None when every answer was right
+pub fn summarize(answers: &[Answer]) -> Summary {
+    let score = answers.iter().filter(|a| a.correct).count();
+    let total = answers.len();
+    let accuracy = if total == 0 { 0.0 } else { score as f64 / total as f64 };
+    let mut per_continent: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for a in answers {
+        let entry = per_continent.entry(a.continent.as_str()).or_default();
+        entry.0 += a.correct as usize;
+        entry.1 += 1;
+    }
+    let weakest = per_continent
+        .into_iter()
+        .filter(|&(_, (right, asked))| right < asked)
+        .min_by(|(_, (ra, aa)), (_, (rb, ab))| {
+            let (sa, sb) = (*ra as f64 / *aa as f64, *rb as f64 / *ab as f64);
+            sa.total_cmp(&sb).then(ab.cmp(aa))
+        })
+        .map(|(continent, (right, asked))| (continent.to_string(), right, asked));
+    Summary { score, total, accuracy, weakest }
+}
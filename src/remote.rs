@@ -0,0 +1,286 @@
+//! Data directory on a web server (`--data-dir http://…`, `online` feature). Each file is
+//! fetched once per run into a local cache keyed by the server's URL and revalidated with
+//! its ETag and Last-Modified; when the server can't be reached the cached copy is used
+//! and a notice says it may be stale. `https://` is spoken with rustls, trusting the
+//! Mozilla roots of webpki-roots.
+use rustls::{pki_types::ServerName, ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fs,
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+use crate::data::{write_atomic, DataSource};
+
+/// Longest wait for connecting, and for each read or write after that
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A server and the path prefix of the data directory on it
+#[derive(Clone, Debug, PartialEq)]
+struct Url {
+    tls: bool, // https://
+    host: String,
+    port: u16,
+    path: String, // starts and ends with '/'
+}
+
+impl Url {
+    /// Parse `http[s]://host[:port][/path]`
+    fn parse(url: &str) -> Result<Self, Box<dyn Error>> {
+        let (tls, rest) = match (url.strip_prefix("https://"), url.strip_prefix("http://")) {
+            (Some(rest), _) => (true, rest),
+            (None, Some(rest)) => (false, rest),
+            (None, None) => return Err(format!("not an http:// or https:// URL: {}", url).into()),
+        };
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let default_port = if tls { 443 } else { 80 };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| format!("bad port in {}", url))?),
+            None => (authority, default_port),
+        };
+        if host.is_empty() {
+            return Err(format!("no host in {}", url).into());
+        }
+        let path = format!("/{}", path.trim_end_matches('/'));
+        let path = if path == "/" { path } else { path + "/" };
+        Ok(Self { tls, host: host.to_string(), port, path })
+    }
+
+    /// Full URL, for keying the cache and for display
+    fn display(&self) -> String {
+        let scheme = if self.tls { "https" } else { "http" };
+        match (self.tls, self.port) {
+            (false, 80) | (true, 443) => format!("{}://{}{}", scheme, self.host, self.path),
+            (_, port) => format!("{}://{}:{}{}", scheme, self.host, port, self.path),
+        }
+    }
+}
+
+/// TLS settings shared by every https:// request: the webpki-roots trust anchors and
+/// rustls's ring provider
+fn tls_config() -> Result<Arc<ClientConfig>, Box<dyn Error>> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    if let Some(config) = CONFIG.get() {
+        return Ok(config.clone());
+    }
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(CONFIG.get_or_init(|| Arc::new(config)).clone())
+}
+
+/// Validators of a cached copy, kept beside it in `<name>.meta.json`
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// Status, lowercase header names and body of a response
+struct Response {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+}
+
+/// Data files on a web server, mirrored into a local cache directory
+pub struct HttpSource {
+    url: Url,
+    cache: PathBuf,                  // local copies and their validators
+    timeout: Duration,
+    checked: Mutex<HashSet<String>>, // names fetched or revalidated this run
+    notices: Mutex<Vec<String>>,     // problems not yet taken by the app
+}
+
+impl HttpSource {
+    /// Source for the data directory at `url`, cached in a directory of `cache_root`
+    /// named after the URL so different servers never share copies
+    pub fn new(url: &str, cache_root: &Path) -> Result<Self, Box<dyn Error>> {
+        let url = Url::parse(url)?;
+        let cache = cache_root.join(format!("{:016x}", fnv1a(url.display().as_bytes())));
+        fs::create_dir_all(&cache)?;
+        Ok(Self { url, cache, timeout: TIMEOUT, checked: Mutex::new(HashSet::new()), notices: Mutex::new(Vec::new()) })
+    }
+
+    /// Bring the cached copy of `name` up to date with the server
+    fn revalidate(&self, name: &str, local: &Path) -> Result<(), Box<dyn Error>> {
+        let meta = self.cache.join(format!("{}.meta.json", name));
+        let validators: Validators = match local.exists() {
+            true => fs::read(&meta).ok().and_then(|b| serde_json::from_slice(&b).ok()).unwrap_or_default(),
+            false => Validators::default(),
+        };
+        let response = self.get(name, &validators)?;
+        match response.status {
+            200 => {
+                write_atomic(local, &response.body)?;
+                let validators = Validators {
+                    etag: response.headers.get("etag").cloned(),
+                    last_modified: response.headers.get("last-modified").cloned(),
+                };
+                write_atomic(&meta, &serde_json::to_vec(&validators)?)?;
+            }
+            304 => {}
+            // Gone from the server: so is the copy
+            404 | 410 => {
+                let _ = fs::remove_file(local);
+                let _ = fs::remove_file(&meta);
+            }
+            status => return Err(format!("HTTP {}", status).into()),
+        }
+        Ok(())
+    }
+
+    /// GET `name`, conditionally on `validators`
+    fn get(&self, name: &str, validators: &Validators) -> Result<Response, Box<dyn Error>> {
+        let addr = (self.url.host.as_str(), self.url.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format!("{} has no address", self.url.host))?;
+        let mut stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        let mut request = format!(
+            "GET {}{} HTTP/1.1\r\nHost: {}\r\nUser-Agent: RustAtlas\r\nConnection: close\r\n",
+            self.url.path,
+            encode(name),
+            self.url.host
+        );
+        if let Some(etag) = &validators.etag {
+            request.push_str(&format!("If-None-Match: {}\r\n", etag));
+        }
+        if let Some(date) = &validators.last_modified {
+            request.push_str(&format!("If-Modified-Since: {}\r\n", date));
+        }
+        request.push_str("\r\n");
+
+        let mut raw = Vec::new();
+        if self.url.tls {
+            let name = ServerName::try_from(self.url.host.clone())?;
+            let mut tls = StreamOwned::new(ClientConnection::new(tls_config()?, name)?, stream);
+            tls.write_all(request.as_bytes())?;
+            match tls.read_to_end(&mut raw) {
+                // Servers often close without close_notify; a cut-short body is caught
+                // by its Content-Length or chunking below
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+                result => { result?; }
+            }
+        } else {
+            stream.write_all(request.as_bytes())?;
+            stream.read_to_end(&mut raw)?;
+        }
+        parse_response(&raw)
+    }
+}
+
+impl DataSource for HttpSource {
+    fn path(&self, name: &str) -> PathBuf {
+        let local = self.cache.join(name);
+        let first = self.checked.lock().map(|mut checked| checked.insert(name.to_string())).unwrap_or(false);
+        if first
+            && let Err(e) = self.revalidate(name, &local)
+            && local.exists()
+            && let Ok(mut notices) = self.notices.lock() {
+            notices.push(format!("{}: serwer niedostępny ({}), użyto zapisanej kopii, może być nieaktualna", name, e));
+        }
+        local
+    }
+
+    /// A copy or a file already looked for answers; anything else is taken to exist rather
+    /// than fetching every file a list names
+    fn exists(&self, name: &str) -> bool {
+        let checked = self.checked.lock().is_ok_and(|checked| checked.contains(name));
+        self.cache.join(name).exists() || !checked
+    }
+
+    fn dir(&self) -> &Path {
+        &self.cache
+    }
+
+    fn origin(&self) -> String {
+        self.url.display()
+    }
+
+    fn editable(&self) -> bool {
+        false
+    }
+
+    fn take_notices(&self) -> Vec<String> {
+        self.notices.lock().map(|mut notices| std::mem::take(&mut *notices)).unwrap_or_default()
+    }
+}
+
+/// Split a whole HTTP/1.1 response into status, headers and body, undoing chunked
+/// transfer encoding
+fn parse_response(raw: &[u8]) -> Result<Response, Box<dyn Error>> {
+    let end = raw.windows(4).position(|w| w == b"\r\n\r\n").ok_or("incomplete HTTP response")?;
+    let head = String::from_utf8_lossy(&raw[..end]);
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or("bad HTTP status line")?;
+    let headers: BTreeMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let mut body = raw[end + 4..].to_vec();
+    if headers.get("transfer-encoding").is_some_and(|te| te.eq_ignore_ascii_case("chunked")) {
+        body = dechunk(&body)?;
+    } else if let Some(length) = headers.get("content-length").and_then(|l| l.parse::<usize>().ok()) {
+        if body.len() < length {
+            return Err("connection closed before the whole file arrived".into());
+        }
+        body.truncate(length);
+    }
+    Ok(Response { status, headers, body })
+}
+
+/// Body of a chunked response
+fn dechunk(mut raw: &[u8]) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = raw.windows(2).position(|w| w == b"\r\n").ok_or("bad chunk")?;
+        let size_text = String::from_utf8_lossy(&raw[..line_end]);
+        let size = usize::from_str_radix(size_text.split(';').next().unwrap_or("").trim(), 16)?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if raw.len() < size + 2 {
+            return Err("connection closed before the whole file arrived".into());
+        }
+        body.extend_from_slice(&raw[..size]);
+        raw = &raw[size + 2..];
+    }
+}
+
+/// `name` percent-encoded for a URL path, segment by segment so the '/' of a nested file
+/// (`dataPKB/pkb.csv`) still separates directories
+pub fn encode(name: &str) -> String {
+    name.split('/').map(encode_segment).collect::<Vec<_>>().join("/")
+}
+
+/// One path segment percent-encoded
+fn encode_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// 64-bit FNV-1a, a hash that stays the same across builds for naming cache directories
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
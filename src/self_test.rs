@@ -0,0 +1,291 @@
+//! `self-test`: drives the atlas without a terminal the way a user would on first start
+//! (world, the first continent, its first country, the GDP chart, the overlays on the
+//! way) and checks every view it draws. A step passes when nothing panics and the frame
+//! isn't obviously broken: a blank map, an empty list, no GDP for a country the dataset
+//! should cover. Meant for packagers and CI after swapping data files.
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
+use std::{
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+use crate::{
+    data::GeoLevel,
+    error::AtlasError,
+    keys::Action,
+    platform::{HeadlessDriver, TerminalDriver},
+    state::{AppState, GdpStatus},
+    ui,
+    view_model::Drawn,
+};
+
+/// Size of the screen the views are drawn on
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 40;
+/// Longest wait for a continent map loading in the background
+const MAP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Outcome of one step
+struct Check {
+    name: &'static str,
+    took: Duration,
+    result: Result<String, String>, // what was seen, or what went wrong
+}
+
+/// The atlas being driven, with the screen it draws on
+struct Session {
+    state: AppState,
+    terminal: Terminal<TestBackend>,
+    map_drawn: bool, // the last frame drew a map
+}
+
+impl Session {
+    /// Press the first key bound to `action`
+    fn press(&mut self, action: Action) -> Result<(), String> {
+        let key = self.state.keys.key(action).ok_or_else(|| format!("no key is bound to {:?}", action))?;
+        self.state.handle_input(key);
+        Ok(())
+    }
+
+    /// Wait for the map loading in the background, if one is
+    fn wait_for_map(&mut self) -> Result<(), String> {
+        let started = Instant::now();
+        while self.state.map_job.is_some() {
+            if started.elapsed() > MAP_TIMEOUT {
+                return Err(format!("map still loading after {} s", MAP_TIMEOUT.as_secs()));
+            }
+            thread::sleep(Duration::from_millis(10));
+            self.state.poll_map_job();
+        }
+        Ok(())
+    }
+
+    /// Draw the current view and check it: a list with items and, where a map is drawn,
+    /// a map that isn't blank
+    fn draw(&mut self) -> Result<String, String> {
+        let mut drawn = Drawn::default();
+        self.terminal.draw(|f| drawn = ui::draw(f, &self.state.view_model())).map_err(|e| e.to_string())?;
+        let map_area = drawn.map_area;
+        self.map_drawn = map_area.is_some();
+        self.state.apply_drawn(drawn);
+        if self.state.list_items.is_empty() {
+            return Err("the list has no items".to_string());
+        }
+        let buffer = self.terminal.backend().buffer();
+        if let Some(area) = map_area
+            && is_blank(buffer, area.inner) {
+            return Err(format!("the map ({}x{} cells) is blank", area.inner.width, area.inner.height));
+        }
+        if is_blank(buffer, buffer.area) {
+            return Err("the screen is blank".to_string());
+        }
+        Ok(format!("{} list items{}", self.state.list_items.len(), if map_area.is_some() { ", map drawn" } else { "" }))
+    }
+
+    /// Open an overlay or view with `action`, draw it, and close it again
+    fn toggle(&mut self, action: Action, open: impl Fn(&AppState) -> bool) -> Result<String, String> {
+        self.press(action)?;
+        if !open(&self.state) {
+            return Err(format!("{:?} didn't open", action));
+        }
+        let seen = self.draw()?;
+        self.press(action)?;
+        if open(&self.state) {
+            return Err(format!("{:?} didn't close", action));
+        }
+        self.draw()?;
+        Ok(seen)
+    }
+
+    /// Like `draw`, for a view that has to show a map
+    fn draw_map(&mut self) -> Result<String, String> {
+        let seen = self.draw()?;
+        match self.map_drawn {
+            true => Ok(seen),
+            false => Err(format!("no map was drawn for {}", self.state.location.label())),
+        }
+    }
+
+    fn gdp_expected(&self) -> bool {
+        self.state.gdp_data.is_some()
+    }
+}
+
+/// Run `self-test` on the data in `base`: print each step as it passes or fails, with its
+/// time, then a summary; a data error when any step failed
+pub fn run(base: &Path) -> Result<(), Box<dyn Error>> {
+    if !base.is_dir() {
+        return Err(AtlasError::Data(format!("data directory {} not found", base.display())).into());
+    }
+    // A panic is reported as a failed step, not as the default message on stderr
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let checks = steps(base);
+    panic::set_hook(hook);
+
+    let total: Duration = checks.iter().map(|c| c.took).sum();
+    let passed = checks.iter().filter(|c| c.result.is_ok()).count();
+    let width = checks.iter().map(|c| c.name.len()).max().unwrap_or(0);
+    for check in &checks {
+        let (status, detail) = match &check.result {
+            Ok(seen) => ("ok", seen),
+            Err(problem) => ("FAIL", problem),
+        };
+        println!("  {:<4}  {:<width$}  {:>6} ms  {}", status, check.name, check.took.as_millis(), detail);
+    }
+    println!("Passed {} of {} checks in {:.1} s", passed, checks.len(), total.as_secs_f64());
+    match passed == checks.len() {
+        true => Ok(()),
+        false => Err(AtlasError::Data(format!("self-test failed {} of {} checks", checks.len() - passed, checks.len())).into()),
+    }
+}
+
+/// Each step in order; the steps after one that panicked or couldn't load the atlas are
+/// left out, since they would only fail for the same reason
+fn steps(base: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let started = Instant::now();
+    let loaded = panic::catch_unwind(AssertUnwindSafe(|| load(base)));
+    let mut session = match loaded {
+        Ok(Ok(session)) => {
+            let seen = format!("{} on the {} list", session.state.list_items.len(), session.state.location.label());
+            checks.push(Check { name: "load atlas", took: started.elapsed(), result: Ok(seen) });
+            session
+        }
+        Ok(Err(e)) => {
+            checks.push(Check { name: "load atlas", took: started.elapsed(), result: Err(e) });
+            return checks;
+        }
+        Err(cause) => {
+            checks.push(Check { name: "load atlas", took: started.elapsed(), result: Err(panic_message(cause)) });
+            return checks;
+        }
+    };
+
+    type Step = fn(&mut Session) -> Result<String, String>;
+    let steps: [(&'static str, Step); 10] = [
+        ("world map", world),
+        ("help overlay", |s| s.toggle(Action::Help, |st| st.show_help)),
+        ("diagnostics overlay", |s| s.toggle(Action::Diagnostics, |st| st.show_diagnostics)),
+        ("continent map", continent),
+        ("choropleth", |s| match s.gdp_expected() {
+            true => s.toggle(Action::Choropleth, |st| st.choropleth.is_some()),
+            false => Ok("skipped, no GDP data".to_string()),
+        }),
+        ("ranking table", |s| match s.gdp_expected() {
+            true => s.toggle(Action::Ranking, |st| st.ranking.is_some()),
+            false => Ok("skipped, no GDP data".to_string()),
+        }),
+        ("scatter plot", |s| match s.gdp_expected() {
+            true => s.toggle(Action::Scatter, |st| st.scatter.is_some()),
+            false => Ok("skipped, no GDP data".to_string()),
+        }),
+        ("country view", country),
+        ("GDP chart", chart),
+        ("back to world", world),
+    ];
+    for (name, step) in steps {
+        let started = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| step(&mut session)));
+        let took = started.elapsed();
+        match result {
+            Ok(result) => checks.push(Check { name, took, result }),
+            Err(cause) => {
+                checks.push(Check { name, took, result: Err(panic_message(cause)) });
+                break;
+            }
+        }
+    }
+    checks
+}
+
+/// Open the atlas on a headless screen, with nothing asked at startup
+fn load(base: &Path) -> Result<Session, String> {
+    let mut state = AppState::open(base, false).map_err(|e| e.to_string())?;
+    state.tour_step = None;
+    state.restore_offer = None;
+    let terminal = HeadlessDriver::new(WIDTH, HEIGHT, []).enter().map_err(|e| e.to_string())?;
+    let mut session = Session { state, terminal, map_drawn: false };
+    session.wait_for_map()?;
+    session.draw()?;
+    Ok(session)
+}
+
+/// Go up to the world from wherever the saved session reopened, or the country view
+fn world(s: &mut Session) -> Result<String, String> {
+    // Back while a map loads cancels the load rather than going up, so wait each time
+    for _ in 0..2 {
+        s.wait_for_map()?;
+        if s.state.level() != GeoLevel::World {
+            s.press(Action::Back)?;
+        }
+    }
+    s.wait_for_map()?;
+    match s.state.level() {
+        GeoLevel::World => s.draw_map(),
+        _ => Err(format!("Back stopped at {}", s.state.location.label())),
+    }
+}
+
+fn continent(s: &mut Session) -> Result<String, String> {
+    s.state.selected = 0;
+    s.press(Action::Select)?;
+    s.wait_for_map()?;
+    if s.state.level() != GeoLevel::Continent {
+        return Err(format!("{} didn't open", s.state.list_items.first().map_or("", |n| n.as_str())));
+    }
+    let seen = s.draw_map()?;
+    Ok(format!("{}: {}", s.state.location.label(), seen))
+}
+
+fn country(s: &mut Session) -> Result<String, String> {
+    s.state.selected = 0;
+    s.press(Action::Select)?;
+    s.wait_for_map()?;
+    if s.state.level() != GeoLevel::Country {
+        return Err(format!("{} didn't open", s.state.list_items.first().map_or("", |n| n.as_str())));
+    }
+    let seen = s.draw_map()?;
+    let gdp = match &s.state.gdp_status {
+        GdpStatus::Found { year, .. } => format!("GDP for {}", year),
+        _ if s.gdp_expected() => return Err(format!("no GDP for {} though the dataset is loaded", s.state.location.label())),
+        _ => "no GDP dataset".to_string(),
+    };
+    Ok(format!("{}: {}, {}", s.state.location.label(), seen, gdp))
+}
+
+fn chart(s: &mut Session) -> Result<String, String> {
+    if !matches!(s.state.gdp_status, GdpStatus::Found { .. }) {
+        return Ok("skipped, no GDP for the country".to_string());
+    }
+    s.press(Action::SwitchView)?;
+    if !s.state.gdp_chart_active {
+        return Err("the chart didn't open".to_string());
+    }
+    let years = s.state.all_gdp_data.as_ref().map_or(0, |d| d.len());
+    if years == 0 {
+        return Err("the chart has no years".to_string());
+    }
+    let seen = s.draw()?;
+    s.press(Action::SwitchView)?;
+    s.draw()?;
+    Ok(format!("{} years, {}", years, seen))
+}
+
+/// Whether every cell of `area` in `buffer` is empty
+fn is_blank(buffer: &Buffer, area: Rect) -> bool {
+    let area = area.intersection(buffer.area);
+    area.positions().all(|p| buffer[p].symbol().trim().is_empty())
+}
+
+/// What a caught panic said
+fn panic_message(cause: Box<dyn std::any::Any + Send>) -> String {
+    let text = cause
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| cause.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown cause".to_string());
+    format!("panicked: {}", text)
+}
@@ -1,208 +1,2815 @@
 use crossterm::event::KeyCode;
+use ratatui::{buffer::Buffer, layout::{Position, Rect}};
 use crate::{
-    data::{CountryInfo, DataCache, GeoLevel},
-    map_draw::MapView,
-    gdp_reader::GDPData,
+    config::{Config, MapMarker, Projection, Severity, WorldOrder},
+    data::{country_key, read_geojson, DataCache, DataSource, GeoLevel, LocalDir, PersistentStore, RenderHints},
+    editor::{self, DataEdit},
+    filter::{self, Filter, Values},
+    keys::{Action, Context, KeyBindings},
+    jobs::{Job, JobState, Outcome, Progress},
+    map_draw::{self, CountryCount, MapFrame, MapStyle, MapView, MiniMap, OutlierPolicy},
+    gdp_reader::{self, aggregate_per_capita, GDPData, Growth, PerCapita, YearValue, GDP_CSV},
+    humanize,
+    input::{Edit, History, LineBuffer},
+    paths::{Artifact, Paths},
+    prefs::{self, ListSort, PlacePrefs, Source, Sources, ViewSettings},
+    quiz::{Candidate, Quiz, QuizMode, QuizStats},
+    suggest,
+    summary::{CountrySummary, FallbackInfo, SummaryCache},
+    theme::{self, ColorSupport, Theme},
+    view_model::{Drawn, MapArea, ViewModel},
+    year_slider::SliderArea,
 };
-use std::{path::Path, collections::HashMap};
+use rand::{rng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::{Path, PathBuf}, process::{Command, Stdio}, collections::{BTreeMap, BTreeSet}, time::{Duration, Instant, SystemTime}};
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 /// UI panel focus states
 pub enum Panel { Left, Center, Right }
 
+impl Panel {
+    /// Every panel, in Tab order
+    pub const ALL: [Panel; 3] = [Panel::Left, Panel::Center, Panel::Right];
+
+    /// Name shown in help
+    pub fn label(self) -> &'static str {
+        match self {
+            Panel::Left => "lista",
+            Panel::Center => "mapa",
+            Panel::Right => "prawy panel",
+        }
+    }
+
+    /// Actions the panel takes before the global bindings while it has the focus, grouped
+    /// with what they do there (for help); the rest go to the global bindings
+    pub fn actions(self) -> &'static [(&'static [Action], &'static str)] {
+        match self {
+            Panel::Left => &[
+                (&[Action::Up, Action::Down], "pozycja (w kraju: ciekawostki)"),
+                (&[Action::Select], "otwiera"),
+            ],
+            Panel::Center => &[
+                (&[Action::ZoomIn, Action::ZoomOut, Action::ZoomReset], "przybliżenie mapy"),
+                (&[Action::Left, Action::Right, Action::Up, Action::Down], "przesuwają przybliżoną mapę"),
+                (&[Action::Left, Action::Right], "kontynenty (świat), rok (kolory wg GDP)"),
+                (&[Action::Up, Action::Down, Action::Select], "jak na liście"),
+            ],
+            Panel::Right => &[(&[Action::Up, Action::Down], "ciekawostki")],
+        }
+    }
+}
+
+/// Outcome of the latest GDP lookup, so the UI can explain missing data
+#[derive(Clone, Debug, PartialEq)]
+pub enum GdpStatus {
+    NoSelection,                           // no country selected yet
+    DatasetMissing(String),                // GDP CSV could not be loaded (path)
+    NotFound(String),                      // dataset loaded but country unmatched
+    Found { year: String, value: f64, growth: Option<Growth> }, // latest GDP (year, value) and change
+}
+
+/// Where the user was, persisted in `session.json` and reopened on the next start
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub continent: Option<String>,
+    pub country: Option<String>,
+    #[serde(default)]
+    pub tour_seen: bool, // first-launch walkthrough was finished or skipped
+    #[serde(default)]
+    pub visited: BTreeSet<String>, // canonical keys of countries opened so far
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_history: Vec<String>, // list search queries, oldest first (with `save_history`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filter_history: Vec<String>, // ranking filters, oldest first (with `save_history`)
+}
+
+/// Where a saved session can be reopened in the current data (see `Session::resolve`)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Restore {
+    Exact(Location),                                  // the saved place is still there, a country maybe on another continent
+    Offer { offer: RestoreOffer, fallback: Location }, // the saved name is gone but a similar one is there: ask, else the fallback
+    Fallback { location: Location, missing: String }, // nothing like it: the nearest place above it still there
+}
+
+/// A saved place gone from the data and the similarly named one offered instead
+#[derive(Clone, Debug, PartialEq)]
+pub struct RestoreOffer {
+    pub saved: String,   // name in the session
+    pub found: Location, // place with the similar name
+}
+
+impl Session {
+    /// Least `suggest` score for a name to be offered as the saved one renamed
+    /// ("Czech Republic" → "Czechia" scores about 0.9)
+    pub const RENAME_SIMILARITY: f64 = 0.85;
+
+    /// Where to reopen this session in `index` (continent → member countries, as
+    /// `DataCache::load_continent_mappings` gives): the saved place itself, found by
+    /// canonical key on any continent; else a place with a similar name, to be offered;
+    /// else the nearest place above it that is still there
+    pub fn resolve(&self, index: &BTreeMap<String, BTreeSet<String>>) -> Restore {
+        let Some(continent) = &self.continent else { return Restore::Exact(Location::World) };
+        let saved_continent = index.keys().find(|c| country_key(c) == country_key(continent));
+        let Some(country) = &self.country else {
+            if let Some(found) = saved_continent {
+                return Restore::Exact(Location::Continent(found.clone()));
+            }
+            return match suggest::best_match(continent, index.keys().map(String::as_str), Self::RENAME_SIMILARITY) {
+                Some(found) => Restore::Offer {
+                    offer: RestoreOffer { saved: continent.clone(), found: Location::Continent(found.to_string()) },
+                    fallback: Location::World,
+                },
+                None => Restore::Fallback { location: Location::World, missing: continent.clone() },
+            };
+        };
+
+        let members = || index.iter().flat_map(|(c, members)| members.iter().map(move |m| (c, m)));
+        let key = country_key(country);
+        if let Some((c, m)) = members().find(|(_, m)| country_key(m) == key) {
+            return Restore::Exact(Location::Country { continent: c.clone(), country: m.clone() });
+        }
+        let fallback = saved_continent.map_or(Location::World, |c| Location::Continent(c.clone()));
+        let found = suggest::best_match(country, members().map(|(_, m)| m.as_str()), Self::RENAME_SIMILARITY)
+            .and_then(|name| members().find(|&(_, m)| m == name));
+        match found {
+            Some((c, m)) => Restore::Offer {
+                offer: RestoreOffer {
+                    saved: country.clone(),
+                    found: Location::Country { continent: c.clone(), country: m.clone() },
+                },
+                fallback,
+            },
+            None => Restore::Fallback { location: fallback, missing: country.clone() },
+        }
+    }
+}
+
+/// A place in the world → continent → country hierarchy
+#[derive(Clone, Debug, PartialEq)]
+pub enum Location {
+    World,
+    Continent(String),
+    Country { continent: String, country: String },
+}
+
+impl Location {
+    /// Geographic level of this place
+    pub fn level(&self) -> GeoLevel {
+        match self {
+            Location::World => GeoLevel::World,
+            Location::Continent(_) => GeoLevel::Continent,
+            Location::Country { .. } => GeoLevel::Country,
+        }
+    }
+
+    /// The place one level up, where Back leads; None for the world
+    pub fn parent(&self) -> Option<Location> {
+        match self {
+            Location::World => None,
+            Location::Continent(_) => Some(Location::World),
+            Location::Country { continent, .. } => Some(Location::Continent(continent.clone())),
+        }
+    }
+
+    /// This place and every place above it, the world first, as the breadcrumb shows them
+    pub fn trail(&self) -> Vec<Location> {
+        let mut trail = vec![self.clone()];
+        while let Some(parent) = trail[0].parent() {
+            trail.insert(0, parent);
+        }
+        trail
+    }
+
+    /// Name of this place in the breadcrumb
+    pub fn label(&self) -> &str {
+        match self {
+            Location::World => "World",
+            Location::Continent(continent) => continent,
+            Location::Country { country, .. } => country,
+        }
+    }
+}
+
+/// Mouse input the TUI reacts to, at a screen cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseInput {
+    Click { column: u16, row: u16 }, // left button pressed
+    Drag { column: u16, row: u16 },  // pointer moved with the left button held
+    Move { column: u16, row: u16 },  // pointer moved, no button held
+}
+
+/// Visited places for undo/redo: bounded, without consecutive repeats
+pub struct NavHistory {
+    entries: Vec<Location>,
+    pos: usize, // index of the current place
+}
+
+impl NavHistory {
+    /// Oldest entries are dropped beyond this many
+    const LIMIT: usize = 100;
+
+    pub fn new(start: Location) -> Self {
+        Self { entries: vec![start], pos: 0 }
+    }
+
+    /// Record a visit to `loc`; drops anything that could be redone.
+    /// Revisiting the current place records nothing.
+    pub fn visit(&mut self, loc: Location) {
+        if self.entries[self.pos] == loc {
+            return;
+        }
+        self.entries.truncate(self.pos + 1);
+        self.entries.push(loc);
+        if self.entries.len() > Self::LIMIT {
+            self.entries.remove(0);
+        }
+        self.pos = self.entries.len() - 1;
+    }
+
+    /// Places recorded, oldest first, and the index of the current one among them
+    pub fn entries(&self) -> (&[Location], usize) {
+        (&self.entries, self.pos)
+    }
+
+    /// Step back to the previously visited place
+    pub fn undo(&mut self) -> Option<&Location> {
+        self.pos = self.pos.checked_sub(1)?;
+        Some(&self.entries[self.pos])
+    }
+
+    /// Step forward again after an undo
+    pub fn redo(&mut self) -> Option<&Location> {
+        if self.pos + 1 >= self.entries.len() {
+            return None;
+        }
+        self.pos += 1;
+        Some(&self.entries[self.pos])
+    }
+}
+
+/// Incremental search narrowing the selection list; the map highlight follows its top
+/// match until it is accepted or cancelled
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ListSearch {
+    pub query: LineBuffer,
+    pub saved: usize,        // selection before the search, restored on cancel
+    pub matches: Vec<usize>, // indices into the full list of the items matching, in list order
+    pub pos: usize,          // highlighted match, index into `matches`
+}
+
+impl ListSearch {
+    /// Search over `items`, starting with every item matching and `saved` highlighted
+    pub fn new(items: &[String], saved: usize) -> Self {
+        let mut search = ListSearch { saved, ..ListSearch::default() };
+        search.refresh(items);
+        search.pos = search.matches.iter().position(|&i| i == saved).unwrap_or(0);
+        search
+    }
+
+    /// Recompute the items whose name contains the query, ignoring case; the top match
+    /// becomes the highlighted one
+    pub fn refresh(&mut self, items: &[String]) {
+        let query = self.query.as_str().to_lowercase();
+        self.matches = (0..items.len()).filter(|&i| items[i].to_lowercase().contains(&query)).collect();
+        self.pos = 0;
+    }
+
+    /// Index into the full list of the highlighted match, or the saved selection when
+    /// nothing matches
+    pub fn current(&self) -> usize {
+        self.matches.get(self.pos).copied().unwrap_or(self.saved)
+    }
+}
+
+/// One country of the continent scatter plot
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScatterPoint {
+    pub name: String,
+    pub population: f64,
+    pub gdp: f64, // latest year available
+}
+
+/// Countries of a continent placed by population and GDP
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scatter {
+    pub points: Vec<ScatterPoint>,
+    pub missing: usize, // members without population, GDP, or with a non-positive value
+}
+
+impl Scatter {
+    /// Join `members` with their population and latest GDP; `lookup` gives both for a
+    /// name, or None when either is unknown. Values that a log axis can't show count as missing.
+    pub fn assemble<F>(members: &[String], mut lookup: F) -> Self
+    where
+        F: FnMut(&str) -> Option<(u64, f64)>,
+    {
+        let mut scatter = Scatter::default();
+        for name in members {
+            match lookup(name) {
+                Some((population, gdp)) if population > 0 && gdp > 0.0 => scatter.points.push(ScatterPoint {
+                    name: name.clone(),
+                    population: population as f64,
+                    gdp,
+                }),
+                _ => scatter.missing += 1,
+            }
+        }
+        scatter
+    }
+}
+
+/// One country of the continent ranking
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankRow {
+    pub name: String,
+    pub population: Option<u64>,
+    pub area: Option<f64>, // km²
+    pub gdp: Option<f64>,  // latest year available
+}
+
+/// Column the ranking is ordered by: numbers largest first, names alphabetically
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RankColumn {
+    #[default]
+    Population,
+    Area,
+    Gdp,
+    Name,
+}
+
+impl RankColumn {
+    /// Column a continent list sorted by `sort` follows; None for names
+    pub fn of(sort: ListSort) -> Option<Self> {
+        match sort {
+            ListSort::Population => Some(RankColumn::Population),
+            ListSort::Area => Some(RankColumn::Area),
+            ListSort::Gdp => Some(RankColumn::Gdp),
+            ListSort::Name | ListSort::Geographic => None,
+        }
+    }
+
+    /// The column `s` switches to next
+    pub fn next(self) -> Self {
+        match self {
+            RankColumn::Population => RankColumn::Area,
+            RankColumn::Area => RankColumn::Gdp,
+            RankColumn::Gdp => RankColumn::Name,
+            RankColumn::Name => RankColumn::Population,
+        }
+    }
+}
+
+/// Members of a continent in a table, sorted by one column and narrowed by a filter
+/// expression (see `filter`)
+#[derive(Clone, Debug, Default)]
+pub struct Ranking {
+    pub rows: Vec<RankRow>,           // every member, in `sort` order
+    pub sort: RankColumn,
+    pub filter: Filter,               // last expression that parsed
+    pub input: LineBuffer,            // filter text as typed
+    pub editing: bool,                // the filter row takes the keys
+    pub error: Option<String>,        // why `input` doesn't parse; `filter` stays in force
+    pub selected: usize,              // index into `visible()`
+}
+
+impl Ranking {
+    /// Table of `rows` sorted by population
+    pub fn new(rows: Vec<RankRow>) -> Self {
+        let mut ranking = Ranking { rows, ..Ranking::default() };
+        ranking.sort_by(RankColumn::default());
+        ranking
+    }
+
+    /// Reorder the rows by `column`; rows missing the value go last
+    pub fn sort_by(&mut self, column: RankColumn) {
+        let key = |row: &RankRow| match column {
+            RankColumn::Population => row.population.map(|p| p as f64),
+            RankColumn::Area => row.area,
+            RankColumn::Gdp => row.gdp,
+            RankColumn::Name => None,
+        };
+        self.rows.sort_by(|a, b| match column {
+            RankColumn::Name => a.name.cmp(&b.name),
+            _ => match (key(a), key(b)) {
+                (Some(x), Some(y)) => y.total_cmp(&x).then_with(|| a.name.cmp(&b.name)),
+                (x, y) => y.is_some().cmp(&x.is_some()).then_with(|| a.name.cmp(&b.name)),
+            },
+        });
+        self.sort = column;
+    }
+
+    /// Rows passing the filter with their 1-based place in the whole ranking
+    pub fn visible(&self) -> Vec<(usize, &RankRow)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                self.filter.matches(&Values {
+                    name: &row.name,
+                    population: row.population.map(|p| p as f64),
+                    area: row.area,
+                    gdp: row.gdp,
+                })
+            })
+            .map(|(i, row)| (i + 1, row))
+            .collect()
+    }
+
+    /// Re-parse `input` after an edit: a valid expression replaces the filter, an
+    /// invalid one only sets `error`
+    pub fn refilter(&mut self) {
+        match filter::parse(self.input.as_str()) {
+            Ok(parsed) => {
+                self.filter = parsed;
+                self.error = None;
+                self.selected = 0;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// Name of the selected row, if the filter left any
+    pub fn selected_name(&self) -> Option<String> {
+        self.visible().get(self.selected).map(|(_, row)| row.name.clone())
+    }
+}
+
+/// A country marked for comparison and the color it keeps while marked
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mark {
+    pub name: String,
+    pub color: usize, // index into theme::MARK_COLORS
+}
+
+/// Value range and size of one choropleth bucket
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bucket {
+    pub lo: f64,
+    pub hi: f64,
+    pub count: usize,
+}
+
+/// Countries of a continent, or of the world, split into equal-count buckets by GDP,
+/// smallest first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Choropleth {
+    pub year: Option<u16>,                   // year shown (None = each country's latest)
+    pub buckets: Vec<Bucket>,
+    pub assignment: BTreeMap<String, usize>, // canonical country key -> bucket
+    pub interpolated: BTreeSet<String>,      // canonical keys whose value fills a data gap
+    pub missing: usize,                      // members without GDP data
+}
+
+impl Choropleth {
+    /// Split `members` into at most `buckets` buckets of (nearly) equal size by the value
+    /// `lookup` gives; members without a value count as missing, interpolated values are
+    /// bucketed like reported ones but remembered. Equal values are ordered by name, so the
+    /// split is stable.
+    pub fn assemble<F>(members: &[String], buckets: usize, mut lookup: F) -> Self
+    where
+        F: FnMut(&str) -> Option<YearValue>,
+    {
+        let mut values: Vec<(f64, &String)> = Vec::new();
+        let mut interpolated = BTreeSet::new();
+        let mut missing = 0;
+        for name in members {
+            match lookup(name) {
+                Some(YearValue::Reported(value)) => values.push((value, name)),
+                Some(YearValue::Interpolated(value)) => {
+                    values.push((value, name));
+                    interpolated.insert(country_key(name));
+                }
+                None => missing += 1,
+            }
+        }
+        values.sort_by(|(va, na), (vb, nb)| va.total_cmp(vb).then_with(|| na.cmp(nb)));
+
+        let n = values.len();
+        let k = buckets.min(n);
+        let mut choropleth = Choropleth { interpolated, missing, ..Default::default() };
+        for (rank, &(value, name)) in values.iter().enumerate() {
+            let bucket = rank * k / n;
+            if bucket == choropleth.buckets.len() {
+                choropleth.buckets.push(Bucket { lo: value, hi: value, count: 0 });
+            }
+            let b = &mut choropleth.buckets[bucket];
+            b.hi = value;
+            b.count += 1;
+            choropleth.assignment.insert(country_key(name), bucket);
+        }
+        choropleth
+    }
+
+    /// Canonical keys of the countries in `bucket`
+    pub fn members(&self, bucket: usize) -> BTreeSet<String> {
+        self.assignment.iter().filter(|&(_, &b)| b == bucket).map(|(key, _)| key.clone()).collect()
+    }
+}
+
+/// GDP dataset names offered for a country the GDP lookup missed
+pub struct AliasPicker {
+    pub country: String,
+    pub options: Vec<String>, // closest names first
+    pub selected: usize,
+}
+
+/// One country's GDP series, prepared once for a chart pane
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartData {
+    pub name: String,
+    pub points: Vec<(f64, f64)>, // (year, GDP) in year order
+    pub color: usize,            // the country's mark color, an index into theme::MARK_COLORS
+}
+
+impl ChartData {
+    pub fn new(name: &str, years: &BTreeMap<u16, f64>, color: usize) -> Self {
+        let points = years.iter().map(|(&y, &v)| (y as f64, v)).collect();
+        Self { name: name.to_string(), points, color }
+    }
+
+    /// Last year with a value, and that value
+    pub fn latest(&self) -> Option<(f64, f64)> {
+        self.points.last().copied()
+    }
+
+    /// Highest value, 0 without data
+    pub fn max(&self) -> f64 {
+        self.points.iter().map(|&(_, v)| v).fold(0.0, f64::max)
+    }
+}
+
+/// Screen region a walkthrough step points at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TourTarget {
+    List,
+    Map,
+    Info,
+    StatusBar,
+}
+
+impl TourTarget {
+    /// Walkthrough steps, in order
+    pub const STEPS: [TourTarget; 4] = [TourTarget::List, TourTarget::Map, TourTarget::Info, TourTarget::StatusBar];
+}
+
+/// Transient status bar message
+pub struct Notification {
+    pub text: String,
+    pub severity: Severity,
+    shown_at: Instant,
+}
+
+impl Notification {
+    /// How long a notification stays visible
+    const TTL: Duration = Duration::from_secs(5);
+}
+
+/// Attract mode: where the user was when it took over, restored on the next input
+pub struct Attract {
+    location: Location,
+    selected: usize,
+    panel: Panel,
+    fact_focus: usize,
+    visited: BTreeSet<String>, // countries shown by attract mode don't count as visited
+    next_at: Instant,          // when the next random country is shown
+}
+
+impl Attract {
+    /// How long each random country stays on screen
+    const LINGER: Duration = Duration::from_secs(15);
+}
+
+/// Scroll position within the focused fun fact and its auto-rotation timing
+pub struct FactScroll {
+    pub offset: u16,      // first visible line
+    pub auto: bool,       // auto-rotation active until the user scrolls manually
+    last_step: Instant,   // time of the last automatic step
+}
+
+impl FactScroll {
+    /// Delay between automatic one-line steps
+    const STEP: Duration = Duration::from_secs(2);
+    /// Pause on the last line before looping back to the top
+    const END_PAUSE: Duration = Duration::from_secs(6);
+
+    fn new(now: Instant) -> Self {
+        Self { offset: 0, auto: true, last_step: now }
+    }
+
+    /// Advance the auto-rotation given the number of lines hidden below the block
+    fn tick(&mut self, now: Instant, overflow: u16) {
+        if !self.auto || overflow == 0 {
+            return;
+        }
+        let elapsed = now.duration_since(self.last_step);
+        if self.offset < overflow && elapsed >= Self::STEP {
+            self.offset += 1;
+            self.last_step = now;
+        } else if self.offset >= overflow && elapsed >= Self::END_PAUSE {
+            self.offset = 0;
+            self.last_step = now;
+        }
+    }
+
+    /// Scroll manually by `delta` lines, which stops auto-rotation for this fact
+    fn scroll(&mut self, delta: i32, overflow: u16) {
+        self.auto = false;
+        self.offset = (self.offset as i32 + delta).clamp(0, overflow as i32) as u16;
+    }
+}
+
+/// Order of the timed fun fact rotation: every fact once, shuffled, before any repeats
+#[derive(Default)]
+pub struct FactCycle {
+    remaining: Vec<usize>, // fact indices not shown yet this round, next one last
+}
+
+impl FactCycle {
+    /// Fact to show after `current` out of `len`; a new round is shuffled when this one
+    /// runs out, never starting with `current`
+    pub fn next(&mut self, len: usize, current: usize) -> usize {
+        self.remaining.retain(|&i| i != current && i < len);
+        if self.remaining.is_empty() {
+            self.remaining = (0..len).filter(|&i| i != current).collect();
+            self.remaining.shuffle(&mut rng());
+        }
+        self.remaining.pop().unwrap_or(current)
+    }
+}
+
+/// Settings re-read from `config.json`, all checked before any is applied so a bad file
+/// leaves the running ones untouched
+pub struct Reload {
+    pub config: Config,
+    pub keys: KeyBindings,
+    pub theme: Theme,
+    pub warnings: Vec<String>, // problems that didn't stop the reload
+}
+
+impl Reload {
+    /// Read `bytes` as `config.json`. Colors come from `forced` (`--colors`), then the file,
+    /// then `detected`. Rejected when the file doesn't parse or leaves a core action unbound.
+    pub fn parse(bytes: &[u8], forced: Option<ColorSupport>, detected: ColorSupport) -> Result<Self, String> {
+        let config = Config::parse(bytes).map_err(|e| format!("config.json: {}", e))?;
+        let (keys, invalid) = KeyBindings::new(&config.keys);
+        let unbound = keys.unbound_core();
+        if !unbound.is_empty() {
+            let names: Vec<_> = unbound.iter().map(|a| a.description()).collect();
+            return Err(format!("config.json zostawia bez klawisza: {}", names.join(", ")));
+        }
+        let mut warnings = Vec::new();
+        if !invalid.is_empty() {
+            warnings.push(format!("nieznane klawisze: {}", invalid.join(", ")));
+        }
+        if forced.is_some() && config.colors.is_some() {
+            warnings.push("kolory z --colors mają pierwszeństwo".to_string());
+        }
+        let theme = Theme::new(forced.or(config.colors).unwrap_or(detected));
+        Ok(Reload { config, keys, theme, warnings })
+    }
+}
+
 pub struct AppState {
     pub cache: DataCache,                  // data loader and cache
-    pub level: GeoLevel,                   // current geographic level
+    pub config: Config,                    // user preferences
+    pub keys: KeyBindings,                 // key -> action table
+    pub show_help: bool,                   // help overlay visible
+    pub show_diagnostics: bool,            // data diagnostics overlay visible
+    pub show_minimap: bool,                // world inset on continent and country maps
+    pub show_labels: bool,                 // country names on the world and continent maps
+    pub world_order: WorldOrder,           // order of the continents in the world list
+    pub continent_sort: ListSort,          // order of the countries in a continent list
+    pub place_filter: String,              // ranking filter the place's table opens with
+    pub view_sources: Sources,             // where the view settings in force came from
+    view_defaults: ViewSettings,           // the session's own, for places without saved ones
+    pub view_prefs: PersistentStore<PlacePrefs>, // view settings saved per place, across runs
+    pub projection: Projection,            // how maps fit longitude and latitude to the cells
+    pub map_marker: MapMarker,             // dots the map outlines are drawn with, kept across maps
+    pub map_style: MapStyle,               // colors of the map and of the highlight everywhere
+    pub show_graticule: bool,              // latitude/longitude grid behind the map outlines
+    pub auto_zoom: bool,                   // continent maps frame the selected country, not the continent
+    pub world_outline: MiniMap,            // simplified world geometry for the inset
+    pub scatter: Option<Scatter>,          // GDP vs population plot replacing the continent map
+    pub ranking: Option<Ranking>,          // sortable, filterable table replacing the continent map
+    pub show_choropleth: bool,             // world and continent maps colored by GDP bucket
+    pub choropleth: Option<Choropleth>,    // buckets of the current map, while shown
+    pub legend_focus: Option<usize>,       // bucket selected in the focused legend
+    pub location: Location,                // current place; Back goes to its parent
+    pub breadcrumb: Vec<(Location, Rect)>, // breadcrumb segments and where they were drawn (see `apply_drawn`)
+    pub breadcrumb_hover: Option<usize>,   // breadcrumb segment under the mouse pointer
+    pub map_area: Option<MapArea>,         // where the map was drawn (see `apply_drawn`)
+    pub map_hover: Option<String>,         // feature under the mouse pointer on the map
+    map_click: Option<(Instant, usize)>,   // latest click selecting on the map, and the item it selected
+    map_cache: Option<(MapFrame, Buffer)>, // the map as last rendered, with what it was rendered from
+    map_frames: (u64, u64),                // frames that drew the map, and those that copied it from the cache
+    hover_summaries: SummaryCache,         // summaries of the features last hovered
     pub list_items: Vec<String>,           // items in the selection list
     pub selected: usize,                   // index of the selected item
-    pub history: Vec<(GeoLevel, String)>,  // navigation history stack
+    pub search: Option<ListSearch>,        // incremental search narrowing the list, while typed
+    pub search_history: History,           // earlier list search queries
+    pub filter_history: History,           // earlier ranking filters
+    pub nav: NavHistory,                   // visited places for undo/redo
+    pub visited: BTreeSet<String>,         // canonical keys of countries opened, across sessions
     pub map: Option<MapView>,              // current map view
-    pub info: String,                      // status and help text
-    pub country_info: Option<CountryInfo>, // metadata for the selected country
-    pub fun_fact: Option<String>,          // random fun fact for a country
+    pub neighbours: BTreeSet<String>,      // canonical keys of the opened country's neighbours, drawn around it
+    pub map_job: Option<Job<MapView>>,     // continent map loading in the background; title is the continent
+    pub map_count: Option<CountryCount>,   // listed countries against the world or continent map's features
+    pub info: String,                      // summary of the loaded map
+    pub summary: Option<CountrySummary>,   // everything known about the opened country
+    pub place_per_capita: Option<PerCapita>, // GDP per capita of the world's or continent's countries
+    pub facts: Vec<String>,                // fun facts of the country
+    pub fact_focus: usize,                 // fact shown in full, starting at a random one
+    pub fact_scroll: FactScroll,           // scroll position within the focused fact
+    pub fact_overflow: u16,                // focused fact lines that don't fit (see `apply_drawn`)
+    fact_cycle: FactCycle,                 // order of the timed fact rotation
+    fact_shown_at: Instant,                // when the focused fact was last changed
     pub active_panel: Panel,               // currently focused panel
     pub gdp_data: Option<GDPData>,         // optional GDP dataset
-    pub current_gdp: Option<(String, f64)>,// latest GDP (year, value)
+    pub gdp_path: String,                  // location of the GDP CSV
+    pub gdp_status: GdpStatus,             // latest GDP lookup result
     pub gdp_chart_active: bool,            // whether detailed GDP chart is active
-    pub all_gdp_data: Option<HashMap<String, f64>>, // full GDP history for chart
+    pub chart_cursor: Option<u16>,         // year under the GDP chart cursor
+    pub chart_log: bool,                   // GDP chart plots log10 of the values
+    pub active_year: Option<u16>,          // year shared by year-aware views (None = latest)
+    pub year_slider: Option<SliderArea>,   // where the year slider was drawn (see `apply_drawn`)
+    slider_drag: bool,                     // a drag that started on the year slider is moving it
+    pub all_gdp_data: Option<BTreeMap<u16, f64>>, // full GDP history for chart, by year
+    pub marked: Vec<Mark>,                 // countries marked for comparison, at most MAX_MARKED
+    pub compare: Option<Vec<ChartData>>,   // GDP chart grid of the marked countries, when shown
+    pub compare_per_capita: Option<PerCapita>, // GDP per capita of the marked countries together
+    pub compare_shared_y: bool,            // grid panes share one GDP scale
+    pub alias_picker: Option<AliasPicker>, // choosing a GDP name for the current country
+    pub restore_offer: Option<RestoreOffer>, // renamed place from the saved session, asked about at startup
+    pub notification: Option<Notification>, // transient status bar message
+    bell_pending: bool,                    // the driver should sound the terminal bell
+    pending_edit: Option<DataEdit>,        // data file the driver should open in the editor (Ctrl+E)
+    pub flash_until: Option<Instant>,      // status bar drawn inverted until then (visual bell)
+    pub paths: Paths,                      // where data, state and cache files live
+    pub theme: Theme,                      // colors for the terminal's capability
+    pub colors_override: Option<ColorSupport>, // --colors, which wins over config.json on reload
+    pub watch: bool,                       // reload config.json when it changes (--watch)
+    config_seen: Option<SystemTime>,       // modification time of config.json when last read
+    watch_at: Instant,                     // when config.json was last checked for changes
+    pub session: PersistentStore<Session>, // last location, saved across runs
+    pub quiz_stats: PersistentStore<QuizStats>, // quiz answers per country, saved across runs
+    pub quiz: Option<Quiz>,                // map quiz under way, or its summary once done
+    pub tour_step: Option<usize>,          // current walkthrough step (index into TourTarget::STEPS)
+    pub attract: Option<Attract>,          // random countries cycling while idle, for kiosks
+    last_input: Instant,                   // when a key or mouse event last arrived
+    pub animations: bool,                  // pulse the highlight and scroll long facts (off with --no-animations)
+    pub accessible: bool,                  // map described in sentences, plain symbols (--accessible)
+    pub pulse_bright: bool,                // highlight currently in its brighter phase
+    pulse_at: Instant,                     // when the highlight last changed phase
+    tour_seen: bool,                       // walkthrough no longer starts on its own
 }
 
 impl AppState {
-    // Help instructions shown in the info panel
-    const HELP_TEXT: &'static str = "\
-↑/↓: ruch w liście
-Enter: zagłębienie
-(świat → kontynent → kraj)
-Esc / Backspace: wstecz
-q: wyjście";
+    /// Time between two checks of `config.json` with --watch
+    const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+    /// Minimum time between two writes of persisted state
+    const SAVE_INTERVAL: Duration = Duration::from_secs(5);
+    /// Time the highlight spends in each phase of its pulse
+    const PULSE_PERIOD: Duration = Duration::from_secs(1);
+    /// How long the visual bell keeps the status bar inverted
+    const FLASH_DURATION: Duration = Duration::from_millis(200);
+    /// Zoom factor of one +/- press
+    const ZOOM_STEP: f64 = 1.5;
+    /// Share of the view one arrow press pans a zoomed map by
+    const PAN_STEP: f64 = 0.2;
 
     /// Initialize application state: load data, map, and help text
     pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, Box<dyn std::error::Error>> {
-        let base = dir.as_ref();
-        let mut cache = DataCache::new(base)?;
+        Self::open(dir, false)
+    }
+
+    /// Like `new`; with `low_mem` every map, the world's included, is streamed
+    /// feature by feature instead of parsed whole (`--low-mem`)
+    pub fn open<P: AsRef<Path>>(dir: P, low_mem: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let base = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&base)?;
+        Self::open_source(Box::new(LocalDir::new(base)), low_mem)
+    }
+
+    /// Like `open`, reading the data files through `source`, e.g. a web server; config,
+    /// session and caches live in its local directory
+    pub fn open_source(source: Box<dyn DataSource>, low_mem: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut cache = DataCache::with_source(source);
+        cache.set_low_mem(low_mem);
+        let base = &cache.base().to_path_buf();
+        let config = Config::load(base);
+        let (keys, invalid_keys) = KeyBindings::new(&config.keys);
+        let colors = config.colors.unwrap_or_else(Self::detect_colors);
+        let unicode = config.unicode.unwrap_or_else(|| theme::utf8_locale(|name| std::env::var(name).ok()));
+        let symbols = if unicode { &theme::UNICODE } else { &theme::ASCII };
+        let show_labels = config.world_labels;
+        let world_order = config.world_order;
+        let projection = config.projection;
+        let map_marker = config.map_marker;
+        let paths = Paths::detect(base);
+        let config_seen = Self::modified(&paths.file(Artifact::Data, "config.json"));
+        let (session, session_warning) =
+            PersistentStore::<Session>::load(paths.file(Artifact::State, "session.json"), Self::SAVE_INTERVAL);
+        let (quiz_stats, quiz_warning) =
+            PersistentStore::<QuizStats>::load(paths.file(Artifact::State, "quiz.json"), Self::SAVE_INTERVAL);
+        let (view_prefs, view_warning) =
+            PersistentStore::<PlacePrefs>::load(paths.file(Artifact::State, "views.json"), Self::SAVE_INTERVAL);
+        let view_defaults = ViewSettings { sort: ListSort::Name, filter: String::new(), choropleth: false, labels: show_labels };
+        let (search_history, filter_history) = if config.save_history {
+            (session.get().search_history.clone(), session.get().filter_history.clone())
+        } else {
+            (Vec::new(), Vec::new())
+        };
 
         // Attempt to load GDP dataset
-        let gdp_data = GDPData::new(&base.join("dataPKB/pkb.csv")).ok();
+        let gdp_path = cache.data_path(GDP_CSV);
+        let (gdp_data, gdp_error) = match GDPData::new(&gdp_path) {
+            Ok(gdp) => (Some(gdp), None),
+            // A missing CSV just leaves GDP out; an unreadable one or its metadata is reported
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (None, None),
+            Err(e) => (None, Some(format!("Nie wczytano GDP: {}", e))),
+        };
 
         // Load world-level list and map view
         let continents = cache.load_list(GeoLevel::World, "world")?;
-        let raw = cache.load_geojson(&GeoLevel::World, "world")?;
-        let view = MapView::new(raw, &mut cache)?;
-        let count = view.feature_count();
-        let info = format!("World – {} krajów\n\n{}", count, Self::HELP_TEXT);
+        let mut view = MapView::load(&mut cache, &GeoLevel::World, "world", config.antarctica)?;
+        view.set_simplify(config.simplify_epsilon);
+        let map_count = Self::map_count(&mut cache, None, &view);
+        let info = Self::map_info("World", &view, &map_count);
 
-        Ok(Self {
+        let mut state = Self {
             cache,
-            level: GeoLevel::World,
+            config,
+            keys,
+            show_help: false,
+            show_diagnostics: false,
+            show_minimap: false,
+            show_labels,
+            world_order,
+            continent_sort: ListSort::Name,
+            place_filter: String::new(),
+            view_sources: Sources::default(),
+            view_defaults,
+            view_prefs,
+            projection,
+            map_marker,
+            map_style: MapStyle::default(),
+            show_graticule: false,
+            auto_zoom: false,
+            scatter: None,
+            ranking: None,
+            show_choropleth: false,
+            choropleth: None,
+            legend_focus: None,
+            map_job: None,
+            map_count: Some(map_count),
+            world_outline: view.mini_map(1.0),
+            location: Location::World,
             list_items: continents,
             selected: 0,
-            history: Vec::new(),
+            search: None,
+            search_history: History::from_entries(search_history),
+            filter_history: History::from_entries(filter_history),
+            breadcrumb: Vec::new(),
+            breadcrumb_hover: None,
+            map_area: None,
+            map_click: None,
+            map_hover: None,
+            map_cache: None,
+            map_frames: (0, 0),
+            hover_summaries: SummaryCache::default(),
+            nav: NavHistory::new(Location::World),
+            visited: session.get().visited.clone(),
             map: Some(view),
+            neighbours: BTreeSet::new(),
             info,
-            country_info: None,
-            fun_fact: None,
+            summary: None,
+            place_per_capita: None,
+            facts: Vec::new(),
+            fact_focus: 0,
+            fact_scroll: FactScroll::new(Instant::now()),
+            fact_overflow: 0,
+            fact_cycle: FactCycle::default(),
+            fact_shown_at: Instant::now(),
             active_panel: Panel::Left,
             gdp_data,
-            current_gdp: None,
+            gdp_path: gdp_path.display().to_string(),
+            gdp_status: GdpStatus::NoSelection,
             gdp_chart_active: false,
+            chart_cursor: None,
+            chart_log: false,
+            active_year: None,
+            year_slider: None,
+            slider_drag: false,
             all_gdp_data: None,
-        })
+            marked: Vec::new(),
+            compare: None,
+            compare_per_capita: None,
+            compare_shared_y: false,
+            alias_picker: None,
+            restore_offer: None,
+            notification: None,
+            bell_pending: false,
+            pending_edit: None,
+            flash_until: None,
+            tour_step: None,
+            animations: true,
+            accessible: false,
+            pulse_bright: false,
+            pulse_at: Instant::now(),
+            attract: None,
+            last_input: Instant::now(),
+            tour_seen: session.get().tour_seen,
+            paths,
+            theme: Theme::new(colors).with_symbols(symbols),
+            colors_override: None,
+            watch: false,
+            config_seen,
+            watch_at: Instant::now(),
+            session,
+            quiz_stats,
+            quiz: None,
+        };
+        // First launch: no session recorded the walkthrough yet
+        if !state.tour_seen {
+            state.start_tour();
+        }
+        if let Some(notice) = state.paths.notice() {
+            state.notify(notice);
+        }
+        if !invalid_keys.is_empty() {
+            state.notify_error(format!("Nieznane klawisze w config.json: {}", invalid_keys.join(", ")));
+        }
+        if let Some(warning) = session_warning {
+            state.notify_error(warning);
+        }
+        if let Some(warning) = quiz_warning {
+            state.notify_error(warning);
+        }
+        if let Some(warning) = view_warning {
+            state.notify_error(warning);
+        }
+        if let Some(error) = gdp_error {
+            state.notify_error(error);
+        }
+        if let Some(magnitude) = state.gdp_data.as_ref().and_then(GDPData::magnitude) {
+            state.notify_error(format!(
+                "GDP w złych jednostkach? Mediana {:.1e} USD poza zakresem {:.0e}–{:.0e}; ustaw \"scale\": {:e} w {}",
+                magnitude.median,
+                magnitude.plausible[0],
+                magnitude.plausible[1],
+                magnitude.suggested_scale,
+                gdp_reader::Metadata::path(&gdp_path).display()
+            ));
+        }
+        state.report_list_issues();
+        state.enter_place(&Location::World);
+        state.place_per_capita = state.per_capita_at(&Location::World);
+        state.order_list();
+
+        // Reopen the continent and country where the previous session ended. If the data
+        // changed since, a similarly named place is offered, else the nearest one above it
+        let index = state.cache.load_continent_mappings().unwrap_or_default();
+        match state.session.get().resolve(&index) {
+            Restore::Exact(location) => state.restore(location),
+            Restore::Offer { offer, fallback } => {
+                state.restore(fallback);
+                state.restore_offer = Some(offer);
+            }
+            Restore::Fallback { location, missing } => {
+                let opened = Self::place_name(&location);
+                state.restore(location);
+                state.notify_error(format!("Brak {} w danych z poprzedniej sesji – otwarto {}", missing, opened));
+            }
+        }
+        Ok(state)
+    }
+
+    /// Open `location` as the place a session starts from, with nothing to go back to
+    fn restore(&mut self, location: Location) {
+        if let Location::Continent(continent) | Location::Country { continent, .. } = &location
+            && self.go_to(Location::Continent(continent.clone()))
+            && let Location::Country { .. } = location {
+            self.go_to(location);
+        }
+        self.nav = NavHistory::new(self.location.clone());
     }
 
-    /// Update `current_gdp` to the latest available for a given country
-    fn update_gdp(&mut self, country_name: &str) {
-        if let Some(data) = &self.gdp_data {
-            self.current_gdp = data
-                .get_latest_gdp(country_name)
-                .map(|(year, val)| (year.to_string(), val));
+    /// Answer the startup question whether to reopen the saved session at a renamed place:
+    /// yes goes there, no stays at the place above it
+    fn answer_restore(&mut self, accept: bool) {
+        let Some(offer) = self.restore_offer.take() else { return };
+        if accept {
+            self.restore(offer.found);
         } else {
-            self.current_gdp = None;
+            let here = Self::place_name(&self.location);
+            self.notify(format!("Nie przywrócono {} – zostaje {}", offer.saved, here));
         }
     }
 
-    /// Handle key events; return true to exit application
-    pub fn handle_input(&mut self, key: KeyCode) -> bool {
-        use KeyCode::*;
-        match key {
-            Char('q') => return true, // quit application
+    /// `location` for messages: "widok świata", the continent, or "country (continent)"
+    pub fn place_name(location: &Location) -> String {
+        match location {
+            Location::World => "widok świata".to_string(),
+            Location::Continent(continent) => continent.clone(),
+            Location::Country { continent, country } => format!("{} ({})", country, continent),
+        }
+    }
 
-            Tab => {
-                // Toggle GDP chart or cycle panel focus
-                if self.level == GeoLevel::Country && self.current_gdp.is_some() {
-                    self.gdp_chart_active = !self.gdp_chart_active;
-                    if self.gdp_chart_active {
-                        // Load full GDP history for chart view
-                        if let Some(data) = &self.gdp_data {
-                            let country = &self.list_items[self.selected];
-                            self.all_gdp_data = data
-                                .get_all_gdp_data(country)
-                                .map(|btree| btree.iter()
-                                    .map(|(&y, &v)| (y.to_string(), v))
-                                    .collect());
-                        }
-                    } else {
-                        // Clear detailed GDP history on exit
-                        self.all_gdp_data = None;
-                    }
-                } else {
-                    // Cycle focus between left, center, and right panels
-                    self.active_panel = match self.active_panel {
-                        Panel::Left => Panel::Center,
-                        Panel::Center => Panel::Right,
-                        Panel::Right => Panel::Left,
-                    };
-                }
+    /// Countries listed for the world (`continent` None) or one continent, against the
+    /// features of `view`
+    fn map_count(cache: &mut DataCache, continent: Option<&str>, view: &MapView) -> CountryCount {
+        let mut lists = cache.load_continent_mappings().unwrap_or_default();
+        if let Some(continent) = continent {
+            lists.retain(|name, _| country_key(name) == country_key(continent));
+        }
+        map_draw::count_countries(&lists, view.feature_names())
+    }
+
+    /// Info panel summary for a loaded map: countries from the lists, the map's features
+    /// in parentheses, and skipped features
+    fn map_info(title: &str, view: &MapView, count: &CountryCount) -> String {
+        let countries = humanize::format_count(count.countries, &humanize::KRAJE);
+        let features = humanize::format_count(count.features, &humanize::OBIEKTY);
+        let mut text = if count.continents > 1 {
+            format!("{} – {} z {} kontynentów ({} na mapie)", title, countries, count.continents, features)
+        } else {
+            format!("{} – {} ({} na mapie)", title, countries, features)
+        };
+        if !view.skipped().is_empty() {
+            text += &format!("\nPominięto obiektów: {}", view.skipped().len());
+        }
+        text
+    }
+
+    /// Show `summary` in the panels, with `gdp_status` set from its latest GDP
+    fn set_summary(&mut self, summary: CountrySummary) {
+        self.gdp_status = match (&self.gdp_data, summary.latest_gdp()) {
+            (None, _) => GdpStatus::DatasetMissing(self.gdp_path.clone()),
+            (Some(_), Some((year, value))) => GdpStatus::Found {
+                year: year.to_string(),
+                value,
+                growth: summary.gdp.as_ref().and_then(GDPData::year_over_year),
+            },
+            (Some(_), None) => GdpStatus::NotFound(summary.name.clone()),
+        };
+        self.summary = Some(summary);
+    }
+
+    /// Replace the fun facts, focusing `focus`, and restart scrolling from the top
+    fn set_facts(&mut self, facts: Vec<String>, focus: usize) {
+        self.facts = facts;
+        self.fact_cycle = FactCycle::default();
+        self.focus_fact(focus, Instant::now());
+    }
+
+    /// Focus fact `focus` from the top, restarting the rotation timer
+    fn focus_fact(&mut self, focus: usize, now: Instant) {
+        self.fact_focus = focus;
+        self.fact_scroll = FactScroll::new(now);
+        self.fact_shown_at = now;
+    }
+
+    /// Move to the next fact of the shuffled cycle
+    fn next_fact(&mut self, now: Instant) {
+        if self.facts.len() > 1 {
+            let next = self.fact_cycle.next(self.facts.len(), self.fact_focus);
+            self.focus_fact(next, now);
+        }
+    }
+
+    /// Time between timed fact changes; None when rotation is off or there is nothing to rotate
+    fn fact_rotation(&self) -> Option<Duration> {
+        (self.config.fact_rotation_secs > 0 && self.facts.len() > 1)
+            .then(|| Duration::from_secs(self.config.fact_rotation_secs))
+    }
+
+    /// Share (1.0 down to 0.0) of the rotation period left before the fact changes;
+    /// None when facts don't rotate
+    pub fn fact_rotation_left(&self, now: Instant) -> Option<f64> {
+        let period = self.fact_rotation()?;
+        let elapsed = now.duration_since(self.fact_shown_at).min(period);
+        Some(1.0 - elapsed.as_secs_f64() / period.as_secs_f64())
+    }
+
+    /// Scroll through a focused fact that doesn't fit, then move the focus to the next
+    /// (`step` 1) or previous (-1) fact
+    fn step_fact(&mut self, step: i32) {
+        let room = if step < 0 { self.fact_scroll.offset > 0 } else { self.fact_scroll.offset < self.fact_overflow };
+        if room {
+            self.fact_scroll.scroll(step, self.fact_overflow);
+            return;
+        }
+        let next = self.fact_focus as i64 + step as i64;
+        if (0..self.facts.len() as i64).contains(&next) {
+            self.focus_fact(next as usize, Instant::now());
+            self.fact_scroll.auto = false;
+        }
+    }
+
+    /// Whether Up/Down go through the fun facts rather than the list
+    fn facts_focused(&self) -> bool {
+        self.active_panel == Panel::Right || self.level() == GeoLevel::Country
+    }
+
+    /// Open the source URL of the focused fact in the system browser
+    fn open_fact_source(&mut self) {
+        let Some(url) = self.facts.get(self.fact_focus).and_then(|fact| fact_url(fact)).map(str::to_string) else {
+            self.notify_error("Ta ciekawostka nie podaje źródła");
+            return;
+        };
+        let opener = if cfg!(target_os = "macos") {
+            "open"
+        } else if cfg!(windows) {
+            "explorer"
+        } else {
+            "xdg-open"
+        };
+        let spawned = Command::new(opener)
+            .arg(&url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                // Reap the opener in the background so it doesn't linger as a zombie
+                std::thread::spawn(move || child.wait());
+                self.notify(format!("Otwieram {}", url));
             }
+            Err(e) => self.notify_error(format!("Nie można otworzyć {}: {}", url, e)),
+        }
+    }
 
-            Up => { if self.selected > 0 { self.selected -= 1; } }
-            Down => { if self.selected + 1 < self.list_items.len() { self.selected += 1; } }
-
-            Enter => {
-                if self.gdp_chart_active { return false; }
-                let choice = self.list_items[self.selected].clone();
-                match self.level {
-                    GeoLevel::World => {
-                        // Drill down to continent level
-                        if let Ok(items) = self.cache.load_list(GeoLevel::Continent, &choice) {
-                            self.history.push((GeoLevel::World, choice.clone()));
-                            self.level = GeoLevel::Continent;
-                            self.list_items = items;
-                            self.selected = 0;
-                            if let Ok(raw) = self.cache.load_geojson(&GeoLevel::Continent, &choice) {
-                                if let Ok(view) = MapView::new(raw, &mut self.cache) {
-                                    let cnt = view.feature_count();
-                                    self.map = Some(view);
-                                    self.info = format!("{} – {} krajów\n\n{}", choice, cnt, Self::HELP_TEXT);
-                                }
-                            }
-                            self.country_info = None;
-                            self.fun_fact = None;
-                        }
-                    }
-                    GeoLevel::Continent => {
-                        // Drill down to country level
-                        if let Some((_, cont)) = self.history.last() {
-                            self.history.push((GeoLevel::Continent, cont.clone()));
-                            self.level = GeoLevel::Country;
-                            self.list_items = vec![choice.clone()];
-                            self.selected = 0;
-                            if let Ok(raw) = self.cache.load_geojson(&GeoLevel::Country, &choice) {
-                                if let Ok(view) = MapView::new(raw, &mut self.cache) {
-                                    self.map = Some(view);
-                                    self.country_info = self.cache.load_country_info(&choice).cloned();
-                                    self.fun_fact = self.cache.random_funfact(&choice);
-                                    self.info = format!("{} – 1 kraj\n\n{}", choice, Self::HELP_TEXT);
-                                    self.update_gdp(&choice);
-                                }
-                            }
-                        }
-                    }
-                    GeoLevel::Country => {}
-                }
-            }
-
-            Backspace | Esc => {
-                if self.gdp_chart_active { return false; }
-                if let Some((prev_lvl, prev_key)) = self.history.pop() {
-                    // Reset country-specific data on back
-                    self.country_info = None;
-                    self.fun_fact = None;
-                    self.current_gdp = None;
-                    self.all_gdp_data = None;
-
-                    // Navigate back to previous level
-                    if prev_lvl == GeoLevel::World {
-                        if let Ok(list) = self.cache.load_list(GeoLevel::World, "world") {
-                            self.level = GeoLevel::World;
-                            self.list_items = list;
-                            self.selected = self.list_items.iter().position(|s| s == &prev_key).unwrap_or(0);
-                            if let Ok(raw) = self.cache.load_geojson(&GeoLevel::World, "world") {
-                                if let Ok(view) = MapView::new(raw, &mut self.cache) {
-                                    let cnt = view.feature_count();
-                                    self.map = Some(view);
-                                    self.info = format!("Świat – {} krajów\n\n{}", cnt, Self::HELP_TEXT);
-                                }
-                            }
-                        }
-                    } else if prev_lvl == GeoLevel::Continent {
-                        self.level = GeoLevel::Continent;
-                        if let Ok(items) = self.cache.load_list(GeoLevel::Continent, &prev_key) {
-                            self.list_items = items;
-                            self.selected = self.list_items.iter().position(|s| s == &prev_key).unwrap_or(0);
-                            if let Ok(raw) = self.cache.load_geojson(&GeoLevel::Continent, &prev_key) {
-                                if let Ok(view) = MapView::new(raw, &mut self.cache) {
-                                    let cnt = view.feature_count();
-                                    self.map = Some(view);
-                                    self.info = format!("{} – {} krajów\n\n{}", prev_key, cnt, Self::HELP_TEXT);
-                                }
-                            }
-                        }
+    /// Go up to `ancestor`, a place above the current one, the way Back goes one level up;
+    /// returns false, changing nothing, if it isn't above the current place or can't be shown
+    pub fn go_up_to(&mut self, ancestor: Location) -> bool {
+        let trail = self.location.trail();
+        if !trail[..trail.len() - 1].contains(&ancestor) {
+            return false;
+        }
+        self.navigate(ancestor)
+    }
+
+    /// React to the mouse on the breadcrumb: a click on a place above the current one goes
+    /// up to it, moving over a segment underlines it. Moving over the map shows the
+    /// feature under the pointer in a strip, leaving the selection alone; clicking it
+    /// selects it (see `click_map`). A click on the year slider sets the active year, and
+    /// dragging from there keeps setting it. Ignored while an overlay is open; ends attract
+    /// mode like a key.
+    pub fn handle_mouse(&mut self, input: MouseInput) {
+        self.last_input = Instant::now();
+        if self.attract.is_some() {
+            self.stop_attract();
+            return;
+        }
+        let (MouseInput::Click { column, row } | MouseInput::Drag { column, row } | MouseInput::Move { column, row }) = input;
+        if self.show_help
+            || self.show_diagnostics
+            || self.tour_step.is_some()
+            || self.alias_picker.is_some()
+            || self.restore_offer.is_some()
+            || self.quiz.is_some() {
+            self.breadcrumb_hover = None;
+            self.map_hover = None;
+            self.slider_drag = false;
+            return;
+        }
+        if let MouseInput::Move { .. } = input {
+            self.hover_map(column, row);
+        }
+        let hit = self.breadcrumb.iter().position(|(_, rect)| rect.contains(Position::new(column, row)));
+        match input {
+            MouseInput::Move { .. } => {
+                self.breadcrumb_hover = hit;
+                self.slider_drag = false;
+            }
+            MouseInput::Drag { .. } => {
+                if let Some(year) = self.year_slider.as_ref().filter(|_| self.slider_drag).map(|s| s.year_along(column)) {
+                    self.set_active_year(year);
+                }
+            }
+            MouseInput::Click { .. } => {
+                let on_slider = self.year_slider.as_ref().and_then(|s| s.year_at(column, row));
+                self.slider_drag = on_slider.is_some();
+                if let Some(year) = on_slider {
+                    self.set_active_year(year);
+                } else if let Some(i) = hit {
+                    if self.go_up_to(self.breadcrumb[i].0.clone()) {
+                        self.breadcrumb_hover = None;
                     }
+                } else {
+                    self.click_map(column, row, Instant::now());
                 }
             }
+        }
+    }
 
-            _ => {}
+    /// Two clicks on the same item within this long open it, like Enter
+    const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+    /// Select what was clicked on the map at the cell `column`, `row`: on the world map the
+    /// continent of the country there, on a continent map the country itself. A second
+    /// click on the same item soon after opens it. Clicks on the sea, on a feature the list
+    /// doesn't have, or on a country's own map change nothing.
+    fn click_map(&mut self, column: u16, row: u16, now: Instant) {
+        let coordinate = self.map_area.and_then(|area| area.coordinate(column, row));
+        let Some(feature) = self.map.as_ref().zip(coordinate).and_then(|(map, (x, y))| map.feature_at(x, y)) else { return };
+        let target = match self.level() {
+            GeoLevel::World => self.map.as_ref().and_then(|map| map.continent_of(feature)).map(str::to_string),
+            GeoLevel::Continent => Some(feature.to_string()),
+            GeoLevel::Country => None,
+        };
+        let key = target.as_deref().map(country_key);
+        let Some(index) = self.list_items.iter().position(|item| Some(country_key(item)) == key) else { return };
+        self.selected = index;
+        let double = self.map_click.is_some_and(|(at, last)| last == index && now.duration_since(at) <= Self::DOUBLE_CLICK);
+        if double {
+            self.map_click = None;
+            self.open_selected();
+        } else {
+            self.map_click = Some((now, index));
         }
-        false
     }
+
+    /// Note the map feature under the cell `column`, `row` and look up its summary; none
+    /// once the pointer is off the map or over the sea
+    fn hover_map(&mut self, column: u16, row: u16) {
+        let coordinate = self.map_area.and_then(|area| area.coordinate(column, row));
+        self.map_hover = self
+            .map
+            .as_ref()
+            .zip(coordinate)
+            .and_then(|(map, (x, y))| map.feature_at(x, y))
+            .map(str::to_string);
+        if let Some(name) = &self.map_hover {
+            self.hover_summaries.get_or_build(name, &self.cache, self.gdp_data.as_ref());
+        }
+    }
+
+    /// Current geographic level
+    pub fn level(&self) -> GeoLevel {
+        self.location.level()
+    }
+
+    /// Show `loc` and record it in the navigation history; returns false if it can't be shown
+    fn navigate(&mut self, loc: Location) -> bool {
+        let shown = self.go_to(loc.clone());
+        if shown {
+            self.nav.visit(loc);
+        }
+        shown
+    }
+
+    /// Show `loc`: its list, map and panels. Coming up from a place below it selects that
+    /// place in the list. Returns false, changing nothing, if the list can't be loaded or
+    /// has nothing left in it once cleaned, which is notified.
+    fn go_to(&mut self, loc: Location) -> bool {
+        // A map still loading is for the place being left
+        if let Some(job) = self.map_job.take() {
+            job.cancel();
+        }
+        let items = match &loc {
+            Location::World => self.cache.load_list(GeoLevel::World, "world"),
+            Location::Continent(continent) => self.cache.load_list(GeoLevel::Continent, continent),
+            Location::Country { country, .. } => Ok(vec![country.clone()]),
+        };
+        let Ok(items) = items else { return false };
+        if items.is_empty() {
+            self.notify_error(format!("Lista {} jest pusta – nie ma czego pokazać", Self::place_name(&loc)));
+            return false;
+        }
+        self.map_hover = None;
+
+        // Select the place we came up from, if the new list has it
+        let child = match (&self.location, &loc) {
+            (Location::Continent(c) | Location::Country { continent: c, .. }, Location::World) => Some(c),
+            (Location::Country { country, .. }, Location::Continent(_)) => Some(country),
+            _ => None,
+        };
+        self.selected = child.and_then(|c| items.iter().position(|i| i == c)).unwrap_or(0);
+        self.list_items = items;
+        self.search = None;
+
+        self.enter_place(&loc);
+
+        // Country panels start empty; opening a country fills them again
+        self.scatter = None;
+        self.ranking = None;
+        self.legend_focus = None;
+        self.choropleth = match &loc {
+            Location::Country { .. } => None,
+            _ if self.show_choropleth => Some(self.choropleth_of(&loc)),
+            _ => None,
+        };
+        self.summary = None;
+        self.place_per_capita = self.per_capita_at(&loc);
+        self.neighbours.clear();
+        self.set_facts(Vec::new(), 0);
+        self.gdp_status = GdpStatus::NoSelection;
+        self.all_gdp_data = None;
+
+        match &loc {
+            Location::World => self.load_map(GeoLevel::World, "world", "Świat"),
+            Location::Continent(continent) => self.load_map(GeoLevel::Continent, continent, continent),
+            Location::Country { continent, country } => {
+                self.load_country(continent, country);
+                self.visited.insert(country_key(country));
+            }
+        }
+        self.report_list_issues();
+        self.location = loc;
+        self.order_list();
+        true
+    }
+
+    /// Apply the view settings saved for `loc`, or its level, over the session's own (see
+    /// `prefs`). What was toggled at the place being left, where nothing saved applied,
+    /// becomes the session's own first. A country keeps its continent's settings.
+    fn enter_place(&mut self, loc: &Location) {
+        let (place, level) = match loc {
+            Location::World => (prefs::WORLD.to_string(), prefs::WORLD),
+            Location::Continent(continent) => (prefs::continent_key(continent), prefs::CONTINENT),
+            Location::Country { .. } => return,
+        };
+        let (here, from) = (self.view_settings(), self.view_sources);
+        if self.level() != GeoLevel::Country {
+            let on_continent = self.level() == GeoLevel::Continent;
+            let defaults = &mut self.view_defaults;
+            if from.sort == Source::Global && on_continent {
+                defaults.sort = here.sort;
+            }
+            if from.choropleth == Source::Global {
+                defaults.choropleth = here.choropleth;
+            }
+            if from.labels == Source::Global {
+                defaults.labels = here.labels;
+            }
+        }
+        // The world's order is its own: no other list is sorted that way
+        let mut global = self.view_defaults.clone();
+        if level == prefs::WORLD {
+            global.sort = self.world_order.into();
+        }
+        let (settings, sources) = self.view_prefs.get().effective(&place, level, &global);
+        match loc {
+            Location::World => self.world_order = settings.sort.world_order(),
+            _ => self.continent_sort = settings.sort,
+        }
+        self.place_filter = settings.filter;
+        self.show_choropleth = settings.choropleth;
+        self.show_labels = settings.labels;
+        self.view_sources = sources;
+    }
+
+    /// View settings in force here; the filter is the open ranking's, as typed
+    fn view_settings(&self) -> ViewSettings {
+        let sort = match self.location {
+            Location::World => self.world_order.into(),
+            _ => self.continent_sort,
+        };
+        let filter = self.ranking.as_ref().map_or_else(|| self.place_filter.clone(), |r| r.input.as_str().to_string());
+        ViewSettings { sort, filter, choropleth: self.show_choropleth, labels: self.show_labels }
+    }
+
+    /// Save the view settings in force as the ones for this place (Ctrl+S), written at once
+    fn save_view(&mut self) {
+        let place = match &self.location {
+            Location::World => prefs::WORLD.to_string(),
+            Location::Continent(continent) => prefs::continent_key(continent),
+            Location::Country { .. } => return,
+        };
+        let settings = self.view_settings();
+        let mut saved = self.view_prefs.get().clone();
+        saved.save(&place, &settings);
+        self.view_prefs.set(saved);
+        self.place_filter = settings.filter;
+        self.view_sources = Sources { sort: Source::Place, filter: Source::Place, choropleth: Source::Place, labels: Source::Place };
+        match self.view_prefs.flush() {
+            Ok(()) => self.notify(format!("Zapisano ustawienia widoku: {}", Self::place_name(&self.location))),
+            Err(e) => self.notify_error(format!("Nie można zapisać ustawień widoku: {}", e)),
+        }
+    }
+
+    /// Put the list in the order in force, keeping the selection: the world's in
+    /// `world_order`, continents the world map has no geometry for ending the geographic
+    /// order by name; a continent's in `continent_sort`, countries without the value last
+    fn order_list(&mut self) {
+        let selected = self.list_items.get(self.selected).cloned();
+        match self.location.clone() {
+            Location::World => {
+                self.list_items.sort();
+                if self.world_order == WorldOrder::Geographic && let Some(map) = &self.map {
+                    let order = map.continents_west_to_east();
+                    self.list_items.sort_by_key(|c| order.iter().position(|o| o == c).unwrap_or(order.len()));
+                }
+            }
+            Location::Continent(continent) => match RankColumn::of(self.continent_sort) {
+                Some(column) => {
+                    let mut ranking = self.continent_ranking(&continent);
+                    ranking.sort_by(column);
+                    let order: Vec<String> = ranking.rows.into_iter().map(|row| row.name).collect();
+                    self.list_items.sort_by_key(|c| order.iter().position(|o| o == c).unwrap_or(order.len()));
+                }
+                None => self.list_items.sort(),
+            },
+            Location::Country { .. } => return,
+        }
+        if let Some(selected) = selected {
+            self.selected = self.list_items.iter().position(|c| *c == selected).unwrap_or(0);
+        }
+    }
+
+    /// Replace the map with the world or a continent, keeping the old one if loading fails.
+    /// Continents load in the background; `tick` shows the map once it is built.
+    fn load_map(&mut self, level: GeoLevel, key: &str, title: &str) {
+        let policy = self.config.antarctica;
+        if level == GeoLevel::Continent {
+            // A continent seen before, or cut out of the world map, is shown at once;
+            // otherwise it loads in the background
+            if self.cache.map_ready(&level, key, policy)
+                && let Ok(view) = MapView::load(&mut self.cache, &level, key, policy)
+            {
+                self.show_map(title, view, Some(key));
+                return;
+            }
+            let path = self.cache.geojson_path(&level, key);
+            let mappings = self.cache.load_continent_mappings().unwrap_or_default();
+            let hints = self.cache.render_hints().clone();
+            let low_mem = self.cache.low_mem();
+            self.map = None;
+            self.map_count = None;
+            self.info = format!("{} – wczytywanie mapy…", title);
+            self.map_job = Some(Job::spawn(title, move |progress| build_map(path, mappings, hints, policy, low_mem, progress)));
+            return;
+        }
+        if let Ok(view) = MapView::load(&mut self.cache, &level, key, policy) {
+            self.show_map(title, view, None);
+        }
+    }
+
+    /// Show `view`, the map of `title`, with constituents merged as configured; counted
+    /// against the list of `continent` only, when given
+    fn show_map(&mut self, title: &str, mut view: MapView, continent: Option<&str>) {
+        if self.config.merge_constituents {
+            view.merge_constituents(self.cache.constituents());
+        }
+        view.set_simplify(self.config.simplify_epsilon);
+        let count = Self::map_count(&mut self.cache, continent, &view);
+        self.info = Self::map_info(title, &view, &count);
+        self.map_count = Some(count);
+        self.map = Some(view);
+    }
+
+    /// Show the map of a finished background load, kept for the next visit, or say why
+    /// there is none
+    fn finish_map_job(&mut self, mut job: Job<MapView>) {
+        if let Some(view) = job.take_result() {
+            self.cache.keep_map(&GeoLevel::Continent, &job.title, self.config.antarctica, &view);
+            self.show_map(&job.title, view, Some(&job.title));
+            return;
+        }
+        match job.state() {
+            JobState::Cancelled => self.info = format!("{} – wczytywanie mapy anulowane", job.title),
+            JobState::Failed(e) => {
+                self.info = format!("{} – brak mapy", job.title);
+                self.notify_error(format!("Nie można wczytać mapy {}: {}", job.title, e));
+            }
+            _ => {}
+        }
+    }
+
+    /// Load the map and panels of `country`, a member of `continent`
+    fn load_country(&mut self, continent: &str, country: &str) {
+        // Without its own file, the country is cut out of the continent's map; a country
+        // whose features have no geometry is left for the check below
+        let view = match MapView::load(&mut self.cache, &GeoLevel::Country, country, self.config.antarctica) {
+            Ok(view) => Ok(view),
+            Err(missing) => {
+                let fallback = MapView::load(&mut self.cache, &GeoLevel::Continent, continent, OutlierPolicy::default())
+                    .and_then(|view| if view.lacks_geometry(country) { Ok(view) } else { view.into_focused(country) });
+                if fallback.as_ref().is_ok_and(|view| !view.lacks_geometry(country)) {
+                    self.notify("Granice z mapy kontynentu (brak pliku kraju)");
+                }
+                fallback.map_err(|_| missing)
+            }
+        };
+        // What the map feature says stands in for a missing country_info.json entry
+        let fallback = view.as_ref().ok().and_then(|view| view.properties(country)).and_then(FallbackInfo::from_properties);
+        match view {
+            // The panels still show what is known about it; the map stays empty
+            Ok(view) if view.lacks_geometry(country) => {
+                self.map = None;
+                self.notify_error(format!("{}: brak danych o granicach", country));
+            }
+            Ok(mut view) => {
+                // Parts kept as separate features elsewhere (Greenland for Denmark)
+                view.include_constituents(country, &mut self.cache, self.config.merge_constituents);
+                // Its neighbours around it, so it isn't drawn alone
+                let neighbours = self.cache.neighbours_of(country).to_vec();
+                view.include_neighbours(&neighbours, continent, &mut self.cache);
+                self.neighbours = neighbours.iter().map(|n| country_key(n)).collect();
+                view.set_simplify(self.config.simplify_epsilon);
+                self.map = Some(view);
+            }
+            Err(e) => {
+                self.map = None;
+                self.notify_error(format!("Brak mapy dla {}: {}", country, e));
+            }
+        }
+        let mut summary = CountrySummary::build(country, &self.cache, self.gdp_data.as_ref());
+        summary.fallback = fallback;
+        let first = if summary.facts.is_empty() { 0 } else { rng().random_range(0..summary.facts.len()) };
+        self.set_facts(summary.facts.clone(), first);
+        self.info = format!("{} – 1 kraj", country);
+        self.map_count = None;
+        self.set_summary(summary);
+    }
+
+    /// Open `country` directly from any level, as if reached via World → `continent`
+    pub fn jump_to_country(&mut self, continent: &str, country: &str) {
+        self.navigate(Location::Country { continent: continent.to_string(), country: country.to_string() });
+    }
+
+    /// Continent whose list has `name`, and the name as that list spells it; None when no
+    /// continent list has it, e.g. a territory's sovereign without data of its own
+    fn locate(&mut self, name: &str) -> Option<(String, String)> {
+        let continents = self.cache.load_list(GeoLevel::World, "world").ok()?;
+        continents.into_iter().find_map(|continent| {
+            let members = self.cache.load_list(GeoLevel::Continent, &continent).ok()?;
+            let member = members.into_iter().find(|m| self.cache.same_place(m, name))?;
+            Some((continent, member))
+        })
+    }
+
+    /// Territory `t` opens from the open country: the first of its own, or for a territory
+    /// the next of its sovereign's, wrapping around; None when there is no other
+    fn next_territory(&self) -> Option<String> {
+        let summary = self.summary.as_ref()?;
+        let ring = match &summary.sovereign {
+            Some(sovereign) if summary.territories.is_empty() => self.cache.territories_of(sovereign),
+            _ => summary.territories.iter().map(String::as_str).collect(),
+        };
+        let at = ring.iter().position(|t| self.cache.same_place(t, &summary.name));
+        let next = ring.get(at.map_or(0, |i| (i + 1) % ring.len()))?;
+        (!self.cache.same_place(next, &summary.name)).then(|| next.to_string())
+    }
+
+    /// Open `name`, a territory or sovereign of the open country, wherever the lists put it
+    fn open_related(&mut self, name: &str) {
+        match self.locate(name) {
+            Some((continent, country)) => self.jump_to_country(&continent, &country),
+            None => self.notify_error(format!("{} nie ma własnych danych na listach kontynentów", name)),
+        }
+    }
+
+    /// Member of `continent` with the highest latest GDP; ties go to the alphabetically first name
+    fn largest_economy(&mut self, continent: &str) -> Option<String> {
+        let gdp = self.gdp_data.as_ref()?;
+        let members = self.cache.load_list(GeoLevel::Continent, continent).ok()?;
+        members
+            .into_iter()
+            .filter_map(|name| {
+                let (_, value) = gdp.get_latest_gdp(self.cache.gdp_name(&name))?;
+                Some((value, name))
+            })
+            .max_by(|(va, na), (vb, nb)| va.total_cmp(vb).then_with(|| nb.cmp(na)))
+            .map(|(_, name)| name)
+    }
+
+    /// Population and latest GDP of every member of `continent`
+    fn continent_scatter(&mut self, continent: &str) -> Scatter {
+        let members = self.cache.load_list(GeoLevel::Continent, continent).unwrap_or_default();
+        let gdp = self.gdp_data.as_ref();
+        let cache = &self.cache;
+        Scatter::assemble(&members, |name| {
+            let population = cache.load_country_info(name)?.population;
+            let (_, value) = gdp?.get_latest_gdp(cache.gdp_name(name))?;
+            Some((population, value))
+        })
+    }
+
+    /// Population, area and latest GDP of every member of `continent`
+    fn continent_ranking(&mut self, continent: &str) -> Ranking {
+        let members = self.cache.load_list(GeoLevel::Continent, continent).unwrap_or_default();
+        let rows = members
+            .iter()
+            .map(|name| {
+                let summary = CountrySummary::build(name, &self.cache, self.gdp_data.as_ref());
+                RankRow {
+                    population: summary.info.as_ref().map(|i| i.population),
+                    area: summary.info.as_ref().map(|i| i.area),
+                    gdp: summary.latest_gdp().map(|(_, value)| value),
+                    name: summary.name,
+                }
+            })
+            .collect();
+        Ranking::new(rows)
+    }
+
+    /// Countries of `location` (every continent's on the world map) bucketed by GDP in the
+    /// active year (latest when none is set), one bucket per choropleth color; gaps up to
+    /// `interpolate_gdp_gaps` years are bridged
+    fn choropleth_of(&mut self, location: &Location) -> Choropleth {
+        let members: Vec<String> = match location {
+            Location::World => {
+                let mappings = self.cache.load_continent_mappings().unwrap_or_default();
+                mappings.into_values().flatten().collect::<BTreeSet<_>>().into_iter().collect()
+            }
+            Location::Continent(continent) => self.cache.load_list(GeoLevel::Continent, continent).unwrap_or_default(),
+            Location::Country { .. } => Vec::new(),
+        };
+        let gdp = self.gdp_data.as_ref();
+        let cache = &self.cache;
+        let (year, max_gap) = (self.active_year, self.config.interpolate_gdp_gaps);
+        let mut choropleth = Choropleth::assemble(&members, theme::CHOROPLETH.len(), |name| {
+            let gdp_name = cache.gdp_name(name);
+            match year {
+                Some(year) => gdp_reader::value_in_year(gdp?.get_all_gdp_data(gdp_name)?, year, max_gap),
+                None => gdp?.get_latest_gdp(gdp_name).map(|(_, value)| YearValue::Reported(value)),
+            }
+        });
+        choropleth.year = year;
+        choropleth
+    }
+
+    /// Countries that can be marked for the comparison grid
+    pub const MAX_MARKED: usize = 4;
+
+    /// Mark the selected country for comparison, or unmark it if already marked
+    fn toggle_mark(&mut self) {
+        if self.level() == GeoLevel::World {
+            return;
+        }
+        let Some(name) = self.list_items.get(self.selected).cloned() else { return };
+        if let Some(pos) = self.marked.iter().position(|m| m.name == name) {
+            self.marked.remove(pos);
+        } else if self.marked.len() < Self::MAX_MARKED {
+            let color = Self::mark_color(&self.marked, theme::MARK_COLORS.len());
+            self.marked.push(Mark { name, color });
+        } else {
+            self.notify_error(format!("Można porównać najwyżej {}", humanize::format_count(Self::MAX_MARKED, &humanize::KRAJE)));
+        }
+    }
+
+    /// Color for a new mark among `marks`, from a palette of `palette` colors: the first
+    /// one no mark holds, so an unmarked country's color is the next given out and the
+    /// same marks in the same order always get the same colors. With every color held,
+    /// the first of those held by the fewest marks.
+    pub fn mark_color(marks: &[Mark], palette: usize) -> usize {
+        (0..palette.max(1))
+            .min_by_key(|&color| marks.iter().filter(|m| m.color == color).count())
+            .unwrap_or(0)
+    }
+
+    /// Population-weighted GDP per capita of `names`, from their latest GDP; None without
+    /// the GDP dataset or when none of them has both figures
+    fn per_capita_of<'a>(&self, names: impl IntoIterator<Item = &'a String>) -> Option<PerCapita> {
+        let gdp = self.gdp_data.as_ref()?;
+        aggregate_per_capita(names.into_iter().map(|name| {
+            let value = gdp.get_latest_gdp(self.cache.gdp_name(name)).map(|(_, value)| value);
+            (value, self.cache.load_country_info(name).map(|info| info.population))
+        }))
+    }
+
+    /// GDP per capita of every country listed at `location`: the world's across all
+    /// continents, each once, or a continent's members; None in a country
+    fn per_capita_at(&mut self, location: &Location) -> Option<PerCapita> {
+        let names: BTreeSet<String> = match location {
+            Location::World => self.cache.load_continent_mappings().ok()?.into_values().flatten().collect(),
+            Location::Continent(continent) => self.cache.load_list(GeoLevel::Continent, continent).ok()?.into_iter().collect(),
+            Location::Country { .. } => return None,
+        };
+        self.per_capita_of(&names)
+    }
+
+    /// Show the GDP chart grid of the marked countries
+    fn open_compare(&mut self) {
+        self.compare_per_capita = self.per_capita_of(self.marked.iter().map(|mark| &mark.name));
+        let Some(gdp) = &self.gdp_data else { return };
+        let charts: Vec<ChartData> = self.marked
+            .iter()
+            .filter_map(|mark| {
+                let years = gdp.get_all_gdp_data(self.cache.gdp_name(&mark.name))?;
+                Some(ChartData::new(&mark.name, years, mark.color))
+            })
+            .filter(|chart| !chart.points.is_empty())
+            .collect();
+        if charts.is_empty() {
+            self.notify_error("Zaznacz kraje z danymi GDP (m), aby je porównać");
+        } else {
+            self.compare = Some(charts);
+        }
+    }
+
+    /// GDP dataset names offered in the alias picker
+    const ALIAS_OPTIONS: usize = 8;
+
+    /// Offer the GDP dataset names closest to the country the GDP lookup missed
+    fn open_alias_picker(&mut self) {
+        let (GdpStatus::NotFound(country), Some(gdp)) = (&self.gdp_status, &self.gdp_data) else { return };
+        let options: Vec<String> = suggest::closest(country, gdp.countries().map(|(name, _, _)| name), Self::ALIAS_OPTIONS)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        self.alias_picker = Some(AliasPicker { country: country.clone(), options, selected: 0 });
+    }
+
+    /// Save the picked GDP name as the country's alias and look its GDP up again. Other
+    /// map countries already resolving to that name are reported, but don't block the alias.
+    fn apply_alias(&mut self) {
+        let Some(picker) = self.alias_picker.take() else { return };
+        let Some(choice) = picker.options.get(picker.selected) else { return };
+        if let Err(e) = self.cache.set_alias(&picker.country, choice) {
+            self.notify_error(format!("Nie zapisano aliases.json: {}", e));
+            return;
+        }
+        self.set_summary(CountrySummary::build(&picker.country, &self.cache, self.gdp_data.as_ref()));
+        self.hover_summaries.clear();
+
+        let key = country_key(&picker.country);
+        let mut others: Vec<String> = self.cache
+            .load_continent_mappings()
+            .unwrap_or_default()
+            .into_values()
+            .flatten()
+            .filter(|c| country_key(c) != key && self.cache.gdp_name(c).eq_ignore_ascii_case(choice))
+            .collect();
+        others.sort();
+        others.dedup();
+        if others.is_empty() {
+            self.notify(format!("Alias zapisany: {} → {}", picker.country, choice));
+        } else {
+            self.notify(format!("Alias zapisany, ale \"{}\" wskazuje już: {}", choice, others.join(", ")));
+        }
+    }
+
+    /// Open the GDP chart for the selected country, with the cursor on the active year if it has data
+    fn open_chart(&mut self) {
+        self.gdp_chart_active = true;
+        // Full GDP history for the chart view
+        self.all_gdp_data = self.summary.as_ref().and_then(|summary| summary.gdp.clone());
+        let years = self.chart_years();
+        self.chart_cursor = self.active_year
+            .filter(|y| years.contains(y))
+            .or_else(|| years.last().copied());
+    }
+
+    /// Close the GDP chart; `active_year` is kept so the next view resumes there
+    fn close_chart(&mut self) {
+        self.gdp_chart_active = false;
+        // Clear detailed GDP history on exit
+        self.all_gdp_data = None;
+        self.chart_cursor = None;
+    }
+
+    /// Sorted years available in the open GDP chart
+    fn chart_years(&self) -> Vec<u16> {
+        self.all_gdp_data.iter().flat_map(|all| all.keys().copied()).collect()
+    }
+
+    /// Move the chart cursor by `step` data points and make its year the active year
+    fn move_chart_cursor(&mut self, step: isize) {
+        let years = self.chart_years();
+        let Some(pos) = self.chart_cursor.and_then(|y| years.iter().position(|&v| v == y)) else { return };
+        let next = pos.saturating_add_signed(step).min(years.len() - 1);
+        self.chart_cursor = Some(years[next]);
+        self.active_year = self.chart_cursor;
+    }
+
+    /// Make `year` the active year, as the year slider picks it: in the GDP chart the
+    /// cursor goes to the nearest year with data and that becomes the active year; a
+    /// shown choropleth is bucketed again for it
+    fn set_active_year(&mut self, year: u16) {
+        if self.gdp_chart_active {
+            let years = self.chart_years();
+            let Some(&nearest) = years.iter().min_by_key(|&&y| y.abs_diff(year)) else { return };
+            self.chart_cursor = Some(nearest);
+            self.active_year = Some(nearest);
+            return;
+        }
+        if self.active_year == Some(year) {
+            return;
+        }
+        self.active_year = Some(year);
+        if self.choropleth.is_some() {
+            self.choropleth = Some(self.choropleth_of(&self.location.clone()));
+        }
+    }
+
+    /// Move the active year by `step` within the GDP dataset's years, starting from the
+    /// latest when none is set (the map's year slider keys)
+    fn step_active_year(&mut self, step: isize) {
+        let Some(span) = self.gdp_data.as_ref().and_then(GDPData::year_span) else { return };
+        let year = self.active_year.unwrap_or(*span.end()).clamp(*span.start(), *span.end());
+        self.set_active_year(year.saturating_add_signed(step as i16).clamp(*span.start(), *span.end()));
+    }
+
+    /// Select the continent `step` places east (or west, if negative) of the current one, wrapping around
+    fn cycle_continent(&mut self, step: isize) {
+        let Some(map) = &self.map else { return };
+        let order = map.continents_west_to_east();
+        if order.is_empty() {
+            return;
+        }
+        let current = self.list_items.get(self.selected);
+        let next = match order.iter().position(|c| Some(c) == current) {
+            Some(pos) => (pos as isize + step).rem_euclid(order.len() as isize) as usize,
+            None if step < 0 => order.len() - 1,
+            None => 0,
+        };
+        if let Some(idx) = self.list_items.iter().position(|c| c == &order[next]) {
+            self.selected = idx;
+        }
+    }
+
+    /// Notify about list entries that can't be opened, found since the last report
+    fn report_list_issues(&mut self) {
+        let issues = self.cache.take_new_list_issues();
+        match issues.as_slice() {
+            [] => {}
+            [issue] => self.notify_error(issue.to_string()),
+            [issue, rest @ ..] => self.notify_error(format!("{} (+{} więcej, zob. check-data)", issue, rest.len())),
+        }
+    }
+
+    /// Color support of the terminal, guessed from the environment
+    fn detect_colors() -> ColorSupport {
+        ColorSupport::detect(|name| std::env::var(name).ok())
+    }
+
+    /// Modification time of `path`, None when it can't be read
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+
+    /// Re-read `config.json` and apply its settings, keys and colors together, or keep all
+    /// of the current ones and say why the file was rejected
+    pub fn reload_config(&mut self) {
+        let path = self.paths.file(Artifact::Data, "config.json");
+        self.config_seen = Self::modified(&path);
+        // A missing file means defaults, as at startup
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => b"{}".to_vec(),
+            Err(e) => return self.notify_error(format!("Nie wczytano config.json: {}", e)),
+        };
+        match Reload::parse(&bytes, self.colors_override, Self::detect_colors()) {
+            Ok(reload) => {
+                let warnings = self.apply_reload(reload);
+                if warnings.is_empty() {
+                    self.notify("Wczytano config.json");
+                } else {
+                    self.notify(format!("Wczytano config.json; {}", warnings.join("; ")));
+                }
+            }
+            Err(e) => self.notify_error(format!("Odrzucono zmiany: {}", e)),
+        }
+    }
+
+    /// Swap in the settings of `reload` in one step; returns its warnings. The symbol set
+    /// stays the one picked at startup.
+    pub fn apply_reload(&mut self, reload: Reload) -> Vec<String> {
+        let Reload { config, keys, theme, warnings } = reload;
+        let theme = theme.with_symbols(self.theme.symbols());
+        (self.config, self.keys, self.theme) = (config, keys, theme);
+        if let Some(map) = &mut self.map {
+            map.set_simplify(self.config.simplify_epsilon);
+        }
+        warnings
+    }
+
+    /// Show a transient message in the status bar
+    pub fn notify(&mut self, text: impl Into<String>) {
+        self.feedback(Severity::Info, text);
+    }
+
+    /// Show a message about an action that failed or had nothing to act on
+    pub fn notify_error(&mut self, text: impl Into<String>) {
+        self.feedback(Severity::Error, text);
+    }
+
+    /// The one way notifications reach the user: shown in the status bar, and ringing
+    /// the bell (sounded, or flashed with `visual_bell`) when the `bell` setting says so
+    fn feedback(&mut self, severity: Severity, text: impl Into<String>) {
+        let now = Instant::now();
+        self.notification = Some(Notification { text: text.into(), severity, shown_at: now });
+        if self.config.bell.rings(severity) {
+            if self.config.visual_bell {
+                self.flash_until = Some(now + Self::FLASH_DURATION);
+            } else {
+                self.bell_pending = true;
+            }
+        }
+    }
+
+    /// Whether the terminal bell should sound now; the driver rings it once per call
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_pending)
+    }
+
+    /// Ask the driver to open the data file behind the focused panel in the editor (Ctrl+E;
+    /// see `editor::data_file`), noting how many entries it has now
+    fn start_edit(&mut self) {
+        if !self.cache.editable() {
+            self.notify_error(format!("Dane pochodzą z {} – popraw pliki na serwerze", self.cache.origin()));
+            return;
+        }
+        let own_map = match &self.location {
+            Location::Country { country, .. } => self.cache.geojson_path(&GeoLevel::Country, country).exists(),
+            _ => true,
+        };
+        let file = editor::data_file(&self.location, self.active_panel, own_map);
+        let path = self.cache.data_path(&file.name());
+        let before = fs::read(&path).ok().and_then(|bytes| editor::count_entries(&bytes).ok());
+        let command = editor::command(|name| std::env::var(name).ok());
+        self.pending_edit = Some(DataEdit { file, modified: Self::modified(&path), path, command, before });
+    }
+
+    /// The data file to open in the editor, once per Ctrl+E
+    pub fn take_edit(&mut self) -> Option<DataEdit> {
+        self.pending_edit.take()
+    }
+
+    /// Pick up after the editor opened for `edit` exited with `outcome`: when the file was
+    /// saved as valid JSON, drop what was read from it and show the place again, keeping
+    /// the selection, with the change in entries in the status bar. A file left broken is
+    /// reported and the view stays as it was.
+    pub fn finish_edit(&mut self, edit: DataEdit, outcome: Result<(), String>) {
+        let name = edit.file.name();
+        if let Err(e) = outcome {
+            return self.notify_error(format!("{}: {}", name, e));
+        }
+        if Self::modified(&edit.path) == edit.modified {
+            return self.notify(format!("{}: bez zmian", name));
+        }
+        let after = match fs::read(&edit.path) {
+            Ok(bytes) => editor::count_entries(&bytes).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        let after = match after {
+            Ok(after) => after,
+            Err(e) => return self.notify_error(format!("{}: nie wczytano po edycji ({}), widok bez zmian", name, e)),
+        };
+        self.cache.forget(&edit.file);
+        self.hover_summaries.clear();
+        let selected = self.list_items.get(self.selected).cloned();
+        if !self.go_to(self.location.clone()) {
+            return self.notify_error(format!("{}: nie można pokazać {} po edycji", name, Self::place_name(&self.location)));
+        }
+        if let Some(i) = selected.and_then(|s| self.list_items.iter().position(|item| *item == s)) {
+            self.selected = i;
+        }
+        self.notify(editor::change_text(&name, edit.before, after));
+    }
+
+    /// The current location as a persistable session
+    fn current_session(&self) -> Session {
+        // Attract mode's random countries are not where the user is
+        let (location, visited) = match &self.attract {
+            Some(attract) => (&attract.location, &attract.visited),
+            None => (&self.location, &self.visited),
+        };
+        let (continent, country) = match location {
+            Location::World => (None, None),
+            Location::Continent(continent) => (Some(continent.clone()), None),
+            Location::Country { continent, country } => (Some(continent.clone()), Some(country.clone())),
+        };
+        let (search_history, filter_history) = if self.config.save_history {
+            (self.search_history.entries().to_vec(), self.filter_history.entries().to_vec())
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        Session { continent, country, tour_seen: self.tour_seen, visited: visited.clone(), search_history, filter_history }
+    }
+
+    /// Whether attract mode may take over: the plain three-panel view with nothing open
+    /// that restoring the location wouldn't bring back
+    fn can_attract(&self) -> bool {
+        !(self.show_help
+            || self.show_diagnostics
+            || self.tour_step.is_some()
+            || self.gdp_chart_active
+            || self.compare.is_some()
+            || self.alias_picker.is_some()
+            || self.restore_offer.is_some()
+            || self.quiz.is_some()
+            || self.search.is_some()
+            || self.scatter.is_some()
+            || self.ranking.is_some()
+            || self.legend_focus.is_some()
+            || self.map_job.is_some())
+    }
+
+    /// Start attract mode, remembering where the user is
+    fn start_attract(&mut self, now: Instant) {
+        self.attract = Some(Attract {
+            location: self.location.clone(),
+            selected: self.selected,
+            panel: self.active_panel,
+            fact_focus: self.fact_focus,
+            visited: self.visited.clone(),
+            next_at: now,
+        });
+    }
+
+    /// Show a random country other than the current one, without recording it in the
+    /// navigation history
+    fn attract_step(&mut self) {
+        let countries: Vec<Location> = self
+            .cache
+            .load_continent_mappings()
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(continent, members)| {
+                members.into_iter().map(move |country| Location::Country { continent: continent.clone(), country })
+            })
+            .filter(|loc| loc != &self.location)
+            .collect();
+        if !countries.is_empty() {
+            let pick = rng().random_range(0..countries.len());
+            self.go_to(countries[pick].clone());
+        }
+    }
+
+    /// End attract mode and go back to exactly where the user was when it started
+    fn stop_attract(&mut self) {
+        let Some(attract) = self.attract.take() else { return };
+        self.go_to(attract.location);
+        self.selected = attract.selected.min(self.list_items.len().saturating_sub(1));
+        self.active_panel = attract.panel;
+        if attract.fact_focus < self.facts.len() {
+            self.focus_fact(attract.fact_focus, Instant::now());
+        }
+        self.visited = attract.visited;
+    }
+
+    /// Describe the map in sentences instead of drawing it, with plain symbols and a
+    /// still screen, for screen readers
+    pub fn make_accessible(&mut self) {
+        self.accessible = true;
+        self.animations = false;
+        let plain = if self.theme.symbols().ascii { &theme::PLAIN_ASCII } else { &theme::PLAIN };
+        self.theme = Theme::new(self.theme.support()).with_symbols(plain);
+    }
+
+    /// Start a quiz over the countries of the map shown: the world's, or the continent's
+    fn start_quiz(&mut self, mode: QuizMode) {
+        let Some(map) = &self.map else { return };
+        let continent = match &self.location {
+            Location::World => None,
+            Location::Continent(continent) => Some(continent.as_str()),
+            Location::Country { .. } => return,
+        };
+        let names: BTreeSet<&str> = map.feature_names().collect();
+        let candidates = names
+            .into_iter()
+            .filter_map(|country| {
+                let continent = continent.or_else(|| map.continent_of(country))?;
+                Some(Candidate { continent: continent.to_string(), country: country.to_string() })
+            })
+            .collect();
+        match Quiz::start(mode, candidates, self.quiz_stats.get()) {
+            Some(quiz) => self.quiz = Some(quiz),
+            None if mode == QuizMode::Review => self.notify_error("Brak krajów do powtórki – wszystkie odpowiedzi były dobre"),
+            None => self.notify_error("Za mało krajów na mapie na quiz"),
+        }
+    }
+
+    /// Answer the current quiz question with the selected option and say how it went
+    fn answer_quiz(&mut self) {
+        let Some(quiz) = &mut self.quiz else { return };
+        let mut stats = self.quiz_stats.get().clone();
+        let Some(answer) = quiz.answer(&mut stats) else { return };
+        self.quiz_stats.set(stats);
+        if answer.correct {
+            self.notify("Dobrze!");
+        } else {
+            self.notify_error(format!("Źle – to był kraj: {}", answer.country));
+        }
+    }
+
+    /// Show the walkthrough from its first step
+    pub fn start_tour(&mut self) {
+        self.tour_step = Some(0);
+    }
+
+    /// Next walkthrough step, or close it after the last one or on Esc;
+    /// a closed walkthrough is remembered and not shown on later starts
+    fn advance_tour(&mut self, key: KeyCode) {
+        let next = self.tour_step.map_or(0, |s| s + 1);
+        if key == KeyCode::Esc || next >= TourTarget::STEPS.len() {
+            self.tour_step = None;
+            self.tour_seen = true;
+        } else {
+            self.tour_step = Some(next);
+        }
+    }
+
+    /// Record the current location and write it out immediately; called on quit
+    pub fn save_session(&mut self) -> io::Result<()> {
+        let current = self.current_session();
+        if self.session.get() != &current {
+            self.session.set(current);
+        }
+        self.quiz_stats.flush()?;
+        self.session.flush()
+    }
+
+    /// Advance time-driven UI state; called once per main loop iteration
+    pub fn tick(&mut self, now: Instant) {
+        // Data served from a stale copy, e.g. while the server is unreachable
+        match self.cache.take_notices().as_slice() {
+            [] => {}
+            [notice] => self.notify_error(notice.clone()),
+            [notice, rest @ ..] => self.notify_error(format!("{} (+{} plików więcej)", notice, rest.len())),
+        }
+        let idle_after = Duration::from_secs(self.config.attract_after_secs);
+        if self.attract.is_none()
+            && !idle_after.is_zero()
+            && now.saturating_duration_since(self.last_input) >= idle_after
+            && self.can_attract() {
+            self.start_attract(now);
+        }
+        if let Some(attract) = &mut self.attract
+            && now >= attract.next_at {
+            attract.next_at = now + Attract::LINGER;
+            self.attract_step();
+        }
+        let current = self.current_session();
+        if self.session.get() != &current {
+            self.session.set(current);
+        }
+        if let Err(e) = self.session.save_due(now) {
+            self.notify_error(format!("Nie można zapisać sesji: {}", e));
+        }
+        if let Err(e) = self.quiz_stats.save_due(now) {
+            self.notify_error(format!("Nie można zapisać wyników quizu: {}", e));
+        }
+        if self.animations && self.config.fact_autoscroll {
+            self.fact_scroll.tick(now, self.fact_overflow);
+        }
+        if let Some(period) = self.fact_rotation()
+            && now.duration_since(self.fact_shown_at) >= period {
+            self.next_fact(now);
+        }
+        if self.watch && now.duration_since(self.watch_at) >= Self::WATCH_INTERVAL {
+            self.watch_at = now;
+            if Self::modified(&self.paths.file(Artifact::Data, "config.json")) != self.config_seen {
+                self.reload_config();
+            }
+        }
+        if self.animations && now.duration_since(self.pulse_at) >= Self::PULSE_PERIOD {
+            self.pulse_bright = !self.pulse_bright;
+            self.pulse_at = now;
+        }
+        if self.flash_until.is_some_and(|until| now >= until) {
+            self.flash_until = None;
+        }
+        if self.notification.as_ref().is_some_and(|n| now.duration_since(n.shown_at) >= Notification::TTL) {
+            self.notification = None;
+        }
+        self.poll_map_job();
+    }
+
+    /// Show the continent map loading in the background once it's ready
+    pub fn poll_map_job(&mut self) {
+        if self.map_job.as_mut().is_some_and(|job| job.poll().finished())
+            && let Some(job) = self.map_job.take() {
+            self.finish_map_job(job);
+        }
+    }
+
+    /// Index into `list_items` of the item the map and plots highlight: the top search
+    /// match while searching, else the selection
+    pub fn highlighted(&self) -> usize {
+        self.search.as_ref().map_or(self.selected, ListSearch::current)
+    }
+
+    /// Snapshot of what the next frame draws
+    pub fn view_model(&self) -> ViewModel<'_> {
+        ViewModel {
+            cache: &self.cache,
+            config: &self.config,
+            keys: &self.keys,
+            theme: &self.theme,
+            paths: &self.paths,
+            context: self.context(),
+            location: &self.location,
+            active_panel: self.active_panel,
+            show_help: self.show_help,
+            show_diagnostics: self.show_diagnostics,
+            tour_step: self.tour_step,
+            attract: self.attract.is_some(),
+            notification: self.notification.as_ref(),
+            flash: self.flash_until.is_some(),
+            breadcrumb_hover: self.breadcrumb_hover,
+            list_items: &self.list_items,
+            selected: self.selected,
+            highlighted: self.highlighted(),
+            search: self.search.as_ref(),
+            search_history: &self.search_history,
+            filter_history: &self.filter_history,
+            visited: &self.visited,
+            marked: &self.marked,
+            map: self.map.as_ref(),
+            neighbours: &self.neighbours,
+            map_job: self.map_job.as_ref(),
+            map_count: self.map_count.as_ref(),
+            world_outline: &self.world_outline,
+            show_minimap: self.show_minimap,
+            show_labels: self.show_labels,
+            show_graticule: self.show_graticule,
+            auto_zoom: self.auto_zoom,
+            list_sort: match self.location {
+                Location::World => Some((self.world_order.into(), self.view_sources.sort)),
+                Location::Continent(_) => Some((self.continent_sort, self.view_sources.sort)),
+                Location::Country { .. } => None,
+            },
+            projection: self.projection,
+            map_marker: (!self.theme.symbols().ascii).then(|| self.map_marker.marker()),
+            map_style: self.map_style,
+            accessible: self.accessible,
+            pulse_bright: self.pulse_bright,
+            scatter: self.scatter.as_ref(),
+            ranking: self.ranking.as_ref(),
+            choropleth: self.choropleth.as_ref(),
+            legend_focus: self.legend_focus,
+            continent_tints: self.continent_tints(),
+            map_hover: self.map_hover.as_deref().and_then(|name| self.hover_summaries.peek(name)),
+            map_cache: self.map_cache.as_ref(),
+            map_frames: self.map_frames,
+            info: &self.info,
+            summary: self.summary.as_ref(),
+            place_per_capita: self.place_per_capita,
+            facts: &self.facts,
+            fact_focus: self.fact_focus,
+            fact_offset: self.fact_scroll.offset,
+            fact_rotation_left: self.fact_rotation_left(Instant::now()),
+            gdp_data: self.gdp_data.as_ref(),
+            gdp_path: &self.gdp_path,
+            gdp_status: &self.gdp_status,
+            gdp_chart_active: self.gdp_chart_active,
+            all_gdp_data: self.all_gdp_data.as_ref(),
+            chart_cursor: self.chart_cursor,
+            active_year: self.active_year,
+            year_span: self.gdp_data.as_ref().and_then(GDPData::year_span),
+            chart_log: self.chart_log,
+            compare: self.compare.as_deref(),
+            compare_per_capita: self.compare_per_capita,
+            compare_shared_y: self.compare_shared_y,
+            alias_picker: self.alias_picker.as_ref(),
+            restore_offer: self.restore_offer.as_ref(),
+            quiz: self.quiz.as_ref(),
+        }
+    }
+
+    /// Continent tints of the world map with `continent_colors`; empty elsewhere
+    fn continent_tints(&self) -> BTreeMap<String, theme::Rgb> {
+        match &self.map {
+            Some(map) if self.config.continent_colors && self.location == Location::World => map.continent_tints(),
+            _ => BTreeMap::new(),
+        }
+    }
+
+    /// Take in what drawing the last frame found out about the layout
+    pub fn apply_drawn(&mut self, drawn: Drawn) {
+        self.breadcrumb = drawn.breadcrumb;
+        self.fact_overflow = drawn.fact_overflow;
+        // The hovered feature goes with the map it was on
+        if drawn.map_area.is_none() {
+            self.map_hover = None;
+        }
+        self.map_area = drawn.map_area;
+        self.year_slider = drawn.year_slider;
+        if drawn.map_area.is_some() {
+            self.map_frames.0 += 1;
+            self.map_frames.1 += drawn.map_reused as u64;
+        }
+        if let Some(rendered) = drawn.map_rendered {
+            self.map_cache = Some(rendered);
+        }
+    }
+
+    /// Current level, chart and focus, for deciding which actions apply
+    pub fn context(&self) -> Context {
+        Context {
+            level: self.level(),
+            chart: self.gdp_chart_active,
+            compare: self.compare.is_some(),
+            gdp_missing: matches!(self.gdp_status, GdpStatus::NotFound(_)),
+            choropleth: self.choropleth.is_some(),
+            panel: self.active_panel,
+            ranking: self.ranking.is_some(),
+            zoomed: self.map.as_ref().is_some_and(MapView::is_zoomed),
+            territory: self.summary.as_ref().is_some_and(|s| s.sovereign.is_some()),
+            territories: self.next_territory().is_some(),
+        }
+    }
+
+    /// Offer `action` to the focused panel before the global bindings; true when it took it
+    fn panel_action(&mut self, action: Action) -> bool {
+        // The GDP chart in the center takes the arrows, whatever has the focus
+        if self.gdp_chart_active {
+            return false;
+        }
+        match self.active_panel {
+            Panel::Left => self.list_action(action),
+            Panel::Center => self.map_action(action),
+            Panel::Right => self.details_action(action),
+        }
+    }
+
+    /// List keys: Up/Down move the selection and Enter opens it. A country's list holds
+    /// only the country, so there the arrows go through its fun facts.
+    fn list_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Up | Action::Down if self.level() == GeoLevel::Country => return self.details_action(action),
+            Action::Up if self.selected > 0 => self.selected -= 1,
+            Action::Down if self.selected + 1 < self.list_items.len() => self.selected += 1,
+            Action::Up | Action::Down => {}
+            Action::Select => self.open_selected(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Map keys: +/- zoom and 0 shows the whole map again; the arrows pan a zoomed map.
+    /// Otherwise Left/Right pick continents geographically on the world map and move the
+    /// year slider under a choropleth, and as the map shows the list's selection, the list
+    /// keys work on it too.
+    fn map_action(&mut self, action: Action) -> bool {
+        let zoomable = self.ranking.is_none() && self.scatter.is_none();
+        if let Some(map) = self.map.as_mut().filter(|_| zoomable) && Self::zoom_map(map, action) {
+            return true;
+        }
+        match action {
+            Action::Left if self.level() == GeoLevel::World => self.cycle_continent(-1),
+            Action::Right if self.level() == GeoLevel::World => self.cycle_continent(1),
+            Action::Left if self.choropleth.is_some() => self.step_active_year(-1),
+            Action::Right if self.choropleth.is_some() => self.step_active_year(1),
+            Action::Up | Action::Down | Action::Select => return self.list_action(action),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Zoom and pan keys on `map`; false for other keys, and for the arrows while the
+    /// whole map is shown
+    fn zoom_map(map: &mut MapView, action: Action) -> bool {
+        let zoomed = map.is_zoomed();
+        match action {
+            Action::ZoomIn => map.zoom(Self::ZOOM_STEP),
+            Action::ZoomOut => map.zoom(1.0 / Self::ZOOM_STEP),
+            Action::ZoomReset => map.reset_zoom(),
+            Action::Left if zoomed => map.pan(-Self::PAN_STEP, 0.0),
+            Action::Right if zoomed => map.pan(Self::PAN_STEP, 0.0),
+            Action::Up if zoomed => map.pan(0.0, Self::PAN_STEP),
+            Action::Down if zoomed => map.pan(0.0, -Self::PAN_STEP),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Right panel keys: Up/Down go through the fun facts
+    fn details_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Up => self.step_fact(-1),
+            Action::Down => self.step_fact(1),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Open the selected list item: a continent's countries or a country
+    fn open_selected(&mut self) {
+        let Some(choice) = self.list_items.get(self.selected).cloned() else {
+            return self.notify_error("Lista jest pusta – nie ma czego otworzyć");
+        };
+        match self.location.clone() {
+            Location::World => {
+                // An empty list was already notified by go_to
+                if !self.navigate(Location::Continent(choice.clone()))
+                    && self.cache.load_list(GeoLevel::Continent, &choice).is_err() {
+                    self.notify_error(format!("Brak listy krajów dla {}", choice));
+                }
+            }
+            Location::Continent(continent) => {
+                self.navigate(Location::Country { continent, country: choice });
+            }
+            Location::Country { .. } => self.notify_error("To już najniższy poziom – Esc wraca wyżej"),
+        }
+    }
+
+    /// Handle key events; return true to exit application
+    pub fn handle_input(&mut self, key: KeyCode) -> bool {
+        // Any key ends attract mode and does nothing else
+        self.last_input = Instant::now();
+        if self.attract.is_some() {
+            self.stop_attract();
+            return false;
+        }
+
+        // The walkthrough takes any key, bound or not
+        if self.tour_step.is_some() {
+            self.advance_tour(key);
+            return false;
+        }
+        // The session restore question takes y/t (or Enter) and n (or Esc) until answered
+        if self.restore_offer.is_some() {
+            match key {
+                KeyCode::Char('y' | 'Y' | 't' | 'T') | KeyCode::Enter => self.answer_restore(true),
+                KeyCode::Char('n' | 'N') | KeyCode::Esc => self.answer_restore(false),
+                _ => {}
+            }
+            return false;
+        }
+        // A list search takes typed text; Enter keeps the match selected, Esc goes back
+        // to the selection from before the search. Up/Down step through the matches, or
+        // through earlier queries while the query is empty or recalled.
+        if let Some(search) = &mut self.search {
+            let history = &mut self.search_history;
+            let stepping = !search.query.is_empty() && !history.recalling() && history.search.is_none();
+            let had_matches = !search.matches.is_empty();
+            match key {
+                KeyCode::Up if stepping => search.pos = search.pos.saturating_sub(1),
+                KeyCode::Down if stepping => search.pos = (search.pos + 1).min(search.matches.len().saturating_sub(1)),
+                _ => match history.edit(&mut search.query, key) {
+                    Edit::Submit => {
+                        self.selected = search.current();
+                        self.search = None;
+                    }
+                    Edit::Cancel => {
+                        self.selected = search.saved;
+                        self.search = None;
+                    }
+                    Edit::Changed => {
+                        search.refresh(&self.list_items);
+                        if had_matches && search.matches.is_empty() {
+                            let text = format!("Brak dopasowań: {}", search.query.as_str());
+                            self.notify_error(text);
+                        }
+                    }
+                    Edit::Unchanged | Edit::NotHandled => {}
+                },
+            }
+            return false;
+        }
+
+        // The ranking's filter row takes typed text until Enter keeps it or Esc clears it;
+        // Up/Down recall earlier filters
+        if let Some(ranking) = &mut self.ranking
+            && ranking.editing {
+            match self.filter_history.edit(&mut ranking.input, key) {
+                Edit::Submit => ranking.editing = false,
+                Edit::Cancel => {
+                    ranking.input.clear();
+                    ranking.editing = false;
+                    ranking.refilter();
+                }
+                Edit::Changed => ranking.refilter(),
+                Edit::Unchanged | Edit::NotHandled => {}
+            }
+            return false;
+        }
+        // The quiz answers with 1–4 whatever they're bound to
+        if let Some(question) = self.quiz.as_mut().and_then(|quiz| quiz.question.as_mut())
+            && let KeyCode::Char(digit @ '1'..='9') = key {
+            let option = digit as usize - '1' as usize;
+            if option < question.options.len() {
+                question.selected = option;
+                self.answer_quiz();
+            }
+            return false;
+        }
+        let Some(action) = self.keys.action(key) else { return false };
+        if action == Action::Reload {
+            self.reload_config();
+            return false;
+        }
+        if action == Action::EditData {
+            self.start_edit();
+            return false;
+        }
+        // The colors switch over any view, so the lists and overlays switch with the map
+        if action == Action::MapColors {
+            self.map_style = self.map_style.next();
+            self.notify(format!("Kolory mapy: {}", self.map_style.label()));
+            return false;
+        }
+
+        // Overlays swallow every key except closing them and quitting
+        if self.show_help || self.show_diagnostics {
+            match action {
+                Action::Quit => return true,
+                Action::Help | Action::Diagnostics | Action::Back => {
+                    self.show_help = false;
+                    self.show_diagnostics = false;
+                }
+                _ => {}
+            }
+            return false;
+        }
+
+        // The alias picker takes list keys until a name is chosen or it's cancelled
+        if let Some(picker) = &mut self.alias_picker {
+            match action {
+                Action::Quit => return true,
+                Action::Up => picker.selected = picker.selected.saturating_sub(1),
+                Action::Down if picker.selected + 1 < picker.options.len() => picker.selected += 1,
+                Action::Select => self.apply_alias(),
+                Action::Back => self.alias_picker = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        // The quiz takes list keys until it's left; its summary closes on Enter or Esc
+        if let Some(quiz) = &mut self.quiz {
+            match (action, &mut quiz.question) {
+                (Action::Quit, _) => return true,
+                (Action::Up, Some(question)) => question.selected = question.selected.saturating_sub(1),
+                (Action::Down, Some(question)) if question.selected + 1 < question.options.len() => question.selected += 1,
+                (Action::Select, Some(_)) => self.answer_quiz(),
+                (Action::Back, Some(_)) if !quiz.answers.is_empty() => quiz.question = None,
+                (Action::Select | Action::Back, _) => self.quiz = None,
+                _ => {}
+            }
+            return false;
+        }
+
+        // The focused legend picks a bucket to highlight until it's left
+        if let Some(focus) = self.legend_focus {
+            let buckets = self.choropleth.as_ref().map_or(0, |c| c.buckets.len());
+            match action {
+                Action::Quit => return true,
+                Action::Up => self.legend_focus = Some(focus.saturating_sub(1)),
+                Action::Down if focus + 1 < buckets => self.legend_focus = Some(focus + 1),
+                Action::Legend | Action::Back => self.legend_focus = None,
+                Action::Help => self.show_help = true,
+                _ => {}
+            }
+            return false;
+        }
+
+        // The comparison grid only takes its own keys; marks stay when it closes
+        if self.compare.is_some() {
+            match action {
+                Action::Quit => return true,
+                Action::Back | Action::CompareGrid => self.compare = None,
+                Action::SharedAxis => self.compare_shared_y = !self.compare_shared_y,
+                Action::Help => self.show_help = true,
+                Action::Diagnostics => self.show_diagnostics = true,
+                _ => {}
+            }
+            return false;
+        }
+
+        // Saving the view works over the ranking too, so its filter can be saved
+        if action == Action::SaveView && !self.gdp_chart_active {
+            self.save_view();
+            return false;
+        }
+
+        // The ranking takes list keys; Enter opens the selected country of the filtered rows
+        if let Some(ranking) = &mut self.ranking {
+            let rows = ranking.visible().len();
+            match action {
+                Action::Quit => return true,
+                Action::Up => ranking.selected = ranking.selected.saturating_sub(1),
+                Action::Down if ranking.selected + 1 < rows => ranking.selected += 1,
+                Action::Filter => ranking.editing = true,
+                Action::SortColumn => {
+                    ranking.sort_by(ranking.sort.next());
+                    ranking.selected = 0;
+                }
+                Action::Select => {
+                    if let Some(country) = ranking.selected_name()
+                        && let Location::Continent(continent) = self.location.clone() {
+                        self.navigate(Location::Country { continent, country });
+                    }
+                }
+                Action::Ranking | Action::Back => self.ranking = None,
+                Action::Help => self.show_help = true,
+                Action::Diagnostics => self.show_diagnostics = true,
+                _ => {}
+            }
+            return false;
+        }
+
+        // Esc while a map loads cancels the load instead of leaving the place
+        if action == Action::Back && let Some(job) = &self.map_job {
+            job.cancel();
+            return false;
+        }
+
+        // The focused panel gets the first say over its keys (see `Panel::actions`)
+        if self.panel_action(action) {
+            return false;
+        }
+
+        match action {
+            Action::Quit => return true, // quit application
+
+            Action::Help => self.show_help = true,
+
+            Action::Diagnostics => self.show_diagnostics = true,
+
+            Action::MiniMap => self.show_minimap = !self.show_minimap,
+            Action::Labels => self.show_labels = !self.show_labels,
+            Action::Graticule => self.show_graticule = !self.show_graticule,
+            Action::AutoZoom => self.auto_zoom = !self.auto_zoom,
+            Action::WorldOrder => {
+                self.world_order = self.world_order.toggled();
+                self.order_list();
+                self.notify(match self.world_order {
+                    WorldOrder::Alphabetical => "Kontynenty alfabetycznie",
+                    WorldOrder::Geographic => "Kontynenty z zachodu na wschód",
+                });
+            }
+            Action::Projection => {
+                self.projection = self.projection.next();
+                self.notify(match self.projection {
+                    Projection::Corrected => "Mapa w proporcjach",
+                    Projection::Equirectangular => "Mapa w stopniach wprost (bez korekty)",
+                    Projection::Robinson => "Mapa w odwzorowaniu Robinsona (świat i kontynenty)",
+                    Projection::Mollweide => "Mapa w odwzorowaniu Mollweidego (świat i kontynenty)",
+                });
+            }
+
+            Action::MapMarker if self.theme.symbols().ascii => {
+                self.notify_error("Bez UTF-8 mapa rysowana jest zawsze kropkami");
+            }
+            Action::MapMarker => {
+                self.map_marker = self.map_marker.next();
+                self.notify(format!("Mapa rysowana: {}", self.map_marker.label()));
+            }
+
+            Action::Mark if !self.gdp_chart_active => self.toggle_mark(),
+            Action::CompareGrid if !self.gdp_chart_active => self.open_compare(),
+            Action::PickAlias if !self.gdp_chart_active => self.open_alias_picker(),
+
+            Action::Choropleth => {
+                self.show_choropleth = !self.show_choropleth;
+                self.choropleth = match self.location.clone() {
+                    Location::Country { .. } => None,
+                    location if self.show_choropleth => Some(self.choropleth_of(&location)),
+                    _ => None,
+                };
+            }
+            Action::Legend if self.choropleth.as_ref().is_some_and(|c| !c.buckets.is_empty()) => {
+                self.legend_focus = Some(0);
+            }
+
+            Action::Scatter => {
+                if self.scatter.take().is_none()
+                    && let Location::Continent(continent) = self.location.clone() {
+                    self.scatter = Some(self.continent_scatter(&continent));
+                }
+            }
+
+            Action::Filter if !self.gdp_chart_active && self.level() != GeoLevel::Country => {
+                self.search = Some(ListSearch::new(&self.list_items, self.selected));
+            }
+
+            Action::Ranking => {
+                if let Location::Continent(continent) = self.location.clone() {
+                    // Opened in the list's order, with the place's saved filter
+                    let mut ranking = self.continent_ranking(&continent);
+                    if let Some(column) = RankColumn::of(self.continent_sort) {
+                        ranking.sort_by(column);
+                    }
+                    if !self.place_filter.is_empty() {
+                        ranking.input.set(&self.place_filter);
+                        ranking.refilter();
+                    }
+                    self.ranking = Some(ranking);
+                }
+            }
+            Action::SortColumn if self.level() == GeoLevel::Continent => {
+                self.continent_sort = self.continent_sort.next();
+                self.order_list();
+                self.notify(format!("Kraje według: {}", self.continent_sort.label()));
+            }
+            Action::SaveView => self.save_view(),
+
+            Action::SwitchView => {
+                // Toggle GDP chart or cycle panel focus
+                if self.gdp_chart_active {
+                    self.close_chart();
+                } else if self.level() == GeoLevel::Country && matches!(self.gdp_status, GdpStatus::Found { .. }) {
+                    self.open_chart();
+                } else {
+                    // Cycle focus between left, center, and right panels
+                    self.active_panel = match self.active_panel {
+                        Panel::Left => Panel::Center,
+                        Panel::Center => Panel::Right,
+                        Panel::Right => Panel::Left,
+                    };
+                }
+            }
+
+            Action::LargestEconomy => {
+                // Jump into the largest economy of the highlighted (world) or current continent
+                let continent = match &self.location {
+                    Location::World => self.list_items.get(self.selected).cloned(),
+                    Location::Continent(continent) => Some(continent.clone()),
+                    Location::Country { .. } => {
+                        self.notify("Największą gospodarkę wybiera się z mapy świata lub kontynentu");
+                        None
+                    }
+                };
+                if let Some(continent) = continent {
+                    match self.largest_economy(&continent) {
+                        Some(country) => self.jump_to_country(&continent, &country),
+                        None => self.notify_error(format!("Brak danych GDP dla krajów: {}", continent)),
+                    }
+                }
+            }
+
+            // In the GDP chart, arrows move the year cursor
+            Action::Left if self.gdp_chart_active => self.move_chart_cursor(-1),
+            Action::Right if self.gdp_chart_active => self.move_chart_cursor(1),
+            Action::LogScale if self.gdp_chart_active => self.chart_log = !self.chart_log,
+
+            Action::Quiz if !self.gdp_chart_active => self.start_quiz(QuizMode::All),
+            Action::QuizReview if !self.gdp_chart_active => self.start_quiz(QuizMode::Review),
+
+            Action::NextFact if self.level() == GeoLevel::Country => self.next_fact(Instant::now()),
+            Action::OpenSource if self.facts_focused() => self.open_fact_source(),
+            Action::Parent if let Some(sovereign) = self.summary.as_ref().and_then(|s| s.sovereign.clone()) => {
+                self.open_related(&sovereign)
+            }
+            Action::Territory if let Some(territory) = self.next_territory() => self.open_related(&territory),
+
+            // Back leaves the chart, keeping the active year for the map views
+            Action::Back if self.gdp_chart_active => self.close_chart(),
+
+            // Back always leads one level up, however the current place was reached
+            Action::Back => {
+                if let Some(parent) = self.location.parent() {
+                    self.go_up_to(parent);
+                }
+            }
+
+            // Undo/redo walk the places visited, in order
+            Action::Undo => {
+                if let Some(loc) = self.nav.undo().cloned() {
+                    self.go_to(loc);
+                }
+            }
+            Action::Redo => {
+                if let Some(loc) = self.nav.redo().cloned() {
+                    self.go_to(loc);
+                }
+            }
+
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Job body for a continent map: read and parse `path`, then build the view, stopping
+/// between the steps if cancelled
+fn build_map(
+    path: PathBuf,
+    mappings: BTreeMap<String, BTreeSet<String>>,
+    hints: RenderHints,
+    policy: OutlierPolicy,
+    low_mem: bool, // stream the file feature by feature, building the map as it is read
+    progress: &Progress,
+) -> Outcome<MapView> {
+    let file = path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default();
+    progress.report(10, format!("Wczytywanie {}", file));
+    if low_mem {
+        let view = match MapView::build_streamed(&path, mappings, &hints, policy) {
+            Ok(view) => view,
+            Err(e) => return Outcome::Failed(e.to_string()),
+        };
+        return if progress.cancelled() { Outcome::Cancelled } else { Outcome::Done(view) };
+    }
+    let raw = match read_geojson(&path) {
+        Ok(raw) => raw,
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+    if progress.cancelled() {
+        return Outcome::Cancelled;
+    }
+    progress.report(60, "Budowanie mapy");
+    let view = match MapView::build(raw, mappings, &hints, policy) {
+        Ok(view) => view,
+        Err(e) => return Outcome::Failed(e.to_string()),
+    };
+    if progress.cancelled() {
+        return Outcome::Cancelled;
+    }
+    Outcome::Done(view)
+}
+
+/// First http(s) URL in a fun fact, taken as its source
+fn fact_url(fact: &str) -> Option<&str> {
+    fact.split_whitespace()
+        .find(|w| w.starts_with("http://") || w.starts_with("https://"))
+        .map(|w| w.trim_end_matches([')', '.', ',', ';']))
 }
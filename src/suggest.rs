@@ -0,0 +1,77 @@
+/// Fuzzy name suggestions, e.g. GDP dataset names close to a map country name.
+use std::cmp::Ordering;
+
+/// Jaro-Winkler similarity of two strings in 0.0..=1.0, compared by characters.
+/// Favors a shared prefix, so "Slovakia" is close to "Slovak Republic".
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() || b.is_empty() {
+        return if a == b { 1.0 } else { 0.0 };
+    }
+
+    // Characters match when equal and no further apart than half the longer length
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_used = vec![false; b.len()];
+    let mut a_matched = Vec::new();
+    for (i, &ca) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        if let Some(j) = (lo..hi).find(|&j| !b_used[j] && b[j] == ca) {
+            b_used[j] = true;
+            a_matched.push(ca);
+        }
+    }
+    if a_matched.is_empty() {
+        return 0.0;
+    }
+    let b_matched = b.iter().zip(&b_used).filter(|&(_, &used)| used).map(|(&c, _)| c);
+    let transpositions = a_matched.iter().zip(b_matched).filter(|&(&x, y)| x != y).count() / 2;
+
+    let m = a_matched.len() as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Similarity of `query` to a candidate name or, slightly discounted, to its best-matching
+/// word, so "Slovakia" prefers "Slovak Republic" over "Slovenia". Both are lowercase.
+fn score(query: &str, candidate: &str) -> f64 {
+    let best_word = candidate
+        .split(|c: char| c.is_whitespace() || matches!(c, ',' | '(' | ')'))
+        .filter(|w| !w.is_empty())
+        .map(|w| 0.95 * similarity(query, w))
+        .fold(0.0, f64::max);
+    similarity(query, candidate).max(best_word)
+}
+
+/// Up to `n` candidates closest to `query`, best first, ignoring case. Ties keep the
+/// candidates' order.
+pub fn closest<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>, n: usize) -> Vec<&'a str> {
+    let query = query.to_lowercase();
+    let mut scored: Vec<(f64, &str)> = candidates
+        .into_iter()
+        .map(|c| (score(&query, &c.to_lowercase()), c))
+        .collect();
+    scored.sort_by(|x, y| y.0.partial_cmp(&x.0).unwrap_or(Ordering::Equal));
+    scored.into_iter().take(n).map(|(_, c)| c).collect()
+}
+
+/// The candidate closest to `query`, ignoring case, if at least `min` similar. Scored
+/// like `closest` but both ways, so a word of `query` can match a whole candidate too
+/// ("Czech Republic" → "Czechia"); ties keep the candidates' order.
+pub fn best_match<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>, min: f64) -> Option<&'a str> {
+    let query = query.to_lowercase();
+    candidates
+        .into_iter()
+        .map(|c| {
+            let lower = c.to_lowercase();
+            (score(&query, &lower).max(score(&lower, &query)), c)
+        })
+        .filter(|&(s, _)| s >= min)
+        .fold(None, |best: Option<(f64, &str)>, (s, c)| match best {
+            Some((b, _)) if b >= s => best,
+            _ => Some((s, c)),
+        })
+        .map(|(_, c)| c)
+}
@@ -0,0 +1,119 @@
+//! Everything known about one country, gathered in one place so the panels, the GDP
+//! chart and the exports all show the same data.
+use serde_json::Value;
+use std::collections::{BTreeMap, VecDeque};
+use crate::{
+    data::{country_key, CountryInfo, DataCache, Properties},
+    gdp_reader::GDPData,
+};
+
+/// Feature properties kept when a map is read: the name candidates, what `FallbackInfo`
+/// shows and the ISO codes. Natural Earth features carry over 160 others (economy codes,
+/// names in 30 languages), dropped as they are parsed.
+pub const KEPT_PROPERTIES: [&str; 13] = [
+    "ADMIN", "NAME", "NAME_LONG", "FORMAL_EN", "NAME_SORT", "NAME_ALT",
+    "POP_EST", "POP_YEAR", "CONTINENT", "SUBREGION", "ISO_A2", "ISO_A3", "ADM0_A3",
+];
+
+/// What a country's map feature says about it, shown when `country_info.json` has no entry
+#[derive(Clone, Debug, PartialEq)]
+pub struct FallbackInfo {
+    pub name: String,                  // long name, else the feature's name
+    pub population: Option<(u64, Option<u16>)>, // estimate and the year it is from
+    pub subregion: Option<String>,     // e.g. "Eastern Europe"
+    pub continent: Option<String>,     // as the map data has it
+    pub iso_a3: Option<String>,        // three-letter ISO code; "-99" in the data means none
+}
+
+impl FallbackInfo {
+    /// Read from a feature's kept properties; None when they hold nothing to show
+    pub fn from_properties(props: &Properties) -> Option<Self> {
+        let text = |key: &str| props.get(key).and_then(Value::as_str).filter(|v| !v.is_empty() && *v != "-99").map(str::to_string);
+        let number = |key: &str| props.get(key).and_then(Value::as_f64).filter(|v| *v >= 0.0);
+        let info = FallbackInfo {
+            name: text("NAME_LONG").or_else(|| text("ADMIN"))?,
+            population: number("POP_EST").map(|pop| (pop as u64, number("POP_YEAR").map(|year| year as u16))),
+            subregion: text("SUBREGION"),
+            continent: text("CONTINENT"),
+            iso_a3: text("ISO_A3").or_else(|| text("ADM0_A3")),
+        };
+        let empty = info.population.is_none() && info.subregion.is_none() && info.continent.is_none() && info.iso_a3.is_none();
+        (!empty).then_some(info)
+    }
+}
+
+/// A country's metadata, GDP history and fun facts; each part is filled when the data
+/// has it and left empty otherwise
+#[derive(Clone, Debug)]
+pub struct CountrySummary {
+    pub name: String,                      // name as the lists spell it
+    pub key: String,                       // canonical key (see `country_key`)
+    pub info: Option<CountryInfo>,         // entry of `country_info.json`
+    pub gdp_name: String,                  // name looked up in the GDP dataset (after aliases)
+    pub gdp: Option<BTreeMap<u16, f64>>,   // year -> GDP; None when the dataset has no such row
+    pub facts: Vec<String>,                // fun facts, in file order
+    pub fallback: Option<FallbackInfo>,    // from the map feature, for countries without `info`
+    pub sovereign: Option<String>,         // country it is a territory of, e.g. Denmark for Greenland
+    pub territories: Vec<String>,          // its own territories, e.g. Greenland for Denmark
+}
+
+impl CountrySummary {
+    /// Gather what `cache` and `gdp` know about `name`
+    pub fn build(name: &str, cache: &DataCache, gdp: Option<&GDPData>) -> Self {
+        let gdp_name = cache.gdp_name(name).to_string();
+        CountrySummary {
+            name: name.to_string(),
+            key: country_key(name),
+            info: cache.load_country_info(name).cloned(),
+            gdp: gdp.and_then(|gdp| gdp.get_all_gdp_data(&gdp_name)).cloned(),
+            gdp_name,
+            facts: cache.facts(name).to_vec(),
+            fallback: None,
+            sovereign: cache.sovereign_of(name).map(str::to_string),
+            territories: cache.territories_of(name).into_iter().map(str::to_string).collect(),
+        }
+    }
+
+    /// Most recent year with a GDP value, and the value
+    pub fn latest_gdp(&self) -> Option<(u16, f64)> {
+        self.gdp.as_ref()?.iter().next_back().map(|(&year, &value)| (year, value))
+    }
+}
+
+/// The summaries of the last few countries looked up, so going back and forth between
+/// them (the mouse moving over the map) doesn't repeat the lookups
+#[derive(Debug, Default)]
+pub struct SummaryCache {
+    entries: VecDeque<CountrySummary>, // least recently used first
+}
+
+impl SummaryCache {
+    /// Summaries kept; the least recently used one is dropped beyond this
+    pub const CAPACITY: usize = 8;
+
+    /// Summary of `name`, built on a miss; either way it becomes the most recently used
+    pub fn get_or_build(&mut self, name: &str, cache: &DataCache, gdp: Option<&GDPData>) -> &CountrySummary {
+        let key = country_key(name);
+        match self.entries.iter().position(|s| s.key == key) {
+            Some(i) => self.entries.make_contiguous()[i..].rotate_left(1),
+            None => {
+                if self.entries.len() == Self::CAPACITY {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(CountrySummary::build(name, cache, gdp));
+            }
+        }
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Cached summary of `name`, if any, without building it or changing the order
+    pub fn peek(&self, name: &str) -> Option<&CountrySummary> {
+        let key = country_key(name);
+        self.entries.iter().find(|s| s.key == key)
+    }
+
+    /// Forget every summary, e.g. after the data they were built from changed
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,65 @@
+//! Fitting names into terminal cells: widths are measured in display cells
+//! and cuts only happen between grapheme clusters.
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+const ELLIPSIS: &str = "…";
+
+/// Shorten `s` to at most `width` display cells, ending in "…" when cut.
+/// Wide glyphs and combining sequences are never split; when even the
+/// ellipsis doesn't fit, the result is empty.
+pub fn truncate_to_width(s: &str, width: usize) -> String {
+    if s.width() <= width {
+        return s.to_string();
+    }
+    let budget = match width.checked_sub(ELLIPSIS.width()) {
+        Some(budget) => budget,
+        None => return String::new(),
+    };
+    let mut out = String::new();
+    let mut used = 0;
+    for g in s.graphemes(true) {
+        let w = g.width();
+        if used + w > budget {
+            break;
+        }
+        out.push_str(g);
+        used += w;
+    }
+    // Don't leave a dangling space before the ellipsis
+    let trimmed = out.trim_end().len();
+    out.truncate(trimmed);
+    out.push_str(ELLIPSIS);
+    out
+}
+
+/// Break `s` into lines of at most `width` display cells, between words where
+/// possible; a word wider than a whole line is split between grapheme clusters.
+pub fn wrap(s: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in s.split_whitespace() {
+        let sep = usize::from(!line.is_empty());
+        if line.width() + sep + word.width() <= width {
+            if sep == 1 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            continue;
+        }
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for g in word.graphemes(true) {
+            if line.width() + g.width() > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            line.push_str(g);
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
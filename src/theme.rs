@@ -0,0 +1,477 @@
+//! Colors the UI asks for as RGB, mapped to what the terminal can actually show, and the
+//! symbols it draws with, in Unicode or plain ASCII for terminals without UTF-8.
+use ratatui::{buffer::Buffer, style::Color, symbols::{border, Marker}};
+use serde::Deserialize;
+
+/// An RGB color as requested by the UI
+pub type Rgb = (u8, u8, u8);
+
+/// GDP chart series
+pub const CHART_LINE: Rgb = (40, 200, 60);
+/// GDP chart year cursor
+pub const CHART_CURSOR: Rgb = (240, 200, 60);
+/// Status bar notifications
+pub const NOTICE: Rgb = (240, 200, 60);
+/// GDP growth against the previous year
+pub const GROWTH: Rgb = (40, 200, 60);
+/// GDP contraction against the previous year
+pub const DECLINE: Rgb = (220, 50, 50);
+/// Border of the focused panel
+pub const FOCUS_BORDER: Rgb = (80, 170, 240);
+/// Status bar key hints and other secondary text
+pub const MUTED: Rgb = (128, 128, 128);
+
+/// Choropleth buckets from smallest to largest value (light to dark)
+pub const CHOROPLETH: [Rgb; 6] = [
+    (255, 255, 204),
+    (199, 233, 180),
+    (127, 205, 187),
+    (65, 182, 196),
+    (44, 127, 184),
+    (37, 52, 148),
+];
+
+/// Continent tints on the world map and bullets in the world list, by the continent's
+/// west→east position; muted so the red highlight still stands out
+pub const CONTINENT_TINTS: [Rgb; 7] = [
+    (120, 170, 230),
+    (230, 190, 110),
+    (150, 210, 140),
+    (200, 150, 220),
+    (230, 150, 130),
+    (120, 200, 200),
+    (210, 210, 150),
+];
+
+/// Tints of the continents the bundled lists name, from `CONTINENT_TINTS`, so each keeps
+/// its color whichever others have geometry; continents not named here take the
+/// palette by position
+pub const NAMED_CONTINENT_TINTS: [(&str, Rgb); 7] = [
+    ("Europe", CONTINENT_TINTS[0]),
+    ("Africa", CONTINENT_TINTS[1]),
+    ("Asia", CONTINENT_TINTS[2]),
+    ("South America", CONTINENT_TINTS[3]),
+    ("North America", CONTINENT_TINTS[4]),
+    ("Oceania", CONTINENT_TINTS[5]),
+    ("Antarctica", CONTINENT_TINTS[6]),
+];
+
+/// Tint of `continent`: its named one, else the palette entry at `position`
+pub fn continent_tint(continent: &str, position: usize) -> Rgb {
+    NAMED_CONTINENT_TINTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(continent))
+        .map_or(CONTINENT_TINTS[position % CONTINENT_TINTS.len()], |&(_, tint)| tint)
+}
+
+/// Countries marked for comparison, one entry each for as long as it stays marked, the
+/// same on the map, in the list and in the comparison grid; no red, which is the selection's
+pub const MARK_COLORS: [Rgb; 8] = [
+    (31, 119, 180),
+    (255, 127, 14),
+    (44, 160, 44),
+    (148, 103, 189),
+    (23, 190, 207),
+    (188, 189, 34),
+    (227, 119, 194),
+    (140, 86, 75),
+];
+
+/// Hand-picked 16-color stand-ins for `MARK_COLORS`, all distinct and none of them red
+const MARK_COLORS_16: [Color; 8] = [
+    Color::LightBlue,
+    Color::Yellow,
+    Color::LightGreen,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::Green,
+    Color::Magenta,
+    Color::Blue,
+];
+
+/// Hand-picked 16-color stand-ins for `CHOROPLETH`: nearest-color mapping would merge
+/// neighbouring buckets, these keep every pair of adjacent buckets distinct
+const CHOROPLETH_16: [Color; 6] = [
+    Color::LightYellow,
+    Color::LightGreen,
+    Color::Cyan,
+    Color::LightBlue,
+    Color::Blue,
+    Color::Magenta,
+];
+
+/// The 16 ANSI colors with their usual (xterm) RGB values
+const ANSI_16: [(Color, Rgb); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// Channel levels of the 6x6x6 color cube in the 256-color palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// How many colors the terminal can show
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum ColorSupport {
+    #[serde(rename = "16")]
+    Ansi16,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "true")]
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Guess from `COLORTERM` and `TERM`; `var` looks up environment variables.
+    /// Unknown terminals get 16 colors, which every color terminal supports.
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Self {
+        let colorterm = var("COLORTERM").unwrap_or_default().to_lowercase();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorSupport::TrueColor;
+        }
+        let term = var("TERM").unwrap_or_default().to_lowercase();
+        if term.contains("truecolor") || term.contains("direct") {
+            ColorSupport::TrueColor
+        } else if term.contains("256color") {
+            ColorSupport::Ansi256
+        } else {
+            ColorSupport::Ansi16
+        }
+    }
+
+    /// Parse a `--colors` value: 16, 256 or true
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "16" => Some(ColorSupport::Ansi16),
+            "256" => Some(ColorSupport::Ansi256),
+            "true" => Some(ColorSupport::TrueColor),
+            _ => None,
+        }
+    }
+
+    /// Name shown in diagnostics
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorSupport::Ansi16 => "16",
+            ColorSupport::Ansi256 => "256",
+            ColorSupport::TrueColor => "true color",
+        }
+    }
+}
+
+/// Squared distance between two colors
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Closest of the 16 ANSI colors
+pub fn nearest_ansi16(rgb: Rgb) -> Color {
+    ANSI_16
+        .iter()
+        .min_by_key(|&&(_, c)| distance(rgb, c))
+        .map(|&(color, _)| color)
+        .unwrap_or(Color::White)
+}
+
+/// Closest entry of the 256-color palette's color cube or grayscale ramp
+pub fn nearest_ansi256(rgb: Rgb) -> Color {
+    let level = |v: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+    let cube_index = 16 + 36 * r + 6 * g + b;
+
+    // Grayscale ramp 232..=255 runs from 8 to 238 in steps of 10
+    let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_step = (avg.saturating_sub(8) / 10).min(23) as u8;
+    let gray_value = 8 + 10 * gray_step;
+    let gray = (gray_value, gray_value, gray_value);
+
+    if distance(rgb, gray) < distance(rgb, cube) {
+        Color::Indexed(232 + gray_step)
+    } else {
+        Color::Indexed(cube_index as u8)
+    }
+}
+
+/// Every non-ASCII glyph the UI draws itself, with an ASCII stand-in set for terminals
+/// whose locale isn't UTF-8
+#[derive(Debug, PartialEq)]
+pub struct Symbols {
+    pub ascii: bool,                   // every symbol below is plain ASCII
+    pub border: border::Set,           // block borders
+    pub marker: Marker,                // lines on maps, charts and plots
+    pub highlight: &'static str,       // before the selected list or table row
+    pub mark: &'static str,            // visited countries in the list; joins status bar hints
+    pub dash: &'static str,            // between a title and what follows it; empty table cells
+    pub ellipsis: &'static str,        // text still going on
+    pub point: &'static str,           // scatter plot point
+    pub selected_point: &'static str,  // highlighted scatter point
+    pub swatch: &'static str,          // legend color sample
+    pub cursor: &'static str,          // end of text being typed
+    pub error: &'static str,           // before an inline error
+    pub ascending: &'static str,       // sort column, A to Z
+    pub descending: &'static str,      // sort column, largest first
+    pub left_right: &'static str,      // the keys moving a cursor sideways
+    pub up_down: &'static str,         // the keys moving a selection
+    pub at_most: &'static str,         // "≤"
+    pub degree: &'static str,          // after a latitude or longitude
+    pub separator: &'static str,       // between breadcrumb segments
+    pub focus: &'static str,           // before the focused panel's title
+    pub rise: &'static str,            // after a growing value
+    pub fall: &'static str,            // after a shrinking value
+    pub countdown: [&'static str; 5],  // full to empty, as a timer runs out
+    pub slider_track: &'static str,    // year slider line
+    pub slider_tick: &'static str,     // year slider decade mark
+    pub slider_handle: &'static str,   // year slider position
+}
+
+pub const UNICODE: Symbols = Symbols {
+    ascii: false,
+    border: border::PLAIN,
+    marker: Marker::Braille,
+    highlight: ">> ",
+    mark: "·",
+    dash: "–",
+    ellipsis: "…",
+    point: "•",
+    selected_point: "●",
+    swatch: "■",
+    cursor: "█",
+    error: "✗",
+    ascending: "▲",
+    descending: "▼",
+    left_right: "←/→",
+    up_down: "↑/↓",
+    at_most: "≤",
+    degree: "°",
+    separator: " ▸ ",
+    focus: "◆ ",
+    rise: "▲",
+    fall: "▼",
+    countdown: ["●", "◕", "◑", "◔", "○"],
+    slider_track: "─",
+    slider_tick: "┼",
+    slider_handle: "●",
+};
+
+pub const ASCII: Symbols = Symbols {
+    ascii: true,
+    border: border::Set {
+        top_left: "+",
+        top_right: "+",
+        bottom_left: "+",
+        bottom_right: "+",
+        vertical_left: "|",
+        vertical_right: "|",
+        horizontal_top: "-",
+        horizontal_bottom: "-",
+    },
+    marker: Marker::Dot,
+    highlight: ">> ",
+    mark: ".",
+    dash: "-",
+    ellipsis: "...",
+    point: "*",
+    selected_point: "@",
+    swatch: "#",
+    cursor: "_",
+    error: "x",
+    ascending: "^",
+    descending: "v",
+    left_right: "Left/Right",
+    up_down: "Up/Down",
+    at_most: "<=",
+    degree: "",
+    separator: " > ",
+    focus: "* ",
+    rise: "^",
+    fall: "v",
+    countdown: ["5", "4", "3", "2", "1"],
+    slider_track: "-",
+    slider_tick: "+",
+    slider_handle: "O",
+};
+
+/// ASCII for screen readers (`--accessible`): blank borders, commas between hints, no
+/// color samples or countdown, and signs instead of arrows after growing or shrinking values.
+/// Text keeps its accents; `PLAIN_ASCII` folds them too, for locales that aren't UTF-8.
+pub const PLAIN: Symbols = Symbols {
+    ascii: false,
+    border: border::EMPTY,
+    mark: ",",
+    swatch: "",
+    rise: "+",
+    fall: "-",
+    countdown: [""; 5],
+    ..ASCII
+};
+
+pub const PLAIN_ASCII: Symbols = Symbols { ascii: true, ..PLAIN };
+
+/// Whether the locale is UTF-8: the first of LC_ALL, LC_CTYPE and LANG that is set
+/// decides; with none set, UTF-8 is assumed. `var` looks up environment variables.
+pub fn utf8_locale(var: impl Fn(&str) -> Option<String>) -> bool {
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|v| !v.is_empty()));
+    locale.is_none_or(|l| {
+        let l = l.to_lowercase();
+        l.contains("utf-8") || l.contains("utf8")
+    })
+}
+
+/// Accented lowercase letters by the plain letter they stand in for
+const ACCENTED: [(&str, char); 14] = [
+    ("áàâäãåąā", 'a'),
+    ("ćçč", 'c'),
+    ("ď", 'd'),
+    ("éèêëęě", 'e'),
+    ("íìîï", 'i'),
+    ("łľ", 'l'),
+    ("ńñň", 'n'),
+    ("óòôöõøő", 'o'),
+    ("ř", 'r'),
+    ("śšş", 's'),
+    ("ť", 't'),
+    ("úùûüűů", 'u'),
+    ("ýÿ", 'y'),
+    ("źżž", 'z'),
+];
+
+/// ASCII stand-in for a character the terminal can't show: box drawing becomes `+-|`,
+/// braille and block elements `*` and `#`, accented letters lose their accents, and
+/// anything else unknown becomes `?`
+pub fn ascii_char(c: char) -> char {
+    match c {
+        c if c.is_ascii() => c,
+        '\u{a0}' | '\u{2009}' | '\u{202f}' => ' ',
+        '─' | '━' | '═' | '╌' | '┄' => '-',
+        '│' | '┃' | '║' | '╎' | '┆' => '|',
+        '\u{2500}'..='\u{257f}' => '+',
+        '\u{2800}' => ' ',
+        '\u{2801}'..='\u{28ff}' => '*',
+        '\u{2580}'..='\u{259f}' | '■' => '#',
+        '←' => '<',
+        '→' | '▶' | '▸' => '>',
+        '↑' | '▲' => '^',
+        '↓' | '▼' => 'v',
+        '•' | '★' => '*',
+        '●' | '○' | '◕' | '◑' | '◔' => 'o',
+        '·' | '…' => '.',
+        '–' | '—' => '-',
+        '≤' => '<',
+        '≥' => '>',
+        '²' => '2',
+        '✗' => 'x',
+        _ => {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            let plain = ACCENTED.iter().find(|(accented, _)| accented.contains(lower)).map(|&(_, plain)| plain);
+            match plain {
+                Some(p) if lower != c => p.to_ascii_uppercase(),
+                Some(p) => p,
+                None => '?',
+            }
+        }
+    }
+}
+
+/// Replace every non-ASCII cell of `buf` with its ASCII stand-in, catching glyphs that
+/// widgets draw on their own (chart axes, canvas dots) and non-English text
+pub fn fold_to_ascii(buf: &mut Buffer) {
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            if !cell.symbol().is_ascii() {
+                let folded: String = cell.symbol().chars().take(1).map(ascii_char).collect();
+                cell.set_symbol(&folded);
+            }
+        }
+    }
+}
+
+/// Resolves requested colors for the detected (or configured) terminal capability, and
+/// holds the symbol set picked at startup
+pub struct Theme {
+    support: ColorSupport,
+    symbols: &'static Symbols,
+}
+
+impl Theme {
+    /// Theme for `support` drawing with Unicode symbols
+    pub fn new(support: ColorSupport) -> Self {
+        Self { support, symbols: &UNICODE }
+    }
+
+    /// The same colors drawn with `symbols`
+    pub fn with_symbols(self, symbols: &'static Symbols) -> Self {
+        Self { symbols, ..self }
+    }
+
+    /// Symbols to draw with, Unicode or ASCII
+    pub fn symbols(&self) -> &'static Symbols {
+        self.symbols
+    }
+
+    /// Color capability this theme renders for
+    pub fn support(&self) -> ColorSupport {
+        self.support
+    }
+
+    /// The terminal color closest to `rgb`
+    pub fn color(&self, rgb: Rgb) -> Color {
+        match self.support {
+            ColorSupport::TrueColor => Color::Rgb(rgb.0, rgb.1, rgb.2),
+            ColorSupport::Ansi256 => nearest_ansi256(rgb),
+            ColorSupport::Ansi16 => nearest_ansi16(rgb),
+        }
+    }
+
+    /// Color of choropleth bucket `i` (0 = smallest values), clamped to the last bucket
+    pub fn bucket(&self, i: usize) -> Color {
+        let i = i.min(CHOROPLETH.len() - 1);
+        match self.support {
+            ColorSupport::Ansi16 => CHOROPLETH_16[i],
+            _ => self.color(CHOROPLETH[i]),
+        }
+    }
+
+    /// Color of mark `i`, an index into `MARK_COLORS` (wrapping past its end)
+    pub fn mark(&self, i: usize) -> Color {
+        let i = i % MARK_COLORS.len();
+        match self.support {
+            ColorSupport::Ansi16 => MARK_COLORS_16[i],
+            _ => self.color(MARK_COLORS[i]),
+        }
+    }
+
+    /// Dimmer variant of bucket `i`, for values interpolated across a data gap. 16-color
+    /// terminals have no dimmer shade to spare and get the bucket color itself.
+    pub fn bucket_interpolated(&self, i: usize) -> Color {
+        let i = i.min(CHOROPLETH.len() - 1);
+        let dim = |c: u8| (u16::from(c) * 3 / 5) as u8;
+        let (r, g, b) = CHOROPLETH[i];
+        match self.support {
+            ColorSupport::Ansi16 => CHOROPLETH_16[i],
+            _ => self.color((dim(r), dim(g), dim(b))),
+        }
+    }
+}
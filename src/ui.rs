@@ -1,19 +1,201 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Margin, Position, Rect},
+    style::{Color, Modifier, Style},
     symbols,
-    widgets::{Axis, Block, Borders, Chart, Dataset, List, ListItem, ListState, Paragraph, Wrap},
-    Frame, text::Span,
+    widgets::{Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem, ListState, Paragraph, Row, Table, TableState, Widget, Wrap},
+    widgets::canvas::{Canvas, Line as CanvasLine},
+    Frame, text::{Line, Span, Text},
 };
-use crate::state::AppState;
-use crate::gdp_reader::GDPData;
-
-/// Main draw function: either shows GDP chart or the three-panel view
-pub fn draw<'a>(f: &mut Frame<'a>, state: &mut AppState) {
-    // If detailed GDP chart is active, render it and return early
-    if state.gdp_chart_active && state.all_gdp_data.is_some() {
-        draw_gdp_chart(f, state);
-        return;
+use std::{collections::BTreeMap, time::Duration};
+use crate::config::Projection;
+use crate::keys::Action;
+use crate::view_model::{Drawn, MapArea, ViewModel};
+use crate::summary::{CountrySummary, FallbackInfo};
+use crate::state::{AliasPicker, AppState, ChartData, Choropleth, GdpStatus, Location, Panel, RankColumn, Ranking, RestoreOffer, Scatter, TourTarget};
+use crate::gdp_reader::{GDPData, Growth, PerCapita};
+use crate::theme;
+use crate::humanize::{self, Lang};
+use crate::expand_list::ExpandList;
+use crate::jobs::{Job, JobState};
+use crate::data::{country_key, GeoLevel};
+use crate::map_draw::{inset_corner, Corner, MapView};
+use crate::describe::{self, Bearing, Scene};
+use crate::quiz::{self, Quiz, QuizMode};
+use crate::prefs::{ListSort, Source};
+use crate::year_slider::{self, YearSlider};
+use unicode_width::UnicodeWidthStr;
+
+/// Screen regions of the three-panel view, for overlays pointing at them
+struct PanelAreas {
+    list: Rect,
+    map: Rect,
+    info: Rect, // whole right column
+    status: Rect,
+}
+
+/// Main draw function: either shows GDP chart or the three-panel view, plus the help
+/// overlay and the first-launch walkthrough. Returns where the clickable parts went.
+pub fn draw<'a>(f: &mut Frame<'a>, view: &ViewModel) -> Drawn {
+    // Only the three-panel view has a breadcrumb to click
+    let mut drawn = Drawn::default();
+    if let Some(quiz) = view.quiz {
+        draw_quiz(f, view, quiz);
+    } else if let Some(charts) = view.compare {
+        draw_compare_grid(f, view, charts);
+    } else if view.gdp_chart_active && view.all_gdp_data.is_some() {
+        draw_gdp_chart(f, view, &mut drawn);
+    } else {
+        let areas = draw_panels(f, view, &mut drawn);
+        if let Some(picker) = view.alias_picker {
+            draw_alias_picker(f, view, picker);
+        }
+        if let Some(offer) = view.restore_offer {
+            draw_restore_offer(f, view, offer);
+        }
+        if let Some(job) = view.map_job {
+            draw_job_progress(f, view, job, areas.map);
+        }
+        if let Some(step) = view.tour_step {
+            draw_tour(f, view, step, &areas);
+        }
+    }
+    if view.show_help {
+        draw_help(f, view);
+    }
+    if view.show_diagnostics {
+        draw_diagnostics(f, view);
+    }
+    // Without UTF-8, whatever widgets drew on their own is folded to ASCII as well
+    if view.theme.symbols().ascii {
+        theme::fold_to_ascii(f.buffer_mut());
+    }
+    drawn
+}
+
+/// One line about a hovered map feature: its name, then capital, population and latest
+/// GDP where known
+fn hover_line(view: &ViewModel, summary: &CountrySummary) -> Line<'static> {
+    let mut spans = vec![Span::styled(summary.name.clone(), Style::default().add_modifier(Modifier::BOLD))];
+    let mut details = Vec::new();
+    if let Some(info) = &summary.info {
+        details.push(format!("Stolica: {}", info.capital));
+        details.push(format!("Populacja: {}", humanize::format_population(info.population, Lang::Pl)));
+    }
+    if let Some((year, value)) = summary.latest_gdp() {
+        details.push(format!("GDP ({}): {}", year, GDPData::format_gdp_value(value)));
+    }
+    if let Some(sovereign) = &summary.sovereign {
+        details.push(format!("Należy do: {}", sovereign));
+    }
+    let joint = Style::default().fg(view.theme.color(theme::MUTED));
+    for detail in details {
+        spans.push(Span::styled(format!(" {} ", view.theme.symbols().mark), joint));
+        spans.push(Span::raw(detail));
+    }
+    Line::from(spans)
+}
+
+/// Info panel of a country without a `country_info.json` entry: what its map feature says
+fn fallback_text(fb: &FallbackInfo) -> String {
+    let mut lines = vec![fb.name.clone()];
+    if let Some((population, year)) = fb.population {
+        let year = year.map(|y| format!(" {}", y)).unwrap_or_default();
+        lines.push(format!("Populacja (szac.{}): {}", year, humanize::format_population(population, Lang::Pl)));
+    }
+    if let Some(region) = fb.subregion.as_ref().or(fb.continent.as_ref()) {
+        lines.push(format!("Region: {}", region));
+    }
+    if let Some(iso) = &fb.iso_a3 {
+        lines.push(format!("Kod ISO: {}", iso));
+    }
+    lines.push("(z danych mapy; brak wpisu w country_info.json)".to_string());
+    lines.join("\n")
+}
+
+/// How much of a group a per-capita figure covers, e.g. "obejmuje 45 krajów z 48"
+fn coverage_text(pc: &PerCapita) -> String {
+    format!("obejmuje {} z {}", humanize::format_count(pc.covered, &humanize::KRAJE), pc.countries)
+}
+
+/// Info panel lines tying a country to its sovereign or its territories, each with the
+/// key that opens them
+fn territory_text(view: &ViewModel, summary: &CountrySummary) -> String {
+    let key = |action: Action| match view.keys.label(action) {
+        key if key.is_empty() => String::new(),
+        key => format!(" ({})", key),
+    };
+    let mut lines = Vec::new();
+    if let Some(sovereign) = &summary.sovereign {
+        lines.push(format!("Należy do: {}{}", sovereign, key(Action::Parent)));
+    }
+    if !summary.territories.is_empty() {
+        lines.push(format!("Terytoria: {}{}", summary.territories.join(", "), key(Action::Territory)));
+    }
+    lines.join("\n")
+}
+
+/// Bordered block drawn with the theme's border symbols
+fn panel(view: &ViewModel) -> Block<'static> {
+    Block::default().borders(Borders::ALL).border_set(view.theme.symbols().border)
+}
+
+/// Border style of one of the three main panels: highlighted while it has the focus
+fn focus_style(view: &ViewModel, which: Panel) -> Style {
+    if view.active_panel == which {
+        Style::default().fg(view.theme.color(theme::FOCUS_BORDER))
+    } else {
+        Style::default()
+    }
+}
+
+/// `panel` for one of the three main panels, with `focus_style` borders
+fn focus_panel(view: &ViewModel, which: Panel) -> Block<'static> {
+    panel(view).border_style(focus_style(view, which))
+}
+
+/// `title` of a main panel, with the focus marker in front while it has the focus
+fn focus_title(view: &ViewModel, which: Panel, title: &str) -> String {
+    if view.active_panel == which {
+        format!("{}{}", view.theme.symbols().focus, title)
+    } else {
+        title.to_string()
+    }
+}
+
+/// Rectangle of at most `width` x `height` centered in `area`
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Draw the list, map and info panels with the status bar below
+fn draw_panels<'a>(f: &mut Frame<'a>, view: &ViewModel, drawn: &mut Drawn) -> PanelAreas {
+    // Reserve the top line for the breadcrumb and the bottom line for the status bar
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(f.area());
+    draw_breadcrumb(f, view, rows[0], drawn);
+
+    // Status bar: latest notification, attract mode's way out, or hints for the keys usable right now
+    let sym = view.theme.symbols();
+    let help = view.keys.hints(&view.context);
+    let status = match view.notification {
+        Some(n) => Paragraph::new(n.text.as_str()).style(Style::default().fg(view.theme.color(theme::NOTICE))),
+        None if view.attract => Paragraph::new("Pokaz losowych krajów – dowolny klawisz wraca do atlasu")
+            .style(Style::default().fg(view.theme.color(theme::NOTICE))),
+        None => Paragraph::new(help.join(&format!(" {} ", sym.mark))).style(Style::default().fg(view.theme.color(theme::MUTED))),
+    };
+    f.render_widget(status, rows[2]);
+    // The visual bell inverts the status bar for a moment
+    if view.flash {
+        f.buffer_mut().set_style(rows[2], Style::default().add_modifier(Modifier::REVERSED));
     }
 
     // Split the terminal horizontally into left, center, and right panels
@@ -24,28 +206,268 @@ pub fn draw<'a>(f: &mut Frame<'a>, state: &mut AppState) {
             Constraint::Percentage(60), // map view
             Constraint::Percentage(20), // info and charts
         ].as_ref())
-        .split(f.area());
+        .split(rows[1]);
 
-    // Left panel: show the selection list with highlight
-    let items: Vec<ListItem> = state.list_items
-        .iter()
-        .map(|i| ListItem::new(i.clone()))
+    // Left panel: show the selection list with highlight, names fitted between
+    // the borders and the ">> " highlight symbol; countries marked for comparison stand out
+    // in their mark colors
+    // With visited shading on, a continent's list dots the countries already opened
+    // While searching, only the matches are listed, with the query in the title (and the
+    // text looked up during a Ctrl+R search through earlier queries)
+    // With continent colors, the world list bullets each continent in its map tint
+    let show_visited = view.config.show_visited_on_map && view.level() == GeoLevel::Continent;
+    let show_tints = !view.continent_tints.is_empty();
+    let name_width = chunks[0].width.saturating_sub(if show_visited || show_tints { 7 } else { 5 }) as usize;
+    let shown: Vec<&String> = match view.search {
+        Some(search) => search.matches.iter().map(|&i| &view.list_items[i]).collect(),
+        None => view.list_items.iter().collect(),
+    };
+    let items: Vec<ListItem> = shown
+        .into_iter()
+        .map(|i| {
+            let name = Span::raw(view.cache.display_name(i, name_width));
+            let line = if show_tints {
+                let bullet = match view.continent_tints.get(i) {
+                    Some(&tint) => Span::styled(format!("{} ", sym.swatch), Style::default().fg(view.theme.color(tint))),
+                    None => Span::raw("  "),
+                };
+                Line::from(vec![bullet, name])
+            } else if !show_visited {
+                Line::from(name)
+            } else if view.visited.contains(&country_key(i)) {
+                Line::from(vec![Span::styled(format!("{} ", sym.mark), Style::default().fg(Color::DarkGray)), name])
+            } else {
+                Line::from(vec![Span::raw("  "), name])
+            };
+            let item = ListItem::new(line);
+            match view.marked.iter().find(|m| m.name == *i) {
+                Some(mark) => item.style(Style::default().fg(view.theme.mark(mark.color)).add_modifier(Modifier::BOLD)),
+                None => item,
+            }
+        })
         .collect();
     let mut ls = ListState::default();
-    ls.select(Some(state.selected));
+    let block = match view.search {
+        Some(search) => {
+            ls.select((!search.matches.is_empty()).then_some(search.pos));
+            let title = match &view.search_history.search {
+                Some(lookup) => format!(
+                    "Historia: {}{} {} {} ({})",
+                    lookup.needle.as_str(),
+                    sym.cursor,
+                    sym.separator.trim(),
+                    search.query.as_str(),
+                    search.matches.len()
+                ),
+                None => {
+                    let (before, after) = search.query.split();
+                    format!("Szukaj: {}{}{} ({})", before, sym.cursor, after, search.matches.len())
+                }
+            };
+            focus_panel(view, Panel::Left)
+                .title(focus_title(view, Panel::Left, &title))
+                .title_bottom(format!("Enter: wybierz {} Esc: anuluj", sym.mark))
+        }
+        None => {
+            ls.select(Some(view.selected));
+            // The order, in gray, once it isn't by name or comes from saved settings
+            let mut title = vec![Span::raw(focus_title(view, Panel::Left, "Wybierz"))];
+            if let Some((sort, source)) = view.list_sort
+                && (sort != ListSort::Name || source != Source::Global) {
+                let note = match source.label() {
+                    Some(from) => format!(" {} {}, {}", sym.mark, sort.label(), from),
+                    None => format!(" {} {}", sym.mark, sort.label()),
+                };
+                title.push(Span::styled(note, Style::default().fg(view.theme.color(theme::MUTED))));
+            }
+            focus_panel(view, Panel::Left).title(Line::from(title))
+        }
+    };
     let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Wybierz"))
-        .highlight_symbol(">> ")
-        .highlight_style(Style::default().fg(Color::Red));
+        .block(block)
+        .highlight_symbol(view.theme.symbols().highlight)
+        .highlight_style(highlight_style(view));
     f.render_stateful_widget(list, chunks[0], &mut ls);
 
-    // Center panel: render the map if available, otherwise placeholder text
-    if let Some(map) = &state.map {
-        let name = &state.list_items[state.selected];
-        map.render(f, chunks[1], name, Some(name.as_str()));
+    // Center panel: the ranking or scatter plot if toggled, else the map if available, otherwise placeholder text
+    if let Some(ranking) = view.ranking {
+        draw_ranking(f, chunks[1], view, ranking);
+    } else if let Some(scatter) = view.scatter {
+        draw_scatter(f, chunks[1], view, scatter);
+    } else if let Some(map) = view.map && view.accessible {
+        draw_description(f, chunks[1], view, map);
+    } else if let Some(map) = view.map {
+        // An empty list highlights nothing
+        let name = view.list_items.get(view.highlighted).map_or("", String::as_str);
+        // Under a choropleth, the year slider takes the panel's bottom rows
+        let slider = view.year_span.clone().filter(|_| view.choropleth.is_some() && chunks[1].height > year_slider::HEIGHT + 6);
+        let (map_rect, slider_rect) = match slider {
+            Some(_) => {
+                let [map_rect, rest] = Layout::vertical([Constraint::Min(0), Constraint::Length(year_slider::HEIGHT)]).areas(chunks[1]);
+                (map_rect, Some(rest.inner(Margin { horizontal: 1, vertical: 0 })))
+            }
+            None => (chunks[1], None),
+        };
+        // A bucket picked in the choropleth legend replaces the red selection
+        let legend = if view.config.show_map_legend && view.legend_focus.is_none() {
+            let open_key = Action::Select
+                .available(&view.context)
+                .then(|| view.keys.label(Action::Select))
+                .filter(|label| !label.is_empty());
+            map.legend(Some(name), open_key.as_deref(), view.map_style)
+        } else {
+            None
+        };
+        // A zoomed map says how far in it is
+        let times = if sym.ascii { "x" } else { "×" };
+        let zoom = if map.is_zoomed() { format!(" {}{:.1}", times, map.zoom_level()) } else { String::new() };
+        // A country keeps to a flat projection; any other than the default is named
+        let projection = match view.level() {
+            GeoLevel::Country => view.projection.for_country(),
+            _ => view.projection,
+        };
+        let projected = match projection {
+            Projection::Corrected => String::new(),
+            other => format!(" · {}", other.label()),
+        };
+        let marker = focus_title(view, Panel::Center, "");
+        let room = (map_rect.width.saturating_sub(2) as usize).saturating_sub(marker.width() + zoom.width() + projected.width());
+        let title = focus_title(view, Panel::Center, &format!("{}{}{}", view.cache.display_name(name, room), zoom, projected));
+        // Zoomed in, the view is where the keys took it; with auto-zoom on a continent, it
+        // closes in on the selected country; otherwise it takes in the selection, framed as
+        // its render hints ask on its own map.
+        // The projection widens it to the shape of the area, which hover and the inset follow.
+        let auto_zoom = view.auto_zoom && view.level() == GeoLevel::Continent;
+        let viewport = match map.is_zoomed() {
+            true => map.bounds(),
+            false => auto_zoom.then(|| map.auto_zoom_viewport(name)).flatten().unwrap_or_else(|| map.preferred_viewport(name)),
+        };
+        let inner = map_rect.inner(Margin { horizontal: 1, vertical: 1 });
+        let shown = map.canvas_bounds(projection, viewport, inner);
+        let mut widget = map.widget()
+            .title(&title)
+            .border_style(focus_style(view, Panel::Center))
+            .highlight(name)
+            .viewport(viewport)
+            .projection(projection)
+            .pulse(view.pulse_bright)
+            .style(view.map_style)
+            .fill_highlight(view.config.fill_highlight)
+            .symbols(sym)
+            .labels(view.show_labels && view.level() != GeoLevel::Country)
+            .graticule(view.show_graticule);
+        if show_visited {
+            widget = widget.visited(view.visited);
+        }
+        if view.level() == GeoLevel::Country && !view.neighbours.is_empty() {
+            widget = widget.neighbours(view.neighbours);
+        }
+        if let Some(marker) = view.map_marker {
+            widget = widget.marker(marker);
+        }
+        if let Some(legend) = &legend {
+            widget = widget.legend(legend);
+        }
+        // Choropleth: countries in their bucket colors, those without GDP data in gray; a
+        // bucket picked in the legend is highlighted instead of the selection. Otherwise, on
+        // the world map, continent colors tint every country like its continent's bullet in
+        // the list.
+        let mut colors: BTreeMap<String, Color> = match view.choropleth {
+            Some(c) => map
+                .feature_names()
+                .map(|name| {
+                    let key = country_key(name);
+                    let color = match c.assignment.get(&key) {
+                        Some(&b) if c.interpolated.contains(&key) => view.theme.bucket_interpolated(b),
+                        Some(&b) => view.theme.bucket(b),
+                        None => view.theme.color(theme::MUTED),
+                    };
+                    (key, color)
+                })
+                .collect(),
+            None => map
+                .member_tints(&view.continent_tints)
+                .into_iter()
+                .map(|(key, tint)| (key, view.theme.color(tint)))
+                .collect(),
+        };
+        // Countries marked for comparison take their mark colors over either
+        for mark in view.marked {
+            colors.insert(country_key(&mark.name), view.theme.mark(mark.color));
+        }
+        if !colors.is_empty() {
+            widget = widget.colors(&colors);
+        }
+        let bucket_members = view.choropleth.as_ref().zip(view.legend_focus).map(|(c, b)| c.members(b));
+        if let Some(members) = &bucket_members {
+            widget = widget.highlight_keys(members);
+        }
+        // Nothing the map shows has changed since it was last rendered: copy those cells in
+        // rather than drawing every outline again
+        let frame = widget.frame(map_rect);
+        match view.map_cache.filter(|(cached, _)| *cached == frame) {
+            Some((_, buffer)) => {
+                f.buffer_mut().merge(buffer);
+                drawn.map_reused = true;
+            }
+            None => {
+                let mut buffer = Buffer::empty(map_rect);
+                widget.render(map_rect, &mut buffer);
+                f.buffer_mut().merge(&buffer);
+                drawn.map_rendered = Some((frame, buffer));
+            }
+        }
+        if let Some(choropleth) = view.choropleth {
+            draw_choropleth_legend(f, view, choropleth, map_rect);
+        }
+
+        // Mini-map inset: where the current view sits in the world
+        if view.show_minimap && view.level() != GeoLevel::World {
+            let (w, h) = ((inner.width / 3).max(16), (inner.height / 3).max(6));
+            if w < inner.width && h < inner.height {
+                let (x_bounds, y_bounds) = map.bounds();
+                let frac = (w as f64 / inner.width as f64, h as f64 / inner.height as f64);
+                let corner = inset_corner(x_bounds, y_bounds, map.highlight_bounds(name), frac);
+                let x = match corner {
+                    Corner::TopLeft | Corner::BottomLeft => inner.x,
+                    Corner::TopRight | Corner::BottomRight => inner.right() - w,
+                };
+                let y = match corner {
+                    Corner::TopLeft | Corner::TopRight => inner.y,
+                    Corner::BottomLeft | Corner::BottomRight => inner.bottom() - h,
+                };
+                // A country's own map is already all selection; a continent's is marked within
+                let selection = (view.level() == GeoLevel::Continent).then(|| map.highlight_bounds(name)).flatten();
+                // The inset is in degrees: a projected view is marked by its viewport
+                let frame = if projection.bends() { viewport } else { shown };
+                view.world_outline.render(f, Rect::new(x, y, w, h), frame, selection, sym, view.map_style);
+            }
+        }
+
+        if let (Some(years), Some(area)) = (slider, slider_rect) {
+            let focused = view.active_panel == Panel::Center && !map.is_zoomed();
+            let slider = YearSlider::new(years, view.active_year, view.theme).focused(focused);
+            drawn.year_slider = Some(slider.area(area));
+            f.render_widget(slider, area);
+        }
+
+        // Detail strip: the feature under the mouse pointer, over the map's bottom line
+        drawn.map_area = Some(MapArea { inner, x_bounds: shown.0, y_bounds: shown.1, projection, central: map.central_meridian() });
+        if let Some(summary) = view.map_hover && inner.height > 0 {
+            let strip = Rect::new(inner.x, inner.bottom() - 1, inner.width, 1);
+            f.render_widget(Clear, strip);
+            f.render_widget(Paragraph::new(hover_line(view, summary)), strip);
+        }
     } else {
-        let placeholder = Paragraph::new("Wybierz kraj, aby zobaczyć mapę")
-            .block(Block::default().borders(Borders::ALL).title("Map"))
+        let text = if view.map_job.is_some() {
+            format!("Wczytywanie mapy{}", sym.ellipsis)
+        } else if let Location::Country { country, .. } = view.location {
+            format!("{}: brak danych o granicach", country)
+        } else {
+            "Wybierz kraj, aby zobaczyć mapę".to_string()
+        };
+        let placeholder = Paragraph::new(text)
+            .block(focus_panel(view, Panel::Center).title(focus_title(view, Panel::Center, "Map")))
             .wrap(Wrap { trim: true });
         f.render_widget(placeholder, chunks[1]);
     }
@@ -61,93 +483,307 @@ pub fn draw<'a>(f: &mut Frame<'a>, state: &mut AppState) {
         .split(chunks[2]);
 
     // Info block: show country details or default help text
-    let info_text = if let Some(ci) = &state.country_info {
+    let info_text = if let Some(ci) = view.summary.as_ref().and_then(|summary| summary.info.as_ref()) {
         format!(
-            "{}\nStolica: {}\nPowierzchnia: {:.0} km²\nPopulacja: {}\nWaluta: {}",
-            ci.name, ci.capital, ci.area, ci.population, ci.currency
+            "{}\nStolica: {}\nPowierzchnia: {}\nPopulacja: {}\nWaluta: {}",
+            ci.name,
+            ci.capital,
+            humanize::format_area(ci.area, Lang::Pl),
+            humanize::format_population(ci.population, Lang::Pl),
+            ci.currency
         )
+    } else if let Some(fb) = view.summary.as_ref().and_then(|summary| summary.fallback.as_ref()) {
+        fallback_text(fb)
     } else {
-        state.info.clone()
+        format!("{}\n\n{}", view.info, help.join("\n"))
     };
+    // Territories and sovereigns are shown whatever else is known about the country
+    let related = view.summary.as_ref().map(|summary| territory_text(view, summary)).unwrap_or_default();
+    let info_text = if related.is_empty() { info_text } else { format!("{}\n{}", info_text, related) };
     let info = Paragraph::new(info_text)
-        .block(Block::default().borders(Borders::ALL).title("Informacje"))
+        .block(focus_panel(view, Panel::Right).title(focus_title(view, Panel::Right, "Informacje")))
         .wrap(Wrap { trim: true });
     f.render_widget(info, right_chunks[0]);
 
-    // GDP summary block: latest GDP value with prompt to view chart
-    let gdp_text = state.current_gdp.as_ref()
-        .map(|(year, value)| {
-            format!(
-                "GDP dla ({}):\n{}\nWciśnij tab aby zobaczyć wykres!",
-                year,
-                GDPData::format_gdp_value(*value)
-            )
-        })
-        .unwrap_or_else(|| "Wybierz kraj aby zobaczyć dane GDP".to_string());
+    // GDP summary block: latest GDP value and its change, or why it is missing
+    let gdp_text: Text = match view.gdp_status {
+        GdpStatus::Found { year, value, growth } => Text::from(vec![
+            Line::from(format!("GDP ({}): {}", year, GDPData::format_gdp_value(*value))),
+            gdp_growth_line(view, *growth),
+            Line::from("Wciśnij tab aby zobaczyć wykres!"),
+        ]),
+        // The world's or continent's totals while no country is open
+        GdpStatus::NoSelection => match &view.place_per_capita {
+            Some(pc) => Text::from(vec![
+                Line::from(format!("GDP łącznie: {}", GDPData::format_gdp_value(pc.gdp))),
+                Line::from(format!("Na osobę: {}", GDPData::format_gdp_value(pc.value()))),
+                Line::styled(
+                    format!("ważone populacją, {}", coverage_text(pc)),
+                    Style::default().fg(view.theme.color(theme::MUTED)),
+                ),
+                Line::from("Wybierz kraj aby zobaczyć dane GDP"),
+            ]),
+            None => "Wybierz kraj aby zobaczyć dane GDP".into(),
+        },
+        GdpStatus::DatasetMissing(path) => format!("Nie wczytano danych GDP (brak {})", path).into(),
+        GdpStatus::NotFound(country) => {
+            let pick = view.keys.label(Action::PickAlias);
+            let hint = if pick.is_empty() {
+                "Dopisz jego nazwę z danych do aliases.json".to_string()
+            } else {
+                format!("{}: wybierz jego nazwę w danych", pick)
+            };
+            format!("Brak danych GDP dla '{}'\n{}", country, hint).into()
+        }
+    };
     let gdp = Paragraph::new(gdp_text)
-        .block(Block::default().borders(Borders::ALL).title("GDP"))
+        .block(focus_panel(view, Panel::Right).title("GDP"))
         .style(Style::default().fg(Color::White))
         .wrap(Wrap { trim: true });
     f.render_widget(gdp, right_chunks[1]);
 
-    // Fun fact block: random fact or prompt to select a country
-    let fact_text = state.fun_fact
-        .as_deref()
-        .unwrap_or("Wybierz kraj, aby zobaczyć ciekawostkę");
-    let fact = Paragraph::new(fact_text)
-        .block(Block::default().borders(Borders::ALL).title("Czy wiesz, że ..."))
-        .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: true });
-    f.render_widget(fact, right_chunks[2]);
+    // Fun fact block: the country's facts, the focused one in full and scrolled when too
+    // long, or a prompt to select a country
+    // With timed rotation, a dot empties as the next fact approaches
+    let mut fact_title = vec![Span::raw("Czy wiesz, że ...")];
+    if let Some(left) = view.fact_rotation_left {
+        let dots = &sym.countdown;
+        let dot = dots[(((1.0 - left) * dots.len() as f64) as usize).min(dots.len() - 1)];
+        fact_title.push(Span::styled(format!(" {}", dot), Style::default().fg(view.theme.color(theme::MUTED))));
+    }
+    let fact_block = focus_panel(view, Panel::Right).title(Line::from(fact_title));
+    if view.facts.is_empty() {
+        drawn.fact_overflow = 0;
+        let prompt = Paragraph::new("Wybierz kraj, aby zobaczyć ciekawostkę")
+            .block(fact_block)
+            .style(Style::default().fg(Color::White))
+            .wrap(Wrap { trim: true });
+        f.render_widget(prompt, right_chunks[2]);
+    } else {
+        let facts = ExpandList::new(view.facts, view.fact_focus)
+            .block(fact_block)
+            .style(Style::default().fg(Color::White));
+        drawn.fact_overflow = facts.focus_overflow(right_chunks[2]);
+        f.render_widget(facts.offset(view.fact_offset.min(drawn.fact_overflow)), right_chunks[2]);
+    }
+
+    PanelAreas { list: chunks[0], map: chunks[1], info: chunks[2], status: rows[2] }
+}
+
+/// Draw `World ▸ Europe ▸ Poland` for the current place, recording where each segment
+/// went so a click can go up to it; the places above are underlined under the mouse
+fn draw_breadcrumb<'a>(f: &mut Frame<'a>, view: &ViewModel, area: Rect, drawn: &mut Drawn) {
+    let sym = view.theme.symbols();
+    let trail = view.location.trail();
+    let last = trail.len() - 1;
+    let mut spans = Vec::new();
+    let mut x = area.x;
+    for (i, loc) in trail.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(sym.separator, Style::default().fg(view.theme.color(theme::MUTED))));
+            x = x.saturating_add(sym.separator.width() as u16);
+        }
+        // Places past the room left are cut, country names to their short names first
+        let label = view.cache.display_name(loc.label(), area.right().saturating_sub(x) as usize);
+        let width = label.width() as u16;
+        let style = if i == last {
+            Style::default().add_modifier(Modifier::BOLD)
+        } else if view.breadcrumb_hover == Some(i) {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::UNDERLINED)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+        drawn.breadcrumb.push((loc, Rect::new(x, area.y, width, 1)));
+        spans.push(Span::styled(label, style));
+        x = x.saturating_add(width);
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Grey out every cell of the already drawn frame outside `keep`
+fn dim_except<'a>(f: &mut Frame<'a>, keep: Rect) {
+    let area = f.area();
+    let dim = Style::default().fg(Color::DarkGray).add_modifier(Modifier::DIM);
+    let buf = f.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            if !keep.contains(Position { x, y }) {
+                buf[(x, y)].set_style(dim);
+            }
+        }
+    }
+}
+
+/// Explanation shown for a walkthrough step, naming the currently bound keys
+fn tour_text(view: &ViewModel, target: TourTarget) -> String {
+    let key = |a| view.keys.label(a);
+    match target {
+        TourTarget::List => format!(
+            "Tu wybierasz kontynent lub kraj: {}/{} przesuwa zaznaczenie, {} wchodzi głębiej, {} wraca.",
+            key(Action::Up), key(Action::Down), key(Action::Select), key(Action::Back)
+        ),
+        TourTarget::Map => format!(
+            "Mapa pokazuje zaznaczenie na czerwono. {} przełącza panel; na mapie świata {}/{} wybiera kontynent.",
+            key(Action::SwitchView), key(Action::Left), key(Action::Right)
+        ),
+        TourTarget::Info => format!(
+            "Po prawej: informacje o kraju, GDP i ciekawostki. Na wybranym kraju {} otwiera wykres historii GDP.",
+            key(Action::SwitchView)
+        ),
+        TourTarget::StatusBar => format!(
+            "Mapy, listy i dane GDP są w katalogu {}. Pasek na dole podpowiada klawisze, {} pokazuje wszystkie.",
+            view.cache.base().display(), key(Action::Help)
+        ),
+    }
+}
+
+/// Draw one walkthrough step: dim everything but the target region and explain it in a popup
+fn draw_tour<'a>(f: &mut Frame<'a>, view: &ViewModel, step: usize, areas: &PanelAreas) {
+    let target = TourTarget::STEPS[step];
+    let keep = match target {
+        TourTarget::List => areas.list,
+        TourTarget::Map => areas.map,
+        TourTarget::Info => areas.info,
+        TourTarget::StatusBar => areas.status,
+    };
+    dim_except(f, keep);
+
+    // Popup in the lower third of the screen
+    let area = f.area();
+    let width = area.width.min(60);
+    let text = Paragraph::new(tour_text(view, target))
+        .wrap(Wrap { trim: true })
+        .block(
+            panel(view)
+                .title(format!("Witaj w RustAtlas ({}/{})", step + 1, TourTarget::STEPS.len()))
+                .title_bottom(format!("dowolny klawisz: dalej {} Esc: pomiń", view.theme.symbols().mark)),
+        );
+    let height = (text.line_count(width.saturating_sub(2)) as u16).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height * 2 / 3).min(area.height - height),
+        width,
+        height,
+    );
+    f.render_widget(Clear, popup);
+    f.render_widget(text, popup);
 }
 
-/// Draw the detailed GDP history chart for the selected country
-fn draw_gdp_chart<'a>(f: &mut Frame<'a>, state: &AppState) {
-    let country = &state.list_items[state.selected];
-    let all = state.all_gdp_data.as_ref().unwrap();
+/// (year, value) points for the GDP chart, in year order
+pub fn chart_points(all: &BTreeMap<u16, f64>) -> Vec<(f64, f64)> {
+    all.iter().map(|(&yr, &val)| (yr as f64, val)).collect()
+}
 
-    // Prepare sorted (year, value) points for the chart
-    let mut pts: Vec<(f64, f64)> = all
+/// Log10 chart points; values that are zero or negative can't be plotted and are
+/// dropped, returning how many were skipped
+pub fn log_points(pts: &[(f64, f64)]) -> (Vec<(f64, f64)>, usize) {
+    let log: Vec<(f64, f64)> = pts
         .iter()
-        .filter_map(|(yr_str, &val)| yr_str.parse::<f64>().ok().map(|yr| (yr, val)))
+        .filter(|&&(_, v)| v > 0.0)
+        .map(|&(yr, v)| (yr, v.log10()))
         .collect();
-    pts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    let skipped = pts.len() - log.len();
+    (log, skipped)
+}
+
+/// Bounds and labels of a log10 axis covering `lo..=hi`: one label per power of ten,
+/// showing the original magnitude ("1B", "10B", "100B", "1T")
+pub fn log_axis(lo: f64, hi: f64) -> ([f64; 2], Vec<String>) {
+    let lo = lo.floor() as i32;
+    let hi = (hi.ceil() as i32).max(lo + 1);
+    ([lo as f64, hi as f64], (lo..=hi).map(magnitude_label).collect())
+}
+
+/// Short label for 10^exp, e.g. 10 → "10B", 12 → "1T"
+pub fn magnitude_label(exp: i32) -> String {
+    if exp < 0 {
+        return 10f64.powi(exp).to_string();
+    }
+    let (unit, base) = match exp {
+        12.. => ("T", 12),
+        9.. => ("B", 9),
+        6.. => ("M", 6),
+        3.. => ("K", 3),
+        _ => ("", 0),
+    };
+    format!("{}{}", 10u64.pow((exp - base) as u32), unit)
+}
+
+/// Draw the detailed GDP history chart for the selected country, with a year slider
+/// under it that follows the cursor
+fn draw_gdp_chart<'a>(f: &mut Frame<'a>, view: &ViewModel, drawn: &mut Drawn) {
+    let (Some(country), Some(all)) = (view.list_items.get(view.selected), view.all_gdp_data.as_ref()) else { return };
+    let pts = chart_points(all);
 
     // Determine axis bounds
     let min_year = pts.first().map(|&(y, _)| y).unwrap_or(1960.0);
     let max_year = pts.last().map(|&(y, _)| y).unwrap_or(2024.0);
-    let max_gdp = pts.iter().map(|&(_, v)| v).fold(0.0, f64::max);
-    let y_max = (max_gdp * 1.1).ceil();
-
-    // Labels for axes
-    let y_labels = vec![
-        "0".to_string(),
-        format!("{:.1}B", y_max / 4e9),
-        format!("{:.1}B", y_max / 2e9),
-        format!("{:.1}B", y_max * 3.0 / 4e9),
-        format!("{:.1}B", y_max / 1e9),
-    ];
+
+    // Plotted values: raw, or log10 with labels at the powers of ten
+    let (plot, skipped, y_bounds, y_labels) = if view.chart_log {
+        let (plot, skipped) = log_points(&pts);
+        let lo = plot.iter().map(|&(_, v)| v).fold(f64::INFINITY, f64::min);
+        let hi = plot.iter().map(|&(_, v)| v).fold(f64::NEG_INFINITY, f64::max);
+        let (bounds, labels) = if plot.is_empty() { log_axis(0.0, 1.0) } else { log_axis(lo, hi) };
+        (plot, skipped, bounds, labels)
+    } else {
+        let max_gdp = pts.iter().map(|&(_, v)| v).fold(0.0, f64::max);
+        let y_max = (max_gdp * 1.1).ceil();
+        let labels = vec![
+            "0".to_string(),
+            format!("{:.1}B", y_max / 4e9),
+            format!("{:.1}B", y_max / 2e9),
+            format!("{:.1}B", y_max * 3.0 / 4e9),
+            format!("{:.1}B", y_max / 1e9),
+        ];
+        (pts.clone(), 0, [0.0, y_max], labels)
+    };
+
+    // Labels for the year axis
     let span = max_year - min_year;
     let step = (span / 6.0).ceil();
     let x_labels: Vec<Span> = (0..=6)
         .map(|i| Span::from(((min_year + step * i as f64) as i32).to_string()))
         .collect();
 
-    // Dataset for the chart
+    // Dataset for the chart, in the country's mark color when it is marked
+    let line = match view.marked.iter().find(|m| m.name == *country) {
+        Some(mark) => view.theme.mark(mark.color),
+        None => view.theme.color(theme::CHART_LINE),
+    };
     let ds = Dataset::default()
         .name(format!("GDP {}", country))
         .marker(symbols::Marker::Bar)
-        .style(Style::default().fg(Color::Green))
-        .data(&pts);
+        .style(Style::default().fg(line))
+        .data(&plot);
 
-    let chart = Chart::new(vec![ds])
+    // Vertical cursor line at the selected year, with its value in the title
+    let cursor = view.chart_cursor
+        .and_then(|y| pts.iter().find(|&&(yr, _)| yr == y as f64).copied());
+    let cursor_pts: Vec<(f64, f64)> = cursor
+        .map(|(yr, _)| vec![(yr, y_bounds[0]), (yr, y_bounds[1])])
+        .unwrap_or_default();
+    let sym = view.theme.symbols();
+    let mut readout = cursor
+        .map(|(yr, val)| format!(" {} {}: {} ({})", sym.dash, yr, GDPData::format_gdp_value(val), sym.left_right))
+        .unwrap_or_default();
+    if skipped > 0 {
+        readout.push_str(&format!(" [pominięto {} wartości {} 0]", skipped, sym.at_most));
+    }
+    let cursor_ds = Dataset::default()
+        .marker(sym.marker)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(view.theme.color(theme::CHART_CURSOR)))
+        .data(&cursor_pts);
+
+    let chart = Chart::new(vec![ds, cursor_ds])
+        .style(chart_style(view))
         .block(
-            Block::default()
+            panel(view)
                 .title(format!(
-                    "Historia GDP dla {} (Wciśnij Tab aby wrócić do widoku mapy!)",
-                    country
-                ))
-                .borders(Borders::ALL),
+                    "Historia GDP dla {}{} (Wciśnij Tab aby wrócić do widoku mapy!)",
+                    country, readout
+                )),
         )
         .x_axis(
             Axis::default()
@@ -158,12 +794,694 @@ fn draw_gdp_chart<'a>(f: &mut Frame<'a>, state: &AppState) {
         )
         .y_axis(
             Axis::default()
-                .title("GDP (USD)")
+                .title(if view.chart_log { "GDP (USD, log)" } else { "GDP (USD)" })
                 .style(Style::default().fg(Color::Gray))
-                .bounds([0.0, y_max])
+                .bounds(y_bounds)
                 .labels(y_labels.into_iter().map(Span::from).collect::<Vec<Span>>()),
         );
 
-    // Render the chart to fill the terminal
-    f.render_widget(chart, f.area());
+    // The chart fills the terminal above the slider, which spans the years it plots
+    let [chart_area, slider_area] = Layout::vertical([Constraint::Min(0), Constraint::Length(year_slider::HEIGHT)]).areas(f.area());
+    f.render_widget(chart, chart_area);
+    if let (Some(&(first, _)), Some(&(last, _))) = (pts.first(), pts.last()) {
+        let area = slider_area.inner(Margin { horizontal: 1, vertical: 0 });
+        let slider = YearSlider::new(first as u16..=last as u16, view.chart_cursor, view.theme).focused(true);
+        drawn.year_slider = Some(slider.area(area));
+        f.render_widget(slider, area);
+    }
+}
+
+/// GDP-per-capita reference lines of the scatter plot, in USD per person
+const PER_CAPITA_LINES: [(f64, &str); 3] = [(1e3, "$1k"), (1e4, "$10k"), (1e5, "$100k")];
+
+/// Part of the line GDP = `per_capita` × population inside the log10 plot bounds, as
+/// (lower, upper) end points; on log axes it is the diagonal y = x + log10(per_capita).
+/// None when the line misses the plot.
+pub fn per_capita_line(per_capita: f64, x_bounds: [f64; 2], y_bounds: [f64; 2]) -> Option<((f64, f64), (f64, f64))> {
+    let offset = per_capita.log10();
+    let x1 = x_bounds[0].max(y_bounds[0] - offset);
+    let x2 = x_bounds[1].min(y_bounds[1] - offset);
+    (x1 < x2).then_some(((x1, x1 + offset), (x2, x2 + offset)))
+}
+
+/// Draw the map as sentences (accessible mode): the continents at world level, where the
+/// selected country lies and what it borders on a continent, the key facts in a country
+fn draw_description<'a>(f: &mut Frame<'a>, area: Rect, view: &ViewModel, map: &MapView) {
+    let name = view.list_items.get(view.highlighted).map_or("", String::as_str);
+    let lines = match view.location {
+        Location::Continent(_) if name.is_empty() => vec!["Lista krajów jest pusta.".to_string()],
+        Location::World => {
+            let continents: Vec<(String, usize)> =
+                map.continents_west_to_east().iter().map(|c| (c.clone(), map.continent_size(c))).collect();
+            describe::describe(&Scene::World { continents: &continents }, Lang::Pl)
+        }
+        Location::Continent(continent) => {
+            let country = CountrySummary::build(name, view.cache, view.gdp_data);
+            let anchor = map.anchor(name);
+            let position = anchor.and_then(|at| Bearing::within(at, map.bounds()));
+            // Neighbours as `neighbours.json` lists them, where this map shows them
+            let neighbours: Vec<(String, Bearing)> = view
+                .cache
+                .neighbours_of(name)
+                .iter()
+                .filter_map(|n| Some((n.clone(), Bearing::between(anchor?, map.anchor(n)?))))
+                .collect();
+            describe::describe(&Scene::Continent { continent, country: &country, position, neighbours: &neighbours }, Lang::Pl)
+        }
+        Location::Country { country, .. } => match view.summary {
+            Some(summary) => describe::describe(&Scene::Country { country: summary }, Lang::Pl),
+            None => {
+                let summary = CountrySummary::build(country, view.cache, view.gdp_data);
+                describe::describe(&Scene::Country { country: &summary }, Lang::Pl)
+            }
+        },
+    };
+    let text: Vec<Line> = lines.into_iter().map(Line::from).collect();
+    let description = Paragraph::new(text)
+        .block(focus_panel(view, Panel::Center).title(focus_title(view, Panel::Center, "Opis mapy")))
+        .wrap(Wrap { trim: true });
+    f.render_widget(description, area);
+}
+
+/// Draw the continent's countries by population (x) and latest GDP (y), both on log axes,
+/// with GDP-per-capita reference lines; the country selected in the list is highlighted and labeled
+fn draw_scatter<'a>(f: &mut Frame<'a>, area: Rect, view: &ViewModel, scatter: &Scatter) {
+    let sym = view.theme.symbols();
+    let legend = match scatter.missing {
+        0 => String::new(),
+        n => format!(" {} bez danych ", humanize::format_count(n, &humanize::KRAJE)),
+    };
+    let block = panel(view)
+        .title("GDP a populacja (log)")
+        .title_bottom(legend);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let pts: Vec<(f64, f64)> = scatter.points
+        .iter()
+        .map(|p| (p.population.log10(), p.gdp.log10()))
+        .collect();
+    if pts.is_empty() {
+        f.render_widget(Paragraph::new("Brak danych o populacji i GDP"), inner);
+        return;
+    }
+    let range = |axis: fn(&(f64, f64)) -> f64| {
+        let lo = pts.iter().map(axis).fold(f64::INFINITY, f64::min);
+        let hi = pts.iter().map(axis).fold(f64::NEG_INFINITY, f64::max);
+        log_axis(lo, hi)
+    };
+    let (x_bounds, x_labels) = range(|p| p.0);
+    let (y_bounds, y_labels) = range(|p| p.1);
+
+    // Y labels in a gutter on the left, x labels on the bottom row
+    let gutter = y_labels.iter().map(|l| l.len()).max().unwrap_or(0) as u16 + 1;
+    if inner.width <= gutter + 10 || inner.height < 5 {
+        return;
+    }
+    let plot = Rect::new(inner.x + gutter, inner.y, inner.width - gutter, inner.height - 1);
+    let label_style = Style::default().fg(Color::Gray);
+    let steps = (y_labels.len() - 1) as f64;
+    for (i, label) in y_labels.iter().enumerate() {
+        let row = plot.bottom() - 1 - (i as f64 / steps * (plot.height - 1) as f64).round() as u16;
+        f.buffer_mut().set_string(inner.x, row, format!("{:>w$}", label, w = gutter as usize - 1), label_style);
+    }
+    let steps = (x_labels.len() - 1) as f64;
+    let mut free = plot.x;
+    for (i, label) in x_labels.iter().enumerate() {
+        let col = plot.x + (i as f64 / steps * (plot.width - 1) as f64).round() as u16;
+        let col = col.min(plot.right().saturating_sub(label.len() as u16));
+        if col >= free {
+            f.buffer_mut().set_string(col, inner.bottom() - 1, label, label_style);
+            free = col + label.len() as u16 + 1;
+        }
+    }
+
+    // Width of one terminal cell in plot units, to place text next to a point
+    let cell = (x_bounds[1] - x_bounds[0]) / plot.width as f64;
+    let selected = view.list_items.get(view.highlighted);
+    let highlight = scatter.points
+        .iter()
+        .zip(&pts)
+        .find(|(p, _)| Some(&p.name) == selected)
+        .map(|(p, &xy)| (xy, view.cache.display_name(&p.name, 20)));
+    let (point_color, highlight_color) = (view.theme.color(theme::CHART_LINE), view.theme.color(theme::CHART_CURSOR));
+
+    let canvas = Canvas::default()
+        .marker(sym.marker)
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            for (per_capita, label) in PER_CAPITA_LINES {
+                if let Some(((x1, y1), (x2, y2))) = per_capita_line(per_capita, x_bounds, y_bounds) {
+                    ctx.draw(&CanvasLine { x1, y1, x2, y2, color: Color::DarkGray });
+                    ctx.print(x2 - cell * label.len() as f64, y2, Span::styled(label, Style::default().fg(Color::DarkGray)));
+                }
+            }
+            ctx.layer();
+            // Whole-cell glyphs: single braille dots are too easy to miss
+            for &(x, y) in &pts {
+                ctx.print(x, y, Span::styled(sym.point, Style::default().fg(point_color)));
+            }
+            if let Some(((x, y), name)) = &highlight {
+                ctx.print(*x, *y, Span::styled(sym.selected_point, Style::default().fg(highlight_color)));
+                // Label on whichever side of the point has room
+                let width = name.width() as f64 + 1.0;
+                let lx = if x + cell * (width + 1.0) > x_bounds[1] { x - cell * width } else { x + cell * 2.0 };
+                ctx.print(lx, *y, Span::styled(name.clone(), Style::default().fg(highlight_color).add_modifier(Modifier::BOLD)));
+            }
+        });
+    f.render_widget(canvas, plot);
+}
+
+/// Draw the continent ranking: the filter row, then the table whose header stays put
+/// while the rows scroll
+fn draw_ranking<'a>(f: &mut Frame<'a>, area: Rect, view: &ViewModel, ranking: &Ranking) {
+    let sym = view.theme.symbols();
+    let visible = ranking.visible();
+    let continent = match view.location {
+        Location::Continent(c) => c.as_str(),
+        _ => "",
+    };
+    let block = panel(view)
+        .title(format!("Ranking: {}", continent))
+        .title(Line::from(format!(" pokazano {} z {} ", visible.len(), ranking.rows.len())).right_aligned());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let [filter_area, table_area] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(inner);
+
+    // Filter row: the expression (with a cursor while typed) and why it doesn't parse,
+    // or a hint when there is no filter
+    let muted = Style::default().fg(view.theme.color(theme::MUTED));
+    let mut spans = Vec::new();
+    if let Some(lookup) = view.filter_history.search.as_ref().filter(|_| ranking.editing) {
+        spans.push(Span::styled(format!("historia: {}{} {} ", lookup.needle.as_str(), sym.cursor, sym.separator.trim()), muted));
+        spans.push(Span::raw(ranking.input.as_str()));
+    } else if ranking.editing || !ranking.input.is_empty() {
+        spans.push(Span::styled("/ ", muted));
+        let (before, after) = ranking.input.split();
+        spans.push(Span::raw(before));
+        if ranking.editing {
+            spans.push(Span::styled(sym.cursor, muted));
+        }
+        spans.push(Span::raw(after));
+        if let Some(error) = &ranking.error {
+            spans.push(Span::styled(format!("  {} {}", sym.error, error), Style::default().fg(Color::Red)));
+        }
+    } else {
+        spans.push(Span::styled(
+            format!(
+                "{}: filtr (np. pop>10m) {} {}: sortowanie",
+                view.keys.label(Action::Filter),
+                sym.mark,
+                view.keys.label(Action::SortColumn)
+            ),
+            muted,
+        ));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), filter_area);
+
+    // Header names the sort column with an arrow
+    let header = [
+        ("#", None),
+        ("Kraj", Some(RankColumn::Name)),
+        ("Populacja", Some(RankColumn::Population)),
+        ("Powierzchnia", Some(RankColumn::Area)),
+        ("GDP", Some(RankColumn::Gdp)),
+    ]
+    .map(|(title, column)| match column {
+        Some(c) if c == ranking.sort => Cell::from(format!("{} {}", title, if c == RankColumn::Name { sym.ascending } else { sym.descending })),
+        _ => Cell::from(title),
+    });
+    let missing = || sym.dash.to_string();
+    // The name column takes what the other four columns, the cells between the five and
+    // the highlight symbol leave
+    let name_width = table_area.width.saturating_sub(4 + 11 + 14 + 15 + 4 + sym.highlight.width() as u16) as usize;
+    let rows = visible.iter().map(|(place, row)| {
+        Row::new([
+            Cell::from(place.to_string()),
+            Cell::from(view.cache.display_name(&row.name, name_width)),
+            Cell::from(row.population.map_or_else(missing, |p| humanize::format_population(p, Lang::Pl))),
+            Cell::from(row.area.map_or_else(missing, |a| humanize::format_area(a, Lang::Pl))),
+            Cell::from(row.gdp.map_or_else(missing, GDPData::format_gdp_value)),
+        ])
+    });
+    let table = Table::new(rows, [
+        Constraint::Length(4),
+        Constraint::Fill(1),
+        Constraint::Length(11),
+        Constraint::Length(14),
+        Constraint::Length(15),
+    ])
+    .header(Row::new(header).style(Style::default().add_modifier(Modifier::BOLD)))
+    .row_highlight_style(highlight_style(view))
+    .highlight_symbol(view.theme.symbols().highlight);
+    let mut ts = TableState::default();
+    ts.select((!visible.is_empty()).then_some(ranking.selected));
+    f.render_stateful_widget(table, table_area, &mut ts);
+}
+
+/// Smallest chart pane worth drawing side by side; below it panes are stacked
+const MIN_PANE: (u16, u16) = (30, 8);
+
+/// Split `area` into `n` equal chart panes: two columns (a 2×2 grid for three or four),
+/// or one full-width row per pane when grid panes would be smaller than MIN_PANE
+pub fn grid_panes(area: Rect, n: usize) -> Vec<Rect> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let cols = n.min(2);
+    let rows = n.div_ceil(cols);
+    let fits = area.width / cols as u16 >= MIN_PANE.0 && area.height / rows as u16 >= MIN_PANE.1;
+    let (cols, rows) = if fits { (cols, rows) } else { (1, n) };
+    Layout::vertical(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(area)
+        .iter()
+        .flat_map(|&row| Layout::horizontal(vec![Constraint::Ratio(1, cols as u32); cols]).split(row).to_vec())
+        .take(n)
+        .collect()
+}
+
+/// Draw one GDP chart per marked country, all over the same years and, when
+/// `compare_shared_y` is on, the same GDP scale
+fn draw_compare_grid<'a>(f: &mut Frame<'a>, view: &ViewModel, charts: &[ChartData]) {
+    let rows = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(f.area());
+
+    let axis = if view.compare_shared_y { "wspólna" } else { "osobna dla każdego kraju" };
+    let sym = view.theme.symbols();
+    let help = view.keys.hints(&view.context).join(&format!(" {} ", sym.mark));
+    let group = view
+        .compare_per_capita
+        .map(|pc| format!("GDP na osobę razem: {} ({}) {} ", GDPData::format_gdp_value(pc.value()), coverage_text(&pc), sym.mark))
+        .unwrap_or_default();
+    let status = Paragraph::new(format!("Oś GDP: {} {} {}{}", axis, sym.mark, group, help))
+        .style(Style::default().fg(view.theme.color(theme::MUTED)));
+    f.render_widget(status, rows[1]);
+
+    // Synchronized years across every pane
+    let years = charts.iter().flat_map(|c| c.points.iter().map(|&(y, _)| y));
+    let min_year = years.clone().fold(f64::INFINITY, f64::min);
+    let max_year = years.fold(f64::NEG_INFINITY, f64::max);
+    let shared_max = charts.iter().map(ChartData::max).fold(0.0, f64::max);
+
+    for (chart, pane) in charts.iter().zip(grid_panes(rows[0], charts.len())) {
+        let max = if view.compare_shared_y { shared_max } else { chart.max() };
+        let y_max = (max * 1.1).ceil().max(1.0);
+        let latest = chart
+            .latest()
+            .map(|(yr, val)| format!(" {} {} ({})", sym.dash, GDPData::format_gdp_value(val), yr))
+            .unwrap_or_default();
+        // Line and name in the country's mark color, as on the map and in the list
+        let color = Style::default().fg(view.theme.mark(chart.color));
+        let ds = Dataset::default()
+            .marker(sym.marker)
+            .graph_type(GraphType::Line)
+            .style(color)
+            .data(&chart.points);
+        let title = Line::from(vec![Span::styled(chart.name.clone(), color.add_modifier(Modifier::BOLD)), Span::raw(latest)]);
+        let widget = Chart::new(vec![ds])
+            .style(chart_style(view))
+            .block(panel(view).title(title))
+            .x_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([min_year, max_year])
+                    .labels([min_year, max_year].map(|y| Span::from((y as i32).to_string()))),
+            )
+            .y_axis(
+                Axis::default()
+                    .style(Style::default().fg(Color::Gray))
+                    .bounds([0.0, y_max])
+                    .labels(["0".to_string(), format!("{:.1}B", y_max / 2e9), format!("{:.1}B", y_max / 1e9)].map(Span::from)),
+            );
+        f.render_widget(widget, pane);
+    }
+}
+
+/// Choropleth legend in the bottom-left corner of the map `area`: each bucket's color,
+/// GDP range and country count. When focused, the selected bucket is marked.
+fn draw_choropleth_legend<'a>(f: &mut Frame<'a>, view: &ViewModel, choropleth: &Choropleth, area: Rect) {
+    let sym = view.theme.symbols();
+    let items: Vec<ListItem> = choropleth
+        .buckets
+        .iter()
+        .enumerate()
+        .map(|(i, b)| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", sym.swatch), Style::default().fg(view.theme.bucket(i))),
+                Span::raw(format!(
+                    "{} {} {} ({})",
+                    GDPData::format_gdp_value(b.lo),
+                    sym.dash,
+                    GDPData::format_gdp_value(b.hi),
+                    b.count
+                )),
+            ]))
+        })
+        .collect();
+    let hint = match view.legend_focus {
+        Some(_) => format!(" {} {} {}: zamknij ", sym.up_down, sym.mark, view.keys.label(Action::Back)),
+        None => format!(" {}: wybierz przedział ", view.keys.label(Action::Legend)),
+    };
+    let title = match choropleth.year {
+        Some(year) => format!("GDP ({})", year),
+        None => "GDP (ostatni rok)".to_string(),
+    };
+    let mut block = panel(view).title(title).title_bottom(hint);
+    if choropleth.missing > 0 {
+        block = block.title(format!(" bez danych: {} ", choropleth.missing));
+    }
+    if !choropleth.interpolated.is_empty() {
+        block = block.title(format!(" interpolowane: {} ", choropleth.interpolated.len()));
+    }
+    let inner = area.inner(Margin { horizontal: 1, vertical: 1 });
+    let (w, h) = (46.min(inner.width), (items.len() as u16 + 2).min(inner.height));
+    let rect = Rect::new(inner.x, inner.bottom() - h, w, h);
+    let mut ls = ListState::default();
+    ls.select(view.legend_focus);
+    let list = List::new(items)
+        .block(block)
+        .highlight_symbol(view.theme.symbols().highlight)
+        .highlight_style(highlight_style(view));
+    f.render_widget(Clear, rect);
+    f.render_stateful_widget(list, rect, &mut ls);
+}
+
+/// Time a job runs before its progress shows, so quick loads don't flash an overlay
+const PROGRESS_DELAY: Duration = Duration::from_millis(150);
+
+/// Progress gauge of a background job over `area`, with the key that cancels it
+fn draw_job_progress<'a, T>(f: &mut Frame<'a>, view: &ViewModel, job: &Job<T>, area: Rect) {
+    if job.started.elapsed() < PROGRESS_DELAY {
+        return;
+    }
+    let (percent, message) = match job.state() {
+        JobState::Running { percent, message } => (*percent, message.as_str()),
+        _ => (0, ""),
+    };
+    let hint = if job.cancel_requested() {
+        format!(" Anulowanie{} ", view.theme.symbols().ellipsis)
+    } else {
+        format!(" {}: anuluj ", view.keys.label(Action::Back))
+    };
+    let gauge = Gauge::default()
+        .block(
+            panel(view)
+                .title(format!("Wczytywanie mapy: {}", job.title))
+                .title_bottom(hint),
+        )
+        .gauge_style(Style::default().fg(view.theme.color(theme::CHART_CURSOR)))
+        .percent(percent as u16)
+        .label(format!("{}% {}", percent, message));
+    let popup = centered(area, 50, 3);
+    f.render_widget(Clear, popup);
+    f.render_widget(gauge, popup);
+}
+
+/// Draw the popup listing GDP dataset names to alias the current country to
+fn draw_alias_picker<'a>(f: &mut Frame<'a>, view: &ViewModel, picker: &AliasPicker) {
+    let items: Vec<ListItem> = picker.options.iter().map(|name| ListItem::new(name.as_str())).collect();
+    let mut ls = ListState::default();
+    ls.select(Some(picker.selected));
+    let hint = format!(
+        " {}: zapisz alias {} {}: anuluj ",
+        view.keys.label(Action::Select),
+        view.theme.symbols().mark,
+        view.keys.label(Action::Back)
+    );
+    let list = List::new(items)
+        .block(
+            panel(view)
+                .title(format!("Nazwa w danych GDP dla {}", picker.country))
+                .title_bottom(hint),
+        )
+        .highlight_symbol(view.theme.symbols().highlight)
+        .highlight_style(highlight_style(view));
+    let popup = centered(f.area(), 60, picker.options.len().max(1) as u16 + 2);
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut ls);
+}
+
+/// Ask whether to reopen the saved session at the place with a similar name
+fn draw_restore_offer<'a>(f: &mut Frame<'a>, view: &ViewModel, offer: &RestoreOffer) {
+    let arrow = if view.theme.symbols().ascii { "->" } else { "→" };
+    let text = format!(
+        "{} {} {}, przywrócić tam? (t/n)",
+        offer.saved,
+        arrow,
+        AppState::place_name(&offer.found)
+    );
+    let question = Paragraph::new(text)
+        .block(panel(view).title("Przywracanie sesji").title_bottom(" nazwa zmieniła się od ostatniego uruchomienia "))
+        .wrap(Wrap { trim: true });
+    let popup = centered(f.area(), 64, 3);
+    f.render_widget(Clear, popup);
+    f.render_widget(question, popup);
+}
+
+/// Draw the map quiz over the whole screen: the names to pick from on the left, the map
+/// with the country asked about highlighted on the right, and once it's over the summary
+fn draw_quiz<'a>(f: &mut Frame<'a>, view: &ViewModel, quiz: &Quiz) {
+    let rows = Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).split(f.area());
+    let sym = view.theme.symbols();
+    let muted = Style::default().fg(view.theme.color(theme::MUTED));
+    let status = match view.notification {
+        Some(n) => Paragraph::new(n.text.as_str()).style(Style::default().fg(view.theme.color(theme::NOTICE))),
+        None => Paragraph::new(format!(
+            "1–{}/{}: odpowiedz {} {}: zakończ {} {}: wyjście",
+            quiz::OPTIONS,
+            view.keys.label(Action::Select),
+            sym.mark,
+            view.keys.label(Action::Back),
+            sym.mark,
+            view.keys.label(Action::Quit)
+        ))
+        .style(muted),
+    };
+    f.render_widget(status, rows[1]);
+    if view.flash {
+        f.buffer_mut().set_style(rows[1], Style::default().add_modifier(Modifier::REVERSED));
+    }
+
+    let [list_area, map_area] = Layout::horizontal([Constraint::Length(32), Constraint::Min(0)]).areas(rows[0]);
+    let (number, total) = quiz.progress();
+    let kind = if quiz.mode == QuizMode::Review { "Powtórka" } else { "Quiz" };
+    let block = panel(view).title(format!("{} {}/{}", kind, number, total));
+    match &quiz.question {
+        Some(question) => {
+            let items: Vec<ListItem> = question
+                .options
+                .iter()
+                .enumerate()
+                .map(|(i, name)| ListItem::new(format!("{}. {}", i + 1, name)))
+                .collect();
+            let mut ls = ListState::default();
+            ls.select(Some(question.selected));
+            let list = List::new(items)
+                .block(block)
+                .highlight_symbol(sym.highlight)
+                .highlight_style(Style::default().fg(view.theme.color(theme::FOCUS_BORDER)));
+            f.render_stateful_widget(list, list_area, &mut ls);
+        }
+        None => f.render_widget(block, list_area),
+    }
+
+    let Some(map) = view.map else { return };
+    let asked = quiz.question.as_ref().map(|q| q.answer.country.as_str());
+    let viewport = asked.map_or_else(|| map.bounds(), |country| map.preferred_viewport(country));
+    let mut widget = map.widget()
+        .title("Który to kraj?")
+        .viewport(viewport)
+        .projection(view.projection)
+        .style(view.map_style)
+        .fill_highlight(true)
+        .symbols(sym);
+    if let Some(country) = asked {
+        widget = widget.highlight(country);
+    }
+    if let Some(marker) = view.map_marker {
+        widget = widget.marker(marker);
+    }
+    f.render_widget(widget, map_area);
+
+    if quiz.question.is_none() {
+        let summary = quiz::summarize(&quiz.answers);
+        let weakest = match &summary.weakest {
+            Some((continent, right, asked)) => format!("Najsłabszy kontynent: {} ({}/{})", continent, right, asked),
+            None => "Bez ani jednego błędu!".to_string(),
+        };
+        let lines = vec![
+            Line::from(format!("Wynik: {}/{} ({:.0}%)", summary.score, summary.total, summary.accuracy * 100.0)),
+            Line::from(weakest),
+        ];
+        let hint = format!(" {}: zamknij ", view.keys.label(Action::Select));
+        let popup = centered(f.area(), 48, lines.len() as u16 + 2);
+        f.render_widget(Clear, popup);
+        f.render_widget(Paragraph::new(lines).block(panel(view).title("Podsumowanie").title_bottom(hint)), popup);
+    }
+}
+
+/// Style of the selected row of a list or table, in the map's highlight color
+fn highlight_style(view: &ViewModel) -> Style {
+    Style::default().fg(view.map_style.highlight)
+}
+
+/// Style of the GDP charts: the map style's background, when it sets one, with its base
+/// color for the axes and labels drawn on it
+fn chart_style(view: &ViewModel) -> Style {
+    let style = view.map_style;
+    style.background.map_or_else(Style::default, |bg| Style::default().bg(bg).fg(style.base))
+}
+
+/// Draw the `?` overlay listing every action with its keys; actions unavailable here are dimmed
+fn draw_help<'a>(f: &mut Frame<'a>, view: &ViewModel) {
+    let ctx = &view.context;
+    let mut lines: Vec<Line> = Action::ALL
+        .into_iter()
+        .filter_map(|a| {
+            let label = view.keys.label(a);
+            if label.is_empty() {
+                return None;
+            }
+            let color = if a.available(ctx) { Color::White } else { Color::DarkGray };
+            Some(Line::styled(format!("{:>12}  {}", label, a.description()), Style::default().fg(color)))
+        })
+        .collect();
+
+    // Keys the focused panel takes before the bindings above, panel by panel
+    lines.push(Line::raw(""));
+    lines.push(Line::raw(format!("Panel z fokusem ({}) ma pierwszeństwo:", view.keys.label(Action::SwitchView))));
+    for which in Panel::ALL {
+        let keys: Vec<String> = which
+            .actions()
+            .iter()
+            .filter_map(|&(actions, what)| {
+                let labels: Vec<String> = actions.iter().map(|&a| view.keys.label(a)).filter(|l| !l.is_empty()).collect();
+                (!labels.is_empty()).then(|| format!("{} {}", labels.join("/"), what))
+            })
+            .collect();
+        let focused = which == view.active_panel && !view.gdp_chart_active;
+        let color = if focused { Color::White } else { Color::DarkGray };
+        let text = format!("{:>12}  {}", focus_title(view, which, which.label()), keys.join(", "));
+        lines.push(Line::styled(text, Style::default().fg(color)));
+    }
+
+    // Center the popup over the current view
+    let popup = centered(f.area(), 72, lines.len() as u16 + 2);
+    let help = Paragraph::new(lines)
+        .block(panel(view).title("Pomoc (Esc aby zamknąć)"));
+    f.render_widget(Clear, popup);
+    f.render_widget(help, popup);
+}
+
+/// "vs 2022: +4.1% ▲" under the latest GDP, naming the previous year with data even when
+/// years are missing in between: green for growth, red for a contraction, gray without one
+fn gdp_growth_line(view: &ViewModel, growth: Option<Growth>) -> Line<'static> {
+    let sym = view.theme.symbols();
+    let Some(growth) = growth else {
+        return Line::styled("vs poprzedni rok: brak danych", Style::default().fg(view.theme.color(theme::MUTED)));
+    };
+    let (arrow, color) = if growth.percent > 0.0 {
+        (sym.rise, theme::GROWTH)
+    } else if growth.percent < 0.0 {
+        (sym.fall, theme::DECLINE)
+    } else {
+        ("", theme::MUTED)
+    };
+    Line::styled(
+        format!("vs {}: {:+.1}% {}", growth.previous_year, growth.percent, arrow).trim_end().to_string(),
+        Style::default().fg(view.theme.color(color)),
+    )
+}
+
+/// Lines of the diagnostics overlay: where data comes from and how much of it loaded
+fn diagnostics_lines(view: &ViewModel) -> Vec<String> {
+    let mut lines = vec![format!("Katalog danych: {}", view.cache.base().display())];
+    lines.extend(view.paths.notice());
+    lines.push(format!("Kolory terminala: {}", view.theme.support().label()));
+    lines.push(format!("Kolory mapy: {}", view.map_style.label()));
+    let sym = view.theme.symbols();
+    lines.push(format!("Symbole: {}", if sym.ascii { "ASCII (brak UTF-8)" } else { "Unicode" }));
+
+    match view.gdp_data {
+        Some(gdp) => {
+            let coverage: Vec<_> = gdp.countries().collect();
+            let empty = coverage.iter().filter(|(_, n, _)| *n == 0).count();
+            lines.push(format!(
+                "GDP: {} w {}, {} bez danych",
+                humanize::format_count(gdp.len(), &humanize::KRAJE),
+                view.gdp_path,
+                empty
+            ));
+            if gdp.metadata().scale != 1.0 {
+                lines.push(format!("  Skala wartości: ×{:e}", gdp.metadata().scale));
+            }
+            if let Some(magnitude) = gdp.magnitude() {
+                lines.push(format!(
+                    "  Mediana {:.1e} USD poza zakresem {:.0e}{}{:.0e}, sugerowana skala {:e}",
+                    magnitude.median, magnitude.plausible[0], sym.dash, magnitude.plausible[1], magnitude.suggested_scale
+                ));
+            }
+
+            // Countries with the fewest data points are the likeliest dataset problems
+            let mut sparse: Vec<_> = coverage.into_iter().filter(|(_, n, _)| *n > 0).collect();
+            sparse.sort_by_key(|&(name, n, _)| (n, name));
+            if !sparse.is_empty() {
+                lines.push("Najmniej lat z danymi GDP:".to_string());
+            }
+            for (name, n, span) in sparse.into_iter().take(5) {
+                lines.push(format!("  {}: {} ({}{}{})", name, n, span.start(), sym.dash, span.end()));
+            }
+        }
+        None => lines.push(format!("GDP: nie wczytano {}", view.gdp_path)),
+    }
+
+    if let Some(map) = view.map {
+        lines.push(format!(
+            "Mapa: {}, pominięto {}",
+            humanize::format_count(map.feature_count(), &humanize::OBIEKTY),
+            map.skipped().len()
+        ));
+        let (full, drawn) = map.segments();
+        lines.push(format!(
+            "  Odcinki: {} w danych, {} rysowanych (uproszczenie {})",
+            humanize::group_thousands(full as u64, Lang::Pl),
+            humanize::group_thousands(drawn as u64, Lang::Pl),
+            view.config.simplify_epsilon
+        ));
+        let (frames, reused) = view.map_frames;
+        lines.push(format!(
+            "  Klatki mapy: {}, w tym {} skopiowanych bez rysowania",
+            humanize::group_thousands(frames, Lang::Pl),
+            humanize::group_thousands(reused, Lang::Pl)
+        ));
+        for (name, reason) in map.skipped() {
+            lines.push(format!("  {}: {}", name, reason));
+        }
+    }
+    // Lists and map disagreeing explain why the country and feature counts differ
+    if let Some(count) = view.map_count {
+        for (label, names) in [("Na liście, brak na mapie", &count.unmapped), ("Na mapie, brak na liście", &count.unlisted)] {
+            if names.is_empty() {
+                continue;
+            }
+            lines.push(format!("{}: {}", label, names.len()));
+            lines.extend(names.iter().take(5).map(|name| format!("  {}", name)));
+            if names.len() > 5 {
+                lines.push(format!("  {} i {} więcej", sym.ellipsis, names.len() - 5));
+            }
+        }
+    }
+    lines
+}
+
+/// Draw the data diagnostics overlay
+fn draw_diagnostics<'a>(f: &mut Frame<'a>, view: &ViewModel) {
+    let lines = diagnostics_lines(view);
+    let width = lines.iter().map(|l| l.chars().count() as u16).max().unwrap_or(0) + 2;
+    let popup = centered(f.area(), width.max(40), lines.len() as u16 + 2);
+    let text = Paragraph::new(lines.join("\n"))
+        .wrap(Wrap { trim: false })
+        .block(panel(view).title("Diagnostyka (Esc aby zamknąć)"));
+    f.render_widget(Clear, popup);
+    f.render_widget(text, popup);
 }
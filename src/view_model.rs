@@ -0,0 +1,149 @@
+//! What one frame shows: `AppState::view_model` takes a consistent snapshot of everything
+//! `ui::draw` reads, so rendering never sees the state halfway through a change and can
+//! be driven by a view model built some other way. What drawing finds out about the
+//! layout goes back to the state as a `Drawn`.
+use ratatui::{buffer::Buffer, layout::{Position, Rect}, symbols::Marker};
+use std::{collections::{BTreeMap, BTreeSet}, ops::RangeInclusive};
+use crate::{
+    config::{Config, Projection},
+    data::{DataCache, GeoLevel},
+    gdp_reader::{GDPData, PerCapita},
+    input::History,
+    jobs::Job,
+    keys::{Context, KeyBindings},
+    map_draw::{projection, CountryCount, MapFrame, MapStyle, MapView, MiniMap},
+    paths::Paths,
+    prefs::{ListSort, Source},
+    quiz::Quiz,
+    state::{AliasPicker, ChartData, Choropleth, GdpStatus, ListSearch, Location, Mark, Notification, Panel, Ranking, RestoreOffer, Scatter},
+    summary::CountrySummary,
+    theme::{Rgb, Theme},
+    year_slider::SliderArea,
+};
+
+/// Everything one frame draws, borrowed from the state or worked out in advance
+pub struct ViewModel<'a> {
+    // Data and preferences
+    pub cache: &'a DataCache,              // display names and the data directory
+    pub config: &'a Config,                // user preferences
+    pub keys: &'a KeyBindings,             // key labels for hints and help
+    pub theme: &'a Theme,                  // colors and symbols
+    pub paths: &'a Paths,                  // where data, state and cache files live
+    pub context: Context,                  // level, chart and focus, for the available actions
+    pub location: &'a Location,            // current place, for the breadcrumb
+    pub active_panel: Panel,               // focused panel
+
+    // Overlays
+    pub show_help: bool,                   // help overlay visible
+    pub show_diagnostics: bool,            // data diagnostics overlay visible
+    pub tour_step: Option<usize>,          // current walkthrough step
+    pub attract: bool,                     // attract mode is cycling random countries
+    pub notification: Option<&'a Notification>, // transient status bar message
+    pub flash: bool,                       // status bar inverted (visual bell)
+    pub breadcrumb_hover: Option<usize>,   // breadcrumb segment under the mouse pointer
+
+    // Left panel
+    pub list_items: &'a [String],          // items in the selection list
+    pub selected: usize,                   // index of the selected item
+    pub highlighted: usize,                // item the map and plots highlight
+    pub search: Option<&'a ListSearch>,    // list search, while typed
+    pub search_history: &'a History,       // earlier list search queries (Ctrl+R state)
+    pub filter_history: &'a History,       // earlier ranking filters (Ctrl+R state)
+    pub visited: &'a BTreeSet<String>,     // canonical keys of countries opened
+    pub marked: &'a [Mark],                // countries marked for comparison, with their colors
+
+    // Center panel
+    pub map: Option<&'a MapView>,          // current map view
+    pub neighbours: &'a BTreeSet<String>,  // canonical keys of the opened country's neighbours
+    pub map_job: Option<&'a Job<MapView>>, // continent map loading in the background
+    pub map_count: Option<&'a CountryCount>, // listed countries against the map's features
+    pub world_outline: &'a MiniMap,        // simplified world geometry for the inset
+    pub show_minimap: bool,                // world inset on continent and country maps
+    pub show_labels: bool,                 // country names on the world map
+    pub show_graticule: bool,              // latitude/longitude grid behind the map
+    pub auto_zoom: bool,                   // continent maps frame the selected country
+    pub list_sort: Option<(ListSort, Source)>, // order of the list and where it was set; None in a country
+    pub projection: Projection,            // how the map fits longitude and latitude to the cells
+    pub map_marker: Option<Marker>,        // dots the map is drawn with; None keeps the symbols' (ASCII)
+    pub map_style: MapStyle,               // colors of the map, its highlight in the lists and the panels' text
+    pub accessible: bool,                  // describe the map in sentences instead of drawing it
+    pub pulse_bright: bool,                // highlight in its brighter phase
+    pub scatter: Option<&'a Scatter>,      // GDP vs population plot replacing the map
+    pub ranking: Option<&'a Ranking>,      // ranking table replacing the map
+    pub choropleth: Option<&'a Choropleth>, // GDP buckets of the current map
+    pub legend_focus: Option<usize>,       // bucket selected in the focused legend
+    pub continent_tints: BTreeMap<String, Rgb>, // world map and list tint per continent; empty when off
+    pub map_hover: Option<&'a CountrySummary>, // feature under the mouse pointer, for the detail strip
+    pub map_cache: Option<&'a (MapFrame, Buffer)>, // the map as last rendered, copied in while its frame is the same
+    pub map_frames: (u64, u64),            // frames that drew the map, and those of them that copied it
+
+    // Right panel
+    pub info: &'a str,                     // summary of the loaded map
+    pub summary: Option<&'a CountrySummary>, // everything known about the opened country
+    pub place_per_capita: Option<PerCapita>, // GDP per capita of the world's or continent's countries
+    pub facts: &'a [String],               // fun facts of the country
+    pub fact_focus: usize,                 // fact shown in full
+    pub fact_offset: u16,                  // scroll position within the focused fact
+    pub fact_rotation_left: Option<f64>,   // share of the rotation period left, when rotating
+    pub gdp_data: Option<&'a GDPData>,     // GDP dataset, for diagnostics
+    pub gdp_path: &'a str,                 // location of the GDP CSV
+    pub gdp_status: &'a GdpStatus,         // latest GDP lookup result
+
+    // Charts and pickers
+    pub gdp_chart_active: bool,            // detailed GDP chart replaces the panels
+    pub all_gdp_data: Option<&'a BTreeMap<u16, f64>>, // GDP history for the chart
+    pub chart_cursor: Option<u16>,         // year under the GDP chart cursor
+    pub active_year: Option<u16>,          // year of the choropleth and the year slider (None = latest)
+    pub year_span: Option<RangeInclusive<u16>>, // years of the GDP dataset, for the map's year slider
+    pub chart_log: bool,                   // GDP chart plots log10 of the values
+    pub compare: Option<&'a [ChartData]>,  // GDP chart grid of the marked countries
+    pub compare_per_capita: Option<PerCapita>, // GDP per capita of the marked countries together
+    pub compare_shared_y: bool,            // grid panes share one GDP scale
+    pub alias_picker: Option<&'a AliasPicker>, // choosing a GDP name for the country
+    pub restore_offer: Option<&'a RestoreOffer>, // startup question about a renamed saved place
+    pub quiz: Option<&'a Quiz>,            // map quiz replacing the panels
+}
+
+impl ViewModel<'_> {
+    /// Current level
+    pub fn level(&self) -> GeoLevel {
+        self.context.level.clone()
+    }
+}
+
+/// What drawing a frame found out about the layout, for the input handlers
+#[derive(Debug, Default)]
+pub struct Drawn {
+    pub breadcrumb: Vec<(Location, Rect)>, // breadcrumb segments and where they went
+    pub fact_overflow: u16,                // focused fact lines that didn't fit
+    pub map_area: Option<MapArea>,         // where the map went; None when none was drawn
+    pub year_slider: Option<SliderArea>,   // where the year slider went; None when none was drawn
+    pub map_rendered: Option<(MapFrame, Buffer)>, // the map rendered afresh, to copy in while nothing it shows changes
+    pub map_reused: bool,                  // the map was copied from the last rendering
+}
+
+/// Inside of the map block and the coordinates it shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapArea {
+    pub inner: Rect,
+    pub x_bounds: [f64; 2], // longitudes at the left and right edges, after projection
+    pub y_bounds: [f64; 2], // latitudes at the bottom and top edges
+    pub projection: Projection, // what the bounds are in: degrees, or projected ones to undo
+    pub central: f64,           // meridian the projection is centred on
+}
+
+impl MapArea {
+    /// Map coordinate (longitude, latitude) at the middle of the cell `column`, `row`;
+    /// None outside the map
+    pub fn coordinate(&self, column: u16, row: u16) -> Option<(f64, f64)> {
+        if !self.inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        let fx = (column - self.inner.x) as f64 + 0.5;
+        let fy = (row - self.inner.y) as f64 + 0.5;
+        let [x0, x1] = self.x_bounds;
+        let [y0, y1] = self.y_bounds;
+        let (x, y) = (x0 + fx / self.inner.width as f64 * (x1 - x0), y1 - fy / self.inner.height as f64 * (y1 - y0));
+        projection(self.projection, self.central).unproject(x, y)
+    }
+}
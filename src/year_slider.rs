@@ -0,0 +1,132 @@
+//! A horizontal year slider: a track over a range of years with tick marks at the
+//! decades, a handle at the chosen year and the year written under it. It knows where its
+//! years went, so a click or drag on the drawn slider maps back to a year; used under the
+//! choropleth map and under the GDP chart so both follow the same active year.
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+    style::{Modifier, Style},
+    widgets::Widget,
+};
+use std::ops::RangeInclusive;
+use unicode_width::UnicodeWidthStr;
+use crate::theme::{self, Theme};
+
+/// Rows the slider takes: the track, then the year labels
+pub const HEIGHT: u16 = 2;
+
+pub struct YearSlider<'a> {
+    years: RangeInclusive<u16>,
+    value: Option<u16>, // None = each series' latest year, drawn at the end
+    focused: bool,      // takes Left/Right, drawn in the focus color
+    theme: &'a Theme,
+}
+
+impl<'a> YearSlider<'a> {
+    pub fn new(years: RangeInclusive<u16>, value: Option<u16>, theme: &'a Theme) -> Self {
+        Self { years, value, focused: false, theme }
+    }
+
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Where the years go when the slider is drawn in `area`, for hit-testing
+    pub fn area(&self, area: Rect) -> SliderArea {
+        SliderArea { area, years: self.years.clone() }
+    }
+}
+
+/// A drawn slider: its cells and the years along them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SliderArea {
+    pub area: Rect,
+    pub years: RangeInclusive<u16>,
+}
+
+impl SliderArea {
+    /// Year at the cell `column`, `row`; None off the slider
+    pub fn year_at(&self, column: u16, row: u16) -> Option<u16> {
+        self.area.contains(Position::new(column, row)).then(|| self.year_along(column))
+    }
+
+    /// Year at `column`, clamped to the ends of the track; a drag keeps going past them
+    pub fn year_along(&self, column: u16) -> u16 {
+        year_at_offset(&self.years, column.saturating_sub(self.area.x), self.area.width)
+    }
+}
+
+/// Cell offset of `year` on a track `width` cells wide, the first and last year at the ends
+pub fn offset_of(years: &RangeInclusive<u16>, year: u16, width: u16) -> u16 {
+    let span = years.end().saturating_sub(*years.start()) as u32;
+    let last = width.saturating_sub(1) as u32;
+    if span == 0 {
+        return 0;
+    }
+    let along = year.clamp(*years.start(), *years.end()) as u32 - *years.start() as u32;
+    ((along * last + span / 2) / span) as u16
+}
+
+/// Year nearest the cell `offset` of a track `width` cells wide; the inverse of `offset_of`
+pub fn year_at_offset(years: &RangeInclusive<u16>, offset: u16, width: u16) -> u16 {
+    let span = years.end().saturating_sub(*years.start()) as u32;
+    let last = width.saturating_sub(1) as u32;
+    if last == 0 {
+        return *years.end();
+    }
+    let offset = (offset as u32).min(last);
+    years.start() + ((offset * span + last / 2) / last) as u16
+}
+
+impl Widget for YearSlider<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 2 || area.height == 0 || self.years.is_empty() {
+            return;
+        }
+        let sym = self.theme.symbols();
+        let muted = Style::default().fg(self.theme.color(theme::MUTED));
+        let track = if self.focused { Style::default().fg(self.theme.color(theme::FOCUS_BORDER)) } else { muted };
+        let handle = Style::default().fg(self.theme.color(theme::CHART_CURSOR)).add_modifier(Modifier::BOLD);
+        let (x, y, width) = (area.x, area.y, area.width);
+
+        // Track, with ticks at the decades
+        for dx in 0..width {
+            buf[(x + dx, y)].set_symbol(sym.slider_track).set_style(track);
+        }
+        let decades: Vec<u16> = self.years.clone().filter(|year| year % 10 == 0).collect();
+        for &decade in &decades {
+            buf[(x + offset_of(&self.years, decade, width), y)].set_symbol(sym.slider_tick).set_style(track);
+        }
+        let value = self.value.unwrap_or(*self.years.end());
+        let at = offset_of(&self.years, value, width);
+        buf[(x + at, y)].set_symbol(sym.slider_handle).set_style(handle);
+
+        if area.height < HEIGHT {
+            return;
+        }
+        // The handle's year, and decade labels where they run into neither it nor each other
+        let label = match self.value {
+            Some(year) => format!(" {} ", year),
+            None => " ostatni rok ".to_string(),
+        };
+        let handle_start = label_start(at, label.width() as u16, width);
+        let handle_end = handle_start + label.width() as u16;
+        let mut free_from = 0;
+        for &decade in &decades {
+            let text = decade.to_string();
+            let start = label_start(offset_of(&self.years, decade, width), text.width() as u16, width);
+            let end = start + text.width() as u16;
+            if start >= free_from && (end <= handle_start || start >= handle_end) {
+                buf.set_string(x + start, y + 1, &text, muted);
+                free_from = end + 1;
+            }
+        }
+        buf.set_stringn(x + handle_start, y + 1, &label, width.saturating_sub(handle_start) as usize, handle);
+    }
+}
+
+/// First cell of a label `len` cells wide centered under `at`, kept inside the track
+fn label_start(at: u16, len: u16, width: u16) -> u16 {
+    at.saturating_sub(len / 2).min(width.saturating_sub(len))
+}
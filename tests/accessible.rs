@@ -0,0 +1,170 @@
+//! Accessible mode: the map described in sentences instead of drawn — the continents at
+//! world level, where the selected country lies and what it borders on a continent, the key
+//! facts in a country — in Polish or English, with plain symbols and animations off
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use rustatlas::{
+    cli::{parse, Command},
+    data::DataCache,
+    describe::{describe, Bearing, Scene},
+    humanize::Lang,
+    keys::Action,
+    state::{AppState, Panel},
+    summary::CountrySummary,
+    ui,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+/// Middle in the middle of Testland, Northeast and Southwest touching its corners, Apart
+/// in the south-east bordering nothing
+fn atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Middle", "Northeast", "Southwest", "Apart"])
+        .country("Middle", square_at(4.0, 4.0))
+        .country("Northeast", square_at(8.0, 8.0))
+        .country("Southwest", square_at(0.0, 0.0))
+        .country("Apart", square_at(8.0, 0.0))
+        .facts("Middle", &["Leży w środku."])
+        .gdp("Middle", 2000..=2010, |year| 1e9 * (year - 1999) as f64)
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("neighbours.json"), r#"{"Middle": ["Northeast", "Southwest"], "Apart": []}"#).unwrap();
+    dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.make_accessible();
+    state
+}
+
+fn enter(state: &mut AppState, place: &str) {
+    state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// The screen as text, with the non-breaking spaces between numbers and units as spaces
+fn screen(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+    terminal.draw(|f| {
+        ui::draw(f, &state.view_model());
+    })
+    .unwrap();
+    let buffer: &Buffer = terminal.backend().buffer();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect::<String>()
+        .replace('\u{a0}', " ")
+}
+
+#[test]
+fn bearings_between_and_within() {
+    assert_eq!(Bearing::between((0.0, 0.0), (0.0, 5.0)), Bearing::North);
+    assert_eq!(Bearing::between((0.0, 0.0), (5.0, 5.0)), Bearing::NorthEast);
+    assert_eq!(Bearing::between((0.0, 0.0), (-5.0, 0.0)), Bearing::West);
+    assert_eq!(Bearing::between((0.0, 0.0), (-5.0, -5.0)), Bearing::SouthWest);
+    // Far north a degree of longitude is short: 10° east and 4° north is mostly north
+    assert_eq!(Bearing::between((0.0, 68.0), (10.0, 72.0)), Bearing::NorthEast);
+    assert_eq!(Bearing::between((0.0, 0.0), (10.0, 4.0)), Bearing::East);
+
+    let bounds = ([0.0, 9.0], [0.0, 9.0]);
+    assert_eq!(Bearing::within((4.5, 4.5), bounds), None);
+    assert_eq!(Bearing::within((8.5, 8.5), bounds), Some(Bearing::NorthEast));
+    assert_eq!(Bearing::within((8.5, 0.5), bounds), Some(Bearing::SouthEast));
+    assert_eq!(Bearing::within((0.5, 4.5), bounds), Some(Bearing::West));
+}
+
+#[test]
+fn scenes_in_both_languages() {
+    let continents = [("Europe".to_string(), 2), ("Asia".to_string(), 1)];
+    let world = Scene::World { continents: &continents };
+    assert_eq!(describe(&world, Lang::Pl), ["Świat: 2 kontynenty, 3 kraje.", "Europe: 2 kraje.", "Asia: 1 kraj."]);
+    assert_eq!(describe(&world, Lang::En), ["The world: 2 continents, 3 countries.", "Europe: 2 countries.", "Asia: 1 country."]);
+
+    let dir = atlas("accessible-scenes");
+    let cache = DataCache::new(&dir).unwrap();
+    let middle = CountrySummary::build("Middle", &cache, None);
+    let neighbours = [("Northeast".to_string(), Bearing::NorthEast), ("Southwest".to_string(), Bearing::SouthWest)];
+    let placed = Scene::Continent { continent: "Testland", country: &middle, position: None, neighbours: &neighbours };
+    assert_eq!(
+        describe(&placed, Lang::Pl),
+        ["Middle: środkowa część kontynentu Testland; graniczy z: Northeast na północnym wschodzie, Southwest na południowym zachodzie."]
+    );
+    assert_eq!(
+        describe(&placed, Lang::En),
+        ["Middle: central Testland, bordered by Northeast to the north-east, Southwest to the south-west."]
+    );
+    let alone = Scene::Continent { continent: "Testland", country: &middle, position: Some(Bearing::SouthEast), neighbours: &[] };
+    assert_eq!(describe(&alone, Lang::En), ["Middle: south-eastern Testland, bordering no country on the map."]);
+
+    // Without a GDP dataset, said so
+    let country = Scene::Country { country: &middle };
+    assert_eq!(
+        describe(&country, Lang::En),
+        [
+            "Middle",
+            "The capital is Middle City.",
+            "The area is 1,000 km².",
+            "The population is 1.00 M.",
+            "The currency is TST.",
+            "There is no GDP data.",
+            "Fun fact: Leży w środku.",
+        ]
+    );
+    assert_eq!(describe(&country, Lang::Pl)[5], "Brak danych o PKB.");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn center_panel_describes_each_level() {
+    let dir = atlas("accessible-tui");
+    let mut state = open(&dir);
+    assert!(state.accessible && !state.animations);
+    assert_eq!(state.theme.symbols().mark, ",");
+
+    let text = screen(&state);
+    assert!(text.contains("Opis mapy"), "{}", text);
+    assert!(text.contains("Świat: 1 kontynent, 4 kraje."), "{}", text);
+
+    enter(&mut state, "Testland");
+    let at = |state: &mut AppState, name: &str| state.selected = state.list_items.iter().position(|i| i == name).unwrap();
+    at(&mut state, "Middle");
+    let text = screen(&state);
+    assert!(text.contains("Middle: środkowa część kontynentu Testland; graniczy z: Northeast na północnym wschodzie,"), "{}", text);
+    assert!(text.contains("Southwest na południowym zachodzie."), "{}", text);
+    at(&mut state, "Apart");
+    let text = screen(&state);
+    assert!(text.contains("Apart: południowo-wschodnia część kontynentu Testland; nie graniczy z żadnym"), "{}", text);
+
+    enter(&mut state, "Middle");
+    let text = screen(&state);
+    for line in ["Stolicą jest Middle City.", "Walutą jest TST.", "PKB w 2010 roku wyniósł 11.00 mld USD.", "Ciekawostka: Leży w środku."] {
+        assert!(text.contains(line), "no {:?} in:\n{}", line, text);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn flag_and_config() {
+    let Ok(Command::Tui { accessible, .. }) = parse(["--accessible".to_string()]) else { panic!("not the TUI") };
+    assert!(accessible);
+    let Ok(Command::Tui { accessible, .. }) = parse(std::iter::empty()) else { panic!("not the TUI") };
+    assert!(!accessible);
+
+    let dir = atlas("accessible-config");
+    fs::write(dir.join("config.json"), r#"{"accessible": true}"#).unwrap();
+    assert!(AppState::open(&dir, false).unwrap().config.accessible);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,77 @@
+//! The active year shared by the map's year slider and the GDP chart cursor: picked on the
+//! choropleth timeline, taken by the chart of a country opened there, and kept when the
+//! chart is left
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Panel},
+};
+use std::fs;
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+/// Select `item` in the list and open it
+fn open(state: &mut AppState, item: &str) {
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    press(state, Action::Select);
+}
+
+#[test]
+fn chart_opens_on_the_timeline_year_and_leaves_it_there() {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Germany"])
+        .gdp("Poland", 1990..=2005, |year| f64::from(year - 1980) * 1e10)
+        .gdp("Germany", 1990..=2005, |year| f64::from(year - 1900) * 1e11)
+        .write("active-year")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+
+    // The timeline: the continent's choropleth, stepped back from the latest year to 1999
+    open(&mut state, "Europe");
+    press(&mut state, Action::Choropleth);
+    state.active_panel = Panel::Center;
+    assert_eq!(state.choropleth.as_ref().unwrap().year, None);
+    while state.active_year != Some(1999) {
+        press(&mut state, Action::Left);
+        assert!(state.active_year.unwrap() >= 1999);
+    }
+    assert_eq!(state.choropleth.as_ref().unwrap().year, Some(1999));
+
+    // Poland's chart opens with the cursor on 1999
+    open(&mut state, "Poland");
+    press(&mut state, Action::SwitchView);
+    assert!(state.gdp_chart_active);
+    assert_eq!(state.chart_cursor, Some(1999));
+
+    // Esc leaves the chart; the timeline is still at 1999
+    press(&mut state, Action::Back);
+    assert!(!state.gdp_chart_active);
+    assert_eq!(state.chart_cursor, None);
+    assert_eq!(state.active_year, Some(1999));
+    press(&mut state, Action::Back);
+    assert_eq!(state.list_items, ["Germany", "Poland"]);
+    // Not saved for the place, the choropleth is off again; shown, it is for 1999
+    assert!(state.choropleth.is_none());
+    press(&mut state, Action::Choropleth);
+    assert_eq!(state.choropleth.as_ref().unwrap().year, Some(1999));
+
+    // Moving the cursor moves the timeline with it
+    open(&mut state, "Poland");
+    press(&mut state, Action::SwitchView);
+    press(&mut state, Action::Right);
+    press(&mut state, Action::Right);
+    assert_eq!((state.chart_cursor, state.active_year), (Some(2001), Some(2001)));
+    press(&mut state, Action::Back);
+    assert_eq!(state.active_year, Some(2001));
+    let _ = fs::remove_dir_all(&dir);
+}
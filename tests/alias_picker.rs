@@ -0,0 +1,137 @@
+//! Picking the GDP name of a country the dataset doesn't know: the suggestions offered,
+//! the alias saved and the GDP found at once, a name another country already uses, and a
+//! save that fails leaving the aliases as they were
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    config::Severity,
+    keys::Action,
+    state::{AppState, GdpStatus},
+    suggest::{best_match, closest, similarity},
+};
+use std::{fs, path::Path};
+
+#[test]
+fn closest_names_first() {
+    assert_eq!(similarity("poland", "poland"), 1.0);
+    assert_eq!(similarity("", ""), 1.0);
+    assert_eq!(similarity("poland", ""), 0.0);
+    assert_eq!(similarity("abc", "xyz"), 0.0);
+    // A word of the name counts, so the longer official name wins over a near spelling
+    let names = ["Sweden", "Slovenia", "Slovak Republic", "Spain"];
+    assert_eq!(closest("Slovakia", names, 2), ["Slovak Republic", "Slovenia"]);
+    assert_eq!(closest("SLOVAKIA", names, 1), ["Slovak Republic"]);
+    assert_eq!(closest("Slovakia", names, 10).len(), 4);
+    assert!(closest("Slovakia", [], 3).is_empty());
+    // Both ways round, and only above the threshold
+    assert_eq!(best_match("Czech Republic", ["Chad", "Czechia"], 0.8), Some("Czechia"));
+    assert_eq!(best_match("Czech Republic", ["Chad", "Peru"], 0.8), None);
+}
+
+/// Testland's Alpha, Beta and Gamma, the dataset knowing Beta by its own name and Alpha
+/// only as "Alfa Republic", which no substring lookup finds
+fn open(name: &str) -> (AppState, std::path::PathBuf) {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .gdp("Alfa Republic", 2000..=2001, |_| 4e9)
+        .gdp("Beta", 2000..=2001, |_| 2e9)
+        .gdp("Zeta", 2000..=2001, |_| 1e9)
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    (state, dir)
+}
+
+fn enter(state: &mut AppState, country: &str) {
+    state.selected = state.list_items.iter().position(|c| c == country).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+}
+
+fn saved_aliases(dir: &Path) -> serde_json::Value {
+    serde_json::from_slice(&fs::read(dir.join("aliases.json")).unwrap()).unwrap()
+}
+
+#[test]
+fn picked_alias_is_saved_and_used() {
+    let (mut state, dir) = open("alias-pick");
+    let pick = state.keys.key(Action::PickAlias).unwrap();
+    // Nothing to pick for a country the dataset knows
+    enter(&mut state, "Beta");
+    state.handle_input(pick);
+    assert!(state.alias_picker.is_none());
+    state.handle_input(state.keys.key(Action::Back).unwrap());
+
+    enter(&mut state, "Alpha");
+    assert_eq!(state.gdp_status, GdpStatus::NotFound("Alpha".to_string()));
+    state.handle_input(pick);
+    let picker = state.alias_picker.as_ref().unwrap();
+    assert_eq!(picker.country, "Alpha");
+    assert_eq!(picker.options[0], "Alfa Republic");
+    assert_eq!(picker.options.len(), 3);
+    // The cursor stays on the list
+    let (up, down) = (state.keys.key(Action::Up).unwrap(), state.keys.key(Action::Down).unwrap());
+    state.handle_input(up);
+    assert_eq!(state.alias_picker.as_ref().unwrap().selected, 0);
+    for _ in 0..5 {
+        state.handle_input(down);
+    }
+    assert_eq!(state.alias_picker.as_ref().unwrap().selected, 2);
+
+    // Cancelled: nothing saved
+    let before = saved_aliases(&dir);
+    state.handle_input(state.keys.key(Action::Back).unwrap());
+    assert!(state.alias_picker.is_none());
+    assert_eq!(saved_aliases(&dir), before);
+
+    state.handle_input(pick);
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert!(state.alias_picker.is_none());
+    assert_eq!(saved_aliases(&dir)["alpha"], "Alfa Republic");
+    assert_eq!(state.cache.gdp_name("Alpha"), "Alfa Republic");
+    assert!(matches!(state.gdp_status, GdpStatus::Found { value, .. } if value == 4e9), "{:?}", state.gdp_status);
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.text, "Alias zapisany: Alpha → Alfa Republic");
+    assert_eq!(notification.severity, Severity::Info);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn name_already_used_is_saved_with_a_warning() {
+    let (mut state, dir) = open("alias-clash");
+    enter(&mut state, "Gamma");
+    state.handle_input(state.keys.key(Action::PickAlias).unwrap());
+    let beta = state.alias_picker.as_ref().unwrap().options.iter().position(|o| o == "Beta").unwrap();
+    state.alias_picker.as_mut().unwrap().selected = beta;
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert_eq!(saved_aliases(&dir)["gamma"], "Beta");
+    assert_eq!(state.notification.as_ref().unwrap().text, "Alias zapisany, ale \"Beta\" wskazuje już: Beta");
+    assert!(matches!(state.gdp_status, GdpStatus::Found { .. }));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn failed_save_changes_nothing() {
+    let (mut state, dir) = open("alias-unsaved");
+    // A directory with something in it where aliases.json would go can't be replaced
+    let aliases = dir.join("aliases.json");
+    fs::remove_file(&aliases).unwrap();
+    fs::create_dir_all(aliases.join("inside")).unwrap();
+    enter(&mut state, "Alpha");
+    state.handle_input(state.keys.key(Action::PickAlias).unwrap());
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("Nie zapisano aliases.json: "), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    assert_eq!(state.cache.gdp_name("Alpha"), "Alpha");
+    assert_eq!(state.gdp_status, GdpStatus::NotFound("Alpha".to_string()));
+    assert!(aliases.is_dir());
+    let _ = fs::remove_dir_all(&dir);
+}
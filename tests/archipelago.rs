@@ -0,0 +1,89 @@
+//! Islands kept or dropped as specks: a mainland's far smaller islands dropped, every
+//! island of an archipelago (no polygon over 40% of the area) kept, `keep_islands` in
+//! `render_hints.json` deciding either way, the same whether the map is parsed or streamed
+mod common;
+
+use common::{collection, multi_feature, FixtureAtlas};
+use geo::{MultiPolygon, Polygon};
+use rustatlas::{
+    data::{DataCache, RenderHints},
+    map_draw::{largest_share, MapView, OutlierPolicy},
+};
+use std::{collections::BTreeMap, fs};
+
+/// Closed ring of a square of `size` degrees with its south-west corner at `x`, `y`
+fn square(x: f64, y: f64, size: f64) -> Vec<Vec<f64>> {
+    vec![vec![x, y], vec![x + size, y], vec![x + size, y + size], vec![x, y + size], vec![x, y]]
+}
+
+fn polygons(rings: &[Vec<Vec<f64>>]) -> MultiPolygon<f64> {
+    MultiPolygon(
+        rings
+            .iter()
+            .map(|ring| Polygon::new(ring.iter().map(|p| (p[0], p[1])).collect::<Vec<_>>().into(), vec![]))
+            .collect(),
+    )
+}
+
+/// Mainland: one 10° square and a 1° speck east of it
+fn mainland() -> Vec<Vec<Vec<f64>>> {
+    vec![square(0.0, 0.0, 10.0), square(20.0, 0.0, 1.0)]
+}
+
+/// Archipelago: five 3° islands in a row and a 1° one at the end, under a fifth of the others
+fn archipelago() -> Vec<Vec<Vec<f64>>> {
+    let mut islands: Vec<_> = (0..5).map(|i| square(5.0 * i as f64, 30.0, 3.0)).collect();
+    islands.push(square(30.0, 30.0, 1.0));
+    islands
+}
+
+#[test]
+fn share_of_the_largest_polygon() {
+    assert_eq!(largest_share(&polygons(&[square(0.0, 0.0, 2.0)])), 1.0);
+    assert!((largest_share(&polygons(&mainland())) - 100.0 / 101.0).abs() < 1e-9);
+    assert!((largest_share(&polygons(&archipelago())) - 9.0 / 46.0).abs() < 1e-9);
+    assert!((largest_share(&polygons(&[square(0.0, 0.0, 1.0), square(5.0, 0.0, 1.0)])) - 0.5).abs() < 1e-9);
+    assert_eq!(largest_share(&MultiPolygon(vec![])), 1.0);
+}
+
+/// Whether the 1° speck of Mainland and of Isles are on a map built with `hints`, parsed
+/// and streamed
+fn specks_kept(hints: &RenderHints, name: &str) -> [(bool, bool); 2] {
+    let features = || collection(vec![multi_feature("Mainland", mainland()), multi_feature("Isles", archipelago())]);
+    let parsed = MapView::build(features(), BTreeMap::new(), hints, OutlierPolicy::default()).unwrap();
+    let path = std::env::temp_dir().join(format!("rustatlas-{}.geojson", name));
+    fs::write(&path, features().to_string()).unwrap();
+    let streamed = MapView::build_streamed(&path, BTreeMap::new(), hints, OutlierPolicy::default()).unwrap();
+    let _ = fs::remove_file(&path);
+    [parsed, streamed].map(|view| {
+        // Mainland itself and the archipelago's larger islands are there either way
+        assert_eq!(view.feature_at(5.0, 5.0), Some("Mainland"));
+        assert_eq!(view.feature_at(21.5, 31.5), Some("Isles"));
+        (view.feature_at(20.5, 0.5) == Some("Mainland"), view.feature_at(30.5, 30.5) == Some("Isles"))
+    })
+}
+
+#[test]
+fn specks_dropped_islands_kept() {
+    assert_eq!(specks_kept(&RenderHints::default(), "islands-default"), [(false, true); 2]);
+
+    // Overridden both ways, by any spelling of the name
+    let hints: RenderHints = serde_json::from_str(r#"{"keep_islands": {"mainland": true, "ISLES": false}}"#).unwrap();
+    assert_eq!((hints.keep_islands("Mainland"), hints.keep_islands("Isles"), hints.keep_islands("Other")), (Some(true), Some(false), None));
+    assert_eq!(specks_kept(&hints, "islands-hinted"), [(true, false); 2]);
+}
+
+#[test]
+fn hints_read_from_the_data_directory() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Mainland"]).write("islands-hints-file").unwrap().dir;
+    let features = || collection(vec![multi_feature("Mainland", mainland())]);
+    let mut cache = DataCache::new(&dir).unwrap();
+    assert!(cache.render_hints().keep_islands.is_empty());
+    assert_eq!(MapView::new(features(), &mut cache).unwrap().feature_at(20.5, 0.5), None);
+
+    fs::write(dir.join("render_hints.json"), r#"{"keep_islands": {"Mainland": true}}"#).unwrap();
+    let mut cache = DataCache::new(&dir).unwrap();
+    assert_eq!(cache.render_hints().keep_islands("Mainland"), Some(true));
+    assert_eq!(MapView::new(features(), &mut cache).unwrap().feature_at(20.5, 0.5), Some("Mainland"));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,151 @@
+//! The ASCII fallback: picked from the locale or the `unicode` setting, every glyph the UI
+//! draws itself from the ASCII set, and whatever else lands on screen (Polish letters,
+//! chart axes, braille) folded to ASCII, so no view leaves a multi-byte cell
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Style, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Panel},
+    theme::{ascii_char, fold_to_ascii, utf8_locale},
+    ui,
+};
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+#[test]
+fn locale_decides_first_set_wins() {
+    let env = |pairs: &[(&str, &str)]| {
+        let vars: BTreeMap<String, String> = pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        utf8_locale(move |name| vars.get(name).cloned())
+    };
+    assert!(env(&[]));
+    assert!(env(&[("LANG", "pl_PL.UTF-8")]));
+    assert!(env(&[("LANG", "en_US.utf8")]));
+    assert!(!env(&[("LANG", "C")]));
+    assert!(!env(&[("LANG", "pl_PL.ISO-8859-2")]));
+    // LC_ALL over LC_CTYPE over LANG; empty ones are skipped
+    assert!(!env(&[("LC_ALL", "POSIX"), ("LANG", "pl_PL.UTF-8")]));
+    assert!(env(&[("LC_CTYPE", "C.UTF-8"), ("LANG", "C")]));
+    assert!(env(&[("LC_ALL", ""), ("LANG", "pl_PL.UTF-8")]));
+}
+
+#[test]
+fn characters_fold_to_their_stand_ins() {
+    for (c, ascii) in [
+        ('a', 'a'),
+        ('─', '-'),
+        ('│', '|'),
+        ('┌', '+'),
+        ('⣿', '*'),
+        ('\u{2800}', ' '),
+        ('█', '#'),
+        ('▲', '^'),
+        ('▼', 'v'),
+        ('→', '>'),
+        ('●', 'o'),
+        ('…', '.'),
+        ('–', '-'),
+        ('\u{a0}', ' '),
+        ('²', '2'),
+        ('ł', 'l'),
+        ('Ł', 'L'),
+        ('ż', 'z'),
+        ('Ś', 'S'),
+        ('ę', 'e'),
+        ('日', '?'),
+    ] {
+        assert_eq!(ascii_char(c), ascii, "{}", c);
+    }
+
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 2));
+    buffer.set_string(0, 0, "Łódź ▸ 5 km²", Style::default());
+    buffer.set_string(0, 1, "┌日┐", Style::default());
+    fold_to_ascii(&mut buffer);
+    let rows: Vec<String> = (0..2).map(|y| (0..12).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+    assert_eq!(rows[0], "Lodz > 5 km2");
+    // The wide character's second cell is left as the blank it was
+    assert!(rows[1].starts_with("+?"), "{:?}", rows[1]);
+    assert!(rows.iter().all(|r| r.is_ascii()));
+}
+
+fn open(name: &str, unicode: bool) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Łódź", "Beta"])
+        .country("Łódź", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .facts("Łódź", &["Zażółć gęślą jaźń – to zdanie ma wszystkie polskie litery."])
+        .gdp("Łódź", 2000..=2010, |y| f64::from(y - 1990) * 1e9)
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("config.json"), format!(r#"{{"unicode": {}}}"#, unicode)).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+fn enter(state: &mut AppState, place: &str) {
+    state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn screen(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 36)).unwrap();
+    terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..36).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn every_view_is_plain_ascii() {
+    let (dir, mut state) = open("ascii-views", false);
+    assert!(state.theme.symbols().ascii);
+    let mut views = vec![("world", screen(&state))];
+    enter(&mut state, "Testland");
+    views.push(("continent", screen(&state)));
+    state.handle_input(state.keys.key(Action::Ranking).unwrap());
+    views.push(("ranking", screen(&state)));
+    state.handle_input(state.keys.key(Action::Ranking).unwrap());
+    state.handle_input(state.keys.key(Action::Choropleth).unwrap());
+    views.push(("choropleth", screen(&state)));
+    enter(&mut state, "Łódź");
+    views.push(("country", screen(&state)));
+    state.handle_input(state.keys.key(Action::SwitchView).unwrap());
+    views.push(("GDP chart", screen(&state)));
+    state.show_help = true;
+    views.push(("help", screen(&state)));
+    state.show_help = false;
+    state.show_diagnostics = true;
+    views.push(("diagnostics", screen(&state)));
+
+    for (name, text) in &views {
+        assert!(text.is_ascii(), "{} has non-ASCII cells:\n{}", name, text);
+    }
+    let country = &views[4].1;
+    // ASCII borders and breadcrumb separator; the Polish letters without accents
+    assert!(country.starts_with("World > Testland > Lodz"), "{}", country);
+    assert!(country.contains("+-") && country.contains('|'), "{}", country);
+    assert!(country.contains("Zazolc gesla jazn"), "{}", country);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unicode_setting_overrides_the_locale() {
+    let (dir, mut state) = open("ascii-unicode", true);
+    assert!(!state.theme.symbols().ascii);
+    enter(&mut state, "Testland");
+    enter(&mut state, "Łódź");
+    let text = screen(&state);
+    assert!(text.starts_with("World ▸ Testland ▸ Łódź"), "{}", text);
+    assert!(text.contains('┌') && text.contains("Zażółć"), "{}", text);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,170 @@
+//! Attract mode: when idling starts it, cycling random countries on the tick, and any
+//! key or mouse input putting the user back exactly where they were
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Location, MouseInput, Panel},
+};
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+fn atlas(name: &str, config: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Northland", ["Gamma", "Delta", "Epsilon"])
+        .facts("Alpha", &["First.", "Second.", "Third."])
+        .write(name)
+        .unwrap()
+        .dir;
+    std::fs::write(dir.join("config.json"), config).unwrap();
+    dir
+}
+
+fn state(dir: &PathBuf) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state
+}
+
+fn wait_for_map(state: &mut AppState) {
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn open(state: &mut AppState, item: &str) {
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    wait_for_map(state);
+}
+
+/// Tick `secs` seconds from now, as if nothing had been pressed since
+fn tick_after(state: &mut AppState, secs: u64) {
+    state.tick(Instant::now() + Duration::from_secs(secs));
+    wait_for_map(state);
+}
+
+/// The user on Alpha, with the second fact in focus and the right panel active
+fn on_alpha(dir: &PathBuf) -> AppState {
+    let mut state = state(dir);
+    open(&mut state, "Testland");
+    open(&mut state, "Alpha");
+    state.fact_focus = 1;
+    state.active_panel = Panel::Right;
+    state
+}
+
+/// Places in the navigation history and the one current
+fn history(state: &AppState) -> (Vec<Location>, usize) {
+    let (entries, current) = state.nav.entries();
+    (entries.to_vec(), current)
+}
+
+fn alpha() -> Location {
+    Location::Country { continent: "Testland".to_string(), country: "Alpha".to_string() }
+}
+
+#[test]
+fn starts_only_after_the_idle_time() {
+    let dir = atlas("attract-idle", r#"{"attract_after_secs": 120}"#);
+    let mut state = on_alpha(&dir);
+    tick_after(&mut state, 100);
+    assert!(state.attract.is_none());
+    assert_eq!(state.location, alpha());
+    tick_after(&mut state, 121);
+    assert!(state.attract.is_some());
+    assert!(state.view_model().attract);
+    assert!(matches!(&state.location, Location::Country { .. }));
+    assert_ne!(state.location, alpha());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn off_by_default_and_held_off_by_an_overlay() {
+    let dir = atlas("attract-off", "{}");
+    let mut state = on_alpha(&dir);
+    tick_after(&mut state, 100_000);
+    assert!(state.attract.is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let dir = atlas("attract-overlay", r#"{"attract_after_secs": 120}"#);
+    let mut state = on_alpha(&dir);
+    state.show_help = true;
+    tick_after(&mut state, 121);
+    assert!(state.attract.is_none());
+    state.show_help = false;
+    tick_after(&mut state, 121);
+    assert!(state.attract.is_some());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn moves_on_after_lingering() {
+    let dir = atlas("attract-cycle", r#"{"attract_after_secs": 120}"#);
+    let mut state = on_alpha(&dir);
+    let before = history(&state);
+    tick_after(&mut state, 120);
+    let first = state.location.clone();
+    // Still lingering on the same country
+    tick_after(&mut state, 130);
+    assert_eq!(state.location, first);
+    tick_after(&mut state, 136);
+    assert_ne!(state.location, first);
+    assert!(matches!(&state.location, Location::Country { .. }));
+    // Random countries are not steps the user can undo
+    assert_eq!(history(&state), before);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_key_restores_the_place_and_does_nothing_else() {
+    let dir = atlas("attract-key", r#"{"attract_after_secs": 120}"#);
+    let mut state = on_alpha(&dir);
+    let (before, visited) = (history(&state), state.visited.clone());
+    for secs in [120, 136, 152] {
+        tick_after(&mut state, secs);
+    }
+    assert!(state.attract.is_some());
+    // Saved as where the user is, not where attract mode is
+    state.save_session().unwrap();
+    let saved = std::fs::read_to_string(dir.join("session.json")).unwrap();
+    assert!(saved.contains("\"Alpha\""), "{}", saved);
+
+    // Back would leave Alpha for Testland, had it not only ended attract mode
+    state.handle_input(state.keys.key(Action::Back).unwrap());
+    wait_for_map(&mut state);
+    assert!(state.attract.is_none());
+    assert_eq!(state.location, alpha());
+    assert_eq!(state.list_items[state.selected], "Alpha");
+    assert_eq!(state.active_panel, Panel::Right);
+    assert_eq!(state.fact_focus, 1);
+    assert_eq!(history(&state), before);
+    assert_eq!(state.visited, visited);
+
+    // Idling counts again from that key
+    state.tick(Instant::now() + Duration::from_secs(60));
+    assert!(state.attract.is_none());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_mouse_interrupts_too() {
+    let dir = atlas("attract-mouse", r#"{"attract_after_secs": 120}"#);
+    let mut state = on_alpha(&dir);
+    tick_after(&mut state, 120);
+    assert!(state.attract.is_some());
+    state.handle_mouse(MouseInput::Move { column: 50, row: 10 });
+    wait_for_map(&mut state);
+    assert!(state.attract.is_none());
+    assert_eq!(state.location, alpha());
+    assert_eq!(state.fact_focus, 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
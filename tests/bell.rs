@@ -0,0 +1,121 @@
+//! The bell on notifications: which ones ring under each `bell` setting, the driver
+//! sounding it once per ring, and `visual_bell` inverting the status bar for a moment
+//! instead
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, style::Modifier, Terminal};
+use rustatlas::{
+    config::{Bell, Config, Severity},
+    keys::Action,
+    platform::{run, HeadlessDriver},
+    state::{AppState, Panel},
+    ui,
+};
+use std::{fs, path::PathBuf, time::{Duration, Instant}};
+
+fn open(name: &str, config: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("config.json"), config).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+#[test]
+fn policy_by_severity() {
+    for (bell, info, error) in [(Bell::Errors, false, true), (Bell::All, true, true), (Bell::Never, false, false)] {
+        assert_eq!((bell.rings(Severity::Info), bell.rings(Severity::Error)), (info, error), "{:?}", bell);
+    }
+    assert_eq!(Config::default().bell, Bell::Never);
+    assert!(!Config::default().visual_bell);
+    let config = Config::parse(br#"{"bell": "errors", "visual_bell": true}"#).unwrap();
+    assert_eq!((config.bell, config.visual_bell), (Bell::Errors, true));
+    assert_eq!(Config::parse(br#"{"bell": "all"}"#).unwrap().bell, Bell::All);
+    assert!(Config::parse(br#"{"bell": "loud"}"#).is_err());
+}
+
+#[test]
+fn rung_once_per_notification_that_asks() {
+    let (dir, mut state) = open("bell-errors", r#"{"bell": "errors"}"#);
+    state.notify("Zapisano");
+    assert_eq!(state.notification.as_ref().unwrap().severity, Severity::Info);
+    assert!(!state.take_bell());
+    state.notify_error("Nie można");
+    assert_eq!(state.notification.as_ref().unwrap().severity, Severity::Error);
+    assert!(state.take_bell());
+    assert!(!state.take_bell());
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, mut state) = open("bell-all", r#"{"bell": "all"}"#);
+    state.notify("Zapisano");
+    assert!(state.take_bell());
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, mut state) = open("bell-never", "{}");
+    state.notify_error("Nie można");
+    assert!(!state.take_bell() && state.flash_until.is_none());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+/// Into Testland and Alpha, then Enter on the lowest level, and a search matching nothing
+fn failing_script(state: &AppState) -> Vec<KeyCode> {
+    let key = |action| state.keys.key(action).unwrap();
+    let testland = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    let mut script: Vec<KeyCode> = std::iter::repeat_n(key(Action::Down), testland).collect();
+    script.extend([key(Action::Select), key(Action::Select), key(Action::Select), key(Action::Back)]);
+    script.extend([key(Action::Filter), KeyCode::Char('a'), KeyCode::Char('x')]);
+    script
+}
+
+#[test]
+fn driver_sounds_failed_actions() {
+    let (dir, mut state) = open("bell-driver", r#"{"bell": "errors"}"#);
+    let mut driver = HeadlessDriver::new(100, 30, failing_script(&state));
+    run(&mut driver, &mut state).unwrap();
+    // Enter on Alpha itself, then "ax" leaving no match where "a" had some
+    assert_eq!(state.notification.as_ref().unwrap().text, "Brak dopasowań: ax");
+    assert_eq!(driver.bells(), 2);
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, mut state) = open("bell-driver-never", "{}");
+    let mut driver = HeadlessDriver::new(100, 30, failing_script(&state));
+    run(&mut driver, &mut state).unwrap();
+    assert_eq!(state.notification.as_ref().unwrap().severity, Severity::Error);
+    assert_eq!(driver.bells(), 0);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn visual_bell_inverts_the_status_bar() {
+    let (dir, mut state) = open("bell-visual", r#"{"bell": "errors", "visual_bell": true}"#);
+    let status_reversed = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..100).all(|x| buffer[(x, 29)].modifier.contains(Modifier::REVERSED))
+    };
+    assert!(!status_reversed(&state));
+    let before = Instant::now();
+    state.notify_error("Nie można");
+    // Flashed, not sounded
+    assert!(!state.take_bell());
+    let until = state.flash_until.unwrap();
+    assert!(until >= before + Duration::from_millis(200) && until <= Instant::now() + Duration::from_millis(200));
+    assert!(status_reversed(&state));
+    state.tick(until);
+    assert!(state.flash_until.is_none());
+    assert!(!status_reversed(&state));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,124 @@
+//! The breadcrumb over the panels: each segment's cells recorded while drawing, a click
+//! on a place above the current one going up to it, the pointer over one underlining it,
+//! and the mouse ignored there while an overlay is open
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Modifier, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Location, MouseInput, Panel},
+    ui,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+fn open(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    (dir, state)
+}
+
+/// Draw a frame and take in where things went, as the event loop does
+fn draw(state: &mut AppState) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    state.apply_drawn(drawn.unwrap());
+    terminal.backend().buffer().clone()
+}
+
+/// Text of the cells `rect` covers on the top line
+fn segment(buffer: &Buffer, state: &AppState, i: usize) -> String {
+    let rect = state.breadcrumb[i].1;
+    (rect.left()..rect.right()).map(|x| buffer[(x, rect.y)].symbol()).collect()
+}
+
+fn country() -> Location {
+    Location::Country { continent: "Testland".to_string(), country: "Alpha".to_string() }
+}
+
+#[test]
+fn segments_recorded_where_drawn() {
+    let (dir, mut state) = open("breadcrumb-rects");
+    let buffer = draw(&mut state);
+    let places: Vec<&Location> = state.breadcrumb.iter().map(|(loc, _)| loc).collect();
+    assert_eq!(places, [&Location::World, &Location::Continent("Testland".to_string()), &country()]);
+    assert!(state.breadcrumb.iter().all(|(_, rect)| rect.y == 0 && rect.height == 1));
+    assert_eq!(segment(&buffer, &state, 1), "Testland");
+    assert_eq!(segment(&buffer, &state, 2), "Alpha");
+    // Left to right, a separator between each
+    let rects: Vec<_> = state.breadcrumb.iter().map(|(_, r)| r).collect();
+    assert!(rects.windows(2).all(|w| w[0].right() < w[1].left()), "{:?}", rects);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn hover_underlines_and_click_goes_up() {
+    let (dir, mut state) = open("breadcrumb-click");
+    draw(&mut state);
+    let continent = state.breadcrumb[1].1;
+
+    // Over the continent: underlined in the next frame; off it again: plain
+    state.handle_mouse(MouseInput::Move { column: continent.x + 2, row: 0 });
+    assert_eq!(state.breadcrumb_hover, Some(1));
+    let buffer = draw(&mut state);
+    assert!(buffer[(continent.x, 0)].modifier.contains(Modifier::UNDERLINED));
+    state.handle_mouse(MouseInput::Move { column: continent.right() + 40, row: 0 });
+    assert_eq!(state.breadcrumb_hover, None);
+    let buffer = draw(&mut state);
+    assert!(!buffer[(continent.x, 0)].modifier.contains(Modifier::UNDERLINED));
+
+    // The current place isn't a way up; clicking it changes nothing
+    let current = state.breadcrumb[2].1;
+    state.handle_mouse(MouseInput::Click { column: current.x, row: 0 });
+    assert_eq!(state.location, country());
+
+    // The continent's last cell is still on it
+    state.handle_mouse(MouseInput::Click { column: continent.right() - 1, row: 0 });
+    assert_eq!(state.location, Location::Continent("Testland".to_string()));
+    draw(&mut state);
+    let world = state.breadcrumb[0].1;
+    state.handle_mouse(MouseInput::Click { column: world.x, row: 0 });
+    assert_eq!(state.location, Location::World);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn ignored_under_an_overlay() {
+    let (dir, mut state) = open("breadcrumb-overlay");
+    draw(&mut state);
+    let world = state.breadcrumb[0].1;
+    state.show_help = true;
+    state.handle_mouse(MouseInput::Move { column: world.x, row: 0 });
+    assert_eq!(state.breadcrumb_hover, None);
+    state.handle_mouse(MouseInput::Click { column: world.x, row: 0 });
+    assert_eq!(state.location, country());
+
+    // go_up_to itself only goes to places above
+    state.show_help = false;
+    assert!(!state.go_up_to(country()));
+    assert!(!state.go_up_to(Location::Continent("Elsewhere".to_string())));
+    assert_eq!(state.location, country());
+    assert!(state.go_up_to(Location::World));
+    assert_eq!(state.location, Location::World);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,39 @@
+//! `check-data` on data directories written by `FixtureAtlas`
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use rustatlas::check;
+
+/// Two continents whose every country has a shape and a GDP row, one of them by alias
+fn complete() -> FixtureAtlas {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Northland", ["Delta", "Epsilon"])
+        .country("Alpha", square_at(10.0, 50.0))
+        .facts("Alpha", &["Alpha is square."])
+        .alias("Beta", "Beta Republic")
+        .gdp("Alpha", 2000..=2020, |y| 1e9 * f64::from(y - 1999))
+        .gdp("Beta Republic", 1990..=2010, |y| 5e8 + f64::from(y))
+        .gdp("Gamma", 1960..=2024, |_| 2e9)
+        .gdp("Delta", 2010..=2011, |_| 3e9)
+        .gdp("Epsilon", 1980..=1980, |_| 4e9)
+}
+
+#[test]
+fn generated_atlas_is_clean() {
+    let fixture = complete().write("check-clean").unwrap();
+    let report = check::report(&fixture.dir).unwrap();
+    assert_eq!(report.problems, 0, "report:\n{}", report.text);
+    assert!(report.text.contains("0 of 5 map countries have no GDP data"), "report:\n{}", report.text);
+    assert!(report.text.contains("0 list entries without a map file or alias"), "report:\n{}", report.text);
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn country_without_gdp_is_reported() {
+    let fixture = complete().continent("Southland", ["Eta"]).write("check-missing").unwrap();
+    let report = check::report(&fixture.dir).unwrap();
+    assert_eq!(report.problems, 1, "report:\n{}", report.text);
+    assert!(report.text.contains("1 of 6 map countries have no GDP data\n  Eta\n"), "report:\n{}", report.text);
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
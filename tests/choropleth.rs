@@ -0,0 +1,130 @@
+//! The GDP choropleth: countries split into equal-count buckets, smallest first, with the
+//! missing ones counted; and its legend taking the keys while focused, drawing the picked
+//! bucket's countries in the highlight color in place of the selection
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    gdp_reader::YearValue,
+    keys::Action,
+    state::{AppState, Bucket, Choropleth, Panel},
+    ui,
+};
+use std::fs;
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn equal_count_buckets_smallest_first() {
+    let members = names(&["A", "B", "C", "D", "E", "F", "G", "H"]);
+    let value = |name: &str| match name {
+        "H" => None,
+        // Gap-filled: bucketed like the rest, but remembered
+        "D" => Some(YearValue::Interpolated(4.0)),
+        _ => Some(YearValue::Reported((name.as_bytes()[0] - b'A' + 1) as f64)),
+    };
+    let choropleth = Choropleth::assemble(&members, 3, value);
+    assert_eq!(
+        choropleth.buckets,
+        [
+            Bucket { lo: 1.0, hi: 3.0, count: 3 },
+            Bucket { lo: 4.0, hi: 5.0, count: 2 },
+            Bucket { lo: 6.0, hi: 7.0, count: 2 },
+        ]
+    );
+    assert_eq!(choropleth.missing, 1);
+    assert_eq!(choropleth.interpolated.iter().collect::<Vec<_>>(), ["d"]);
+    assert_eq!(choropleth.members(1).into_iter().collect::<Vec<_>>(), ["d", "e"]);
+    assert!(!choropleth.assignment.contains_key("h"));
+    assert!(choropleth.members(3).is_empty());
+
+    // Fewer countries than buckets: one each; equal values in name order
+    let choropleth = Choropleth::assemble(&names(&["B", "A"]), 6, |_| Some(YearValue::Reported(5.0)));
+    assert_eq!(choropleth.buckets.len(), 2);
+    assert_eq!(choropleth.assignment["a"], 0);
+    assert_eq!(choropleth.assignment["b"], 1);
+    // None with GDP: no buckets at all
+    let choropleth = Choropleth::assemble(&names(&["A"]), 6, |_| None);
+    assert!(choropleth.buckets.is_empty());
+    assert_eq!(choropleth.missing, 1);
+}
+
+#[test]
+fn focused_legend_highlights_a_bucket() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .country("Gamma", square_at(10.0, 0.0))
+        .gdp("Alpha", 2000..=2001, |_| 1e9)
+        .gdp("Beta", 2000..=2001, |_| 2e9)
+        .gdp("Gamma", 2000..=2001, |_| 3e9)
+        .write("choropleth-legend")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    let key = |state: &AppState, action| state.keys.key(action).unwrap();
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(key(&state, Action::Select));
+    state.selected = state.list_items.iter().position(|c| c == "Gamma").unwrap();
+
+    // No legend to focus without the choropleth
+    state.handle_input(key(&state, Action::Legend));
+    assert_eq!(state.legend_focus, None);
+    state.handle_input(key(&state, Action::Choropleth));
+    assert_eq!(state.choropleth.as_ref().unwrap().buckets.len(), 3);
+    state.handle_input(key(&state, Action::Legend));
+    assert_eq!(state.legend_focus, Some(0));
+
+    // Columns of the map above the legend holding the highlight color, and the screen's text
+    let draw = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut inner = None;
+        terminal.draw(|f| inner = ui::draw(f, &state.view_model()).map_area.map(|a| a.inner)).unwrap();
+        let (buffer, inner) = (terminal.backend().buffer().clone(), inner.unwrap());
+        let red: Vec<u16> = (inner.left()..inner.right())
+            .filter(|&x| (inner.top()..inner.bottom() - 6).any(|y| buffer[(x, y)].fg == state.map_style.highlight))
+            .collect();
+        let text: String = (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect();
+        (red, inner.left() + inner.width / 2, text)
+    };
+    // Alpha, in the smallest bucket, on the left in place of the selected Gamma
+    let (red, middle, text) = draw(&state);
+    assert!(!red.is_empty() && red.iter().all(|&x| x < middle), "{:?}", red);
+    assert!(text.contains("GDP (ostatni rok)"), "{}", text);
+    assert!(text.contains("zamknij"), "{}", text);
+
+    // The legend takes Up and Down, kept within its buckets, and leaves the list alone
+    let selected = state.selected;
+    for _ in 0..5 {
+        state.handle_input(key(&state, Action::Down));
+    }
+    assert_eq!(state.legend_focus, Some(2));
+    assert_eq!(state.selected, selected);
+    let (red, middle, _) = draw(&state);
+    assert!(!red.is_empty() && red.iter().all(|&x| x > middle), "{:?}", red);
+    state.handle_input(key(&state, Action::Up));
+    state.handle_input(key(&state, Action::Select));
+    assert_eq!(state.legend_focus, Some(1));
+    assert_eq!(state.level(), rustatlas::data::GeoLevel::Continent);
+
+    // Left with Esc, the selection highlighted again; k again focuses the first bucket
+    state.handle_input(key(&state, Action::Back));
+    assert_eq!(state.legend_focus, None);
+    assert_eq!(state.level(), rustatlas::data::GeoLevel::Continent);
+    let (_, _, text) = draw(&state);
+    assert!(text.contains("wybierz przedział"), "{}", text);
+    state.handle_input(key(&state, Action::Legend));
+    assert_eq!(state.legend_focus, Some(0));
+    state.handle_input(key(&state, Action::Legend));
+    assert_eq!(state.legend_focus, None);
+    let _ = fs::remove_dir_all(&dir);
+}
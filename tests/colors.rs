@@ -0,0 +1,119 @@
+//! Colors for what the terminal can show: the capability guessed from the environment or
+//! given with `--colors`, exact on true color terminals, the nearest palette entry on 256
+//! and 16, and choropleth buckets and marks that stay apart on every one
+use ratatui::style::Color;
+use rustatlas::theme::{
+    nearest_ansi16, nearest_ansi256, ColorSupport, Theme, CHOROPLETH, MARK_COLORS, MUTED,
+};
+use std::collections::HashSet;
+
+#[test]
+fn support_from_the_environment() {
+    // (COLORTERM, TERM, detected)
+    let table = [
+        (Some("truecolor"), Some("xterm"), ColorSupport::TrueColor),
+        (Some("24bit"), None, ColorSupport::TrueColor),
+        (Some("TrueColor"), None, ColorSupport::TrueColor),
+        (None, Some("xterm-direct"), ColorSupport::TrueColor),
+        (None, Some("xterm-256color"), ColorSupport::Ansi256),
+        (Some("yes"), Some("screen-256color"), ColorSupport::Ansi256),
+        (None, Some("xterm"), ColorSupport::Ansi16),
+        (None, Some("linux"), ColorSupport::Ansi16),
+        (None, None, ColorSupport::Ansi16),
+    ];
+    for (colorterm, term, expected) in table {
+        let detected = ColorSupport::detect(|name| match name {
+            "COLORTERM" => colorterm.map(String::from),
+            "TERM" => term.map(String::from),
+            _ => None,
+        });
+        assert_eq!(detected, expected, "COLORTERM={:?} TERM={:?}", colorterm, term);
+    }
+}
+
+#[test]
+fn support_from_the_command_line() {
+    assert_eq!(ColorSupport::parse("16"), Some(ColorSupport::Ansi16));
+    assert_eq!(ColorSupport::parse("256"), Some(ColorSupport::Ansi256));
+    assert_eq!(ColorSupport::parse("true"), Some(ColorSupport::TrueColor));
+    for bad in ["", "8", "truecolor", "TRUE"] {
+        assert_eq!(ColorSupport::parse(bad), None, "{:?}", bad);
+    }
+}
+
+#[test]
+fn nearest_of_sixteen() {
+    // Palette colors map to themselves
+    let table = [
+        ((0, 0, 0), Color::Black),
+        ((205, 0, 0), Color::Red),
+        ((255, 0, 0), Color::LightRed),
+        ((127, 127, 127), Color::DarkGray),
+        ((229, 229, 229), Color::Gray),
+        ((255, 255, 255), Color::White),
+        ((92, 92, 255), Color::LightBlue),
+        // And colors in between to the closest
+        ((10, 5, 0), Color::Black),
+        ((200, 30, 20), Color::Red),
+        ((140, 130, 120), Color::DarkGray),
+        ((250, 250, 240), Color::White),
+        ((0, 180, 190), Color::Cyan),
+    ];
+    for (rgb, expected) in table {
+        assert_eq!(nearest_ansi16(rgb), expected, "{:?}", rgb);
+    }
+}
+
+#[test]
+fn nearest_of_256() {
+    let table = [
+        // Cube corners and levels: 16 + 36r + 6g + b
+        ((0, 0, 0), 16),
+        ((255, 255, 255), 231),
+        ((255, 0, 0), 196),
+        ((95, 135, 175), 67),
+        // Between levels, to the closer one
+        ((100, 130, 180), 67),
+        ((150, 0, 0), 88),
+        // Grays closer to the ramp than to the cube's grays
+        ((128, 128, 128), 244),
+        ((8, 8, 8), 232),
+        ((238, 238, 238), 255),
+        ((120, 125, 130), 243),
+        // The cube's own grays stay in the cube
+        ((95, 95, 95), 59),
+    ];
+    for (rgb, expected) in table {
+        assert_eq!(nearest_ansi256(rgb), Color::Indexed(expected), "{:?}", rgb);
+    }
+}
+
+#[test]
+fn theme_resolves_for_its_support() {
+    let rgb = (31, 119, 180);
+    assert_eq!(Theme::new(ColorSupport::TrueColor).color(rgb), Color::Rgb(31, 119, 180));
+    assert_eq!(Theme::new(ColorSupport::Ansi256).color(rgb), nearest_ansi256(rgb));
+    assert_eq!(Theme::new(ColorSupport::Ansi16).color(rgb), nearest_ansi16(rgb));
+    assert_eq!(Theme::new(ColorSupport::Ansi16).color(MUTED), Color::DarkGray);
+}
+
+#[test]
+fn buckets_and_marks_stay_apart() {
+    for support in [ColorSupport::TrueColor, ColorSupport::Ansi256, ColorSupport::Ansi16] {
+        let theme = Theme::new(support);
+        let buckets: Vec<Color> = (0..CHOROPLETH.len()).map(|i| theme.bucket(i)).collect();
+        assert_eq!(buckets.iter().collect::<HashSet<_>>().len(), buckets.len(), "{:?}: {:?}", support, buckets);
+        // Past the last bucket: the last
+        assert_eq!(theme.bucket(CHOROPLETH.len() + 3), buckets[buckets.len() - 1]);
+        let marks: Vec<Color> = (0..MARK_COLORS.len()).map(|i| theme.mark(i)).collect();
+        assert_eq!(marks.iter().collect::<HashSet<_>>().len(), marks.len(), "{:?}: {:?}", support, marks);
+        // None of them red, the selection's color
+        assert!(!marks.contains(&Color::Red) && !marks.contains(&Color::LightRed), "{:?}", support);
+        assert_eq!(theme.mark(MARK_COLORS.len()), marks[0]);
+    }
+    // Interpolated buckets are dimmer where there are shades to spare
+    let true_color = Theme::new(ColorSupport::TrueColor);
+    assert_ne!(true_color.bucket_interpolated(2), true_color.bucket(2));
+    let sixteen = Theme::new(ColorSupport::Ansi16);
+    assert_eq!(sixteen.bucket_interpolated(2), sixteen.bucket(2));
+}
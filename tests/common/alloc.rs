@@ -0,0 +1,62 @@
+//! Allocator counting what each thread allocates, for tests that assert memory use. A test
+//! crate installs it with `#[global_allocator] static ALLOC: Counting = Counting;`.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// The system allocator, counting per thread
+pub struct Counting;
+
+/// What a thread allocated while measured
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Counts {
+    pub allocations: usize, // allocations and reallocations made
+    pub live: isize,        // bytes allocated minus bytes freed; negative after freeing older memory
+    pub peak: isize,        // highest `live` reached
+}
+
+thread_local! {
+    static COUNTS: Cell<Counts> = const { Cell::new(Counts { allocations: 0, live: 0, peak: 0 }) };
+}
+
+fn record(delta: isize, allocation: bool) {
+    // Allocations while the thread is being torn down go uncounted
+    let _ = COUNTS.try_with(|counts| {
+        let mut c = counts.get();
+        c.allocations += usize::from(allocation);
+        c.live += delta;
+        c.peak = c.peak.max(c.live);
+        counts.set(c);
+    });
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record(layout.size() as isize, true);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        record(-(layout.size() as isize), false);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            record(new_size as isize - layout.size() as isize, true);
+        }
+        new
+    }
+}
+
+/// Run `f` and count what it allocates on this thread, from zero
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Counts) {
+    COUNTS.with(|counts| counts.set(Counts::default()));
+    let result = f();
+    (result, COUNTS.with(Cell::get))
+}
@@ -0,0 +1,241 @@
+//! Synthetic inputs for the integration tests and benchmarks, so large fixtures don't
+//! have to be committed. Everything is derived from a seed or spelled out by the caller:
+//! the same arguments always give the same output.
+#![allow(dead_code)] // each test crate uses its own subset
+pub mod alloc;
+
+use geojson::{Feature, FeatureCollection, GeoJson, Geometry, JsonObject, Value};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::BTreeMap, fmt::Write, fs, io, ops::RangeInclusive, path::PathBuf};
+
+/// FeatureCollection of `features` star-shaped polygons with `vertices` points each,
+/// laid out on a grid covering the whole globe. Features are named "Synth 0000", …
+pub fn polygon_world(features: usize, vertices: usize, seed: u64) -> GeoJson {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let cols = (features as f64).sqrt().ceil().max(1.0) as usize;
+    let rows = features.div_ceil(cols).max(1);
+    let (cell_w, cell_h) = (360.0 / cols as f64, 170.0 / rows as f64);
+
+    let features = (0..features)
+        .map(|i| {
+            let cx = -180.0 + cell_w * ((i % cols) as f64 + 0.5);
+            let cy = -85.0 + cell_h * ((i / cols) as f64 + 0.5);
+            let mut ring: Vec<Vec<f64>> = (0..vertices.max(3))
+                .map(|v| {
+                    let angle = std::f64::consts::TAU * v as f64 / vertices.max(3) as f64;
+                    let r = rng.random_range(0.25..0.5);
+                    vec![cx + angle.cos() * r * cell_w, cy + angle.sin() * r * cell_h]
+                })
+                .collect();
+            ring.push(ring[0].clone());
+
+            let mut properties = JsonObject::new();
+            properties.insert("ADMIN".to_string(), format!("Synth {:04}", i).into());
+            Feature {
+                geometry: Some(Geometry::new(Value::Polygon(vec![ring]))),
+                properties: Some(properties),
+                ..Default::default()
+            }
+        })
+        .collect();
+    GeoJson::FeatureCollection(FeatureCollection { bbox: None, features, foreign_members: None })
+}
+
+/// World Bank style GDP CSV (same header layout as `dataPKB/pkb.csv`) with
+/// `countries` rows of yearly values 1960–2024; roughly one value in ten is missing.
+pub fn gdp_csv(countries: usize, seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut out = String::from(
+        "\"Data Source\",\"World Development Indicators\",\n\n\"Last Updated Date\",\"2025-06-05\",\n\n",
+    );
+    out.push_str("\"Country Name\",\"Country Code\",\"Indicator Name\",\"Indicator Code\"");
+    for year in 1960..=2024 {
+        let _ = write!(out, ",\"{}\"", year);
+    }
+    out.push_str(",\n");
+
+    for i in 0..countries {
+        let _ = write!(out, "\"Synth {:04}\",\"S{:04}\",\"GDP (current US$)\",\"NY.GDP.MKTP.CD\"", i, i);
+        let mut value = rng.random_range(1e8..1e11);
+        for _ in 1960..=2024 {
+            value *= rng.random_range(0.95..1.08);
+            if rng.random_bool(0.1) {
+                out.push_str(",\"\"");
+            } else {
+                let _ = write!(out, ",\"{:.1}\"", value);
+            }
+        }
+        out.push_str(",\n");
+    }
+    out
+}
+
+/// Closed ring of a 1°×1° square with its south-west corner at (`x`, `y`)
+pub fn square_at(x: f64, y: f64) -> Vec<Vec<f64>> {
+    vec![vec![x, y], vec![x + 1.0, y], vec![x + 1.0, y + 1.0], vec![x, y + 1.0], vec![x, y]]
+}
+
+/// Builder of a miniature but consistent data directory: continent and country lists,
+/// world/continent/country GeoJSON, country info, fun facts, aliases and the GDP CSV.
+/// Countries without a shape get a square placed by their position in the lists.
+#[derive(Default)]
+pub struct FixtureAtlas {
+    continents: Vec<(String, Vec<String>)>,
+    shapes: BTreeMap<String, Vec<Vec<Vec<f64>>>>, // country -> closed rings of its parts
+    facts: BTreeMap<String, Vec<String>>,
+    aliases: BTreeMap<String, String>,
+    gdp: BTreeMap<String, BTreeMap<u16, f64>>,
+    codes: BTreeMap<String, String>,
+}
+
+/// A written fixture directory and the values it was built with
+pub struct Fixture {
+    pub dir: PathBuf,
+    pub gdp: BTreeMap<String, BTreeMap<u16, f64>>, // GDP row name -> year -> value
+}
+
+impl FixtureAtlas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continent `name` with these member countries, in list order
+    pub fn continent<'a>(mut self, name: &str, countries: impl IntoIterator<Item = &'a str>) -> Self {
+        self.continents.push((name.to_string(), countries.into_iter().map(str::to_string).collect()));
+        self
+    }
+
+    /// Outline of `country` as one closed ring of [lon, lat] points
+    pub fn country(mut self, country: &str, ring: Vec<Vec<f64>>) -> Self {
+        self.shapes.insert(country.to_string(), vec![ring]);
+        self
+    }
+
+    /// Outline of `country` as a MultiPolygon of these parts, each one closed ring, e.g.
+    /// the two sides of a country across the antimeridian
+    pub fn country_parts(mut self, country: &str, parts: Vec<Vec<Vec<f64>>>) -> Self {
+        self.shapes.insert(country.to_string(), parts);
+        self
+    }
+
+    /// Fun facts of `country`
+    pub fn facts(mut self, country: &str, facts: &[&str]) -> Self {
+        self.facts.insert(key(country), facts.iter().map(|f| f.to_string()).collect());
+        self
+    }
+
+    /// Name of `country` in the GDP dataset, when it differs from the map name
+    pub fn alias(mut self, country: &str, gdp_name: &str) -> Self {
+        self.aliases.insert(key(country), gdp_name.to_string());
+        self
+    }
+
+    /// GDP row `name` (a map name or an alias target) with `value(year)` for every year in `years`
+    pub fn gdp(mut self, name: &str, years: RangeInclusive<u16>, value: impl Fn(u16) -> f64) -> Self {
+        self.gdp.insert(name.to_string(), years.map(|y| (y, value(y))).collect());
+        self
+    }
+
+    /// GDP row `name` with `years` left empty, a gap in its series
+    pub fn gdp_gap(mut self, name: &str, years: RangeInclusive<u16>) -> Self {
+        if let Some(series) = self.gdp.get_mut(name) {
+            series.retain(|year, _| !years.contains(year));
+        }
+        self
+    }
+
+    /// Country code of GDP row `name`; rows get "F00", "F01", … in name order otherwise
+    pub fn code(mut self, name: &str, code: &str) -> Self {
+        self.codes.insert(name.to_string(), code.to_string());
+        self
+    }
+
+    /// Write the directory as `rustatlas-fixture-<name>` under the system temp directory,
+    /// replacing an earlier one
+    pub fn write(self, name: &str) -> io::Result<Fixture> {
+        let dir = std::env::temp_dir().join(format!("rustatlas-fixture-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("dataPKB"))?;
+        let json = |file: &str, value: serde_json::Value| fs::write(dir.join(file), value.to_string());
+
+        let mut world = Vec::new();
+        let mut info = serde_json::Map::new();
+        let mut placed = 0;
+        json("continent_world.json", self.continents.iter().map(|(c, _)| c.as_str()).collect())?;
+        for (continent, countries) in &self.continents {
+            json(&format!("country_{}.json", key(continent)), countries.iter().map(String::as_str).collect())?;
+            let mut members = Vec::new();
+            for country in countries {
+                let parts = self.shapes.get(country).cloned().unwrap_or_else(|| {
+                    placed += 1;
+                    vec![square_at(-170.0 + 3.0 * placed as f64, 40.0)]
+                });
+                let feature = multi_feature(country, parts);
+                fs::write(dir.join(format!("country_{}.geojson", key(country))), collection(vec![feature.clone()]).to_string())?;
+                members.push(feature);
+                info.insert(key(country), serde_json::json!({
+                    "name": country, "capital": format!("{} City", country),
+                    "area": 1000.0, "population": 1_000_000, "currency": "TST",
+                }));
+            }
+            world.extend(members.iter().cloned());
+            fs::write(dir.join(format!("country_{}.geojson", key(continent))), collection(members).to_string())?;
+        }
+        fs::write(dir.join("continent_world.geojson"), collection(world).to_string())?;
+        json("country_info.json", info.into())?;
+        json("funfacts.json", serde_json::to_value(&self.facts)?)?;
+        json("aliases.json", serde_json::to_value(&self.aliases)?)?;
+
+        let mut csv = String::from("\"Data Source\",\"Fixture\",\n\n\"Last Updated Date\",\"2025-01-01\",\n\n");
+        csv.push_str("\"Country Name\",\"Country Code\",\"Indicator Name\",\"Indicator Code\"");
+        for year in 1960..=2024 {
+            let _ = write!(csv, ",\"{}\"", year);
+        }
+        csv.push_str(",\n");
+        for (i, (name, series)) in self.gdp.iter().enumerate() {
+            let code = self.codes.get(name).cloned().unwrap_or_else(|| format!("F{:02}", i));
+            let _ = write!(csv, "\"{}\",\"{}\",\"GDP (current US$)\",\"NY.GDP.MKTP.CD\"", name, code);
+            for year in 1960..=2024 {
+                match series.get(&year) {
+                    Some(v) => { let _ = write!(csv, ",\"{}\"", v); }
+                    None => csv.push_str(",\"\""),
+                }
+            }
+            csv.push_str(",\n");
+        }
+        fs::write(dir.join("dataPKB/pkb.csv"), csv)?;
+        Ok(Fixture { dir, gdp: self.gdp })
+    }
+}
+
+/// File name key of a list entry, as `rustatlas::data::country_key` makes it
+fn key(name: &str) -> String {
+    rustatlas::data::country_key(name)
+}
+
+/// Feature named `name` (its ADMIN property) with the outline `ring`
+pub fn feature(name: &str, ring: Vec<Vec<f64>>) -> Feature {
+    let mut properties = JsonObject::new();
+    properties.insert("ADMIN".to_string(), name.into());
+    Feature {
+        geometry: Some(Geometry::new(Value::Polygon(vec![ring]))),
+        properties: Some(properties),
+        ..Default::default()
+    }
+}
+
+/// Feature named `name` with one polygon per ring of `parts`, a plain Polygon when there
+/// is only one
+pub fn multi_feature(name: &str, mut parts: Vec<Vec<Vec<f64>>>) -> Feature {
+    if parts.len() == 1 {
+        return feature(name, parts.remove(0));
+    }
+    let mut feature = feature(name, Vec::new());
+    feature.geometry = Some(Geometry::new(Value::MultiPolygon(parts.into_iter().map(|ring| vec![ring]).collect())));
+    feature
+}
+
+/// FeatureCollection of `features`
+pub fn collection(features: Vec<Feature>) -> GeoJson {
+    GeoJson::FeatureCollection(FeatureCollection { bbox: None, features, foreign_members: None })
+}
@@ -0,0 +1,108 @@
+//! The GDP chart grid of marked countries: panes two to a row until they would be too
+//! small, at most four countries marked, the marks kept past the grid and navigation,
+//! and the shared GDP axis
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, layout::Rect, Terminal};
+use rustatlas::{config::Severity, keys::Action, state::AppState, ui::{self, grid_panes}};
+use std::fs;
+
+#[test]
+fn panes_two_to_a_row_or_stacked() {
+    let area = Rect::new(0, 0, 100, 40);
+    assert!(grid_panes(area, 0).is_empty());
+    assert_eq!(grid_panes(area, 1), [area]);
+    assert_eq!(grid_panes(area, 2), [Rect::new(0, 0, 50, 40), Rect::new(50, 0, 50, 40)]);
+    // Three leave the last place of the 2×2 grid empty
+    assert_eq!(
+        grid_panes(area, 3),
+        [Rect::new(0, 0, 50, 20), Rect::new(50, 0, 50, 20), Rect::new(0, 20, 50, 20)]
+    );
+    assert_eq!(grid_panes(area, 4).len(), 4);
+    assert_eq!(grid_panes(area, 4)[3], Rect::new(50, 20, 50, 20));
+
+    // Too narrow or too low for 30×8 panes side by side: one full-width row each
+    let narrow = Rect::new(0, 0, 59, 40);
+    assert_eq!(grid_panes(narrow, 2), [Rect::new(0, 0, 59, 20), Rect::new(0, 20, 59, 20)]);
+    let low = Rect::new(0, 0, 100, 15);
+    assert_eq!(grid_panes(low, 4).iter().map(|p| (p.width, p.x)).collect::<Vec<_>>(), [(100, 0); 4]);
+    // Just big enough stays a grid
+    assert_eq!(grid_panes(Rect::new(0, 0, 60, 16), 4)[1], Rect::new(30, 0, 30, 8));
+}
+
+#[test]
+fn marks_are_capped_kept_and_compared() {
+    let countries = ["Alpha", "Beta", "Gamma", "Delta", "Epsilon"];
+    let mut atlas = FixtureAtlas::new().continent("Testland", countries);
+    for (i, name) in countries.iter().enumerate() {
+        atlas = atlas.gdp(name, 2000..=2002, move |year| (i as f64 + 1.0) * 1e9 * (year - 1999) as f64);
+    }
+    let dir = atlas.write("compare-grid").unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    let key = |state: &AppState, action| state.keys.key(action).unwrap();
+    let (mark, grid) = (key(&state, Action::Mark), key(&state, Action::CompareGrid));
+
+    // Nothing to mark on the world map, nothing to compare without marks
+    state.handle_input(mark);
+    assert!(state.marked.is_empty());
+    state.handle_input(key(&state, Action::Select));
+    state.handle_input(grid);
+    assert!(state.compare.is_none());
+    assert_eq!(state.notification.take().unwrap().severity, Severity::Error);
+
+    for name in countries {
+        state.selected = state.list_items.iter().position(|c| c == name).unwrap();
+        state.handle_input(mark);
+    }
+    let marked: Vec<&str> = state.marked.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(marked, ["Alpha", "Beta", "Gamma", "Delta"]);
+    let notification = state.notification.take().unwrap();
+    assert_eq!(notification.text, "Można porównać najwyżej 4 kraje");
+    assert_eq!(notification.severity, Severity::Error);
+    // Unmarking one makes room again
+    state.selected = state.list_items.iter().position(|c| c == "Beta").unwrap();
+    state.handle_input(mark);
+    state.selected = state.list_items.iter().position(|c| c == "Epsilon").unwrap();
+    state.handle_input(mark);
+    let marked: Vec<String> = state.marked.iter().map(|m| m.name.clone()).collect();
+    assert_eq!(marked, ["Alpha", "Gamma", "Delta", "Epsilon"]);
+
+    // Marks kept through a country and back
+    state.handle_input(key(&state, Action::Select));
+    state.handle_input(key(&state, Action::Back));
+    assert_eq!(state.marked.len(), 4);
+
+    state.handle_input(grid);
+    let charts = state.compare.as_ref().unwrap();
+    assert_eq!(charts.iter().map(|c| c.name.clone()).collect::<Vec<_>>(), marked);
+    let screen = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect::<String>()
+    };
+    let text = screen(&state);
+    for name in marked {
+        assert!(text.contains(&name), "{} has no pane:\n{}", name, text);
+    }
+    assert!(text.contains("Oś GDP: osobna dla każdego kraju"), "{}", text);
+    // Each scale tops out near the country's own peak; shared, all near Epsilon's 15B
+    assert!(text.contains("3.3B"), "{}", text);
+    assert_eq!(text.matches("16.5B").count(), 1, "{}", text);
+    state.handle_input(key(&state, Action::SharedAxis));
+    let text = screen(&state);
+    assert!(text.contains("Oś GDP: wspólna"), "{}", text);
+    assert_eq!(text.matches("16.5B").count(), 4, "{}", text);
+    assert!(!text.contains("3.3B"), "{}", text);
+
+    // Closed with Esc, the marks still there
+    state.handle_input(key(&state, Action::Back));
+    assert!(state.compare.is_none());
+    assert_eq!(state.marked.len(), 4);
+    let _ = fs::remove_dir_all(&dir);
+}
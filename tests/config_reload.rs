@@ -0,0 +1,154 @@
+//! Re-reading `config.json` with Ctrl+T or `--watch`: keys, settings and colors swapped in
+//! together, a file that doesn't parse or leaves Back or Quit unbound rejected whole, and
+//! Ctrl+letter keys written and shown as "Ctrl+T"
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use rustatlas::{
+    cli::{parse, Command},
+    config::Severity,
+    keys::{ctrl, key_name, parse_key, Action},
+    state::{AppState, Reload},
+    theme::ColorSupport,
+};
+use std::{
+    fs::{self, File},
+    path::PathBuf,
+    time::{Duration, Instant, SystemTime},
+};
+
+fn open(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write(name).unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    (dir, state)
+}
+
+/// Text and severity of the notification on screen
+fn notification(state: &AppState) -> (String, Severity) {
+    let n = state.notification.as_ref().expect("no notification");
+    (n.text.clone(), n.severity)
+}
+
+#[test]
+fn control_keys_by_name() {
+    for name in ["Ctrl+T", "ctrl+t", "CTRL-T"] {
+        assert_eq!(parse_key(name), Some(ctrl('t')), "{}", name);
+    }
+    assert_eq!(ctrl('t'), KeyCode::Char('\x14'));
+    for name in ["Ctrl+1", "Ctrl+", "Ctrl+tt"] {
+        assert_eq!(parse_key(name), None, "{}", name);
+    }
+    assert_eq!(key_name(ctrl('t')), "Ctrl+T");
+    assert_eq!(key_name(ctrl('a')), "Ctrl+A");
+    assert_eq!(key_name(KeyCode::Char('t')), "t");
+}
+
+#[test]
+fn parsed_whole_or_rejected() {
+    let reload = Reload::parse(br#"{"keys": {"quit": ["x"]}, "colors": "16", "fact_rotation_secs": 7}"#, None, ColorSupport::TrueColor).unwrap();
+    assert_eq!(reload.keys.key(Action::Quit), Some(KeyCode::Char('x')));
+    assert_eq!(reload.theme.support(), ColorSupport::Ansi16);
+    assert_eq!(reload.config.fact_rotation_secs, 7);
+    assert!(reload.warnings.is_empty());
+
+    // Detected colors when the file has none; --colors over the file's, with a warning
+    let reload = Reload::parse(b"{}", None, ColorSupport::Ansi256).unwrap();
+    assert_eq!(reload.theme.support(), ColorSupport::Ansi256);
+    let reload = Reload::parse(br#"{"colors": "16"}"#, Some(ColorSupport::TrueColor), ColorSupport::Ansi256).unwrap();
+    assert_eq!(reload.theme.support(), ColorSupport::TrueColor);
+    assert_eq!(reload.warnings, ["kolory z --colors mają pierwszeństwo"]);
+    // Unknown key names are warned about, the rest applied
+    let reload = Reload::parse(br#"{"keys": {"help": ["F13", "?"]}}"#, None, ColorSupport::Ansi16).unwrap();
+    assert_eq!(reload.warnings, ["nieznane klawisze: F13"]);
+    assert_eq!(reload.keys.key(Action::Help), Some(KeyCode::Char('?')));
+
+    let error = |bytes: &[u8]| Reload::parse(bytes, None, ColorSupport::Ansi16).err().unwrap();
+    assert!(error(br#"{"keys": "#).starts_with("config.json: "));
+    assert!(error(br#"{"colors": "4"}"#).starts_with("config.json: "));
+    assert_eq!(error(br#"{"keys": {"quit": []}}"#), "config.json zostawia bez klawisza: wyjście");
+    assert!(error(br#"{"keys": {"back": [], "quit": []}}"#).contains(", "));
+}
+
+#[test]
+fn ctrl_t_swaps_everything_or_nothing() {
+    let (dir, mut state) = open("reload-keys");
+    let config = dir.join("config.json");
+    let reload = state.keys.key(Action::Reload).unwrap();
+    assert_eq!(reload, ctrl('t'));
+
+    fs::write(&config, r#"{"keys": {"quit": ["x"]}, "colors": "16", "show_map_legend": false}"#).unwrap();
+    state.handle_input(reload);
+    assert_eq!(notification(&state), ("Wczytano config.json".to_string(), Severity::Info));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('x')));
+    assert_eq!(state.theme.support(), ColorSupport::Ansi16);
+    assert!(!state.config.show_map_legend);
+    // q is no longer Quit
+    assert!(!state.handle_input(KeyCode::Char('q')));
+
+    // Broken: every setting stays as it was
+    fs::write(&config, r#"{"keys": {"quit": []}, "colors": "256", "show_map_legend": true}"#).unwrap();
+    state.handle_input(reload);
+    assert_eq!(
+        notification(&state),
+        ("Odrzucono zmiany: config.json zostawia bez klawisza: wyjście".to_string(), Severity::Error)
+    );
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('x')));
+    assert_eq!(state.theme.support(), ColorSupport::Ansi16);
+    assert!(!state.config.show_map_legend);
+
+    // With warnings
+    fs::write(&config, r#"{"keys": {"help": ["F13"]}}"#).unwrap();
+    state.handle_input(reload);
+    assert_eq!(notification(&state).0, "Wczytano config.json; nieznane klawisze: F13");
+
+    // Gone: back to the defaults, as at startup
+    fs::remove_file(&config).unwrap();
+    state.handle_input(reload);
+    assert_eq!(notification(&state).0, "Wczytano config.json");
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('q')));
+    assert!(state.config.show_map_legend);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn watch_reloads_on_change() {
+    let (dir, mut state) = open("reload-watch");
+    let config = dir.join("config.json");
+    fs::write(&config, r#"{"keys": {"quit": ["x"]}}"#).unwrap();
+    let later = |secs| File::options().write(true).open(&config).unwrap().set_modified(SystemTime::now() + Duration::from_secs(secs)).unwrap();
+    let start = Instant::now();
+
+    // Not watched: the change waits for Ctrl+T
+    later(10);
+    state.tick(start + Duration::from_secs(5));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('q')));
+
+    state.watch = true;
+    state.tick(start + Duration::from_secs(10));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('x')));
+
+    // Unchanged since: nothing read again, even with the file's keys set aside
+    state.keys = Reload::parse(b"{}", None, ColorSupport::Ansi16).unwrap().keys;
+    state.tick(start + Duration::from_secs(15));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('q')));
+    // Changed again, but checked at most once a second
+    fs::write(&config, r#"{"keys": {"quit": ["y"]}}"#).unwrap();
+    later(20);
+    state.tick(start + Duration::from_millis(15500));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('q')));
+    state.tick(start + Duration::from_secs(17));
+    assert_eq!(state.keys.key(Action::Quit), Some(KeyCode::Char('y')));
+    let _ = fs::remove_dir_all(&dir);
+
+    let watch = |args: &[&str]| match parse(args.iter().map(|a| a.to_string())).unwrap() {
+        Command::Tui { watch, .. } => watch,
+        _ => panic!("{:?}: not the TUI", args),
+    };
+    assert!(!watch(&[]));
+    assert!(watch(&["--watch"]));
+}
@@ -0,0 +1,134 @@
+//! Constituent features from `constituents.json` (Greenland for Denmark): folded into the
+//! parent on maps holding both, added to the parent's own map dimmed beside it or merged
+//! into it, and reported by check-data when they name no feature
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use ratatui::style::Color;
+use rustatlas::{
+    check,
+    config::Config,
+    data::DataCache,
+    keys::Action,
+    map_draw::MapView,
+    state::{AppState, Panel},
+};
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+/// Axis-aligned ring from `x0,y0` to `x1,y1`
+fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+/// Denmark in Europe, Greenland far off in North America, and `constituents` as the file
+fn atlas(name: &str, constituents: &str, config: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Denmark", "Sweden"])
+        .continent("North America", ["Greenland"])
+        .country("Denmark", rect(8.0, 54.0, 12.0, 57.0))
+        .country("Sweden", rect(12.0, 55.0, 20.0, 65.0))
+        .country("Greenland", rect(-55.0, 60.0, -25.0, 80.0))
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("constituents.json"), constituents).unwrap();
+    fs::write(dir.join("config.json"), config).unwrap();
+    dir
+}
+
+/// Denmark's own map, reached from the world list
+fn open_denmark(dir: &PathBuf) -> AppState {
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    for place in ["Europe", "Denmark"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    state
+}
+
+#[test]
+fn merged_into_the_parent_on_a_shared_map() {
+    let dir = atlas("constituents-merge", "{}", "{}");
+    let mut cache = DataCache::new(&dir).unwrap();
+    let features = || {
+        collection(vec![
+            feature("Denmark", rect(8.0, 54.0, 12.0, 57.0)),
+            feature("Sweden", rect(12.0, 55.0, 20.0, 65.0)),
+            feature("Greenland", rect(-55.0, 60.0, -25.0, 80.0)),
+        ])
+    };
+    let constituents = BTreeMap::from([("Denmark".to_string(), vec!["Greenland".to_string()])]);
+
+    let mut view = MapView::new(features(), &mut cache).unwrap();
+    view.merge_constituents(&constituents);
+    let mut names: Vec<&str> = view.feature_names().collect();
+    names.sort();
+    assert_eq!(names, ["Denmark", "Sweden"]);
+    // Greenland is drawn, highlighted and looked up as Denmark
+    assert_eq!(view.feature_at(-40.0, 70.0), Some("Denmark"));
+    assert_eq!(view.feature_at(10.0, 55.0), Some("Denmark"));
+    let (x, y) = view.highlight_bounds("Denmark").unwrap();
+    assert_eq!((x, y), ([-55.0, 12.0], [54.0, 80.0]));
+    assert_eq!(view.bounds().0[0], -55.0);
+
+    // Without the parent on the map nothing is folded away
+    let mut view = MapView::new(
+        collection(vec![feature("Sweden", rect(12.0, 55.0, 20.0, 65.0)), feature("Greenland", rect(-55.0, 60.0, -25.0, 80.0))]),
+        &mut cache,
+    )
+    .unwrap();
+    view.merge_constituents(&constituents);
+    assert_eq!(view.feature_count(), 2);
+    assert_eq!(view.feature_at(-40.0, 70.0), Some("Greenland"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn beside_the_parent_on_its_own_map() {
+    assert!(!Config::default().merge_constituents);
+    let dir = atlas("constituents-beside", r#"{"Denmark": ["Greenland"]}"#, "{}");
+    let state = open_denmark(&dir);
+    let map = state.map.as_ref().unwrap();
+    // Read from its own file into Denmark's map, dimmed, and the bounds reaching it
+    assert!(map.feature_names().any(|n| n == "Greenland"));
+    assert_eq!(map.widget().base_color("Greenland"), Color::DarkGray);
+    assert_ne!(map.widget().base_color("Denmark"), Color::DarkGray);
+    assert_eq!(map.feature_at(-40.0, 70.0), Some("Greenland"));
+    let (x, y) = map.bounds();
+    assert!(x[0] <= -55.0 && y[1] >= 80.0, "{:?} {:?}", x, y);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn merged_when_configured() {
+    let dir = atlas("constituents-merged", r#"{"Denmark": ["Greenland"]}"#, r#"{"merge_constituents": true}"#);
+    let state = open_denmark(&dir);
+    let map = state.map.as_ref().unwrap();
+    assert!(!map.feature_names().any(|n| n == "Greenland"));
+    assert_eq!(map.feature_at(-40.0, 70.0), Some("Denmark"));
+    let (x, _) = map.highlight_bounds("Denmark").unwrap();
+    assert_eq!(x, [-55.0, 12.0]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_constituents_are_skipped_and_reported() {
+    let dir = atlas("constituents-missing", r#"{"Denmark": ["Greenland", "Atlantis"]}"#, "{}");
+    let state = open_denmark(&dir);
+    let map = state.map.as_ref().unwrap();
+    assert!(map.feature_names().any(|n| n == "Greenland"));
+    assert!(map.skipped().contains(&("Atlantis".to_string(), "constituent without map file")), "{:?}", map.skipped());
+
+    let report = check::report(&dir).unwrap();
+    assert!(report.text.contains("1 name in constituents.json match no map feature\n  Atlantis\n"), "report:\n{}", report.text);
+    let _ = fs::remove_dir_all(&dir);
+}
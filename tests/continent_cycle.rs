@@ -0,0 +1,109 @@
+//! Left/Right on the world map pick continents by where they lie, west to east, wrapping
+//! around at either end; the pick is the list's selection, so Enter opens it and Back
+//! comes back to it, and the next step goes on from there
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Panel},
+};
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+/// Three continents listed out of their west-to-east order, and one with no countries
+/// and so no place on the map
+fn atlas(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Middle", ["Bravo"])
+        .continent("East", ["Charlie"])
+        .continent("Empty", Vec::<&str>::new())
+        .continent("West", ["Alpha"])
+        .country("Alpha", square_at(-100.0, 0.0))
+        .country("Bravo", square_at(0.0, 0.0))
+        .country("Charlie", square_at(100.0, 0.0))
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    wait_for_map(&mut state);
+    state.active_panel = Panel::Center;
+    (dir, state)
+}
+
+fn wait_for_map(state: &mut AppState) {
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+    wait_for_map(state);
+}
+
+fn selected(state: &AppState) -> &str {
+    &state.list_items[state.selected]
+}
+
+#[test]
+fn right_and_left_wrap_around() {
+    let (dir, mut state) = atlas("cycle-wrap");
+    assert_eq!(state.map.as_ref().unwrap().continents_west_to_east(), ["West", "Middle", "East"]);
+    state.selected = state.list_items.iter().position(|c| c == "West").unwrap();
+    let mut east = Vec::new();
+    for _ in 0..4 {
+        press(&mut state, Action::Right);
+        east.push(selected(&state).to_string());
+    }
+    assert_eq!(east, ["Middle", "East", "West", "Middle"]);
+    let mut west = Vec::new();
+    for _ in 0..4 {
+        press(&mut state, Action::Left);
+        west.push(selected(&state).to_string());
+    }
+    assert_eq!(west, ["West", "East", "Middle", "West"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn from_a_continent_off_the_map_the_ends() {
+    let (dir, mut state) = atlas("cycle-off-map");
+    // Nothing of it to place: Right starts from the west, Left from the east
+    state.selected = state.list_items.iter().position(|c| c == "Empty").unwrap();
+    press(&mut state, Action::Right);
+    assert_eq!(selected(&state), "West");
+    state.selected = state.list_items.iter().position(|c| c == "Empty").unwrap();
+    press(&mut state, Action::Left);
+    assert_eq!(selected(&state), "East");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_pick_carries_over() {
+    let (dir, mut state) = atlas("cycle-carry");
+    state.selected = state.list_items.iter().position(|c| c == "West").unwrap();
+    press(&mut state, Action::Right);
+    press(&mut state, Action::Right);
+    assert_eq!(selected(&state), "East");
+
+    // Enter opens the continent picked on the map
+    press(&mut state, Action::Select);
+    assert_eq!(state.list_items, ["Charlie"]);
+    // Back selects it again, and the arrows go on from it
+    press(&mut state, Action::Back);
+    assert_eq!(selected(&state), "East");
+    state.active_panel = Panel::Center;
+    press(&mut state, Action::Right);
+    assert_eq!(selected(&state), "West");
+
+    // A pick in the list is where the map goes on from too
+    state.selected = state.list_items.iter().position(|c| c == "Middle").unwrap();
+    press(&mut state, Action::Left);
+    assert_eq!(selected(&state), "West");
+    let _ = fs::remove_dir_all(&dir);
+}
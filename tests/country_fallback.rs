@@ -0,0 +1,111 @@
+//! A country without its own GeoJSON file is cut out of its continent's map: zoomed onto
+//! it with the continent dimmed around it, said so in the status bar, the panels filled
+//! as usual; missing from the continent's map too, the error and the panels alone
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use ratatui::style::Color;
+use rustatlas::{
+    config::Severity,
+    keys::Action,
+    state::{AppState, Panel},
+};
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .country("Alpha", square_at(10.0, 40.0))
+        .country("Beta", square_at(11.2, 40.0))
+        .gdp("Alpha", 2020..=2021, |_| 1e10)
+        .facts("Alpha", &["Alpha is square."])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// The atlas in `dir`, on the world map
+fn start(dir: &PathBuf) -> AppState {
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.active_panel = Panel::Left;
+    state
+}
+
+/// Open Alpha from the world map, through its continent
+fn open_alpha(state: &mut AppState) {
+    for place in ["Testland", "Alpha"] {
+        state.notification = None;
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+}
+
+/// The panels are filled whatever happened to the map
+fn assert_panels(state: &AppState) {
+    let summary = state.summary.as_ref().unwrap();
+    assert_eq!(summary.info.as_ref().unwrap().capital, "Alpha City");
+    assert_eq!(summary.latest_gdp(), Some((2021, 1e10)));
+    assert_eq!(state.facts, ["Alpha is square."]);
+}
+
+#[test]
+fn own_file_no_notice() {
+    let dir = atlas("fallback-own");
+    let mut state = start(&dir);
+    open_alpha(&mut state);
+    let map = state.map.as_ref().unwrap();
+    assert_eq!(map.feature_names().collect::<Vec<_>>(), ["Alpha"]);
+    assert!(state.notification.is_none());
+    assert_panels(&state);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_file_cut_from_the_continent() {
+    let dir = atlas("fallback-continent");
+    fs::remove_file(dir.join("country_alpha.geojson")).unwrap();
+    let mut state = start(&dir);
+    open_alpha(&mut state);
+    let map = state.map.as_ref().unwrap();
+    // The continent's features, zoomed onto Alpha with a quarter of its size around it
+    let mut names: Vec<&str> = map.feature_names().collect();
+    names.sort();
+    assert_eq!(names, ["Alpha", "Beta"]);
+    assert_eq!(map.bounds(), ([9.75, 11.25], [39.75, 41.25]));
+    let widget = map.widget();
+    assert_eq!(widget.base_color("Beta"), Color::DarkGray);
+    assert_ne!(widget.base_color("Alpha"), Color::DarkGray);
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.text, "Granice z mapy kontynentu (brak pliku kraju)");
+    assert_eq!(notification.severity, Severity::Info);
+    assert_panels(&state);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_from_the_continent_too() {
+    let dir = atlas("fallback-none");
+    fs::remove_file(dir.join("country_alpha.geojson")).unwrap();
+    // The maps it would be cut from without Alpha: the continent's, and the world's the
+    // continent's is sharded from
+    let beta = collection(vec![feature("Beta", square_at(11.2, 40.0))]).to_string();
+    for map in ["country_testland.geojson", "continent_world.geojson"] {
+        fs::write(dir.join(map), &beta).unwrap();
+    }
+    let mut state = start(&dir);
+    open_alpha(&mut state);
+    // The continent's map isn't left behind
+    assert!(state.map.is_none());
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("Brak mapy dla Alpha: "), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    assert_panels(&state);
+    let _ = fs::remove_dir_all(&dir);
+}
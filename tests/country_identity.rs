@@ -0,0 +1,105 @@
+//! A country listed on two continents is one country: opened from either, it has the same
+//! key, info, facts and GDP, counts as visited once and stays marked; Back returns to the
+//! continent it was opened from
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    keys::Action,
+    state::{AppState, GdpStatus, Location, Panel},
+};
+use std::{fs, path::PathBuf};
+
+fn atlas(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Russia"])
+        .continent("Asia", ["China", "Russia"])
+        .facts("Russia", &["Spans eleven time zones."])
+        .alias("Russia", "Russian Federation")
+        .gdp("Russian Federation", 2020..=2022, |year| f64::from(year - 2000) * 1e11)
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+/// Open `item` from the list
+fn open(state: &mut AppState, item: &str) {
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    press(state, Action::Select);
+}
+
+/// Open `country` from the world map through `continent`
+fn open_via(state: &mut AppState, continent: &str, country: &str) {
+    while state.location != Location::World {
+        press(state, Action::Back);
+    }
+    open(state, continent);
+    open(state, country);
+}
+
+#[test]
+fn same_country_from_either_continent() {
+    let (dir, mut state) = atlas("identity-same");
+    let mut seen = Vec::new();
+    for continent in ["Europe", "Asia"] {
+        open_via(&mut state, continent, "Russia");
+        let summary = state.summary.as_ref().unwrap();
+        let info = summary.info.as_ref().unwrap();
+        seen.push((
+            summary.key.clone(),
+            info.capital.clone(),
+            summary.gdp_name.clone(),
+            summary.facts.clone(),
+            summary.gdp.clone(),
+        ));
+        assert!(matches!(&state.gdp_status, GdpStatus::Found { year, .. } if year == "2022"), "{}", continent);
+    }
+    assert_eq!(seen[0], seen[1]);
+    assert_eq!(seen[0].0, "russia");
+    assert_eq!(seen[0].2, "Russian Federation");
+    // Visited once, under its key
+    assert_eq!(state.visited.iter().collect::<Vec<_>>(), ["russia"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn back_returns_to_the_continent_it_was_opened_from() {
+    let (dir, mut state) = atlas("identity-back");
+    for continent in ["Europe", "Asia", "Europe"] {
+        open_via(&mut state, continent, "Russia");
+        assert_eq!(state.location, Location::Country { continent: continent.to_string(), country: "Russia".to_string() });
+        press(&mut state, Action::Back);
+        assert_eq!(state.location, Location::Continent(continent.to_string()));
+        // With the country selected again
+        assert_eq!(state.list_items[state.selected], "Russia");
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn marked_on_one_continent_marked_on_the_other() {
+    let (dir, mut state) = atlas("identity-mark");
+    open(&mut state, "Europe");
+    state.selected = state.list_items.iter().position(|c| c == "Russia").unwrap();
+    press(&mut state, Action::Mark);
+    assert_eq!(state.marked.len(), 1);
+
+    // From Asia the same mark: pressed again, it comes off rather than adding a second
+    press(&mut state, Action::Back);
+    open(&mut state, "Asia");
+    state.selected = state.list_items.iter().position(|c| c == "Russia").unwrap();
+    press(&mut state, Action::Mark);
+    assert!(state.marked.is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,95 @@
+//! `CountrySummary::build`: a country's info, GDP series found through its alias and facts
+//! in file order, each left empty where the data has nothing; and the opened country's
+//! panels reading the same summary
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    data::DataCache,
+    gdp_reader::GDPData,
+    keys::Action,
+    state::{AppState, Panel},
+    summary::CountrySummary,
+};
+use std::{fs, time::Duration};
+
+/// Alpha with everything, its GDP under another name; Beta with its info alone
+fn fixture(name: &str) -> common::Fixture {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .alias("Alpha", "Alfa Republic")
+        .gdp("Alfa Republic", 2000..=2003, |y| f64::from(y - 1999) * 1e9)
+        .facts("Alpha", &["First.", "Second.", "Third."])
+        .write(name)
+        .unwrap()
+}
+
+#[test]
+fn gathers_what_the_data_has() {
+    let fixture = fixture("summary-build");
+    let cache = DataCache::new(&fixture.dir).unwrap();
+    let gdp = GDPData::new(fixture.dir.join("dataPKB/pkb.csv")).unwrap();
+
+    let alpha = CountrySummary::build("Alpha", &cache, Some(&gdp));
+    assert_eq!(alpha.name, "Alpha");
+    assert_eq!(alpha.key, "alpha");
+    let info = alpha.info.as_ref().unwrap();
+    assert_eq!((info.capital.as_str(), info.population), ("Alpha City", 1_000_000));
+    assert_eq!(alpha.gdp_name, "Alfa Republic");
+    assert_eq!(alpha.gdp.as_ref().unwrap().len(), 4);
+    assert_eq!(alpha.latest_gdp(), Some((2003, 4e9)));
+    assert_eq!(alpha.facts, ["First.", "Second.", "Third."]);
+    assert!(alpha.sovereign.is_none() && alpha.territories.is_empty());
+
+    // Nothing but the info: the rest empty, its own name looked up
+    let beta = CountrySummary::build("Beta", &cache, Some(&gdp));
+    assert!(beta.info.is_some());
+    assert_eq!(beta.gdp_name, "Beta");
+    assert!(beta.gdp.is_none() && beta.latest_gdp().is_none());
+    assert!(beta.facts.is_empty());
+
+    // Without the dataset, and for a name the data doesn't know
+    let alpha = CountrySummary::build("Alpha", &cache, None);
+    assert!(alpha.gdp.is_none() && alpha.info.is_some() && alpha.facts.len() == 3);
+    let nowhere = CountrySummary::build("Nowhere", &cache, Some(&gdp));
+    assert!(nowhere.info.is_none() && nowhere.gdp.is_none() && nowhere.facts.is_empty());
+    assert_eq!(nowhere.key, "nowhere");
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn panels_read_the_opened_summary() {
+    let fixture = fixture("summary-state");
+    let _ = fs::remove_file(fixture.dir.join("session.json"));
+    let mut state = AppState::open(&fixture.dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    assert!(state.summary.is_none());
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+
+    // The state's copy is what build gives, and the fact list is its facts
+    let built = CountrySummary::build("Alpha", &state.cache, state.gdp_data.as_ref());
+    let summary = state.summary.as_ref().unwrap();
+    assert_eq!(summary.key, built.key);
+    assert_eq!(summary.gdp, built.gdp);
+    assert_eq!(summary.facts, built.facts);
+    let mut facts = state.facts.clone();
+    facts.sort();
+    assert_eq!(facts, ["First.", "Second.", "Third."]);
+    let view = state.view_model();
+    assert_eq!(view.summary.unwrap().latest_gdp(), Some((2003, 4e9)));
+
+    // Back to the continent: no country opened
+    state.handle_input(state.keys.key(Action::Back).unwrap());
+    assert!(state.summary.is_none());
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
@@ -0,0 +1,152 @@
+//! `data-diff` on two fixture directories: continents and countries added and removed, a
+//! rename told by the unchanged record, a move, geometry over the threshold, GDP spans and
+//! changed info fields, as text and as JSON; reformatted files showing no differences
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use rustatlas::cli::{parse, Command};
+use std::{fs, path::{Path, PathBuf}, process};
+
+fn old_atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Europe", ["Alpha", "Beta", "Gamma"])
+        .continent("Asia", ["Delta"])
+        .continent("Oceania", ["Kappa"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .gdp("Alpha", 2000..=2010, |_| 1e9)
+        .gdp("Beta", 2000..=2010, |_| 1e9)
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Beta renamed Bravo, Epsilon added, Gamma moved to Asia, Oceania gone and Africa new;
+/// Alpha drawn in two parts, its GDP two years longer, Delta's population changed
+fn new_atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Alpha", "Bravo", "Epsilon"])
+        .continent("Asia", ["Delta", "Gamma"])
+        .continent("Africa", ["Zeta"])
+        .country_parts("Alpha", vec![square_at(0.0, 0.0), square_at(3.0, 0.0)])
+        .gdp("Alpha", 2000..=2012, |_| 1e9)
+        .gdp("Beta", 2000..=2010, |_| 1e9)
+        .write(name)
+        .unwrap()
+        .dir;
+    let path = dir.join("country_info.json");
+    let mut info: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    info["bravo"]["capital"] = "Beta City".into();
+    info["delta"]["population"] = 2_000_000.into();
+    fs::write(&path, info.to_string()).unwrap();
+    dir
+}
+
+fn data_diff(old: &Path, new: &Path, flags: &[&str]) -> process::Output {
+    process::Command::new(env!("CARGO_BIN_EXE_RustAtlas"))
+        .arg("data-diff")
+        .arg(old)
+        .arg(new)
+        .args(flags)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: process::Output) -> String {
+    assert!(output.status.success(), "data-diff failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn every_kind_of_change_as_text() {
+    let (old, new) = (old_atlas("diff-text-old"), new_atlas("diff-text-new"));
+    let text = stdout(data_diff(&old, &new, &[]));
+    let expected = [
+        "Continents: 1 added, 1 removed",
+        "  + Africa",
+        "  - Oceania",
+        "Countries: 2 added, 1 removed, 1 renamed, 1 moved",
+        "  + Epsilon (Europe)",
+        "  + Zeta (Africa)",
+        "  - Kappa (Oceania)",
+        "  ~ Beta -> Bravo (Europe)",
+        "  > Gamma: Europe -> Asia",
+        "Geometry: 1 country with the vertex count changed by more than 5%",
+        "GDP years: 1 dataset country with a different span",
+        "Country info: 2 fields changed",
+    ];
+    for line in expected {
+        assert!(text.lines().any(|l| l == line), "no line {:?} in:\n{}", line, text);
+    }
+    assert!(text.starts_with(&format!("Data in {} compared with {}\n", new.display(), old.display())), "{}", text);
+    let row = |start: &str| text.lines().find(|l| l.trim_start().starts_with(start)).unwrap().split_whitespace().collect::<Vec<_>>().join(" ");
+    assert_eq!(row("Alpha"), "Alpha 5 -> 10");
+    assert!(text.contains("2000-2010 -> 2000-2012"), "{}", text);
+    // The renamed country's name, and a changed figure
+    assert!(text.contains("name: Beta -> Bravo"), "{}", text);
+    assert!(text.contains("population: 1000000 -> 2000000"), "{}", text);
+
+    // Raised threshold: the doubled outline no longer counts
+    let text = stdout(data_diff(&old, &new, &["--threshold", "150"]));
+    assert!(!text.contains("Geometry:"), "{}", text);
+    let _ = fs::remove_dir_all(&old);
+    let _ = fs::remove_dir_all(&new);
+}
+
+#[test]
+fn same_changes_as_json() {
+    let (old, new) = (old_atlas("diff-json-old"), new_atlas("diff-json-new"));
+    let json: serde_json::Value = serde_json::from_str(&stdout(data_diff(&old, &new, &["--json"]))).unwrap();
+    assert_eq!(json["continents_added"], serde_json::json!(["Africa"]));
+    assert_eq!(json["continents_removed"], serde_json::json!(["Oceania"]));
+    assert_eq!(json["countries_renamed"], serde_json::json!([{"continent": "Europe", "from": "Beta", "to": "Bravo"}]));
+    assert_eq!(json["countries_moved"], serde_json::json!([{"country": "Gamma", "from": "Europe", "to": "Asia"}]));
+    assert_eq!(json["geometry"], serde_json::json!([{"country": "Alpha", "old": 5, "new": 10}]));
+    assert_eq!(json["gdp_years"], serde_json::json!([{"country": "Alpha", "old": [2000, 2010], "new": [2000, 2012]}]));
+    let added: Vec<&str> = json["countries_added"].as_array().unwrap().iter().map(|p| p["country"].as_str().unwrap()).collect();
+    assert_eq!(added, ["Epsilon", "Zeta"]);
+    let info: Vec<(&str, &str)> = json["info"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| (i["country"].as_str().unwrap(), i["field"].as_str().unwrap()))
+        .collect();
+    assert_eq!(info, [("Bravo", "name"), ("Delta", "population")]);
+    let _ = fs::remove_dir_all(&old);
+    let _ = fs::remove_dir_all(&new);
+}
+
+#[test]
+fn reformatted_files_are_no_change() {
+    let (old, new) = (old_atlas("diff-same-old"), old_atlas("diff-same-new"));
+    for file in ["country_info.json", "continent_world.json", "country_europe.json", "country_europe.geojson"] {
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(new.join(file)).unwrap()).unwrap();
+        fs::write(new.join(file), serde_json::to_string_pretty(&value).unwrap()).unwrap();
+    }
+    let text = stdout(data_diff(&old, &new, &[]));
+    assert!(text.ends_with("No differences\n"), "{}", text);
+    let json: serde_json::Value = serde_json::from_str(&stdout(data_diff(&old, &new, &["--json"]))).unwrap();
+    assert!(json.as_object().unwrap().values().all(|v| v.as_array().unwrap().is_empty()), "{}", json);
+
+    // A mistyped directory is an error, not an empty one
+    let output = data_diff(&old, &old.join("missing"), &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("is not a data directory"));
+    let _ = fs::remove_dir_all(&old);
+    let _ = fs::remove_dir_all(&new);
+}
+
+#[test]
+fn arguments() {
+    let args = |list: &[&str]| parse(list.iter().map(|a| a.to_string()));
+    let Ok(Command::DataDiff(diff)) = args(&["data-diff", "a", "b"]) else { panic!("not data-diff") };
+    assert_eq!((diff.old.as_str(), diff.new.as_str(), diff.threshold, diff.json), ("a", "b", 5.0, false));
+    let Ok(Command::DataDiff(diff)) = args(&["data-diff", "--json", "a", "--threshold", "12.5", "b"]) else { panic!("not data-diff") };
+    assert_eq!((diff.threshold, diff.json), (12.5, true));
+
+    let error = |list: &[&str]| args(list).err().unwrap();
+    assert_eq!(error(&["data-diff", "a"]), "data-diff needs an old and a new data directory");
+    assert_eq!(error(&["data-diff", "a", "b", "c"]), "data-diff needs an old and a new data directory");
+    assert_eq!(error(&["data-diff", "a", "b", "--threshold", "-1"]), "--threshold must be a percentage, not '-1'");
+    assert_eq!(error(&["data-diff", "a", "b", "--threshold"]), "missing value after --threshold");
+    assert_eq!(error(&["data-diff", "a", "b", "--fast"]), "unknown option '--fast'");
+}
@@ -0,0 +1,100 @@
+//! Rings that aren't GeoJSON linear rings: fewer than four positions or not closed, as
+//! written in the file, or fewer than three distinct points. Their polygons are dropped,
+//! or their holes when only a hole is bad; a feature left with nothing is skipped, and a
+//! map with nothing left is refused.
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use geojson::{Feature, Geometry, Value};
+use rustatlas::{data::DataCache, map_draw::{MapView, DEGENERATE}};
+
+const SQUARE: [[f64; 2]; 5] = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]];
+const HOLE: [[f64; 2]; 5] = [[4.0, 4.0], [6.0, 4.0], [6.0, 6.0], [4.0, 6.0], [4.0, 4.0]];
+
+fn ring(points: &[[f64; 2]]) -> Vec<Vec<f64>> {
+    points.iter().map(|p| p.to_vec()).collect()
+}
+
+/// `ring` moved `dx` degrees east
+fn shifted(points: &[[f64; 2]], dx: f64) -> Vec<Vec<f64>> {
+    points.iter().map(|p| vec![p[0] + dx, p[1]]).collect()
+}
+
+fn with_rings(name: &str, value: Value) -> Feature {
+    let mut feature = feature(name, Vec::new());
+    feature.geometry = Some(Geometry::new(value));
+    feature
+}
+
+/// The view of `features`, with the fixture directory `name` for its data cache
+fn view(name: &str, features: Vec<Feature>) -> Result<MapView, Box<dyn std::error::Error>> {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write(name).unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let view = MapView::new(collection(features), &mut cache);
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+    view
+}
+
+#[test]
+fn degenerate_exteriors_are_skipped() {
+    // (name, the ring as written, whether it is a linear ring)
+    let table: [(&str, Vec<Vec<f64>>, bool); 10] = [
+        ("Triangle", ring(&[[0.0, 0.0], [4.0, 0.0], [0.0, 4.0], [0.0, 0.0]]), true),
+        ("Square", ring(&SQUARE), true),
+        // Four positions, but only two distinct points: nothing enclosed
+        ("Doubled", ring(&[[0.0, 0.0], [4.0, 0.0], [4.0, 0.0], [0.0, 0.0]]), false),
+        ("One point", ring(&[[1.0, 1.0], [1.0, 1.0], [1.0, 1.0], [1.0, 1.0]]), false),
+        // A point repeated inside a triangle still leaves three
+        ("Repeated", ring(&[[0.0, 0.0], [4.0, 0.0], [4.0, 0.0], [0.0, 4.0], [0.0, 0.0]]), true),
+        ("Empty", Vec::new(), false),
+        ("Point", ring(&[[1.0, 1.0]]), false),
+        ("Closed pair", ring(&[[1.0, 1.0], [2.0, 2.0], [1.0, 1.0]]), false),
+        // A triangle left open: conversion would close it into four positions
+        ("Open triangle", ring(&[[0.0, 0.0], [4.0, 0.0], [0.0, 4.0]]), false),
+        ("Open square", ring(&SQUARE[..4]), false),
+    ];
+    for (name, exterior, valid) in table {
+        let shape = feature(name, exterior);
+        let features = vec![feature("Anchor", shifted(&SQUARE, 50.0)), shape];
+        let view = view("degenerate-exteriors", features).unwrap();
+        let drawn: Vec<&str> = view.feature_names().collect();
+        assert_eq!(drawn.contains(&name), valid, "{}", name);
+        let expected = if valid { vec![] } else { vec![(name.to_string(), DEGENERATE)] };
+        assert_eq!(view.skipped(), expected, "{}", name);
+    }
+}
+
+#[test]
+fn only_the_bad_part_or_hole_is_dropped() {
+    let open = ring(&SQUARE[..4]);
+    let parts = Value::MultiPolygon(vec![vec![ring(&SQUARE)], vec![shifted(&SQUARE[..4], 20.0)], vec![shifted(&SQUARE, 40.0)]]);
+    let features = vec![
+        with_rings("Islands", parts),
+        // A valid hole is kept, an open one dropped
+        with_rings("Lake", Value::Polygon(vec![shifted(&SQUARE, 60.0), shifted(&HOLE, 60.0)])),
+        with_rings("Puddle", Value::Polygon(vec![shifted(&SQUARE, 80.0), shifted(&HOLE[..4], 80.0)])),
+        // A bad exterior takes its valid hole with it
+        with_rings("Sunk", Value::Polygon(vec![open, ring(&HOLE)])),
+    ];
+    let view = view("degenerate-parts", features).unwrap();
+    let mut drawn: Vec<&str> = view.feature_names().collect();
+    drawn.sort();
+    assert_eq!(drawn, ["Islands", "Lake", "Puddle"]);
+    assert_eq!(view.skipped(), [("Sunk".to_string(), DEGENERATE)]);
+    assert_eq!(view.feature_at(5.0, 5.0), Some("Islands"));
+    assert_eq!(view.feature_at(25.0, 5.0), None);
+    assert_eq!(view.feature_at(45.0, 5.0), Some("Islands"));
+    assert_eq!(view.feature_at(65.0, 5.0), None);
+    assert_eq!(view.feature_at(61.0, 1.0), Some("Lake"));
+    assert_eq!(view.feature_at(85.0, 5.0), Some("Puddle"));
+}
+
+#[test]
+fn map_of_degenerate_rings_only_is_refused() {
+    let features = vec![
+        feature("Open", ring(&SQUARE[..4])),
+        feature("Short", ring(&[[0.0, 0.0], [1.0, 1.0], [0.0, 0.0]])),
+    ];
+    let error = view("degenerate-only", features).err().unwrap();
+    assert_eq!(error.to_string(), "no valid polygon geometry in GeoJSON");
+}
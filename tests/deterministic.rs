@@ -0,0 +1,106 @@
+//! Outputs that must not depend on hash map iteration order: the same data directory,
+//! built twice, gives byte-identical exports, `check-data` reports and diagnostics; and
+//! the seeded generators behind the large fixtures and benchmarks give the same bytes
+//! for the same seed
+mod common;
+
+use common::{gdp_csv, polygon_world, FixtureAtlas};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{state::AppState, ui};
+use std::{path::Path, process::Command};
+
+/// Enough continents, countries and problems of each kind for any leaked order to show:
+/// countries without GDP rows, aliased ones, list entries without a map
+fn atlas(name: &str) -> std::path::PathBuf {
+    let mut atlas = FixtureAtlas::new()
+        .continent("Zeta", ["Zulu", "Yankee", "Xray", "Whiskey", "Victor", "Uniform"])
+        .continent("Eta", ["Tango", "Sierra", "Romeo", "Quebec", "Papa"])
+        .continent("Alpha", ["Oscar", "November", "Mike", "Lima", "Kilo", "Juliett"])
+        .continent("Mu", ["India", "Hotel", "Golf", "Foxtrot", "Echo", "Delta", "Charlie", "Bravo"])
+        .alias("Hotel", "Hotel Republic")
+        .alias("Sierra", "Sierra, The");
+    for (i, country) in ["Zulu", "Xray", "Victor", "Tango", "Romeo", "Papa", "Oscar", "Mike", "Kilo", "Golf", "Echo", "Charlie"]
+        .into_iter()
+        .enumerate()
+    {
+        atlas = atlas.gdp(country, 1990 + i as u16..=2000 + i as u16, move |y| 1e9 * (i + 1) as f64 + f64::from(y));
+    }
+    let dir = atlas
+        .gdp("Hotel Republic", 1960..=1961, |_| 5e8)
+        .gdp("Sierra, The", 2020..=2024, |y| f64::from(y) * 1e7)
+        .write(name)
+        .unwrap()
+        .dir;
+    // Entries naming no map file nor alias, reported in list order
+    std::fs::write(dir.join("country_eta.json"), r#"["Tango", "Sierra", "Romeo", "Quebec", "Papa", "Nowhere", "Atlantis"]"#).unwrap();
+    dir
+}
+
+fn run(dir: &Path, args: &[&str]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_RustAtlas")).arg("--data-dir").arg(dir).args(args).output().unwrap();
+    [output.stdout, output.stderr].concat()
+}
+
+/// Everything each output gives for one construction of the atlas
+fn outputs(name: &str) -> Vec<(&'static str, Vec<u8>)> {
+    let dir = atlas(name);
+    let export = |file: &str, flags: &[&str]| {
+        let path = dir.join(file);
+        let printed = run(&dir, &[&["export-gdp", "--all", "-o", path.to_str().unwrap()], flags].concat());
+        [printed, std::fs::read(&path).unwrap()].concat()
+    };
+    let outputs = vec![
+        ("export wide", export("wide.csv", &[])),
+        ("export long", export("long.csv", &["--long"])),
+        ("check-data", run(&dir, &["check-data"])),
+        ("check-data --json-errors", run(&dir, &["check-data", "--json-errors"])),
+        ("map", run(&dir, &["map", "--width", "80", "--height", "24", "--no-color"])),
+        ("diagnostics", diagnostics(&dir)),
+    ];
+    let _ = std::fs::remove_dir_all(&dir);
+    outputs
+}
+
+/// The diagnostics overlay as drawn, row by row
+fn diagnostics(dir: &Path) -> Vec<u8> {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.show_diagnostics = true;
+    let mut terminal = Terminal::new(TestBackend::new(140, 50)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..50).map(|y| (0..140).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+    let screen = rows.join("\n");
+    assert!(screen.contains("Katalog danych"), "no diagnostics overlay:\n{}", screen);
+    screen.into_bytes()
+}
+
+#[test]
+fn two_constructions_give_identical_bytes() {
+    // Written to the same place both times, so paths in the output match too
+    let first = outputs("deterministic");
+    let second = outputs("deterministic");
+    for ((what, a), (_, b)) in first.iter().zip(&second) {
+        assert!(!a.is_empty(), "{} printed nothing", what);
+        assert!(a == b, "{} differs between runs:\n{}\n---\n{}", what, String::from_utf8_lossy(a), String::from_utf8_lossy(b));
+    }
+    // The reports list things in a fixed order, not just the same one twice
+    let check = String::from_utf8_lossy(&first[2].1);
+    let nowhere = check.find("\"Nowhere\"").unwrap();
+    let atlantis = check.find("\"Atlantis\"").unwrap();
+    assert!(nowhere < atlantis, "list issues out of list order:\n{}", check);
+}
+
+#[test]
+fn generators_repeat_for_a_seed() {
+    let world = |seed| polygon_world(40, 64, seed).to_string().into_bytes();
+    let (first, second) = (world(7), world(7));
+    assert!(first == second, "polygon_world differs for the same seed");
+    assert!(first != world(8), "polygon_world ignores its seed");
+
+    let (first, second) = (gdp_csv(120, 7), gdp_csv(120, 7));
+    assert!(first.as_bytes() == second.as_bytes(), "gdp_csv differs for the same seed");
+    assert!(first != gdp_csv(120, 8), "gdp_csv ignores its seed");
+}
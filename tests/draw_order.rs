@@ -0,0 +1,93 @@
+//! Map features drawn largest first whatever their order in the file, so a small country
+//! overlapping a large one stays visible, with the highlight drawn over both
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use rustatlas::{data::DataCache, map_draw::{MapStyle, MapView}};
+use std::collections::BTreeMap;
+
+const VIEWPORT: ([f64; 2], [f64; 2]) = ([-5.0, 45.0], [-5.0, 45.0]);
+
+fn square(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+/// A view of these features, in this file order
+fn view(cache: &mut DataCache, names: &[&str]) -> MapView {
+    let features = names
+        .iter()
+        .map(|&name| match name {
+            // Sharing the large one's southern edge, and inside it
+            "Small" => feature(name, square(15.0, 0.0, 25.0, 10.0)),
+            _ => feature(name, square(0.0, 0.0, 40.0, 40.0)),
+        })
+        .collect();
+    MapView::new(collection(features), cache).unwrap()
+}
+
+/// `view` drawn on a fixed viewport, each feature in a color of its own, with `highlight`
+fn draw(view: &MapView, highlight: Option<&str>) -> Buffer {
+    let colors = BTreeMap::from([("large".to_string(), Color::Blue), ("small".to_string(), Color::Green)]);
+    let area = Rect::new(0, 0, 42, 22);
+    let mut buffer = Buffer::empty(area);
+    let mut widget = view.widget().viewport(VIEWPORT).colors(&colors);
+    if let Some(name) = highlight {
+        widget = widget.highlight(name);
+    }
+    widget.render(area, &mut buffer);
+    buffer
+}
+
+/// Cells with some of a feature's outline in them, from `view` drawn alone
+fn cells_of(buffer: &Buffer) -> Vec<(u16, u16)> {
+    let area = buffer.area;
+    (1..area.height - 1)
+        .flat_map(|y| (1..area.width - 1).map(move |x| (x, y)))
+        .filter(|&(x, y)| !buffer[(x, y)].symbol().trim().is_empty())
+        .collect()
+}
+
+#[test]
+fn the_small_country_is_drawn_over_the_large_one() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("draw-order").unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let small_first = view(&mut cache, &["Small", "Large"]);
+    let large_first = view(&mut cache, &["Large", "Small"]);
+    assert_eq!(small_first.feature_names().collect::<Vec<_>>(), ["Large", "Small"]);
+    assert_eq!(large_first.feature_names().collect::<Vec<_>>(), ["Large", "Small"]);
+    // The one on top is the one found there
+    assert_eq!(small_first.feature_at(20.0, 5.0), Some("Small"));
+    assert_eq!(small_first.feature_at(5.0, 30.0), Some("Large"));
+
+    let drawn = draw(&small_first, None);
+    assert!(drawn == draw(&large_first, None), "drawing depends on the file order");
+    let small = cells_of(&draw(&view(&mut cache, &["Small"]), None));
+    let large = cells_of(&draw(&view(&mut cache, &["Large"]), None));
+    let shared: Vec<_> = small.iter().filter(|cell| large.contains(cell)).collect();
+    assert!(!shared.is_empty(), "the outlines don't meet");
+    // All of the small outline shows, the shared edge too
+    for &(x, y) in &small {
+        assert_eq!(drawn[(x, y)].fg, Color::Green, "({}, {})", x, y);
+    }
+    assert!(large.iter().any(|&(x, y)| drawn[(x, y)].fg == Color::Blue));
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn the_highlight_is_drawn_last() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("draw-order-highlight").unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let both = view(&mut cache, &["Small", "Large"]);
+    let red = MapStyle::CLASSIC.highlight;
+    for (highlight, alone, other) in [("Large", "Large", Color::Green), ("Small", "Small", Color::Blue)] {
+        let drawn = draw(&both, Some(highlight));
+        let cells = cells_of(&draw(&view(&mut cache, &[alone]), None));
+        // The highlighted outline is whole, over the other feature's where they meet
+        for &(x, y) in &cells {
+            assert_eq!(drawn[(x, y)].fg, red, "{} at ({}, {})", highlight, x, y);
+        }
+        assert!(cells_of(&drawn).iter().any(|&(x, y)| drawn[(x, y)].fg == other), "{}", highlight);
+    }
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
@@ -0,0 +1,73 @@
+//! Ctrl+E's choice of data file for each place and focused panel, the editor command
+//! taken from the environment, and the entry counts reported after an edit
+use rustatlas::{
+    data::GeoLevel,
+    editor::{change_text, command, count_entries, data_file, DataFile, FALLBACK_EDITOR},
+    state::{Location, Panel},
+};
+
+#[test]
+fn file_behind_each_panel() {
+    let world = Location::World;
+    let europe = Location::Continent("Europe".to_string());
+    let poland = Location::Country { continent: "Europe".to_string(), country: "Poland".to_string() };
+    let list = |level, key: &str| DataFile::List(level, key.to_string());
+    let map = |level, key: &str| DataFile::Map(level, key.to_string());
+    // (place, focused panel, whether a country has a map file of its own, file, its name)
+    let table = [
+        (&world, Panel::Left, true, list(GeoLevel::World, "world"), "continent_world.json"),
+        (&world, Panel::Center, true, map(GeoLevel::World, "world"), "continent_world.geojson"),
+        (&world, Panel::Right, true, list(GeoLevel::World, "world"), "continent_world.json"),
+        (&europe, Panel::Left, true, list(GeoLevel::Continent, "Europe"), "country_europe.json"),
+        (&europe, Panel::Center, true, map(GeoLevel::Continent, "Europe"), "country_europe.geojson"),
+        (&europe, Panel::Right, true, DataFile::CountryInfo, "country_info.json"),
+        (&poland, Panel::Left, true, DataFile::CountryInfo, "country_info.json"),
+        (&poland, Panel::Center, true, map(GeoLevel::Country, "Poland"), "country_poland.geojson"),
+        (&poland, Panel::Center, false, map(GeoLevel::Continent, "Europe"), "country_europe.geojson"),
+        (&poland, Panel::Right, true, DataFile::FunFacts, "funfacts.json"),
+        (&poland, Panel::Right, false, DataFile::FunFacts, "funfacts.json"),
+    ];
+    for (location, panel, own_map, file, name) in table {
+        let chosen = data_file(location, panel, own_map);
+        assert_eq!(chosen, file, "{:?} {:?} own map {}", location, panel, own_map);
+        assert_eq!(chosen.name(), name);
+    }
+    // A continent's list panel doesn't depend on a country's map
+    assert_eq!(data_file(&europe, Panel::Left, false), list(GeoLevel::Continent, "Europe"));
+}
+
+#[test]
+fn editor_command_from_the_environment() {
+    let env = |visual: Option<&'static str>, editor: Option<&'static str>| {
+        move |name: &str| match name {
+            "VISUAL" => visual.map(str::to_string),
+            "EDITOR" => editor.map(str::to_string),
+            _ => None,
+        }
+    };
+    assert_eq!(command(env(Some("nvim"), Some("nano"))), ["nvim"]);
+    assert_eq!(command(env(None, Some("nano"))), ["nano"]);
+    assert_eq!(command(env(Some("code --wait"), None)), ["code", "--wait"]);
+    // Set but blank: the next one
+    assert_eq!(command(env(Some("  "), Some("nano"))), ["nano"]);
+    assert_eq!(command(env(None, None)), [FALLBACK_EDITOR]);
+    assert_eq!(command(env(Some(""), Some(""))), [FALLBACK_EDITOR]);
+}
+
+#[test]
+fn entries_counted_in_each_kind_of_file() {
+    assert_eq!(count_entries(br#"["Europe", "Asia"]"#).unwrap(), 2);
+    assert_eq!(count_entries(br#"{"type": "FeatureCollection", "features": [{}, {}, {}]}"#).unwrap(), 3);
+    assert_eq!(count_entries(br#"{"type": "Feature", "properties": {}, "geometry": null}"#).unwrap(), 1);
+    assert_eq!(count_entries(br#"{"poland": {}, "germany": {}}"#).unwrap(), 2);
+    assert_eq!(count_entries(b"[]").unwrap(), 0);
+    assert!(count_entries(b"[\"Europe\",").is_err());
+}
+
+#[test]
+fn change_in_entries_for_the_status_bar() {
+    assert_eq!(change_text("country_europe.json", Some(45), 46), "country_europe.json: 45 → 46 wpisów (+1)");
+    assert_eq!(change_text("country_europe.json", Some(3), 1), "country_europe.json: 3 → 1 wpis (-2)");
+    assert_eq!(change_text("funfacts.json", Some(4), 4), "funfacts.json: zapisano, nadal 4 wpisy");
+    assert_eq!(change_text("funfacts.json", None, 2), "funfacts.json: 2 wpisy");
+}
@@ -0,0 +1,95 @@
+//! Ctrl+E end to end on the headless driver, with a script standing in for the editor:
+//! the terminal handed over and taken back, and the view after a saved edit, an untouched
+//! file, a file left broken and an editor that failed. One test, as it sets $VISUAL.
+#![cfg(unix)]
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    config::Severity,
+    keys::Action,
+    platform::{run, HeadlessDriver},
+    state::AppState,
+};
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+/// An editor that runs `body` with the file to edit as $1
+fn script(dir: &Path, name: &str, body: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    path.display().to_string()
+}
+
+/// Press Ctrl+E in `state` with `editor` as $VISUAL; the driver it ran on
+fn edit(state: &mut AppState, editor: &str) -> HeadlessDriver {
+    // SAFETY: the only test in this binary, so no other thread reads the environment
+    unsafe { std::env::set_var("VISUAL", editor) };
+    let mut driver = HeadlessDriver::new(100, 30, [state.keys.key(Action::EditData).unwrap()]);
+    run(&mut driver, state).unwrap();
+    driver
+}
+
+/// The screen as text, row by row
+fn screen(driver: &HeadlessDriver) -> String {
+    let buffer = driver.last_frame().unwrap();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect()
+}
+
+#[test]
+fn editor_lifecycle() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Northland", ["Delta"])
+        .write("edit-lifecycle")
+        .unwrap()
+        .dir;
+    let editors = dir.join("editors");
+    fs::create_dir_all(&editors).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    let list = dir.join("continent_world.json");
+
+    // Saved with a continent more: the list read again, the selection kept
+    let add = script(&editors, "add.sh", r#"printf '["Testland", "Northland", "Southland"]' > "$1""#);
+    let driver = edit(&mut state, &add);
+    assert_eq!(driver.suspensions(), 1);
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.text, "continent_world.json: 2 → 3 wpisy (+1)");
+    assert_eq!(notification.severity, Severity::Info);
+    assert!(state.list_items.contains(&"Southland".to_string()));
+    assert_eq!(state.list_items[state.selected], "Testland");
+    // Redrawn whole after the editor had the terminal
+    assert!(screen(&driver).contains("Southland"), "{}", screen(&driver));
+
+    // Closed without saving
+    let keep = script(&editors, "keep.sh", "true");
+    edit(&mut state, &keep);
+    assert_eq!(state.notification.as_ref().unwrap().text, "continent_world.json: bez zmian");
+
+    // Saved as broken JSON: reported, the view left as it was
+    let items = state.list_items.clone();
+    let broken = script(&editors, "broken.sh", r#"printf '["Testland",' > "$1""#);
+    edit(&mut state, &broken);
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("continent_world.json: nie wczytano po edycji"), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    assert_eq!(state.list_items, items);
+
+    // The editor failed: reported, nothing read
+    fs::write(&list, r#"["Testland"]"#).unwrap();
+    let fail = script(&editors, "fail.sh", "exit 3");
+    let driver = edit(&mut state, &fail);
+    assert_eq!(driver.suspensions(), 1);
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.contains("zakończył pracę z błędem"), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    assert_eq!(state.list_items, items);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,98 @@
+//! Exit codes and error reports of the headless subcommands, as documented in the usage text
+mod common;
+
+use common::FixtureAtlas;
+use serde_json::Value;
+use std::{path::Path, process::{Command, Output}};
+
+/// Run the binary on the data directory `dir` with `args`
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_RustAtlas")).arg("--data-dir").arg(dir).args(args).output().unwrap()
+}
+
+/// The one JSON object `--json-errors` printed on stderr, checked to have exactly the
+/// documented fields
+fn json_error(output: &Output) -> Value {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let report: Value = serde_json::from_str(stderr.trim_end()).unwrap_or_else(|e| panic!("{}: {}", e, stderr));
+    let mut fields: Vec<&str> = report.as_object().unwrap().keys().map(String::as_str).collect();
+    fields.sort_unstable();
+    assert_eq!(fields, ["code", "kind", "message", "suggestions"]);
+    assert_eq!(report["code"], output.status.code().unwrap());
+    report
+}
+
+fn fixture(name: &str) -> std::path::PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Alpha", 2000..=2002, f64::from)
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+#[test]
+fn success_is_0() {
+    let dir = fixture("exit-success");
+    let output = run(&dir, &["export-gdp", "Alpha"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "country,2000,2001,2002\nAlpha,2000,2001,2002\n");
+    assert_eq!(run(&dir, &["check-data"]).status.code(), Some(0));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn usage_error_is_2() {
+    let dir = fixture("exit-usage");
+    let output = run(&dir, &["export-gdp"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("export-gdp needs either --all or a list of countries\n\nUsage:"));
+
+    let report = json_error(&run(&dir, &["export-gdp", "--json-errors"]));
+    assert_eq!(report["kind"], "usage");
+    assert_eq!(report["message"], "export-gdp needs either --all or a list of countries");
+    assert_eq!(report["suggestions"], serde_json::json!([]));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unknown_name_is_3() {
+    let dir = fixture("exit-unknown");
+    let output = run(&dir, &["export-gdp", "Alpah"]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "Error: unknown name 'Alpah'\nDid you mean: Alpha, Beta?\n");
+
+    let report = json_error(&run(&dir, &["export-gdp", "Alpah", "--json-errors"]));
+    assert_eq!(report["kind"], "unknown_name");
+    assert_eq!(report["message"], "unknown name 'Alpah'");
+    assert_eq!(report["suggestions"], serde_json::json!(["Alpha", "Beta"]));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_data_is_4() {
+    let dir = fixture("exit-data");
+    std::fs::remove_file(dir.join("dataPKB/pkb.csv")).unwrap();
+    let output = run(&dir, &["check-data"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("Error: cannot read "));
+
+    let report = json_error(&run(&dir, &["check-data", "--json-errors"]));
+    assert_eq!(report["kind"], "data");
+    assert!(report["message"].as_str().unwrap().contains("pkb.csv"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn io_failure_is_5() {
+    let dir = fixture("exit-io");
+    let target = dir.join("no-such-dir/gdp.csv");
+    let target = target.to_str().unwrap();
+    let output = run(&dir, &["export-gdp", "--all", "-o", target]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with(&format!("Error: cannot write {}", target)));
+
+    let report = json_error(&run(&dir, &["export-gdp", "--all", "-o", target, "--json-errors"]));
+    assert_eq!(report["kind"], "io");
+    let _ = std::fs::remove_dir_all(&dir);
+}
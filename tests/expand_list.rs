@@ -0,0 +1,119 @@
+//! The fun fact list: every fact numbered, the focused one in full and the rest by their
+//! first line, a scrollbar once they don't fit, Up/Down going through the facts in a
+//! country or with the right panel focused, and a fact without a link saying so
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use rustatlas::{
+    config::Severity,
+    expand_list::ExpandList,
+    keys::Action,
+    state::{AppState, Panel},
+};
+use std::fs;
+
+fn render(list: ExpandList, width: u16, height: u16) -> Vec<String> {
+    let area = Rect::new(0, 0, width, height);
+    let mut buffer = Buffer::empty(area);
+    list.render(area, &mut buffer);
+    (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string()).collect()
+}
+
+fn items(texts: &[&str]) -> Vec<String> {
+    texts.iter().map(|t| t.to_string()).collect()
+}
+
+#[test]
+fn focused_item_in_full_the_rest_by_their_first_line() {
+    let facts = items(&["Short one", "The focused fact is long enough to wrap", "Another fact far too long to fit"]);
+    // One column kept for the scrollbar: 19 for the text
+    let lines = render(ExpandList::new(&facts, 1), 20, 6);
+    assert_eq!(
+        lines,
+        ["1. Short one", "2. The focused fact", "   is long enough", "   to wrap", "3. Another fact fa…", ""]
+    );
+    let lines = render(ExpandList::new(&facts, 0), 20, 4);
+    assert_eq!(lines, ["1. Short one", "2. The focused fac…", "3. Another fact fa…", ""]);
+
+    // Numbers aligned past nine
+    let many: Vec<String> = (1..=10).map(|i| format!("Fact {}", i)).collect();
+    let lines = render(ExpandList::new(&many, 9), 20, 10);
+    assert_eq!(lines[0], " 1. Fact 1");
+    assert_eq!(lines[9], "10. Fact 10");
+}
+
+#[test]
+fn scrollbar_and_scrolling_when_they_dont_fit() {
+    let many: Vec<String> = (1..=10).map(|i| format!("Fact {}", i)).collect();
+    // All of them fit: no scrollbar
+    let lines = render(ExpandList::new(&many, 0), 20, 10);
+    assert!(lines.iter().all(|l| l.chars().count() < 20), "{:?}", lines);
+    // Too many: a scrollbar in the last column, and the focused item kept in sight
+    let lines = render(ExpandList::new(&many, 7), 20, 4);
+    for (line, text) in lines.iter().zip([" 5. Fact 5", " 6. Fact 6", " 7. Fact 7", " 8. Fact 8"]) {
+        assert!(line.starts_with(text) && line.chars().count() == 20, "{:?}", lines);
+    }
+
+    // A focused item taller than the list: its lines from the offset on, up to what's left
+    let long = items(&["one two three four five six seven eight nine ten"]);
+    let list = || ExpandList::new(&long, 0);
+    let area = Rect::new(0, 0, 10, 2);
+    // "1. " leaves six columns: one, two, three, four, five, six, seven, eight, nine, ten
+    assert_eq!(list().focus_overflow(area), 8);
+    assert!(render(list(), 10, 2)[0].starts_with("1. one "));
+    assert!(render(list().offset(3), 10, 2)[0].starts_with("   four "));
+    assert!(render(list().offset(99), 10, 2)[1].starts_with("   ten "));
+}
+
+#[test]
+fn up_and_down_go_through_the_facts() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .facts("Alpha", &["First", "Second, see https://example.org/alpha.", "Third"])
+        .write("expand-list-keys")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    let key = |state: &AppState, action| state.keys.key(action).unwrap();
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(key(&state, Action::Select));
+    state.selected = state.list_items.iter().position(|c| c == "Alpha").unwrap();
+    state.handle_input(key(&state, Action::Select));
+    assert_eq!(state.facts.len(), 3);
+    state.fact_focus = 0;
+
+    // In a country the facts are the list
+    state.handle_input(key(&state, Action::Down));
+    assert_eq!(state.fact_focus, 1);
+    state.handle_input(key(&state, Action::Down));
+    state.handle_input(key(&state, Action::Down));
+    assert_eq!(state.fact_focus, 2);
+    state.handle_input(key(&state, Action::Up));
+    assert_eq!(state.fact_focus, 1);
+
+    // Only a fact with a link has a source to open
+    state.fact_focus = 0;
+    state.handle_input(key(&state, Action::OpenSource));
+    let notification = state.notification.take().unwrap();
+    assert_eq!(notification.text, "Ta ciekawostka nie podaje źródła");
+    assert_eq!(notification.severity, Severity::Error);
+
+    // On the continent, with no country open, Down moves the list unless the right
+    // panel is focused
+    state.handle_input(key(&state, Action::Back));
+    assert!(state.facts.is_empty());
+    let selected = state.selected;
+    state.active_panel = Panel::Right;
+    state.handle_input(key(&state, Action::Down));
+    assert_eq!(state.selected, selected);
+    state.active_panel = Panel::Left;
+    state.handle_input(key(&state, Action::Down));
+    assert_ne!(state.selected, selected);
+    let _ = fs::remove_dir_all(&dir);
+}
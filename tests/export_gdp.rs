@@ -0,0 +1,54 @@
+//! `export-gdp --all` on a fixture of three countries with GDP data and one without,
+//! compared with the expected files in `tests/golden`
+mod common;
+
+use common::FixtureAtlas;
+use std::{fs, path::Path, process::Command};
+
+/// A country whose dataset name has a comma in it, and the two Congos, told apart by code
+fn three_countries() -> FixtureAtlas {
+    FixtureAtlas::new()
+        .continent("Africa", ["Democratic Republic of the Congo", "Republic of the Congo", "Delta"])
+        .continent("North America", ["Bahamas, The"])
+        .alias("Democratic Republic of the Congo", "COD")
+        .alias("Republic of the Congo", "COG")
+        .gdp("Bahamas, The", 1960..=1962, |y| 1000.5 * f64::from(y - 1959))
+        .gdp("Congo, Dem. Rep.", 1961..=1963, |y| 2e9 + f64::from(y))
+        .code("Congo, Dem. Rep.", "COD")
+        .gdp("Congo, Rep.", 1960..=1960, |_| 3e8)
+        .code("Congo, Rep.", "COG")
+}
+
+/// Run `export-gdp --all` with `flags` into `name` under the fixture directory; the
+/// exported file and what was printed on stderr
+fn export(dir: &Path, name: &str, flags: &[&str]) -> (String, String) {
+    let output = dir.join(name);
+    let run = Command::new(env!("CARGO_BIN_EXE_RustAtlas"))
+        .arg("--data-dir")
+        .arg(dir)
+        .args(["export-gdp", "--all", "-o"])
+        .arg(&output)
+        .args(flags)
+        .output()
+        .unwrap();
+    assert!(run.status.success(), "export failed: {}", String::from_utf8_lossy(&run.stderr));
+    (fs::read_to_string(output).unwrap(), String::from_utf8(run.stderr).unwrap())
+}
+
+#[test]
+fn wide_matches_golden_file() {
+    let fixture = three_countries().write("export-wide").unwrap();
+    let (csv, stderr) = export(&fixture.dir, "wide.csv", &[]);
+    assert_eq!(csv, include_str!("golden/gdp_wide.csv"));
+    assert_eq!(stderr, "Exported 3 countries, skipped 1 without GDP data\n");
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn long_matches_golden_file() {
+    let fixture = three_countries().write("export-long").unwrap();
+    let (csv, stderr) = export(&fixture.dir, "long.csv", &["--long"]);
+    assert_eq!(csv, include_str!("golden/gdp_long.csv"));
+    assert_eq!(stderr, "Exported 3 countries, skipped 1 without GDP data\n");
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
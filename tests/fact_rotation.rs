@@ -0,0 +1,151 @@
+//! Timed fun fact rotation: off by default, the focused fact moved on each period through
+//! a shuffled cycle that shows every fact before repeating one, the timer restarted by any
+//! change of fact, and the countdown dot in the panel title
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    config::Config,
+    keys::Action,
+    state::{AppState, FactCycle, Panel},
+    theme::{Theme, UNICODE},
+    ui,
+};
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// The atlas on Poland, with `facts`, rotating every `secs` seconds
+fn poland(name: &str, facts: &[&str], secs: u64) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new().continent("Europe", ["Poland"]).facts("Poland", facts).write(name).unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.config.fact_rotation_secs = secs;
+    state.theme = Theme::new(state.theme.support()).with_symbols(&UNICODE);
+    for place in ["Europe", "Poland"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    (dir, state)
+}
+
+/// The fact panel's title row
+fn fact_title(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+    terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..30)
+        .map(|y| (0..160).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+        .find(|row| row.contains("Czy wiesz, że"))
+        .unwrap()
+}
+
+#[test]
+fn cycle_shows_every_fact_before_repeating() {
+    let mut cycle = FactCycle::default();
+    let mut current = 2;
+    for _ in 0..20 {
+        // A round: every other fact once, never the one on screen
+        let mut seen = BTreeSet::new();
+        for _ in 0..4 {
+            let next = cycle.next(5, current);
+            assert_ne!(next, current);
+            assert!(seen.insert(next), "{} twice in a round", next);
+            current = next;
+        }
+    }
+    // Nothing to move to
+    assert_eq!(FactCycle::default().next(1, 0), 0);
+    assert_eq!(FactCycle::default().next(2, 1), 0);
+    // Fewer facts than the round was shuffled for
+    let mut cycle = FactCycle::default();
+    cycle.next(6, 0);
+    for _ in 0..5 {
+        let next = cycle.next(3, current.min(2));
+        assert!(next < 3, "{}", next);
+        current = next;
+    }
+}
+
+#[test]
+fn moves_on_each_period() {
+    assert_eq!(Config::default().fact_rotation_secs, 0);
+    let (dir, mut state) = poland("fact-rotation-timer", &["One.", "Two.", "Three."], 5);
+    let start = Instant::now();
+    let first = state.fact_focus;
+    assert!(state.fact_rotation_left(start).unwrap() > 0.9);
+    assert!(fact_title(&state).contains("Czy wiesz, że ... ●"), "{}", fact_title(&state));
+
+    state.tick(start + Duration::from_secs(4));
+    assert_eq!(state.fact_focus, first);
+    let left = state.fact_rotation_left(start + Duration::from_secs(4)).unwrap();
+    assert!(left > 0.1 && left <= 0.2, "{}", left);
+    state.tick(start + Duration::from_secs(5));
+    let second = state.fact_focus;
+    assert_ne!(second, first);
+    // The timer started again with the new fact
+    assert!(state.fact_rotation_left(start + Duration::from_secs(5)).unwrap() == 1.0);
+    state.tick(start + Duration::from_secs(9));
+    assert_eq!(state.fact_focus, second);
+    state.tick(start + Duration::from_secs(10));
+    let third = state.fact_focus;
+    assert!(third != second && third != first);
+    // Clamped at empty, however late the tick
+    assert_eq!(state.fact_rotation_left(start + Duration::from_secs(60)), Some(0.0));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn changing_the_fact_restarts_the_timer() {
+    let (dir, mut state) = poland("fact-rotation-keys", &["One.", "Two.", "Three."], 5);
+    let first = state.fact_focus;
+    // f: the next fact of the cycle, and a whole period before the next change
+    state.handle_input(state.keys.key(Action::NextFact).unwrap());
+    let next = state.fact_focus;
+    assert_ne!(next, first);
+    let now = Instant::now();
+    assert!(state.fact_rotation_left(now).unwrap() > 0.9);
+    state.tick(now + Duration::from_secs(4));
+    assert_eq!(state.fact_focus, next);
+
+    // Up or Down to another fact does the same
+    state.active_panel = Panel::Right;
+    let step = if next == 0 { Action::Down } else { Action::Up };
+    state.handle_input(state.keys.key(step).unwrap());
+    assert_ne!(state.fact_focus, next);
+    assert!(state.fact_rotation_left(Instant::now()).unwrap() > 0.9);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn off_without_a_period_or_a_second_fact() {
+    let (dir, mut state) = poland("fact-rotation-off", &["One.", "Two."], 0);
+    let start = Instant::now();
+    let first = state.fact_focus;
+    assert_eq!(state.fact_rotation_left(start), None);
+    state.tick(start + Duration::from_secs(3600));
+    assert_eq!(state.fact_focus, first);
+    assert!(!fact_title(&state).contains('●'), "{}", fact_title(&state));
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, mut state) = poland("fact-rotation-single", &["Only."], 5);
+    assert_eq!(state.fact_rotation_left(start), None);
+    state.tick(start + Duration::from_secs(60));
+    assert_eq!(state.fact_focus, 0);
+    // f has nowhere to go
+    state.handle_input(state.keys.key(Action::NextFact).unwrap());
+    assert_eq!(state.fact_focus, 0);
+    let _ = fs::remove_dir_all(&dir);
+}
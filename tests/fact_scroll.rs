@@ -0,0 +1,139 @@
+//! Scrolling a fun fact too long for its panel: by hand, clamped to its lines and going
+//! on to the next fact past its end; and by itself, a line at a time, pausing at the end
+//! before starting over, until the user scrolls
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Panel},
+    ui,
+};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const LONG: &str = "Poland has one of the oldest salt mines in Europe, worked without a break from the \
+    thirteenth century until the end of the twentieth, with chapels, statues and chandeliers all carved \
+    from rock salt by the miners themselves, and an underground lake that visitors still cross today \
+    on their way through more than three hundred kilometres of galleries on nine levels.";
+const OTHER: &str = "The Polish alphabet has thirty-two letters.";
+
+/// The atlas on Poland, with `facts`
+fn poland(name: &str, facts: &[&str]) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new().continent("Europe", ["Poland"]).facts("Poland", facts).write(name).unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    for place in ["Europe", "Poland"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+    }
+    (dir, state)
+}
+
+/// Draw at `width` x `height`, which sets how many lines of the fact don't fit
+fn draw(state: &mut AppState, width: u16, height: u16) {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    state.apply_drawn(drawn.unwrap());
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+#[test]
+fn scrolling_by_hand_is_clamped() {
+    let (dir, mut state) = poland("fact-scroll-clamp", &[LONG]);
+    draw(&mut state, 100, 30);
+    let overflow = state.fact_overflow;
+    assert!(overflow >= 3, "{}", overflow);
+    assert_eq!(state.fact_scroll.offset, 0);
+
+    // Above the first line: nowhere to go
+    press(&mut state, Action::Up);
+    assert_eq!(state.fact_scroll.offset, 0);
+    // Down to the last line, and no further: there is no next fact
+    for _ in 0..overflow + 3 {
+        press(&mut state, Action::Down);
+    }
+    assert_eq!((state.fact_scroll.offset, state.fact_focus), (overflow, 0));
+    assert!(!state.fact_scroll.auto);
+
+    // A taller panel hides fewer lines: a step up lands on the new last line, not one
+    // above the old one
+    draw(&mut state, 100, 50);
+    assert!(state.fact_overflow + 1 < overflow, "{} of {}", state.fact_overflow, overflow);
+    press(&mut state, Action::Up);
+    assert_eq!(state.fact_scroll.offset, state.fact_overflow);
+    // Everything fits: back to the top
+    draw(&mut state, 200, 80);
+    assert_eq!(state.fact_overflow, 0);
+    press(&mut state, Action::Up);
+    assert_eq!(state.fact_scroll.offset, 0);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn scrolling_past_the_end_goes_to_the_next_fact() {
+    let (dir, mut state) = poland("fact-scroll-next", &[LONG, OTHER]);
+    // The first fact shown is picked at random; Up on its first line goes to the one before
+    if state.facts[state.fact_focus] == OTHER {
+        press(&mut state, Action::Up);
+    }
+    assert_eq!(state.facts[state.fact_focus], LONG);
+    draw(&mut state, 100, 30);
+    let overflow = state.fact_overflow;
+    for _ in 0..overflow {
+        press(&mut state, Action::Down);
+    }
+    assert_eq!(state.fact_scroll.offset, overflow);
+    press(&mut state, Action::Down);
+    assert_eq!(state.facts[state.fact_focus], OTHER);
+    assert_eq!(state.fact_scroll.offset, 0);
+    // And back: to the long fact's top, not where it was left
+    draw(&mut state, 100, 30);
+    press(&mut state, Action::Up);
+    assert_eq!((state.facts[state.fact_focus].as_str(), state.fact_scroll.offset), (LONG, 0));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn scrolling_by_itself_until_the_user_scrolls() {
+    let (dir, mut state) = poland("fact-scroll-auto", &[LONG]);
+    draw(&mut state, 100, 30);
+    let overflow = state.fact_overflow;
+    let start = Instant::now();
+    let at = |secs: u64| start + Duration::from_secs(secs);
+
+    // A line every two seconds down to the last
+    state.tick(at(1));
+    assert_eq!(state.fact_scroll.offset, 0);
+    let mut now = 0;
+    for line in 1..=overflow {
+        now += 2;
+        state.tick(at(now));
+        assert_eq!(state.fact_scroll.offset, line);
+    }
+    // Held there for six seconds, then from the top again
+    state.tick(at(now + 5));
+    assert_eq!(state.fact_scroll.offset, overflow);
+    state.tick(at(now + 6));
+    assert_eq!(state.fact_scroll.offset, 0);
+
+    // Scrolled by hand: it stays put
+    press(&mut state, Action::Down);
+    for secs in [now + 8, now + 20, now + 40] {
+        state.tick(at(secs));
+        assert_eq!(state.fact_scroll.offset, 1);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
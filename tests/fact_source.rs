@@ -0,0 +1,52 @@
+//! `o` on a fact with a link hands the link to the system opener, here a script standing
+//! in for xdg-open. One test, as it sets $PATH.
+#![cfg(target_os = "linux")]
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{config::Severity, keys::Action, state::AppState};
+use std::{
+    fs,
+    os::unix::fs::PermissionsExt,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+#[test]
+fn link_goes_to_the_opener() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha"])
+        .facts("Alpha", &["Its flag is older than the state (source: https://example.org/alpha)."])
+        .write("fact-source")
+        .unwrap()
+        .dir;
+    let bin = dir.join("bin");
+    fs::create_dir_all(&bin).unwrap();
+    let opened = dir.join("opened.txt");
+    let opener = bin.join("xdg-open");
+    fs::write(&opener, format!("#!/bin/sh\nprintf '%s' \"$1\" > '{}'\n", opened.display())).unwrap();
+    fs::set_permissions(&opener, fs::Permissions::from_mode(0o755)).unwrap();
+    // SAFETY: the only test in this binary, so no other thread reads the environment
+    unsafe { std::env::set_var("PATH", format!("{}:{}", bin.display(), std::env::var("PATH").unwrap_or_default())) };
+
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|c| c == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+    }
+    state.handle_input(state.keys.key(Action::OpenSource).unwrap());
+    let notification = state.notification.as_ref().unwrap();
+    // Without the closing bracket and full stop after it
+    assert_eq!(notification.text, "Otwieram https://example.org/alpha");
+    assert_eq!(notification.severity, Severity::Info);
+    let start = Instant::now();
+    while !opened.exists() && start.elapsed() < Duration::from_secs(10) {
+        sleep(Duration::from_millis(10));
+    }
+    sleep(Duration::from_millis(50));
+    assert_eq!(fs::read_to_string(&opened).unwrap(), "https://example.org/alpha");
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,40 @@
+//! The indexed `MapView::feature_at` against the scan of every feature it replaces
+mod common;
+
+use common::{polygon_world, FixtureAtlas};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustatlas::{data::{DataCache, GeoLevel}, map_draw::MapView};
+
+/// Compare both lookups at `probes` seeded points over the whole globe
+fn assert_same_features(map: &MapView, probes: usize, seed: u64) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut hits = 0;
+    for _ in 0..probes {
+        let (x, y) = (rng.random_range(-180.0..180.0), rng.random_range(-90.0..90.0));
+        let indexed = map.feature_at(x, y);
+        assert_eq!(indexed, map.feature_at_exhaustive(x, y), "at ({}, {}), seed {}", x, y, seed);
+        hits += usize::from(indexed.is_some());
+    }
+    assert!(hits > probes / 10, "only {} of {} probes hit a feature, seed {}", hits, probes, seed);
+}
+
+#[test]
+fn indexed_matches_brute_force_on_synthetic_worlds() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("feature-at").unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    for seed in 0..8 {
+        let map = MapView::new(polygon_world(50 + 40 * seed as usize, 24, seed), &mut cache).unwrap();
+        assert_same_features(&map, 500, seed);
+    }
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn indexed_matches_brute_force_on_the_world_map() {
+    let mut cache = DataCache::new(concat!(env!("CARGO_MANIFEST_DIR"), "/data")).unwrap();
+    let raw = cache.load_geojson(&GeoLevel::World, "world").unwrap();
+    let map = MapView::new(raw, &mut cache).unwrap();
+    for seed in 0..4 {
+        assert_same_features(&map, 500, seed);
+    }
+}
@@ -0,0 +1,153 @@
+//! Map feature properties cut down to `KEPT_PROPERTIES` as they are parsed, by the full
+//! reader and by the streaming one alike, features without geometry included; and what
+//! is left standing in for a missing `country_info.json` entry in the info panel
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use geojson::{Feature, GeoJson};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    data::{keep_properties, stream_features, Properties, RenderHints},
+    keys::Action,
+    map_draw::{MapView, OutlierPolicy},
+    state::{AppState, Panel},
+    summary::{FallbackInfo, KEPT_PROPERTIES},
+    ui,
+};
+use serde_json::json;
+use std::{collections::BTreeMap, fs, time::Duration};
+
+/// Feature named `name` carrying Natural Earth's kind of properties, most of them unread
+fn rich(name: &str, ring: Vec<Vec<f64>>) -> Feature {
+    let mut feature = feature(name, ring);
+    let properties = feature.properties.as_mut().unwrap();
+    for (key, value) in [
+        ("NAME_LONG", json!(format!("Republic of {}", name))),
+        ("POP_EST", json!(38_000_000.0)),
+        ("POP_YEAR", json!(2019)),
+        ("SUBREGION", json!("Eastern Testland")),
+        ("ISO_A3", json!("-99")),
+        ("ADM0_A3", json!("TST")),
+        ("MAPCOLOR7", json!(3)),
+        ("WIKIDATAID", json!("Q36")),
+        ("NAME_ZH", json!("波兰")),
+        ("FCLASS_ISO", json!({"nested": [1, 2, {"deep": null}]})),
+    ] {
+        properties.insert(key.to_string(), value);
+    }
+    feature
+}
+
+fn kept(name: &str) -> Properties {
+    let mut expected = Properties::new();
+    expected.insert("ADMIN".into(), json!(name));
+    expected.insert("NAME_LONG".into(), json!(format!("Republic of {}", name)));
+    expected.insert("POP_EST".into(), json!(38_000_000.0));
+    expected.insert("POP_YEAR".into(), json!(2019));
+    expected.insert("SUBREGION".into(), json!("Eastern Testland"));
+    expected.insert("ISO_A3".into(), json!("-99"));
+    expected.insert("ADM0_A3".into(), json!("TST"));
+    expected
+}
+
+/// Alpha with its outline, Void with `"geometry": null`
+fn features() -> GeoJson {
+    let mut void = rich("Void", Vec::new());
+    void.geometry = None;
+    collection(vec![rich("Alpha", square_at(0.0, 0.0)), void])
+}
+
+#[test]
+fn only_the_kept_properties_are_left() {
+    let all = rich("Alpha", square_at(0.0, 0.0)).properties.unwrap();
+    assert_eq!(all.len(), 11);
+    let left = keep_properties(all);
+    assert_eq!(left, kept("Alpha"));
+    assert!(left.keys().all(|key| KEPT_PROPERTIES.contains(&key.as_str())));
+}
+
+#[test]
+fn streamed_with_the_same_properties() {
+    let path = std::env::temp_dir().join("rustatlas-properties-stream.geojson");
+    fs::write(&path, features().to_string()).unwrap();
+    let mut seen = Vec::new();
+    stream_features(&path, |name, properties, geometry| {
+        seen.push((name, properties, geometry.is_some()));
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(seen, [("Alpha".to_string(), kept("Alpha"), true), ("Void".to_string(), kept("Void"), false)]);
+
+    // Parsed whole or streamed, the map keeps them by any spelling of the name, the
+    // feature without geometry too
+    let hints = RenderHints::default();
+    let parsed = MapView::build(features(), BTreeMap::new(), &hints, OutlierPolicy::default()).unwrap();
+    let streamed = MapView::build_streamed(&path, BTreeMap::new(), &hints, OutlierPolicy::default()).unwrap();
+    for view in [parsed, streamed] {
+        assert_eq!(view.properties("ALPHA"), Some(&kept("Alpha")));
+        assert_eq!(view.properties("Void"), Some(&kept("Void")));
+        assert_eq!(view.properties("Beta"), None);
+    }
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn fallback_read_from_the_properties() {
+    assert_eq!(
+        FallbackInfo::from_properties(&kept("Alpha")),
+        Some(FallbackInfo {
+            name: "Republic of Alpha".to_string(),
+            population: Some((38_000_000, Some(2019))),
+            subregion: Some("Eastern Testland".to_string()),
+            continent: None,
+            // "-99" is no code: the next one is taken
+            iso_a3: Some("TST".to_string()),
+        })
+    );
+    let mut bare = Properties::new();
+    bare.insert("ADMIN".into(), json!("Alpha"));
+    bare.insert("POP_EST".into(), json!(-99));
+    assert_eq!(FallbackInfo::from_properties(&bare), None);
+    bare.insert("CONTINENT".into(), json!("Testland"));
+    let info = FallbackInfo::from_properties(&bare).unwrap();
+    assert_eq!((info.name.as_str(), info.population, info.continent.as_deref()), ("Alpha", None, Some("Testland")));
+}
+
+#[test]
+fn info_panel_falls_back_to_the_map_data() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("properties-fallback").unwrap().dir;
+    fs::write(dir.join("country_alpha.geojson"), collection(vec![rich("Alpha", square_at(0.0, 0.0))]).to_string()).unwrap();
+    fs::write(dir.join("country_info.json"), "{}").unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    let summary = state.summary.as_ref().unwrap();
+    assert!(summary.info.is_none());
+    assert_eq!(summary.fallback.as_ref().map(|fb| fb.name.as_str()), Some("Republic of Alpha"));
+
+    let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+    terminal.draw(|f| {
+        ui::draw(f, &state.view_model());
+    })
+    .unwrap();
+    let buffer = terminal.backend().buffer();
+    let text: String = (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect::<String>()
+        .replace('\u{a0}', " ");
+    for line in ["Populacja (szac. 2019):", "38.0 mln", "Region: Eastern Testland", "Kod ISO: TST", "(z danych mapy; brak wpisu"] {
+        assert!(text.contains(line), "no {:?} in:\n{}", line, text);
+    }
+    let _ = fs::remove_dir_all(&dir);
+}
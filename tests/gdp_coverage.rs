@@ -0,0 +1,100 @@
+//! GDP coverage per dataset country: what `GDPData::countries` reports, the summary and
+//! sparsest countries in the diagnostics overlay, and the full list in `check-data`
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{check, gdp_reader::GDPData, state::AppState, ui};
+use std::{fs, ops::RangeInclusive, path::PathBuf};
+
+/// Seven dataset rows of different lengths, one without values
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Empty"])
+        .gdp("Alpha", 1960..=2024, |_| 1e9)
+        .gdp("Beta", 2000..=2009, |_| 2e9)
+        .gdp("Gamma", 2020..=2022, |_| 3e9)
+        .gdp("Delta", 2023..=2023, |_| 4e9)
+        .gdp("Epsilon", 1990..=1992, |_| 5e9)
+        .gdp("Zeta", 2010..=2014, |_| 6e9)
+        // A row with every year blank
+        .gdp("Empty", RangeInclusive::new(1, 0), |_| 0.0)
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+#[test]
+fn coverage_per_row_in_file_order() {
+    let dir = atlas("coverage-rows");
+    let gdp = GDPData::new(dir.join("dataPKB/pkb.csv")).unwrap();
+    assert_eq!(gdp.len(), 7);
+    let coverage: Vec<_> = gdp.countries().collect();
+    assert_eq!(
+        coverage,
+        [
+            ("Alpha", 65, 1960..=2024),
+            ("Beta", 10, 2000..=2009),
+            ("Delta", 1, 2023..=2023),
+            ("Empty", 0, RangeInclusive::new(1, 0)),
+            ("Epsilon", 3, 1990..=1992),
+            ("Gamma", 3, 2020..=2022),
+            ("Zeta", 5, 2010..=2014),
+        ]
+    );
+    assert!(coverage[3].2.is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn diagnostics_summarize_and_list_the_sparsest() {
+    let dir = atlas("coverage-diagnostics");
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.show_diagnostics = true;
+    let mut terminal = Terminal::new(TestBackend::new(140, 50)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..50).map(|y| (0..140).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+    // The overlay's lines, cut from the panels around it
+    let lines: Vec<&str> = rows.iter().map(|row| row.split('│').map(str::trim).find(|cell| !cell.is_empty()).unwrap_or("")).collect();
+
+    let summary = lines.iter().find(|l| l.starts_with("GDP: ")).unwrap_or_else(|| panic!("{}", rows.join("\n")));
+    assert!(summary.starts_with("GDP: 7 krajów w ") && summary.ends_with(", 1 bez danych"), "{}", summary);
+    // The five with the fewest years, fewest first and ties by name; the empty row is
+    // counted above, not listed
+    let start = lines.iter().position(|l| *l == "Najmniej lat z danymi GDP:").unwrap();
+    assert_eq!(
+        lines[start + 1..start + 6],
+        ["Delta: 1 (2023–2023)", "Epsilon: 3 (1990–1992)", "Gamma: 3 (2020–2022)", "Zeta: 5 (2010–2014)", "Beta: 10 (2000–2009)"]
+    );
+    assert!(!lines[start + 6].starts_with("Alpha"), "{}", lines[start + 6]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn check_data_lists_every_row() {
+    let dir = atlas("coverage-check");
+    let report = check::report(&dir).unwrap();
+    let lines: Vec<&str> = report.text.lines().collect();
+    assert!(lines[0].ends_with("pkb.csv: 7 countries, 1 without values"), "{}", report.text);
+    let rows: Vec<String> = lines[1..8].iter().map(|l| l.split_whitespace().collect::<Vec<_>>().join(" ")).collect();
+    assert_eq!(
+        rows,
+        [
+            "Alpha 65 years 1960-2024",
+            "Beta 10 years 2000-2009",
+            "Delta 1 years 2023-2023",
+            "Empty no values",
+            "Epsilon 3 years 1990-1992",
+            "Gamma 3 years 2020-2022",
+            "Zeta 5 years 2010-2014",
+        ]
+    );
+    // The empty row's country counts as one without GDP data
+    assert!(report.text.contains("1 of 7 map countries have no GDP data\n  Empty\n"), "{}", report.text);
+    let _ = fs::remove_dir_all(&dir);
+}
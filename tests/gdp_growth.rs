@@ -0,0 +1,118 @@
+//! The GDP change against the previous year with data: taken across a gap in the series
+//! and naming the year it compares with, none for a series' first year, and drawn in the
+//! right panel green, red or gray
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Color, Terminal};
+use rustatlas::{
+    gdp_reader::{GDPData, Growth},
+    keys::Action,
+    state::{AppState, GdpStatus, Location, Panel},
+    theme::{self, Theme, UNICODE},
+    ui,
+};
+use std::{collections::BTreeMap, fs, path::PathBuf, time::Duration};
+
+fn series(values: &[(u16, f64)]) -> BTreeMap<u16, f64> {
+    values.iter().copied().collect()
+}
+
+#[test]
+fn latest_against_the_year_before_it_with_data() {
+    let growth = GDPData::year_over_year(&series(&[(2020, 100.0), (2021, 110.0)])).unwrap();
+    assert_eq!(growth.previous_year, 2020);
+    assert!((growth.percent - 10.0).abs() < 1e-9);
+    // Only the last two years count
+    let growth = GDPData::year_over_year(&series(&[(2019, 1.0), (2020, 200.0), (2021, 150.0)])).unwrap();
+    assert_eq!(growth.previous_year, 2020);
+    assert!((growth.percent + 25.0).abs() < 1e-9);
+
+    // A gap: compared with the last year before it, not the missing one
+    let growth = GDPData::year_over_year(&series(&[(2017, 80.0), (2018, 100.0), (2021, 120.0)])).unwrap();
+    assert_eq!(growth.previous_year, 2018);
+    assert!((growth.percent - 20.0).abs() < 1e-9);
+
+    // The first year has nothing to compare with; nor has a zero
+    assert_eq!(GDPData::year_over_year(&series(&[(2021, 120.0)])), None);
+    assert_eq!(GDPData::year_over_year(&BTreeMap::new()), None);
+    assert_eq!(GDPData::year_over_year(&series(&[(2020, 0.0), (2021, 5.0)])), None);
+    assert_eq!(
+        GDPData::year_over_year(&series(&[(2020, 5.0), (2021, 0.0)])),
+        Some(Growth { previous_year: 2020, percent: -100.0 })
+    );
+}
+
+/// Growing with 2019 and 2020 missing, shrinking, flat and a single year
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Gap", "Shrink", "Flat", "Single"])
+        .country("Gap", square_at(0.0, 0.0))
+        .country("Shrink", square_at(5.0, 0.0))
+        .country("Flat", square_at(10.0, 0.0))
+        .country("Single", square_at(15.0, 0.0))
+        .gdp("Gap", 2015..=2021, |y| if y == 2021 { 1.5e9 } else { 1e9 })
+        .gdp_gap("Gap", 2019..=2020)
+        .gdp("Shrink", 2020..=2021, |y| if y == 2021 { 9e8 } else { 1e9 })
+        .gdp("Flat", 2020..=2021, |_| 1e9)
+        .gdp("Single", 2021..=2021, |_| 1e9)
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Open `country` of Testland from the world map and draw the screen
+fn show(state: &mut AppState, country: &str) -> Buffer {
+    state.go_up_to(Location::World);
+    for place in ["Testland", country] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+    terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The growth line: its text from "vs " to the end of the panel, and its color
+fn growth_line(buffer: &Buffer) -> (String, Color) {
+    let area = buffer.area;
+    for y in area.top()..area.bottom() {
+        let row: Vec<&str> = (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect();
+        if let Some(x) = (0..row.len()).find(|&x| row[x..].concat().starts_with("vs ")) {
+            let text: String = row[x..].concat();
+            let text = text.split('│').next().unwrap().trim_end().to_string();
+            return (text, buffer[(x as u16, y)].fg);
+        }
+    }
+    panic!("no growth line");
+}
+
+#[test]
+fn drawn_with_its_color_in_the_right_panel() {
+    let dir = atlas("gdp-growth");
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.theme = Theme::new(state.theme.support()).with_symbols(&UNICODE);
+    let color = |rgb| state.theme.color(rgb);
+    let (growth, decline, muted) = (color(theme::GROWTH), color(theme::DECLINE), color(theme::MUTED));
+
+    let buffer = show(&mut state, "Gap");
+    assert_eq!(growth_line(&buffer), ("vs 2018: +50.0% ▲".to_string(), growth));
+    let buffer = show(&mut state, "Shrink");
+    assert_eq!(growth_line(&buffer), ("vs 2020: -10.0% ▼".to_string(), decline));
+    let buffer = show(&mut state, "Flat");
+    assert_eq!(growth_line(&buffer), ("vs 2020: +0.0%".to_string(), muted));
+
+    let buffer = show(&mut state, "Single");
+    assert!(matches!(state.gdp_status, GdpStatus::Found { growth: None, .. }));
+    assert_eq!(growth_line(&buffer), ("vs poprzedni rok: brak danych".to_string(), muted));
+    let _ = fs::remove_dir_all(&dir);
+}
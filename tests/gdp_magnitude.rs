@@ -0,0 +1,107 @@
+//! GDP values checked against a plausible range: the median latest value outside it
+//! reported with the power of 1000 that would bring it back, `scale` in the sidecar
+//! `pkb.meta.json` applied on load, and a sidecar that doesn't parse refused
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    check,
+    config::Severity,
+    gdp_reader::{check_magnitude, GDPData, Metadata, GDP_CSV},
+    state::AppState,
+};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn median_against_the_range() {
+    let plausible = Metadata::default().plausible;
+    assert_eq!(plausible, [1e7, 1e14]);
+    assert_eq!(Metadata::default().scale, 1.0);
+    assert_eq!(check_magnitude(&[], plausible), None);
+    assert_eq!(check_magnitude(&[f64::NAN, 0.0, -5.0], plausible), None);
+    // Inside, edges included
+    assert_eq!(check_magnitude(&[1e7, 5e11, 1e14], plausible), None);
+    assert_eq!(check_magnitude(&[1.0, 2.0, 1e7, 1e7, 1e7], plausible), None);
+
+    // In millions: the median 7.2e4 wants a million to reach the range's middle (1e10.5)
+    let magnitude = check_magnitude(&[3e3, 7.2e4, 5e5], plausible).unwrap();
+    assert_eq!((magnitude.median, magnitude.plausible, magnitude.suggested_scale), (7.2e4, plausible, 1e6));
+    // In thousands, and too large
+    assert_eq!(check_magnitude(&[1e6, 5e6, 9e6], plausible).unwrap().suggested_scale, 1e3);
+    assert_eq!(check_magnitude(&[2e17, 3e17], plausible).unwrap().suggested_scale, 1e-6);
+    // An even count takes the upper middle value
+    assert_eq!(check_magnitude(&[1.0, 2.0, 3.0, 4.0], [10.0, 100.0]).unwrap().median, 3.0);
+}
+
+/// Testland with three GDP rows in millions, and `meta` as the sidecar if given
+fn atlas(name: &str, meta: Option<&str>) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .gdp("Alpha", 2019..=2020, |y| if y == 2020 { 7.2e4 } else { 7e4 })
+        .gdp("Beta", 2020..=2020, |_| 3e3)
+        .gdp("Gamma", 2020..=2020, |_| 5e5)
+        .write(name)
+        .unwrap()
+        .dir;
+    if let Some(meta) = meta {
+        fs::write(dir.join("dataPKB/pkb.meta.json"), meta).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn reported_where_the_user_looks() {
+    let dir = atlas("magnitude-reported", None);
+    let gdp = GDPData::new(dir.join(GDP_CSV)).unwrap();
+    assert_eq!(gdp.magnitude().unwrap().suggested_scale, 1e6);
+    assert_eq!(Metadata::path(&dir.join(GDP_CSV)), dir.join("dataPKB/pkb.meta.json"));
+
+    let report = check::report(&dir).unwrap();
+    assert!(
+        report.text.contains("median latest value 7.2e4 USD is outside the plausible range 1e7-1e14")
+            && report.text.contains("set \"scale\": 1e6 in "),
+        "report:\n{}",
+        report.text
+    );
+    let _ = fs::remove_file(dir.join("session.json"));
+    let state = AppState::open(&dir, false).unwrap();
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.severity, Severity::Error);
+    assert!(notification.text.starts_with("GDP w złych jednostkach? Mediana 7.2e4 USD"), "{}", notification.text);
+    assert!(notification.text.contains("ustaw \"scale\": 1e6 w "), "{}", notification.text);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn scale_applied_on_load() {
+    let dir = atlas("magnitude-scaled", Some(r#"{"scale": 1e6}"#));
+    let gdp = GDPData::new(dir.join(GDP_CSV)).unwrap();
+    assert_eq!(gdp.metadata().scale, 1e6);
+    assert_eq!(gdp.get_latest_gdp("Alpha"), Some((2020, 7.2e10)));
+    assert_eq!(gdp.series("Alpha").unwrap(), [(2019, 7e10), (2020, 7.2e10)]);
+    assert!(gdp.magnitude().is_none());
+    assert!(!check::report(&dir).unwrap().text.contains("WARNING: median"));
+    let _ = fs::remove_dir_all(&dir);
+
+    // A narrower range of its own: the median just under it, no power of 1000 gets closer
+    let dir = atlas("magnitude-range", Some(r#"{"plausible": [1e5, 1e6]}"#));
+    let gdp = GDPData::new(dir.join(GDP_CSV)).unwrap();
+    assert_eq!((gdp.metadata().plausible, gdp.metadata().scale), ([1e5, 1e6], 1.0));
+    assert_eq!(gdp.magnitude().unwrap().suggested_scale, 1e0);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn broken_sidecar_is_an_error() {
+    let dir = atlas("magnitude-broken", Some(r#"{"scale": "million"}"#));
+    let error = GDPData::new(dir.join(GDP_CSV)).err().unwrap();
+    assert!(error.to_string().contains("pkb.meta.json"), "{}", error);
+
+    // The TUI goes on without GDP and says why
+    let _ = fs::remove_file(dir.join("session.json"));
+    let state = AppState::open(&dir, false).unwrap();
+    assert!(state.gdp_data.is_none());
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("Nie wczytano GDP: "), "{}", notification.text);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,99 @@
+//! Allocations made loading the GDP CSV, against the year map per country it used to be
+//! read into
+mod common;
+
+use common::alloc::{measure, Counting};
+use rustatlas::gdp_reader::GDPData;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{self, File},
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+const ROWS: usize = 266;
+
+/// Rows as the earlier loader kept them: name and year map, in file order
+type YearMaps = Vec<(String, BTreeMap<u16, f64>)>;
+
+/// The earlier loader, step for step: each line read into its own String and split into
+/// a Vec of fields, a `BTreeMap<u16, f64>` of the values per country, and a lowercase
+/// name -> row index
+fn year_maps(path: &Path) -> (YearMaps, HashMap<String, usize>) {
+    let mut lines = BufReader::new(File::open(path).unwrap()).lines();
+    for _ in 0..5 {
+        let _ = lines.next();
+    }
+    let mut countries = Vec::new();
+    let mut index = HashMap::new();
+    for line in lines.map_while(Result::ok) {
+        let parts: Vec<&str> = line.split(',').collect();
+        if parts.len() < 5 {
+            continue;
+        }
+        let name = parts[0].trim_matches('"');
+        let mut years = BTreeMap::new();
+        for (i, raw) in parts.iter().enumerate().skip(4) {
+            let year = 1960 + (i - 4);
+            if year > 2024 {
+                break;
+            }
+            if let Ok(value) = raw.trim_matches('"').parse::<f64>() {
+                years.insert(year as u16, value);
+            }
+        }
+        index.insert(name.to_lowercase(), countries.len());
+        countries.push((name.to_string(), years));
+    }
+    (countries, index)
+}
+
+#[test]
+fn flat_storage_allocates_a_fraction_of_year_maps() {
+    let path = std::env::temp_dir().join("rustatlas-gdp-memory.csv");
+    fs::write(&path, common::gdp_csv(ROWS, 5)).unwrap();
+
+    let (gdp, flat) = measure(|| GDPData::new(&path).unwrap());
+    let (maps, per_country) = measure(|| year_maps(&path));
+    assert_eq!(gdp.len(), ROWS);
+    assert_eq!(maps.0.len(), ROWS);
+    // A name, its lowercase and code keys and the index's growth per row, against a line,
+    // a field list and a handful of B-tree nodes per row
+    assert!(flat.allocations < 6 * ROWS, "{} allocations for {} rows", flat.allocations, ROWS);
+    assert!(
+        flat.allocations * 4 < per_country.allocations,
+        "flat {} allocations, year maps {}",
+        flat.allocations,
+        per_country.allocations
+    );
+    assert!(flat.live < per_country.live, "flat keeps {} bytes, year maps {}", flat.live, per_country.live);
+    assert!(flat.peak < per_country.peak, "flat peak {} bytes, year maps {}", flat.peak, per_country.peak);
+    // The same values come back out
+    for (name, years) in &maps.0 {
+        assert_eq!(gdp.get_all_gdp_data(name), Some(years), "{}", name);
+    }
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn rows_alike_in_name_stay_apart() {
+    let path = std::env::temp_dir().join("rustatlas-gdp-congos.csv");
+    let csv = "\"Data Source\",\"Fixture\",\n\n\"Last Updated Date\",\"2025-01-01\",\n\n\
+        \"Country Name\",\"Country Code\",\"Indicator Name\",\"Indicator Code\",\"1960\",\"1961\",\n\
+        \"Congo, Dem. Rep.\",\"COD\",\"GDP (current US$)\",\"NY.GDP.MKTP.CD\",\"1\",\"2\",\n\
+        \"Congo, Rep.\",\"COG\",\"GDP (current US$)\",\"NY.GDP.MKTP.CD\",\"3\",\"\",\n";
+    fs::write(&path, csv).unwrap();
+    let gdp = GDPData::new(&path).unwrap();
+
+    let names: Vec<_> = gdp.countries().collect();
+    assert_eq!(names, [("Congo, Dem. Rep.", 2, 1960..=1961), ("Congo, Rep.", 1, 1960..=1960)]);
+    assert_eq!(gdp.series("COD"), Some(&[(1960, 1.0), (1961, 2.0)][..]));
+    assert_eq!(gdp.series("cog"), Some(&[(1960, 3.0)][..]));
+    assert_eq!(gdp.series("Congo, Rep."), gdp.series("COG"));
+    // A name matching both by substring finds the first row, as an exact name would
+    assert_eq!(gdp.series("Congo"), gdp.series("COD"));
+    let _ = fs::remove_file(&path);
+}
@@ -0,0 +1,47 @@
+//! A GDP CSV that can't be read to the end fails the load instead of keeping the rows
+//! before the bad line, and the atlas says so at startup
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{config::Severity, gdp_reader::GDPData, state::AppState};
+use std::{fs, io::ErrorKind};
+
+#[test]
+fn invalid_utf8_fails_the_load() {
+    let fixture = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Alpha", 2000..=2001, |_| 1e9)
+        .gdp("Beta", 2000..=2001, |_| 2e9)
+        .write("gdp-invalid-utf8")
+        .unwrap();
+    let csv = fixture.dir.join("dataPKB/pkb.csv");
+    assert_eq!(GDPData::new(&csv).unwrap().get_latest_gdp("Beta"), Some((2001, 2e9)));
+
+    // A Latin-2 byte on the second row: the first row alone would load
+    let mut bytes = fs::read(&csv).unwrap();
+    let beta = bytes.windows(6).position(|w| w == b"\"Beta\"").unwrap();
+    bytes[beta + 2] = 0xEA;
+    fs::write(&csv, &bytes).unwrap();
+    let error = GDPData::new(&csv).err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    let _ = fs::remove_file(fixture.dir.join("session.json"));
+    let state = AppState::open(&fixture.dir, false).unwrap();
+    assert!(state.gdp_data.is_none());
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("Nie wczytano GDP: "), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn read_error_fails_the_load() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("gdp-read-error").unwrap();
+    // Opened, but every read fails
+    let csv = fixture.dir.join("dataPKB/pkb.csv");
+    fs::remove_file(&csv).unwrap();
+    fs::create_dir(&csv).unwrap();
+    let error = GDPData::new(&csv).err().unwrap();
+    assert_ne!(error.kind(), ErrorKind::NotFound);
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
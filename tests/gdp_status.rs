@@ -0,0 +1,104 @@
+//! The GDP panel in each of its states, drawn from the view model: nothing selected, the
+//! dataset not loaded, the country not in it, and its latest value found
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, GdpStatus, Panel},
+    ui,
+};
+use std::{fs, path::Path};
+
+/// The atlas in `dir`, with nothing on screen but the view
+fn open_atlas(dir: &Path) -> AppState {
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state
+}
+
+/// Open `item` from the list
+fn open(state: &mut AppState, item: &str) {
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+}
+
+/// The GDP panel's text, its wrapped lines joined by spaces
+fn gdp_panel(state: &AppState) -> String {
+    let (width, height) = (160, 48);
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<Vec<&str>> = (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+    let (top, left) = rows.iter().enumerate()
+        .find_map(|(y, row)| row.windows(4).position(|w| w.concat() == "┌GDP").map(|x| (y, x)))
+        .expect("no GDP panel");
+    let right = left + rows[top][left..].iter().position(|&c| c == "┐").unwrap();
+    let lines = rows[top + 1..].iter().take_while(|row| row[left] != "└");
+    lines.map(|row| row[left + 1..right].concat().trim().to_string()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ")
+}
+
+fn fixture() -> FixtureAtlas {
+    FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Freedonia"])
+        .gdp("Poland", 2020..=2022, |year| f64::from(year - 2000) * 1e11)
+}
+
+#[test]
+fn no_selection() {
+    let dir = fixture().write("gdp-status-none").unwrap().dir;
+    let mut state = open_atlas(&dir);
+    open(&mut state, "Europe");
+    assert!(matches!(state.gdp_status, GdpStatus::NoSelection));
+    let panel = gdp_panel(&state);
+    assert!(panel.contains("Wybierz kraj aby zobaczyć dane GDP"), "{}", panel);
+    // The continent's totals instead
+    assert!(panel.contains("GDP łącznie:"), "{}", panel);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn dataset_missing() {
+    let dir = fixture().write("gdp-status-missing").unwrap().dir;
+    fs::remove_file(dir.join("dataPKB/pkb.csv")).unwrap();
+    let mut state = open_atlas(&dir);
+    open(&mut state, "Europe");
+    open(&mut state, "Poland");
+    assert!(matches!(state.gdp_status, GdpStatus::DatasetMissing(_)));
+    let panel = gdp_panel(&state);
+    assert!(panel.contains("Nie wczytano danych GDP"), "{}", panel);
+    assert!(panel.contains("pkb.csv"), "{}", panel);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn not_found() {
+    let dir = fixture().write("gdp-status-not-found").unwrap().dir;
+    let mut state = open_atlas(&dir);
+    open(&mut state, "Europe");
+    open(&mut state, "Freedonia");
+    assert!(matches!(&state.gdp_status, GdpStatus::NotFound(name) if name == "Freedonia"));
+    let panel = gdp_panel(&state);
+    // With the alias picker's key, as bound
+    assert_eq!(panel, "Brak danych GDP dla 'Freedonia' a: wybierz jego nazwę w danych");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn found() {
+    let dir = fixture().write("gdp-status-found").unwrap().dir;
+    let mut state = open_atlas(&dir);
+    open(&mut state, "Europe");
+    open(&mut state, "Poland");
+    assert!(matches!(&state.gdp_status, GdpStatus::Found { year, .. } if year == "2022"));
+    let panel = gdp_panel(&state);
+    assert!(panel.contains("GDP (2022): "), "{}", panel);
+    assert!(panel.contains("Wciśnij tab aby zobaczyć wykres!"), "{}", panel);
+    assert!(!panel.contains("Brak danych GDP"), "{}", panel);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,97 @@
+//! Help generated from the key bindings: the status bar hints and the `?` overlay
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    data::GeoLevel,
+    keys::{Action, Context, KeyBindings},
+    state::{AppState, Panel},
+    ui,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+fn world() -> Context {
+    Context {
+        level: GeoLevel::World,
+        chart: false,
+        compare: false,
+        gdp_missing: false,
+        choropleth: false,
+        panel: Panel::Left,
+        ranking: false,
+        zoomed: false,
+        territory: false,
+        territories: false,
+    }
+}
+
+/// Rows of the screen after drawing `state` at `width` x `height`
+fn screen(state: &AppState, width: u16, height: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+}
+
+#[test]
+fn every_action_is_described() {
+    let mut hints = BTreeSet::new();
+    for action in Action::ALL {
+        assert!(!action.description().is_empty(), "{:?} has no description", action);
+        assert!(!action.hint().is_empty(), "{:?} has no hint", action);
+        assert!(action.hint().chars().count() <= 24, "{:?}'s hint is too long for the status bar", action);
+        assert!(hints.insert(action.hint()), "{:?} shares its hint", action);
+    }
+}
+
+#[test]
+fn default_hints_name_the_keys() {
+    let (keys, invalid) = KeyBindings::new(&BTreeMap::new());
+    assert!(invalid.is_empty());
+    let hints = keys.hints(&world());
+    for expected in ["↑/↓: ruch", "Enter: otwórz", "Tab: panel", "?: pomoc", "q: wyjście"] {
+        assert!(hints.iter().any(|h| h == expected), "{:?} not in {:?}", expected, hints);
+    }
+    // Country-only actions aren't offered on the world map, Back is once there is a level above
+    assert!(!hints.iter().any(|h| h.ends_with(Action::NextFact.hint())), "{:?}", hints);
+    let continent = keys.hints(&Context { level: GeoLevel::Continent, ..world() });
+    assert!(continent.iter().any(|h| h == "Esc/Backspace: wstecz"), "{:?}", continent);
+}
+
+#[test]
+fn rebinding_changes_the_hints() {
+    let overrides = BTreeMap::from([(Action::Quit, vec!["x".to_string()]), (Action::Down, vec!["j".to_string()])]);
+    let (keys, _) = KeyBindings::new(&overrides);
+    let hints = keys.hints(&world());
+    assert!(hints.iter().any(|h| h == "x: wyjście"), "{:?}", hints);
+    assert!(!hints.iter().any(|h| h.starts_with("q:")), "{:?}", hints);
+    assert!(hints.iter().any(|h| h == "↑/j: ruch"), "{:?}", hints);
+}
+
+#[test]
+fn status_bar_and_overlay_follow_the_bindings() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("help-screen").unwrap();
+    let mut state = AppState::open(&fixture.dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+
+    // At 160 columns the status bar has room for a good part of the world's hints
+    let status = screen(&state, 160, 50).pop().unwrap();
+    let shown = keys_shown(&state, &status);
+    assert!(shown >= 10, "only {} hints fit: {}", shown, status.trim_end());
+
+    state.handle_input(state.keys.key(Action::Help).unwrap());
+    let rows = screen(&state, 160, 60);
+    for action in [Action::Select, Action::Quit] {
+        let line = format!("{:>12}  {}", state.keys.label(action), action.description());
+        assert!(rows.iter().any(|row| row.contains(&line)), "{:?} not in the overlay:\n{}", line, rows.join("\n"));
+    }
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+/// Hints of the current bindings shown whole in `status`
+fn keys_shown(state: &AppState, status: &str) -> usize {
+    state.keys.hints(&state.view_model().context).iter().filter(|h| status.contains(h.as_str())).count()
+}
@@ -0,0 +1,151 @@
+//! The strip under the map describing the country under the mouse pointer: the summaries
+//! kept for the last few countries hovered, and the strip clearing once the pointer
+//! leaves the map
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    data::DataCache,
+    gdp_reader::{GDPData, GDP_CSV},
+    keys::Action,
+    state::{AppState, MouseInput},
+    summary::SummaryCache,
+    ui,
+};
+use std::{path::PathBuf, thread, time::Duration};
+
+const COUNTRIES: [&str; 10] = ["Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa"];
+
+/// Square of `size` degrees with its south-west corner at (`x`, `y`)
+fn square(x: f64, y: f64, size: f64) -> Vec<Vec<f64>> {
+    vec![vec![x, y], vec![x + size, y], vec![x + size, y + size], vec![x, y + size], vec![x, y]]
+}
+
+/// Testland of ten countries with GDP rows, Alpha and Beta large enough to point at
+fn atlas(name: &str) -> PathBuf {
+    let mut atlas = FixtureAtlas::new()
+        .continent("Testland", COUNTRIES)
+        .country("Alpha", square(0.0, 40.0, 10.0))
+        .country("Beta", square(12.0, 40.0, 10.0));
+    for (i, country) in COUNTRIES.into_iter().enumerate() {
+        atlas = atlas.gdp(country, 2000..=2001, move |_| 1e9 * (i + 1) as f64);
+    }
+    atlas.write(name).unwrap().dir
+}
+
+/// Names the cache holds a summary for, in `COUNTRIES` order
+fn cached(summaries: &SummaryCache) -> Vec<&'static str> {
+    COUNTRIES.into_iter().filter(|c| summaries.peek(c).is_some()).collect()
+}
+
+#[test]
+fn keeps_the_last_eight_hovered() {
+    let dir = atlas("hover-lru");
+    let cache = DataCache::new(&dir).unwrap();
+    let gdp = GDPData::new(dir.join(GDP_CSV)).unwrap();
+    let mut summaries = SummaryCache::default();
+    for country in &COUNTRIES[..8] {
+        summaries.get_or_build(country, &cache, Some(&gdp));
+    }
+    assert_eq!(cached(&summaries).len(), SummaryCache::CAPACITY);
+
+    // Alpha used again is the most recent: Beta, now the least recent, goes first
+    summaries.get_or_build("Alpha", &cache, Some(&gdp));
+    summaries.get_or_build("Iota", &cache, Some(&gdp));
+    assert_eq!(cached(&summaries), ["Alpha", "Gamma", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota"]);
+    summaries.get_or_build("Kappa", &cache, Some(&gdp));
+    assert_eq!(cached(&summaries), ["Alpha", "Delta", "Epsilon", "Zeta", "Eta", "Theta", "Iota", "Kappa"]);
+
+    // A hit isn't looked up again: without the dataset it still has the GDP found before
+    let hit = summaries.get_or_build("alpha", &cache, None);
+    assert_eq!(hit.name, "Alpha");
+    assert!(hit.gdp.is_some());
+    // …while a miss is, and takes the place of the least recent, Delta
+    assert!(summaries.get_or_build("Beta", &cache, None).gdp.is_none());
+    assert!(summaries.peek("Delta").is_none());
+    // Peeking doesn't make anything more recent
+    assert!(summaries.peek("Epsilon").is_some());
+    summaries.get_or_build("Gamma", &cache, None);
+    assert!(summaries.peek("Epsilon").is_none());
+
+    summaries.clear();
+    assert!(cached(&summaries).is_empty());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Press the key bound to `action`, waiting for a map loading in the background
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// Draw a frame and note where the map went, as the main loop does; the screen's rows
+fn draw(state: &mut AppState) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    state.apply_drawn(drawn.unwrap());
+    let buffer = terminal.backend().buffer();
+    (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+}
+
+/// A cell over `country` on the drawn map
+fn cell_over(state: &mut AppState, country: &str) -> (u16, u16) {
+    for row in 0..40 {
+        for column in 0..120 {
+            state.handle_mouse(MouseInput::Move { column, row });
+            if state.map_hover.as_deref() == Some(country) {
+                return (column, row);
+            }
+        }
+    }
+    panic!("{} not on the map", country)
+}
+
+#[test]
+fn strip_follows_the_pointer_and_clears_off_the_map() {
+    let dir = atlas("hover-strip");
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    press(&mut state, Action::Select);
+    state.notification = None;
+    let before = draw(&mut state);
+    let selected = state.selected;
+    // Columns from the right panel's left border on
+    let left = before.iter().find_map(|line| line.find("┌Informacje").map(|at| line[..at].chars().count())).unwrap();
+    let right_panel = |screen: &[String]| screen.iter().map(|line| line.chars().skip(left).collect::<String>()).collect::<Vec<_>>();
+
+    let (column, row) = cell_over(&mut state, "Beta");
+    let screen = draw(&mut state);
+    assert!(screen.iter().any(|line| line.contains("Beta City")), "{}", screen.join("\n"));
+    assert!(state.view_model().map_hover.is_some());
+    // Hovering changes neither the selection nor the right panel
+    assert_eq!(state.selected, selected);
+    assert_eq!(right_panel(&screen), right_panel(&before));
+
+    // Off the map, over the list
+    state.handle_mouse(MouseInput::Move { column: 2, row: 5 });
+    assert_eq!(state.map_hover, None);
+    assert!(state.view_model().map_hover.is_none());
+    assert!(!draw(&mut state).iter().any(|line| line.contains("Beta City")));
+
+    // Back over the map, then the map goes away under an overlay
+    state.handle_mouse(MouseInput::Move { column, row });
+    assert_eq!(state.map_hover.as_deref(), Some("Beta"));
+    state.show_help = true;
+    state.handle_mouse(MouseInput::Move { column, row });
+    assert_eq!(state.map_hover, None);
+    state.show_help = false;
+
+    // Leaving the place clears it too
+    state.handle_mouse(MouseInput::Move { column, row });
+    press(&mut state, Action::Back);
+    assert_eq!(state.map_hover, None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
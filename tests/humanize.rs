@@ -0,0 +1,133 @@
+//! Counted nouns in both languages, and populations and areas at the magnitude boundaries
+use rustatlas::humanize::{
+    format_area, format_count, format_population, group_thousands, Lang, Noun, COUNTRIES, KRAJE, LIST_ENTRIES, OBIEKTY,
+};
+
+const NBSP: char = '\u{a0}';
+
+/// Polish form by the grammar rule, worked out from the digits: one for exactly 1; few
+/// when the last digit is 2, 3 or 4 and the tens digit isn't 1; many otherwise
+fn polish_form(n: usize) -> &'static str {
+    let (units, tens) = (n % 10, n / 10 % 10);
+    match n {
+        1 => "one",
+        _ if (2..=4).contains(&units) && tens != 1 => "few",
+        _ => "many",
+    }
+}
+
+fn form_name(noun: &Noun, n: usize) -> &'static str {
+    let form = noun.form(n);
+    if form == noun.one {
+        "one"
+    } else if form == noun.few {
+        "few"
+    } else {
+        "many"
+    }
+}
+
+#[test]
+fn polish_counts_0_to_200() {
+    for n in 0..=200 {
+        assert_eq!(form_name(&KRAJE, n), polish_form(n), "{}", n);
+    }
+    // Spelled out, so the rule above isn't only checked against itself
+    let table = [
+        (0, "0 krajów"),
+        (1, "1 kraj"),
+        (2, "2 kraje"),
+        (3, "3 kraje"),
+        (4, "4 kraje"),
+        (5, "5 krajów"),
+        (10, "10 krajów"),
+        (20, "20 krajów"),
+        (21, "21 krajów"),
+        (22, "22 kraje"),
+        (24, "24 kraje"),
+        (25, "25 krajów"),
+        (101, "101 krajów"),
+        (102, "102 kraje"),
+        (111, "111 krajów"),
+        (122, "122 kraje"),
+        (200, "200 krajów"),
+    ];
+    for (n, text) in table {
+        assert_eq!(format_count(n, &KRAJE), text);
+    }
+    assert_eq!(format_count(1, &OBIEKTY), "1 obiekt");
+    assert_eq!(format_count(2, &OBIEKTY), "2 obiekty");
+    assert_eq!(format_count(5, &OBIEKTY), "5 obiektów");
+}
+
+#[test]
+fn polish_teens_take_the_many_form() {
+    for hundreds in [0, 100, 200, 1000] {
+        for teen in 11..=14 {
+            let n = hundreds + teen;
+            assert_eq!(format_count(n, &KRAJE), format!("{} krajów", n));
+        }
+    }
+    // …while the same last digits outside the teens take the few form
+    for n in [2, 3, 4, 32, 33, 34, 102, 1_000_002] {
+        assert_eq!(format_count(n, &KRAJE), format!("{} kraje", n));
+    }
+}
+
+#[test]
+fn english_counts() {
+    assert_eq!(format_count(0, &COUNTRIES), "0 countries");
+    assert_eq!(format_count(1, &COUNTRIES), "1 country");
+    assert_eq!(format_count(2, &COUNTRIES), "2 countries");
+    for n in (2..=200).chain([11, 12, 13, 14, 21, 22, 101]) {
+        assert_eq!(format_count(n, &COUNTRIES), format!("{} countries", n));
+        assert_eq!(format_count(n, &LIST_ENTRIES), format!("{} list entries", n));
+    }
+    assert_eq!(format_count(1, &LIST_ENTRIES), "1 list entry");
+}
+
+#[test]
+fn thousands_grouping() {
+    assert_eq!(group_thousands(0, Lang::En), "0");
+    assert_eq!(group_thousands(999, Lang::En), "999");
+    assert_eq!(group_thousands(1000, Lang::En), "1,000");
+    assert_eq!(group_thousands(38_000_000, Lang::En), "38,000,000");
+    // Polish leaves four digits alone and groups with a non-breaking space from five
+    assert_eq!(group_thousands(9999, Lang::Pl), "9999");
+    assert_eq!(group_thousands(10_000, Lang::Pl), format!("10{NBSP}000"));
+    assert_eq!(group_thousands(38_000_000, Lang::Pl), format!("38{NBSP}000{NBSP}000"));
+}
+
+#[test]
+fn populations_at_the_unit_boundaries() {
+    let table: [(u64, &str, String); 11] = [
+        (0, "0", "0".to_string()),
+        (1, "1", "1".to_string()),
+        (999_499, "999,499", format!("999{NBSP}499")),
+        // Rounded to three digits before the unit is picked
+        (999_500, "1.00 M", format!("1.00{NBSP}mln")),
+        (1_000_000, "1.00 M", format!("1.00{NBSP}mln")),
+        (9_994_999, "9.99 M", format!("9.99{NBSP}mln")),
+        (9_995_000, "10.0 M", format!("10.0{NBSP}mln")),
+        (38_000_000, "38.0 M", format!("38.0{NBSP}mln")),
+        (999_499_999, "999 M", format!("999{NBSP}mln")),
+        (999_500_000, "1.00 bn", format!("1.00{NBSP}mld")),
+        (1_440_000_000, "1.44 bn", format!("1.44{NBSP}mld")),
+    ];
+    for (n, en, pl) in table {
+        assert_eq!(format_population(n, Lang::En), en, "{}", n);
+        assert_eq!(format_population(n, Lang::Pl), pl, "{}", n);
+    }
+}
+
+#[test]
+fn areas_at_the_rounding_boundaries() {
+    assert_eq!(format_area(0.0, Lang::En), "0.0 km²");
+    assert_eq!(format_area(0.44, Lang::En), "0.4 km²");
+    assert_eq!(format_area(9.94, Lang::En), "9.9 km²");
+    assert_eq!(format_area(9.95, Lang::En), "10 km²");
+    assert_eq!(format_area(1234.4, Lang::En), "1,234 km²");
+    assert_eq!(format_area(1234.4, Lang::Pl), format!("1234{NBSP}km²"));
+    assert_eq!(format_area(312_679.0, Lang::Pl), format!("312{NBSP}679{NBSP}km²"));
+    assert_eq!(format_area(17_098_246.0, Lang::En), "17,098,246 km²");
+}
@@ -0,0 +1,143 @@
+//! The history of the text inputs: the order Up/Down recall entries in, what is
+//! recorded (no empty, cancelled or repeated inputs, at most `History::LIMIT`), typing
+//! leaving the recall, and the Ctrl+R reverse search
+use crossterm::event::KeyCode;
+use rustatlas::input::{Edit, History, LineBuffer, CTRL_R};
+
+/// Feed `keys` to the history editing `text`, returning what the last one did
+fn keys(history: &mut History, text: &mut LineBuffer, keys: &[KeyCode]) -> Edit {
+    keys.iter().fold(Edit::NotHandled, |_, &key| history.edit(text, key))
+}
+
+/// Type `input` and submit or cancel it with `end`
+fn enter(history: &mut History, input: &str, end: KeyCode) {
+    let mut text = LineBuffer::default();
+    for c in input.chars() {
+        history.edit(&mut text, KeyCode::Char(c));
+    }
+    history.edit(&mut text, end);
+}
+
+fn history(entries: &[&str]) -> History {
+    History::from_entries(entries.iter().map(|e| e.to_string()).collect())
+}
+
+#[test]
+fn up_recalls_newest_first_and_down_returns_to_the_draft() {
+    let mut history = history(&["pol", "ger", "spa"]);
+    let mut text = LineBuffer::new("dra");
+    let mut recalled = Vec::new();
+    while history.edit(&mut text, KeyCode::Up) == Edit::Changed {
+        recalled.push(text.as_str().to_string());
+    }
+    assert_eq!(recalled, ["spa", "ger", "pol"]);
+    // Up at the oldest keeps it
+    assert_eq!(history.edit(&mut text, KeyCode::Up), Edit::Unchanged);
+    assert_eq!(text.as_str(), "pol");
+
+    let mut back = Vec::new();
+    while history.edit(&mut text, KeyCode::Down) == Edit::Changed {
+        back.push(text.as_str().to_string());
+    }
+    assert_eq!(back, ["ger", "spa", "dra"]);
+    assert!(!history.recalling());
+    assert_eq!(history.edit(&mut text, KeyCode::Down), Edit::Unchanged);
+}
+
+#[test]
+fn empty_history_recalls_nothing() {
+    let mut history = History::default();
+    let mut text = LineBuffer::new("abc");
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Up]), Edit::Unchanged);
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Down]), Edit::Unchanged);
+    assert_eq!(text.as_str(), "abc");
+}
+
+#[test]
+fn repeats_move_to_the_end_instead_of_repeating() {
+    let mut history = History::default();
+    for input in ["pol", "ger", "pol", " ger ", "spa"] {
+        enter(&mut history, input, KeyCode::Enter);
+    }
+    assert_eq!(history.entries(), ["pol", "ger", "spa"]);
+    assert_eq!(History::from_entries(vec!["a".into(), "b".into(), "a".into()]).entries(), ["b", "a"]);
+}
+
+#[test]
+fn empty_and_cancelled_inputs_are_not_recorded() {
+    let mut history = History::default();
+    enter(&mut history, "", KeyCode::Enter);
+    enter(&mut history, "   ", KeyCode::Enter);
+    enter(&mut history, "cancelled", KeyCode::Esc);
+    enter(&mut history, "kept", KeyCode::Enter);
+    assert_eq!(history.entries(), ["kept"]);
+}
+
+#[test]
+fn capped_at_the_limit_dropping_the_oldest() {
+    let mut history = History::default();
+    for i in 0..History::LIMIT + 5 {
+        enter(&mut history, &format!("q{}", i), KeyCode::Enter);
+    }
+    assert_eq!(history.entries().len(), History::LIMIT);
+    assert_eq!(history.entries()[0], "q5");
+    assert_eq!(history.entries().last().unwrap(), &format!("q{}", History::LIMIT + 4));
+}
+
+#[test]
+fn typing_resets_the_recall() {
+    let mut history = history(&["pol", "ger", "spa"]);
+    let mut text = LineBuffer::default();
+    keys(&mut history, &mut text, &[KeyCode::Up, KeyCode::Up]);
+    assert_eq!(text.as_str(), "ger");
+    assert!(history.recalling());
+
+    // Editing a recalled entry makes it the text being typed: Up starts from the newest again
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Char('x')]), Edit::Changed);
+    assert!(!history.recalling());
+    assert_eq!(text.as_str(), "gerx");
+    keys(&mut history, &mut text, &[KeyCode::Up]);
+    assert_eq!(text.as_str(), "spa");
+    // …and Down past the newest brings back the edited text
+    keys(&mut history, &mut text, &[KeyCode::Down]);
+    assert_eq!(text.as_str(), "gerx");
+
+    keys(&mut history, &mut text, &[KeyCode::Up, KeyCode::Up, KeyCode::Backspace]);
+    assert!(!history.recalling());
+    assert_eq!(text.as_str(), "ge");
+    keys(&mut history, &mut text, &[KeyCode::Up]);
+    assert_eq!(text.as_str(), "spa");
+
+    // Moving the cursor isn't typing
+    keys(&mut history, &mut text, &[KeyCode::Left, KeyCode::Home]);
+    assert!(history.recalling());
+    // Nor is cancelling: the next input starts from the newest entry
+    keys(&mut history, &mut text, &[KeyCode::Esc]);
+    assert!(!history.recalling());
+}
+
+#[test]
+fn ctrl_r_searches_backwards() {
+    let mut history = history(&["poland", "portugal", "germany", "spain"]);
+    let mut text = LineBuffer::new("typed");
+    assert_eq!(keys(&mut history, &mut text, &[CTRL_R]), Edit::Unchanged);
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Char('P'), KeyCode::Char('o')]), Edit::Changed);
+    assert_eq!(text.as_str(), "portugal");
+    // Ctrl+R again: the next older match, and the last one stays when there is none older
+    keys(&mut history, &mut text, &[CTRL_R]);
+    assert_eq!(text.as_str(), "poland");
+    keys(&mut history, &mut text, &[CTRL_R]);
+    assert_eq!(text.as_str(), "poland");
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Enter]), Edit::Changed);
+    assert!(history.search.is_none());
+    assert_eq!(text.as_str(), "poland");
+    // The input itself is still open; its own Enter records it
+    assert_eq!(keys(&mut history, &mut text, &[KeyCode::Enter]), Edit::Submit);
+    assert_eq!(history.entries(), ["portugal", "germany", "spain", "poland"]);
+
+    // Esc leaves the search with the text from before it
+    let mut text = LineBuffer::new("typed");
+    keys(&mut history, &mut text, &[CTRL_R, KeyCode::Char('s'), KeyCode::Esc]);
+    assert_eq!(text.as_str(), "typed");
+    assert!(history.search.is_none());
+}
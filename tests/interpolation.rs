@@ -0,0 +1,154 @@
+//! GDP gaps bridged on the choropleth: only runs of at most `interpolate_gdp_gaps` missing
+//! years, linearly, never before a series' first or after its last year; the bridged
+//! countries drawn a dimmer shade and counted in the legend
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    config::Config,
+    gdp_reader::{value_in_year, YearValue},
+    keys::Action,
+    state::{AppState, Panel},
+    theme::{ColorSupport, Theme, CHOROPLETH},
+    ui,
+};
+use std::{collections::BTreeMap, fs};
+
+/// Values in 2000, 2001, 2004 and 2010: a gap of two years and one of five
+fn series() -> BTreeMap<u16, f64> {
+    BTreeMap::from([(2000, 10.0), (2001, 20.0), (2004, 50.0), (2010, 110.0)])
+}
+
+#[test]
+fn within_the_gap_only() {
+    let series = series();
+    // Reported years whatever the setting
+    assert_eq!(value_in_year(&series, 2001, 0), Some(YearValue::Reported(20.0)));
+    assert_eq!(value_in_year(&series, 2010, 5), Some(YearValue::Reported(110.0)));
+    // 0 turns interpolation off
+    assert_eq!(value_in_year(&series, 2002, 0), None);
+    // Linear between the years around the gap
+    assert_eq!(value_in_year(&series, 2002, 2), Some(YearValue::Interpolated(30.0)));
+    assert_eq!(value_in_year(&series, 2003, 2), Some(YearValue::Interpolated(40.0)));
+    assert_eq!(value_in_year(&series, 2007, 5), Some(YearValue::Interpolated(80.0)));
+    assert_eq!(YearValue::Interpolated(80.0).value(), 80.0);
+}
+
+#[test]
+fn max_gap_is_the_longest_run_bridged() {
+    let series = series();
+    // Two missing years: bridged from max_gap 2 up, not below
+    assert_eq!(value_in_year(&series, 2002, 1), None);
+    assert!(value_in_year(&series, 2002, 2).is_some());
+    // Five missing years, whichever of them is asked for
+    for year in 2005..=2009 {
+        assert_eq!(value_in_year(&series, year, 4), None, "{}", year);
+        assert!(value_in_year(&series, year, 5).is_some(), "{}", year);
+    }
+}
+
+#[test]
+fn no_extrapolation_at_the_edges() {
+    let series = series();
+    for max_gap in [0, 1, 50, u16::MAX] {
+        assert_eq!(value_in_year(&series, 1999, max_gap), None);
+        assert_eq!(value_in_year(&series, 1960, max_gap), None);
+        assert_eq!(value_in_year(&series, 2011, max_gap), None);
+        assert_eq!(value_in_year(&series, 2024, max_gap), None);
+    }
+    assert_eq!(value_in_year(&BTreeMap::new(), 2000, 10), None);
+    let single = BTreeMap::from([(2000, 1.0)]);
+    assert_eq!(value_in_year(&single, 2000, 10), Some(YearValue::Reported(1.0)));
+    assert_eq!(value_in_year(&single, 2001, 10), None);
+}
+
+#[test]
+fn setting_and_shade() {
+    assert_eq!(Config::default().interpolate_gdp_gaps, 0);
+    assert_eq!(Config::parse(br#"{"interpolate_gdp_gaps": 3}"#).unwrap().interpolate_gdp_gaps, 3);
+
+    // A dimmer shade where there are colors to spare, the bucket's own on 16 colors
+    for support in [ColorSupport::TrueColor, ColorSupport::Ansi256] {
+        let theme = Theme::new(support);
+        for i in 0..CHOROPLETH.len() {
+            assert_ne!(theme.bucket_interpolated(i), theme.bucket(i), "{:?} {}", support, i);
+        }
+    }
+    let theme = Theme::new(ColorSupport::Ansi16);
+    for i in 0..CHOROPLETH.len() {
+        assert_eq!(theme.bucket_interpolated(i), theme.bucket(i));
+    }
+}
+
+#[test]
+fn bridged_countries_counted_in_the_legend() {
+    // Beta is missing 2004-2005, Gamma 2001-2009, Delta has nothing before 2010
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma", "Delta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .country("Gamma", square_at(10.0, 0.0))
+        .country("Delta", square_at(15.0, 0.0))
+        .gdp("Alpha", 2000..=2010, |_| 1e9)
+        .gdp("Beta", 2000..=2010, |_| 2e9)
+        .gdp_gap("Beta", 2004..=2005)
+        .gdp("Gamma", 2000..=2010, |_| 3e9)
+        .gdp_gap("Gamma", 2001..=2009)
+        .gdp("Delta", 2010..=2010, |_| 4e9)
+        .write("interpolation-legend")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        state.poll_map_job();
+    }
+    let legend = |state: &mut AppState| {
+        state.handle_input(state.keys.key(Action::Choropleth).unwrap());
+        let choropleth = state.choropleth.clone().unwrap();
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let text: String = (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect();
+        state.handle_input(state.keys.key(Action::Choropleth).unwrap());
+        (choropleth, text)
+    };
+
+    // Off by default: the gaps count as missing
+    state.active_year = Some(2005);
+    let (choropleth, text) = legend(&mut state);
+    assert_eq!(choropleth.year, Some(2005));
+    assert!(choropleth.interpolated.is_empty());
+    assert_eq!(choropleth.missing, 3);
+    assert!(text.contains("GDP (2005)") && text.contains("bez danych: 3"), "{}", text);
+    assert!(!text.contains("interpolowane"), "{}", text);
+
+    // Two years bridge Beta; Gamma's gap is too long and Delta's year comes before its series
+    state.config.interpolate_gdp_gaps = 2;
+    let (choropleth, text) = legend(&mut state);
+    assert_eq!(choropleth.interpolated.iter().collect::<Vec<_>>(), ["beta"]);
+    assert_eq!(choropleth.missing, 2);
+    assert!(text.contains("interpolowane: 1") && text.contains("bez danych: 2"), "{}", text);
+
+    // Nine bridge Gamma too, never Delta
+    state.config.interpolate_gdp_gaps = 9;
+    let (choropleth, text) = legend(&mut state);
+    assert_eq!(choropleth.interpolated.iter().collect::<Vec<_>>(), ["beta", "gamma"]);
+    assert_eq!(choropleth.missing, 1);
+    assert!(text.contains("interpolowane: 2"), "{}", text);
+
+    // Latest values have no gaps to bridge
+    state.active_year = None;
+    let (choropleth, text) = legend(&mut state);
+    assert!(choropleth.interpolated.is_empty() && choropleth.missing == 0);
+    assert!(text.contains("GDP (ostatni rok)") && !text.contains("interpolowane"), "{}", text);
+    let _ = fs::remove_dir_all(&dir);
+}
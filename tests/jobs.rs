@@ -0,0 +1,130 @@
+//! Background jobs: queued, running with their last progress report, and finished done,
+//! cancelled or failed, including a worker that panicked; and the GDP export stopping
+//! when its job is cancelled
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    data::DataCache,
+    export::{write_gdp_csv, CsvLayout},
+    gdp_reader::GDPData,
+    jobs::{Job, JobState, Outcome, Progress},
+};
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    sync::mpsc::channel,
+    thread::sleep,
+    time::Duration,
+};
+
+/// Poll `job` until it finishes
+fn finish<T>(job: &mut Job<T>) -> JobState {
+    while !job.poll().finished() {
+        sleep(Duration::from_millis(2));
+    }
+    job.state().clone()
+}
+
+#[test]
+fn progress_then_the_result() {
+    let (go, wait) = channel::<()>();
+    let (reported, seen) = channel::<()>();
+    let mut job = Job::spawn("Testland", move |progress| {
+        wait.recv().unwrap();
+        progress.report(250, "Budowanie");
+        reported.send(()).unwrap();
+        wait.recv().unwrap();
+        Outcome::Done(42)
+    });
+    assert_eq!(job.title, "Testland");
+    assert!(!job.state().finished());
+    go.send(()).unwrap();
+    seen.recv().unwrap();
+    // The worker's own report at the start, then the one above, clamped to 100
+    assert_eq!(job.poll(), &JobState::Running { percent: 100, message: "Budowanie".to_string() });
+    assert_eq!(job.take_result(), None);
+    go.send(()).unwrap();
+    assert_eq!(finish(&mut job), JobState::Done);
+    assert_eq!(job.take_result(), Some(42));
+    assert_eq!(job.take_result(), None);
+    // Finished stays finished
+    assert_eq!(job.poll(), &JobState::Done);
+}
+
+#[test]
+fn cancelled_at_the_next_check() {
+    let mut job = Job::spawn("Testland", |progress| {
+        while !progress.cancelled() {
+            sleep(Duration::from_millis(1));
+        }
+        Outcome::<()>::Cancelled
+    });
+    assert!(!job.cancel_requested());
+    sleep(Duration::from_millis(10));
+    assert!(!job.poll().finished());
+    job.cancel();
+    assert!(job.cancel_requested());
+    assert_eq!(finish(&mut job), JobState::Cancelled);
+    assert_eq!(job.take_result(), None);
+    // A handle for running job code directly is never cancelled
+    assert!(!Progress::detached().cancelled());
+}
+
+#[test]
+fn failures_reported() {
+    let mut failed = Job::spawn("Testland", |_| Outcome::<()>::Failed("no such file".to_string()));
+    assert_eq!(finish(&mut failed), JobState::Failed("no such file".to_string()));
+    let mut panicked = Job::spawn("Testland", |_| -> Outcome<()> { panic!("worker gave up") });
+    assert_eq!(finish(&mut panicked), JobState::Failed("worker thread stopped".to_string()));
+
+    // Waiting hears of every change and the end
+    let mut states = Vec::new();
+    let mut job = Job::spawn("Testland", |progress| {
+        progress.report(50, "połowa");
+        Outcome::Done(())
+    });
+    assert_eq!(job.wait(Duration::from_millis(1), |state| states.push(state.clone())), &JobState::Done);
+    assert_eq!(states.last(), Some(&JobState::Done));
+}
+
+#[test]
+fn cancelled_export_is_interrupted() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Alpha", 2000..=2001, |_| 1e9)
+        .gdp("Beta", 2000..=2001, |_| 2e9)
+        .write("jobs-export")
+        .unwrap()
+        .dir;
+    let export = |dir: std::path::PathBuf| {
+        Job::spawn("export", move |progress: &Progress| {
+            while !progress.cancelled() {
+                sleep(Duration::from_millis(1));
+            }
+            let cache = DataCache::new(&dir).unwrap();
+            let gdp = GDPData::new(dir.join("dataPKB/pkb.csv")).unwrap();
+            let countries: BTreeSet<String> = ["Alpha", "Beta"].map(String::from).into();
+            let mut out = Vec::new();
+            let result = write_gdp_csv(&mut out, &countries, &cache, &gdp, CsvLayout::Long, progress);
+            Outcome::Done((result.map(|_| ()).map_err(|e| e.kind()), out))
+        })
+    };
+    let mut job = export(dir.clone());
+    job.cancel();
+    assert_eq!(finish(&mut job), JobState::Done);
+    let (result, out) = job.take_result().unwrap();
+    assert_eq!(result, Err(ErrorKind::Interrupted));
+    // Stopped before the first row
+    assert_eq!(String::from_utf8(out).unwrap(), "country,year,value\n");
+
+    // Not cancelled, the same export writes every row
+    let cache = DataCache::new(&dir).unwrap();
+    let gdp = GDPData::new(dir.join("dataPKB/pkb.csv")).unwrap();
+    let countries: BTreeSet<String> = ["Alpha", "Beta"].map(String::from).into();
+    let mut out = Vec::new();
+    let report = write_gdp_csv(&mut out, &countries, &cache, &gdp, CsvLayout::Long, &Progress::detached()).unwrap();
+    assert_eq!(report.exported, 2);
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 5);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,148 @@
+//! Country names on the world and continent maps: placed largest feature first, moved up
+//! to two rows to dodge each other, kept a column apart and inside the map or left out;
+//! printed by the widget when asked, toggled with n and off on country maps
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Color, widgets::Widget, Terminal};
+use rustatlas::{
+    config::Config,
+    data::DataCache,
+    keys::Action,
+    map_draw::{place_labels, LabelCandidate, MapStyle, MapView},
+    state::{AppState, Panel},
+    ui,
+};
+use std::{fs, time::Duration};
+
+fn label(x: u16, y: u16, width: u16, weight: f64) -> LabelCandidate {
+    LabelCandidate { rect: Rect::new(x, y, width, 1), weight }
+}
+
+#[test]
+fn heaviest_first_then_nearest_free_row() {
+    let area = Rect::new(0, 0, 40, 10);
+    // Alone: where it wants to be
+    assert_eq!(place_labels(&[label(5, 5, 6, 1.0)], area), [(0, Rect::new(5, 5, 6, 1))]);
+
+    // Three on one spot: the heaviest keeps it, then a row up, then a row down
+    let placed = place_labels(&[label(5, 5, 6, 1.0), label(5, 5, 6, 3.0), label(5, 5, 6, 2.0)], area);
+    assert_eq!(placed, [(1, Rect::new(5, 5, 6, 1)), (2, Rect::new(5, 4, 6, 1)), (0, Rect::new(5, 6, 6, 1))]);
+
+    // Five fill every row within two; a sixth is left out
+    let crowd: Vec<_> = (0..6).map(|i| label(5, 5, 6, 10.0 - i as f64)).collect();
+    let rows: Vec<u16> = place_labels(&crowd, area).iter().map(|(_, r)| r.y).collect();
+    assert_eq!(rows, [5, 4, 6, 3, 7]);
+}
+
+#[test]
+fn a_column_apart_and_inside_the_map() {
+    let area = Rect::new(0, 0, 40, 10);
+    // Touching end to end counts as a clash; a column between them doesn't
+    let placed = place_labels(&[label(0, 5, 5, 2.0), label(5, 5, 5, 1.0)], area);
+    assert_eq!(placed[1], (1, Rect::new(5, 4, 5, 1)));
+    let placed = place_labels(&[label(0, 5, 5, 2.0), label(6, 5, 5, 1.0)], area);
+    assert_eq!(placed[1], (1, Rect::new(6, 5, 5, 1)));
+
+    // Past the right edge, or pushed above the top: left out or moved down
+    assert!(place_labels(&[label(36, 5, 6, 1.0)], area).is_empty());
+    assert!(place_labels(&[label(3, 3, 0, 1.0)], area).is_empty());
+    let placed = place_labels(&[label(5, 0, 6, 2.0), label(5, 0, 6, 1.0)], area);
+    assert_eq!(placed, [(0, Rect::new(5, 0, 6, 1)), (1, Rect::new(5, 1, 6, 1))]);
+    // An offset map area
+    let area = Rect::new(10, 10, 20, 5);
+    assert!(place_labels(&[label(5, 12, 4, 1.0)], area).is_empty());
+    assert_eq!(place_labels(&[label(12, 9, 4, 1.0)], area), [(0, Rect::new(12, 10, 4, 1))]);
+}
+
+/// Rows of `buffer` as text
+fn text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect()
+}
+
+/// Where `word` is printed in `buffer`: its first cell
+fn find(buffer: &Buffer, word: &str) -> Option<(u16, u16)> {
+    let area = buffer.area;
+    (area.top()..area.bottom()).find_map(|y| {
+        let row: Vec<&str> = (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect();
+        let x = (0..row.len()).find(|&x| row[x..].concat().starts_with(word))?;
+        Some((area.left() + x as u16, y))
+    })
+}
+
+#[test]
+fn widget_prints_names_when_asked() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write("labels-widget").unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let square = |x0: f64| vec![vec![x0, 0.0], vec![x0 + 20.0, 0.0], vec![x0 + 20.0, 20.0], vec![x0, 20.0], vec![x0, 0.0]];
+    let view = MapView::new(collection(vec![feature("Alpha", square(0.0)), feature("Beta", square(40.0))]), &mut cache).unwrap();
+    let area = Rect::new(0, 0, 80, 24);
+    let draw = |labels: bool| {
+        let mut buffer = Buffer::empty(area);
+        view.widget().highlight("Beta").labels(labels).render(area, &mut buffer);
+        buffer
+    };
+
+    let plain = draw(false);
+    assert!(find(&plain, "Alpha").is_none() && find(&plain, "Beta").is_none(), "{}", text(&plain));
+    let labelled = draw(true);
+    let (alpha, beta) = (find(&labelled, "Alpha").unwrap(), find(&labelled, "Beta").unwrap());
+    // Each over its own feature, Alpha on the left
+    assert!(alpha.0 < 40 && beta.0 > 40, "{}", text(&labelled));
+    // The selection's name in its color, the rest cyan
+    assert_eq!(labelled[alpha].fg, Color::Cyan);
+    assert_eq!(labelled[beta].fg, MapStyle::CLASSIC.highlight);
+    let _ = fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn toggled_with_n_and_off_on_country_maps() {
+    assert!(!Config::default().world_labels);
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha"])
+        .country("Alpha", vec![vec![0.0, 0.0], vec![30.0, 0.0], vec![30.0, 30.0], vec![0.0, 30.0], vec![0.0, 0.0]])
+        .write("labels-toggle")
+        .unwrap()
+        .dir;
+    fs::write(dir.join("config.json"), r#"{"world_labels": true}"#).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    let map_text = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut inner = None;
+        terminal.draw(|f| inner = ui::draw(f, &state.view_model()).map_area.map(|a| a.inner)).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        let inner = inner.unwrap();
+        (inner.top()..inner.bottom())
+            .map(|y| (inner.left()..inner.right()).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+            .collect::<String>()
+    };
+
+    // On from config.json: the world map names its continent's country
+    assert!(state.show_labels);
+    assert!(map_text(&state).contains("Alpha"), "{}", map_text(&state));
+    state.handle_input(state.keys.key(Action::Labels).unwrap());
+    assert!(!state.show_labels);
+    assert!(!map_text(&state).contains("Alpha"), "{}", map_text(&state));
+    state.handle_input(state.keys.key(Action::Labels).unwrap());
+
+    // On the country's own map there is nothing to tell apart
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        while state.map_job.is_some() {
+            std::thread::sleep(Duration::from_millis(5));
+            state.poll_map_job();
+        }
+    }
+    assert!(state.show_labels);
+    assert!(!map_text(&state).contains("Alpha"), "{}", map_text(&state));
+    let _ = fs::remove_dir_all(&dir);
+}
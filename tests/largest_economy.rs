@@ -0,0 +1,93 @@
+//! `$` jumps to the largest economy: of the highlighted continent on the world map, of
+//! the open continent, ties to the alphabetically first name; a notice where there is
+//! no continent to look in or no GDP to compare
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    config::Severity,
+    keys::Action,
+    state::{AppState, Location, Panel},
+};
+use std::{fs, path::PathBuf};
+
+fn atlas(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Germany", "France"])
+        .continent("Asia", ["Japan", "China", "Laos"])
+        .continent("Antarctica", ["Ross Dependency"])
+        .gdp("Poland", 2020..=2022, |_| 7e11)
+        .gdp("Germany", 2020..=2022, |year| if year == 2022 { 4e12 } else { 1e11 })
+        // Larger once, but not in its latest year
+        .gdp("France", 2020..=2022, |year| if year == 2020 { 9e12 } else { 3e12 })
+        .gdp("Japan", 2020..=2022, |_| 5e12)
+        .gdp("China", 2020..=2022, |_| 5e12)
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+fn country(continent: &str, country: &str) -> Location {
+    Location::Country { continent: continent.to_string(), country: country.to_string() }
+}
+
+#[test]
+fn from_the_world_map_the_highlighted_continent() {
+    let (dir, mut state) = atlas("largest-world");
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    press(&mut state, Action::LargestEconomy);
+    assert_eq!(state.location, country("Europe", "Germany"));
+    assert_eq!(state.list_items[state.selected], "Germany");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn from_a_continent_its_own_and_ties_alphabetically() {
+    let (dir, mut state) = atlas("largest-continent");
+    state.selected = state.list_items.iter().position(|c| c == "Asia").unwrap();
+    press(&mut state, Action::Select);
+    assert_eq!(state.location, Location::Continent("Asia".to_string()));
+    // Japan and China level, Laos without data
+    state.selected = state.list_items.iter().position(|c| c == "Laos").unwrap();
+    press(&mut state, Action::LargestEconomy);
+    assert_eq!(state.location, country("Asia", "China"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn from_a_country_a_notice() {
+    let (dir, mut state) = atlas("largest-country");
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    press(&mut state, Action::Select);
+    state.selected = state.list_items.iter().position(|c| c == "Poland").unwrap();
+    press(&mut state, Action::Select);
+    press(&mut state, Action::LargestEconomy);
+    assert_eq!(state.location, country("Europe", "Poland"));
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.text, "Największą gospodarkę wybiera się z mapy świata lub kontynentu");
+    assert_eq!(notification.severity, Severity::Info);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn without_gdp_an_error() {
+    let (dir, mut state) = atlas("largest-no-gdp");
+    state.selected = state.list_items.iter().position(|c| c == "Antarctica").unwrap();
+    press(&mut state, Action::LargestEconomy);
+    assert_eq!(state.location, Location::World);
+    let notification = state.notification.as_ref().unwrap();
+    assert_eq!(notification.text, "Brak danych GDP dla krajów: Antarctica");
+    assert_eq!(notification.severity, Severity::Error);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,69 @@
+//! The map legend's line for each kind of highlight, as a view computes it and as the
+//! atlas draws it
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    data::{DataCache, GeoLevel},
+    map_draw::{MapStyle, MapView},
+    state::AppState,
+    ui,
+};
+use std::path::PathBuf;
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Northland", ["Gamma"])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+fn map(cache: &mut DataCache, level: GeoLevel, key: &str) -> MapView {
+    let raw = cache.load_geojson(&level, key).unwrap();
+    MapView::new(raw, cache).unwrap()
+}
+
+#[test]
+fn legend_per_highlight() {
+    let dir = atlas("legend-lines");
+    let mut cache = DataCache::new(&dir).unwrap();
+    let world = map(&mut cache, GeoLevel::World, "world");
+    let continent = map(&mut cache, GeoLevel::Continent, "Testland");
+    let country = map(&mut cache, GeoLevel::Country, "Alpha");
+    let classic = MapStyle::CLASSIC;
+
+    // A continent on the world map, which Enter opens
+    assert_eq!(world.legend(Some("Testland"), Some("Enter"), classic).as_deref(), Some("czerwony: Testland (Enter otwiera)"));
+    // A country on its continent's map, which Enter opens
+    assert_eq!(continent.legend(Some("Beta"), Some("Enter"), classic).as_deref(), Some("czerwony: Beta (Enter otwiera)"));
+    // The opened country, which nothing opens further
+    assert_eq!(country.legend(Some("Alpha"), None, classic).as_deref(), Some("czerwony: Alpha"));
+    // Nothing on this map is highlighted
+    assert_eq!(continent.legend(Some("Gamma"), Some("Enter"), classic), None);
+    assert_eq!(continent.legend(None, Some("Enter"), classic), None);
+    // The color named is the style's
+    assert_eq!(continent.legend(Some("Beta"), None, MapStyle::NO_RED).as_deref(), Some("jasnoniebieski: Beta"));
+    assert_eq!(continent.legend(Some("Beta"), None, MapStyle::LIGHT).as_deref(), Some("niebieski: Beta"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn legend_is_drawn_under_the_world_map() {
+    let dir = atlas("legend-drawn");
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect())
+        .collect();
+    assert!(rows.iter().any(|row| row.contains("czerwony: Testland (Enter otwiera)")), "{}", rows.join("\n"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
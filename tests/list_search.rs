@@ -0,0 +1,139 @@
+//! The list search and the map highlight: the highlight follows the top match while
+//! typing, Enter keeps it selected, Esc restores the selection from before the search,
+//! and with no match the earlier selection stays highlighted
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{keys::Action, state::AppState, ui};
+use std::{path::PathBuf, thread, time::Duration};
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Europe", ["Germany", "Poland", "Portugal", "Spain", "Norway"])
+        .continent("Asia", ["Japan"])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// On the Europe list with Spain selected
+fn on_europe(dir: &PathBuf) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    let select = state.keys.key(Action::Select).unwrap();
+    press(&mut state, select);
+    assert_eq!(state.list_items, ["Germany", "Norway", "Poland", "Portugal", "Spain"]);
+    state.selected = 4;
+    state.notification = None;
+    state
+}
+
+fn press(state: &mut AppState, key: KeyCode) {
+    state.handle_input(key);
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn type_query(state: &mut AppState, query: &str) {
+    let search = state.keys.key(Action::Filter).unwrap();
+    press(state, search);
+    for c in query.chars() {
+        press(state, KeyCode::Char(c));
+    }
+}
+
+/// Names of the item selected and of the one the map highlights
+fn selection(state: &AppState) -> (&str, &str) {
+    let view = state.view_model();
+    assert_eq!(view.highlighted, state.highlighted());
+    (state.list_items[state.selected].as_str(), state.list_items[state.highlighted()].as_str())
+}
+
+/// Title of the list panel as drawn
+fn list_title(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..40).map(|x| buffer[(x, 1)].symbol()).collect::<String>()
+}
+
+#[test]
+fn highlight_follows_the_top_match_while_typing() {
+    let dir = atlas("search-live");
+    let mut state = on_europe(&dir);
+    let search = state.keys.key(Action::Filter).unwrap();
+    press(&mut state, search);
+    // Every item matches an empty query; the selection stays highlighted
+    assert_eq!(selection(&state), ("Spain", "Spain"));
+    press(&mut state, KeyCode::Char('p'));
+    assert_eq!(selection(&state), ("Spain", "Poland"));
+    press(&mut state, KeyCode::Char('o'));
+    assert_eq!(state.search.as_ref().unwrap().matches, [2, 3]);
+    assert_eq!(selection(&state), ("Spain", "Poland"));
+    press(&mut state, KeyCode::Char('r'));
+    // Matches are indices into the full list, not into the narrowed one
+    assert_eq!(state.search.as_ref().unwrap().matches, [3]);
+    assert_eq!(selection(&state), ("Spain", "Portugal"));
+    press(&mut state, KeyCode::Backspace);
+    press(&mut state, KeyCode::Down);
+    assert_eq!(selection(&state), ("Spain", "Portugal"));
+    assert!(list_title(&state).contains("Szukaj: po"), "{}", list_title(&state));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn accept_selects_the_highlighted_match() {
+    let dir = atlas("search-accept");
+    let mut state = on_europe(&dir);
+    type_query(&mut state, "POL");
+    press(&mut state, KeyCode::Enter);
+    assert!(state.search.is_none());
+    assert_eq!(state.selected, 2);
+    assert_eq!(selection(&state), ("Poland", "Poland"));
+    assert!(list_title(&state).contains("Wybierz"));
+
+    // A match stepped to is the one kept
+    type_query(&mut state, "o");
+    press(&mut state, KeyCode::Down);
+    press(&mut state, KeyCode::Down);
+    press(&mut state, KeyCode::Enter);
+    assert_eq!(selection(&state), ("Portugal", "Portugal"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cancel_restores_the_selection_and_highlight() {
+    let dir = atlas("search-cancel");
+    let mut state = on_europe(&dir);
+    type_query(&mut state, "ger");
+    assert_eq!(selection(&state), ("Spain", "Germany"));
+    press(&mut state, KeyCode::Esc);
+    assert!(state.search.is_none());
+    assert_eq!(selection(&state), ("Spain", "Spain"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn zero_matches_highlight_the_earlier_selection() {
+    let dir = atlas("search-none");
+    let mut state = on_europe(&dir);
+    type_query(&mut state, "po");
+    assert_eq!(selection(&state), ("Spain", "Poland"));
+    press(&mut state, KeyCode::Char('x'));
+    assert!(state.search.as_ref().unwrap().matches.is_empty());
+    assert_eq!(selection(&state), ("Spain", "Spain"));
+    assert_eq!(state.notification.as_ref().unwrap().text, "Brak dopasowań: pox");
+    list_title(&state);
+    // Accepting nothing leaves the selection where it was
+    press(&mut state, KeyCode::Enter);
+    assert!(state.search.is_none());
+    assert_eq!(selection(&state), ("Spain", "Spain"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,43 @@
+//! The GDP chart's log scale: values that can't be plotted, the axis at whole powers of
+//! ten around the data, and the labels at decade boundaries
+use rustatlas::ui::{log_axis, log_points, magnitude_label};
+
+#[test]
+fn zero_and_negative_values_are_skipped() {
+    let pts = [(2000.0, 1e9), (2001.0, 0.0), (2002.0, -5e8), (2003.0, 1e12), (2004.0, f64::NAN), (2005.0, -0.0)];
+    let (log, skipped) = log_points(&pts);
+    assert_eq!(log, [(2000.0, 9.0), (2003.0, 12.0)]);
+    assert_eq!(skipped, 4);
+    // Nothing left to plot
+    assert_eq!(log_points(&[(2000.0, 0.0), (2001.0, -1.0)]), (Vec::new(), 2));
+    assert_eq!(log_points(&[]), (Vec::new(), 0));
+    // Small positive values stay, below zero on the log scale
+    assert_eq!(log_points(&[(2000.0, 0.01), (2001.0, 1.0)]), (vec![(2000.0, -2.0), (2001.0, 0.0)], 0));
+}
+
+#[test]
+fn axis_covers_the_data_in_whole_decades() {
+    // Exactly on decade boundaries: no decade added on either side
+    assert_eq!(log_axis(9.0, 12.0), ([9.0, 12.0], ["1B", "10B", "100B", "1T"].map(String::from).to_vec()));
+    // Just past one: the next decade in
+    assert_eq!(log_axis(8.999, 12.001).0, [8.0, 13.0]);
+    assert_eq!(log_axis(9.5, 10.2), ([9.0, 11.0], ["1B", "10B", "100B"].map(String::from).to_vec()));
+    // One value, or all values in one decade: still a decade tall
+    assert_eq!(log_axis(10.0, 10.0), ([10.0, 11.0], ["10B", "100B"].map(String::from).to_vec()));
+    assert_eq!(log_axis(10.3, 10.6).0, [10.0, 11.0]);
+    // Below one and across zero
+    assert_eq!(log_axis(-2.0, 0.0), ([-2.0, 0.0], ["0.01", "0.1", "1"].map(String::from).to_vec()));
+    assert_eq!(log_axis(-0.5, 0.5).0, [-1.0, 1.0]);
+}
+
+#[test]
+fn labels_change_unit_at_every_third_decade() {
+    let labels: Vec<String> = (0..=15).map(magnitude_label).collect();
+    assert_eq!(
+        labels,
+        ["1", "10", "100", "1K", "10K", "100K", "1M", "10M", "100M", "1B", "10B", "100B", "1T", "10T", "100T", "1000T"]
+    );
+    assert_eq!(magnitude_label(-1), "0.1");
+    assert_eq!(magnitude_label(-2), "0.01");
+    assert_eq!(magnitude_label(-3), "0.001");
+}
@@ -0,0 +1,46 @@
+//! Peak memory of loading a map whole and in low-memory mode, which streams it a feature
+//! at a time
+mod common;
+
+use common::{alloc::{measure, Counting}, polygon_world, FixtureAtlas};
+use geojson::GeoJson;
+use rustatlas::{data::{DataCache, GeoLevel}, map_draw::{MapView, OutlierPolicy}};
+use std::fs;
+
+#[global_allocator]
+static ALLOC: Counting = Counting;
+
+/// Data directory whose world map has 400 features of 300 points, each with 40 properties
+/// besides its name like Natural Earth's
+fn mid_size() -> std::path::PathBuf {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("low-mem").unwrap();
+    let GeoJson::FeatureCollection(mut world) = polygon_world(400, 300, 11) else { unreachable!() };
+    for feature in &mut world.features {
+        for i in 0..40 {
+            feature.set_property(format!("PROPERTY_{:02}", i), format!("value {} of a feature", i));
+        }
+    }
+    fs::write(fixture.dir.join("continent_world.geojson"), GeoJson::FeatureCollection(world).to_string()).unwrap();
+    fixture.dir
+}
+
+#[test]
+fn low_mem_peak_is_below_whole_parse() {
+    let dir = mid_size();
+    let load = |low_mem: bool| {
+        let mut cache = DataCache::new(&dir).unwrap();
+        cache.set_low_mem(low_mem);
+        measure(|| MapView::load(&mut cache, &GeoLevel::World, "world", OutlierPolicy::Dim).unwrap())
+    };
+    let (whole, normal) = load(false);
+    let (streamed, low) = load(true);
+
+    assert_eq!(whole.feature_names().count(), streamed.feature_names().count());
+    assert!(
+        low.peak * 4 < normal.peak,
+        "low-memory peak {} bytes, whole parse {} bytes",
+        low.peak,
+        normal.peak
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
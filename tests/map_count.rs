@@ -0,0 +1,51 @@
+//! Countries of the continent lists counted against the features of a map that doesn't
+//! agree with them, and the info line built from the count
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use rustatlas::{map_draw::{count_countries, CountryCount}, state::AppState};
+use std::collections::{BTreeMap, BTreeSet};
+
+fn lists(entries: &[(&str, &[&str])]) -> BTreeMap<String, BTreeSet<String>> {
+    entries.iter().map(|(c, names)| (c.to_string(), names.iter().map(|n| n.to_string()).collect())).collect()
+}
+
+#[test]
+fn mismatches_are_named_on_both_sides() {
+    // Beta is listed twice but mapped nowhere, Delta is drawn twice but listed nowhere
+    let lists = lists(&[("North", &["Alpha", "Beta"]), ("South", &["Beta", "Gamma"]), ("Empty", &[])]);
+    let count = count_countries(&lists, ["Alpha", "Delta", "gamma", "Delta"]);
+    assert_eq!(
+        count,
+        CountryCount {
+            countries: 3,
+            continents: 2,
+            features: 4,
+            unmapped: vec!["Beta".to_string()],
+            unlisted: vec!["Delta".to_string()],
+        }
+    );
+}
+
+#[test]
+fn info_line_counts_lists_with_features_in_parentheses() {
+    let fixture = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Northland", ["Delta", "Epsilon"])
+        .write("map-count")
+        .unwrap();
+    // The world map lacks Beta and has an island no list names
+    let world = ["Alpha", "Gamma", "Delta", "Epsilon", "Zeta Island"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| feature(name, square_at(5.0 * i as f64, 10.0)))
+        .collect();
+    std::fs::write(fixture.dir.join("continent_world.geojson"), collection(world).to_string()).unwrap();
+
+    let state = AppState::new(&fixture.dir).unwrap();
+    assert!(state.info.starts_with("World – 5 krajów z 2 kontynentów (5 obiektów na mapie)"), "{}", state.info);
+    let count = state.map_count.as_ref().unwrap();
+    assert_eq!(count.unmapped, ["Beta"]);
+    assert_eq!(count.unlisted, ["Zeta Island"]);
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
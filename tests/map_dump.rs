@@ -0,0 +1,67 @@
+//! `map` on a small fixture at fixed sizes, compared with the expected text in
+//! `tests/golden`: the world in braille, a continent with a highlighted country in ASCII
+//! and one country on its own
+mod common;
+
+use common::FixtureAtlas;
+use std::{fs, path::Path, process::Command};
+
+/// Two continents of differently shaped countries, far enough apart to tell in a small grid
+fn atlas(name: &str) -> std::path::PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Otherland", ["Delta"])
+        .country("Alpha", vec![vec![0.0, 40.0], vec![20.0, 40.0], vec![20.0, 55.0], vec![0.0, 55.0], vec![0.0, 40.0]])
+        .country("Beta", vec![vec![20.0, 40.0], vec![35.0, 30.0], vec![40.0, 50.0], vec![20.0, 55.0], vec![20.0, 40.0]])
+        .country("Gamma", vec![vec![5.0, 25.0], vec![20.0, 25.0], vec![12.0, 38.0], vec![5.0, 25.0]])
+        .country("Delta", vec![vec![-80.0, -30.0], vec![-50.0, -30.0], vec![-50.0, 0.0], vec![-80.0, 0.0], vec![-80.0, -30.0]])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Run `map` with `args` under `locale`, with stdout a pipe: what it printed
+fn map(dir: &Path, locale: &str, args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_RustAtlas"))
+        .arg("--data-dir")
+        .arg(dir)
+        .arg("map")
+        .args(args)
+        .env("LC_ALL", locale)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "map failed: {}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn world_matches_golden_file() {
+    let dir = atlas("map-world");
+    let text = map(&dir, "C.UTF-8", &["--width", "60", "--height", "20"]);
+    assert_eq!(text, include_str!("golden/map_world.txt"));
+    // The same map as the default, named or not, and uncolored without a terminal
+    assert_eq!(map(&dir, "C.UTF-8", &["world", "--width", "60", "--height", "20", "--no-color"]), text);
+    assert!(!text.contains('\x1b'));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn continent_with_highlight_matches_golden_file() {
+    let dir = atlas("map-continent");
+    // Filled, so the highlight shows without colors
+    fs::write(dir.join("config.json"), r#"{"fill_highlight": true}"#).unwrap();
+    let text = map(&dir, "C", &["testland", "--width", "48", "--height", "16", "--highlight", "beta"]);
+    assert_eq!(text, include_str!("golden/map_testland_beta.txt"));
+    assert!(text.is_ascii());
+    assert_ne!(text, map(&dir, "C", &["testland", "--width", "48", "--height", "16"]));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn country_matches_golden_file() {
+    let dir = atlas("map-country");
+    let text = map(&dir, "C.UTF-8", &["Gamma", "--width", "40", "--height", "12"]);
+    assert_eq!(text, include_str!("golden/map_gamma.txt"));
+    assert_eq!(text.lines().count(), 12);
+    let _ = fs::remove_dir_all(&dir);
+}
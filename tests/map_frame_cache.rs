@@ -0,0 +1,115 @@
+//! The rendered map reused while its `MapFrame` is unchanged: after each of a series of
+//! mixed actions, the frame drawn with the cache is byte-identical to one drawn afresh,
+//! and the map is copied exactly when nothing it shows changed
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+use rustatlas::{keys::Action, state::AppState, ui};
+use std::{path::PathBuf, thread, time::Duration};
+
+/// What happens before the next frame
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Nothing,
+    Key(Action),
+    Resize(u16, u16),
+}
+use Step::*;
+
+/// Whether the map was copied from the cache or rendered again
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Map {
+    Hit,
+    Miss,
+}
+use Map::*;
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Northland", ["Delta"])
+        .facts("Alpha", &["First.", "Second."])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Draw one frame of `state` at `size`, with the map cache or without it; the screen
+/// and, when the cache was offered, whether the map was copied from it
+fn draw(state: &mut AppState, (width, height): (u16, u16), cached: bool) -> (Buffer, bool) {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut drawn = None;
+    terminal
+        .draw(|f| {
+            let mut view = state.view_model();
+            if !cached {
+                view.map_cache = None;
+            }
+            drawn = Some(ui::draw(f, &view));
+        })
+        .unwrap();
+    let drawn = drawn.unwrap();
+    let reused = drawn.map_reused;
+    if cached {
+        state.apply_drawn(drawn);
+    }
+    (terminal.backend().buffer().clone(), reused)
+}
+
+#[test]
+fn cached_map_matches_a_fresh_one() {
+    let dir = atlas("map-frame");
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.selected = 0;
+    // The pulsing highlight would change the frame with the clock
+    state.animations = false;
+
+    let table = [
+        (Nothing, Miss),                         // first frame
+        (Nothing, Hit),                          // nothing changed
+        (Key(Action::Down), Miss),               // the highlight moved to the next continent
+        (Nothing, Hit),
+        (Key(Action::Select), Miss),             // a continent's map
+        (Key(Action::Down), Miss),               // another country highlighted
+        (Key(Action::SwitchView), Miss),         // the map's border shows the focus
+        (Key(Action::ZoomIn), Miss),
+        (Nothing, Hit),
+        (Key(Action::Right), Miss),              // panning the zoomed map
+        (Key(Action::SwitchView), Miss),         // focus on the details panel
+        (Key(Action::Down), Hit),                // details only: no facts on a continent
+        (Key(Action::Help), Hit),                // help drawn over an unchanged map
+        (Key(Action::Help), Hit),
+        (Resize(100, 30), Miss),                 // a different area
+        (Nothing, Hit),
+        (Key(Action::Back), Miss),               // the world again
+    ];
+    let mut size = (120, 40);
+    let mut hits = 0;
+    for (i, (step, expected)) in table.into_iter().enumerate() {
+        match step {
+            Nothing => {}
+            Key(action) => {
+                state.handle_input(state.keys.key(action).unwrap());
+                while state.map_job.is_some() {
+                    thread::sleep(Duration::from_millis(5));
+                    state.poll_map_job();
+                }
+            }
+            Resize(width, height) => size = (width, height),
+        }
+        let (fresh, _) = draw(&mut state, size, false);
+        let (cached, reused) = draw(&mut state, size, true);
+        let map = if reused { Hit } else { Miss };
+        assert!(cached == fresh, "step {} ({:?}): cached frame differs from a fresh one", i, step);
+        assert_eq!(map, expected, "step {} ({:?})", i, step);
+        hits += reused as u64;
+    }
+    // As counted on the diagnostics overlay
+    assert_eq!(state.view_model().map_frames, (table.len() as u64, hits));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,124 @@
+//! A continent map loading in the background, its file a named pipe so the test decides
+//! when the read ends: the gauge over the map, Esc cancelling the load rather than
+//! leaving, a failed load reported, a finished one shown, and leaving the place dropping it
+#![cfg(unix)]
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{config::Severity, keys::Action, state::AppState, ui};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    thread::sleep,
+    time::Duration,
+};
+
+/// Testland, listing Omega, which the world map lacks, so its map isn't cut out of the
+/// world's but read from `country_testland.geojson`, here a named pipe
+fn atlas(name: &str) -> (PathBuf, String) {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write(name).unwrap().dir;
+    fs::write(dir.join("country_testland.json"), r#"["Alpha", "Beta", "Omega"]"#).unwrap();
+    let map = dir.join("country_testland.geojson");
+    let contents = fs::read_to_string(&map).unwrap();
+    fs::remove_file(&map).unwrap();
+    assert!(Command::new("mkfifo").arg(&map).status().unwrap().success());
+    (dir, contents)
+}
+
+fn open(dir: &Path) -> AppState {
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert!(state.map_job.is_some());
+    state
+}
+
+/// Let the job read `contents` from the pipe, then wait for it to finish
+fn feed(state: &mut AppState, dir: &Path, contents: &str) {
+    fs::write(dir.join("country_testland.geojson"), contents).unwrap();
+    while state.map_job.is_some() {
+        sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn screen(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer().clone();
+    (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn finished_load_is_shown() {
+    let (dir, contents) = atlas("map-job-done");
+    let mut state = open(&dir);
+    assert!(state.map.is_none());
+    assert_eq!(state.info, "Testland – wczytywanie mapy…");
+    // The gauge once the load has taken a while
+    sleep(Duration::from_millis(200));
+    state.poll_map_job();
+    let text = screen(&state);
+    assert!(text.contains("Wczytywanie mapy: Testland"), "{}", text);
+    assert!(text.contains("10% Wczytywanie country_testland.geojson"), "{}", text);
+    assert!(text.contains("Esc/Backspace: anuluj"), "{}", text);
+
+    feed(&mut state, &dir, &contents);
+    assert_eq!(state.map.as_ref().unwrap().feature_count(), 2);
+    assert!(state.info.starts_with("Testland – "), "{}", state.info);
+    assert!(!screen(&state).contains("Wczytywanie mapy"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn esc_cancels_the_load_and_stays() {
+    let (dir, contents) = atlas("map-job-cancel");
+    let mut state = open(&dir);
+    let back = state.keys.key(Action::Back).unwrap();
+    state.handle_input(back);
+    assert_eq!(state.location, rustatlas::state::Location::Continent("Testland".to_string()));
+    assert!(state.map_job.as_ref().unwrap().cancel_requested());
+    sleep(Duration::from_millis(200));
+    assert!(screen(&state).contains("Anulowanie…"));
+
+    feed(&mut state, &dir, &contents);
+    assert!(state.map.is_none());
+    assert_eq!(state.info, "Testland – wczytywanie mapy anulowane");
+    assert!(state.notification.is_none());
+    // With nothing loading, Esc leaves as usual
+    state.handle_input(back);
+    assert_eq!(state.location, rustatlas::state::Location::World);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn failed_load_is_reported() {
+    let (dir, _) = atlas("map-job-failed");
+    let mut state = open(&dir);
+    feed(&mut state, &dir, "{ not geojson");
+    assert!(state.map.is_none());
+    assert_eq!(state.info, "Testland – brak mapy");
+    let notification = state.notification.as_ref().unwrap();
+    assert!(notification.text.starts_with("Nie można wczytać mapy Testland: "), "{}", notification.text);
+    assert_eq!(notification.severity, Severity::Error);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn leaving_drops_the_load() {
+    let (dir, contents) = atlas("map-job-left");
+    let mut state = open(&dir);
+    state.selected = state.list_items.iter().position(|c| c == "Alpha").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert!(state.map_job.is_none());
+    assert!(state.map.is_some());
+    // The abandoned worker still has the pipe open: let it finish
+    fs::write(dir.join("country_testland.geojson"), contents).unwrap();
+    let _ = fs::remove_dir_all(&dir);
+}
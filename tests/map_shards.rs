@@ -0,0 +1,128 @@
+//! Continents cut out of the world map: each shard gives the same features and bounds
+//! as the continent's own file, starting at the world level reads only the world map,
+//! and a continent the world map can't give in full is still read from its file
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    data::{DataCache, DataSource, GeoLevel, LocalDir},
+    keys::Action,
+    map_draw::{MapView, OutlierPolicy},
+    state::AppState,
+};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// A data directory that notes every file read from it; looking for one isn't noted
+struct Recording {
+    dir: LocalDir,
+    asked: Arc<Mutex<Vec<String>>>,
+}
+
+impl DataSource for Recording {
+    fn path(&self, name: &str) -> PathBuf {
+        self.asked.lock().unwrap().push(name.to_string());
+        self.dir.path(name)
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        self.dir.exists(name)
+    }
+
+    fn dir(&self) -> &Path {
+        self.dir.dir()
+    }
+}
+
+/// The maps read from a recording source so far, in order
+fn maps_read(asked: &Mutex<Vec<String>>) -> Vec<String> {
+    asked.lock().unwrap().iter().filter(|name| name.ends_with(".geojson")).cloned().collect()
+}
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Northland", ["Delta", "Epsilon"])
+        .country("Beta", vec![vec![10.0, 10.0], vec![30.0, 10.0], vec![30.0, 25.0], vec![10.0, 25.0], vec![10.0, 10.0]])
+        .country("Delta", vec![vec![-20.0, 60.0], vec![-5.0, 60.0], vec![-5.0, 70.0], vec![-20.0, 70.0], vec![-20.0, 60.0]])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Feature names of `view`, sorted, and its bounds
+fn summary(view: &MapView) -> (Vec<String>, ([f64; 2], [f64; 2])) {
+    let mut names: Vec<String> = view.feature_names().map(str::to_string).collect();
+    names.sort();
+    (names, view.bounds())
+}
+
+#[test]
+fn shards_match_the_continents_files() {
+    let dir = atlas("shards-match");
+    for policy in [OutlierPolicy::Dim, OutlierPolicy::Hide, OutlierPolicy::Include] {
+        let mut cut = DataCache::new(&dir).unwrap();
+        MapView::load(&mut cut, &GeoLevel::World, "world", policy).unwrap();
+        for (continent, members) in [("Testland", 3), ("Northland", 2)] {
+            assert!(cut.map_ready(&GeoLevel::Continent, continent, policy), "{} {:?}", continent, policy);
+            let from_shard = MapView::load(&mut cut, &GeoLevel::Continent, continent, policy).unwrap();
+            let mut read = DataCache::new(&dir).unwrap();
+            assert!(!read.map_ready(&GeoLevel::Continent, continent, policy));
+            let from_file = MapView::load(&mut read, &GeoLevel::Continent, continent, policy).unwrap();
+            assert_eq!(summary(&from_shard), summary(&from_file), "{} {:?}", continent, policy);
+            assert_eq!(from_shard.feature_count(), members);
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn starting_at_the_world_reads_only_the_world_map() {
+    let dir = atlas("shards-startup");
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let source = Recording { dir: LocalDir::new(dir.clone()), asked: asked.clone() };
+    let mut state = AppState::open_source(Box::new(source), false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    assert_eq!(maps_read(&asked), ["continent_world.geojson"]);
+
+    // Both continents come from the world map, shown at once
+    for continent in ["Northland", "Testland"] {
+        state.selected = state.list_items.iter().position(|c| c == continent).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+        assert!(state.map_job.is_none(), "{} loads in the background", continent);
+        assert_eq!(state.map.as_ref().unwrap().feature_count(), if continent == "Testland" { 3 } else { 2 });
+        state.handle_input(state.keys.key(Action::Back).unwrap());
+    }
+    assert_eq!(maps_read(&asked), ["continent_world.geojson"]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_continent_missing_from_the_world_map_is_read_from_its_file() {
+    let dir = atlas("shards-fallback");
+    // Northland lists a country only its own file has
+    std::fs::write(dir.join("country_northland.json"), r#"["Delta", "Epsilon", "Zeta"]"#).unwrap();
+    let northland = dir.join("country_northland.geojson");
+    let mut map: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&northland).unwrap()).unwrap();
+    let mut zeta = map["features"][0].clone();
+    zeta["properties"]["ADMIN"] = "Zeta".into();
+    map["features"].as_array_mut().unwrap().push(zeta);
+    std::fs::write(&northland, map.to_string()).unwrap();
+
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let mut cache = DataCache::with_source(Box::new(Recording { dir: LocalDir::new(dir.clone()), asked: asked.clone() }));
+    MapView::load(&mut cache, &GeoLevel::World, "world", OutlierPolicy::Dim).unwrap();
+    assert!(cache.map_ready(&GeoLevel::Continent, "Testland", OutlierPolicy::Dim));
+    assert!(!cache.map_ready(&GeoLevel::Continent, "Northland", OutlierPolicy::Dim));
+
+    MapView::load(&mut cache, &GeoLevel::Continent, "Testland", OutlierPolicy::Dim).unwrap();
+    assert_eq!(maps_read(&asked), ["continent_world.geojson"]);
+    let view = MapView::load(&mut cache, &GeoLevel::Continent, "Northland", OutlierPolicy::Dim).unwrap();
+    assert_eq!(maps_read(&asked), ["continent_world.geojson", "country_northland.geojson"]);
+    assert_eq!(view.feature_count(), 3);
+    let _ = std::fs::remove_dir_all(&dir);
+}
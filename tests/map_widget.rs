@@ -0,0 +1,101 @@
+//! `MapWidget` drawn straight into a `Buffer`, without a terminal: kept to its area, with
+//! its title and legend in the border, the highlight in its color, a viewport narrowing
+//! what is shown, and the `MapView::render` wrapper drawing the same cells
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Color, widgets::Widget, Terminal};
+use rustatlas::{
+    data::DataCache,
+    map_draw::{MapStyle, MapView},
+};
+
+/// Alpha at 0..10 and Beta at 20..30 degrees east, both 0..10 north
+fn view(name: &str) -> MapView {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write(name).unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let big = |x: f64| square_at(x, 0.0).into_iter().map(|p| vec![x + (p[0] - x) * 10.0, p[1] * 10.0]).collect();
+    let view = MapView::new(collection(vec![feature("Alpha", big(0.0)), feature("Beta", big(20.0))]), &mut cache).unwrap();
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+    view
+}
+
+/// Text of row `y` of `buffer`
+fn row(buffer: &Buffer, y: u16) -> String {
+    (buffer.area.left()..buffer.area.right()).map(|x| buffer[(x, y)].symbol()).collect()
+}
+
+/// Columns of `buffer` with a cell drawn in `color`
+fn columns(buffer: &Buffer, color: Color) -> Vec<u16> {
+    let area = buffer.area;
+    (area.left()..area.right()).filter(|&x| (area.top()..area.bottom()).any(|y| buffer[(x, y)].fg == color)).collect()
+}
+
+#[test]
+fn drawn_into_its_area_only() {
+    let view = view("map-widget-area");
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 60, 20));
+    let area = Rect::new(5, 3, 40, 12);
+    view.widget().title("Testland").legend("2 kraje").render(area, &mut buffer);
+    for y in 0..20 {
+        for x in 0..60 {
+            if !area.contains((x, y).into()) {
+                assert_eq!(buffer[(x, y)].symbol(), " ", "drawn outside the area at {},{}", x, y);
+            }
+        }
+    }
+    assert_eq!(buffer[(5, 3)].symbol(), "┌");
+    assert_eq!(buffer[(44, 14)].symbol(), "┘");
+    assert!(row(&buffer, 3).contains("Testland"), "{}", row(&buffer, 3));
+    assert!(row(&buffer, 14).contains("2 kraje"), "{}", row(&buffer, 14));
+    // Outlines inside the border
+    assert!(columns(&buffer, MapStyle::CLASSIC.base).iter().all(|&x| x > 5 && x < 44));
+    assert!(!columns(&buffer, MapStyle::CLASSIC.base).is_empty());
+
+    // Too small for anything inside the border, or for the border itself
+    for area in [Rect::new(0, 0, 2, 2), Rect::new(0, 0, 1, 1), Rect::new(0, 0, 0, 0)] {
+        let mut buffer = Buffer::empty(area);
+        view.widget().title("Testland").render(area, &mut buffer);
+    }
+}
+
+#[test]
+fn highlight_and_viewport() {
+    let view = view("map-widget-highlight");
+    let area = Rect::new(0, 0, 62, 20);
+    let style = MapStyle::CLASSIC;
+    let draw = |widget: rustatlas::map_draw::MapWidget| {
+        let mut buffer = Buffer::empty(area);
+        widget.render(area, &mut buffer);
+        buffer
+    };
+
+    // Alpha on the left half in the highlight color, Beta on the right in the base color
+    let buffer = draw(view.widget().highlight("Alpha"));
+    let (red, white) = (columns(&buffer, style.highlight), columns(&buffer, style.base));
+    assert!(!red.is_empty() && red.iter().all(|&x| x < 31), "{:?}", red);
+    assert!(white.iter().any(|&x| x > 31), "{:?}", white);
+    assert!(!white.iter().any(|&x| x < 20), "{:?}", white);
+    // Another style's highlight
+    let buffer = draw(view.widget().highlight("Alpha").style(MapStyle::NO_RED));
+    assert!(columns(&buffer, Color::Red).is_empty());
+    assert!(!columns(&buffer, MapStyle::NO_RED.highlight).is_empty());
+
+    // A viewport around Beta alone: Alpha out of sight, Beta drawn larger
+    let whole = white.len();
+    let buffer = draw(view.widget().highlight("Alpha").viewport(([19.0, 31.0], [-1.0, 11.0])));
+    assert!(columns(&buffer, style.highlight).is_empty());
+    let white = columns(&buffer, style.base);
+    assert!(white.len() > whole, "{:?}", white);
+}
+
+#[test]
+fn render_wrapper_draws_the_widget() {
+    let view = view("map-widget-wrapper");
+    let area = Rect::new(2, 1, 50, 16);
+    let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+    terminal.draw(|f| view.render(f, area, "Mapa", Some("Beta"), Some("legenda"), MapStyle::LIGHT)).unwrap();
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 60, 20));
+    view.widget().title("Mapa").highlight("Beta").legend("legenda").style(MapStyle::LIGHT).render(area, &mut buffer);
+    assert_eq!(terminal.backend().buffer(), &buffer);
+}
@@ -0,0 +1,119 @@
+//! Each marked country in its own color: the first color no mark holds given out, so an
+//! unmarked country's color is the next one used; the same color on the list, on the map
+//! and in the comparison grid, with distinct non-red stand-ins on 16-color terminals
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Color, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Mark, Panel},
+    theme::{ColorSupport, Theme, MARK_COLORS},
+    ui,
+};
+use std::{collections::BTreeSet, fs};
+
+fn marks(colors: &[usize]) -> Vec<Mark> {
+    colors.iter().enumerate().map(|(i, &color)| Mark { name: format!("Country {}", i), color }).collect()
+}
+
+#[test]
+fn first_free_color_given_out() {
+    let palette = MARK_COLORS.len();
+    assert_eq!(AppState::mark_color(&[], palette), 0);
+    assert_eq!(AppState::mark_color(&marks(&[0, 1]), palette), 2);
+    // The color of an unmarked country, before any not yet used
+    assert_eq!(AppState::mark_color(&marks(&[0, 2, 3]), palette), 1);
+    // Every color held: the first of the least used
+    assert_eq!(AppState::mark_color(&marks(&[0, 1, 2, 0, 1]), 3), 2);
+    assert_eq!(AppState::mark_color(&marks(&[0, 1, 2, 0]), 3), 1);
+    assert_eq!(AppState::mark_color(&marks(&[0]), 0), 0);
+}
+
+#[test]
+fn palette_on_every_terminal() {
+    let true_color = Theme::new(ColorSupport::TrueColor);
+    for (i, &rgb) in MARK_COLORS.iter().enumerate() {
+        assert_eq!(true_color.mark(i), true_color.color(rgb));
+    }
+    assert_eq!(true_color.mark(MARK_COLORS.len()), true_color.mark(0));
+
+    let ansi16 = Theme::new(ColorSupport::Ansi16);
+    let colors: BTreeSet<String> = (0..MARK_COLORS.len()).map(|i| format!("{:?}", ansi16.mark(i))).collect();
+    assert_eq!(colors.len(), MARK_COLORS.len());
+    assert!((0..MARK_COLORS.len()).all(|i| !matches!(ansi16.mark(i), Color::Red | Color::LightRed)));
+}
+
+fn draw(state: &AppState) -> (Buffer, Option<Rect>) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut map = None;
+    terminal.draw(|f| map = ui::draw(f, &state.view_model()).map_area.map(|a| a.inner)).unwrap();
+    (terminal.backend().buffer().clone(), map)
+}
+
+/// Color `name` is written in, in the left third of the screen
+fn written_in(buffer: &Buffer, name: &str) -> Option<Color> {
+    (0..buffer.area.height).find_map(|y| {
+        let row: String = (0..40).map(|x| buffer[(x, y)].symbol()).collect();
+        let x = row.find(name)?;
+        Some(buffer[(row[..x].chars().count() as u16, y)].fg)
+    })
+}
+
+#[test]
+fn same_color_on_list_map_and_grid() {
+    let countries = ["Alpha", "Beta", "Gamma"];
+    let mut atlas = FixtureAtlas::new().continent("Testland", countries);
+    for (i, name) in countries.iter().enumerate() {
+        atlas = atlas.country(name, square_at(3.0 * i as f64, 0.0)).gdp(name, 2000..=2002, |year| 1e9 * (year - 1999) as f64);
+    }
+    let dir = atlas.write("mark-colors").unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        state.poll_map_job();
+    }
+    let toggle = |state: &mut AppState, name: &str| {
+        state.selected = state.list_items.iter().position(|c| c == name).unwrap();
+        state.handle_input(state.keys.key(Action::Mark).unwrap());
+    };
+
+    // Alpha's color goes to Gamma once Alpha is unmarked
+    toggle(&mut state, "Alpha");
+    toggle(&mut state, "Beta");
+    toggle(&mut state, "Alpha");
+    toggle(&mut state, "Gamma");
+    let held: Vec<(&str, usize)> = state.marked.iter().map(|m| (m.name.as_str(), m.color)).collect();
+    assert_eq!(held, [("Beta", 1), ("Gamma", 0)]);
+
+    // Alpha selected, so drawn red; Beta and Gamma in theirs
+    state.selected = 0;
+    let (buffer, map) = draw(&state);
+    let (beta, gamma) = (state.theme.mark(1), state.theme.mark(0));
+    assert_eq!(written_in(&buffer, "Beta"), Some(beta));
+    assert_eq!(written_in(&buffer, "Gamma"), Some(gamma));
+    assert_ne!(written_in(&buffer, "Alpha"), Some(beta));
+    let map = map.unwrap();
+    let on_map = |color| (map.top()..map.bottom()).any(|y| (map.left()..map.right()).any(|x| buffer[(x, y)].fg == color));
+    assert!(on_map(beta) && on_map(gamma));
+
+    // The grid's charts keep them
+    state.handle_input(state.keys.key(Action::CompareGrid).unwrap());
+    let charts: Vec<(&str, usize)> = state.compare.as_ref().unwrap().iter().map(|c| (c.name.as_str(), c.color)).collect();
+    assert_eq!(charts, [("Beta", 1), ("Gamma", 0)]);
+    let (buffer, _) = draw(&state);
+    let titled = |name: &str, color| {
+        (0..buffer.area.height).any(|y| {
+            let row: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+            row.find(name).is_some_and(|at| buffer[(row[..at].chars().count() as u16, y)].fg == color)
+        })
+    };
+    assert!(titled("Beta", beta) && titled("Gamma", gamma));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,141 @@
+//! Hand-edited list files: stray whitespace, blank and repeated entries, entries that
+//! open nothing and lists left with no entries at all
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    check,
+    data::{clean_list, DataCache, GeoLevel, ListIssue},
+    keys::Action,
+    state::{AppState, Location},
+    ui,
+};
+use std::{fs, path::PathBuf};
+
+fn list(entries: &[&str]) -> Vec<String> {
+    entries.iter().map(|e| e.to_string()).collect()
+}
+
+/// Testland written by hand, with Emptyland, whose list has only blanks
+fn messy_atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Emptyland", [])
+        .write(name)
+        .unwrap()
+        .dir;
+    let testland = r#"["  Alpha", "Beta ", "", "alpha", "   ", "Gamma", "Beta"]"#;
+    fs::write(dir.join("country_testland.json"), testland).unwrap();
+    fs::write(dir.join("country_emptyland.json"), r#"["", "  ", "\t"]"#).unwrap();
+    dir
+}
+
+fn state(dir: &PathBuf) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state
+}
+
+fn draw(state: &AppState) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+}
+
+#[test]
+fn entries_are_trimmed_and_blanks_and_repeats_dropped() {
+    assert_eq!(clean_list(list(&["  Alpha", "Beta\t", "Gamma"])), ["Alpha", "Beta", "Gamma"]);
+    assert_eq!(clean_list(list(&["Alpha", "", "   ", "Beta"])), ["Alpha", "Beta"]);
+    // A repeat is the same canonical key, whatever its case or spacing; the first stays
+    assert_eq!(clean_list(list(&["Côte d'Ivoire", " côte d'ivoire ", "Alpha", "ALPHA"])), ["Côte d'Ivoire", "Alpha"]);
+    assert!(clean_list(list(&["", " ", "\n"])).is_empty());
+    assert!(clean_list(Vec::new()).is_empty());
+}
+
+#[test]
+fn loaded_list_is_clean_and_unopenable_entries_are_reported() {
+    let dir = messy_atlas("messy-load");
+    let mut cache = DataCache::new(&dir).unwrap();
+    assert_eq!(cache.load_list(GeoLevel::Continent, "Testland").unwrap(), ["Alpha", "Beta", "Gamma"]);
+    assert!(cache.load_list(GeoLevel::Continent, "Emptyland").unwrap().is_empty());
+    let issues = cache.take_new_list_issues();
+    assert_eq!(
+        issues,
+        [ListIssue {
+            list: "country_testland.json".to_string(),
+            entry: "Gamma".to_string(),
+            probed: "country_gamma.geojson".to_string(),
+        }]
+    );
+    assert_eq!(issues[0].to_string(), "country_testland.json: \"Gamma\" has no country_gamma.geojson and no alias");
+    // Loading the list again doesn't report it twice
+    cache.load_list(GeoLevel::Continent, "Testland").unwrap();
+    assert!(cache.take_new_list_issues().is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn check_data_names_the_file_probed() {
+    let dir = messy_atlas("messy-check");
+    let report = check::report(&dir).unwrap();
+    assert!(
+        report.text.contains(
+            "1 list entry without a map file or alias\n  country_testland.json: \"Gamma\" has no country_gamma.geojson and no alias\n"
+        ),
+        "{}",
+        report.text
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn startup_notifies_unopenable_entries() {
+    let dir = messy_atlas("messy-notify");
+    let mut state = AppState::open(&dir, false).unwrap();
+    assert_eq!(
+        state.notification.as_ref().unwrap().text,
+        "country_testland.json: \"Gamma\" has no country_gamma.geojson and no alias"
+    );
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert_eq!(state.list_items, ["Alpha", "Beta", "Gamma"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn empty_continent_is_refused_with_a_notice() {
+    let dir = messy_atlas("messy-empty");
+    let mut state = state(&dir);
+    let select = state.keys.key(Action::Select).unwrap();
+    state.selected = state.list_items.iter().position(|c| c == "Emptyland").unwrap();
+    // Enter twice, as on a continent whose list emptied out; the second used to index
+    // the empty list
+    for _ in 0..2 {
+        state.handle_input(select);
+        assert_eq!(state.location, Location::World);
+        assert_eq!(state.notification.as_ref().unwrap().text, "Lista Emptyland jest pusta – nie ma czego pokazać");
+        assert_eq!(state.list_items[state.selected], "Emptyland");
+    }
+    draw(&state);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn empty_world_list_draws_and_opens_nothing() {
+    let dir = messy_atlas("messy-world");
+    fs::write(dir.join("continent_world.json"), r#"[" ", ""]"#).unwrap();
+    let mut state = state(&dir);
+    assert!(state.list_items.is_empty());
+    draw(&state);
+    state.handle_input(state.keys.key(Action::Down).unwrap());
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    assert_eq!(state.location, Location::World);
+    assert_eq!(state.notification.as_ref().unwrap().text, "Lista jest pusta – nie ma czego otworzyć");
+    state.make_accessible();
+    draw(&state);
+    let _ = fs::remove_dir_all(&dir);
+}
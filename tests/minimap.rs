@@ -0,0 +1,135 @@
+//! The world inset marking the views of countries across the antimeridian, whose maps run
+//! over 0..360 while the inset keeps to -180..180
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Color, Terminal};
+use rustatlas::{
+    data::{DataCache, GeoLevel},
+    keys::Action,
+    map_draw::{viewport_rects, MapStyle, MapView},
+    state::AppState,
+    theme::UNICODE,
+    ui,
+};
+use std::path::PathBuf;
+
+fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+/// Fiji and Russia with parts on both sides of the antimeridian, a country on each of
+/// their continents that keeps to one side, and Spain on the prime meridian so the world
+/// goes all the way round
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Europe", ["Spain"])
+        .continent("Asia", ["Japan", "Russia"])
+        .continent("Oceania", ["Australia", "Fiji"])
+        .country("Spain", rect(-9.0, 36.0, 3.0, 43.0))
+        .country("Japan", rect(130.0, 31.0, 141.0, 45.0))
+        .country_parts("Russia", vec![rect(40.0, 50.0, 180.0, 70.0), rect(-180.0, 55.0, -150.0, 75.0)])
+        .country("Australia", rect(114.0, -39.0, 153.0, -11.0))
+        .country_parts("Fiji", vec![rect(177.0, -19.0, 180.0, -16.0), rect(-180.0, -17.5, -178.5, -16.0)])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+fn map(cache: &mut DataCache, level: GeoLevel, key: &str) -> MapView {
+    let raw = cache.load_geojson(&level, key).unwrap();
+    MapView::new(raw, cache).unwrap()
+}
+
+/// Columns of `buffer` inside `area` holding a cell drawn in `color`
+fn columns_in(buffer: &Buffer, area: Rect, color: Color) -> Vec<u16> {
+    (area.left()..area.right())
+        .filter(|&x| (area.top()..area.bottom()).any(|y| buffer[(x, y)].fg == color))
+        .collect()
+}
+
+#[test]
+fn shifted_bounds_come_back_in_two_parts() {
+    let dir = atlas("minimap-parts");
+    let mut cache = DataCache::new(&dir).unwrap();
+    let world = map(&mut cache, GeoLevel::World, "world").bounds();
+    assert_eq!(world.0, [-180.0, 180.0]);
+    for country in ["Fiji", "Russia"] {
+        let view = map(&mut cache, GeoLevel::Country, country);
+        let ([west, east], _) = view.bounds();
+        assert!(east > 180.0, "{country} isn't drawn across the antimeridian: {west}..{east}");
+        let parts = viewport_rects(view.bounds(), world);
+        let xs: Vec<[f64; 2]> = parts.iter().map(|(x, _)| *x).collect();
+        assert_eq!(xs, [[west, 180.0], [-180.0, east - 360.0]], "{country}");
+    }
+    // A view on one side of the antimeridian stays whole
+    let japan = map(&mut cache, GeoLevel::Country, "Japan").bounds();
+    assert_eq!(viewport_rects(japan, world), [japan]);
+    // Nor does a view ending at the antimeridian grow a part at the other edge
+    assert_eq!(viewport_rects(([170.0, 180.0], [0.0, 10.0]), world), [([170.0, 180.0], [0.0, 10.0])]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn inset_marks_both_sides_of_the_antimeridian() {
+    let dir = atlas("minimap-inset");
+    let mut cache = DataCache::new(&dir).unwrap();
+    let inset = map(&mut cache, GeoLevel::World, "world").mini_map(0.0);
+    let style = MapStyle::CLASSIC;
+    let area = Rect::new(0, 0, 74, 20);
+    let inner = Rect::new(1, 1, 72, 18);
+    let (edge, last) = (inner.left() + 2, inner.right() - 3);
+
+    // A country's view in the highlight color, the continent's selection in yellow
+    for (level, key, selection, color) in [
+        (GeoLevel::Country, "Fiji", None, style.highlight),
+        (GeoLevel::Country, "Russia", None, style.highlight),
+        (GeoLevel::Continent, "Oceania", Some("Fiji"), Color::Yellow),
+        (GeoLevel::Continent, "Asia", Some("Russia"), Color::Yellow),
+    ] {
+        let view = map(&mut cache, level, key);
+        let selected = selection.and_then(|name| view.highlight_bounds(name));
+        let mut terminal = Terminal::new(TestBackend::new(area.width, area.height)).unwrap();
+        terminal.draw(|f| inset.render(f, area, view.bounds(), selected, &UNICODE, style)).unwrap();
+        let columns = columns_in(terminal.backend().buffer(), inner, color);
+        assert!(columns.first().is_some_and(|&x| x <= edge), "{key}: nothing marked by the west edge: {columns:?}");
+        assert!(columns.last().is_some_and(|&x| x >= last), "{key}: nothing marked by the east edge: {columns:?}");
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn atlas_inset_marks_fiji_on_both_sides() {
+    let dir = atlas("minimap-atlas");
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    let select = state.keys.key(Action::Select).unwrap();
+    state.selected = state.list_items.iter().position(|c| c == "Oceania").unwrap();
+    state.handle_input(select);
+    state.selected = state.list_items.iter().position(|c| c == "Fiji").unwrap();
+    state.handle_input(select);
+
+    let draw = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+        terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+        terminal.backend().buffer().clone()
+    };
+    let plain = draw(&state);
+    state.show_minimap = true;
+    let inset = draw(&state);
+
+    // The inset is where the two screens differ
+    let changed: Vec<(u16, u16)> = (0..50)
+        .flat_map(|y| (0..160).map(move |x| (x, y)))
+        .filter(|&(x, y)| plain[(x, y)] != inset[(x, y)])
+        .collect();
+    let (xs, ys) = (changed.iter().map(|c| c.0), changed.iter().map(|c| c.1));
+    let (left, right) = (xs.clone().min().unwrap(), xs.max().unwrap());
+    let (top, bottom) = (ys.clone().min().unwrap(), ys.max().unwrap());
+    let inner = Rect::new(left + 1, top + 1, right - left - 1, bottom - top - 1);
+    let columns = columns_in(&inset, inner, state.map_style.highlight);
+    assert!(columns.first().is_some_and(|&x| x <= inner.left() + 2), "west edge unmarked: {columns:?} in {inner:?}");
+    assert!(columns.last().is_some_and(|&x| x >= inner.right() - 3), "east edge unmarked: {columns:?} in {inner:?}");
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,152 @@
+//! The world inset on continent and country maps: toggled with its key, never on the world
+//! map itself, left out when the map is too small for it, and put in the corner hiding
+//! the least of the highlighted country
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
+use rustatlas::{
+    keys::Action,
+    map_draw::{inset_corner, Corner},
+    state::AppState,
+    ui,
+};
+use std::{path::PathBuf, thread::sleep, time::Duration};
+
+/// Alpha in the south-west, Beta in the south-east and Gamma in the north-east of Testland
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(20.0, 0.0))
+        .country("Gamma", square_at(20.0, 10.0))
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state
+}
+
+fn enter(state: &mut AppState, item: &str) {
+    state.selected = state.list_items.iter().position(|c| c == item).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// The screen at `width` x `height`, and the inside of the map block
+fn draw(state: &AppState, width: u16, height: u16) -> (Buffer, Option<Rect>) {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut inner = None;
+    terminal.draw(|f| inner = ui::draw(f, &state.view_model()).map_area.map(|a| a.inner)).unwrap();
+    (terminal.backend().buffer().clone(), inner)
+}
+
+/// The box around the cells where `a` and `b` differ; None when they are alike
+fn difference(a: &Buffer, b: &Buffer) -> Option<Rect> {
+    let area = a.area;
+    let cells: Vec<(u16, u16)> = (area.top()..area.bottom())
+        .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+        .filter(|&(x, y)| a[(x, y)] != b[(x, y)])
+        .collect();
+    let left = cells.iter().map(|c| c.0).min()?;
+    let right = cells.iter().map(|c| c.0).max()?;
+    let top = cells.iter().map(|c| c.1).min()?;
+    let bottom = cells.iter().map(|c| c.1).max()?;
+    Some(Rect::new(left, top, right - left + 1, bottom - top + 1))
+}
+
+#[test]
+fn corner_avoids_the_highlight() {
+    let (x, y) = ([0.0, 30.0], [0.0, 30.0]);
+    let third = (1.0 / 3.0, 1.0 / 3.0);
+    // Nothing highlighted, or nothing in the way: bottom-right first
+    assert_eq!(inset_corner(x, y, None, third), Corner::BottomRight);
+    assert_eq!(inset_corner(x, y, Some(([12.0, 18.0], [12.0, 18.0])), third), Corner::BottomRight);
+    for (highlight, corner) in [
+        (([25.0, 30.0], [0.0, 5.0]), Corner::BottomLeft),
+        // Over both bottom corners
+        (([0.0, 30.0], [0.0, 5.0]), Corner::TopRight),
+        // Over the whole right-hand side
+        (([25.0, 30.0], [0.0, 30.0]), Corner::BottomLeft),
+    ] {
+        assert_eq!(inset_corner(x, y, Some(highlight), third), corner, "{:?}", highlight);
+    }
+    // Everywhere: the corner covering the least of it
+    assert_eq!(inset_corner(x, y, Some(([0.0, 30.0], [0.0, 25.0])), third), Corner::TopRight);
+    assert_eq!(inset_corner(x, y, Some(([0.0, 30.0], [0.0, 30.0])), third), Corner::BottomRight);
+}
+
+#[test]
+fn toggled_off_the_world_map_only() {
+    let dir = atlas("minimap-toggle");
+    let mut state = open(&dir);
+    let toggle = state.keys.key(Action::MiniMap).unwrap();
+
+    // The world map is the whole inset already
+    let (plain, _) = draw(&state, 120, 40);
+    state.handle_input(toggle);
+    assert!(state.show_minimap);
+    assert_eq!(difference(&plain, &draw(&state, 120, 40).0), None);
+    state.handle_input(toggle);
+    assert!(!state.show_minimap);
+
+    enter(&mut state, "Testland");
+    let (plain, inner) = draw(&state, 120, 40);
+    let inner = inner.unwrap();
+    state.handle_input(toggle);
+    let inset = difference(&plain, &draw(&state, 120, 40).0).expect("no inset drawn");
+    assert!(inner.contains(inset.as_position()) && inset.right() <= inner.right() && inset.bottom() <= inner.bottom(), "{:?} outside {:?}", inset, inner);
+    assert!(inset.width <= inner.width / 2 && inset.height <= inner.height / 2, "{:?} in {:?}", inset, inner);
+
+    // Kept on into a country, and off again with the key
+    enter(&mut state, "Alpha");
+    let with = draw(&state, 120, 40).0;
+    state.handle_input(toggle);
+    assert!(!state.show_minimap);
+    assert!(difference(&with, &draw(&state, 120, 40).0).is_some());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn inset_goes_where_the_highlight_isnt() {
+    let dir = atlas("minimap-corner");
+    let mut state = open(&dir);
+    enter(&mut state, "Testland");
+    // (highlighted country, inset at the right, inset at the bottom)
+    for (country, right, bottom) in [("Gamma", true, true), ("Beta", false, true), ("Alpha", true, true)] {
+        state.selected = state.list_items.iter().position(|c| c == country).unwrap();
+        state.show_minimap = false;
+        let (plain, inner) = draw(&state, 120, 40);
+        let inner = inner.unwrap();
+        state.show_minimap = true;
+        let inset = difference(&plain, &draw(&state, 120, 40).0).unwrap();
+        assert_eq!(inset.right() == inner.right(), right, "{}: {:?} in {:?}", country, inset, inner);
+        assert_eq!(inset.left() == inner.left(), !right, "{}: {:?} in {:?}", country, inset, inner);
+        assert_eq!(inset.bottom() == inner.bottom(), bottom, "{}: {:?} in {:?}", country, inset, inner);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn no_room_no_inset() {
+    let dir = atlas("minimap-small");
+    let mut state = open(&dir);
+    enter(&mut state, "Testland");
+    let (plain, inner) = draw(&state, 40, 10);
+    let inner = inner.unwrap();
+    // The inset wouldn't fit beside anything at its smallest
+    assert!(inner.width <= 16 || inner.height <= 6, "{:?}", inner);
+    state.show_minimap = true;
+    assert_eq!(difference(&plain, &draw(&state, 40, 10).0), None);
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,158 @@
+//! Back, undo/redo and the navigation history, as a table of key sequences and the place
+//! and history each ends in
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use rustatlas::{keys::Action, state::AppState};
+use std::{path::PathBuf, thread, time::Duration};
+
+/// One thing the user does
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    Key(Action),
+    Open(&'static str),   // select the item of that name and press Enter
+    Search(&'static str), // type the query into the list search and accept the top match
+}
+use Step::*;
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Northland", ["Gamma"])
+        .facts("Alpha", &["First.", "Second."])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// A fresh start on the world map, whatever an earlier run saved
+fn state(dir: &PathBuf) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state
+}
+
+/// Press `key`, waiting for a map loading in the background
+fn press(state: &mut AppState, key: KeyCode) {
+    state.handle_input(key);
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn run(state: &mut AppState, step: Step) {
+    match step {
+        Key(action) => press(state, state.keys.key(action).unwrap()),
+        Open(item) => {
+            state.selected = state.list_items.iter().position(|i| i == item).unwrap_or_else(|| panic!("{item} not listed"));
+            press(state, state.keys.key(Action::Select).unwrap());
+        }
+        Search(query) => {
+            press(state, state.keys.key(Action::Filter).unwrap());
+            for c in query.chars() {
+                press(state, KeyCode::Char(c));
+            }
+            press(state, KeyCode::Enter);
+        }
+    }
+}
+
+/// Labels of the places in the history and the current one's index
+fn history(state: &AppState) -> (Vec<&str>, usize) {
+    let (entries, pos) = state.nav.entries();
+    (entries.iter().map(|loc| loc.label()).collect(), pos)
+}
+
+#[test]
+fn transitions() {
+    // Steps from the world map; where they end; the history and its current index
+    let table: &[(&[Step], &str, &[&str], usize)] = &[
+        (&[], "World", &["World"], 0),
+        (&[Open("Testland")], "Testland", &["World", "Testland"], 1),
+        // Going in and out records each visit, but never the same place twice in a row
+        (&[Open("Testland"), Key(Action::Back)], "World", &["World", "Testland", "World"], 2),
+        (&[Open("Testland"), Key(Action::Back), Key(Action::Back)], "World", &["World", "Testland", "World"], 2),
+        (
+            &[Open("Testland"), Key(Action::Back), Open("Testland"), Key(Action::Back)],
+            "World",
+            &["World", "Testland", "World", "Testland", "World"],
+            4,
+        ),
+        // Back goes to the parent, however the place was reached
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Back)], "Testland", &["World", "Testland", "Alpha", "Testland"], 3),
+        (
+            &[Open("Testland"), Open("Alpha"), Key(Action::Back), Key(Action::Back)],
+            "World",
+            &["World", "Testland", "Alpha", "Testland", "World"],
+            4,
+        ),
+        // Undo and redo walk the history without recording anything
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Undo)], "Testland", &["World", "Testland", "Alpha"], 1),
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Undo), Key(Action::Undo)], "World", &["World", "Testland", "Alpha"], 0),
+        (&[Key(Action::Undo)], "World", &["World"], 0),
+        (
+            &[Open("Testland"), Open("Alpha"), Key(Action::Undo), Key(Action::Undo), Key(Action::Redo), Key(Action::Redo)],
+            "Alpha",
+            &["World", "Testland", "Alpha"],
+            2,
+        ),
+        (&[Open("Testland"), Key(Action::Redo)], "Testland", &["World", "Testland"], 1),
+        // A new visit after an undo drops what could have been redone
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Undo), Open("Beta")], "Beta", &["World", "Testland", "Beta"], 2),
+        // Back after an undo still goes to the parent, and is recorded
+        (
+            &[Open("Testland"), Open("Alpha"), Key(Action::Undo), Key(Action::Back)],
+            "World",
+            &["World", "Testland", "World"],
+            2,
+        ),
+        // A search only moves the selection; opening the match is the visit
+        (&[Search("Nor")], "World", &["World"], 0),
+        (&[Search("Nor"), Key(Action::Select)], "Northland", &["World", "Northland"], 1),
+        (&[Open("Testland"), Search("bet"), Key(Action::Select)], "Beta", &["World", "Testland", "Beta"], 2),
+        (
+            &[Open("Testland"), Search("bet"), Key(Action::Select), Key(Action::Back)],
+            "Testland",
+            &["World", "Testland", "Beta", "Testland"],
+            3,
+        ),
+        // Up/Down in a country step through its facts, not places
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Down), Key(Action::Up)], "Alpha", &["World", "Testland", "Alpha"], 2),
+        // Select at the lowest level opens nothing
+        (&[Open("Testland"), Open("Alpha"), Key(Action::Select)], "Alpha", &["World", "Testland", "Alpha"], 2),
+    ];
+
+    let dir = atlas("navigation-table");
+    for (steps, place, entries, pos) in table {
+        let mut state = state(&dir);
+        for &step in *steps {
+            run(&mut state, step);
+        }
+        assert_eq!(state.location.label(), *place, "after {:?}", steps);
+        assert_eq!(history(&state), (entries.to_vec(), *pos), "after {:?}", steps);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn history_is_bounded() {
+    let dir = atlas("navigation-bounded");
+    let mut state = state(&dir);
+    for _ in 0..150 {
+        run(&mut state, Open("Testland"));
+        run(&mut state, Key(Action::Back));
+    }
+    let (entries, pos) = history(&state);
+    assert_eq!(entries.len(), 100);
+    assert_eq!(pos, 99);
+    assert_eq!(entries.last(), Some(&"World"));
+    assert!(entries.windows(2).all(|pair| pair[0] != pair[1]), "{entries:?}");
+    // The oldest visits were dropped: undo stops after 99 steps
+    let undone = std::iter::from_fn(|| state.nav.undo().cloned()).count();
+    assert_eq!(undone, 99);
+    let _ = std::fs::remove_dir_all(&dir);
+}
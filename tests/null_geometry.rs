@@ -0,0 +1,79 @@
+//! A listed country whose map features are all `"geometry": null`
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use rustatlas::{
+    check,
+    data::{DataCache, GeoLevel},
+    keys::Action,
+    map_draw::{MapView, NO_GEOMETRY},
+    state::{AppState, GdpStatus},
+};
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+/// Testland of Alpha, drawn, and Ghost, present in every map file without a geometry
+fn ghost_atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Ghost"])
+        .gdp("Ghost", 2000..=2001, |_| 5e9)
+        .write(name)
+        .unwrap()
+        .dir;
+    let mut ghost = feature("Ghost", square_at(0.0, 0.0));
+    ghost.geometry = None;
+    let both = collection(vec![feature("Alpha", square_at(10.0, 50.0)), ghost.clone()]).to_string();
+    for file in ["continent_world.geojson", "country_testland.geojson"] {
+        fs::write(dir.join(file), &both).unwrap();
+    }
+    fs::write(dir.join("country_ghost.geojson"), collection(vec![ghost]).to_string()).unwrap();
+    dir
+}
+
+/// Select `item` of the list and open it, waiting for a map loading in the background
+fn open(state: &mut AppState, item: &str) {
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+#[test]
+fn skipped_with_no_geometry() {
+    let dir = ghost_atlas("null-geometry-map");
+    let mut cache = DataCache::new(&dir).unwrap();
+    let raw = cache.load_geojson(&GeoLevel::Continent, "Testland").unwrap();
+    let view = MapView::new(raw, &mut cache).unwrap();
+    assert_eq!(view.feature_names().collect::<Vec<_>>(), ["Alpha"]);
+    assert_eq!(view.skipped(), [("Ghost".to_string(), NO_GEOMETRY)]);
+    assert!(view.lacks_geometry("ghost") && !view.lacks_geometry("Alpha"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn country_opens_without_a_map() {
+    let dir = ghost_atlas("null-geometry-open");
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    open(&mut state, "Testland");
+    open(&mut state, "Ghost");
+
+    assert_eq!(state.location.label(), "Ghost");
+    assert!(state.map.is_none());
+    assert_eq!(state.notification.as_ref().unwrap().text, "Ghost: brak danych o granicach");
+    // The panels still show what is known about it
+    assert_eq!(state.summary.as_ref().unwrap().info.as_ref().unwrap().capital, "Ghost City");
+    assert!(matches!(&state.gdp_status, GdpStatus::Found { year, .. } if year == "2001"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reported_by_check_data() {
+    let dir = ghost_atlas("null-geometry-check");
+    let report = check::report(&dir).unwrap();
+    assert!(report.text.contains("1 list entry have no boundary data (null geometry)\n  Ghost\n"), "{}", report.text);
+    assert_eq!(report.problems, 2, "{}", report.text); // Ghost, and Alpha without GDP
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,102 @@
+//! Antarctica and features in no continent list: dimmed and kept out of the bounds by
+//! default, hidden or treated like the rest by config, widening the view only while
+//! highlighted, and no outliers at all on a map made of nothing else
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use geojson::Feature;
+use ratatui::style::Color;
+use rustatlas::{
+    config::Config,
+    data::DataCache,
+    map_draw::{MapView, OutlierPolicy},
+};
+
+fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+/// Alpha and Beta in the band of latitudes, Antarctica below 60°S but listed, and
+/// Kerguelen inside the band but in no continent list
+fn world() -> Vec<Feature> {
+    vec![
+        feature("Alpha", rect(0.0, 0.0, 10.0, 10.0)),
+        feature("Beta", rect(20.0, 40.0, 30.0, 50.0)),
+        feature("Antarctica", rect(-40.0, -90.0, 60.0, -65.0)),
+        feature("Kerguelen", rect(69.0, -50.0, 70.0, -49.0)),
+    ]
+}
+
+/// The view of `features` under `policy`, with the continent lists of the fixture `name`
+fn view(name: &str, features: Vec<Feature>, policy: OutlierPolicy) -> MapView {
+    let fixture = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Antarctica", ["Antarctica"])
+        .write(name)
+        .unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let view = MapView::with_outliers(collection(features), &mut cache, policy).unwrap();
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+    view
+}
+
+fn names(view: &MapView) -> Vec<&str> {
+    let mut names: Vec<&str> = view.feature_names().collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn dimmed_and_out_of_the_bounds_by_default() {
+    let view = view("outliers-dim", world(), OutlierPolicy::Dim);
+    assert_eq!(names(&view), ["Alpha", "Antarctica", "Beta", "Kerguelen"]);
+    assert!(view.skipped().is_empty());
+    assert_eq!(view.bounds(), ([0.0, 30.0], [0.0, 50.0]));
+    let widget = view.widget();
+    assert_eq!(widget.base_color("Antarctica"), Color::DarkGray);
+    assert_eq!(widget.base_color("Kerguelen"), Color::DarkGray);
+    assert_eq!(widget.base_color("Alpha"), Color::White);
+
+    // Highlighted, one widens the view to take it in
+    assert_eq!(view.bounds_including("Antarctica"), ([-40.0, 60.0], [-90.0, 50.0]));
+    assert_eq!(view.bounds_including("Kerguelen"), ([0.0, 70.0], [-50.0, 50.0]));
+    assert_eq!(view.bounds_including("Alpha"), view.bounds());
+}
+
+#[test]
+fn hidden_or_included_by_config() {
+    let hidden = view("outliers-hide", world(), OutlierPolicy::Hide);
+    assert_eq!(names(&hidden), ["Alpha", "Beta"]);
+    let mut skipped = hidden.skipped().to_vec();
+    skipped.sort();
+    assert_eq!(skipped, [("Antarctica".to_string(), "hidden by config"), ("Kerguelen".to_string(), "hidden by config")]);
+    assert_eq!(hidden.bounds(), ([0.0, 30.0], [0.0, 50.0]));
+
+    let included = view("outliers-include", world(), OutlierPolicy::Include);
+    assert_eq!(names(&included), ["Alpha", "Antarctica", "Beta", "Kerguelen"]);
+    assert_eq!(included.bounds(), ([-40.0, 70.0], [-90.0, 50.0]));
+    assert_eq!(included.widget().base_color("Antarctica"), Color::White);
+
+    // Read from config.json, dimmed when left out
+    for (json, policy) in [
+        (r#"{"antarctica": "hide"}"#, OutlierPolicy::Hide),
+        (r#"{"antarctica": "include"}"#, OutlierPolicy::Include),
+        (r#"{"antarctica": "dim"}"#, OutlierPolicy::Dim),
+        ("{}", OutlierPolicy::Dim),
+    ] {
+        assert_eq!(Config::parse(json.as_bytes()).unwrap().antarctica, policy, "{}", json);
+    }
+    assert!(Config::parse(br#"{"antarctica": "drop"}"#).is_err());
+}
+
+#[test]
+fn a_map_of_outliers_alone_has_none() {
+    let antarctica = vec![feature("Antarctica", rect(-40.0, -90.0, 60.0, -65.0))];
+    for policy in [OutlierPolicy::Dim, OutlierPolicy::Hide] {
+        let view = view("outliers-alone", antarctica.clone(), policy);
+        assert_eq!(names(&view), ["Antarctica"], "{:?}", policy);
+        assert!(view.skipped().is_empty(), "{:?}", policy);
+        assert_eq!(view.bounds(), ([-40.0, 60.0], [-90.0, -65.0]), "{:?}", policy);
+        assert_eq!(view.widget().base_color("Antarctica"), Color::White, "{:?}", policy);
+    }
+}
@@ -0,0 +1,157 @@
+//! Keys offered to the focused panel first: for each panel with the focus, which of the
+//! list, the map and the details panel takes Up, Down and Enter, and the marker and
+//! border the focused panel is drawn with
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, style::Color, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Location, Panel},
+    theme, ui,
+};
+use std::{path::PathBuf, thread, time::Duration};
+
+/// The panel whose handler a key ended up in, told by what it changed
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Took {
+    List,    // moved the selection or opened it
+    Map,     // panned the zoomed map
+    Details, // stepped through the fun facts
+    Nothing,
+}
+use Took::*;
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .facts("Alpha", &["First.", "Second.", "Third."])
+        .facts("Beta", &["One.", "Two.", "Three."])
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+fn press(state: &mut AppState, key: KeyCode) {
+    state.handle_input(key);
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// On Testland with Beta selected, or on Beta itself, with `panel` focused and the
+/// second fact shown; the map zoomed in when asked
+fn state(dir: &PathBuf, country: bool, panel: Panel, zoomed: bool) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    let select = state.keys.key(Action::Select).unwrap();
+    press(&mut state, select);
+    state.selected = 1;
+    if country {
+        press(&mut state, select);
+    }
+    state.fact_focus = 1;
+    state.active_panel = panel;
+    if zoomed {
+        state.map.as_mut().unwrap().zoom(2.0);
+    }
+    state
+}
+
+/// Press `key` and tell which panel's handler took it
+fn took(mut state: AppState, key: KeyCode) -> Took {
+    let (location, selected, fact) = (state.location.clone(), state.selected, state.fact_focus);
+    let viewport = format!("{:?}", state.map.as_ref().unwrap().bounds());
+    press(&mut state, key);
+    if state.location != location || state.selected != selected {
+        List
+    } else if state.fact_focus != fact {
+        Details
+    } else if format!("{:?}", state.map.as_ref().unwrap().bounds()) != viewport {
+        Map
+    } else {
+        Nothing
+    }
+}
+
+#[test]
+fn focused_panel_takes_the_keys_first() {
+    let dir = atlas("focus-routing");
+    let (up, down, enter) = (KeyCode::Up, KeyCode::Down, KeyCode::Enter);
+    // (focus, on the country, map zoomed, key, handler)
+    let table = [
+        (Panel::Left, false, false, up, List),
+        (Panel::Left, false, false, down, List),
+        (Panel::Left, false, false, enter, List),
+        (Panel::Left, false, true, down, List),
+        // A country's list has only the country: the arrows go through its facts
+        (Panel::Left, true, false, up, Details),
+        (Panel::Left, true, false, down, Details),
+        (Panel::Center, false, false, up, List),
+        (Panel::Center, false, false, down, List),
+        (Panel::Center, false, false, enter, List),
+        // Zoomed in, the map pans with the arrows; Enter still opens
+        (Panel::Center, false, true, up, Map),
+        (Panel::Center, false, true, down, Map),
+        (Panel::Center, false, true, enter, List),
+        (Panel::Center, true, true, down, Map),
+        (Panel::Right, false, false, up, Nothing),
+        (Panel::Right, false, false, down, Nothing),
+        (Panel::Right, false, false, enter, Nothing),
+        (Panel::Right, true, false, up, Details),
+        (Panel::Right, true, false, down, Details),
+        (Panel::Right, true, true, down, Details),
+    ];
+    for (panel, country, zoomed, key, expected) in table {
+        let state = state(&dir, country, panel, zoomed);
+        assert_eq!(took(state, key), expected, "{:?} focused, country {}, zoomed {}, {:?}", panel, country, zoomed, key);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn enter_on_the_list_opens_the_selection() {
+    let dir = atlas("focus-enter");
+    let mut state = state(&dir, false, Panel::Left, false);
+    press(&mut state, KeyCode::Enter);
+    assert_eq!(state.location, Location::Country { continent: "Testland".to_string(), country: "Beta".to_string() });
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// The row of the screen the panel titles are on, as drawn, and the border color of each
+/// panel's top-left corner, left to right
+fn title_row(state: &AppState) -> (String, Vec<Color>) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let y = (0..40).find(|&y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>().contains("Wybierz")).unwrap();
+    let row = (0..120).map(|x| buffer[(x, y)].symbol()).collect();
+    let corners = (0..120).map(|x| &buffer[(x, y)]).filter(|cell| cell.symbol() == "┌").map(|cell| cell.fg).collect();
+    (row, corners)
+}
+
+#[test]
+fn only_the_focused_panel_is_marked() {
+    let dir = atlas("focus-marker");
+    for (i, panel) in Panel::ALL.into_iter().enumerate() {
+        let state = state(&dir, true, panel, false);
+        let focus = state.theme.color(theme::FOCUS_BORDER);
+        let (row, corners) = title_row(&state);
+        assert_eq!(row.matches("◆ ").count(), 1, "{:?}: {}", panel, row);
+        let marked = row.find("◆ ").unwrap();
+        let list = row.find("Wybierz").unwrap();
+        match panel {
+            Panel::Left => assert!(marked < list, "{}", row),
+            _ => assert!(marked > list, "{}", row),
+        }
+        assert_eq!(corners.len(), 3, "{}", row);
+        for (j, color) in corners.into_iter().enumerate() {
+            assert_eq!(color == focus, i == j, "{:?} focused, panel {} border {:?}", panel, j, color);
+        }
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,117 @@
+//! Where state and caches go: the data directory while it is writable, else the XDG
+//! directory, then `$HOME`'s default, then the system temp directory; and the notice of
+//! the move, in the status bar and in diagnostics. The order is tested on a made-up
+//! environment; only the test of `Paths::detect` sets variables.
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    paths::{select_dir, Artifact, Paths},
+    state::AppState,
+    ui,
+};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// An empty directory `rustatlas-paths-<name>` under the system temp directory
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("rustatlas-paths-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// A directory the write probe can't write to, whoever runs the tests: the probe's name
+/// is taken by a directory
+fn read_only_dir(name: &str) -> PathBuf {
+    let dir = temp_dir(name);
+    fs::create_dir(dir.join(".rustatlas-write-probe")).unwrap();
+    dir
+}
+
+/// `select_dir` for `kind` with only the variables in `vars` set
+fn select(kind: Artifact, data: &Path, read_only: bool, vars: &[(&str, &Path)]) -> PathBuf {
+    let vars: BTreeMap<&str, String> = vars.iter().map(|&(k, v)| (k, v.display().to_string())).collect();
+    select_dir(kind, data, read_only, |name| vars.get(name).cloned())
+}
+
+#[test]
+fn directories_in_order_of_preference() {
+    let root = temp_dir("order");
+    let (data, xdg, home) = (root.join("data"), root.join("xdg"), root.join("home"));
+    for kind in [Artifact::Data, Artifact::State, Artifact::Cache] {
+        // Writable: everything next to the data, whatever the environment says
+        assert_eq!(select(kind, &data, false, &[("XDG_STATE_HOME", &xdg), ("XDG_CACHE_HOME", &xdg), ("HOME", &home)]), data);
+    }
+    // Read only: data stays, state and caches move
+    assert_eq!(select(Artifact::Data, &data, true, &[("HOME", &home)]), data);
+    let table = [
+        (Artifact::State, "XDG_STATE_HOME", ".local/state"),
+        (Artifact::Cache, "XDG_CACHE_HOME", ".cache"),
+    ];
+    for (kind, xdg_var, default) in table {
+        assert_eq!(select(kind, &data, true, &[(xdg_var, &xdg), ("HOME", &home)]), xdg.join("rustatlas"), "{}", xdg_var);
+        // The other kind's variable doesn't count
+        let other = if xdg_var == "XDG_STATE_HOME" { "XDG_CACHE_HOME" } else { "XDG_STATE_HOME" };
+        assert_eq!(select(kind, &data, true, &[(other, &xdg), ("HOME", &home)]), home.join(default).join("rustatlas"));
+        // A relative XDG directory is ignored, as the spec says
+        assert_eq!(select(kind, &data, true, &[(xdg_var, Path::new("xdg")), ("HOME", &home)]), home.join(default).join("rustatlas"));
+        // Nowhere to go but the temp directory
+        assert_eq!(select(kind, &data, true, &[("HOME", Path::new(""))]), env::temp_dir().join("rustatlas"));
+        assert_eq!(select(kind, &data, true, &[]), env::temp_dir().join("rustatlas"));
+    }
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn read_only_data_moves_state_and_says_so() {
+    let root = temp_dir("detect");
+    let (state_home, cache_home) = (root.join("state"), root.join("cache"));
+    // SAFETY: no other test in this binary reads these variables
+    unsafe {
+        env::set_var("XDG_STATE_HOME", &state_home);
+        env::set_var("XDG_CACHE_HOME", &cache_home);
+    }
+
+    let writable = temp_dir("writable");
+    let paths = Paths::detect(&writable);
+    assert!(!paths.read_only);
+    assert_eq!(paths.file(Artifact::State, "session.json"), writable.join("session.json"));
+    assert_eq!(paths.file(Artifact::Cache, "maps"), writable.join("maps"));
+    assert_eq!(paths.notice(), None);
+    // The probe left nothing behind
+    assert_eq!(fs::read_dir(&writable).unwrap().count(), 0);
+
+    let locked = read_only_dir("read-only");
+    let paths = Paths::detect(&locked);
+    assert!(paths.read_only);
+    assert_eq!(paths.file(Artifact::Data, "continent_world.json"), locked.join("continent_world.json"));
+    assert_eq!(paths.file(Artifact::State, "session.json"), state_home.join("rustatlas/session.json"));
+    assert_eq!(paths.file(Artifact::Cache, "maps"), cache_home.join("rustatlas/maps"));
+    let notice = paths.notice().unwrap();
+    assert_eq!(notice, format!("Katalog danych tylko do odczytu; stan użytkownika w {}", state_home.join("rustatlas").display()));
+
+    // The atlas on a read-only data directory: the notice in the status bar at startup
+    // and in diagnostics for as long as it runs
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("paths-read-only").unwrap().dir;
+    fs::create_dir(dir.join(".rustatlas-write-probe")).unwrap();
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    assert!(state.notification.as_ref().is_some_and(|n| n.text == notice), "{:?}", state.notification.as_ref().map(|n| &n.text));
+    state.notification = None;
+    state.show_diagnostics = true;
+    let mut terminal = Terminal::new(TestBackend::new(160, 50)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen: Vec<String> = (0..50).map(|y| (0..160).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+    assert!(screen.iter().any(|row| row.contains(&notice)), "{}", screen.join("\n"));
+
+    for dir in [root, writable, locked, dir] {
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,78 @@
+//! Population-weighted GDP per capita of a group: total GDP over total population of the
+//! countries with both figures, against the naive mean of their own figures, with partial
+//! coverage, and as the continent panel shows it
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{gdp_reader::aggregate_per_capita, keys::Action, state::AppState};
+use std::{thread, time::Duration};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs())
+}
+
+#[test]
+fn weighted_differs_from_the_naive_mean_by_an_order_of_magnitude() {
+    // A country of a billion at $1000 a head, and a microstate of 30 000 at $200 000
+    let group = [(Some(1e12), Some(1_000_000_000)), (Some(6e9), Some(30_000))];
+    let naive = group.iter().map(|&(gdp, pop)| gdp.unwrap() / pop.unwrap() as f64).sum::<f64>() / group.len() as f64;
+    let weighted = aggregate_per_capita(group).unwrap();
+    assert!(close(weighted.value(), (1e12 + 6e9) / 1_000_030_000.0));
+    assert!(close(naive, 100_500.0));
+    assert!(naive / weighted.value() > 10.0, "naive {} weighted {}", naive, weighted.value());
+    assert_eq!((weighted.covered, weighted.countries), (2, 2));
+    assert_eq!(weighted.coverage(), 1.0);
+}
+
+#[test]
+fn partial_coverage_counts_only_countries_with_both_figures() {
+    let group = [
+        (Some(3e11), Some(10_000_000)), // covered
+        (None, Some(50_000_000)),       // no GDP
+        (Some(9e12), None),             // no population
+        (Some(1e9), Some(0)),           // no one lives there
+        (Some(1e11), Some(10_000_000)), // covered
+    ];
+    let pc = aggregate_per_capita(group).unwrap();
+    assert_eq!((pc.gdp, pc.population), (4e11, 20_000_000));
+    assert!(close(pc.value(), 20_000.0));
+    assert_eq!((pc.covered, pc.countries), (2, 5));
+    assert!(close(pc.coverage(), 0.4));
+}
+
+#[test]
+fn nothing_to_weigh() {
+    assert_eq!(aggregate_per_capita([]), None);
+    assert_eq!(aggregate_per_capita([(None, Some(1_000)), (Some(5e9), None)]), None);
+}
+
+#[test]
+fn continent_panel_shows_the_weighted_figure() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Big", "Tiny", "Unknown"])
+        .gdp("Big", 2020..=2020, |_| 1e12)
+        .gdp("Tiny", 2020..=2020, |_| 6e9)
+        .write("per-capita")
+        .unwrap()
+        .dir;
+    // Populations to match: the fixture gives every country a million
+    let path = dir.join("country_info.json");
+    let mut info: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    info["big"]["population"] = 1_000_000_000u64.into();
+    info["tiny"]["population"] = 30_000.into();
+    std::fs::write(&path, info.to_string()).unwrap();
+
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+    let pc = state.view_model().place_per_capita.unwrap();
+    assert!(close(pc.value(), (1e12 + 6e9) / 1_000_030_000.0));
+    assert_eq!((pc.covered, pc.countries), (2, 3));
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,218 @@
+//! Saving state when the filesystem says no, whoever runs the tests: `write_atomic` and
+//! `PersistentStore` through a filesystem that fails the step asked of it, leaving no
+//! temporary file and the old file whole; unsaved changes kept until a save succeeds; and
+//! unparsable files quarantined as `.corrupt`, or reported when they can't be moved
+use rustatlas::data::{write_atomic, write_atomic_in, PersistentStore, RealFs, StateFs};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+type Counts = BTreeMap<String, u32>;
+
+/// A step of saving a file
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Step {
+    CreateDir,
+    Write,
+    Rename,
+}
+
+/// The disk, but failing `Step` while it is set; a failed write leaves half the bytes
+/// behind, as a full disk would
+#[derive(Clone, Default)]
+struct Failing {
+    step: Arc<Mutex<Option<Step>>>,
+}
+
+impl Failing {
+    fn fail(&self, step: Option<Step>) {
+        *self.step.lock().unwrap() = step;
+    }
+
+    fn check(&self, step: Step) -> io::Result<()> {
+        match *self.step.lock().unwrap() == Some(step) {
+            true => Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{:?} refused", step))),
+            false => Ok(()),
+        }
+    }
+}
+
+impl StateFs for Failing {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        RealFs.read(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.check(Step::CreateDir)?;
+        RealFs.create_dir_all(path)
+    }
+
+    fn write(&self, path: &Path, bytes: &[u8]) -> io::Result<()> {
+        if let Err(e) = self.check(Step::Write) {
+            RealFs.write(path, &bytes[..bytes.len() / 2])?;
+            return Err(e);
+        }
+        RealFs.write(path, bytes)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.check(Step::Rename)?;
+        RealFs.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        RealFs.remove_file(path)
+    }
+}
+
+/// An empty directory `rustatlas-store-<name>` under the system temp directory
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustatlas-store-{}", name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Files in `dir`, sorted
+fn listing(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+    names.sort();
+    names
+}
+
+fn counts(entries: &[(&str, u32)]) -> Counts {
+    entries.iter().map(|&(k, v)| (k.to_string(), v)).collect()
+}
+
+#[test]
+fn write_atomic_failing_at_each_step() {
+    let dir = temp_dir("steps");
+    let path = dir.join("session.json");
+    let disk = Failing::default();
+    write_atomic_in(&disk, &path, b"old").unwrap();
+    for step in [Step::CreateDir, Step::Write, Step::Rename] {
+        disk.fail(Some(step));
+        let error = write_atomic_in(&disk, &path, b"new contents").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied, "{:?}", step);
+        assert_eq!(error.to_string(), format!("{:?} refused", step));
+        // The old file whole, no temporary file
+        assert_eq!(fs::read(&path).unwrap(), b"old", "{:?}", step);
+        assert_eq!(listing(&dir), ["session.json"], "{:?}", step);
+    }
+    disk.fail(None);
+    write_atomic_in(&disk, &path, b"new contents").unwrap();
+    assert_eq!(fs::read(&path).unwrap(), b"new contents");
+    assert_eq!(listing(&dir), ["session.json"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn write_atomic_on_the_disk_when_the_rename_fails() {
+    let dir = temp_dir("rename");
+    // A directory with something in it can't be replaced by a file
+    let path = dir.join("session.json");
+    fs::create_dir_all(path.join("inside")).unwrap();
+    assert!(write_atomic(&path, b"new").is_err());
+    assert!(path.is_dir());
+    assert_eq!(listing(&dir), ["session.json"]);
+
+    // Nor can a file be written under another file
+    let blocker = dir.join("state");
+    fs::write(&blocker, b"").unwrap();
+    assert!(write_atomic(&blocker.join("session.json"), b"new").is_err());
+    assert_eq!(fs::read(&blocker).unwrap(), b"");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn store_keeps_unsaved_changes_until_a_save_succeeds() {
+    let dir = temp_dir("store-retry");
+    let path = dir.join("quiz.json");
+    let disk = Failing::default();
+    let (mut store, warning) = PersistentStore::<Counts>::load_in(Box::new(disk.clone()), path.clone(), Duration::from_secs(5));
+    assert_eq!(warning, None);
+    assert_eq!(store.get(), &Counts::new());
+    store.set(counts(&[("poland", 3)]));
+    disk.fail(Some(Step::Write));
+    assert_eq!(store.flush().unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+    disk.fail(Some(Step::Rename));
+    let now = Instant::now();
+    assert!(store.save_due(now).is_err());
+    assert!(listing(&dir).is_empty());
+
+    // The filesystem back: the change is still there to be saved, but not before the
+    // debounce interval after the failed attempt
+    disk.fail(None);
+    store.save_due(now + Duration::from_secs(1)).unwrap();
+    assert!(!path.exists());
+    store.save_due(now + Duration::from_secs(5)).unwrap();
+    let (reloaded, _) = PersistentStore::<Counts>::load(path.clone(), Duration::from_secs(5));
+    assert_eq!(reloaded.get(), &counts(&[("poland", 3)]));
+    assert_eq!(listing(&dir), ["quiz.json"]);
+
+    // Saved: a failure now leaves the saved file as it was
+    store.set(counts(&[("poland", 4)]));
+    disk.fail(Some(Step::Rename));
+    assert!(store.flush().is_err());
+    assert_eq!(PersistentStore::<Counts>::load(path, Duration::ZERO).0.get(), &counts(&[("poland", 3)]));
+    assert_eq!(listing(&dir), ["quiz.json"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unparsable_file_is_quarantined() {
+    let dir = temp_dir("corrupt");
+    let path = dir.join("session.json");
+    fs::write(&path, b"{\"poland\": 3").unwrap();
+    let (mut store, warning) = PersistentStore::<Counts>::load(path.clone(), Duration::ZERO);
+    let warning = warning.unwrap();
+    assert!(warning.contains("przeniesiono do") && warning.contains("session.json.corrupt"), "{}", warning);
+    assert_eq!(store.get(), &Counts::new());
+    // Moved aside as it was, nothing in its place until the next save
+    assert_eq!(listing(&dir), ["session.json.corrupt"]);
+    assert_eq!(fs::read(dir.join("session.json.corrupt")).unwrap(), b"{\"poland\": 3");
+    store.set(counts(&[("germany", 1)]));
+    store.flush().unwrap();
+    assert_eq!(listing(&dir), ["session.json", "session.json.corrupt"]);
+
+    // A later broken file replaces the quarantined one
+    fs::write(&path, b"[1, 2]").unwrap();
+    let (store, warning) = PersistentStore::<Counts>::load(path.clone(), Duration::ZERO);
+    assert!(warning.is_some());
+    assert_eq!(store.get(), &Counts::new());
+    assert_eq!(fs::read(dir.join("session.json.corrupt")).unwrap(), b"[1, 2]");
+
+    // A valid file loads without a word
+    fs::write(&path, b"{\"spain\": 2}").unwrap();
+    let (store, warning) = PersistentStore::<Counts>::load(path, Duration::ZERO);
+    assert_eq!((store.get(), warning), (&counts(&[("spain", 2)]), None));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unparsable_file_that_cant_be_moved_is_reported() {
+    let dir = temp_dir("corrupt-stuck");
+    let path = dir.join("views.json");
+    fs::write(&path, b"not json").unwrap();
+    let disk = Failing::default();
+    disk.fail(Some(Step::Rename));
+    let (mut store, warning) = PersistentStore::<Counts>::load_in(Box::new(disk.clone()), path.clone(), Duration::ZERO);
+    let warning = warning.unwrap();
+    assert!(warning.contains("nie przeniesiono do") && warning.contains("Rename refused"), "{}", warning);
+    assert_eq!(store.get(), &Counts::new());
+    assert_eq!(listing(&dir), ["views.json"]);
+
+    // Replaced by the next save that gets through
+    store.set(counts(&[("europe", 1)]));
+    assert!(store.flush().is_err());
+    assert_eq!(fs::read(&path).unwrap(), b"not json");
+    disk.fail(None);
+    store.flush().unwrap();
+    assert_eq!(PersistentStore::<Counts>::load(path, Duration::ZERO).0.get(), &counts(&[("europe", 1)]));
+    assert_eq!(listing(&dir), ["views.json"]);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,102 @@
+//! The map projections behind the `Projection` trait and the key cycling them
+use crossterm::event::KeyCode;
+use rustatlas::{
+    config,
+    keys::{Action, KeyBindings},
+    map_draw::{projection, Equirectangular, Mollweide, Projection, Robinson},
+};
+use std::collections::BTreeMap;
+
+const CLOSE: f64 = 1e-6;
+
+/// Points over the whole globe, poles and edge meridians included
+fn globe() -> impl Iterator<Item = (f64, f64)> {
+    (-6..=6).flat_map(|i| (-6..=6).map(move |j| (i as f64 * 30.0, j as f64 * 15.0)))
+}
+
+fn round_trips(projection: &dyn Projection, central: f64) {
+    for (lon, lat) in globe() {
+        let (lon, lat) = (central + lon, lat);
+        let (x, y) = projection.project(lon, lat);
+        let (back_lon, back_lat) = projection.unproject(x, y).unwrap_or_else(|| panic!("({lon}, {lat}) off the globe"));
+        assert!((back_lat - lat).abs() < CLOSE, "latitude {lat} came back as {back_lat}");
+        // Every meridian meets at the poles, so longitude only comes back away from them
+        if lat.abs() < 90.0 {
+            assert!((back_lon - lon).abs() < CLOSE, "longitude {lon} at {lat} came back as {back_lon}");
+        }
+    }
+}
+
+#[test]
+fn every_projection_undoes_itself() {
+    round_trips(&Equirectangular, 0.0);
+    for central in [0.0, 150.0] {
+        round_trips(&Robinson { central }, central);
+        round_trips(&Mollweide { central }, central);
+    }
+}
+
+#[test]
+fn equirectangular_keeps_the_degrees() {
+    assert_eq!(Equirectangular.project(21.0, 52.2), (21.0, 52.2));
+    assert_eq!(Equirectangular.unproject(-74.0, 40.7), Some((-74.0, 40.7)));
+}
+
+#[test]
+fn the_equator_keeps_its_degrees() {
+    for projection in [&Robinson { central: 0.0 } as &dyn Projection, &Mollweide { central: 0.0 }] {
+        for lon in [-180.0, -90.0, 0.0, 45.0, 180.0] {
+            let (x, y) = projection.project(lon, 0.0);
+            assert!((x - lon).abs() < CLOSE && y.abs() < CLOSE, "{lon} on the equator went to ({x}, {y})");
+        }
+    }
+}
+
+#[test]
+fn the_poles_flatten_or_close() {
+    // Robinson's poles are lines a little over half the equator, Mollweide's are points
+    let (west, pole) = Robinson { central: 0.0 }.project(-180.0, 90.0);
+    assert!((west + 180.0 * 0.5322).abs() < CLOSE, "Robinson's pole ends at {west}");
+    assert!(pole > 90.0 && pole < 92.0, "Robinson's pole at {pole}");
+    let (west, pole) = Mollweide { central: 0.0 }.project(-180.0, 90.0);
+    assert!(west.abs() < CLOSE && (pole - 90.0).abs() < CLOSE, "Mollweide's pole at ({west}, {pole})");
+}
+
+#[test]
+fn points_off_the_globe_are_none() {
+    assert_eq!(Mollweide { central: 0.0 }.unproject(0.0, 91.0), None);
+    assert_eq!(Mollweide { central: 0.0 }.unproject(170.0, 80.0), None);
+    assert_eq!(Robinson { central: 0.0 }.unproject(0.0, 95.0), None);
+    assert_eq!(Robinson { central: 0.0 }.unproject(185.0, 0.0), None);
+}
+
+#[test]
+fn the_config_picks_the_projection() {
+    let (lon, lat) = (100.0, 60.0);
+    let expected = [
+        (config::Projection::Corrected, Equirectangular.project(lon, lat)),
+        (config::Projection::Equirectangular, Equirectangular.project(lon, lat)),
+        (config::Projection::Robinson, Robinson { central: 10.0 }.project(lon, lat)),
+        (config::Projection::Mollweide, Mollweide { central: 10.0 }.project(lon, lat)),
+    ];
+    for (kind, point) in expected {
+        assert_eq!(projection(kind, 10.0).project(lon, lat), point, "{kind:?}");
+    }
+}
+
+#[test]
+fn p_cycles_the_projection() {
+    let (keys, unknown) = KeyBindings::new(&BTreeMap::new());
+    assert!(unknown.is_empty());
+    assert_eq!(keys.action(KeyCode::Char('p')), Some(Action::Projection));
+    assert_ne!(keys.action(KeyCode::Char('e')), Some(Action::Projection));
+    let mut kind = config::Projection::default();
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        seen.push(kind);
+        kind = kind.next();
+    }
+    assert_eq!(kind, config::Projection::default());
+    seen.dedup();
+    assert_eq!(seen.len(), 4);
+}
@@ -0,0 +1,128 @@
+//! The map highlight: pulsing between its color and the brighter variant on the tick,
+//! held still by --no-animations, and boxed in when it covers too few cells to notice
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use rustatlas::{
+    cli::{parse, Command},
+    data::DataCache,
+    map_draw::{cells_covered, too_small_to_see, MapStyle, MapView, MIN_VISIBLE_CELLS},
+    state::AppState,
+};
+use std::time::{Duration, Instant};
+
+/// Axis-aligned ring from `x0,y0` to `x1,y1`
+fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+/// Big at 0..40 x 0..40, Tiny a fifth of a degree across at 60,20
+fn view(name: &str) -> MapView {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Big", "Tiny"]).write(name).unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    let view = MapView::new(
+        collection(vec![feature("Big", rect(0.0, 0.0, 40.0, 40.0)), feature("Tiny", rect(60.0, 20.0, 60.2, 20.2))]),
+        &mut cache,
+    )
+    .unwrap();
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+    view
+}
+
+/// Cells of `buffer` drawn in `color`, as (column, row)
+fn cells(buffer: &Buffer, color: Color) -> Vec<(u16, u16)> {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+        .filter(|&(x, y)| buffer[(x, y)].fg == color)
+        .collect()
+}
+
+#[test]
+fn size_in_cells() {
+    // 100x50 data units into 102x52 cells: a unit a cell each way, inside the borders
+    let viewport = ([0.0, 100.0], [0.0, 50.0]);
+    let area = Rect::new(0, 0, 102, 52);
+    assert_eq!(cells_covered(([0.0, 10.0], [0.0, 5.0]), viewport, area), 50.0);
+    assert_eq!(cells_covered(([0.0, 1.0], [0.0, 1.0]), viewport, area), 1.0);
+    assert!(too_small_to_see(([0.0, 1.0], [0.0, 3.0]), viewport, area));
+    assert!(!too_small_to_see(([0.0, 2.0], [0.0, 2.0]), viewport, area));
+    assert_eq!(MIN_VISIBLE_CELLS, 4.0);
+    // The same box is smaller on a smaller screen
+    assert!(too_small_to_see(([0.0, 10.0], [0.0, 5.0]), viewport, Rect::new(0, 0, 12, 7)));
+}
+
+#[test]
+fn tiny_highlight_is_boxed() {
+    let view = view("pulse-box");
+    let area = Rect::new(0, 0, 80, 24);
+    let draw = |name: &str, bright: bool| {
+        let mut buffer = Buffer::empty(area);
+        view.widget().highlight(name).pulse(bright).render(area, &mut buffer);
+        buffer
+    };
+    let style = MapStyle::CLASSIC;
+
+    // Big is plain to see: no box, so nothing red beyond its own columns
+    let big = cells(&draw("Big", false), style.highlight);
+    let tiny = cells(&draw("Tiny", false), style.highlight);
+    assert!(!big.is_empty() && !tiny.is_empty());
+    assert!(big.iter().all(|p| p.0 < 80 * 3 / 4), "{:?}", big);
+    let spread = |c: &[(u16, u16)]| {
+        let xs = c.iter().map(|p| p.0);
+        let ys = c.iter().map(|p| p.1);
+        (xs.clone().max().unwrap() - xs.min().unwrap(), ys.clone().max().unwrap() - ys.min().unwrap())
+    };
+    // Tiny covers under a cell; its box a few cells each way
+    let (w, h) = spread(&tiny);
+    assert!(w >= 2 && h >= 1, "{:?}", tiny);
+    assert!(tiny.len() >= 6, "{:?}", tiny);
+    // Around Tiny, right of Big
+    let big_right = big.iter().map(|p| p.0).max().unwrap();
+    assert!(tiny.iter().all(|p| p.0 > big_right), "{:?} vs {}", tiny, big_right);
+
+    // The brighter phase draws the same cells in the brighter color, none in the plain one
+    let bright = draw("Tiny", true);
+    assert_eq!(cells(&bright, style.bright_highlight()), tiny);
+    assert!(cells(&bright, style.highlight).is_empty());
+    assert_eq!(MapStyle::CLASSIC.bright_highlight(), Color::LightRed);
+}
+
+#[test]
+fn pulses_on_the_tick() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("pulse-tick").unwrap();
+    let _ = std::fs::remove_file(fixture.dir.join("session.json"));
+    let mut state = AppState::open(&fixture.dir, false).unwrap();
+    let start = Instant::now();
+    assert!(!state.pulse_bright);
+
+    // Within the period nothing changes; after it, each period flips the phase
+    state.tick(start);
+    assert!(!state.pulse_bright);
+    state.tick(start + Duration::from_millis(1100));
+    assert!(state.pulse_bright);
+    assert!(state.view_model().pulse_bright);
+    state.tick(start + Duration::from_millis(1500));
+    assert!(state.pulse_bright);
+    state.tick(start + Duration::from_millis(2200));
+    assert!(!state.pulse_bright);
+
+    // Held still without animations
+    state.animations = false;
+    state.tick(start + Duration::from_secs(10));
+    assert!(!state.pulse_bright);
+    let _ = std::fs::remove_dir_all(&fixture.dir);
+}
+
+#[test]
+fn no_animations_flag() {
+    let animations = |args: &[&str]| match parse(args.iter().map(|a| a.to_string())).unwrap() {
+        Command::Tui { animations, .. } => animations,
+        _ => panic!("{:?}: not the TUI", args),
+    };
+    assert!(animations(&[]));
+    assert!(animations(&["--tour"]));
+    assert!(!animations(&["--no-animations"]));
+    assert!(!animations(&["--watch", "--no-animations"]));
+}
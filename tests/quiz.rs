@@ -0,0 +1,197 @@
+//! The map quiz: the draw weights and how answers update them, weighted picking, the
+//! summary of a quiz (including all-correct and empty ones), review mode, and answers
+//! kept in `quiz.json` across runs
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    keys::Action,
+    quiz::{pick, summarize, weight, Answer, Candidate, Quiz, QuizMode, QuizStats, Summary, Tally, QUESTIONS},
+    state::AppState,
+};
+use std::{path::PathBuf, thread, time::Duration};
+
+fn tally(hits: u32, misses: u32) -> Tally {
+    Tally { hits, misses }
+}
+
+fn answer(continent: &str, country: &str, correct: bool) -> Answer {
+    Answer { continent: continent.to_string(), country: country.to_string(), correct }
+}
+
+fn candidates(continent: &str, countries: &[&str]) -> Vec<Candidate> {
+    countries.iter().map(|c| Candidate { continent: continent.to_string(), country: c.to_string() }).collect()
+}
+
+#[test]
+fn weight_is_one_plus_three_per_miss_less_hits_at_least_one() {
+    assert_eq!(weight(Tally::default()), 1); // never played
+    assert_eq!(weight(tally(5, 0)), 1); // always right: floored
+    assert_eq!(weight(tally(0, 1)), 4);
+    assert_eq!(weight(tally(0, 2)), 7);
+    assert_eq!(weight(tally(2, 1)), 2);
+    assert_eq!(weight(tally(3, 1)), 1);
+    assert_eq!(weight(tally(10, 1)), 1);
+    assert_eq!(weight(tally(u32::MAX, 0)), 1);
+}
+
+#[test]
+fn answers_update_the_weight_of_their_country() {
+    let mut stats = QuizStats::default();
+    assert_eq!(stats.weight("Poland"), 1);
+    stats.record("Poland", false);
+    assert_eq!(stats.weight("Poland"), 4);
+    // Counted under the canonical key, whatever the spelling
+    stats.record("  poland ", false);
+    assert_eq!(stats.weight("POLAND"), 7);
+    assert_eq!(stats.countries["poland"], tally(0, 2));
+    for _ in 0..6 {
+        stats.record("Poland", true);
+    }
+    assert_eq!(stats.weight("Poland"), 1);
+    assert_eq!(stats.weight("Germany"), 1);
+}
+
+#[test]
+fn pick_lands_each_entry_as_often_as_its_weight() {
+    let weights = [1, 4, 0, 2];
+    let mut landed = [0; 4];
+    for roll in 0..7 {
+        landed[pick(&weights, roll).unwrap()] += 1;
+    }
+    assert_eq!(landed, [1, 4, 0, 2]);
+    assert_eq!(pick(&weights, 0), Some(0));
+    assert_eq!(pick(&weights, 1), Some(1));
+    assert_eq!(pick(&weights, 5), Some(3));
+    // Past the end, or nothing to pick from
+    assert_eq!(pick(&weights, 7), None);
+    assert_eq!(pick(&[], 0), None);
+    assert_eq!(pick(&[0, 0], 0), None);
+}
+
+#[test]
+fn summary_of_a_quiz() {
+    let answers = [
+        answer("Europe", "Poland", true),
+        answer("Europe", "Germany", false),
+        answer("Asia", "Japan", false),
+        answer("Asia", "China", false),
+        answer("Africa", "Kenya", true),
+    ];
+    let summary = summarize(&answers);
+    assert_eq!((summary.score, summary.total), (2, 5));
+    assert!((summary.accuracy - 0.4).abs() < 1e-12);
+    assert_eq!(summary.weakest, Some(("Asia".to_string(), 0, 2)));
+
+    // A tie in share goes to the continent asked more, then by name
+    let tie = summarize(&[
+        answer("Europe", "Poland", false),
+        answer("Asia", "Japan", false),
+        answer("Asia", "China", false),
+    ]);
+    assert_eq!(tie.weakest, Some(("Asia".to_string(), 0, 2)));
+    let tie = summarize(&[answer("Europe", "Poland", false), answer("Asia", "Japan", false)]);
+    assert_eq!(tie.weakest, Some(("Asia".to_string(), 0, 1)));
+}
+
+#[test]
+fn summary_of_all_correct_and_of_nothing() {
+    let all = summarize(&[answer("Europe", "Poland", true), answer("Asia", "Japan", true)]);
+    assert_eq!(all, Summary { score: 2, total: 2, accuracy: 1.0, weakest: None });
+    assert_eq!(summarize(&[]), Summary { score: 0, total: 0, accuracy: 0.0, weakest: None });
+}
+
+#[test]
+fn review_asks_only_countries_still_owed_one() {
+    let europe = candidates("Europe", &["Poland", "Germany", "Spain", "France"]);
+    let mut stats = QuizStats::default();
+    // Never played, or all correct: nothing to review
+    assert!(Quiz::start(QuizMode::Review, europe.clone(), &stats).is_none());
+    stats.record("Germany", true);
+    assert!(Quiz::start(QuizMode::Review, europe.clone(), &stats).is_none());
+
+    stats.record("Poland", false);
+    stats.record("Spain", false);
+    stats.record("Spain", true);
+    stats.record("Spain", true);
+    stats.record("Spain", true); // 1 + 3 - 3: made up for
+    let mut quiz = Quiz::start(QuizMode::Review, europe.clone(), &stats).unwrap();
+    assert_eq!(quiz.progress(), (1, 1));
+    let question = quiz.question.clone().unwrap();
+    assert_eq!(question.answer.country, "Poland");
+    // Wrong options still come from the whole map
+    assert_eq!(question.options.len(), 4);
+    assert!(question.options.contains(&"Poland".to_string()));
+
+    quiz.question.as_mut().unwrap().selected = question.options.iter().position(|o| o == "Poland").unwrap();
+    let given = quiz.answer(&mut stats).unwrap();
+    assert!(given.correct);
+    assert!(quiz.question.is_none());
+    assert_eq!(stats.weight("Poland"), 3);
+}
+
+#[test]
+fn a_full_quiz_asks_each_country_once_and_counts_every_answer() {
+    let countries: Vec<String> = (0..QUESTIONS + 3).map(|i| format!("Country {}", i)).collect();
+    let names: Vec<&str> = countries.iter().map(String::as_str).collect();
+    let mut stats = QuizStats::default();
+    let mut quiz = Quiz::start(QuizMode::All, candidates("Testland", &names), &stats).unwrap();
+    let mut asked = Vec::new();
+    while let Some(question) = quiz.question.clone() {
+        assert_eq!(quiz.progress(), (asked.len() + 1, QUESTIONS));
+        asked.push(question.answer.country);
+        quiz.answer(&mut stats);
+    }
+    asked.sort();
+    asked.dedup();
+    assert_eq!(asked.len(), QUESTIONS);
+    assert_eq!(stats.countries.values().map(|t| t.hits + t.misses).sum::<u32>(), QUESTIONS as u32);
+    // Fewer than two countries: no options to choose from
+    assert!(Quiz::start(QuizMode::All, candidates("Testland", &["Alone"]), &stats).is_none());
+}
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new().continent("Testland", ["Alpha", "Beta", "Gamma", "Delta"]).write(name).unwrap().dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+    while state.map_job.is_some() {
+        thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+#[test]
+fn answers_are_kept_across_runs() {
+    let dir = atlas("quiz-persist");
+    let mut state = open(&dir);
+    press(&mut state, Action::Select);
+    press(&mut state, Action::Quiz);
+    // Always the first option: some right, most likely some wrong
+    while state.quiz.as_ref().unwrap().question.is_some() {
+        press(&mut state, Action::Select);
+    }
+    let answers = state.quiz.as_ref().unwrap().answers.clone();
+    assert_eq!(answers.len(), 4);
+    press(&mut state, Action::Select);
+    assert!(state.quiz.is_none());
+    let stats = state.quiz_stats.get().clone();
+    state.save_session().unwrap();
+    assert!(dir.join("quiz.json").exists());
+
+    let state = open(&dir);
+    assert_eq!(state.quiz_stats.get(), &stats);
+    for a in &answers {
+        let tally = stats.countries[&a.country.to_lowercase()];
+        assert_eq!((tally.hits, tally.misses), (a.correct as u32, !a.correct as u32));
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
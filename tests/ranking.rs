@@ -0,0 +1,193 @@
+//! The continent ranking: filter expressions parsed and applied, rows sorted by each
+//! column with missing values last, and the table driven by its keys, a filter that
+//! doesn't parse leaving the last good one in force
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    filter::{parse, Field, Filter, Op, Term, Values},
+    keys::Action,
+    state::{AppState, Location, Panel, RankColumn, RankRow, Ranking},
+    ui,
+};
+use std::{fs, time::Duration};
+
+fn row(name: &str, population: Option<u64>, area: Option<f64>, gdp: Option<f64>) -> RankRow {
+    RankRow { name: name.to_string(), population, area, gdp }
+}
+
+fn names(ranking: &Ranking) -> Vec<&str> {
+    ranking.visible().into_iter().map(|(_, row)| row.name.as_str()).collect()
+}
+
+#[test]
+fn expressions_parse() {
+    assert_eq!(parse("").unwrap(), Filter::default());
+    assert_eq!(parse("  ").unwrap(), Filter::default());
+    assert_eq!(
+        parse("Slo pop>10m gdp<=2.5bn area>=1k area<5 pop=7").unwrap().terms,
+        [
+            Term::Name("slo".to_string()),
+            Term::Compare(Field::Population, Op::Gt, 1e7),
+            Term::Compare(Field::Gdp, Op::Le, 2.5e9),
+            Term::Compare(Field::Area, Op::Ge, 1e3),
+            Term::Compare(Field::Area, Op::Lt, 5.0),
+            Term::Compare(Field::Population, Op::Eq, 7.0),
+        ]
+    );
+    assert_eq!(parse("POPULATION>1M").unwrap().terms, [Term::Compare(Field::Population, Op::Gt, 1e6)]);
+
+    // What is wrong, in Polish
+    assert_eq!(parse(">10m").unwrap_err(), "brak pola przed '>10m' (pop, area, gdp)");
+    assert_eq!(parse("size>10").unwrap_err(), "nieznane pole 'size' (pop, area, gdp)");
+    assert_eq!(parse("pop=>10").unwrap_err(), "zła liczba '>10' (np. 10m, 2.5bn)");
+    assert_eq!(parse("pop>ten").unwrap_err(), "zła liczba 'ten' (np. 10m, 2.5bn)");
+    assert_eq!(parse("pop>").unwrap_err(), "zła liczba '' (np. 10m, 2.5bn)");
+    assert_eq!(parse("pop>1e999").unwrap_err(), "zła liczba '1e999' (np. 10m, 2.5bn)");
+    // One bad term spoils the whole expression
+    assert!(parse("slo gdp>x").is_err());
+}
+
+#[test]
+fn every_term_must_hold() {
+    let filter = parse("ov pop>=2m gdp<1bn").unwrap();
+    let values = |name, population, gdp| Values { name, population, area: None, gdp };
+    assert!(filter.matches(&values("Slovakia", Some(5e6), Some(1e8))));
+    assert!(filter.matches(&values("SLOVENIA", Some(2e6), Some(9.9e8))));
+    assert!(!filter.matches(&values("Poland", Some(3.8e7), Some(1e8))));
+    assert!(!filter.matches(&values("Slovakia", Some(1e6), Some(1e8))));
+    assert!(!filter.matches(&values("Slovakia", Some(5e6), Some(1e9))));
+    // A comparison on a missing value fails; names alone don't look at the numbers
+    assert!(!filter.matches(&values("Slovakia", Some(5e6), None)));
+    assert!(parse("ov").unwrap().matches(&values("Slovakia", None, None)));
+    assert!(Filter::default().matches(&values("", None, None)));
+}
+
+#[test]
+fn sorted_by_each_column_missing_last() {
+    let mut ranking = Ranking::new(vec![
+        row("Beta", Some(200), None, Some(5.0)),
+        row("Alpha", Some(100), Some(30.0), None),
+        row("Delta", None, Some(10.0), Some(7.0)),
+        row("Gamma", Some(200), Some(20.0), Some(6.0)),
+    ]);
+    // Population first, largest first; ties by name
+    assert_eq!(ranking.sort, RankColumn::Population);
+    assert_eq!(names(&ranking), ["Beta", "Gamma", "Alpha", "Delta"]);
+    let mut order = Vec::new();
+    for _ in 0..4 {
+        ranking.sort_by(ranking.sort.next());
+        order.push((ranking.sort, names(&ranking).join(" ")));
+    }
+    assert_eq!(
+        order,
+        [
+            (RankColumn::Area, "Alpha Gamma Delta Beta".to_string()),
+            (RankColumn::Gdp, "Delta Gamma Beta Alpha".to_string()),
+            (RankColumn::Name, "Alpha Beta Delta Gamma".to_string()),
+            (RankColumn::Population, "Beta Gamma Alpha Delta".to_string()),
+        ]
+    );
+
+    // Filtered rows keep their place in the whole ranking
+    ranking.input.set("gdp>5.5");
+    ranking.refilter();
+    let visible: Vec<(usize, &str)> = ranking.visible().into_iter().map(|(i, r)| (i, r.name.as_str())).collect();
+    assert_eq!(visible, [(2, "Gamma"), (4, "Delta")]);
+    assert_eq!(ranking.selected_name().as_deref(), Some("Gamma"));
+    // A bad edit is explained and changes nothing else
+    ranking.selected = 1;
+    ranking.input.set("gdp>");
+    ranking.refilter();
+    assert_eq!(ranking.error.as_deref(), Some("zła liczba '' (np. 10m, 2.5bn)"));
+    assert_eq!((names(&ranking), ranking.selected), (vec!["Gamma", "Delta"], 1));
+    ranking.input.set("zzz");
+    ranking.refilter();
+    assert_eq!((ranking.error.clone(), ranking.selected_name()), (None, None));
+}
+
+#[test]
+fn table_driven_by_its_keys() {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Slovakia", "Slovenia"])
+        .gdp("Poland", 2020..=2020, |_| 7e11)
+        .gdp("Slovakia", 2020..=2020, |_| 1e11)
+        .gdp("Slovenia", 2020..=2020, |_| 6e10)
+        .write("ranking-keys")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+    let press = |state: &mut AppState, action| {
+        state.handle_input(state.keys.key(action).unwrap());
+    };
+    let screen = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+        terminal.draw(|f| { ui::draw(f, &state.view_model()); }).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..30).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect::<String>()
+    };
+
+    press(&mut state, Action::Ranking);
+    assert!(screen(&state).contains("pokazano 3 z 3"), "{}", screen(&state));
+    // s: area, then GDP, largest first
+    press(&mut state, Action::SortColumn);
+    press(&mut state, Action::SortColumn);
+    assert_eq!(state.ranking.as_ref().unwrap().sort, RankColumn::Gdp);
+    assert_eq!(names(state.ranking.as_ref().unwrap()), ["Poland", "Slovakia", "Slovenia"]);
+
+    // Typed a key at a time, applied as it goes
+    press(&mut state, Action::Filter);
+    assert!(state.ranking.as_ref().unwrap().editing);
+    for c in "slo gdp<".chars() {
+        state.handle_input(KeyCode::Char(c));
+    }
+    // "slo gdp" was the last to parse, a name term too many; "slo gdp<" only explained
+    let ranking = state.ranking.as_ref().unwrap();
+    assert!(names(ranking).is_empty());
+    assert_eq!(ranking.filter, parse("slo gdp").unwrap());
+    assert!(ranking.error.is_some());
+    assert!(screen(&state).contains("zła liczba ''"), "{}", screen(&state));
+    for c in "80bn".chars() {
+        state.handle_input(KeyCode::Char(c));
+    }
+    assert_eq!(names(state.ranking.as_ref().unwrap()), ["Slovenia"]);
+    state.handle_input(KeyCode::Enter);
+    assert!(!state.ranking.as_ref().unwrap().editing);
+    let text = screen(&state);
+    assert!(text.contains("pokazano 1 z 3") && text.contains("/ slo gdp<80bn"), "{}", text);
+
+    // Enter opens the selected row of the filtered ones
+    press(&mut state, Action::Select);
+    assert_eq!(
+        state.location,
+        Location::Country { continent: "Europe".to_string(), country: "Slovenia".to_string() }
+    );
+    assert!(state.ranking.is_none());
+
+    // Esc while typing clears the filter
+    press(&mut state, Action::Back);
+    press(&mut state, Action::Ranking);
+    press(&mut state, Action::Filter);
+    state.handle_input(KeyCode::Char('x'));
+    assert!(names(state.ranking.as_ref().unwrap()).is_empty());
+    state.handle_input(KeyCode::Esc);
+    let ranking = state.ranking.as_ref().unwrap();
+    assert!(!ranking.editing && ranking.input.is_empty());
+    assert_eq!(names(ranking).len(), 3);
+    press(&mut state, Action::Ranking);
+    assert!(state.ranking.is_none());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,252 @@
+//! The `online` data source against a tiny in-process HTTP server serving a fixture atlas:
+//! cache hits, ETag and Last-Modified revalidation and falling back to the cached copies
+//! when the server is down
+#![cfg(feature = "online")]
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::{
+    data::{DataCache, DataSource, GeoLevel},
+    gdp_reader::{GDPData, GDP_CSV},
+    remote::{encode, HttpSource},
+};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+
+const LAST_MODIFIED: &str = "Wed, 01 Jan 2025 00:00:00 GMT";
+
+/// Which validators the server sends and honours
+#[derive(Clone, Copy)]
+enum Validator {
+    ETag,
+    LastModified,
+}
+
+/// HTTP server on 127.0.0.1 serving the files of a directory under `/atlas/`, one
+/// request per connection, noting each request's path and conditional headers
+struct Server {
+    port: u16,
+    requests: Arc<Mutex<Vec<Request>>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Clone, Debug)]
+struct Request {
+    path: String,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+    status: u16,
+}
+
+impl Server {
+    fn start(dir: PathBuf, validator: Validator) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let (requests, stop) = (requests.clone(), stop.clone());
+            move || {
+                for stream in listener.incoming() {
+                    if stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Ok(stream) = stream {
+                        let request = serve(stream, &dir, validator);
+                        requests.lock().unwrap().push(request);
+                    }
+                }
+            }
+        });
+        Self { port, requests, stop, thread: Some(thread) }
+    }
+
+    fn url(&self) -> String {
+        format!("http://127.0.0.1:{}/atlas/", self.port)
+    }
+
+    /// Requests answered so far, and forget them
+    fn take(&self) -> Vec<Request> {
+        std::mem::take(&mut *self.requests.lock().unwrap())
+    }
+
+    /// Stop listening: the port refuses connections from now on
+    fn shut_down(mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = TcpStream::connect(("127.0.0.1", self.port));
+        if let Some(thread) = self.thread.take() {
+            thread.join().unwrap();
+        }
+    }
+}
+
+/// Answer one request with the file under `dir` its path names, 304 when the client's
+/// validator still matches
+fn serve(mut stream: TcpStream, dir: &Path, validator: Validator) -> Request {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let path = line.split_whitespace().nth(1).unwrap_or("").to_string();
+    let (mut if_none_match, mut if_modified_since) = (None, None);
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let Some((name, value)) = header.trim_end().split_once(':') else { break };
+        match name.to_ascii_lowercase().as_str() {
+            "if-none-match" => if_none_match = Some(value.trim().to_string()),
+            "if-modified-since" => if_modified_since = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let file = path.strip_prefix("/atlas/").map(|name| dir.join(name));
+    let (status, body, validator_header) = match file.and_then(|file| fs::read(file).ok()) {
+        None => (404, Vec::new(), String::new()),
+        Some(body) => {
+            let etag = format!("\"{}-{}\"", body.len(), body.iter().map(|&b| b as u64).sum::<u64>());
+            let (fresh, header) = match validator {
+                Validator::ETag => (if_none_match.as_deref() == Some(etag.as_str()), format!("ETag: {}\r\n", etag)),
+                Validator::LastModified => (
+                    if_modified_since.as_deref() == Some(LAST_MODIFIED),
+                    format!("Last-Modified: {}\r\n", LAST_MODIFIED),
+                ),
+            };
+            if fresh { (304, Vec::new(), header) } else { (200, body, header) }
+        }
+    };
+    let head = format!("HTTP/1.1 {} X\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n", status, body.len(), validator_header);
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&body);
+    Request { path, if_none_match, if_modified_since, status }
+}
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Alpha", 2000..=2002, f64::from)
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+/// Empty cache root for a test
+fn cache_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("rustatlas-remote-cache-{}", name));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+#[test]
+fn nested_names_keep_their_slashes() {
+    assert_eq!(encode("dataPKB/pkb.csv"), "dataPKB/pkb.csv");
+    assert_eq!(encode("country_côte d'ivoire.geojson"), "country_c%C3%B4te%20d%27ivoire.geojson");
+    assert_eq!(encode("a b/c?d"), "a%20b/c%3Fd");
+}
+
+#[test]
+fn atlas_loads_over_http_and_hits_the_cache() {
+    let dir = atlas("remote-load");
+    let server = Server::start(dir.clone(), Validator::ETag);
+    let root = cache_root("load");
+    let source = HttpSource::new(&server.url(), &root).unwrap();
+
+    // The GDP CSV in its subdirectory comes through like the top-level files
+    let gdp = GDPData::new(source.path(GDP_CSV)).unwrap();
+    assert_eq!(gdp.get_latest_gdp("Alpha"), Some((2002, 2002.0)));
+    let requests = server.take();
+    assert_eq!(requests.iter().map(|r| (r.path.as_str(), r.status)).collect::<Vec<_>>(), [("/atlas/dataPKB/pkb.csv", 200)]);
+
+    // Asked again in the same run, a file is read from the cache without a request
+    assert_eq!(fs::read(source.path(GDP_CSV)).unwrap(), fs::read(dir.join(GDP_CSV)).unwrap());
+    assert!(server.take().is_empty());
+
+    let mut cache = DataCache::with_source(Box::new(source));
+    assert_eq!(cache.load_list(GeoLevel::World, "world").unwrap(), ["Testland"]);
+    assert_eq!(cache.load_list(GeoLevel::Continent, "Testland").unwrap(), ["Alpha", "Beta"]);
+    assert!(cache.take_notices().is_empty());
+    server.shut_down();
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn next_run_revalidates_with_the_etag() {
+    let dir = atlas("remote-etag");
+    let server = Server::start(dir.clone(), Validator::ETag);
+    let root = cache_root("etag");
+    HttpSource::new(&server.url(), &root).unwrap().path("continent_world.json");
+    let first = server.take();
+    assert_eq!(first[0].status, 200);
+    assert_eq!(first[0].if_none_match, None);
+
+    // Unchanged on the server: 304, and the copy stays
+    let source = HttpSource::new(&server.url(), &root).unwrap();
+    let copy = source.path("continent_world.json");
+    let second = server.take();
+    assert_eq!(second.len(), 1);
+    assert!(second[0].if_none_match.is_some());
+    assert_eq!(second[0].status, 304);
+    assert_eq!(fs::read_to_string(&copy).unwrap(), "[\"Testland\"]");
+
+    // Changed on the server: the new contents replace the copy
+    fs::write(dir.join("continent_world.json"), "[\"Testland\",\"Otherland\"]").unwrap();
+    let copy = HttpSource::new(&server.url(), &root).unwrap().path("continent_world.json");
+    assert_eq!(server.take()[0].status, 200);
+    assert_eq!(fs::read_to_string(&copy).unwrap(), "[\"Testland\",\"Otherland\"]");
+    server.shut_down();
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn next_run_revalidates_with_last_modified() {
+    let dir = atlas("remote-modified");
+    let server = Server::start(dir.clone(), Validator::LastModified);
+    let root = cache_root("modified");
+    HttpSource::new(&server.url(), &root).unwrap().path("country_info.json");
+    assert_eq!(server.take()[0].if_modified_since, None);
+
+    let copy = HttpSource::new(&server.url(), &root).unwrap().path("country_info.json");
+    let requests = server.take();
+    assert_eq!(requests[0].if_modified_since.as_deref(), Some(LAST_MODIFIED));
+    assert_eq!(requests[0].status, 304);
+    assert_eq!(fs::read(&copy).unwrap(), fs::read(dir.join("country_info.json")).unwrap());
+    server.shut_down();
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&root);
+}
+
+#[test]
+fn server_down_falls_back_to_the_cache() {
+    let dir = atlas("remote-down");
+    let server = Server::start(dir.clone(), Validator::ETag);
+    let url = server.url();
+    let root = cache_root("down");
+    HttpSource::new(&url, &root).unwrap().path("continent_world.json");
+    server.shut_down();
+
+    let source = HttpSource::new(&url, &root).unwrap();
+    let copy = source.path("continent_world.json");
+    assert_eq!(fs::read_to_string(&copy).unwrap(), "[\"Testland\"]");
+    let notices = source.take_notices();
+    assert_eq!(notices.len(), 1, "{notices:?}");
+    assert!(notices[0].starts_with("continent_world.json: serwer niedostępny"), "{notices:?}");
+    assert!(notices[0].contains("może być nieaktualna"), "{notices:?}");
+    assert!(source.take_notices().is_empty());
+
+    // Never fetched, nothing to fall back on
+    assert!(!source.path("country_info.json").exists());
+    assert!(source.take_notices().is_empty());
+    let _ = fs::remove_dir_all(&dir);
+    let _ = fs::remove_dir_all(&root);
+}
@@ -0,0 +1,156 @@
+//! Per-country render hints: the country's own map framed by its padding or bounds, its
+//! name printed at the hinted anchor and only from `min_zoom` on, the highlight boxed with
+//! `force_marker`, and check-data naming the hints that can't apply
+mod common;
+
+use common::{collection, feature, FixtureAtlas};
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Widget};
+use rustatlas::{
+    check,
+    data::{CountryHints, RenderHints},
+    map_draw::{MapStyle, MapView, OutlierPolicy},
+};
+use std::{collections::BTreeMap, fs};
+
+/// Axis-aligned ring from `x0,y0` to `x1,y1`
+fn rect(x0: f64, y0: f64, x1: f64, y1: f64) -> Vec<Vec<f64>> {
+    vec![vec![x0, y0], vec![x1, y0], vec![x1, y1], vec![x0, y1], vec![x0, y0]]
+}
+
+fn hints(json: &str) -> RenderHints {
+    serde_json::from_str(json).unwrap()
+}
+
+/// Long at 0..10 x 0..40 and Wide at 20..60 x 0..10, built with `hints`
+fn two(hints: &RenderHints) -> MapView {
+    let features = collection(vec![feature("Long", rect(0.0, 0.0, 10.0, 40.0)), feature("Wide", rect(20.0, 0.0, 60.0, 10.0))]);
+    MapView::build(features, BTreeMap::new(), hints, OutlierPolicy::default()).unwrap()
+}
+
+/// Long alone, as on its own map
+fn own(hints: &RenderHints) -> MapView {
+    MapView::build(collection(vec![feature("Long", rect(0.0, 0.0, 10.0, 40.0))]), BTreeMap::new(), hints, OutlierPolicy::default()).unwrap()
+}
+
+#[test]
+fn parsed_with_every_field_optional() {
+    let all = hints(r#"{"countries": {"long": {"padding": [0.5, 0.1], "bounds": [[-5, 15], [0, 40]], "label": [5, 30], "force_marker": true, "min_zoom": 2}}}"#);
+    assert_eq!(
+        all.country("LONG"),
+        Some(&CountryHints {
+            padding: Some([0.5, 0.1]),
+            bounds: Some([[-5.0, 15.0], [0.0, 40.0]]),
+            label: Some([5.0, 30.0]),
+            force_marker: true,
+            min_zoom: Some(2.0),
+        })
+    );
+    assert_eq!(hints(r#"{"countries": {"Long": {}}}"#).country("Long"), Some(&CountryHints::default()));
+    assert_eq!(all.country("Wide"), None);
+    assert_eq!(hints(r#"{"keep_islands": {"A": true}, "countries": {"B": {}}}"#).names().collect::<Vec<_>>(), ["A", "B"]);
+}
+
+#[test]
+fn framing_on_the_own_map_only() {
+    // Padding: shares of the country's width and height each side
+    let padded = hints(r#"{"countries": {"Long": {"padding": [0.5, 0.1]}}}"#);
+    assert_eq!(own(&padded).hinted_viewport("Long"), Some(([-5.0, 15.0], [-4.0, 44.0])));
+    assert_eq!(own(&padded).preferred_viewport("Long"), ([-5.0, 15.0], [-4.0, 44.0]));
+    // Bounds win over padding
+    let bounded = hints(r#"{"countries": {"Long": {"padding": [0.5, 0.1], "bounds": [[-20, 30], [-1, 41]]}}}"#);
+    assert_eq!(own(&bounded).hinted_viewport("Long"), Some(([-20.0, 30.0], [-1.0, 41.0])));
+    // A view focused on it counts as its own map
+    let focused = two(&bounded).into_focused("Long").unwrap();
+    assert_eq!(focused.hinted_viewport("Long"), Some(([-20.0, 30.0], [-1.0, 41.0])));
+
+    // On a shared map, or without hints, the usual bounds
+    let shared = two(&bounded);
+    assert_eq!(shared.hinted_viewport("Long"), None);
+    assert_eq!(shared.preferred_viewport("Long"), shared.bounds_including("Long"));
+    let plain = own(&RenderHints::default());
+    assert_eq!(plain.hinted_viewport("Long"), None);
+    assert_eq!(plain.preferred_viewport("Long"), plain.bounds());
+}
+
+/// Where `word` is printed in `buffer`: its first cell
+fn find(buffer: &Buffer, word: &str) -> Option<(u16, u16)> {
+    let area = buffer.area;
+    (area.top()..area.bottom()).find_map(|y| {
+        let row: Vec<&str> = (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect();
+        let x = (0..row.len()).find(|&x| row[x..].concat().starts_with(word))?;
+        Some((area.left() + x as u16, y))
+    })
+}
+
+fn labelled(view: &MapView) -> Buffer {
+    let area = Rect::new(0, 0, 80, 30);
+    let mut buffer = Buffer::empty(area);
+    view.widget().labels(true).render(area, &mut buffer);
+    buffer
+}
+
+#[test]
+fn label_anchor_and_min_zoom() {
+    // The middle of Long's polygon, then the top of it
+    let plain = find(&labelled(&two(&RenderHints::default())), "Long").unwrap();
+    let anchored = find(&labelled(&two(&hints(r#"{"countries": {"Long": {"label": [5, 38]}}}"#))), "Long").unwrap();
+    assert!(anchored.1 + 5 < plain.1, "{:?} {:?}", anchored, plain);
+
+    // Wide waits for the zoom level it asks for
+    let mut view = two(&hints(r#"{"countries": {"Wide": {"min_zoom": 1.5}}}"#));
+    assert!(find(&labelled(&view), "Wide").is_none());
+    assert!(find(&labelled(&view), "Long").is_some());
+    // Closer, and down to where Wide is
+    view.zoom(2.0);
+    view.pan(0.0, -1.0);
+    assert!(view.zoom_level() >= 1.5);
+    assert!(find(&labelled(&view), "Wide").is_some());
+}
+
+#[test]
+fn forced_marker_boxes_a_visible_highlight() {
+    let area = Rect::new(0, 0, 80, 30);
+    let features = || {
+        collection(vec![
+            feature("West", rect(0.0, 0.0, 10.0, 40.0)),
+            feature("Middle", rect(25.0, 15.0, 35.0, 25.0)),
+            feature("East", rect(50.0, 0.0, 60.0, 40.0)),
+        ])
+    };
+    // Columns and rows the highlight's red spans
+    let red = |hints: &RenderHints| {
+        let view = MapView::build(features(), BTreeMap::new(), hints, OutlierPolicy::default()).unwrap();
+        let mut buffer = Buffer::empty(area);
+        view.widget().highlight("Middle").render(area, &mut buffer);
+        let cells: Vec<(u16, u16)> = (0..30)
+            .flat_map(|y| (0..80).map(move |x| (x, y)))
+            .filter(|&p| buffer[p].fg == MapStyle::CLASSIC.highlight)
+            .collect();
+        let (xs, ys) = (cells.iter().map(|p| p.0), cells.iter().map(|p| p.1));
+        ((xs.clone().min().unwrap(), xs.max().unwrap()), (ys.clone().min().unwrap(), ys.max().unwrap()))
+    };
+    let (x, y) = red(&RenderHints::default());
+    let (boxed_x, boxed_y) = red(&hints(r#"{"countries": {"Middle": {"force_marker": true}}}"#));
+    // The box reaches past the country on every side
+    assert!(boxed_x.0 < x.0 && boxed_x.1 > x.1, "{:?} boxed {:?}", x, boxed_x);
+    assert!(boxed_y.0 < y.0 && boxed_y.1 > y.1, "{:?} boxed {:?}", y, boxed_y);
+}
+
+#[test]
+fn check_data_names_hints_that_cannot_apply() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write("hints-check").unwrap().dir;
+    fs::write(
+        dir.join("render_hints.json"),
+        r#"{"keep_islands": {"Atlantis": true},
+            "countries": {"Alpha": {"bounds": [[10, 0], [0, 5]]}, "Beta": {"padding": [-0.1, 0]}, "Lemuria": {}}}"#,
+    )
+    .unwrap();
+    let report = check::report(&dir).unwrap();
+    let expected = "2 names in render_hints.json match no map feature, 2 have inverted bounds or negative padding\n  Atlantis\n  Lemuria\n  Alpha (bounds or padding)\n  Beta (bounds or padding)\n";
+    assert!(report.text.contains(expected), "report:\n{}", report.text);
+
+    fs::write(dir.join("render_hints.json"), r#"{"countries": {"Alpha": {"padding": [0.2, 0.2]}}}"#).unwrap();
+    let report = check::report(&dir).unwrap();
+    assert!(report.text.contains("0 names in render_hints.json match no map feature, 0 have"), "report:\n{}", report.text);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,104 @@
+//! The continent scatter plot: which countries it places and which it counts as missing,
+//! the per-capita lines clipped to the plot, and the plot on screen following the
+//! selection
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Scatter},
+    ui::{self, per_capita_line},
+};
+use std::fs;
+
+fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|n| n.to_string()).collect()
+}
+
+#[test]
+fn countries_without_values_a_log_axis_can_show_are_missing() {
+    let members = names(&["Alpha", "Beta", "Gamma", "Delta", "Epsilon", "Zeta"]);
+    let scatter = Scatter::assemble(&members, |name| match name {
+        "Alpha" => Some((1_000_000, 1e9)),
+        "Beta" => Some((38_000_000, 6e11)),
+        // No population, no GDP, or either not positive
+        "Gamma" => None,
+        "Delta" => Some((0, 1e9)),
+        "Epsilon" => Some((5_000, 0.0)),
+        _ => Some((5_000, -1e6)),
+    });
+    let placed: Vec<(&str, f64, f64)> = scatter.points.iter().map(|p| (p.name.as_str(), p.population, p.gdp)).collect();
+    assert_eq!(placed, [("Alpha", 1e6, 1e9), ("Beta", 3.8e7, 6e11)]);
+    assert_eq!(scatter.missing, 4);
+    assert_eq!(Scatter::assemble(&[], |_| None), Scatter::default());
+}
+
+#[test]
+fn per_capita_lines_are_clipped_to_the_plot() {
+    // $1k a head from 1M people (1B) up to 100M people (100B)
+    assert_eq!(per_capita_line(1e3, [6.0, 8.0], [9.0, 12.0]), Some(((6.0, 9.0), (8.0, 11.0))));
+    // Entering through the bottom and leaving through the top
+    assert_eq!(per_capita_line(1e4, [6.0, 8.0], [11.0, 12.0]), Some(((7.0, 11.0), (8.0, 12.0))));
+    // Above and below the plot, or only touching its corner
+    assert_eq!(per_capita_line(1e5, [6.0, 7.0], [9.0, 10.0]), None);
+    assert_eq!(per_capita_line(1.0, [6.0, 7.0], [9.0, 10.0]), None);
+    assert_eq!(per_capita_line(1e3, [6.0, 7.0], [10.0, 11.0]), None);
+}
+
+#[test]
+fn plot_counts_the_missing_and_labels_the_selection() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .gdp("Alpha", 2000..=2001, |_| 2e9)
+        .gdp("Beta", 2000..=2001, |_| 3e11)
+        .write("scatter-plot")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    let scatter = state.keys.key(Action::Scatter).unwrap();
+    // Only a continent has a plot
+    state.handle_input(scatter);
+    assert!(state.scatter.is_none());
+    state.selected = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    state.handle_input(scatter);
+    let plot = state.scatter.as_ref().unwrap();
+    assert_eq!(plot.points.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(), ["Alpha", "Beta"]);
+    assert_eq!(plot.missing, 1);
+
+    let screen = |state: &AppState| {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+        let buffer = terminal.backend().buffer().clone();
+        (0..40).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect::<String>()
+    };
+    // The plot, between the list and the info panel
+    let plot_rows = |text: &str| -> Vec<String> {
+        text.lines().filter_map(|l| l.split('│').nth(3).map(str::to_string)).collect()
+    };
+    state.selected = state.list_items.iter().position(|c| c == "Alpha").unwrap();
+    let text = screen(&state);
+    assert!(text.contains("GDP a populacja (log)"), "{}", text);
+    assert!(text.contains("1 kraj bez danych"), "{}", text);
+    // The y axis in whole decades around 2B..300B, the x axis around a million people
+    for label in ["1B", "1T", "1M", "10M"] {
+        assert!(text.contains(label), "{} missing:\n{}", label, text);
+    }
+    let rows = plot_rows(&text);
+    assert!(rows.iter().any(|r| r.contains("Alpha")) && !rows.iter().any(|r| r.contains("Beta")), "{}", text);
+
+    // The label follows the selection
+    state.selected = state.list_items.iter().position(|c| c == "Beta").unwrap();
+    let rows = plot_rows(&screen(&state));
+    assert!(rows.iter().any(|r| r.contains("Beta")) && !rows.iter().any(|r| r.contains("Alpha")));
+
+    // Off again with the key
+    state.handle_input(scatter);
+    assert!(state.scatter.is_none());
+    let _ = fs::remove_dir_all(&dir);
+}
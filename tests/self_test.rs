@@ -0,0 +1,90 @@
+//! `self-test` on fixture data: every step passing on a complete atlas, a country the GDP
+//! dataset misses failing its step with the data exit code, a missing directory refused,
+//! and nothing saved to `session.json` on the way
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use rustatlas::cli::{parse, Command};
+use std::{fs, path::Path, process::{self, Output}};
+
+fn self_test(dir: &Path) -> Output {
+    process::Command::new(env!("CARGO_BIN_EXE_RustAtlas")).arg("--data-dir").arg(dir).arg("self-test").output().unwrap()
+}
+
+/// The step `name`'s line of the report: its status and what it saw
+fn step(stdout: &str, name: &str) -> (String, String) {
+    let line = stdout.lines().find(|l| l.split("  ").map(str::trim).any(|part| part == name)).unwrap_or_else(|| panic!("no step {:?} in:\n{}", name, stdout));
+    let status = line.split_whitespace().next().unwrap().to_string();
+    let seen = line.split(" ms  ").nth(1).unwrap_or("").to_string();
+    (status, seen)
+}
+
+#[test]
+fn every_step_passes_on_a_complete_atlas() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(2.0, 0.0))
+        .gdp("Alpha", 2000..=2010, |year| 1e9 * f64::from(year - 1999))
+        .gdp("Beta", 2000..=2010, |_| 2e9)
+        .write("self-test-ok")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let output = self_test(&dir);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}{}", stdout, String::from_utf8_lossy(&output.stderr));
+    for name in [
+        "load atlas",
+        "world map",
+        "help overlay",
+        "diagnostics overlay",
+        "continent map",
+        "choropleth",
+        "ranking table",
+        "scatter plot",
+        "country view",
+        "GDP chart",
+        "back to world",
+    ] {
+        assert_eq!(step(&stdout, name).0, "ok", "{}", stdout);
+    }
+    assert_eq!(step(&stdout, "continent map").1, "Testland: 2 list items, map drawn");
+    assert!(step(&stdout, "country view").1.ends_with("GDP for 2010"), "{}", stdout);
+    assert_eq!(step(&stdout, "GDP chart").1.split(',').next(), Some("11 years"));
+    assert!(stdout.contains("Passed 11 of 11 checks in "), "{}", stdout);
+    assert!(!dir.join("session.json").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn country_without_gdp_fails_with_the_data_code() {
+    // Alpha, opened first, is not in the dataset Beta is in
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Beta", 2000..=2010, |_| 2e9)
+        .write("self-test-no-gdp")
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let output = self_test(&dir);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(4), "{}", stdout);
+    assert_eq!(step(&stdout, "country view"), ("FAIL".to_string(), "no GDP for Alpha though the dataset is loaded".to_string()));
+    // The chart has nothing to show, and says so rather than failing too
+    assert_eq!(step(&stdout, "GDP chart"), ("ok".to_string(), "skipped, no GDP for the country".to_string()));
+    assert!(stdout.contains("Passed 10 of 11 checks"), "{}", stdout);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("self-test failed 1 of 11 checks"));
+
+    let output = self_test(&dir.join("missing"));
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not found"), "{}", String::from_utf8_lossy(&output.stderr));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn takes_no_arguments() {
+    let args = |list: &[&str]| parse(list.iter().map(|a| a.to_string()));
+    assert!(matches!(args(&["self-test"]), Ok(Command::SelfTest)));
+    assert_eq!(args(&["self-test", "now"]).err().unwrap(), "unexpected argument 'now'");
+}
@@ -0,0 +1,136 @@
+//! A saved session reopened against changed data: the place itself, on whatever continent
+//! it moved to; a renamed one offered at startup and opened or declined; nothing like it,
+//! the nearest place above it opened with the status bar saying what was missing
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    state::{AppState, Location, Restore, RestoreOffer, Session},
+    suggest::best_match,
+    ui,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+};
+
+fn index() -> BTreeMap<String, BTreeSet<String>> {
+    [("Europe", &["Czechia", "Poland"][..]), ("Asia", &["Gamma"][..])]
+        .into_iter()
+        .map(|(c, members)| (c.to_string(), members.iter().map(|m| m.to_string()).collect()))
+        .collect()
+}
+
+fn session(continent: Option<&str>, country: Option<&str>) -> Session {
+    Session { continent: continent.map(str::to_string), country: country.map(str::to_string), ..Session::default() }
+}
+
+fn country(continent: &str, country: &str) -> Location {
+    Location::Country { continent: continent.to_string(), country: country.to_string() }
+}
+
+#[test]
+fn resolved_against_the_lists() {
+    let index = index();
+    let resolve = |continent, country| session(continent, country).resolve(&index);
+    assert_eq!(resolve(None, None), Restore::Exact(Location::World));
+    assert_eq!(resolve(Some("europe"), None), Restore::Exact(Location::Continent("Europe".to_string())));
+    assert_eq!(resolve(Some("Europe"), Some("POLAND")), Restore::Exact(country("Europe", "Poland")));
+    // Moved to another continent: reopened there
+    assert_eq!(resolve(Some("Europe"), Some("Gamma")), Restore::Exact(country("Asia", "Gamma")));
+
+    // Renamed: offered, over the continent it was on
+    assert_eq!(
+        resolve(Some("Europe"), Some("Czech Republic")),
+        Restore::Offer {
+            offer: RestoreOffer { saved: "Czech Republic".to_string(), found: country("Europe", "Czechia") },
+            fallback: Location::Continent("Europe".to_string()),
+        }
+    );
+    assert_eq!(
+        resolve(Some("Eurupe"), None),
+        Restore::Offer {
+            offer: RestoreOffer { saved: "Eurupe".to_string(), found: Location::Continent("Europe".to_string()) },
+            fallback: Location::World,
+        }
+    );
+
+    // Nothing like it: the place above it, or the world when that is gone too
+    assert_eq!(
+        resolve(Some("Europe"), Some("Atlantis")),
+        Restore::Fallback { location: Location::Continent("Europe".to_string()), missing: "Atlantis".to_string() }
+    );
+    assert_eq!(resolve(Some("Lemuria"), Some("Atlantis")), Restore::Fallback { location: Location::World, missing: "Atlantis".to_string() });
+    assert_eq!(resolve(Some("Lemuria"), None), Restore::Fallback { location: Location::World, missing: "Lemuria".to_string() });
+}
+
+#[test]
+fn best_match_both_ways() {
+    let names = ["Chad", "Czechia", "Poland"];
+    assert_eq!(best_match("Czech Republic", names, Session::RENAME_SIMILARITY), Some("Czechia"));
+    assert_eq!(best_match("czechia", names, Session::RENAME_SIMILARITY), Some("Czechia"));
+    assert_eq!(best_match("Atlantis", names, Session::RENAME_SIMILARITY), None);
+    assert_eq!(best_match("Czech Republic", [], 0.0), None);
+}
+
+/// Europe with Czechia and Poland, `session.json` saved at `continent`/`country`
+fn atlas(name: &str, continent: &str, country: &str) -> PathBuf {
+    let dir = FixtureAtlas::new().continent("Europe", ["Czechia", "Poland"]).write(name).unwrap().dir;
+    let saved = serde_json::json!({"continent": continent, "country": country, "tour_seen": true});
+    fs::write(dir.join("session.json"), saved.to_string()).unwrap();
+    dir
+}
+
+fn screen(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    terminal.draw(|f| {
+        ui::draw(f, &state.view_model());
+    })
+    .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height).map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn renamed_country_offered_at_startup() {
+    let dir = atlas("restore-offer", "Europe", "Czech Republic");
+    let mut state = AppState::open(&dir, false).unwrap();
+    assert_eq!(state.location, Location::Continent("Europe".to_string()));
+    assert_eq!(state.restore_offer.as_ref().map(|o| &o.found), Some(&country("Europe", "Czechia")));
+    let text = screen(&state);
+    assert!(text.contains("Czech Republic → Czechia (Europe), przywrócić tam? (t/n)"), "{}", text);
+
+    // Other keys wait for the answer; t opens it
+    state.handle_input(KeyCode::Down);
+    assert!(state.restore_offer.is_some());
+    state.handle_input(KeyCode::Char('t'));
+    assert!(state.restore_offer.is_none());
+    assert_eq!(state.location, country("Europe", "Czechia"));
+    assert!(!screen(&state).contains("przywrócić tam?"));
+
+    // n stays where it is, and says so
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.handle_input(KeyCode::Char('n'));
+    assert_eq!(state.location, Location::Continent("Europe".to_string()));
+    assert_eq!(state.notification.as_ref().unwrap().text, "Nie przywrócono Czech Republic – zostaje Europe");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn missing_country_falls_back_with_a_notice() {
+    let dir = atlas("restore-fallback", "Europe", "Atlantis");
+    let state = AppState::open(&dir, false).unwrap();
+    assert!(state.restore_offer.is_none());
+    assert_eq!(state.location, Location::Continent("Europe".to_string()));
+    assert_eq!(state.notification.as_ref().unwrap().text, "Brak Atlantis w danych z poprzedniej sesji – otwarto Europe");
+    let _ = fs::remove_dir_all(&dir);
+
+    let dir = atlas("restore-fallback-world", "Lemuria", "Atlantis");
+    let state = AppState::open(&dir, false).unwrap();
+    assert_eq!(state.location, Location::World);
+    assert_eq!(state.notification.as_ref().unwrap().text, "Brak Atlantis w danych z poprzedniej sesji – otwarto widok świata");
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,162 @@
+//! `platform::run` on scripted drivers: the keys replayed in order, the run ending with
+//! the script or at Quit, and the terminal given back even when reading input fails
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    platform::{run, HeadlessDriver, Input, TerminalDriver},
+    state::{AppState, Location},
+};
+use std::{collections::VecDeque, io, path::PathBuf, time::Duration};
+
+fn atlas(name: &str) -> PathBuf {
+    FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .continent("Northland", ["Delta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .country("Delta", square_at(0.0, 20.0))
+        .write(name)
+        .unwrap()
+        .dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.selected = 0;
+    state
+}
+
+fn current(state: &AppState) -> Location {
+    let (entries, pos) = state.nav.entries();
+    entries[pos].clone()
+}
+
+fn screen(driver: &HeadlessDriver) -> String {
+    let buffer = driver.last_frame().unwrap();
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect()
+}
+
+#[test]
+fn script_runs_to_its_end() {
+    let dir = atlas("driver-script");
+    let mut state = open(&dir);
+    let keys = &state.keys;
+    let position = state.list_items.iter().position(|c| c == "Testland").unwrap();
+    let script: Vec<KeyCode> = std::iter::repeat_n(keys.key(Action::Down).unwrap(), position)
+        .chain([keys.key(Action::Select).unwrap(), keys.key(Action::Down).unwrap()])
+        .collect();
+    let mut driver = HeadlessDriver::new(100, 30, script);
+    run(&mut driver, &mut state).unwrap();
+
+    assert_eq!(current(&state), Location::Continent("Testland".to_string()));
+    assert_eq!(state.list_items[state.selected], "Beta");
+    // The last frame is the one drawn before the script ran out
+    let text = screen(&driver);
+    assert!(text.contains("Alpha") && text.contains("Beta"), "{}", text);
+    assert_eq!(driver.bells(), 0);
+    assert_eq!(driver.suspensions(), 0);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn quit_stops_before_the_rest() {
+    let dir = atlas("driver-quit");
+    let mut state = open(&dir);
+    let script = [state.keys.key(Action::Quit).unwrap(), state.keys.key(Action::Select).unwrap()];
+    let mut driver = HeadlessDriver::new(100, 30, script);
+    run(&mut driver, &mut state).unwrap();
+    assert_eq!(current(&state), Location::World);
+    assert!(driver.last_frame().is_some());
+
+    // Nothing to replay: one frame, then the end
+    let mut driver = HeadlessDriver::with_inputs(100, 30, []);
+    run(&mut driver, &mut state).unwrap();
+    assert!(screen(&driver).contains("Testland"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Driver whose input fails once its script is used up, recording what the run did to it
+struct Failing {
+    inputs: VecDeque<Input>,
+    entered: usize,
+    left: usize,
+    reads: usize,
+}
+
+impl TerminalDriver for Failing {
+    type Backend = TestBackend;
+
+    fn enter(&mut self) -> io::Result<Terminal<TestBackend>> {
+        self.entered += 1;
+        Terminal::new(TestBackend::new(80, 24))
+    }
+
+    fn next_input(&mut self, _timeout: Duration) -> io::Result<Input> {
+        self.reads += 1;
+        self.inputs.pop_front().ok_or_else(|| io::Error::other("input closed"))
+    }
+
+    fn bell(&mut self, _terminal: &mut Terminal<TestBackend>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn leave(&mut self, _terminal: &mut Terminal<TestBackend>) -> io::Result<()> {
+        self.left += 1;
+        Ok(())
+    }
+
+    fn suspend(&mut self, _terminal: &mut Terminal<TestBackend>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn resume(&mut self, _terminal: &mut Terminal<TestBackend>) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn failed_input_still_restores_the_terminal() {
+    let dir = atlas("driver-failing");
+    let mut state = open(&dir);
+    let mut driver = Failing {
+        inputs: [Input::Idle, Input::Key(state.keys.key(Action::Down).unwrap()), Input::Idle].into(),
+        entered: 0,
+        left: 0,
+        reads: 0,
+    };
+    let error = run(&mut driver, &mut state).unwrap_err();
+    assert_eq!(error.to_string(), "input closed");
+    assert_eq!((driver.entered, driver.left, driver.reads), (1, 1, 4));
+    // Keys before the failure were handled
+    assert_eq!(state.selected, 1);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[cfg(all(unix, feature = "backend-termion"))]
+#[test]
+fn termion_keys_map_to_the_key_model() {
+    use rustatlas::{keys::ctrl, platform::key_from_termion};
+    use termion::event::Key;
+    for (key, code) in [
+        (Key::Char('\n'), Some(KeyCode::Enter)),
+        (Key::Char('\t'), Some(KeyCode::Tab)),
+        (Key::Char('q'), Some(KeyCode::Char('q'))),
+        (Key::Up, Some(KeyCode::Up)),
+        (Key::Backspace, Some(KeyCode::Backspace)),
+        (Key::Esc, Some(KeyCode::Esc)),
+        (Key::Ctrl('E'), Some(ctrl('e'))),
+        (Key::F(5), None),
+    ] {
+        assert_eq!(key_from_termion(key), code, "{:?}", key);
+    }
+}
@@ -0,0 +1,167 @@
+//! Territories tied to their sovereigns by `constituents.json` and `territories.json`:
+//! each listed once however it is spelled, named in the info panel and the line under the
+//! map, and opened with `u` and `t` wherever the continent lists put them
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    data::DataCache,
+    keys::Action,
+    state::{AppState, Location, MouseInput, Panel},
+    summary::CountrySummary,
+    ui,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+/// Denmark with Greenland and the Faroe Islands (Greenland listed again under its GDP
+/// name), France with New Caledonia, and Lost Isle of an Atlantis no list has
+fn atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Denmark", "Faroe Islands", "France"])
+        .continent("North America", ["Greenland"])
+        .continent("Oceania", ["New Caledonia", "Lost Isle"])
+        .country("Greenland", square_at(-40.0, 70.0))
+        .alias("Greenland", "Kalaallit Nunaat")
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("constituents.json"), r#"{"Denmark": ["Greenland", "Faroe Islands"]}"#).unwrap();
+    fs::write(
+        dir.join("territories.json"),
+        r#"{"Denmark": ["Kalaallit Nunaat"], "France": ["New Caledonia"], "Atlantis": ["Lost Isle"]}"#,
+    )
+    .unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    dir
+}
+
+#[test]
+fn listed_once_under_either_name() {
+    let dir = atlas("territories-data");
+    let cache = DataCache::new(&dir).unwrap();
+    assert!(cache.same_place("Greenland", "Kalaallit Nunaat"));
+    assert!(cache.same_place("GREENLAND", "greenland"));
+    assert!(!cache.same_place("Greenland", "Denmark"));
+
+    // Constituents first, then territories.json, without Greenland a second time
+    assert_eq!(cache.territories_of("Denmark"), ["Greenland", "Faroe Islands"]);
+    assert_eq!(cache.territories_of("denmark"), ["Greenland", "Faroe Islands"]);
+    assert_eq!(cache.territories_of("France"), ["New Caledonia"]);
+    assert!(cache.territories_of("Greenland").is_empty());
+
+    assert_eq!(cache.sovereign_of("Greenland"), Some("Denmark"));
+    assert_eq!(cache.sovereign_of("Kalaallit Nunaat"), Some("Denmark"));
+    assert_eq!(cache.sovereign_of("Lost Isle"), Some("Atlantis"));
+    assert_eq!(cache.sovereign_of("Denmark"), None);
+
+    let greenland = CountrySummary::build("Greenland", &cache, None);
+    assert_eq!((greenland.sovereign.as_deref(), greenland.territories.len()), (Some("Denmark"), 0));
+    let denmark = CountrySummary::build("Denmark", &cache, None);
+    assert_eq!((denmark.sovereign, denmark.territories), (None, vec!["Greenland".to_string(), "Faroe Islands".to_string()]));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// Open `places` one below the other from the world list
+fn go(state: &mut AppState, places: &[&str]) {
+    state.go_up_to(Location::World);
+    for place in places {
+        state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+        press(state, Action::Select);
+    }
+}
+
+fn country(continent: &str, country: &str) -> Location {
+    Location::Country { continent: continent.to_string(), country: country.to_string() }
+}
+
+/// Draw a frame and note where the map went, as the main loop does; the screen's text
+fn screen(state: &mut AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(160, 40)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    state.apply_drawn(drawn.unwrap());
+    let buffer = terminal.backend().buffer();
+    (0..buffer.area.height).map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn sovereign_and_territories_opened_across_continents() {
+    let dir = atlas("territories-navigation");
+    let mut state = open(&dir);
+    go(&mut state, &["North America", "Greenland"]);
+    let text = screen(&mut state);
+    assert!(text.contains("Należy do: Denmark (u)"), "{}", text);
+
+    // u: the sovereign, on its own continent; it has no sovereign to go on to
+    press(&mut state, Action::Parent);
+    assert_eq!(state.location, country("Europe", "Denmark"));
+    let text = screen(&mut state);
+    for fragment in ["Terytoria: Greenland, Faroe", "Islands (t)"] {
+        assert!(text.contains(fragment), "no {:?} in:\n{}", fragment, text);
+    }
+    assert!(!text.contains("Należy do:"));
+    press(&mut state, Action::Parent);
+    assert_eq!(state.location, country("Europe", "Denmark"));
+
+    // t: its first territory, then from there the sovereign's next ones, around again
+    press(&mut state, Action::Territory);
+    assert_eq!(state.location, country("North America", "Greenland"));
+    press(&mut state, Action::Territory);
+    assert_eq!(state.location, country("Europe", "Faroe Islands"));
+    press(&mut state, Action::Territory);
+    assert_eq!(state.location, country("North America", "Greenland"));
+
+    // The only territory has no other to go on to
+    go(&mut state, &["Europe", "France"]);
+    press(&mut state, Action::Territory);
+    assert_eq!(state.location, country("Oceania", "New Caledonia"));
+    press(&mut state, Action::Territory);
+    assert_eq!(state.location, country("Oceania", "New Caledonia"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sovereign_without_data_reported() {
+    let dir = atlas("territories-missing");
+    let mut state = open(&dir);
+    go(&mut state, &["Oceania", "Lost Isle"]);
+    assert!(screen(&mut state).contains("Należy do: Atlantis (u)"));
+    press(&mut state, Action::Parent);
+    assert_eq!(state.location, country("Oceania", "Lost Isle"));
+    assert_eq!(state.notification.as_ref().unwrap().text, "Atlantis nie ma własnych danych na listach kontynentów");
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn hover_line_names_the_sovereign() {
+    let dir = atlas("territories-hover");
+    let mut state = open(&dir);
+    go(&mut state, &["North America"]);
+    screen(&mut state);
+    let over = (0..40u16).flat_map(|row| (0..160u16).map(move |column| (column, row))).find(|&(column, row)| {
+        state.handle_mouse(MouseInput::Move { column, row });
+        state.map_hover.as_deref() == Some("Greenland")
+    });
+    assert!(over.is_some(), "Greenland not on the map");
+    let text = screen(&mut state);
+    assert!(text.lines().any(|line| line.contains("Greenland City") && line.contains("Należy do: Denmark")), "{}", text);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,168 @@
+//! Editing text by grapheme cluster: precomposed and combining input giving the same
+//! text, Backspace, Delete and the cursor moving over whole clusters in mixed ASCII,
+//! diacritic and emoji strings, and the drawn cursor placed by display width
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{input::LineBuffer, keys::Action, state::AppState, ui};
+use unicode_width::UnicodeWidthStr;
+
+const FAMILY: &str = "👨\u{200d}👩\u{200d}👧"; // one cluster of five code points
+const FLAG: &str = "🇵🇱"; // two regional indicators
+
+/// `chars` typed one at a time into an empty buffer
+fn typed(chars: &str) -> LineBuffer {
+    let mut line = LineBuffer::default();
+    for c in chars.chars() {
+        line.insert(c);
+    }
+    line
+}
+
+/// Text before and after the cursor
+fn parts(line: &LineBuffer) -> (String, String) {
+    let (before, after) = line.split();
+    (before.to_string(), after.to_string())
+}
+
+#[test]
+fn combining_input_gives_the_precomposed_text() {
+    // The way a dead key or a terminal may deliver "São Tomé": base letter, then the mark
+    let combining = typed("Sa\u{303}o Tome\u{301}");
+    let precomposed = typed("São Tomé");
+    assert_eq!(combining.as_str(), "São Tomé");
+    assert_eq!(combining, precomposed);
+    assert_eq!(typed("Ło\u{301}dz\u{301}").as_str(), "Łódź");
+    assert_eq!(LineBuffer::new("Z\u{307}ywiec").as_str(), "Żywiec");
+    // A mark without a precomposed form stays as its own code point, in the same cluster
+    let mut line = typed("q\u{303}x");
+    assert_eq!(line.as_str(), "q\u{303}x");
+    line.left();
+    assert_eq!(parts(&line), ("q\u{303}".to_string(), "x".to_string()));
+}
+
+#[test]
+fn backspace_and_delete_remove_whole_clusters() {
+    let mut line = LineBuffer::new(&format!("ab{}é{}", FAMILY, FLAG));
+    assert!(line.backspace());
+    assert_eq!(line.as_str(), format!("ab{}é", FAMILY));
+    assert!(line.backspace());
+    assert_eq!(line.as_str(), format!("ab{}", FAMILY));
+    assert!(line.backspace());
+    assert_eq!(line.as_str(), "ab");
+    line.home();
+    assert!(!line.backspace());
+    assert!(line.delete());
+    assert_eq!(line.as_str(), "b");
+    line.end();
+    assert!(!line.delete());
+
+    // Deleting forwards over an emoji and a combined letter
+    let mut line = LineBuffer::new(&format!("{}e\u{301}z", FAMILY));
+    line.home();
+    assert!(line.delete());
+    assert_eq!(line.as_str(), "éz");
+    assert!(line.delete());
+    assert_eq!(line.as_str(), "z");
+}
+
+#[test]
+fn cursor_moves_by_cluster() {
+    let text = format!("Łódź {} ok{}", FAMILY, FLAG);
+    let mut line = LineBuffer::new(&text);
+    let mut stops = vec![parts(&line).0];
+    while line.left() {
+        stops.push(parts(&line).0);
+    }
+    let expected_before = [
+        text.clone(),
+        format!("Łódź {} ok", FAMILY),
+        format!("Łódź {} o", FAMILY),
+        format!("Łódź {} ", FAMILY),
+        format!("Łódź {}", FAMILY),
+        "Łódź ".to_string(),
+        "Łódź".to_string(),
+        "Łód".to_string(),
+        "Łó".to_string(),
+        "Ł".to_string(),
+        String::new(),
+    ];
+    assert_eq!(stops, expected_before);
+    assert!(!line.left());
+
+    let mut right = vec![];
+    while line.right() {
+        right.push(parts(&line).0);
+    }
+    assert_eq!(right, expected_before.iter().rev().skip(1).cloned().collect::<Vec<_>>());
+    assert!(!line.right());
+    assert!(line.home());
+    assert!(!line.home());
+    assert!(line.end());
+    assert!(!line.end());
+}
+
+#[test]
+fn typing_in_the_middle_keeps_the_cursor_after_the_cluster() {
+    let mut line = LineBuffer::new("Sao");
+    line.left();
+    line.left();
+    // Cursor after "S": typing a mark there would join "S", so first step past the "a"
+    line.right();
+    line.insert('\u{303}');
+    assert_eq!(parts(&line), ("Sã".to_string(), "o".to_string()));
+    line.insert(FAMILY.chars().next().unwrap());
+    for c in FAMILY.chars().skip(1) {
+        line.insert(c);
+    }
+    assert_eq!(parts(&line), (format!("Sã{}", FAMILY), "o".to_string()));
+    assert!(line.backspace());
+    assert_eq!(line.as_str(), "São");
+}
+
+/// The list panel's title row while searching, as drawn: the cells and the column of
+/// the cursor symbol
+fn search_title(state: &AppState) -> (String, usize) {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    let row = (0..30).find(|&y| (0..100).any(|x| buffer[(x, y)].symbol() == "S" && buffer[(x + 1, y)].symbol() == "z")).unwrap();
+    let cells: Vec<&str> = (0..100).map(|x| buffer[(x, row)].symbol()).collect();
+    let cursor = cells.iter().position(|&s| s == "█").unwrap();
+    (cells.concat(), cursor)
+}
+
+#[test]
+fn drawn_cursor_follows_the_display_width() {
+    let dir = FixtureAtlas::new().continent("日本", ["Tokio"]).continent("São Tomé", ["Príncipe"]).write("text-input").unwrap().dir;
+    let _ = std::fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.handle_input(state.keys.key(Action::Filter).unwrap());
+    for c in "日本".chars() {
+        state.handle_input(KeyCode::Char(c));
+    }
+    let (row, end) = search_title(&state);
+    let start = row.find("Szukaj: ").unwrap();
+    let column = |s: &str| s.width();
+    // Two wide characters, two columns each
+    assert_eq!(end, column(&row[..start]) + column("Szukaj: 日本"), "{}", row);
+    state.handle_input(KeyCode::Left);
+    let (_, middle) = search_title(&state);
+    assert_eq!(middle, end - 2);
+
+    // A decomposed accent typed from the terminal takes no column of its own
+    state.handle_input(KeyCode::Esc);
+    state.handle_input(state.keys.key(Action::Filter).unwrap());
+    for c in "Sa\u{303}o".chars() {
+        state.handle_input(KeyCode::Char(c));
+    }
+    let (row, end) = search_title(&state);
+    assert_eq!(end, column(&row[..row.find("Szukaj: ").unwrap()]) + column("Szukaj: São"), "{}", row);
+    assert_eq!(state.list_items[state.highlighted()], "São Tomé");
+    let _ = std::fs::remove_dir_all(&dir);
+}
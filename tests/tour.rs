@@ -0,0 +1,95 @@
+//! The first-launch walkthrough: shown on a start with no session that saw it, a step per
+//! key and closed after the last or on Esc, each step drawn over its panel; a closed
+//! walkthrough is saved in the session and not shown again unless asked for
+mod common;
+
+use common::FixtureAtlas;
+use crossterm::event::KeyCode;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, Location, TourTarget},
+    ui,
+};
+use std::{fs, path::Path};
+
+/// The atlas in `dir` as it would start
+fn start(dir: &Path) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.notification = None;
+    state
+}
+
+fn screen(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(120, 36)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..36).map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n").collect()
+}
+
+#[test]
+fn shown_once_and_stepped_through() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("tour-steps").unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = start(&dir);
+    assert_eq!(state.tour_step, Some(0));
+
+    // Any key moves it on, bound or not, and does nothing else
+    let steps = TourTarget::STEPS.len();
+    for (step, key) in [KeyCode::Enter, KeyCode::Char('x'), KeyCode::Down, KeyCode::Char('q')].into_iter().enumerate().take(steps) {
+        assert_eq!(state.tour_step, Some(step));
+        let title = format!("Witaj w RustAtlas ({}/{})", step + 1, steps);
+        assert!(screen(&state).contains(&title), "{}", screen(&state));
+        assert!(!state.handle_input(key), "{:?} quit during the tour", key);
+        assert_eq!(state.location, Location::World);
+    }
+    assert_eq!(state.tour_step, None);
+    assert!(!screen(&state).contains("Witaj w RustAtlas"));
+
+    // Seen: the next start goes straight to the map
+    state.save_session().unwrap();
+    assert_eq!(start(&dir).tour_step, None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn skipped_with_esc() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("tour-skip").unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = start(&dir);
+    state.handle_input(KeyCode::Enter);
+    assert_eq!(state.tour_step, Some(1));
+    state.handle_input(KeyCode::Esc);
+    assert_eq!(state.tour_step, None);
+    // Esc only closed it, the map is where it was
+    assert_eq!(state.location, Location::World);
+    state.save_session().unwrap();
+    assert_eq!(start(&dir).tour_step, None);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn unfinished_shown_again_and_replayed_on_request() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha"]).write("tour-again").unwrap().dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    // Quit halfway: not seen
+    let mut state = start(&dir);
+    state.handle_input(KeyCode::Enter);
+    state.save_session().unwrap();
+    let mut state = start(&dir);
+    assert_eq!(state.tour_step, Some(0));
+
+    // Seen, then asked for again (--tour): from the first step, and still seen afterwards
+    state.handle_input(KeyCode::Esc);
+    state.save_session().unwrap();
+    let mut state = start(&dir);
+    assert_eq!(state.tour_step, None);
+    state.start_tour();
+    assert_eq!(state.tour_step, Some(0));
+    state.handle_input(state.keys.key(Action::Quit).unwrap());
+    assert_eq!(state.tour_step, Some(1));
+    state.handle_input(KeyCode::Esc);
+    state.save_session().unwrap();
+    assert_eq!(start(&dir).tour_step, None);
+    let _ = fs::remove_dir_all(&dir);
+}
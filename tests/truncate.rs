@@ -0,0 +1,122 @@
+//! Names cut to the cells they have: `truncate_to_width` on wide characters, combining
+//! marks and the narrowest widths, and the breadcrumb and ranking table going through it
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{keys::Action, state::{AppState, Panel}, text::truncate_to_width, ui};
+use std::fs;
+use unicode_width::UnicodeWidthStr;
+
+const LONG: &str = "United Republic of the Very Long Country Name";
+
+#[test]
+fn ascii_is_cut_with_an_ellipsis() {
+    assert_eq!(truncate_to_width("Poland", 6), "Poland");
+    assert_eq!(truncate_to_width("Poland", 10), "Poland");
+    assert_eq!(truncate_to_width("Poland", 5), "Pola…");
+    assert_eq!(truncate_to_width("Poland", 2), "P…");
+    // No space left dangling before the ellipsis
+    assert_eq!(truncate_to_width("New York", 5), "New…");
+}
+
+#[test]
+fn wide_characters_are_never_split() {
+    // Two cells each
+    assert_eq!("日本国".width(), 6);
+    assert_eq!(truncate_to_width("日本国", 6), "日本国");
+    assert_eq!(truncate_to_width("日本国", 5), "日本…");
+    // Half of 本 would fit, so it is left out: one cell short of the width
+    assert_eq!(truncate_to_width("日本国", 4), "日…");
+    assert_eq!(truncate_to_width("日本国", 2), "…");
+    for width in 0..8 {
+        assert!(truncate_to_width("日本国", width).width() <= width, "{}", width);
+    }
+}
+
+#[test]
+fn combining_marks_stay_with_their_letter() {
+    // "Côte" with the circumflex as a combining mark after the o
+    let cote = "Co\u{302}te d'Ivoire";
+    assert_eq!(cote.width(), 13);
+    assert_eq!(truncate_to_width(cote, 13), cote);
+    assert_eq!(truncate_to_width(cote, 3), "Co\u{302}…");
+    assert_eq!(truncate_to_width(cote, 2), "C…");
+    // A letter with two marks is one grapheme
+    assert_eq!(truncate_to_width("a\u{301}\u{323}bc", 2), "a\u{301}\u{323}…");
+}
+
+#[test]
+fn widths_zero_and_one() {
+    for s in ["Poland", "日本", "Co\u{302}te"] {
+        assert_eq!(truncate_to_width(s, 0), "", "{}", s);
+        // Room for the ellipsis alone
+        assert_eq!(truncate_to_width(s, 1), "…", "{}", s);
+    }
+    // Nothing to cut
+    assert_eq!(truncate_to_width("", 0), "");
+    assert_eq!(truncate_to_width("A", 1), "A");
+    // A wide character doesn't fit in one cell
+    assert_eq!(truncate_to_width("日", 1), "…");
+}
+
+/// The atlas on `Europe`, with a long-named country and nothing on screen but the view
+fn europe(name: &str) -> (std::path::PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", [LONG, "Poland"])
+        .gdp("Poland", 2020..=2020, |_| 7e11)
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    (dir, state)
+}
+
+/// Rows of the screen after drawing `state` at `width` x `height`
+fn draw(state: &mut AppState, width: u16, height: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    let drawn = drawn.unwrap();
+    // Every clickable segment inside the row
+    for (_, rect) in &drawn.breadcrumb {
+        assert!(rect.right() <= width, "{:?}", rect);
+    }
+    state.apply_drawn(drawn);
+    let buffer = terminal.backend().buffer();
+    (0..height).map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+}
+
+#[test]
+fn breadcrumb_cuts_the_last_place() {
+    let (dir, mut state) = europe("truncate-breadcrumb");
+    state.selected = state.list_items.iter().position(|c| c == LONG).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    let screen = draw(&mut state, 50, 30);
+    let crumb = screen[0].trim_end();
+    assert!(crumb.starts_with("World ▸ Europe ▸ United Republic"), "{}", crumb);
+    assert!(crumb.ends_with('…'), "{}", crumb);
+    assert!(crumb.width() <= 50, "{}", crumb);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn ranking_cuts_names_to_their_column() {
+    let (dir, mut state) = europe("truncate-ranking");
+    state.handle_input(state.keys.key(Action::Ranking).unwrap());
+    assert!(state.ranking.is_some());
+    let screen = draw(&mut state, 120, 30);
+    let row = screen.iter().find(|row| row.contains("United Republic") && row.contains("mln")).unwrap();
+    // Cut to fill its column exactly, the columns after it all there
+    assert!(row.contains("United Republic of… 1.00\u{a0}mln    1000\u{a0}km²"), "{}", row);
+    // A name that fits is left whole
+    assert!(screen.iter().any(|row| row.contains("1    Poland              1.00\u{a0}mln")), "{}", screen.join("\n"));
+    let _ = fs::remove_dir_all(&dir);
+}
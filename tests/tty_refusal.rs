@@ -0,0 +1,74 @@
+//! Refusing to start the interactive atlas when stdout isn't a terminal: the decision for
+//! every command with stdout a terminal or not, and the binary run with stdout a pipe
+mod common;
+
+use common::FixtureAtlas;
+use rustatlas::cli::{parse, tty_refusal};
+use std::{path::Path, process::{Command, Output}};
+
+const WHY: &str = "stdout is not a terminal, so the interactive atlas can't start; use a subcommand \
+                   (export-gdp, check-data, map, data-diff, self-test) or pass --force-tui";
+
+#[test]
+fn decision_matrix() {
+    // (command line, refused with stdout a terminal, refused without one)
+    let table: [(&[&str], bool, bool); 12] = [
+        (&[], false, true),
+        (&["--tour"], false, true),
+        (&["--colors", "256", "--no-animations"], false, true),
+        (&["--accessible", "--low-mem", "--watch"], false, true),
+        (&["--force-tui"], false, false),
+        (&["--tour", "--force-tui"], false, false),
+        (&["export-gdp", "--all"], false, false),
+        (&["export-gdp", "Alpha", "-o", "out.csv"], false, false),
+        (&["check-data"], false, false),
+        (&["self-test"], false, false),
+        (&["map", "europe", "--no-color"], false, false),
+        (&["data-diff", "old", "new"], false, false),
+    ];
+    for (args, on_tty, off_tty) in table {
+        let command = parse(args.iter().map(|a| a.to_string())).unwrap_or_else(|e| panic!("{:?}: {}", args, e));
+        assert_eq!(tty_refusal(&command, true).is_some(), on_tty, "{:?} on a terminal", args);
+        match tty_refusal(&command, false) {
+            Some(why) => {
+                assert!(off_tty, "{:?} refused off a terminal", args);
+                assert_eq!(why, WHY);
+            }
+            None => assert!(!off_tty, "{:?} not refused off a terminal", args),
+        }
+    }
+}
+
+/// Run the binary on `dir` with `args`, stdout a pipe
+fn run(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_RustAtlas")).arg("--data-dir").arg(dir).args(args).output().unwrap()
+}
+
+#[test]
+fn piped_binary_refuses_the_atlas_but_runs_subcommands() {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .gdp("Alpha", 2000..=2002, f64::from)
+        .write("tty-refusal")
+        .unwrap()
+        .dir;
+    for args in [&[][..], &["--tour"]] {
+        let output = run(&dir, args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(output.stdout.is_empty(), "escape sequences written to the pipe: {:?}", output.stdout);
+        assert_eq!(String::from_utf8_lossy(&output.stderr), format!("Error: {}\n", WHY));
+    }
+
+    let output = run(&dir, &["--json-errors"]);
+    assert_eq!(output.status.code(), Some(2));
+    let report: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(report["kind"], "usage");
+    assert_eq!(report["message"], WHY);
+
+    // Nothing was saved from an atlas that never started
+    assert!(!dir.join("session.json").exists());
+    assert_eq!(run(&dir, &["check-data"]).status.code(), Some(0));
+    assert_eq!(run(&dir, &["export-gdp", "Alpha"]).status.code(), Some(0));
+    assert_eq!(run(&dir, &["map", "--width", "20", "--height", "5"]).status.code(), Some(0));
+    let _ = std::fs::remove_dir_all(&dir);
+}
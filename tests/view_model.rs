@@ -0,0 +1,137 @@
+//! Drawing from a `ViewModel` snapshot: the snapshot showing the state as it is, the same
+//! snapshot drawing the same frame, a snapshot changed by hand drawn as changed with the
+//! state left alone, and what drawing found out handed back through `Drawn`
+mod common;
+
+use common::{square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, Terminal};
+use rustatlas::{
+    config::Projection,
+    data::GeoLevel,
+    keys::Action,
+    state::{AppState, Location, Panel},
+    ui,
+    view_model::{Drawn, MapArea, ViewModel},
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+fn open(name: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta"])
+        .country("Alpha", square_at(0.0, 0.0))
+        .country("Beta", square_at(5.0, 0.0))
+        .facts("Alpha", &["Pierwsza ciekawostka.", "Druga ciekawostka."])
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+fn enter(state: &mut AppState, place: &str) {
+    state.selected = state.list_items.iter().position(|i| i == place).unwrap();
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+fn draw(view: &ViewModel) -> (Buffer, Drawn) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, view))).unwrap();
+    (terminal.backend().buffer().clone(), drawn.unwrap())
+}
+
+fn text(buffer: &Buffer) -> String {
+    (0..buffer.area.height)
+        .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+        .collect()
+}
+
+#[test]
+fn snapshot_of_the_state_as_it_is() {
+    let (dir, mut state) = open("view-model-snapshot");
+    enter(&mut state, "Testland");
+    state.selected = 1;
+    state.notify("Zapisano");
+    let view = state.view_model();
+    assert_eq!(view.location, &Location::Continent("Testland".to_string()));
+    assert_eq!(view.list_items, ["Alpha", "Beta"]);
+    assert_eq!((view.selected, view.highlighted), (1, 1));
+    assert_eq!(view.level(), GeoLevel::Continent);
+    assert_eq!(view.context.panel, Panel::Left);
+    assert!(!view.context.chart && !view.show_help);
+    assert_eq!(view.notification.map(|n| n.text.as_str()), Some("Zapisano"));
+    assert!(view.map.is_some_and(|m| m.feature_count() == 2));
+
+    enter(&mut state, "Alpha");
+    let view = state.view_model();
+    assert_eq!(view.level(), GeoLevel::Country);
+    assert_eq!(view.facts.len(), 2);
+    assert_eq!(view.summary.map(|s| s.name.as_str()), Some("Alpha"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn drawn_from_the_snapshot_alone() {
+    let (dir, mut state) = open("view-model-draw");
+    enter(&mut state, "Testland");
+    enter(&mut state, "Alpha");
+
+    // The same snapshot, the same frame, the same layout found
+    let (first, drawn) = draw(&state.view_model());
+    let (second, again) = draw(&state.view_model());
+    assert_eq!(first, second);
+    assert_eq!(drawn.breadcrumb, again.breadcrumb);
+    assert_eq!(drawn.map_area, again.map_area);
+
+    // Changed by hand: drawn as changed, the state untouched
+    let (help, _) = draw(&ViewModel { show_help: true, ..state.view_model() });
+    assert!(text(&help).contains("Pomoc (Esc aby zamknąć)"), "{}", text(&help));
+    assert!(!state.show_help);
+    assert!(!text(&draw(&state.view_model()).0).contains("Pomoc (Esc aby zamknąć)"));
+    let world = Location::World;
+    let (_, drawn) = draw(&ViewModel { location: &world, ..state.view_model() });
+    assert_eq!(drawn.breadcrumb.len(), 1);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn layout_handed_back_through_drawn() {
+    let (dir, mut state) = open("view-model-drawn");
+    enter(&mut state, "Testland");
+    enter(&mut state, "Alpha");
+    assert!(state.breadcrumb.is_empty() && state.map_area.is_none());
+    let (_, drawn) = draw(&state.view_model());
+    let (breadcrumb, map_area) = (drawn.breadcrumb.clone(), drawn.map_area);
+    assert_eq!(breadcrumb.len(), 3);
+    assert!(map_area.is_some());
+    state.apply_drawn(drawn);
+    assert_eq!(state.breadcrumb, breadcrumb);
+    assert_eq!(state.map_area, map_area);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn map_area_cells_to_coordinates() {
+    let area = MapArea {
+        inner: Rect::new(10, 5, 20, 10),
+        x_bounds: [0.0, 20.0],
+        y_bounds: [0.0, 10.0],
+        projection: Projection::Equirectangular,
+        central: 0.0,
+    };
+    // Middle of each cell, latitude growing upwards
+    assert_eq!(area.coordinate(10, 5), Some((0.5, 9.5)));
+    assert_eq!(area.coordinate(29, 14), Some((19.5, 0.5)));
+    assert_eq!(area.coordinate(9, 5), None);
+    assert_eq!(area.coordinate(30, 5), None);
+    assert_eq!(area.coordinate(10, 15), None);
+}
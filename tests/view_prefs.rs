@@ -0,0 +1,189 @@
+//! View settings per place in `views.json`: each one taken from the place's entry, else
+//! its level's, else the session's own; `s` cycling a continent list's order; Ctrl+S
+//! saving what is in force, read again on the next start; the list title saying where
+//! the order came from
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, Terminal};
+use rustatlas::{
+    keys::Action,
+    prefs::{continent_key, ListSort, PlacePrefs, Source, Sources, ViewPrefs, ViewSettings, CONTINENT, WORLD},
+    state::{AppState, Location, Panel},
+    ui,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+fn global() -> ViewSettings {
+    ViewSettings { sort: ListSort::Name, filter: String::new(), choropleth: false, labels: true }
+}
+
+#[test]
+fn place_then_level_then_session() {
+    let prefs: PlacePrefs = serde_json::from_str(
+        r#"{"continent": {"sort": "area", "choropleth": true},
+            "continent:africa": {"sort": "gdp", "filter": "pop>10m"},
+            "world": {"sort": "population", "labels": false}}"#,
+    )
+    .unwrap();
+    let (settings, sources) = prefs.effective(&continent_key("Africa"), CONTINENT, &global());
+    assert_eq!(settings, ViewSettings { sort: ListSort::Gdp, filter: "pop>10m".to_string(), choropleth: true, labels: true });
+    assert_eq!(sources, Sources { sort: Source::Place, filter: Source::Place, choropleth: Source::Level, labels: Source::Global });
+
+    // A continent of its own: the level's, then the session's
+    let (settings, sources) = prefs.effective(&continent_key("Europe"), CONTINENT, &global());
+    assert_eq!((settings.sort, sources.sort, settings.filter.as_str(), sources.filter), (ListSort::Area, Source::Level, "", Source::Global));
+
+    // The world is its own place and level; a sort it can't have is passed over
+    let (settings, sources) = prefs.effective(WORLD, WORLD, &global());
+    assert_eq!((settings.sort, sources.sort), (ListSort::Name, Source::Global));
+    assert_eq!((settings.labels, sources.labels, settings.choropleth), (false, Source::Place, false));
+
+    // Saved: every setting, the empty filter left out
+    let mut saved = PlacePrefs::default();
+    saved.save(&continent_key("Europe"), &ViewSettings { sort: ListSort::Population, ..global() });
+    assert_eq!(
+        saved.entries[&continent_key("Europe")],
+        ViewPrefs { sort: Some(ListSort::Population), filter: None, choropleth: Some(false), labels: Some(true) }
+    );
+    assert_eq!(serde_json::to_string(&saved).unwrap(), r#"{"continent:europe":{"sort":"population","choropleth":false,"labels":true}}"#);
+}
+
+#[test]
+fn orders_and_levels() {
+    let mut sort = ListSort::Name;
+    let mut cycle = Vec::new();
+    for _ in 0..4 {
+        sort = sort.next();
+        cycle.push(sort);
+    }
+    assert_eq!(cycle, [ListSort::Population, ListSort::Area, ListSort::Gdp, ListSort::Name]);
+    assert!(ListSort::Geographic.fits(WORLD) && !ListSort::Geographic.fits(CONTINENT));
+    assert!(ListSort::Gdp.fits(CONTINENT) && !ListSort::Gdp.fits(WORLD));
+    assert!(ListSort::Name.fits(WORLD) && ListSort::Name.fits(CONTINENT));
+}
+
+/// Testland's countries each first by a different column (Gamma has no GDP), and Otherland
+fn atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Testland", ["Alpha", "Beta", "Gamma"])
+        .continent("Otherland", ["Delta"])
+        .gdp("Alpha", 2000..=2001, |_| 1e9)
+        .gdp("Beta", 2000..=2001, |_| 3e9)
+        .write(name)
+        .unwrap()
+        .dir;
+    let path = dir.join("country_info.json");
+    let mut info: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    for (key, population, area) in [("alpha", 3_000_000, 100), ("beta", 1_000_000, 300), ("gamma", 2_000_000, 200)] {
+        info[key]["population"] = population.into();
+        info[key]["area"] = area.into();
+    }
+    fs::write(&path, info.to_string()).unwrap();
+    for file in ["session.json", "views.json"] {
+        let _ = fs::remove_file(dir.join(file));
+    }
+    dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+fn go(state: &mut AppState, location: Location) {
+    state.go_up_to(Location::World);
+    if let Location::Continent(continent) = location {
+        state.selected = state.list_items.iter().position(|c| *c == continent).unwrap();
+        press(state, Action::Select);
+    }
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+}
+
+/// The top border of the list panel, on a screen wide enough for its whole title
+fn list_title(state: &AppState) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(200, 30)).unwrap();
+    terminal.draw(|f| {
+        ui::draw(f, &state.view_model());
+    })
+    .unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..80).map(|x| buffer[(x, 1)].symbol()).collect::<String>()
+}
+
+#[test]
+fn sorted_saved_and_reopened() {
+    let dir = atlas("view-prefs-saved");
+    let mut state = open(&dir);
+    let testland = Location::Continent("Testland".to_string());
+    go(&mut state, testland.clone());
+    assert_eq!(state.list_items, ["Alpha", "Beta", "Gamma"]);
+    assert!(!list_title(&state).contains("nazwa"));
+
+    // s: by population, area, GDP (without it last), and names again; the selection kept
+    state.selected = 1;
+    press(&mut state, Action::SortColumn);
+    assert_eq!(state.list_items, ["Alpha", "Gamma", "Beta"]);
+    assert_eq!(state.list_items[state.selected], "Beta");
+    assert_eq!(state.notification.as_ref().unwrap().text, "Kraje według: populacja");
+    assert!(list_title(&state).contains("populacja"), "{}", list_title(&state));
+    press(&mut state, Action::SortColumn);
+    assert_eq!(state.list_items, ["Beta", "Gamma", "Alpha"]);
+    press(&mut state, Action::SortColumn);
+    assert_eq!(state.list_items, ["Beta", "Alpha", "Gamma"]);
+
+    // Ctrl+S keeps it for Testland, written at once
+    press(&mut state, Action::SaveView);
+    assert_eq!(state.notification.as_ref().unwrap().text, "Zapisano ustawienia widoku: Testland");
+    let saved: PlacePrefs = serde_json::from_str(&fs::read_to_string(dir.join("views.json")).unwrap()).unwrap();
+    assert_eq!(saved.entries[&continent_key("Testland")].sort, Some(ListSort::Gdp));
+    assert!(list_title(&state).contains("GDP, tu"), "{}", list_title(&state));
+
+    // Back to names elsewhere; Testland opens in its own order, this run and the next
+    press(&mut state, Action::SortColumn);
+    go(&mut state, Location::Continent("Otherland".to_string()));
+    assert_eq!(state.continent_sort, ListSort::Name);
+    go(&mut state, testland.clone());
+    assert_eq!((state.continent_sort, state.view_sources.sort), (ListSort::Gdp, Source::Place));
+    assert_eq!(state.list_items, ["Beta", "Alpha", "Gamma"]);
+
+    let mut state = open(&dir);
+    go(&mut state, testland);
+    assert_eq!(state.list_items, ["Beta", "Alpha", "Gamma"]);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn level_entry_and_toggles_carried_over() {
+    let dir = atlas("view-prefs-level");
+    fs::write(dir.join("views.json"), r#"{"continent": {"sort": "area"}, "continent:otherland": {"choropleth": false, "filter": "gdp>1bn"}}"#).unwrap();
+    let mut state = open(&dir);
+    go(&mut state, Location::Continent("Testland".to_string()));
+    assert_eq!(state.list_items, ["Beta", "Gamma", "Alpha"]);
+    assert_eq!(state.view_sources.sort, Source::Level);
+    assert!(list_title(&state).contains("powierzchnia, poziom"), "{}", list_title(&state));
+
+    // Toggled where nothing saved says otherwise, the colors follow to the next place...
+    press(&mut state, Action::Choropleth);
+    assert!(state.show_choropleth);
+    go(&mut state, Location::World);
+    assert!(state.show_choropleth);
+    // ...but not to one that saved them off; its saved filter opens its ranking
+    go(&mut state, Location::Continent("Otherland".to_string()));
+    assert!(!state.show_choropleth);
+    assert_eq!(state.place_filter, "gdp>1bn");
+    press(&mut state, Action::Ranking);
+    assert_eq!(state.ranking.as_ref().unwrap().input.as_str(), "gdp>1bn");
+    let _ = fs::remove_dir_all(&dir);
+}
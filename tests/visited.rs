@@ -0,0 +1,112 @@
+//! Countries already opened, shaded on continent maps and dotted in the list with
+//! `show_visited_on_map`: off by default, kept across sessions, and under every other
+//! layer but the base color
+mod common;
+
+use common::{collection, feature, square_at, FixtureAtlas};
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, style::Color, widgets::Widget, Terminal};
+use rustatlas::{
+    data::DataCache,
+    keys::Action,
+    map_draw::{MapStyle, MapView},
+    state::{AppState, Panel},
+    ui,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+fn keys(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|n| n.to_lowercase()).collect()
+}
+
+#[test]
+fn visited_under_the_other_layers() {
+    let fixture = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write("visited-layers").unwrap();
+    let mut cache = DataCache::new(&fixture.dir).unwrap();
+    // Antarctica is in no list, so outlying
+    let features = vec![
+        feature("Alpha", square_at(0.0, 0.0)),
+        feature("Beta", square_at(3.0, 0.0)),
+        feature("Antarctica", square_at(0.0, -80.0)),
+    ];
+    let view = MapView::new(collection(features), &mut cache).unwrap();
+    let _ = fs::remove_dir_all(&fixture.dir);
+    let visited = keys(&["Alpha", "Beta", "Antarctica"]);
+    let colors = BTreeMap::from([("beta".to_string(), Color::Green)]);
+
+    let plain = view.widget();
+    assert_eq!(plain.base_color("Alpha"), MapStyle::CLASSIC.base);
+    let widget = view.widget().visited(&visited);
+    assert_eq!(widget.base_color("Alpha"), Color::Gray);
+    assert_eq!(widget.base_color("Antarctica"), Color::DarkGray);
+    let widget = view.widget().visited(&visited).colors(&colors);
+    assert_eq!(widget.base_color("Beta"), Color::Green);
+    let neighbours = keys(&["Alpha"]);
+    assert_eq!(view.widget().visited(&visited).neighbours(&neighbours).base_color("Alpha"), Color::Yellow);
+
+    // The highlight drawn over it
+    let area = Rect::new(0, 0, 40, 12);
+    let mut buffer = Buffer::empty(area);
+    view.widget().visited(&visited).highlight("Alpha").render(area, &mut buffer);
+    let left: Vec<Color> = (1..20).flat_map(|x| (1..11).map(move |y| (x, y))).map(|c| buffer[c].fg).collect();
+    assert!(left.contains(&MapStyle::CLASSIC.highlight), "{:?}", left);
+    assert!(!left.contains(&Color::Gray), "{:?}", left);
+}
+
+/// The atlas on Testland's map after opening Alpha, with `config`
+fn after_alpha(dir: &Path, config: &str) -> AppState {
+    fs::write(dir.join("config.json"), config).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    for place in ["Testland", "Alpha"] {
+        state.selected = state.list_items.iter().position(|c| c == place).unwrap();
+        state.handle_input(state.keys.key(Action::Select).unwrap());
+    }
+    state.handle_input(state.keys.key(Action::Back).unwrap());
+    state.selected = state.list_items.iter().position(|c| c == "Beta").unwrap();
+    state
+}
+
+fn screen(state: &AppState) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+    terminal.draw(|f| drop(ui::draw(f, &state.view_model()))).unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The list row of `name`
+fn list_row(buffer: &Buffer, name: &str) -> String {
+    (0..buffer.area.height)
+        .map(|y| (0..30).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+        .find(|row| row.starts_with('│') && row.contains(name))
+        .unwrap()
+}
+
+#[test]
+fn shaded_and_dotted_only_when_asked() {
+    let dir = FixtureAtlas::new().continent("Testland", ["Alpha", "Beta"]).write("visited-atlas").unwrap().dir;
+
+    let state = after_alpha(&dir, "{}");
+    assert!(state.visited.contains("alpha"));
+    let buffer = screen(&state);
+    assert!(!buffer.content.iter().any(|c| c.fg == Color::Gray && c.symbol() != " "));
+    assert!(list_row(&buffer, "Alpha").starts_with("│   Alpha"), "{}", list_row(&buffer, "Alpha"));
+
+    let mut state = after_alpha(&dir, r#"{"show_visited_on_map": true}"#);
+    let buffer = screen(&state);
+    assert!(buffer.content.iter().any(|c| c.fg == Color::Gray && c.symbol() != " "));
+    assert!(list_row(&buffer, "Alpha").starts_with("│   · Alpha"), "{}", list_row(&buffer, "Alpha"));
+    assert!(list_row(&buffer, "Beta").starts_with("│>>   Beta"), "{}", list_row(&buffer, "Beta"));
+
+    // Remembered in session.json for the next start
+    state.save_session().unwrap();
+    let reopened = AppState::open(&dir, false).unwrap();
+    assert_eq!(reopened.visited, keys(&["Alpha"]));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,132 @@
+//! The world list's order and continent tints: alphabetical by default, west to east with
+//! `world_order` or `g`, continents without geometry last; with `continent_colors` each
+//! continent's countries tinted on the world map and its list entry bulleted to match
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, buffer::Buffer, style::Color, Terminal};
+use rustatlas::{
+    config::{Config, WorldOrder},
+    keys::Action,
+    state::{AppState, Panel},
+    theme::{continent_tint, CONTINENT_TINTS},
+    ui,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+/// Closed ring of a square of `size` degrees with its south-west corner at `x`, `y`
+fn square(x: f64, y: f64, size: f64) -> Vec<Vec<f64>> {
+    vec![vec![x, y], vec![x + size, y], vec![x + size, y + size], vec![x, y + size], vec![x, y]]
+}
+
+/// Continents listed out of order: Europe in the middle, Westland and Eastland either
+/// side, Emptyland with no countries to place it
+fn open(name: &str, config: &str) -> (PathBuf, AppState) {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Midland"])
+        .continent("Emptyland", [])
+        .continent("Westland", ["Farwest", "Nearwest"])
+        .continent("Eastland", ["Fareast"])
+        .country("Midland", square(-10.0, 0.0, 20.0))
+        .country("Farwest", square(-150.0, 0.0, 20.0))
+        .country("Nearwest", square(-90.0, 0.0, 20.0))
+        .country("Fareast", square(100.0, 0.0, 40.0))
+        .write(name)
+        .unwrap()
+        .dir;
+    fs::write(dir.join("config.json"), config).unwrap();
+    let _ = fs::remove_file(dir.join("session.json"));
+    let mut state = AppState::open(&dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    (dir, state)
+}
+
+#[test]
+fn alphabetical_geographic_and_toggled() {
+    assert_eq!(Config::default().world_order, WorldOrder::Alphabetical);
+    assert_eq!(WorldOrder::Alphabetical.toggled(), WorldOrder::Geographic);
+    assert_eq!(WorldOrder::Geographic.toggled(), WorldOrder::Alphabetical);
+
+    // By name, whatever order the file lists them in
+    let (dir, mut state) = open("world-order-toggle", "{}");
+    assert_eq!(state.list_items, ["Eastland", "Emptyland", "Europe", "Westland"]);
+    let west_to_east = ["Westland", "Europe", "Eastland", "Emptyland"];
+    assert_eq!(state.map.as_ref().unwrap().continents_west_to_east(), &west_to_east[..3]);
+
+    // g: west to east by the centroid of their countries, the selection kept
+    state.selected = 2;
+    state.handle_input(state.keys.key(Action::WorldOrder).unwrap());
+    assert_eq!(state.world_order, WorldOrder::Geographic);
+    assert_eq!(state.list_items, west_to_east);
+    assert_eq!(state.list_items[state.selected], "Europe");
+    assert_eq!(state.notification.as_ref().unwrap().text, "Kontynenty z zachodu na wschód");
+    state.handle_input(state.keys.key(Action::WorldOrder).unwrap());
+    assert_eq!(state.list_items, ["Eastland", "Emptyland", "Europe", "Westland"]);
+    assert_eq!(state.notification.as_ref().unwrap().text, "Kontynenty alfabetycznie");
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, state) = open("world-order-config", r#"{"world_order": "geographic"}"#);
+    assert_eq!(state.list_items, west_to_east);
+    let _ = fs::remove_dir_all(&dir);
+}
+
+fn screen(state: &AppState) -> (Buffer, ratatui::layout::Rect) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut inner = None;
+    terminal.draw(|f| inner = ui::draw(f, &state.view_model()).map_area.map(|a| a.inner)).unwrap();
+    (terminal.backend().buffer().clone(), inner.unwrap())
+}
+
+/// Color of the bullet before `continent` in the list
+fn bullet(buffer: &Buffer, continent: &str) -> Option<Color> {
+    (0..buffer.area.height).find_map(|y| {
+        let row: String = (0..40).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+        let at = row.find(continent)?;
+        let x = row[..at].chars().position(|c| c == '■')?;
+        Some(buffer[(x as u16, y)].fg)
+    })
+}
+
+#[test]
+fn continents_tinted_on_map_and_list() {
+    let (dir, mut state) = open("world-order-tints", r#"{"continent_colors": true, "unicode": true}"#);
+    let tints = state.view_model().continent_tints;
+    // Named continents keep their own tint; the rest take the palette by position
+    assert_eq!(tints.len(), 3);
+    assert_eq!(tints["Westland"], CONTINENT_TINTS[0]);
+    assert_eq!(tints["Europe"], continent_tint("Europe", 1));
+    assert_eq!(tints["Eastland"], CONTINENT_TINTS[2]);
+    let map = state.map.as_ref().unwrap();
+    let members = map.member_tints(&tints);
+    assert_eq!(members.len(), 4);
+    assert_eq!(members["farwest"], tints["Westland"]);
+    assert_eq!(members["fareast"], tints["Eastland"]);
+
+    // The list's bullets and the map's countries in the same colors
+    state.selected = state.list_items.iter().position(|c| c == "Europe").unwrap();
+    let (buffer, inner) = screen(&state);
+    let color = |rgb| state.theme.color(rgb);
+    assert_eq!(bullet(&buffer, "Westland"), Some(color(tints["Westland"])));
+    assert_eq!(bullet(&buffer, "Eastland"), Some(color(tints["Eastland"])));
+    assert_eq!(bullet(&buffer, "Emptyland"), None);
+    let on_map = |rgb| {
+        (inner.top()..inner.bottom()).any(|y| (inner.left()..inner.right()).any(|x| buffer[(x, y)].fg == color(rgb)))
+    };
+    assert!(on_map(tints["Westland"]) && on_map(tints["Eastland"]));
+
+    // Only the world map is tinted
+    state.handle_input(state.keys.key(Action::Select).unwrap());
+    while state.map_job.is_some() {
+        std::thread::sleep(Duration::from_millis(5));
+        state.poll_map_job();
+    }
+    assert!(state.view_model().continent_tints.is_empty());
+    let _ = fs::remove_dir_all(&dir);
+
+    let (dir, state) = open("world-order-no-tints", "{}");
+    assert!(state.view_model().continent_tints.is_empty());
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,166 @@
+//! The year slider: years spread along the track and read back from a cell, decade ticks
+//! and labels, the handle with its year; clicking and dragging it under the choropleth and
+//! under the GDP chart sets the active year
+mod common;
+
+use common::FixtureAtlas;
+use ratatui::{backend::TestBackend, buffer::Buffer, layout::Rect, widgets::Widget, Terminal};
+use rustatlas::{
+    keys::Action,
+    state::{AppState, MouseInput, Panel},
+    theme::{ColorSupport, Theme, UNICODE},
+    ui,
+    year_slider::{offset_of, year_at_offset, SliderArea, YearSlider},
+};
+use std::{fs, path::PathBuf};
+
+#[test]
+fn years_along_the_track() {
+    let years = 1990..=2010;
+    // A cell a year, and two
+    assert_eq!((offset_of(&years, 1990, 21), offset_of(&years, 2000, 21), offset_of(&years, 2010, 21)), (0, 10, 20));
+    assert_eq!(offset_of(&years, 2005, 41), 30);
+    // Years off the range at the ends
+    assert_eq!((offset_of(&years, 1950, 21), offset_of(&years, 2050, 21)), (0, 20));
+    assert_eq!(offset_of(&(2000..=2000), 2000, 21), 0);
+    for year in years.clone() {
+        assert_eq!(year_at_offset(&years, offset_of(&years, year, 41), 41), year);
+    }
+    // Narrower than the years: the nearest one, the last past the end
+    assert_eq!(year_at_offset(&years, 5, 11), 2000);
+    assert_eq!(year_at_offset(&years, 99, 11), 2010);
+    assert_eq!(year_at_offset(&years, 0, 1), 2010);
+
+    let area = SliderArea { area: Rect::new(10, 5, 21, 2), years };
+    assert_eq!(area.year_at(10, 5), Some(1990));
+    assert_eq!(area.year_at(20, 6), Some(2000));
+    assert_eq!((area.year_at(9, 5), area.year_at(31, 5), area.year_at(20, 7)), (None, None, None));
+    // A drag past the ends stays on them
+    assert_eq!((area.year_along(0), area.year_along(200)), (1990, 2010));
+}
+
+fn row(buffer: &Buffer, y: u16) -> String {
+    (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect()
+}
+
+#[test]
+fn ticks_handle_and_labels() {
+    let theme = Theme::new(ColorSupport::TrueColor).with_symbols(&UNICODE);
+    let area = Rect::new(0, 0, 41, 2);
+    let mut buffer = Buffer::empty(area);
+    YearSlider::new(1990..=2010, Some(2005), &theme).render(area, &mut buffer);
+    assert_eq!(row(&buffer, 0), format!("┼{}┼{}●{}┼", "─".repeat(19), "─".repeat(9), "─".repeat(9)));
+    // Decade labels, and the handle's year under it
+    assert_eq!(row(&buffer, 1), format!("1990{}2000{} 2005 {}2010", " ".repeat(14), " ".repeat(5), " ".repeat(4)));
+
+    // No year chosen: the handle at the end, said to be the latest
+    let mut buffer = Buffer::empty(area);
+    YearSlider::new(1990..=2010, None, &theme).render(area, &mut buffer);
+    assert!(row(&buffer, 0).ends_with('●'));
+    assert!(row(&buffer, 1).ends_with(" ostatni rok "), "{:?}", row(&buffer, 1));
+    assert!(!row(&buffer, 1).contains("2010"));
+
+    // The track alone in a single row
+    let area = Rect::new(0, 0, 41, 1);
+    let mut buffer = Buffer::empty(area);
+    YearSlider::new(1990..=2010, Some(2000), &theme).render(area, &mut buffer);
+    assert_eq!(buffer[(20, 0)].symbol(), "●");
+}
+
+fn atlas(name: &str) -> PathBuf {
+    let dir = FixtureAtlas::new()
+        .continent("Europe", ["Poland", "Germany"])
+        .gdp("Poland", 1990..=2005, |year| f64::from(year - 1980) * 1e10)
+        .gdp("Germany", 1990..=2005, |year| f64::from(year - 1900) * 1e11)
+        .write(name)
+        .unwrap()
+        .dir;
+    let _ = fs::remove_file(dir.join("session.json"));
+    dir
+}
+
+fn open(dir: &PathBuf) -> AppState {
+    let mut state = AppState::open(dir, false).unwrap();
+    state.tour_step = None;
+    state.restore_offer = None;
+    state.notification = None;
+    state.active_panel = Panel::Left;
+    state
+}
+
+fn press(state: &mut AppState, action: Action) {
+    state.handle_input(state.keys.key(action).unwrap());
+}
+
+fn enter(state: &mut AppState, item: &str) {
+    state.active_panel = Panel::Left;
+    state.selected = state.list_items.iter().position(|i| i == item).unwrap();
+    press(state, Action::Select);
+}
+
+/// Draw a frame and keep what it laid out, as the main loop does
+fn draw(state: &mut AppState) {
+    let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+    let mut drawn = None;
+    terminal.draw(|f| drawn = Some(ui::draw(f, &state.view_model()))).unwrap();
+    state.apply_drawn(drawn.unwrap());
+}
+
+/// Column of `year` on the drawn slider
+fn column(slider: &SliderArea, year: u16) -> u16 {
+    slider.area.x + offset_of(&slider.years, year, slider.area.width)
+}
+
+#[test]
+fn clicked_and_dragged_under_the_choropleth() {
+    let dir = atlas("year-slider-map");
+    let mut state = open(&dir);
+    enter(&mut state, "Europe");
+    draw(&mut state);
+    assert!(state.year_slider.is_none());
+
+    press(&mut state, Action::Choropleth);
+    draw(&mut state);
+    let slider = state.year_slider.clone().unwrap();
+    assert_eq!(slider.years, 1990..=2005);
+    let y = slider.area.y;
+
+    state.handle_mouse(MouseInput::Click { column: column(&slider, 1995), row: y });
+    assert_eq!(state.active_year, Some(1995));
+    assert_eq!(state.choropleth.as_ref().unwrap().year, Some(1995));
+    state.handle_mouse(MouseInput::Drag { column: column(&slider, 1998), row: y + 10 });
+    assert_eq!(state.choropleth.as_ref().unwrap().year, Some(1998));
+    // Dragged off the end, the last year
+    state.handle_mouse(MouseInput::Drag { column: 200, row: y });
+    assert_eq!(state.active_year, Some(2005));
+
+    // Let go, a drag from elsewhere leaves it alone
+    state.handle_mouse(MouseInput::Move { column: 0, row: 0 });
+    state.handle_mouse(MouseInput::Drag { column: column(&slider, 1990), row: y });
+    assert_eq!(state.active_year, Some(2005));
+
+    // Left/Right on the focused map step it
+    state.active_panel = Panel::Center;
+    press(&mut state, Action::Left);
+    assert_eq!(state.choropleth.as_ref().unwrap().year, Some(2004));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn clicked_under_the_chart() {
+    let dir = atlas("year-slider-chart");
+    let mut state = open(&dir);
+    enter(&mut state, "Europe");
+    enter(&mut state, "Poland");
+    press(&mut state, Action::SwitchView);
+    assert!(state.gdp_chart_active);
+    draw(&mut state);
+    let slider = state.year_slider.clone().unwrap();
+    assert_eq!(slider.years, 1990..=2005);
+
+    state.handle_mouse(MouseInput::Click { column: column(&slider, 1993), row: slider.area.y + 1 });
+    assert_eq!((state.chart_cursor, state.active_year), (Some(1993), Some(1993)));
+    state.handle_mouse(MouseInput::Drag { column: column(&slider, 2000), row: 0 });
+    assert_eq!(state.chart_cursor, Some(2000));
+    let _ = fs::remove_dir_all(&dir);
+}